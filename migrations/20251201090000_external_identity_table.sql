@@ -0,0 +1,13 @@
+BEGIN;
+-- Staff provisioned through single sign-on have no date of birth
+ALTER TABLE tuser ALTER COLUMN "date_of_birth" DROP NOT NULL;
+CREATE TABLE external_identity (
+    "issuer" TEXT NOT NULL,
+    "subject" TEXT NOT NULL,
+    "user_id" UUID NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "last_login_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(issuer, subject),
+    CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+COMMIT;
@@ -0,0 +1,23 @@
+BEGIN;
+CREATE TABLE api_key (
+    "id" UUID,
+    "name" VARCHAR(64) NOT NULL,
+    "prefix" VARCHAR(16) NOT NULL UNIQUE,
+    "key_hash" TEXT NOT NULL,
+    "scopes" TEXT [] NOT NULL,
+    "allowed_ips" TEXT [] NOT NULL DEFAULT '{}',
+    "created_by" UUID NOT NULL,
+    "rotated_from" UUID,
+    "expires_at" timestamptz(3),
+    "revoked_at" timestamptz(3),
+    "last_used_at" timestamptz(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_api_key_creator FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_api_key_rotated FOREIGN KEY(rotated_from) REFERENCES api_key(id) ON DELETE SET NULL
+);
+-- Which integration and device posted a journal entry
+ALTER TABLE journal_entry ADD COLUMN "api_key_id" UUID;
+ALTER TABLE journal_entry ADD COLUMN "device_id" VARCHAR(64);
+ALTER TABLE journal_entry ADD CONSTRAINT fk_journal_api_key FOREIGN KEY(api_key_id) REFERENCES api_key(id);
+COMMIT;
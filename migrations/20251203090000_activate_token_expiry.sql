@@ -0,0 +1,7 @@
+BEGIN;
+-- Tokens issued before this change could never be redeemed, they expire straight away
+ALTER TABLE activate_token ADD COLUMN "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE activate_token ADD COLUMN "expires_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP;
+CREATE INDEX idx_activate_token_user ON activate_token(user_id);
+CREATE INDEX idx_activate_token_expires ON activate_token(expires_at);
+COMMIT;
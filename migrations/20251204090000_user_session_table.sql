@@ -0,0 +1,19 @@
+BEGIN;
+CREATE TABLE user_session (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "device" VARCHAR(255),
+    "ip_address" VARCHAR(64),
+    "user_agent" VARCHAR(255),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "last_seen_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "expires_at" timestamptz(3) NOT NULL,
+    "ended_at" timestamptz(3),
+    -- Set when someone other than the owner ended the session
+    "ended_by" UUID,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_user_session_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_user_session_ended_by FOREIGN KEY(ended_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_user_session_user ON user_session(user_id);
+COMMIT;
@@ -0,0 +1,20 @@
+BEGIN;
+CREATE TYPE kyc_document_type AS ENUM ('passport', 'national_id', 'drivers_license', 'proof_of_address', 'selfie');
+CREATE TYPE kyc_document_status AS ENUM ('uploaded', 'accepted', 'rejected');
+CREATE TABLE kyc_document (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "document_type" kyc_document_type NOT NULL,
+    "status" kyc_document_status NOT NULL DEFAULT 'uploaded',
+    "object_key" TEXT NOT NULL UNIQUE,
+    "file_name" VARCHAR(255),
+    "content_type" VARCHAR(64) NOT NULL,
+    "size_bytes" BIGINT NOT NULL,
+    -- Hex encoded sha256 of the uploaded bytes
+    "checksum" VARCHAR(64) NOT NULL,
+    "uploaded_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_kyc_document_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT uq_kyc_document_checksum UNIQUE(user_id, checksum)
+);
+COMMIT;
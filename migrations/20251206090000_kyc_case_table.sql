@@ -0,0 +1,30 @@
+BEGIN;
+CREATE TYPE kyc_case_status AS ENUM ('submitted', 'in_review', 'more_info_needed', 'approved', 'rejected');
+-- One case per customer, reopened in place when more information is asked for
+CREATE TABLE kyc_case (
+    "id" UUID,
+    "user_id" UUID NOT NULL UNIQUE,
+    "status" kyc_case_status NOT NULL,
+    "reviewer_id" UUID,
+    "decided_at" timestamptz(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_kyc_case_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_kyc_case_reviewer FOREIGN KEY(reviewer_id) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_kyc_case_status ON kyc_case(status);
+-- Every status change with who made it and why
+CREATE TABLE kyc_case_note (
+    "id" UUID,
+    "case_id" UUID NOT NULL,
+    "author_id" UUID NOT NULL,
+    "status_from" kyc_case_status,
+    "status_to" kyc_case_status NOT NULL,
+    "note" TEXT,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_kyc_note_case FOREIGN KEY(case_id) REFERENCES kyc_case(id) ON DELETE CASCADE,
+    CONSTRAINT fk_kyc_note_author FOREIGN KEY(author_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+COMMIT;
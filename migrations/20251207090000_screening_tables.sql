@@ -0,0 +1,57 @@
+BEGIN;
+CREATE TYPE watchlist_kind AS ENUM ('sanctions', 'pep');
+-- Every load of a list is kept, only the latest version of a source is active
+CREATE TABLE watchlist_version (
+    "id" UUID,
+    "source" VARCHAR(64) NOT NULL,
+    "kind" watchlist_kind NOT NULL,
+    "checksum" CHAR(64) NOT NULL,
+    "entry_count" INTEGER NOT NULL,
+    "is_active" BOOLEAN NOT NULL DEFAULT TRUE,
+    "loaded_by" UUID NOT NULL,
+    "loaded_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_watchlist_version_checksum UNIQUE(source, checksum),
+    CONSTRAINT fk_watchlist_version_loaded_by FOREIGN KEY(loaded_by) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uq_watchlist_version_active ON watchlist_version(source) WHERE is_active;
+-- Names are stored normalized for matching, display_name as published
+CREATE TABLE watchlist_entry (
+    "id" UUID,
+    "version_id" UUID NOT NULL,
+    "external_ref" VARCHAR(64) NOT NULL,
+    "display_name" TEXT NOT NULL,
+    "names" TEXT[] NOT NULL,
+    "birth_dates" DATE[] NOT NULL DEFAULT '{}',
+    "birth_years" INTEGER[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY(id),
+    CONSTRAINT fk_watchlist_entry_version FOREIGN KEY(version_id) REFERENCES watchlist_version(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_watchlist_entry_version ON watchlist_entry(version_id);
+CREATE TYPE screening_hit_status AS ENUM ('open', 'confirmed', 'dismissed');
+CREATE TYPE screening_dob_match AS ENUM ('exact', 'year', 'unknown');
+-- A listed person is only raised once per customer, a new list version does not reopen a decided hit
+CREATE TABLE screening_hit (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "version_id" UUID NOT NULL,
+    "entry_id" UUID NOT NULL,
+    "list_source" VARCHAR(64) NOT NULL,
+    "external_ref" VARCHAR(64) NOT NULL,
+    "matched_name" TEXT NOT NULL,
+    "score" DOUBLE PRECISION NOT NULL,
+    "dob_match" screening_dob_match NOT NULL,
+    "status" screening_hit_status NOT NULL DEFAULT 'open',
+    "reviewer_id" UUID,
+    "review_note" TEXT,
+    "reviewed_at" timestamptz(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_screening_hit_listing UNIQUE(user_id, list_source, external_ref),
+    CONSTRAINT fk_screening_hit_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_screening_hit_version FOREIGN KEY(version_id) REFERENCES watchlist_version(id) ON DELETE CASCADE,
+    CONSTRAINT fk_screening_hit_entry FOREIGN KEY(entry_id) REFERENCES watchlist_entry(id) ON DELETE CASCADE,
+    CONSTRAINT fk_screening_hit_reviewer FOREIGN KEY(reviewer_id) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_screening_hit_status ON screening_hit(status);
+COMMIT;
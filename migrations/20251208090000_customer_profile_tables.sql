@@ -0,0 +1,58 @@
+BEGIN;
+CREATE TYPE address_kind AS ENUM ('residential', 'mailing');
+-- Addresses are never edited in place, a new row supersedes the current one of the same kind
+CREATE TABLE customer_address (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "kind" address_kind NOT NULL,
+    "line1" VARCHAR(128) NOT NULL,
+    "line2" VARCHAR(128),
+    "city" VARCHAR(64) NOT NULL,
+    "region" VARCHAR(64),
+    "postal_code" VARCHAR(16) NOT NULL,
+    "country_code" CHAR(2) NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "superseded_at" timestamptz(3),
+    PRIMARY KEY(id),
+    CONSTRAINT fk_customer_address_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uq_customer_address_current ON customer_address(user_id, kind) WHERE superseded_at IS NULL;
+CREATE TYPE phone_kind AS ENUM ('mobile', 'home', 'work');
+CREATE TABLE customer_phone (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "kind" phone_kind NOT NULL,
+    "number" VARCHAR(16) NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "superseded_at" timestamptz(3),
+    PRIMARY KEY(id),
+    CONSTRAINT fk_customer_phone_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uq_customer_phone_current ON customer_phone(user_id, kind) WHERE superseded_at IS NULL;
+-- The new address only replaces the old one once the link sent to it is followed
+CREATE TABLE email_change (
+    "token_hash" CHAR(64),
+    "user_id" UUID NOT NULL,
+    "new_email" TEXT NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "expires_at" timestamptz(3) NOT NULL,
+    PRIMARY KEY(token_hash),
+    CONSTRAINT fk_email_change_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_email_change_user ON email_change(user_id);
+CREATE TYPE profile_change_reason AS ENUM ('customer_request', 'legal_name_change', 'marriage', 'data_correction', 'kyc_reverification');
+CREATE TABLE profile_change (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "field" VARCHAR(32) NOT NULL,
+    "old_value" TEXT,
+    "new_value" TEXT,
+    "reason" profile_change_reason NOT NULL,
+    "changed_by" UUID,
+    "changed_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_profile_change_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_profile_change_changed_by FOREIGN KEY(changed_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_profile_change_user ON profile_change(user_id, changed_at);
+COMMIT;
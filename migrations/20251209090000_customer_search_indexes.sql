@@ -0,0 +1,15 @@
+BEGIN;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+-- Free text search over names, usernames and emails, tolerant of typos and partial input
+CREATE INDEX idx_tuser_full_name_trgm ON tuser USING GIN ((first_name || ' ' || last_name) gin_trgm_ops);
+CREATE INDEX idx_tuser_username_trgm ON tuser USING GIN (username gin_trgm_ops);
+CREATE INDEX idx_tuser_email_trgm ON tuser USING GIN (email gin_trgm_ops);
+-- Prefix lookups for exact identifiers
+CREATE INDEX idx_tuser_email_prefix ON tuser (lower(email) text_pattern_ops);
+CREATE INDEX idx_tuser_username_prefix ON tuser (lower(username) text_pattern_ops);
+CREATE INDEX idx_tuser_date_of_birth ON tuser (date_of_birth);
+CREATE INDEX idx_user_account_user ON user_account (user_id);
+CREATE INDEX idx_user_account_number_prefix ON user_account (account_number text_pattern_ops);
+CREATE INDEX idx_user_account_iban_prefix ON user_account (iban text_pattern_ops);
+CREATE INDEX idx_journal_entry_account_created ON journal_entry (user_account_id, created_date);
+COMMIT;
@@ -0,0 +1,41 @@
+BEGIN;
+-- The licensed company that books the accounts opened at its branches
+CREATE TABLE legal_entity (
+    "id" UUID,
+    "code" VARCHAR(16) NOT NULL UNIQUE,
+    "name" VARCHAR(128) NOT NULL,
+    "country_code" CHAR(2) NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id)
+);
+-- Timezone is an IANA name, reporting days are cut in the branch's local time
+CREATE TABLE branch (
+    "id" UUID,
+    "legal_entity_id" UUID NOT NULL,
+    "code" VARCHAR(16) NOT NULL UNIQUE,
+    "name" VARCHAR(128) NOT NULL,
+    "country_code" CHAR(2) NOT NULL,
+    "timezone" VARCHAR(64) NOT NULL,
+    "is_active" BOOLEAN NOT NULL DEFAULT TRUE,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_branch_legal_entity FOREIGN KEY(legal_entity_id) REFERENCES legal_entity(id)
+);
+CREATE INDEX idx_branch_legal_entity ON branch(legal_entity_id);
+-- Managers only act on the branches they are assigned to, superusers on all of them
+CREATE TABLE staff_branch (
+    "staff_id" UUID NOT NULL,
+    "branch_id" UUID NOT NULL,
+    "assigned_by" UUID,
+    "assigned_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(staff_id, branch_id),
+    CONSTRAINT fk_staff_branch_staff FOREIGN KEY(staff_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_staff_branch_branch FOREIGN KEY(branch_id) REFERENCES branch(id) ON DELETE CASCADE,
+    CONSTRAINT fk_staff_branch_assigned_by FOREIGN KEY(assigned_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_staff_branch_branch ON staff_branch(branch_id);
+-- Accounts opened before branches existed carry made up ids, only new rows are checked
+ALTER TABLE user_account
+    ADD CONSTRAINT fk_user_account_branch FOREIGN KEY(branch_id) REFERENCES branch(id) NOT VALID;
+CREATE INDEX idx_user_account_branch ON user_account(branch_id, created_at);
+COMMIT;
@@ -0,0 +1,50 @@
+BEGIN;
+-- account_class becomes the product, its terms live in numbered versions
+ALTER TABLE account_class ADD PRIMARY KEY(id);
+ALTER TABLE account_class ALTER COLUMN kind TYPE account_kind USING lower(kind)::account_kind;
+ALTER TABLE account_class ADD CONSTRAINT uq_account_class_code UNIQUE(code);
+-- Retired products keep serving existing accounts but can't be opened any more
+ALTER TABLE account_class ADD COLUMN "is_active" BOOLEAN NOT NULL DEFAULT TRUE;
+CREATE TYPE kyc_level AS ENUM ('none', 'verified');
+CREATE TYPE statement_frequency AS ENUM ('monthly', 'quarterly', 'annual');
+CREATE TYPE fee_frequency AS ENUM ('one_off', 'per_transaction', 'monthly', 'quarterly', 'annual');
+-- Versions are never edited, changing terms adds the next one
+CREATE TABLE product_version (
+    "id" UUID,
+    "account_class_id" UUID NOT NULL,
+    "version" INTEGER NOT NULL,
+    "currencies" TEXT[] NOT NULL,
+    "min_age" SMALLINT NOT NULL,
+    "max_age" SMALLINT,
+    "min_kyc_level" kyc_level NOT NULL,
+    "overdraft_limit_cents" BIGINT NOT NULL DEFAULT 0 CHECK (overdraft_limit_cents >= 0),
+    "dormancy_days" INTEGER NOT NULL CHECK (dormancy_days > 0),
+    "statement_frequency" statement_frequency NOT NULL,
+    "created_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_product_version UNIQUE(account_class_id, version),
+    CONSTRAINT fk_product_version_class FOREIGN KEY(account_class_id) REFERENCES account_class(id) ON DELETE CASCADE,
+    CONSTRAINT fk_product_version_created_by FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+-- A tier's rate applies to balances from min_balance_cents up to the next tier
+CREATE TABLE product_interest_tier (
+    "version_id" UUID NOT NULL,
+    "min_balance_cents" BIGINT NOT NULL CHECK (min_balance_cents >= 0),
+    "rate_bps" INTEGER NOT NULL,
+    PRIMARY KEY(version_id, min_balance_cents),
+    CONSTRAINT fk_product_interest_tier_version FOREIGN KEY(version_id) REFERENCES product_version(id) ON DELETE CASCADE
+);
+CREATE TABLE product_fee (
+    "version_id" UUID NOT NULL,
+    "code" VARCHAR(32) NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents >= 0),
+    "frequency" fee_frequency NOT NULL,
+    PRIMARY KEY(version_id, code),
+    CONSTRAINT fk_product_fee_version FOREIGN KEY(version_id) REFERENCES product_version(id) ON DELETE CASCADE
+);
+-- The terms an account was opened on, empty for accounts opened before the catalogue
+ALTER TABLE user_account ADD COLUMN "product_version_id" UUID;
+ALTER TABLE user_account
+    ADD CONSTRAINT fk_user_account_product_version FOREIGN KEY(product_version_id) REFERENCES product_version(id);
+COMMIT;
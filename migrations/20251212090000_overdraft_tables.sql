@@ -0,0 +1,28 @@
+BEGIN;
+-- Annual rate charged on the overdrawn part of a balance, arranged or not
+ALTER TABLE product_version
+    ADD COLUMN "debit_rate_bps" INTEGER NOT NULL DEFAULT 0 CHECK (debit_rate_bps BETWEEN 0 AND 10000);
+-- One arranged facility per account, granting again replaces it
+CREATE TABLE account_overdraft (
+    "account_id" UUID,
+    "limit_cents" BIGINT NOT NULL CHECK (limit_cents > 0),
+    "expires_on" DATE NOT NULL,
+    "granted_by" UUID,
+    "granted_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(account_id),
+    CONSTRAINT fk_account_overdraft_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_account_overdraft_granted_by FOREIGN KEY(granted_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+-- End of day negative balances, charged to the account at month end
+CREATE TABLE overdraft_accrual (
+    "account_id" UUID NOT NULL,
+    "accrual_date" DATE NOT NULL,
+    "balance_cents" BIGINT NOT NULL CHECK (balance_cents < 0),
+    "rate_bps" INTEGER NOT NULL,
+    "journal_entry_id" UUID,
+    PRIMARY KEY(account_id, accrual_date),
+    CONSTRAINT fk_overdraft_accrual_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_overdraft_accrual_journal_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id)
+);
+CREATE INDEX idx_overdraft_accrual_unposted ON overdraft_accrual(accrual_date) WHERE journal_entry_id IS NULL;
+COMMIT;
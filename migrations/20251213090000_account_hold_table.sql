@@ -0,0 +1,30 @@
+BEGIN;
+CREATE TYPE hold_source AS ENUM ('manual', 'card_authorization');
+CREATE TYPE hold_release_reason AS ENUM ('manual', 'expired', 'settled');
+-- Funds set aside on an account, they reduce the available balance but are never posted
+CREATE TABLE account_hold (
+    "id" UUID,
+    "account_id" UUID NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "reason" VARCHAR(200) NOT NULL,
+    "reference" VARCHAR(50) NOT NULL,
+    "source" hold_source NOT NULL,
+    "expires_at" timestamptz(3) NOT NULL,
+    "created_by" UUID,
+    "api_key_id" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "released_at" timestamptz(3),
+    "released_by" UUID,
+    "release_reason" hold_release_reason,
+    "journal_entry_id" UUID,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_account_hold_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_account_hold_created_by FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE SET NULL,
+    CONSTRAINT fk_account_hold_api_key FOREIGN KEY(api_key_id) REFERENCES api_key(id) ON DELETE SET NULL,
+    CONSTRAINT fk_account_hold_released_by FOREIGN KEY(released_by) REFERENCES tuser(id) ON DELETE SET NULL,
+    CONSTRAINT fk_account_hold_journal_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT chk_account_hold_release CHECK ((released_at IS NULL) = (release_reason IS NULL))
+);
+-- A reference names at most one open hold on an account, it is how a posting finds its hold
+CREATE UNIQUE INDEX idx_account_hold_open_reference ON account_hold(account_id, reference) WHERE released_at IS NULL;
+COMMIT;
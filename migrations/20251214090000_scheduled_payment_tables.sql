@@ -0,0 +1,46 @@
+BEGIN;
+CREATE TYPE payment_frequency AS ENUM ('once', 'weekly', 'monthly', 'quarterly', 'annual');
+CREATE TYPE scheduled_payment_status AS ENUM ('active', 'completed', 'cancelled');
+CREATE TYPE payment_execution_status AS ENUM ('pending', 'retrying', 'succeeded', 'failed');
+-- Standing orders, and one-off payments dated in the future when the frequency is once
+CREATE TABLE scheduled_payment (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "account_id" UUID NOT NULL,
+    "destination_account_id" UUID NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "reference" VARCHAR(35) NOT NULL,
+    "frequency" payment_frequency NOT NULL,
+    "start_date" DATE NOT NULL,
+    "end_date" DATE CHECK (end_date >= start_date),
+    -- Counted from the start date so monthly orders on the 31st come back to it after shorter months
+    "next_occurrence" INTEGER NOT NULL DEFAULT 0,
+    "next_due_date" DATE,
+    "status" scheduled_payment_status NOT NULL DEFAULT 'active',
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "cancelled_at" timestamptz(3),
+    PRIMARY KEY(id),
+    CONSTRAINT fk_scheduled_payment_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_scheduled_payment_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_scheduled_payment_destination FOREIGN KEY(destination_account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT chk_scheduled_payment_accounts CHECK (account_id <> destination_account_id)
+);
+CREATE INDEX idx_scheduled_payment_due ON scheduled_payment(next_due_date) WHERE status = 'active';
+-- One row per due date, the execution key doubles as the journal reference so a date is never paid twice
+CREATE TABLE scheduled_payment_execution (
+    "scheduled_payment_id" UUID NOT NULL,
+    "due_date" DATE NOT NULL,
+    "execution_key" VARCHAR(50) NOT NULL UNIQUE,
+    "status" payment_execution_status NOT NULL DEFAULT 'pending',
+    "attempts" INTEGER NOT NULL DEFAULT 0,
+    "next_attempt_at" timestamptz(3),
+    "last_error" TEXT,
+    "journal_entry_id" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(scheduled_payment_id, due_date),
+    CONSTRAINT fk_payment_execution_payment FOREIGN KEY(scheduled_payment_id) REFERENCES scheduled_payment(id) ON DELETE CASCADE,
+    CONSTRAINT fk_payment_execution_journal_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id)
+);
+CREATE INDEX idx_payment_execution_open ON scheduled_payment_execution(next_attempt_at) WHERE status IN ('pending', 'retrying');
+COMMIT;
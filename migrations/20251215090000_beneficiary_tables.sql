@@ -0,0 +1,35 @@
+BEGIN;
+-- Saved payees for outbound payments, removed ones are kept for the payments already sent to them
+CREATE TABLE beneficiary (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "name" VARCHAR(70) NOT NULL,
+    "iban" VARCHAR(34),
+    "account_number" VARCHAR(34),
+    "bic" VARCHAR(11),
+    "bank_country" CHAR(2) NOT NULL,
+    "currency" CHAR(3) NOT NULL,
+    -- Until then only amounts up to the large amount threshold can be sent
+    "cooling_off_until" timestamptz(3) NOT NULL,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "removed_at" timestamptz(3),
+    PRIMARY KEY(id),
+    CONSTRAINT fk_beneficiary_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT chk_beneficiary_account CHECK ((iban IS NULL) <> (account_number IS NULL))
+);
+CREATE UNIQUE INDEX idx_beneficiary_user_account ON beneficiary(user_id, COALESCE(iban, bank_country || ':' || account_number)) WHERE removed_at IS NULL;
+-- One-time codes emailed to the customer before a beneficiary can be added
+CREATE TABLE beneficiary_challenge (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "code_hash" VARCHAR(64) NOT NULL,
+    "attempts" INTEGER NOT NULL DEFAULT 0,
+    "expires_at" timestamptz(3) NOT NULL,
+    "consumed_at" timestamptz(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_beneficiary_challenge_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_beneficiary_challenge_user ON beneficiary_challenge(user_id) WHERE consumed_at IS NULL;
+COMMIT;
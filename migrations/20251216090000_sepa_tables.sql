@@ -0,0 +1,50 @@
+BEGIN;
+CREATE TYPE sepa_transfer_status AS ENUM ('queued', 'batched', 'accepted', 'returned');
+CREATE TYPE sepa_batch_status AS ENUM ('generated', 'accepted', 'partially_accepted', 'rejected');
+-- One pain.001 file, the message id is what the clearing house quotes back in pain.002
+CREATE TABLE sepa_batch (
+    "id" UUID,
+    "message_id" VARCHAR(35) NOT NULL UNIQUE,
+    "status" sepa_batch_status NOT NULL DEFAULT 'generated',
+    "n_transactions" INTEGER NOT NULL,
+    "control_sum_cents" BIGINT NOT NULL,
+    "file_location" TEXT NOT NULL,
+    "created_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "status_updated_at" timestamptz(3),
+    PRIMARY KEY(id),
+    CONSTRAINT fk_sepa_batch_staff FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+-- Outbound credit transfers, the customer is debited against clearing as soon as one is queued
+CREATE TABLE sepa_transfer (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "account_id" UUID NOT NULL,
+    "beneficiary_id" UUID NOT NULL,
+    "creditor_name" VARCHAR(70) NOT NULL,
+    "creditor_iban" VARCHAR(34) NOT NULL,
+    "creditor_bic" VARCHAR(11),
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "currency" CHAR(3) NOT NULL,
+    "end_to_end_id" VARCHAR(35) NOT NULL UNIQUE,
+    "remittance_info" VARCHAR(140),
+    "status" sepa_transfer_status NOT NULL DEFAULT 'queued',
+    "batch_id" UUID,
+    "payment_info_id" VARCHAR(35),
+    "journal_entry_id" UUID NOT NULL,
+    "return_entry_id" UUID,
+    "return_reason" VARCHAR(4),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_sepa_transfer_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_sepa_transfer_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_sepa_transfer_beneficiary FOREIGN KEY(beneficiary_id) REFERENCES beneficiary(id) ON DELETE CASCADE,
+    CONSTRAINT fk_sepa_transfer_batch FOREIGN KEY(batch_id) REFERENCES sepa_batch(id),
+    CONSTRAINT fk_sepa_transfer_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_sepa_transfer_return_entry FOREIGN KEY(return_entry_id) REFERENCES journal_entry(id)
+);
+CREATE INDEX idx_sepa_transfer_queued ON sepa_transfer(created_at) WHERE status = 'queued';
+CREATE INDEX idx_sepa_transfer_batch ON sepa_transfer(batch_id);
+CREATE INDEX idx_sepa_transfer_user ON sepa_transfer(user_id, created_at DESC);
+COMMIT;
@@ -0,0 +1,7 @@
+BEGIN;
+-- Scheduled payments may also go to a saved beneficiary, paid by SEPA credit transfer
+ALTER TABLE scheduled_payment ALTER COLUMN "destination_account_id" DROP NOT NULL;
+ALTER TABLE scheduled_payment ADD COLUMN "beneficiary_id" UUID;
+ALTER TABLE scheduled_payment ADD CONSTRAINT fk_scheduled_payment_beneficiary FOREIGN KEY(beneficiary_id) REFERENCES beneficiary(id) ON DELETE CASCADE;
+ALTER TABLE scheduled_payment ADD CONSTRAINT chk_scheduled_payment_destination CHECK ((destination_account_id IS NULL) <> (beneficiary_id IS NULL));
+COMMIT;
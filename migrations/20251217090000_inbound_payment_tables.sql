@@ -0,0 +1,34 @@
+BEGIN;
+CREATE TYPE inbound_payment_status AS ENUM ('posted', 'repair', 'resolved', 'rejected');
+-- Credits received in camt.054 and pacs.008 files, those that could not be posted wait here for staff
+CREATE TABLE inbound_payment (
+    "id" UUID,
+    -- End-to-end id, or the transaction id when the sender gave none, used as the journal reference
+    "reference" VARCHAR(35) NOT NULL UNIQUE,
+    "message_type" VARCHAR(8) NOT NULL,
+    "message_id" VARCHAR(35) NOT NULL,
+    "source_file" TEXT NOT NULL,
+    "creditor_iban" VARCHAR(34),
+    "creditor_name" VARCHAR(140),
+    "debtor_name" VARCHAR(140),
+    "debtor_iban" VARCHAR(34),
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "currency" CHAR(3) NOT NULL,
+    "remittance_info" TEXT,
+    "value_date" DATE,
+    "status" inbound_payment_status NOT NULL,
+    "account_id" UUID,
+    "journal_entry_id" UUID,
+    "repair_reason" TEXT,
+    "resolved_by" UUID,
+    "resolution_note" TEXT,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_inbound_payment_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE SET NULL,
+    CONSTRAINT fk_inbound_payment_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_inbound_payment_staff FOREIGN KEY(resolved_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_inbound_payment_repair ON inbound_payment(created_at) WHERE status = 'repair';
+CREATE INDEX idx_inbound_payment_account ON inbound_payment(account_id, created_at DESC);
+COMMIT;
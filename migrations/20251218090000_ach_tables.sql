@@ -0,0 +1,59 @@
+BEGIN;
+-- US beneficiaries can name their bank by ABA routing number, and are flagged when an ACH
+-- return says the account can't be paid again
+ALTER TABLE beneficiary ADD COLUMN "routing_number" CHAR(9);
+ALTER TABLE beneficiary ADD COLUMN "flagged_reason" VARCHAR(140);
+ALTER TABLE beneficiary ADD COLUMN "flagged_at" timestamptz(3);
+CREATE TYPE ach_transfer_status AS ENUM ('queued', 'batched', 'returned');
+-- One NACHA file, the creation date and file id modifier tell the files of a day apart
+CREATE TABLE ach_file (
+    "id" UUID,
+    "file_creation_date" DATE NOT NULL,
+    "file_id_modifier" CHAR(1) NOT NULL,
+    "effective_entry_date" DATE NOT NULL,
+    "n_entries" INTEGER NOT NULL,
+    "entry_hash" BIGINT NOT NULL,
+    "total_credit_cents" BIGINT NOT NULL,
+    "file_location" TEXT NOT NULL,
+    "created_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_ach_file_modifier UNIQUE(file_creation_date, file_id_modifier),
+    CONSTRAINT fk_ach_file_staff FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+-- Outbound PPD credits, the customer is debited against clearing as soon as one is queued
+CREATE TABLE ach_transfer (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "account_id" UUID NOT NULL,
+    "beneficiary_id" UUID NOT NULL,
+    "receiver_name" VARCHAR(22) NOT NULL,
+    "routing_number" CHAR(9) NOT NULL,
+    "dfi_account_number" VARCHAR(17) NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "currency" CHAR(3) NOT NULL,
+    "trace_number" CHAR(15) NOT NULL UNIQUE,
+    "addenda_info" VARCHAR(80),
+    "status" ach_transfer_status NOT NULL DEFAULT 'queued',
+    "file_id" UUID,
+    "journal_entry_id" UUID NOT NULL,
+    "return_entry_id" UUID,
+    "fee_entry_id" UUID,
+    "return_code" CHAR(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_ach_transfer_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_ach_transfer_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_ach_transfer_beneficiary FOREIGN KEY(beneficiary_id) REFERENCES beneficiary(id) ON DELETE CASCADE,
+    CONSTRAINT fk_ach_transfer_file FOREIGN KEY(file_id) REFERENCES ach_file(id),
+    CONSTRAINT fk_ach_transfer_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_ach_transfer_return_entry FOREIGN KEY(return_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_ach_transfer_fee_entry FOREIGN KEY(fee_entry_id) REFERENCES journal_entry(id)
+);
+CREATE INDEX idx_ach_transfer_queued ON ach_transfer(created_at) WHERE status = 'queued';
+CREATE INDEX idx_ach_transfer_file ON ach_transfer(file_id);
+CREATE INDEX idx_ach_transfer_user ON ach_transfer(user_id, created_at DESC);
+-- Last seven digits of the trace number, the first eight are the ODFI routing number
+CREATE SEQUENCE ach_trace_seq MAXVALUE 9999999 CYCLE;
+COMMIT;
@@ -0,0 +1,77 @@
+BEGIN;
+-- Mid rates staff keep for the currencies customers pay out in, quote units per base unit
+-- times a million
+CREATE TABLE fx_rate (
+    "base_currency" CHAR(3) NOT NULL,
+    "quote_currency" CHAR(3) NOT NULL,
+    "rate_micros" BIGINT NOT NULL CHECK (rate_micros > 0),
+    "updated_by" UUID,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(base_currency, quote_currency),
+    CONSTRAINT fk_fx_rate_staff FOREIGN KEY(updated_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE TYPE swift_charges AS ENUM ('OUR', 'SHA', 'BEN');
+-- Outbound MT103s, the customer is debited against the nostro account when one is sent
+CREATE TABLE swift_transfer (
+    "id" UUID,
+    "user_id" UUID NOT NULL,
+    "account_id" UUID NOT NULL,
+    "beneficiary_id" UUID NOT NULL,
+    "reference" VARCHAR(16) NOT NULL UNIQUE,
+    "uetr" UUID NOT NULL UNIQUE,
+    "beneficiary_name" VARCHAR(70) NOT NULL,
+    "beneficiary_account" VARCHAR(34) NOT NULL,
+    "beneficiary_bic" VARCHAR(11) NOT NULL,
+    "charges" swift_charges NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "currency" CHAR(3) NOT NULL,
+    "settlement_cents" BIGINT NOT NULL CHECK (settlement_cents > 0),
+    "sender_charge_cents" BIGINT NOT NULL DEFAULT 0,
+    "debit_currency" CHAR(3) NOT NULL,
+    "principal_cents" BIGINT NOT NULL,
+    "rate_micros" BIGINT,
+    "fx_margin_cents" BIGINT NOT NULL DEFAULT 0,
+    "fee_cents" BIGINT NOT NULL DEFAULT 0,
+    "remittance_info" VARCHAR(140),
+    "value_date" DATE NOT NULL,
+    "message_location" TEXT NOT NULL,
+    "journal_entry_id" UUID NOT NULL,
+    "fx_entry_id" UUID,
+    "fee_entry_id" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_swift_transfer_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE,
+    CONSTRAINT fk_swift_transfer_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_swift_transfer_beneficiary FOREIGN KEY(beneficiary_id) REFERENCES beneficiary(id) ON DELETE CASCADE,
+    CONSTRAINT fk_swift_transfer_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_swift_transfer_fx_entry FOREIGN KEY(fx_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_swift_transfer_fee_entry FOREIGN KEY(fee_entry_id) REFERENCES journal_entry(id)
+);
+CREATE INDEX idx_swift_transfer_user ON swift_transfer(user_id, created_at DESC);
+CREATE SEQUENCE swift_reference_seq;
+-- Inbound MT103s credited to customers and the MT202s covering them, a sender's reference is
+-- only taken once per message type
+CREATE TABLE swift_inbound (
+    "id" UUID,
+    "message_type" CHAR(3) NOT NULL,
+    "sender_bic" VARCHAR(11) NOT NULL,
+    "reference" VARCHAR(16) NOT NULL,
+    "related_reference" VARCHAR(16),
+    "uetr" VARCHAR(36),
+    "value_date" DATE NOT NULL,
+    "amount_cents" BIGINT NOT NULL,
+    "currency" CHAR(3) NOT NULL,
+    "ordering_party" VARCHAR(35),
+    "beneficiary_account" VARCHAR(34),
+    "account_id" UUID,
+    "journal_entry_id" UUID,
+    "received_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_swift_inbound_reference UNIQUE(sender_bic, message_type, reference),
+    CONSTRAINT fk_swift_inbound_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE SET NULL,
+    CONSTRAINT fk_swift_inbound_entry FOREIGN KEY(journal_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_swift_inbound_staff FOREIGN KEY(received_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_swift_inbound_created ON swift_inbound(created_at DESC);
+COMMIT;
@@ -0,0 +1,50 @@
+BEGIN;
+CREATE TYPE nostro_statement_format AS ENUM ('mt940', 'camt053');
+-- Statements our correspondents send for the nostro accounts they hold for us, a statement is
+-- only imported once per account
+CREATE TABLE nostro_statement (
+    "id" UUID,
+    "format" nostro_statement_format NOT NULL,
+    "statement_ref" VARCHAR(35) NOT NULL,
+    "account" VARCHAR(35) NOT NULL,
+    "sequence" VARCHAR(35),
+    "currency" CHAR(3) NOT NULL,
+    "opening_cents" BIGINT NOT NULL,
+    "closing_cents" BIGINT NOT NULL,
+    "statement_date" DATE NOT NULL,
+    "imported_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_nostro_statement_ref UNIQUE(account, statement_ref),
+    CONSTRAINT fk_nostro_statement_staff FOREIGN KEY(imported_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE TYPE nostro_direction AS ENUM ('credit', 'debit');
+CREATE TYPE nostro_item_status AS ENUM ('unmatched', 'matched');
+CREATE TYPE nostro_match_rule AS ENUM ('reference', 'amount', 'manual');
+-- One entry of a statement, matched to at most one nostro journal line and a line to at most
+-- one entry
+CREATE TABLE nostro_item (
+    "id" UUID,
+    "statement_id" UUID NOT NULL,
+    "value_date" DATE NOT NULL,
+    "booking_date" DATE,
+    "direction" nostro_direction NOT NULL,
+    "amount_cents" BIGINT NOT NULL CHECK (amount_cents > 0),
+    "currency" CHAR(3) NOT NULL,
+    "reference" VARCHAR(35),
+    "bank_reference" VARCHAR(35),
+    "narrative" TEXT,
+    "status" nostro_item_status NOT NULL DEFAULT 'unmatched',
+    "journal_line_id" UUID UNIQUE,
+    "match_rule" nostro_match_rule,
+    "matched_by" UUID,
+    "matched_at" timestamptz(3),
+    "note" TEXT,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT fk_nostro_item_statement FOREIGN KEY(statement_id) REFERENCES nostro_statement(id) ON DELETE CASCADE,
+    CONSTRAINT fk_nostro_item_line FOREIGN KEY(journal_line_id) REFERENCES journal_line(id),
+    CONSTRAINT fk_nostro_item_staff FOREIGN KEY(matched_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_nostro_item_status ON nostro_item(status, currency, value_date);
+COMMIT;
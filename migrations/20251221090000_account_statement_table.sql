@@ -0,0 +1,29 @@
+BEGIN;
+-- A customer statement for one account and period, the rendered files are kept in S3
+CREATE TABLE account_statement (
+    "id" UUID,
+    "account_id" UUID NOT NULL,
+    "user_id" UUID NOT NULL,
+    "period_start" DATE NOT NULL,
+    "period_end" DATE NOT NULL CHECK (period_end >= period_start),
+    "currency" CHAR(3) NOT NULL,
+    "opening_cents" BIGINT NOT NULL,
+    "closing_cents" BIGINT NOT NULL,
+    "credits_cents" BIGINT NOT NULL,
+    "debits_cents" BIGINT NOT NULL,
+    -- Net of what was paid and charged, negative when the customer paid
+    "interest_cents" BIGINT NOT NULL,
+    "fees_cents" BIGINT NOT NULL,
+    "entry_count" INTEGER NOT NULL,
+    "html_key" TEXT NOT NULL,
+    "pdf_key" TEXT NOT NULL,
+    "csv_key" TEXT NOT NULL,
+    "emailed_at" timestamptz(3),
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_account_statement_period UNIQUE(account_id, period_start),
+    CONSTRAINT fk_account_statement_account FOREIGN KEY(account_id) REFERENCES user_account(id) ON DELETE CASCADE,
+    CONSTRAINT fk_account_statement_user FOREIGN KEY(user_id) REFERENCES tuser(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_account_statement_account ON account_statement(account_id, period_end DESC);
+COMMIT;
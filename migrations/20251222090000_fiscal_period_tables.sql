@@ -0,0 +1,42 @@
+BEGIN;
+-- Adjusting entries may still go into a soft-closed period, the year-end closing entry is
+-- generated. Entries without an account are the bank's own, like the closing entry.
+CREATE TYPE journal_entry_kind AS ENUM ('regular', 'adjusting', 'closing');
+ALTER TABLE journal_entry
+    ADD COLUMN "entry_kind" journal_entry_kind NOT NULL DEFAULT 'regular',
+    ALTER COLUMN "user_account_id" DROP NOT NULL;
+CREATE TABLE fiscal_year (
+    "id" UUID,
+    "name" VARCHAR(32) NOT NULL UNIQUE,
+    "start_date" DATE NOT NULL,
+    "end_date" DATE NOT NULL,
+    "closing_entry_id" UUID,
+    "closed_by" UUID,
+    "closed_at" timestamptz(3),
+    "created_by" UUID,
+    "created_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT ck_fiscal_year_dates CHECK (start_date <= end_date),
+    CONSTRAINT fk_fiscal_year_entry FOREIGN KEY(closing_entry_id) REFERENCES journal_entry(id),
+    CONSTRAINT fk_fiscal_year_closed_by FOREIGN KEY(closed_by) REFERENCES tuser(id) ON DELETE SET NULL,
+    CONSTRAINT fk_fiscal_year_created_by FOREIGN KEY(created_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE TYPE fiscal_period_status AS ENUM ('open', 'soft_closed', 'hard_closed');
+-- Days outside every period can be posted to as before there were fiscal years
+CREATE TABLE fiscal_period (
+    "id" UUID,
+    "fiscal_year_id" UUID NOT NULL,
+    "period_number" SMALLINT NOT NULL,
+    "start_date" DATE NOT NULL,
+    "end_date" DATE NOT NULL,
+    "status" fiscal_period_status NOT NULL DEFAULT 'open',
+    "updated_by" UUID,
+    "updated_at" timestamptz(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    CONSTRAINT uq_fiscal_period_number UNIQUE(fiscal_year_id, period_number),
+    CONSTRAINT ck_fiscal_period_dates CHECK (start_date <= end_date),
+    CONSTRAINT fk_fiscal_period_year FOREIGN KEY(fiscal_year_id) REFERENCES fiscal_year(id) ON DELETE CASCADE,
+    CONSTRAINT fk_fiscal_period_staff FOREIGN KEY(updated_by) REFERENCES tuser(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_fiscal_period_dates ON fiscal_period(start_date, end_date);
+COMMIT;
@@ -0,0 +1,12 @@
+BEGIN;
+-- Every journal line signed the way balances are read, credits positive. An account's balance
+-- is the sum of its entries' lines on liability chart accounts; reports and the year-end close
+-- sum by chart account. Balance queries read this rather than signing lines themselves.
+CREATE VIEW journal_line_balance AS
+    SELECT l.id AS journal_line_id, l.journal_entry_id, e.user_account_id, e.created_date,
+        e.entry_kind, l.coa_id, c.coa_type,
+        CASE l.line_type WHEN 'credit' THEN l.amount_cents ELSE -l.amount_cents END AS credit_cents
+    FROM journal_line l
+    JOIN journal_entry e ON e.id = l.journal_entry_id
+    JOIN chart_of_account c ON c.id = l.coa_id;
+COMMIT;
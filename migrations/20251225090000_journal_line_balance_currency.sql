@@ -0,0 +1,14 @@
+BEGIN;
+-- The currency a line is booked in: its customer account's currency, or the chart account's own
+-- for bank-side entries. Reports and the year-end close both convert by this column so the
+-- income statement and the closing sweep agree on every line.
+CREATE OR REPLACE VIEW journal_line_balance AS
+    SELECT l.id AS journal_line_id, l.journal_entry_id, e.user_account_id, e.created_date,
+        e.entry_kind, l.coa_id, c.coa_type,
+        CASE l.line_type WHEN 'credit' THEN l.amount_cents ELSE -l.amount_cents END AS credit_cents,
+        COALESCE(a.currency, c.currency)::VARCHAR AS currency
+    FROM journal_line l
+    JOIN journal_entry e ON e.id = l.journal_entry_id
+    JOIN chart_of_account c ON c.id = l.coa_id
+    LEFT JOIN user_account a ON a.id = e.user_account_id;
+COMMIT;
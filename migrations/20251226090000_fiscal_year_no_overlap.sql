@@ -0,0 +1,7 @@
+BEGIN;
+-- Two years creating at once could each find no overlap before either saved, the database
+-- refuses the second instead.
+ALTER TABLE fiscal_year
+    ADD CONSTRAINT ex_fiscal_year_overlap
+    EXCLUDE USING gist (daterange(start_date, end_date, '[]') WITH &&);
+COMMIT;
@@ -1,10 +1,12 @@
 use std::str::FromStr;
+use strum::Display;
 
 use crate::base::error::ValidationError;
 use uuid::Uuid;
 
-#[derive(Debug, serde::Deserialize, serde::Serialize, sqlx::Type)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, sqlx::Type, Display)]
 #[sqlx(type_name = "user_account_status", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum UserAccountStatus {
     Active,
     Closed,
@@ -23,11 +25,15 @@ pub struct UserAccountEntity {
     pub branch_id: Uuid,
     pub currency: String,
     pub status: UserAccountStatus,
+    pub product_version_id: Option<Uuid>,
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize, sqlx::Type)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize, sqlx::Type, Display,
+)]
 #[sqlx(type_name = "account_kind", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum AccountKind {
     Deposit,
     Investment,
@@ -19,8 +19,8 @@ impl<'a, 'b> AccountRepository<'a, 'b> {
         skip(self, user_account)
     )]
     pub async fn create(&mut self, user_account: &UserAccountEntity) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO user_account(id, user_id, account_number, iban, account_class, coa_id, branch_id, currency, status) 
-            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)")
+        sqlx::query("INSERT INTO user_account(id, user_id, account_number, iban, account_class, coa_id, branch_id, currency, status, product_version_id) 
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)")
             .bind(user_account.id)
             .bind(user_account.user_id)
             .bind(&user_account.account_number)
@@ -30,6 +30,7 @@ impl<'a, 'b> AccountRepository<'a, 'b> {
             .bind(user_account.branch_id)
             .bind(&user_account.currency)
             .bind(&user_account.status)
+            .bind(user_account.product_version_id)
             .execute(&mut **self.tx).await?;
 
         Ok(())
@@ -49,6 +50,22 @@ impl<'a, 'b> AccountRepository<'a, 'b> {
         Ok(result)
     }
 
+    #[tracing::instrument("Fetching account owner verification", skip(self, account_id))]
+    pub async fn fetch_owner_is_verified(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<bool>, sqlx::Error> {
+        let result: Option<bool> = sqlx::query(
+            "SELECT u.is_verified FROM user_account a JOIN tuser u ON u.id = a.user_id WHERE a.id=$1",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?
+        .map(|r| r.get("is_verified"));
+
+        Ok(result)
+    }
+
     #[tracing::instrument("Fetching account balance by id", skip(self, account_id))]
     pub async fn fetch_balance_by_user_account_id(
         &self,
@@ -62,6 +62,7 @@ impl TryFrom<UserAccountCreateRequest> for UserAccountEntity {
             branch_id: value.branch_id,
             currency: currency.code().to_string(),
             status: UserAccountStatus::Pending,
+            product_version_id: None,
         })
     }
 }
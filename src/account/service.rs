@@ -2,10 +2,13 @@ use uuid::Uuid;
 
 use crate::account::models::UserAccountEntity;
 use crate::account::schemas::UserAccountCreateRequest;
+use crate::authentication::token::SessionClaims;
 use crate::base::error::{AppError, DomainError, SqlErrorExt};
+use crate::branch::service::BranchService;
 use crate::config::state::AppState;
 use crate::infra::pgdb::UnitofWork;
 use crate::ledger::models::{CreditLine, DebitLine, IntoJournalLine, JournalEntry, LineType};
+use crate::product::service::ProductService;
 use crate::staff::models::CoaType;
 use crate::transaction::service::TransactionService;
 
@@ -20,16 +23,41 @@ impl<'a> AccountService<'a> {
     }
 
     // Create account
-    #[tracing::instrument("Create user account", skip(self))]
+    #[tracing::instrument("Create user account", skip(self, claims))]
     pub async fn create_user_account(
         &self,
+        claims: &SessionClaims,
         create_req: UserAccountCreateRequest,
     ) -> Result<(), AppError> {
         let mut uow = UnitofWork::from(&self.app_state.pgpool)
             .await
             .to_app_err("Failed to start postgres uow")?;
 
-        let user_account_entity: UserAccountEntity = create_req.try_into()?;
+        let mut user_account_entity: UserAccountEntity = create_req.try_into()?;
+
+        let owner = match uow
+            .authentication()
+            .fetch_user_by_id(user_account_entity.user_id)
+            .await
+            .to_app_err("Failed to fetch account owner")?
+        {
+            Some(u) => u,
+            None => Err(DomainError::NotFound("customer".into()))?,
+        };
+
+        // The account keeps the terms current at opening even when the product changes later
+        user_account_entity.product_version_id = ProductService::from(self.app_state)
+            .check_account_opening(
+                &mut uow,
+                user_account_entity.account_class,
+                &owner,
+                &user_account_entity.currency,
+            )
+            .await?;
+
+        BranchService::from(self.app_state)
+            .check_account_opening(&mut uow, claims, user_account_entity.branch_id)
+            .await?;
 
         uow.accounts()
             .create(&user_account_entity)
@@ -86,7 +114,7 @@ impl<'a> AccountService<'a> {
             .to_app_err("failed to create journal line")?;
 
         uow.accounts()
-            .start_acc_balance(*journal_entry.get_user_account_id())
+            .start_acc_balance(user_account_entity.id)
             .await
             .to_app_err("Failed to start account balance")?;
 
@@ -0,0 +1,18 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::ach::routes::create_ach_transfer,
+    crate::ach::routes::list_ach_transfers,
+    crate::ach::routes::fetch_ach_transfer,
+))]
+pub struct AchTransferApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::ach::routes::generate_ach_file,
+    crate::ach::routes::list_ach_files,
+    crate::ach::routes::fetch_ach_file,
+    crate::ach::routes::import_ach_returns,
+))]
+pub struct AchFileApi;
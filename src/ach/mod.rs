@@ -0,0 +1,7 @@
+pub mod docs;
+pub mod models;
+pub mod nacha;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,468 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc, Weekday};
+use std::path::PathBuf;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::ach::schemas::AchTransferRequest;
+use crate::base::error::{DomainError, ValidationError};
+use crate::beneficiary::models::BeneficiaryEntity;
+use crate::sepa::models::sepa_text;
+
+// Outbound transfers sit here between the customer debit and the ACH operator settling them
+pub const ACH_CLEARING_COA: &str = "1040";
+pub const ACH_CURRENCY: &str = "USD";
+// Product fee charged to the customer when a transfer comes back
+pub const RETURN_FEE_CODE: &str = "ach_return";
+
+const MAX_ADDENDA_LEN: usize = 80;
+// The entry detail amount field is ten digits
+const MAX_AMOUNT_CENTS: i64 = 9_999_999_999;
+const MAX_DFI_ACCOUNT_LEN: usize = 17;
+const MAX_RECEIVER_NAME_LEN: usize = 22;
+// Files of one day are told apart by A to Z, then 0 to 9
+const FILE_ID_MODIFIERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+// Return reason codes from the NACHA rules, and whether the receiver's account can't be paid
+// again until the customer has checked the details with them
+const RETURN_REASONS: &[(&str, &str, bool)] = &[
+    ("R01", "Insufficient funds", false),
+    ("R02", "Account closed", true),
+    ("R03", "No account, unable to locate account", true),
+    ("R04", "Invalid account number", true),
+    ("R06", "Returned per ODFI's request", false),
+    ("R07", "Authorization revoked by customer", true),
+    ("R08", "Payment stopped", false),
+    ("R09", "Uncollected funds", false),
+    ("R10", "Customer advises not authorized", true),
+    (
+        "R11",
+        "Customer advises entry not in accordance with the terms",
+        false,
+    ),
+    ("R14", "Representative payee deceased", true),
+    ("R15", "Beneficiary or account holder deceased", true),
+    ("R16", "Account frozen", true),
+    ("R17", "File record edit criteria", false),
+    ("R20", "Non-transaction account", true),
+    ("R23", "Credit entry refused by receiver", true),
+    ("R24", "Duplicate entry", false),
+    ("R29", "Corporate customer advises not authorized", true),
+];
+
+pub fn return_reason(code: &str) -> &'static str {
+    RETURN_REASONS
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, reason, _)| *reason)
+        .unwrap_or("Returned by the receiving bank")
+}
+
+pub fn return_flags_beneficiary(code: &str) -> bool {
+    RETURN_REASONS
+        .iter()
+        .any(|(c, _, flags)| *c == code && *flags)
+}
+
+// NACHA files are upper case ASCII, names are spelled out the way SEPA ones are
+pub fn nacha_text(value: &str) -> String {
+    sepa_text(value).to_uppercase()
+}
+
+#[derive(Debug, Clone)]
+pub struct AchProfile {
+    pub odfi_routing: String,
+    pub immediate_destination: String,
+    pub destination_name: String,
+    pub origin_name: String,
+    pub company_id: String,
+    pub company_name: String,
+    pub outbound_dir: Option<PathBuf>,
+    pub return_dir: Option<PathBuf>,
+}
+
+impl AchProfile {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        odfi_routing: &str,
+        immediate_destination: &str,
+        destination_name: &str,
+        origin_name: &str,
+        company_id: &str,
+        company_name: &str,
+        outbound_dir: Option<&str>,
+        return_dir: Option<&str>,
+    ) -> Self {
+        Self {
+            odfi_routing: odfi_routing.trim().to_string(),
+            immediate_destination: immediate_destination.trim().to_string(),
+            destination_name: nacha_text(destination_name),
+            origin_name: nacha_text(origin_name),
+            company_id: company_id.trim().to_uppercase(),
+            company_name: nacha_text(company_name),
+            outbound_dir: outbound_dir.map(PathBuf::from),
+            return_dir: return_dir.map(PathBuf::from),
+        }
+    }
+
+    // ODFI routing number followed by the next value of the trace sequence
+    pub fn trace_number(&self, sequence: i64) -> String {
+        format!(
+            "{}{:07}",
+            &self.odfi_routing[..8],
+            sequence.rem_euclid(10_000_000)
+        )
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "ach_transfer_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AchTransferStatus {
+    Queued,
+    Batched,
+    Returned,
+}
+
+// What the customer asked for, checked before any account is touched
+#[derive(Debug)]
+pub struct TransferInstruction {
+    pub amount_cents: i64,
+    pub addenda_info: Option<String>,
+}
+
+impl TransferInstruction {
+    pub fn parse(request: &AchTransferRequest) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 || request.amount_cents > MAX_AMOUNT_CENTS {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: format!("Must be between 1 and {}", MAX_AMOUNT_CENTS),
+            });
+        }
+
+        let addenda_info = match request.addenda_info.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(info) if info.chars().count() > MAX_ADDENDA_LEN => {
+                return Err(ValidationError::TooLong {
+                    field: "addenda_info".into(),
+                    max: MAX_ADDENDA_LEN,
+                });
+            }
+            Some(info) if !info.chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+                return Err(ValidationError::InvalidValue {
+                    field: "addenda_info".into(),
+                    reason: "Only printable ASCII characters are allowed".into(),
+                });
+            }
+            Some(info) => Some(info.to_uppercase()),
+        };
+
+        Ok(Self {
+            amount_cents: request.amount_cents,
+            addenda_info,
+        })
+    }
+}
+
+// The receiver as it goes on the entry detail record, only dollar beneficiaries at a US bank
+// with a routing number qualify
+#[derive(Debug)]
+pub struct Receiver {
+    pub name: String,
+    pub routing_number: String,
+    pub dfi_account_number: String,
+}
+
+impl Receiver {
+    pub fn from_beneficiary(beneficiary: &BeneficiaryEntity) -> Result<Self, DomainError> {
+        let (routing_number, account_number) =
+            match (&beneficiary.routing_number, &beneficiary.account_number) {
+                (Some(routing), Some(number)) => (routing.clone(), number.clone()),
+                _ => {
+                    return Err(DomainError::ConstraintViolation(
+                        "beneficiary has no US routing and account number".into(),
+                    ));
+                }
+            };
+
+        if account_number.len() > MAX_DFI_ACCOUNT_LEN {
+            return Err(DomainError::ConstraintViolation(format!(
+                "ACH account numbers are at most {} characters",
+                MAX_DFI_ACCOUNT_LEN
+            )));
+        }
+
+        if beneficiary.currency != ACH_CURRENCY {
+            return Err(DomainError::ConstraintViolation(format!(
+                "beneficiary is paid in {}, ACH transfers are in {}",
+                beneficiary.currency, ACH_CURRENCY
+            )));
+        }
+
+        Ok(Self {
+            name: nacha_text(&beneficiary.name)
+                .chars()
+                .take(MAX_RECEIVER_NAME_LEN)
+                .collect(),
+            routing_number,
+            dfi_account_number: account_number,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct OriginatorAccountEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub currency: String,
+    pub status: UserAccountStatus,
+}
+
+impl OriginatorAccountEntity {
+    pub fn check_debit(&self, amount_cents: i64, available_cents: i64) -> Result<(), DomainError> {
+        if !matches!(self.status, UserAccountStatus::Active) {
+            return Err(DomainError::InvalidState(format!(
+                "account is {}",
+                self.status
+            )));
+        }
+
+        if self.currency != ACH_CURRENCY {
+            return Err(DomainError::ConstraintViolation(format!(
+                "account is held in {}, ACH transfers are in {}",
+                self.currency, ACH_CURRENCY
+            )));
+        }
+
+        if amount_cents > available_cents {
+            return Err(DomainError::ConstraintViolation(
+                "insufficient available funds".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// Journal reference of the customer debit, the returned credit and the return fee add a suffix
+pub fn transfer_reference(trace_number: &str) -> String {
+    format!("ACH{}", trace_number)
+}
+
+pub fn return_reference(trace_number: &str) -> String {
+    format!("{}-RT", transfer_reference(trace_number))
+}
+
+pub fn return_fee_reference(trace_number: &str) -> String {
+    format!("{}-RF", transfer_reference(trace_number))
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AchTransferEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub receiver_name: String,
+    pub routing_number: String,
+    pub dfi_account_number: String,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub trace_number: String,
+    pub addenda_info: Option<String>,
+    pub status: AchTransferStatus,
+    pub file_id: Option<Uuid>,
+    pub journal_entry_id: Uuid,
+    pub return_entry_id: Option<Uuid>,
+    pub fee_entry_id: Option<Uuid>,
+    pub return_code: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AchTransferEntity {
+    pub fn queued(
+        account: &OriginatorAccountEntity,
+        beneficiary_id: Uuid,
+        receiver: Receiver,
+        instruction: TransferInstruction,
+        trace_number: String,
+        journal_entry_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id: account.user_id,
+            account_id: account.id,
+            beneficiary_id,
+            receiver_name: receiver.name,
+            routing_number: receiver.routing_number,
+            dfi_account_number: receiver.dfi_account_number,
+            amount_cents: instruction.amount_cents,
+            currency: ACH_CURRENCY.into(),
+            trace_number,
+            addenda_info: instruction.addenda_info,
+            status: AchTransferStatus::Queued,
+            file_id: None,
+            journal_entry_id,
+            return_entry_id: None,
+            fee_entry_id: None,
+            return_code: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+// A queued transfer with the originating account number, which identifies the customer to
+// the receiver
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct QueuedAchEntity {
+    pub id: Uuid,
+    pub originator_account_number: String,
+    pub receiver_name: String,
+    pub routing_number: String,
+    pub dfi_account_number: String,
+    pub amount_cents: i64,
+    pub trace_number: String,
+    pub addenda_info: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AchFileEntity {
+    pub id: Uuid,
+    pub file_creation_date: NaiveDate,
+    pub file_id_modifier: String,
+    pub effective_entry_date: NaiveDate,
+    pub n_entries: i32,
+    pub entry_hash: i64,
+    pub total_credit_cents: i64,
+    pub file_location: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AchFileEntity {
+    // n_files_today is how many files were already created today
+    pub fn new(
+        n_files_today: i64,
+        created_by: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Self, DomainError> {
+        let file_id_modifier = match FILE_ID_MODIFIERS.chars().nth(n_files_today as usize) {
+            Some(c) => c.to_string(),
+            None => {
+                return Err(DomainError::ConstraintViolation(format!(
+                    "no more than {} ACH files can be created in a day",
+                    FILE_ID_MODIFIERS.len()
+                )));
+            }
+        };
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            file_creation_date: now.date_naive(),
+            file_id_modifier,
+            effective_entry_date: next_business_day(now.date_naive()),
+            n_entries: 0,
+            entry_hash: 0,
+            total_credit_cents: 0,
+            file_location: String::new(),
+            created_by: Some(created_by),
+            created_at: now,
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "ach_{}_{}.txt",
+            self.file_creation_date.format("%Y%m%d"),
+            self.file_id_modifier
+        )
+    }
+}
+
+// Weekends are skipped, bank holidays are left to the ACH operator
+fn next_business_day(date: NaiveDate) -> NaiveDate {
+    let mut date = date + Days::new(1);
+    while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        date = date + Days::new(1);
+    }
+
+    date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    fn beneficiary(routing_number: Option<&str>, currency: &str) -> BeneficiaryEntity {
+        let now = Utc::now();
+
+        BeneficiaryEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            name: "Zoë Hernández-López Enterprises".into(),
+            iban: None,
+            account_number: Some("000123456789".into()),
+            bic: None,
+            routing_number: routing_number.map(Into::into),
+            bank_country: "US".into(),
+            currency: currency.into(),
+            cooling_off_until: now,
+            flagged_reason: None,
+            flagged_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn receivers_need_a_routing_number_and_dollars() {
+        let receiver = assert_ok!(Receiver::from_beneficiary(&beneficiary(
+            Some("026009593"),
+            "USD"
+        )));
+        assert_eq!(receiver.name, "ZOE HERNANDEZ-LOPEZ EN");
+        assert_eq!(receiver.dfi_account_number, "000123456789");
+
+        let _ = assert_err!(Receiver::from_beneficiary(&beneficiary(None, "USD")));
+        let _ = assert_err!(Receiver::from_beneficiary(&beneficiary(
+            Some("026009593"),
+            "CAD"
+        )));
+    }
+
+    #[test]
+    fn return_codes_that_rule_out_the_account_flag_the_beneficiary() {
+        assert!(return_flags_beneficiary("R03"));
+        assert!(return_flags_beneficiary("R16"));
+        assert!(!return_flags_beneficiary("R01"));
+        assert!(!return_flags_beneficiary("R99"));
+        assert_eq!(return_reason("R02"), "Account closed");
+    }
+
+    #[test]
+    fn files_of_a_day_take_the_next_modifier() {
+        // A Friday, the entries settle on Monday
+        let now = DateTime::parse_from_rfc3339("2025-12-19T16:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        let first = assert_ok!(AchFileEntity::new(0, Uuid::now_v7(), now));
+        assert_eq!(first.file_id_modifier, "A");
+        assert_eq!(first.file_name(), "ach_20251219_A.txt");
+        assert_eq!(
+            first.effective_entry_date,
+            NaiveDate::from_ymd_opt(2025, 12, 22).unwrap()
+        );
+
+        let last = assert_ok!(AchFileEntity::new(35, Uuid::now_v7(), now));
+        assert_eq!(last.file_id_modifier, "9");
+        let _ = assert_err!(AchFileEntity::new(36, Uuid::now_v7(), now));
+    }
+}
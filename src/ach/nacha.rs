@@ -0,0 +1,409 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::ach::models::{AchProfile, QueuedAchEntity};
+use crate::base::error::ValidationError;
+
+const RECORD_LEN: usize = 94;
+const BLOCKING_FACTOR: usize = 10;
+// Credits only, all entries are PPD credits to checking accounts
+const SERVICE_CLASS_CREDITS: &str = "220";
+const CHECKING_CREDIT: &str = "22";
+const STANDARD_ENTRY_CLASS: &str = "PPD";
+const ENTRY_DESCRIPTION: &str = "PAYMENT";
+// The entry hash keeps only its low ten digits when it overflows the field
+const ENTRY_HASH_MODULUS: i64 = 10_000_000_000;
+
+// Alphanumeric fields are upper case, left justified and blank filled
+fn alpha(value: &str, len: usize) -> String {
+    let value: String = value
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { ' ' })
+        .take(len)
+        .collect::<String>()
+        .to_uppercase();
+
+    format!("{:<len$}", value, len = len)
+}
+
+// Numeric fields are right justified and zero filled
+fn numeric(value: i64, len: usize) -> String {
+    format!("{:0>len$}", value, len = len)
+}
+
+// The ninth digit of a routing number, from the first eight weighted 3, 7, 1 in turn
+pub fn routing_check_digit(routing: &str) -> u32 {
+    let sum: u32 = routing
+        .chars()
+        .take(8)
+        .zip([3, 7, 1].iter().cycle())
+        .map(|(c, weight)| c.to_digit(10).unwrap_or_default() * weight)
+        .sum();
+
+    (10 - sum % 10) % 10
+}
+
+// One PPD batch of credits in a single file, written out record by record
+#[derive(Debug)]
+pub struct NachaFile<'a> {
+    profile: &'a AchProfile,
+    created_at: DateTime<Utc>,
+    file_id_modifier: &'a str,
+    effective_entry_date: NaiveDate,
+    entries: &'a [QueuedAchEntity],
+}
+
+impl<'a> NachaFile<'a> {
+    pub fn new(
+        profile: &'a AchProfile,
+        created_at: DateTime<Utc>,
+        file_id_modifier: &'a str,
+        effective_entry_date: NaiveDate,
+        entries: &'a [QueuedAchEntity],
+    ) -> Self {
+        Self {
+            profile,
+            created_at,
+            file_id_modifier,
+            effective_entry_date,
+            entries,
+        }
+    }
+
+    // Sum of the first eight digits of every receiving bank's routing number
+    pub fn entry_hash(&self) -> i64 {
+        self.entries
+            .iter()
+            .map(|e| e.routing_number[..8].parse::<i64>().unwrap_or_default())
+            .sum::<i64>()
+            % ENTRY_HASH_MODULUS
+    }
+
+    pub fn total_credit_cents(&self) -> i64 {
+        self.entries.iter().map(|e| e.amount_cents).sum()
+    }
+
+    fn entry_addenda_count(&self) -> usize {
+        self.entries.len()
+            + self
+                .entries
+                .iter()
+                .filter(|e| e.addenda_info.is_some())
+                .count()
+    }
+
+    fn file_header(&self) -> String {
+        [
+            "1".to_string(),
+            "01".to_string(),
+            format!(" {}", alpha(&self.profile.immediate_destination, 9)),
+            format!(" {}", alpha(&self.profile.odfi_routing, 9)),
+            self.created_at.format("%y%m%d").to_string(),
+            self.created_at.format("%H%M").to_string(),
+            alpha(self.file_id_modifier, 1),
+            "094".to_string(),
+            numeric(BLOCKING_FACTOR as i64, 2),
+            "1".to_string(),
+            alpha(&self.profile.destination_name, 23),
+            alpha(&self.profile.origin_name, 23),
+            alpha("", 8),
+        ]
+        .concat()
+    }
+
+    fn batch_header(&self) -> String {
+        [
+            "5".to_string(),
+            SERVICE_CLASS_CREDITS.to_string(),
+            alpha(&self.profile.company_name, 16),
+            alpha("", 20),
+            alpha(&self.profile.company_id, 10),
+            STANDARD_ENTRY_CLASS.to_string(),
+            alpha(ENTRY_DESCRIPTION, 10),
+            alpha("", 6),
+            self.effective_entry_date.format("%y%m%d").to_string(),
+            alpha("", 3),
+            "1".to_string(),
+            alpha(&self.profile.odfi_routing[..8], 8),
+            numeric(1, 7),
+        ]
+        .concat()
+    }
+
+    fn entry_detail(entry: &QueuedAchEntity) -> String {
+        let addenda_indicator = if entry.addenda_info.is_some() {
+            "1"
+        } else {
+            "0"
+        };
+
+        [
+            "6".to_string(),
+            CHECKING_CREDIT.to_string(),
+            alpha(&entry.routing_number[..8], 8),
+            routing_check_digit(&entry.routing_number).to_string(),
+            alpha(&entry.dfi_account_number, 17),
+            numeric(entry.amount_cents, 10),
+            alpha(&entry.originator_account_number, 15),
+            alpha(&entry.receiver_name, 22),
+            alpha("", 2),
+            addenda_indicator.to_string(),
+            alpha(&entry.trace_number, 15),
+        ]
+        .concat()
+    }
+
+    fn addenda(entry: &QueuedAchEntity, info: &str) -> String {
+        [
+            "7".to_string(),
+            "05".to_string(),
+            alpha(info, 80),
+            numeric(1, 4),
+            alpha(&entry.trace_number[8..], 7),
+        ]
+        .concat()
+    }
+
+    fn batch_control(&self) -> String {
+        [
+            "8".to_string(),
+            SERVICE_CLASS_CREDITS.to_string(),
+            numeric(self.entry_addenda_count() as i64, 6),
+            numeric(self.entry_hash(), 10),
+            numeric(0, 12),
+            numeric(self.total_credit_cents(), 12),
+            alpha(&self.profile.company_id, 10),
+            alpha("", 19),
+            alpha("", 6),
+            alpha(&self.profile.odfi_routing[..8], 8),
+            numeric(1, 7),
+        ]
+        .concat()
+    }
+
+    fn file_control(&self, n_blocks: usize) -> String {
+        [
+            "9".to_string(),
+            numeric(1, 6),
+            numeric(n_blocks as i64, 6),
+            numeric(self.entry_addenda_count() as i64, 8),
+            numeric(self.entry_hash(), 10),
+            numeric(0, 12),
+            numeric(self.total_credit_cents(), 12),
+            alpha("", 39),
+        ]
+        .concat()
+    }
+
+    // Every record is 94 characters, the file is filled out to whole blocks of ten with
+    // records of nines
+    pub fn to_records(&self) -> Vec<String> {
+        let mut records = vec![self.file_header(), self.batch_header()];
+
+        for entry in self.entries {
+            records.push(Self::entry_detail(entry));
+            if let Some(info) = &entry.addenda_info {
+                records.push(Self::addenda(entry, info));
+            }
+        }
+
+        records.push(self.batch_control());
+
+        let n_blocks = (records.len() + 1).div_ceil(BLOCKING_FACTOR);
+        records.push(self.file_control(n_blocks));
+        records.resize(n_blocks * BLOCKING_FACTOR, "9".repeat(RECORD_LEN));
+
+        records
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut text = self.to_records().join("\n");
+        text.push('\n');
+
+        text.into_bytes()
+    }
+}
+
+// A returned entry as the receiving bank sent it back, matched to ours by the original trace
+#[derive(Debug, PartialEq)]
+pub struct AchReturn {
+    pub return_code: String,
+    pub original_trace_number: String,
+    pub amount_cents: i64,
+}
+
+// Return entries are entry detail records followed by a 99 addenda. Notifications of change,
+// 98 addenda, ask for no action on the ledger and are passed over.
+pub fn parse_return_file(bytes: &[u8]) -> Result<Vec<AchReturn>, ValidationError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| ValidationError::InvalidFormat("ACH return file".into()))?;
+
+    let records: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+
+    if !records.first().is_some_and(|r| r.starts_with('1')) {
+        return Err(ValidationError::InvalidFormat("ACH return file".into()));
+    }
+    if records
+        .iter()
+        .any(|r| r.len() != RECORD_LEN || !r.is_ascii())
+    {
+        return Err(ValidationError::InvalidValue {
+            field: "ACH return file".into(),
+            reason: format!("Every record must be {} characters", RECORD_LEN),
+        });
+    }
+
+    let mut returns = Vec::new();
+    let mut amount_cents = None;
+
+    for record in records {
+        match &record[..1] {
+            "6" => {
+                amount_cents =
+                    Some(record[29..39].parse::<i64>().map_err(|_| {
+                        ValidationError::InvalidFormat("entry detail amount".into())
+                    })?);
+            }
+            "7" if &record[1..3] == "99" => {
+                let amount_cents = match amount_cents.take() {
+                    Some(amount) => amount,
+                    None => return Err(ValidationError::MissingField("entry detail".into())),
+                };
+
+                returns.push(AchReturn {
+                    return_code: record[3..6].to_string(),
+                    original_trace_number: record[6..21].to_string(),
+                    amount_cents,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(returns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn profile() -> AchProfile {
+        AchProfile::new(
+            "026009593",
+            "011000015",
+            "Federal Reserve Bank",
+            "Thalia Bank",
+            "1234567890",
+            "Thalia Corp.",
+            None,
+            None,
+        )
+    }
+
+    fn entry(routing_number: &str, amount_cents: i64, addenda: Option<&str>) -> QueuedAchEntity {
+        QueuedAchEntity {
+            id: Uuid::now_v7(),
+            originator_account_number: "1000000001".into(),
+            receiver_name: "JANE DOE".into(),
+            routing_number: routing_number.into(),
+            dfi_account_number: "000123456789".into(),
+            amount_cents,
+            trace_number: "026009590000042".into(),
+            addenda_info: addenda.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn routing_check_digits_match_published_numbers() {
+        assert_eq!(routing_check_digit("02600959"), 3);
+        assert_eq!(routing_check_digit("01100001"), 5);
+        assert_eq!(routing_check_digit("12100035"), 8);
+    }
+
+    #[test]
+    fn files_are_blocked_94_character_records_with_balanced_controls() {
+        let profile = profile();
+        let created_at = DateTime::parse_from_rfc3339("2025-12-18T09:30:00Z")
+            .unwrap()
+            .to_utc();
+        let entries = vec![
+            entry("026009593", 125_000, Some("INVOICE 7781")),
+            entry("121000358", 2_550, None),
+        ];
+
+        let file = NachaFile::new(
+            &profile,
+            created_at,
+            "A",
+            NaiveDate::from_ymd_opt(2025, 12, 19).unwrap(),
+            &entries,
+        );
+        let records = file.to_records();
+
+        assert_eq!(records.len(), 10);
+        assert!(records.iter().all(|r| r.len() == 94));
+        assert_eq!(&records[0][..3], "101");
+        assert_eq!(&records[0][3..23], " 011000015 026009593");
+        assert_eq!(&records[0][23..34], "2512180930A");
+        assert_eq!(&records[1][50..53], "PPD");
+        assert_eq!(&records[1][69..75], "251219");
+
+        let first = &records[2];
+        assert_eq!(&first[..12], "622026009593");
+        assert_eq!(&first[29..39], "0000125000");
+        assert_eq!(&first[78..79], "1");
+        assert_eq!(&first[79..], "026009590000042");
+        assert_eq!(&records[3][..15], "705INVOICE 7781");
+        assert_eq!(&records[3][83..], "00010000042");
+        assert_eq!(&records[4][..12], "622121000358");
+
+        // 02600959 + 12100035
+        assert_eq!(file.entry_hash(), 14_700_994);
+        let batch_control = &records[5];
+        assert_eq!(&batch_control[..20], "82200000030014700994");
+        assert_eq!(&batch_control[32..44], "000000127550");
+        let file_control = &records[6];
+        assert_eq!(&file_control[..31], "9000001000001000000030014700994");
+        assert_eq!(records[9], "9".repeat(94));
+    }
+
+    #[test]
+    fn return_files_give_the_reason_and_original_trace() {
+        let entry = format!(
+            "621026009593{:<17}{:010}{:<15}{:<22}  1{}",
+            "1000000001", 125_000, "", "THALIA CORP", "011000010000001"
+        );
+        let dishonored = format!(
+            "799R03026009590000042      02600959{:<44}011000010000001",
+            ""
+        );
+        let notice = format!(
+            "798C01026009590000043      02600959{:<29}{:<15}011000010000002",
+            "000987654321", ""
+        );
+        let file = [
+            format!("101 026009593 0110000152512200800A094101{:<54}", ""),
+            entry.clone(),
+            dishonored,
+            entry,
+            notice,
+        ]
+        .join("\r\n");
+
+        let returns = parse_return_file(file.as_bytes()).unwrap();
+
+        assert_eq!(
+            returns,
+            vec![AchReturn {
+                return_code: "R03".into(),
+                original_trace_number: "026009590000042".into(),
+                amount_cents: 125_000,
+            }]
+        );
+        assert!(parse_return_file(b"<Document/>").is_err());
+    }
+}
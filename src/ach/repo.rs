@@ -0,0 +1,285 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::ach::models::{
+    AchFileEntity, AchTransferEntity, OriginatorAccountEntity, QueuedAchEntity, RETURN_FEE_CODE,
+};
+
+const TRANSFER_COLUMNS: &str = "id, user_id, account_id, beneficiary_id, receiver_name,
+    routing_number, dfi_account_number, amount_cents, currency, trace_number, addenda_info,
+    status, file_id, journal_entry_id, return_entry_id, fee_entry_id, return_code, created_at,
+    updated_at";
+
+const FILE_COLUMNS: &str = "id, file_creation_date, file_id_modifier, effective_entry_date,
+    n_entries, entry_hash, total_credit_cents, file_location, created_by, created_at";
+
+pub struct AchRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> AchRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    // Locked so two transfers can't both spend the same available balance
+    #[tracing::instrument("Locking ACH originator account", skip(self))]
+    pub async fn fetch_originator_account_for_update(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<OriginatorAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, OriginatorAccountEntity>(
+            "SELECT id, user_id, currency, status FROM user_account
+                WHERE id=$1
+                FOR UPDATE",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Drawing ACH trace sequence", skip(self))]
+    pub async fn next_trace_sequence(&mut self) -> Result<i64, sqlx::Error> {
+        let result: i64 = sqlx::query_scalar("SELECT nextval('ach_trace_seq')")
+            .fetch_one(&mut **self.tx)
+            .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving ACH transfer", skip(self, transfer))]
+    pub async fn insert_transfer(
+        &mut self,
+        transfer: &AchTransferEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO ach_transfer(id, user_id, account_id, beneficiary_id, receiver_name,
+                    routing_number, dfi_account_number, amount_cents, currency, trace_number,
+                    addenda_info, status, journal_entry_id, created_at, updated_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+        )
+        .bind(transfer.id)
+        .bind(transfer.user_id)
+        .bind(transfer.account_id)
+        .bind(transfer.beneficiary_id)
+        .bind(&transfer.receiver_name)
+        .bind(&transfer.routing_number)
+        .bind(&transfer.dfi_account_number)
+        .bind(transfer.amount_cents)
+        .bind(&transfer.currency)
+        .bind(&transfer.trace_number)
+        .bind(&transfer.addenda_info)
+        .bind(transfer.status)
+        .bind(transfer.journal_entry_id)
+        .bind(transfer.created_at)
+        .bind(transfer.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving ACH transfers", skip(self))]
+    pub async fn fetch_transfers(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<AchTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchTransferEntity>(&format!(
+            "SELECT {} FROM ach_transfer WHERE user_id=$1 ORDER BY created_at DESC",
+            TRANSFER_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving ACH transfer", skip(self))]
+    pub async fn fetch_transfer(
+        &self,
+        user_id: Uuid,
+        transfer_id: Uuid,
+    ) -> Result<Option<AchTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchTransferEntity>(&format!(
+            "SELECT {} FROM ach_transfer WHERE id=$1 AND user_id=$2",
+            TRANSFER_COLUMNS
+        ))
+        .bind(transfer_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Transfers another run has already picked up are skipped rather than waited on
+    #[tracing::instrument("Locking queued ACH transfers", skip(self))]
+    pub async fn fetch_queued_for_update(&mut self) -> Result<Vec<QueuedAchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, QueuedAchEntity>(
+            "SELECT t.id, a.account_number AS originator_account_number, t.receiver_name,
+                    t.routing_number, t.dfi_account_number, t.amount_cents, t.trace_number,
+                    t.addenda_info
+                FROM ach_transfer t
+                JOIN user_account a ON a.id = t.account_id
+                WHERE t.status = 'queued'
+                ORDER BY t.trace_number
+                FOR UPDATE OF t SKIP LOCKED",
+        )
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Counting ACH files of the day", skip(self))]
+    pub async fn count_files_on(&mut self, date: NaiveDate) -> Result<i64, sqlx::Error> {
+        let result: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM ach_file WHERE file_creation_date=$1")
+                .bind(date)
+                .fetch_one(&mut **self.tx)
+                .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving ACH file", skip(self, file))]
+    pub async fn insert_file(&mut self, file: &AchFileEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO ach_file(id, file_creation_date, file_id_modifier, effective_entry_date,
+                    n_entries, entry_hash, total_credit_cents, file_location, created_by,
+                    created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(file.id)
+        .bind(file.file_creation_date)
+        .bind(&file.file_id_modifier)
+        .bind(file.effective_entry_date)
+        .bind(file.n_entries)
+        .bind(file.entry_hash)
+        .bind(file.total_credit_cents)
+        .bind(&file.file_location)
+        .bind(file.created_by)
+        .bind(file.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Marking ACH transfers batched", skip(self, transfer_ids))]
+    pub async fn mark_batched(
+        &mut self,
+        file_id: Uuid,
+        transfer_ids: &[Uuid],
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE ach_transfer SET status='batched', file_id=$1, updated_at=$2
+                WHERE id = ANY($3)",
+        )
+        .bind(file_id)
+        .bind(now)
+        .bind(transfer_ids)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving ACH files", skip(self))]
+    pub async fn fetch_files(&self) -> Result<Vec<AchFileEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchFileEntity>(&format!(
+            "SELECT {} FROM ach_file ORDER BY created_at DESC",
+            FILE_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving ACH file", skip(self))]
+    pub async fn fetch_file(&self, file_id: Uuid) -> Result<Option<AchFileEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchFileEntity>(&format!(
+            "SELECT {} FROM ach_file WHERE id=$1",
+            FILE_COLUMNS
+        ))
+        .bind(file_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving ACH file transfers", skip(self))]
+    pub async fn fetch_file_transfers(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Vec<AchTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchTransferEntity>(&format!(
+            "SELECT {} FROM ach_transfer WHERE file_id=$1 ORDER BY trace_number",
+            TRANSFER_COLUMNS
+        ))
+        .bind(file_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking ACH transfer by trace number", skip(self))]
+    pub async fn fetch_by_trace_for_update(
+        &mut self,
+        trace_number: &str,
+    ) -> Result<Option<AchTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AchTransferEntity>(&format!(
+            "SELECT {} FROM ach_transfer WHERE trace_number=$1 FOR UPDATE",
+            TRANSFER_COLUMNS
+        ))
+        .bind(trace_number)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // From the fee schedule of the product version the account is on
+    #[tracing::instrument("Retrieving ACH return fee", skip(self))]
+    pub async fn fetch_return_fee(&mut self, account_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        let result: Option<i64> = sqlx::query_scalar(
+            "SELECT f.amount_cents FROM user_account a
+                JOIN product_fee f ON f.version_id = a.product_version_id AND f.code=$2
+                WHERE a.id=$1",
+        )
+        .bind(account_id)
+        .bind(RETURN_FEE_CODE)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Marking ACH transfer returned", skip(self, transfer))]
+    pub async fn mark_returned(&mut self, transfer: &AchTransferEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE ach_transfer
+                SET status=$2, return_entry_id=$3, fee_entry_id=$4, return_code=$5, updated_at=$6
+                WHERE id=$1",
+        )
+        .bind(transfer.id)
+        .bind(transfer.status)
+        .bind(transfer.return_entry_id)
+        .bind(transfer.fee_entry_id)
+        .bind(&transfer.return_code)
+        .bind(transfer.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::ach::{
+    schemas::{
+        AchFileDetailResponse, AchFileResponse, AchTransferRequest, AchTransferResponse,
+        ReturnImportResponse,
+    },
+    service::AchService,
+};
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+
+#[tracing::instrument("Queue ACH transfer", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/ach-transfers", request_body=AchTransferRequest, responses((status=200, body=AchTransferResponse, description="Account debited and transfer queued for the next NACHA file"), (status=400, description="Invalid amount or addenda information"), (status=404, description="Account or beneficiary not found"), (status=409, description="Account is not active"), (status=422, description="Not a dollar account or US beneficiary, insufficient funds, beneficiary flagged or still cooling off")))]
+pub async fn create_ach_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<AchTransferRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.create(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List ACH transfers", skip(app_state, claims))]
+#[utoipa::path(get, path="/ach-transfers", responses((status=200, body=Vec<AchTransferResponse>, description="Customer's ACH transfers, newest first")))]
+pub async fn list_ach_transfers(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.list(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch ACH transfer", skip(app_state, claims))]
+#[utoipa::path(get, path="/ach-transfers/{transfer_id}", params(("transfer_id"=Uuid, Path, description="ACH transfer id")), responses((status=200, body=AchTransferResponse, description="ACH transfer"), (status=404, description="ACH transfer not found")))]
+pub async fn fetch_ach_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    transfer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.fetch(&claims, transfer_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Generate ACH file", skip(app_state, claims))]
+#[utoipa::path(post, path="/ach/files", responses((status=200, body=AchFileResponse, description="Queued transfers written to a NACHA file"), (status=403, description="Only superusers can generate files"), (status=404, description="No queued ACH transfers"), (status=422, description="No file id modifiers left for the day")))]
+pub async fn generate_ach_file(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.generate_file(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List ACH files", skip(app_state))]
+#[utoipa::path(get, path="/ach/files", responses((status=200, body=Vec<AchFileResponse>, description="NACHA files, newest first")))]
+pub async fn list_ach_files(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.list_files().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch ACH file", skip(app_state))]
+#[utoipa::path(get, path="/ach/files/{file_id}", params(("file_id"=Uuid, Path, description="ACH file id")), responses((status=200, body=AchFileDetailResponse, description="File with its transfers"), (status=404, description="ACH file not found")))]
+pub async fn fetch_ach_file(
+    app_state: web::Data<AppState>,
+    file_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.fetch_file(file_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Import ACH returns", skip(app_state, claims))]
+#[utoipa::path(post, path="/ach/returns/import", responses((status=200, body=ReturnImportResponse, description="Returns applied, transfers credited back less the return fee"), (status=403, description="Only superusers can import returns"), (status=409, description="No ACH return directory configured")))]
+pub async fn import_ach_returns(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let ach_service = AchService::from(&app_state);
+
+    let response = ach_service.import_returns(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::ach::models::{AchFileEntity, AchTransferEntity, AchTransferStatus};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct AchTransferRequest {
+    // Dollar account the transfer is paid from
+    pub account_id: Uuid,
+    // A saved beneficiary with a US routing and account number
+    pub beneficiary_id: Uuid,
+    #[schema(example = 125_000)]
+    pub amount_cents: i64,
+    // Sent to the receiving bank in a payment related addenda record
+    #[schema(example = "INVOICE 7781")]
+    pub addenda_info: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AchTransferResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub receiver_name: String,
+    pub routing_number: String,
+    pub dfi_account_number: String,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub trace_number: String,
+    pub addenda_info: Option<String>,
+    pub status: AchTransferStatus,
+    pub file_id: Option<Uuid>,
+    // NACHA return reason code given by the receiving bank
+    #[schema(example = "R03")]
+    pub return_code: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<AchTransferEntity> for AchTransferResponse {
+    fn from(value: AchTransferEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            beneficiary_id: value.beneficiary_id,
+            receiver_name: value.receiver_name,
+            routing_number: value.routing_number,
+            dfi_account_number: value.dfi_account_number,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            trace_number: value.trace_number,
+            addenda_info: value.addenda_info,
+            status: value.status,
+            file_id: value.file_id,
+            return_code: value.return_code,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AchFileResponse {
+    pub id: Uuid,
+    pub file_creation_date: NaiveDate,
+    #[schema(example = "A")]
+    pub file_id_modifier: String,
+    pub effective_entry_date: NaiveDate,
+    pub n_entries: i32,
+    pub entry_hash: i64,
+    pub total_credit_cents: i64,
+    // A path on the outbound directory or an s3:// url
+    pub file_location: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AchFileEntity> for AchFileResponse {
+    fn from(value: AchFileEntity) -> Self {
+        Self {
+            id: value.id,
+            file_creation_date: value.file_creation_date,
+            file_id_modifier: value.file_id_modifier,
+            effective_entry_date: value.effective_entry_date,
+            n_entries: value.n_entries,
+            entry_hash: value.entry_hash,
+            total_credit_cents: value.total_credit_cents,
+            file_location: value.file_location,
+            created_by: value.created_by,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AchFileDetailResponse {
+    #[serde(flatten)]
+    pub file: AchFileResponse,
+    pub transfers: Vec<AchTransferResponse>,
+}
+
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct ReturnImportResponse {
+    pub files_processed: u32,
+    // Unreadable files, moved aside for a look
+    pub files_failed: u32,
+    pub transfers_returned: u32,
+    // For a trace number we never sent or a transfer already returned
+    pub returns_skipped: u32,
+    pub beneficiaries_flagged: u32,
+}
@@ -0,0 +1,445 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::ach::{
+    models::{
+        ACH_CLEARING_COA, AchFileEntity, AchTransferEntity, AchTransferStatus, Receiver,
+        TransferInstruction, return_fee_reference, return_flags_beneficiary, return_reason,
+        return_reference, transfer_reference,
+    },
+    nacha::{NachaFile, parse_return_file},
+    schemas::{
+        AchFileDetailResponse, AchFileResponse, AchTransferRequest, AchTransferResponse,
+        ReturnImportResponse,
+    },
+};
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::beneficiary::service::BeneficiaryService;
+use crate::config::state::AppState;
+use crate::hold::service::HoldService;
+use crate::identity_verify::service::KycService;
+use crate::infra::files::{OutboundFile, archive_drop_file, list_drop_files};
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::{models::ContraAccount, service::LedgerService};
+use crate::staff::models::CoaType;
+use crate::user::models::AccessRole;
+
+// What one return file added to the import summary
+#[derive(Default)]
+struct FileOutcome {
+    returned: u32,
+    skipped: u32,
+    flagged: u32,
+}
+
+pub struct AchService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> AchService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // The customer is debited straight away against clearing, the transfer then waits for a file
+    #[tracing::instrument("Queue ACH transfer", skip(self, claims, request))]
+    pub async fn create(
+        &self,
+        claims: &SessionClaims,
+        request: AchTransferRequest,
+    ) -> Result<AchTransferResponse, AppError> {
+        let instruction = TransferInstruction::parse(&request)?;
+        let user_id = *claims.get_user_id();
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = match uow
+            .ach()
+            .fetch_originator_account_for_update(request.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) if a.user_id == user_id => a,
+            _ => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        let beneficiary = BeneficiaryService::from(self.app_state)
+            .check_payment(
+                &mut uow,
+                user_id,
+                request.beneficiary_id,
+                instruction.amount_cents,
+                now,
+            )
+            .await?;
+        let receiver = Receiver::from_beneficiary(&beneficiary)?;
+        KycService::from(self.app_state)
+            .check_transaction(&mut uow, account.id, instruction.amount_cents)
+            .await?;
+
+        let available = HoldService::available_balance(&mut uow, account.id, now).await?;
+        account.check_debit(instruction.amount_cents, available.available_cents())?;
+
+        let sequence = uow
+            .ach()
+            .next_trace_sequence()
+            .await
+            .to_app_err("Failed to draw ACH trace number")?;
+        let trace_number = self.app_state.ach.trace_number(sequence);
+
+        let journal_entry_id = LedgerService::from(self.app_state)
+            .post_account_debit(
+                &mut uow,
+                account.id,
+                transfer_reference(&trace_number),
+                format!("ACH transfer to {}", receiver.name),
+                instruction.amount_cents,
+                ContraAccount::Code(ACH_CLEARING_COA),
+            )
+            .await?;
+
+        let transfer = AchTransferEntity::queued(
+            &account,
+            beneficiary.id,
+            receiver,
+            instruction,
+            trace_number,
+            journal_entry_id,
+            now,
+        );
+
+        uow.ach()
+            .insert_transfer(&transfer)
+            .await
+            .to_app_err("Failed to save ACH transfer")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit ACH transfer")?;
+
+        Ok(transfer.into())
+    }
+
+    #[tracing::instrument("List ACH transfers", skip(self, claims))]
+    pub async fn list(&self, claims: &SessionClaims) -> Result<Vec<AchTransferResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let transfers = uow
+            .ach()
+            .fetch_transfers(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch ACH transfers")?;
+
+        Ok(transfers
+            .into_iter()
+            .map(AchTransferResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch ACH transfer", skip(self, claims))]
+    pub async fn fetch(
+        &self,
+        claims: &SessionClaims,
+        transfer_id: Uuid,
+    ) -> Result<AchTransferResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        match uow
+            .ach()
+            .fetch_transfer(*claims.get_user_id(), transfer_id)
+            .await
+            .to_app_err("Failed to fetch ACH transfer")?
+        {
+            Some(t) => Ok(t.into()),
+            None => Err(DomainError::NotFound("ACH transfer".into()))?,
+        }
+    }
+
+    // Every queued transfer goes into one NACHA file, which is stored before the file record
+    // commits and removed again if the commit fails
+    #[tracing::instrument("Generate ACH file", skip(self, claims))]
+    pub async fn generate_file(&self, claims: &SessionClaims) -> Result<AchFileResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let transfers = uow
+            .ach()
+            .fetch_queued_for_update()
+            .await
+            .to_app_err("Failed to fetch queued ACH transfers")?;
+        if transfers.is_empty() {
+            Err(DomainError::NotFound("queued ACH transfers".into()))?
+        }
+
+        let n_files_today = uow
+            .ach()
+            .count_files_on(now.date_naive())
+            .await
+            .to_app_err("Failed to count ACH files")?;
+        let mut file = AchFileEntity::new(n_files_today, *claims.get_user_id(), now)?;
+
+        let nacha = NachaFile::new(
+            &self.app_state.ach,
+            now,
+            &file.file_id_modifier,
+            file.effective_entry_date,
+            &transfers,
+        );
+        file.n_entries = transfers.len() as i32;
+        file.entry_hash = nacha.entry_hash();
+        file.total_credit_cents = nacha.total_credit_cents();
+        let contents = nacha.to_bytes();
+
+        let outbound = OutboundFile::new(
+            self.app_state.ach.outbound_dir.as_deref(),
+            &self.app_state.s3_client.bucket,
+            "ach/outbound",
+            &file.file_name(),
+        );
+        file.file_location = outbound.location();
+
+        let transfer_ids: Vec<Uuid> = transfers.iter().map(|t| t.id).collect();
+
+        uow.ach()
+            .insert_file(&file)
+            .await
+            .to_app_err("Failed to save ACH file")?;
+
+        uow.ach()
+            .mark_batched(file.id, &transfer_ids, now)
+            .await
+            .to_app_err("Failed to mark ACH transfers batched")?;
+
+        outbound
+            .store(&self.app_state.s3_client, contents, "text/plain")
+            .await?;
+
+        let committed = uow.commit().await;
+        if committed.is_err() {
+            outbound.discard(&self.app_state.s3_client).await;
+        }
+        committed.to_app_err("Failed to commit ACH file")?;
+
+        Ok(file.into())
+    }
+
+    #[tracing::instrument("List ACH files", skip(self))]
+    pub async fn list_files(&self) -> Result<Vec<AchFileResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let files = uow
+            .ach()
+            .fetch_files()
+            .await
+            .to_app_err("Failed to fetch ACH files")?;
+
+        Ok(files.into_iter().map(AchFileResponse::from).collect())
+    }
+
+    #[tracing::instrument("Fetch ACH file", skip(self))]
+    pub async fn fetch_file(&self, file_id: Uuid) -> Result<AchFileDetailResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let file = match uow
+            .ach()
+            .fetch_file(file_id)
+            .await
+            .to_app_err("Failed to fetch ACH file")?
+        {
+            Some(f) => f,
+            None => Err(DomainError::NotFound("ACH file".into()))?,
+        };
+
+        let transfers = uow
+            .ach()
+            .fetch_file_transfers(file_id)
+            .await
+            .to_app_err("Failed to fetch ACH file transfers")?;
+
+        Ok(AchFileDetailResponse {
+            file: file.into(),
+            transfers: transfers
+                .into_iter()
+                .map(AchTransferResponse::from)
+                .collect(),
+        })
+    }
+
+    // Return files are taken in name order. Unreadable ones go to failed/ and the rest to
+    // processed/. A database error leaves the file in place for the next run.
+    #[tracing::instrument("Import ACH returns", skip(self, claims))]
+    pub async fn import_returns(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<ReturnImportResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let dir = match &self.app_state.ach.return_dir {
+            Some(dir) => dir.clone(),
+            None => Err(DomainError::InvalidState(
+                "no ACH return directory is configured".into(),
+            ))?,
+        };
+
+        let files = list_drop_files(&dir, &["ach", "txt"])?;
+        let mut summary = ReturnImportResponse::default();
+
+        for path in files {
+            let bytes = std::fs::read(&path).context("Failed to read ACH return file")?;
+
+            let folder = match self.apply_returns(&bytes, Utc::now()).await {
+                Ok(outcome) => {
+                    summary.files_processed += 1;
+                    summary.transfers_returned += outcome.returned;
+                    summary.returns_skipped += outcome.skipped;
+                    summary.beneficiaries_flagged += outcome.flagged;
+                    "processed"
+                }
+                Err(e @ AppError::Validation(_)) => {
+                    tracing::error!("ACH return file {} not applied: {}", path.display(), e);
+                    summary.files_failed += 1;
+                    "failed"
+                }
+                Err(e) => return Err(e),
+            };
+
+            archive_drop_file(&dir, &path, folder)?;
+        }
+
+        Ok(summary)
+    }
+
+    // A return reverses the customer debit out of clearing and charges the return fee of the
+    // account's product. Reasons that rule out the receiver's account flag the beneficiary.
+    // Returns for a trace number we never sent, or one already returned, are skipped.
+    async fn apply_returns(
+        &self,
+        bytes: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<FileOutcome, AppError> {
+        let returns = parse_return_file(bytes)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let ledger = LedgerService::from(self.app_state);
+        let mut outcome = FileOutcome::default();
+
+        for entry in returns {
+            let mut transfer = match uow
+                .ach()
+                .fetch_by_trace_for_update(&entry.original_trace_number)
+                .await
+                .to_app_err("Failed to fetch ACH transfer")?
+            {
+                Some(t) if t.status == AchTransferStatus::Batched => t,
+                _ => {
+                    outcome.skipped += 1;
+                    continue;
+                }
+            };
+
+            if entry.amount_cents != transfer.amount_cents {
+                tracing::warn!(
+                    "ACH return for {} is for {} cents, the transfer was {}",
+                    transfer.trace_number,
+                    entry.amount_cents,
+                    transfer.amount_cents
+                );
+            }
+
+            let reason = format!(
+                "{} {}",
+                entry.return_code,
+                return_reason(&entry.return_code)
+            );
+
+            let return_entry_id = ledger
+                .post_account_credit(
+                    &mut uow,
+                    transfer.account_id,
+                    return_reference(&transfer.trace_number),
+                    format!(
+                        "Returned ACH transfer to {}: {}",
+                        transfer.receiver_name, reason
+                    ),
+                    transfer.amount_cents,
+                    ContraAccount::Code(ACH_CLEARING_COA),
+                )
+                .await?;
+
+            let fee_cents = uow
+                .ach()
+                .fetch_return_fee(transfer.account_id)
+                .await
+                .to_app_err("Failed to fetch ACH return fee")?;
+            let fee_entry_id = match fee_cents {
+                Some(fee_cents) if fee_cents > 0 => Some(
+                    ledger
+                        .post_account_debit(
+                            &mut uow,
+                            transfer.account_id,
+                            return_fee_reference(&transfer.trace_number),
+                            format!("ACH return fee for {}", transfer.trace_number),
+                            fee_cents,
+                            CoaType::Income,
+                        )
+                        .await?,
+                ),
+                _ => None,
+            };
+
+            if return_flags_beneficiary(&entry.return_code) {
+                uow.beneficiaries()
+                    .flag_beneficiary(transfer.beneficiary_id, &reason, now)
+                    .await
+                    .to_app_err("Failed to flag beneficiary")?;
+                outcome.flagged += 1;
+            }
+
+            transfer.status = AchTransferStatus::Returned;
+            transfer.return_entry_id = Some(return_entry_id);
+            transfer.fee_entry_id = fee_entry_id;
+            transfer.return_code = Some(entry.return_code);
+            transfer.updated_at = now;
+
+            uow.ach()
+                .mark_returned(&transfer)
+                .await
+                .to_app_err("Failed to mark ACH transfer returned")?;
+
+            outcome.returned += 1;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit ACH returns")?;
+
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,10 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::api_key::routes::issue_api_key,
+    crate::api_key::routes::list_api_keys,
+    crate::api_key::routes::rotate_api_key,
+    crate::api_key::routes::revoke_api_key,
+))]
+pub struct ApiKeyApi;
@@ -0,0 +1,333 @@
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::str::FromStr;
+use strum::Display;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::base::error::{AuthError, ValidationError};
+
+const KEY_SCHEME: &str = "tha";
+const PREFIX_LEN: usize = 12;
+const SECRET_LEN: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum ApiScope {
+    #[strum(serialize = "transaction:deposit")]
+    TransactionDeposit,
+    #[strum(serialize = "transaction:withdraw")]
+    TransactionWithdraw,
+    #[strum(serialize = "card:authorize")]
+    CardAuthorize,
+}
+
+impl FromStr for ApiScope {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "transaction:deposit" => Ok(ApiScope::TransactionDeposit),
+            "transaction:withdraw" => Ok(ApiScope::TransactionWithdraw),
+            "card:authorize" => Ok(ApiScope::CardAuthorize),
+            _ => Err(ValidationError::InvalidValue {
+                field: "scopes".into(),
+                reason: format!("Unknown scope {}", s),
+            }),
+        }
+    }
+}
+
+// Keys look like `tha_{prefix}_{secret}`. The prefix is stored in clear to find the key,
+// only a hash of the whole key is kept at rest.
+pub struct RawApiKey {
+    prefix: String,
+    key: String,
+}
+
+impl RawApiKey {
+    pub fn generate() -> Self {
+        let prefix = Alphanumeric.sample_string(&mut rand::rng(), PREFIX_LEN);
+        let secret = Alphanumeric.sample_string(&mut rand::rng(), SECRET_LEN);
+
+        Self {
+            key: format!("{}_{}_{}", KEY_SCHEME, prefix, secret),
+            prefix,
+        }
+    }
+
+    pub fn parse(key: &str) -> Result<Self, AuthError> {
+        match key.trim().split('_').collect::<Vec<_>>()[..] {
+            [KEY_SCHEME, prefix, secret]
+                if prefix.len() == PREFIX_LEN && secret.len() == SECRET_LEN =>
+            {
+                Ok(Self {
+                    prefix: prefix.to_string(),
+                    key: key.trim().to_string(),
+                })
+            }
+            _ => Err(AuthError::InvalidTokenScheme),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    // Keys carry 238 bits of entropy so a fast hash is enough here
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.key.as_bytes()))
+    }
+}
+
+// Never print the secret part of a key
+impl std::fmt::Debug for RawApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawApiKey")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<str> for RawApiKey {
+    fn as_ref(&self) -> &str {
+        &self.key
+    }
+}
+
+pub fn parse_scopes(scopes: &[String]) -> Result<Vec<ApiScope>, ValidationError> {
+    if scopes.is_empty() {
+        return Err(ValidationError::MissingField("scopes".into()));
+    }
+
+    scopes.iter().map(|s| ApiScope::from_str(s)).collect()
+}
+
+pub fn parse_allowed_ips(allowed_ips: &[String]) -> Result<Vec<IpNet>, ValidationError> {
+    allowed_ips
+        .iter()
+        .map(|ip| {
+            // A bare address is treated as a single host network
+            IpNet::from_str(ip.trim())
+                .or_else(|_| IpAddr::from_str(ip.trim()).map(IpNet::from))
+                .map_err(|_| ValidationError::InvalidFormat(format!("allowed_ips {}", ip)))
+        })
+        .collect()
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct ApiKeyEntity {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub allowed_ips: Vec<String>,
+    pub created_by: Uuid,
+    pub rotated_from: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyEntity {
+    pub fn new(
+        name: String,
+        raw_key: &RawApiKey,
+        scopes: &[ApiScope],
+        allowed_ips: &[IpNet],
+        created_by: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            name,
+            prefix: raw_key.prefix().to_string(),
+            key_hash: raw_key.hash(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            allowed_ips: allowed_ips.iter().map(|ip| ip.to_string()).collect(),
+            created_by,
+            rotated_from: None,
+            expires_at,
+            revoked_at: None,
+            last_used_at: None,
+        }
+    }
+
+    // Same name, scopes and allow-list as the key being rotated
+    pub fn rotate(
+        &self,
+        raw_key: &RawApiKey,
+        created_by: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            name: self.name.clone(),
+            prefix: raw_key.prefix().to_string(),
+            key_hash: raw_key.hash(),
+            scopes: self.scopes.clone(),
+            allowed_ips: self.allowed_ips.clone(),
+            created_by,
+            rotated_from: Some(self.id),
+            expires_at,
+            revoked_at: None,
+            last_used_at: None,
+        }
+    }
+
+    pub fn authorize(
+        &self,
+        raw_key: &RawApiKey,
+        peer_ip: Option<IpAddr>,
+        now: DateTime<Utc>,
+    ) -> Result<ApiKeyPrincipal, AuthError> {
+        // Constant time so response timing says nothing about how much of a guess matched
+        let hash_matches: bool = self
+            .key_hash
+            .as_bytes()
+            .ct_eq(raw_key.hash().as_bytes())
+            .into();
+
+        if !hash_matches || self.revoked_at.is_some() {
+            return Err(AuthError::Unauthorized);
+        }
+
+        if self.expires_at.is_some_and(|exp| exp <= now) {
+            return Err(AuthError::Expired("api key".into()));
+        }
+
+        if !self.allowed_ips.is_empty() {
+            let allowed =
+                parse_allowed_ips(&self.allowed_ips).map_err(|_| AuthError::Unauthorized)?;
+
+            match peer_ip {
+                Some(ip) if allowed.iter().any(|net| net.contains(&ip)) => {}
+                _ => return Err(AuthError::InsufficientPermissions),
+            }
+        }
+
+        // Unknown scopes stored by an older release are simply ignored
+        let scopes = self
+            .scopes
+            .iter()
+            .filter_map(|s| ApiScope::from_str(s).ok())
+            .collect();
+
+        Ok(ApiKeyPrincipal {
+            key_id: self.id,
+            name: self.name.clone(),
+            scopes,
+        })
+    }
+}
+
+// The authenticated integration, available to handlers as request data
+#[derive(Debug, Clone, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct ApiKeyPrincipal {
+    key_id: Uuid,
+    name: String,
+    scopes: Vec<ApiScope>,
+}
+
+impl ApiKeyPrincipal {
+    pub fn require(&self, scope: ApiScope) -> Result<(), AuthError> {
+        if self.scopes.contains(&scope) {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientPermissions)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiKeyEntity, ApiScope, RawApiKey, parse_allowed_ips};
+    use crate::base::error::AuthError;
+    use chrono::{Duration, Utc};
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    fn entity(raw_key: &RawApiKey, allowed_ips: &[&str]) -> ApiKeyEntity {
+        let allowed_ips: Vec<String> = allowed_ips.iter().map(|ip| ip.to_string()).collect();
+
+        ApiKeyEntity::new(
+            "atm-001".into(),
+            raw_key,
+            &[ApiScope::TransactionDeposit],
+            &parse_allowed_ips(&allowed_ips).unwrap(),
+            Uuid::now_v7(),
+            Some(Utc::now() + Duration::days(30)),
+        )
+    }
+
+    #[test]
+    fn generated_key_parses_back_to_the_same_prefix() {
+        let raw_key = RawApiKey::generate();
+        let parsed = assert_ok!(RawApiKey::parse(raw_key.as_ref()));
+
+        assert_eq!(parsed.prefix(), raw_key.prefix());
+        assert_eq!(parsed.hash(), raw_key.hash());
+    }
+
+    #[test]
+    fn malformed_key_is_rejected() {
+        let _ = assert_err!(RawApiKey::parse("tha_short_key"));
+        let _ = assert_err!(RawApiKey::parse("Bearer something"));
+    }
+
+    #[test]
+    fn key_is_not_stored_in_clear() {
+        let raw_key = RawApiKey::generate();
+        let entity = entity(&raw_key, &[]);
+
+        assert!(!entity.key_hash.contains(raw_key.as_ref()));
+    }
+
+    #[test]
+    fn wrong_secret_for_a_prefix_is_rejected() {
+        let raw_key = RawApiKey::generate();
+        let entity = entity(&raw_key, &[]);
+
+        let forged = format!("tha_{}_{}", raw_key.prefix(), "x".repeat(40));
+        let forged = RawApiKey::parse(&forged).unwrap();
+
+        let _ = assert_err!(entity.authorize(&forged, None, Utc::now()));
+    }
+
+    #[test]
+    fn expired_key_is_rejected() {
+        let raw_key = RawApiKey::generate();
+        let entity = entity(&raw_key, &[]);
+
+        let result = entity.authorize(&raw_key, None, Utc::now() + Duration::days(31));
+        assert!(matches!(result, Err(AuthError::Expired(_))));
+    }
+
+    #[test]
+    fn peer_outside_allow_list_is_rejected() {
+        let raw_key = RawApiKey::generate();
+        let entity = entity(&raw_key, &["10.0.0.0/8", "192.168.1.20"]);
+
+        assert_ok!(entity.authorize(&raw_key, "10.1.2.3".parse().ok(), Utc::now()));
+        assert_ok!(entity.authorize(&raw_key, "192.168.1.20".parse().ok(), Utc::now()));
+
+        let result = entity.authorize(&raw_key, "172.16.0.1".parse().ok(), Utc::now());
+        assert!(matches!(result, Err(AuthError::InsufficientPermissions)));
+    }
+
+    #[test]
+    fn principal_only_holds_granted_scopes() {
+        let raw_key = RawApiKey::generate();
+        let principal = entity(&raw_key, &[])
+            .authorize(&raw_key, None, Utc::now())
+            .unwrap();
+
+        assert_ok!(principal.require(ApiScope::TransactionDeposit));
+        let _ = assert_err!(principal.require(ApiScope::TransactionWithdraw));
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::api_key::models::ApiKeyEntity;
+
+const API_KEY_COLUMNS: &str = "id, name, prefix, key_hash, scopes, allowed_ips, created_by, rotated_from, expires_at, revoked_at, last_used_at";
+
+pub struct ApiKeyRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> ApiKeyRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Saving api key in the database", skip(self, api_key))]
+    pub async fn create(&mut self, api_key: &ApiKeyEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO api_key(id, name, prefix, key_hash, scopes, allowed_ips, created_by, rotated_from, expires_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(api_key.id)
+        .bind(&api_key.name)
+        .bind(&api_key.prefix)
+        .bind(&api_key.key_hash)
+        .bind(&api_key.scopes)
+        .bind(&api_key.allowed_ips)
+        .bind(api_key.created_by)
+        .bind(api_key.rotated_from)
+        .bind(api_key.expires_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving api key by prefix", skip(self))]
+    pub async fn fetch_by_prefix(&self, prefix: &str) -> Result<Option<ApiKeyEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ApiKeyEntity>(&format!(
+            "SELECT {} FROM api_key WHERE prefix=$1",
+            API_KEY_COLUMNS
+        ))
+        .bind(prefix)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving api key by id", skip(self))]
+    pub async fn fetch_by_id(&self, id: Uuid) -> Result<Option<ApiKeyEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ApiKeyEntity>(&format!(
+            "SELECT {} FROM api_key WHERE id=$1",
+            API_KEY_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving all api keys", skip(self))]
+    pub async fn fetch_all(&self) -> Result<Vec<ApiKeyEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ApiKeyEntity>(&format!(
+            "SELECT {} FROM api_key ORDER BY created_at DESC",
+            API_KEY_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Revoking api key", skip(self))]
+    pub async fn revoke(&mut self, id: Uuid) -> Result<u64, sqlx::Error> {
+        let n_revoked = sqlx::query(
+            "UPDATE api_key SET revoked_at = CURRENT_TIMESTAMP WHERE id=$1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_revoked)
+    }
+
+    // Never extends a key, only brings its expiry forward
+    #[tracing::instrument("Expiring api key", skip(self))]
+    pub async fn expire_at(
+        &mut self,
+        id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE api_key SET expires_at = LEAST(COALESCE(expires_at, $1), $1) WHERE id=$2",
+        )
+        .bind(expires_at)
+        .bind(id)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Recording api key usage", skip(self))]
+    pub async fn touch_last_used(&mut self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE api_key SET last_used_at = CURRENT_TIMESTAMP WHERE id=$1")
+            .bind(id)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::api_key::{
+    schemas::{ApiKeyCreateRequest, ApiKeyIssuedResponse, ApiKeyResponse, ApiKeyRotateRequest},
+    service::ApiKeyService,
+};
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+
+#[tracing::instrument("Issue api key", skip(app_state, claims, payload), fields(name=%payload.name))]
+#[utoipa::path(post, path="/api-keys", responses((status=200, body=ApiKeyIssuedResponse, description="Api key issued, the key is only shown once"), (status=403, description="Only superusers can issue api keys")))]
+pub async fn issue_api_key(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<ApiKeyCreateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let api_key_service = ApiKeyService::from(&app_state);
+
+    let response = api_key_service
+        .issue_key(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List api keys", skip(app_state, claims))]
+#[utoipa::path(get, path="/api-keys", responses((status=200, body=Vec<ApiKeyResponse>, description="Issued api keys"), (status=403, description="Only superusers can list api keys")))]
+pub async fn list_api_keys(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let api_key_service = ApiKeyService::from(&app_state);
+
+    let response = api_key_service.list_keys(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Rotate api key", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/api-keys/{key_id}/rotate", responses((status=200, body=ApiKeyIssuedResponse, description="Replacement api key issued"), (status=404, description="Api key not found")))]
+pub async fn rotate_api_key(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    key_id: web::Path<Uuid>,
+    payload: web::Json<ApiKeyRotateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let api_key_service = ApiKeyService::from(&app_state);
+
+    let response = api_key_service
+        .rotate_key(&claims, key_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Revoke api key", skip(app_state, claims))]
+#[utoipa::path(delete, path="/api-keys/{key_id}", responses((status=200, body=StdResponse, description="Api key revoked"), (status=404, description="Api key not found")))]
+pub async fn revoke_api_key(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    key_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let api_key_service = ApiKeyService::from(&app_state);
+
+    api_key_service
+        .revoke_key(&claims, key_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Api key revoked")))
+}
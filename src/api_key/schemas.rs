@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::api_key::models::ApiKeyEntity;
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ApiKeyCreateRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ApiKeyRotateRequest {
+    pub expires_in_days: Option<u32>,
+    // How long the old key keeps working so devices can be reconfigured
+    #[serde(default)]
+    pub grace_period_hours: u32,
+}
+
+// The plain key is only ever returned once, when it is issued
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ApiKeyIssuedResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub allowed_ips: Vec<String>,
+    pub rotated_from: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKeyEntity> for ApiKeyResponse {
+    fn from(value: ApiKeyEntity) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            prefix: value.prefix,
+            scopes: value.scopes,
+            allowed_ips: value.allowed_ips,
+            rotated_from: value.rotated_from,
+            expires_at: value.expires_at,
+            revoked_at: value.revoked_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
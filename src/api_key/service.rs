@@ -0,0 +1,220 @@
+use chrono::{Duration, Utc};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::api_key::{
+    models::{ApiKeyEntity, ApiKeyPrincipal, RawApiKey, parse_allowed_ips, parse_scopes},
+    schemas::{ApiKeyCreateRequest, ApiKeyIssuedResponse, ApiKeyResponse, ApiKeyRotateRequest},
+};
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::user::models::AccessRole;
+
+pub struct ApiKeyService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> ApiKeyService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // Integrations can move money so only superusers manage their keys
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    fn expires_at(expires_in_days: Option<u32>) -> Option<chrono::DateTime<Utc>> {
+        expires_in_days.map(|days| Utc::now() + Duration::days(days.into()))
+    }
+
+    #[tracing::instrument("Issue api key", skip(self, claims))]
+    pub async fn issue_key(
+        &self,
+        claims: &SessionClaims,
+        request: ApiKeyCreateRequest,
+    ) -> Result<ApiKeyIssuedResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        if request.name.trim().is_empty() {
+            Err(ValidationError::MissingField("name".into()))?
+        }
+
+        let scopes = parse_scopes(&request.scopes)?;
+        let allowed_ips = parse_allowed_ips(&request.allowed_ips)?;
+
+        let raw_key = RawApiKey::generate();
+        let api_key = ApiKeyEntity::new(
+            request.name.trim().to_string(),
+            &raw_key,
+            &scopes,
+            &allowed_ips,
+            *claims.get_user_id(),
+            Self::expires_at(request.expires_in_days),
+        );
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        uow.api_keys()
+            .create(&api_key)
+            .await
+            .to_app_err("Failed to create api key")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit api key creation")?;
+
+        Ok(ApiKeyIssuedResponse {
+            id: api_key.id,
+            name: api_key.name,
+            key: raw_key.as_ref().to_string(),
+            scopes: api_key.scopes,
+            expires_at: api_key.expires_at,
+        })
+    }
+
+    #[tracing::instrument("List api keys", skip(self, claims))]
+    pub async fn list_keys(&self, claims: &SessionClaims) -> Result<Vec<ApiKeyResponse>, AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let keys = uow
+            .api_keys()
+            .fetch_all()
+            .await
+            .to_app_err("Failed to fetch api keys")?;
+
+        Ok(keys.into_iter().map(ApiKeyResponse::from).collect())
+    }
+
+    #[tracing::instrument("Rotate api key", skip(self, claims, request))]
+    pub async fn rotate_key(
+        &self,
+        claims: &SessionClaims,
+        key_id: Uuid,
+        request: ApiKeyRotateRequest,
+    ) -> Result<ApiKeyIssuedResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let current = match uow
+            .api_keys()
+            .fetch_by_id(key_id)
+            .await
+            .to_app_err("Failed to fetch api key")?
+        {
+            Some(k) => k,
+            None => Err(DomainError::NotFound("api key".into()))?,
+        };
+
+        if current.revoked_at.is_some() {
+            Err(DomainError::InvalidState("api key has been revoked".into()))?
+        }
+
+        let raw_key = RawApiKey::generate();
+        let rotated = current.rotate(
+            &raw_key,
+            *claims.get_user_id(),
+            Self::expires_at(request.expires_in_days),
+        );
+
+        uow.api_keys()
+            .create(&rotated)
+            .await
+            .to_app_err("Failed to create rotated api key")?;
+
+        uow.api_keys()
+            .expire_at(
+                current.id,
+                Utc::now() + Duration::hours(request.grace_period_hours.into()),
+            )
+            .await
+            .to_app_err("Failed to expire rotated api key")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit api key rotation")?;
+
+        Ok(ApiKeyIssuedResponse {
+            id: rotated.id,
+            name: rotated.name,
+            key: raw_key.as_ref().to_string(),
+            scopes: rotated.scopes,
+            expires_at: rotated.expires_at,
+        })
+    }
+
+    #[tracing::instrument("Revoke api key", skip(self, claims))]
+    pub async fn revoke_key(&self, claims: &SessionClaims, key_id: Uuid) -> Result<(), AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let n_revoked = uow
+            .api_keys()
+            .revoke(key_id)
+            .await
+            .to_app_err("Failed to revoke api key")?;
+
+        if n_revoked == 0 {
+            Err(DomainError::NotFound("active api key".into()))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit api key revocation")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Authenticate api key", skip(self, presented_key))]
+    pub async fn authenticate(
+        &self,
+        presented_key: &str,
+        peer_ip: Option<IpAddr>,
+    ) -> Result<ApiKeyPrincipal, AppError> {
+        let raw_key = RawApiKey::parse(presented_key)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let api_key = match uow
+            .api_keys()
+            .fetch_by_prefix(raw_key.prefix())
+            .await
+            .to_app_err("Failed to fetch api key")?
+        {
+            Some(k) => k,
+            None => Err(AuthError::Unauthorized)?,
+        };
+
+        let principal = api_key.authorize(&raw_key, peer_ip, Utc::now())?;
+
+        uow.api_keys()
+            .touch_last_used(api_key.id)
+            .await
+            .to_app_err("Failed to record api key usage")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit api key usage")?;
+
+        Ok(principal)
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Context;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{get_pgconnect_pool, runtime::Config};
+use crate::infra::pgdb::UnitofWork;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn run_cleanup_until_stopped(config: Arc<Config>) -> Result<(), anyhow::Error> {
+    let pool = get_pgconnect_pool(&config.database);
+
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // A failed run is retried on the next tick
+        if let Err(e) = purge_expired_activate_tokens(&pool).await {
+            tracing::error!(error.cause_chain = ?e, error.message = %e, "Failed to purge expired activate tokens");
+        }
+    }
+}
+
+#[tracing::instrument("Purge expired activate tokens", skip(pool))]
+pub async fn purge_expired_activate_tokens(pool: &PgPool) -> Result<u64, anyhow::Error> {
+    let mut uow = UnitofWork::from(pool)
+        .await
+        .context("Failed to start postgres uow")?;
+
+    let n_deleted = uow
+        .authentication()
+        .delete_expired_tokens()
+        .await
+        .context("Failed to delete expired activate tokens")?;
+
+    uow.commit()
+        .await
+        .context("Failed to commit activate token cleanup")?;
+
+    Ok(n_deleted)
+}
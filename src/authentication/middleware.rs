@@ -1,6 +1,5 @@
 use actix_web::HttpMessage;
 use actix_web::{
-    FromRequest,
     body::{EitherBody, MessageBody},
     dev::{ServiceRequest, ServiceResponse},
     http::header,
@@ -9,47 +8,77 @@ use actix_web::{
     web,
 };
 
-use crate::authentication::{CustomerSession, SessionType, StaffSession};
+use crate::api_key::service::ApiKeyService;
+use crate::authentication::{
+    CustomerSession, SessionType, StaffSession, service::AuthService, token::SessionClaims,
+};
 use crate::base::error::AuthError;
 use crate::config::state::AppState;
 use crate::user::models::AccessRole;
 
 const DEFAULT_WWW: HeaderValue = HeaderValue::from_static("Basic realm=\"thalia\"");
 
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// Claims from the cookie session or else the bearer token, as long as their session is still live
+async fn live_session_claims<T: SessionType>(
+    app_state: &AppState,
+    req: &mut ServiceRequest,
+    missing: &str,
+) -> Result<SessionClaims, actix_web::Error>
+where
+    T::Error: Into<actix_web::Error>,
+{
+    let session = {
+        let (http_req, payload) = req.parts_mut();
+        T::from_request(http_req, payload)
+            .await
+            .map_err(Into::into)?
+    };
+
+    let claims = match (
+        session.get_sesh_user()?,
+        app_state.token_handler.verify_from_service_req(req),
+    ) {
+        (Some(metadata), _) | (None, Ok(metadata)) => metadata,
+        (None, Err(_)) => Err(AuthError::InvalidCredentials(missing.into()))?,
+    };
+
+    if let Err(e) = AuthService::from(app_state).check_session(&claims).await {
+        // The cookie goes too so the browser stops presenting an ended session
+        session.log_out();
+        Err(e)?
+    }
+
+    Ok(claims)
+}
+
 #[tracing::instrument(name = "Customer Authorization Check" skip(req, next, app_state))]
 pub async fn reject_unauthorized_customer(
     app_state: web::Data<AppState>,
     mut req: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
-    let session = {
-        let (http_req, payload) = req.parts_mut();
-        CustomerSession::from_request(http_req, payload).await?
-    };
+    let metadata = live_session_claims::<CustomerSession>(
+        &app_state,
+        &mut req,
+        "No active session found or missing credentials",
+    )
+    .await?;
 
-    match (
-        session.get_sesh_user()?,
-        app_state.token_handler.verify_from_service_req(&req),
-    ) {
-        (Some(metadata), Ok(_)) | (Some(metadata), Err(_)) | (None, Ok(metadata)) => {
-            // Handle case if not customer
-            match metadata.get_role() {
-                AccessRole::Customer => {
-                    req.extensions_mut().insert(metadata);
-                    let mut res = next.call(req).await?;
-
-                    // Add default header
-                    let headers = res.headers_mut();
-                    headers.insert(header::WWW_AUTHENTICATE, DEFAULT_WWW);
-
-                    return Ok(res.map_body(|_, body| EitherBody::left(body)));
-                }
-                _ => Err(AuthError::InsufficientPermissions)?,
-            }
+    // Handle case if not customer
+    match metadata.get_role() {
+        AccessRole::Customer => {
+            req.extensions_mut().insert(metadata);
+            let mut res = next.call(req).await?;
+
+            // Add default header
+            let headers = res.headers_mut();
+            headers.insert(header::WWW_AUTHENTICATE, DEFAULT_WWW);
+
+            Ok(res.map_body(|_, body| EitherBody::left(body)))
         }
-        (None, Err(_)) => Err(AuthError::InvalidCredentials(
-            "No active session found or missing credentials".into(),
-        ))?,
+        _ => Err(AuthError::InsufficientPermissions)?,
     }
 }
 
@@ -59,33 +88,76 @@ pub async fn reject_unauthorized_staff(
     mut req: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
-    let session = {
-        let (http_req, payload) = req.parts_mut();
-        StaffSession::from_request(http_req, payload).await?
-    };
+    let metadata = live_session_claims::<StaffSession>(
+        &app_state,
+        &mut req,
+        "Missing active session or credentials",
+    )
+    .await?;
 
-    match (
-        session.get_sesh_user()?,
-        app_state.token_handler.verify_from_service_req(&req),
-    ) {
-        (Some(metadata), Ok(_)) | (Some(metadata), Err(_)) | (None, Ok(metadata)) => {
-            // Handle case if not superuser/manager
-            match metadata.get_role() {
-                AccessRole::Manager | AccessRole::Superuser => {
-                    req.extensions_mut().insert(metadata);
-                    let mut res = next.call(req).await?;
-
-                    // Add default header
-                    let headers = res.headers_mut();
-                    headers.insert(header::WWW_AUTHENTICATE, DEFAULT_WWW);
-
-                    return Ok(res.map_body(|_, body| EitherBody::left(body)));
-                }
-                _ => Err(AuthError::InsufficientPermissions)?,
-            }
+    // Handle case if not superuser/manager
+    match metadata.get_role() {
+        AccessRole::Manager | AccessRole::Superuser => {
+            req.extensions_mut().insert(metadata);
+            let mut res = next.call(req).await?;
+
+            // Add default header
+            let headers = res.headers_mut();
+            headers.insert(header::WWW_AUTHENTICATE, DEFAULT_WWW);
+
+            Ok(res.map_body(|_, body| EitherBody::left(body)))
         }
-        (None, Err(_)) => Err(AuthError::InvalidCredentials(
-            "Missing active session or credentials".into(),
-        ))?,
+        _ => Err(AuthError::InsufficientPermissions)?,
     }
 }
+
+// Any signed in user regardless of role, for endpoints about the user's own account
+#[tracing::instrument(name = "User Authorization Check" skip(req, next, app_state))]
+pub async fn reject_unauthenticated(
+    app_state: web::Data<AppState>,
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let metadata = live_session_claims::<CustomerSession>(
+        &app_state,
+        &mut req,
+        "Missing active session or credentials",
+    )
+    .await?;
+
+    req.extensions_mut().insert(metadata);
+    let mut res = next.call(req).await?;
+
+    let headers = res.headers_mut();
+    headers.insert(header::WWW_AUTHENTICATE, DEFAULT_WWW);
+
+    Ok(res.map_body(|_, body| EitherBody::left(body)))
+}
+
+#[tracing::instrument(name = "Api Key Authorization Check" skip(req, next, app_state))]
+pub async fn reject_unauthorized_api_key(
+    app_state: web::Data<AppState>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let presented_key = match req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(key) => key.to_string(),
+        None => Err(AuthError::MissingAuth("api key".into()))?,
+    };
+
+    // Forwarded headers can be spoofed so the allow-list is checked against the socket peer
+    let peer_ip = req.peer_addr().map(|addr| addr.ip());
+
+    let principal = ApiKeyService::from(&app_state)
+        .authenticate(&presented_key, peer_ip)
+        .await?;
+
+    req.extensions_mut().insert(principal);
+    let res = next.call(req).await?;
+
+    Ok(res.map_body(|_, body| EitherBody::left(body)))
+}
@@ -1,8 +1,12 @@
 pub mod session_state;
 pub use session_state::*;
+pub mod cleanup;
 pub mod credential;
 pub mod middleware;
+pub mod models;
+pub mod oidc;
 pub mod repo;
+pub mod routes;
 pub mod schemas;
 pub mod service;
 pub mod token;
@@ -0,0 +1,76 @@
+use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header};
+use chrono::{DateTime, Utc};
+use std::future::{Ready, ready};
+use uuid::Uuid;
+
+pub const DEVICE_HEADER: &str = "x-device-name";
+
+// How often a session's last activity is written, requests in between are not recorded
+pub const SESSION_TOUCH_SECS: i64 = 60;
+
+// Where a login came from, shown back to the user when they review their sessions
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl FromRequest for ClientInfo {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let header_value = |name| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.chars().take(255).collect::<String>())
+        };
+
+        // Only displayed, never trusted, so the forwarded address is good enough
+        let ip_address = req
+            .connection_info()
+            .realip_remote_addr()
+            .map(|ip| ip.to_string());
+
+        ready(Ok(Self {
+            device: header_value(DEVICE_HEADER),
+            ip_address,
+            user_agent: header_value(header::USER_AGENT.as_str()),
+        }))
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct UserSessionEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UserSessionEntity {
+    pub fn new(user_id: Uuid, client: &ClientInfo, ttl_secs: u64) -> Self {
+        let now = Utc::now();
+
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            device: client.device.clone(),
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + chrono::Duration::seconds(ttl_secs as i64),
+        }
+    }
+}
+
+pub fn session_touch_due(last_seen_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now - last_seen_at >= chrono::Duration::seconds(SESSION_TOUCH_SECS)
+}
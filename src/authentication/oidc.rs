@@ -0,0 +1,256 @@
+use anyhow::Context;
+use openid::{Client, CustomClaims, Discovered, Options, Pkce, PkceSha256, StandardClaims, Token};
+use rand::distr::{Alphanumeric, SampleString};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::base::error::{AppError, AuthError, ValidationError};
+use crate::user::models::{AccessRole, UserEntity};
+
+pub const OIDC_LOGIN_KEY: &str = "oidc_login";
+
+// Not a valid PHC string so password login always fails for sso-only staff
+pub const SSO_PASSWORD_MARKER: &str = "!sso";
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct StaffIdClaims {
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(flatten)]
+    pub standard_claims: StandardClaims,
+}
+
+impl CustomClaims for StaffIdClaims {
+    fn standard_claims(&self) -> &StandardClaims {
+        &self.standard_claims
+    }
+}
+
+impl openid::CompactJson for StaffIdClaims {}
+
+impl StaffIdClaims {
+    pub fn issuer(&self) -> &str {
+        self.standard_claims.iss.as_str()
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.standard_claims.sub
+    }
+
+    pub fn email(&self) -> Result<&str, ValidationError> {
+        self.standard_claims
+            .userinfo
+            .email
+            .as_deref()
+            .ok_or(ValidationError::MissingField("email".into()))
+    }
+
+    // Matching an unlinked login to a user by address is only safe when the IdP has checked
+    // the address belongs to whoever signed in
+    pub fn verified_email(&self) -> Result<&str, AppError> {
+        if !self.standard_claims.userinfo.email_verified {
+            Err(AuthError::InvalidCredentials(
+                "email not verified by the IdP".into(),
+            ))?
+        }
+
+        Ok(self.email()?)
+    }
+
+    // Just in time provisioning of a staff member on their first sso login
+    pub fn to_user_entity(&self, access_role: AccessRole) -> Result<UserEntity, ValidationError> {
+        let userinfo = &self.standard_claims.userinfo;
+        let email = self.email()?.to_string();
+
+        Ok(UserEntity {
+            id: Uuid::now_v7(),
+            first_name: userinfo.given_name.clone().unwrap_or_default(),
+            last_name: userinfo.family_name.clone().unwrap_or_default(),
+            username: userinfo.preferred_username.clone().unwrap_or(email.clone()),
+            password: SSO_PASSWORD_MARKER.into(),
+            email,
+            date_of_birth: None,
+            // The IdP has already vouched for the identity
+            is_confirmed: true,
+            is_active: true,
+            is_verified: false,
+            access_role,
+        })
+    }
+}
+
+pub type StaffOidcClient = Client<Discovered, StaffIdClaims>;
+
+// Kept in the staff session between the redirect to the IdP and the callback
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+#[derive(Debug)]
+pub struct OidcProvider {
+    issuer_url: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    superuser_groups: Vec<String>,
+    manager_groups: Vec<String>,
+    // Discovery happens on the first login and is reused afterwards
+    client: OnceLock<StaffOidcClient>,
+}
+
+fn split_groups(groups: &str) -> Vec<String> {
+    groups
+        .split(',')
+        .map(|g| g.trim().to_string())
+        .filter(|g| !g.is_empty())
+        .collect()
+}
+
+impl OidcProvider {
+    pub fn new(
+        issuer_url: String,
+        client_id: String,
+        client_secret: Option<String>,
+        redirect_uri: String,
+        superuser_groups: &str,
+        manager_groups: &str,
+    ) -> Self {
+        Self {
+            issuer_url,
+            client_id,
+            client_secret,
+            redirect_uri,
+            superuser_groups: split_groups(superuser_groups),
+            manager_groups: split_groups(manager_groups),
+            client: OnceLock::new(),
+        }
+    }
+
+    async fn client(&self) -> Result<&StaffOidcClient, anyhow::Error> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+
+        let issuer = reqwest::Url::parse(&self.issuer_url).context("Invalid oidc issuer url")?;
+
+        let client = StaffOidcClient::discover(
+            self.client_id.clone(),
+            self.client_secret.clone(),
+            Some(self.redirect_uri.clone()),
+            issuer,
+        )
+        .await
+        .context("Failed to discover oidc provider")?;
+
+        Ok(self.client.get_or_init(|| client))
+    }
+
+    pub async fn authorization_url(&self) -> Result<(String, PendingLogin), anyhow::Error> {
+        let pkce = PkceSha256::generate();
+
+        let pending = PendingLogin {
+            state: Alphanumeric.sample_string(&mut rand::rng(), 32),
+            nonce: Alphanumeric.sample_string(&mut rand::rng(), 32),
+            code_verifier: pkce.code_verifier.clone(),
+        };
+
+        // The client carries a single pkce pair so a per login copy is used
+        let mut client = self.client().await?.clone();
+        client.pkce = Some(Pkce::S256(pkce));
+
+        let url = client.auth_url(&Options {
+            scope: Some("openid email profile".into()),
+            state: Some(pending.state.clone()),
+            nonce: Some(pending.nonce.clone()),
+            ..Default::default()
+        });
+
+        Ok((url.to_string(), pending))
+    }
+
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pending: &PendingLogin,
+    ) -> Result<StaffIdClaims, anyhow::Error> {
+        let client = self.client().await?;
+
+        let bearer = client
+            .request_token_pkce(code, Some(&pending.code_verifier))
+            .await
+            .context("Failed to exchange oidc authorization code")?;
+
+        let mut token: Token<StaffIdClaims> = bearer.into();
+
+        let mut id_token = token
+            .id_token
+            .take()
+            .ok_or(anyhow::anyhow!(AuthError::MissingAuth("id_token".into())))?;
+
+        client
+            .decode_token(&mut id_token)
+            .context("Failed to decode oidc id token")?;
+
+        client
+            .validate_token(&id_token, pending.nonce.as_str(), None::<&chrono::Duration>)
+            .map_err(|e| anyhow::anyhow!(AuthError::Unauthorized).context(e))?;
+
+        let claims = id_token
+            .payload()
+            .context("Failed to read oidc id token claims")?;
+
+        Ok(StaffIdClaims {
+            groups: claims.groups.clone(),
+            standard_claims: claims.standard_claims.clone(),
+        })
+    }
+
+    // Superuser wins when a member of both groups
+    pub fn map_role(&self, groups: &[String]) -> Option<AccessRole> {
+        if groups.iter().any(|g| self.superuser_groups.contains(g)) {
+            Some(AccessRole::Superuser)
+        } else if groups.iter().any(|g| self.manager_groups.contains(g)) {
+            Some(AccessRole::Manager)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OidcProvider;
+    use crate::user::models::AccessRole;
+
+    fn provider() -> OidcProvider {
+        OidcProvider::new(
+            "https://idp.thalia.test".into(),
+            "thalia".into(),
+            None,
+            "https://thalia.test/staff/sso/callback".into(),
+            "t24-admins",
+            "t24-managers, branch-managers",
+        )
+    }
+
+    #[test]
+    fn superuser_group_takes_precedence() {
+        let groups = vec!["branch-managers".to_string(), "t24-admins".to_string()];
+        assert_eq!(provider().map_role(&groups), Some(AccessRole::Superuser));
+    }
+
+    #[test]
+    fn manager_groups_are_trimmed() {
+        let groups = vec!["branch-managers".to_string()];
+        assert_eq!(provider().map_role(&groups), Some(AccessRole::Manager));
+    }
+
+    #[test]
+    fn unmapped_groups_get_no_role() {
+        let groups = vec!["everyone".to_string()];
+        assert_eq!(provider().map_role(&groups), None);
+    }
+}
@@ -1,8 +1,13 @@
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+use crate::authentication::models::{SESSION_TOUCH_SECS, UserSessionEntity};
 use crate::user::models::UserEntity;
 
+const SESSION_COLUMNS: &str =
+    "id, user_id, device, ip_address, user_agent, created_at, last_seen_at, expires_at";
+
 pub struct AuthRepository<'a, 'b> {
     pool: &'a PgPool,
     tx: &'b mut Transaction<'a, Postgres>,
@@ -19,17 +24,56 @@ impl<'a, 'b> AuthRepository<'a, 'b> {
         token: &str,
         user_id: Uuid,
         user_email: &str,
+        ttl_secs: u64,
     ) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO activate_token(token, user_id, user_email) VALUES($1, $2, $3)")
-            .bind(token)
+        sqlx::query(
+            "INSERT INTO activate_token(token, user_id, user_email, expires_at)
+                VALUES($1, $2, $3, CURRENT_TIMESTAMP + $4 * INTERVAL '1 second')",
+        )
+        .bind(token)
+        .bind(user_id)
+        .bind(user_email)
+        .bind(ttl_secs as i64)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Returns the owner only if the token was still outstanding, so a token can be used once
+    #[tracing::instrument("Consuming activate token", skip(self, token))]
+    pub async fn consume_token(&mut self, token: &str) -> Result<Option<Uuid>, sqlx::Error> {
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "DELETE FROM activate_token WHERE token=$1 AND expires_at > CURRENT_TIMESTAMP RETURNING user_id",
+        )
+        .bind(token)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    #[tracing::instrument("Removing activate tokens for a user", skip(self))]
+    pub async fn delete_user_tokens(&mut self, user_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM activate_token WHERE user_id=$1")
             .bind(user_id)
-            .bind(user_email)
             .execute(&mut **self.tx)
             .await?;
 
         Ok(())
     }
 
+    #[tracing::instrument("Removing expired activate tokens", skip(self))]
+    pub async fn delete_expired_tokens(&mut self) -> Result<u64, sqlx::Error> {
+        let n_deleted =
+            sqlx::query("DELETE FROM activate_token WHERE expires_at <= CURRENT_TIMESTAMP")
+                .execute(&mut **self.tx)
+                .await?
+                .rows_affected();
+
+        Ok(n_deleted)
+    }
+
     #[tracing::instrument("Retrieving password for a username", skip(self))]
     pub async fn fetch_password_by_username(
         &self,
@@ -58,4 +102,162 @@ impl<'a, 'b> AuthRepository<'a, 'b> {
 
         Ok(result)
     }
+
+    #[tracing::instrument("Retrieving user by id", skip(self))]
+    pub async fn fetch_user_by_id(&self, user_id: Uuid) -> Result<Option<UserEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, UserEntity>("SELECT id, first_name, last_name, username, password, email, date_of_birth, is_confirmed, is_active, is_verified, access_role FROM tuser WHERE id=$1")
+            .bind(user_id)
+            .fetch_optional(self.pool)
+            .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving user for an external identity", skip(self))]
+    pub async fn fetch_user_by_external_identity(
+        &self,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<UserEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, UserEntity>(
+            "SELECT u.id, u.first_name, u.last_name, u.username, u.password, u.email, u.date_of_birth, u.is_confirmed, u.is_active, u.is_verified, u.access_role
+                FROM tuser u JOIN external_identity e ON e.user_id = u.id
+                WHERE e.issuer=$1 AND e.subject=$2",
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Linking external identity to a user", skip(self))]
+    pub async fn link_external_identity(
+        &mut self,
+        issuer: &str,
+        subject: &str,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO external_identity(issuer, subject, user_id) VALUES($1, $2, $3)
+                ON CONFLICT (issuer, subject) DO UPDATE SET last_login_at = CURRENT_TIMESTAMP",
+        )
+        .bind(issuer)
+        .bind(subject)
+        .bind(user_id)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving user session in the database", skip(self, user_session))]
+    pub async fn create_session(
+        &mut self,
+        user_session: &UserSessionEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO user_session(id, user_id, device, ip_address, user_agent, created_at, last_seen_at, expires_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(user_session.id)
+        .bind(user_session.user_id)
+        .bind(&user_session.device)
+        .bind(&user_session.ip_address)
+        .bind(&user_session.user_agent)
+        .bind(user_session.created_at)
+        .bind(user_session.last_seen_at)
+        .bind(user_session.expires_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving active sessions for a user", skip(self))]
+    pub async fn fetch_active_sessions(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<UserSessionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, UserSessionEntity>(&format!(
+            "SELECT {} FROM user_session
+                WHERE user_id=$1 AND ended_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+                ORDER BY last_seen_at DESC",
+            SESSION_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Ending a user session", skip(self))]
+    pub async fn end_session(
+        &mut self,
+        id: Uuid,
+        user_id: Uuid,
+        ended_by: Option<Uuid>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_ended = sqlx::query(
+            "UPDATE user_session SET ended_at = CURRENT_TIMESTAMP, ended_by = $3
+                WHERE id=$1 AND user_id=$2 AND ended_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(ended_by)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_ended)
+    }
+
+    #[tracing::instrument("Ending every session of a user", skip(self))]
+    pub async fn end_user_sessions(
+        &mut self,
+        user_id: Uuid,
+        ended_by: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let ended = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE user_session SET ended_at = CURRENT_TIMESTAMP, ended_by = $2
+                WHERE user_id=$1 AND ended_at IS NULL RETURNING id",
+        )
+        .bind(user_id)
+        .bind(ended_by)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(ended)
+    }
+
+    // Read outside any transaction, most requests come in before a touch is due
+    #[tracing::instrument("Retrieving session activity", skip(pool))]
+    pub async fn fetch_session_last_seen(
+        pool: &PgPool,
+        id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let result = sqlx::query_scalar("SELECT last_seen_at FROM user_session WHERE id=$1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(result)
+    }
+
+    // Guarded again here so concurrent requests write the touch once
+    #[tracing::instrument("Recording session activity", skip(self))]
+    pub async fn touch_session(&mut self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE user_session SET last_seen_at = CURRENT_TIMESTAMP
+                WHERE id=$1 AND last_seen_at < CURRENT_TIMESTAMP - make_interval(secs => $2)",
+        )
+        .bind(id)
+        .bind(SESSION_TOUCH_SECS as f64)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
 }
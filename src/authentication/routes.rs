@@ -0,0 +1,95 @@
+use actix_web::{HttpResponse, http::header, web};
+use uuid::Uuid;
+
+use crate::authentication::{
+    CustomerSession, SessionType,
+    schemas::{ResendActivationRequest, SessionResponse, SessionsEndedResponse},
+    service::AuthService,
+    token::SessionClaims,
+};
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+
+// Public keys for downstream services verifying Thalia issued tokens
+#[tracing::instrument("Fetch jwks", skip(app_state))]
+#[utoipa::path(get, path="/.well-known/jwks.json", responses((status=200, description="JSON Web Key Set")))]
+pub async fn jwks(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+        .json(app_state.key_store.jwks()))
+}
+
+#[tracing::instrument("Resend activation email", skip(app_state, payload))]
+#[utoipa::path(post, path="/auth/activation/resend", request_body=ResendActivationRequest, responses((status=200, body=StdResponse, description="Activation email sent if the account is awaiting confirmation"), (status=429, description="Too many resend requests")))]
+pub async fn resend_activation(
+    app_state: web::Data<AppState>,
+    payload: web::Json<ResendActivationRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    auth_service.resend_activation(payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from(
+        "If the account is awaiting confirmation a new activation email has been sent",
+    )))
+}
+
+#[tracing::instrument("List sessions", skip(app_state, claims))]
+#[utoipa::path(get, path="/auth/sessions", responses((status=200, body=Vec<SessionResponse>, description="Active sessions of the signed in user"), (status=401, description="Not signed in")))]
+pub async fn list_sessions(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    let response = auth_service.list_sessions(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("End session", skip(app_state, claims))]
+#[utoipa::path(delete, path="/auth/sessions/{session_id}", responses((status=200, body=StdResponse, description="Session ended"), (status=404, description="No such active session")))]
+pub async fn end_session(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    session_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    auth_service
+        .end_session(&claims, session_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Session ended")))
+}
+
+#[tracing::instrument("Log out everywhere", skip(app_state, claims, session))]
+#[utoipa::path(delete, path="/auth/sessions", responses((status=200, body=SessionsEndedResponse, description="Every session of the signed in user ended")))]
+pub async fn end_all_sessions(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    session: CustomerSession,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    let ended = auth_service.end_all_sessions(&claims).await?;
+    session.log_out();
+
+    Ok(HttpResponse::Ok().json(SessionsEndedResponse { ended }))
+}
+
+#[tracing::instrument("Terminate customer sessions", skip(app_state, claims))]
+#[utoipa::path(delete, path="/staff/customers/{customer_id}/sessions", responses((status=200, body=SessionsEndedResponse, description="Every session of the customer ended"), (status=403, description="Only customer sessions can be terminated"), (status=404, description="Customer not found")))]
+pub async fn end_customer_sessions(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    customer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    let ended = auth_service
+        .end_customer_sessions(&claims, customer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(SessionsEndedResponse { ended }))
+}
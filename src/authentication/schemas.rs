@@ -1,3 +1,8 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authentication::models::UserSessionEntity;
+
 #[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LoginIdentifier {
@@ -10,3 +15,45 @@ pub struct LoginRequest {
     pub login_id: LoginIdentifier,
     pub password: String,
 }
+
+#[derive(serde::Deserialize, utoipa::IntoParams, Debug)]
+pub struct SsoCallback {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema, Debug)]
+pub struct ResendActivationRequest {
+    pub email: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    // The session the request was made from
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn from(value: UserSessionEntity, current_sid: Uuid) -> Self {
+        Self {
+            current: value.id == current_sid,
+            id: value.id,
+            device: value.device,
+            ip_address: value.ip_address,
+            user_agent: value.user_agent,
+            created_at: value.created_at,
+            last_seen_at: value.last_seen_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SessionsEndedResponse {
+    pub ended: usize,
+}
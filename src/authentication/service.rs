@@ -1,17 +1,32 @@
 use actix_web_flash_messages::FlashMessage;
+use anyhow::Context;
+use chrono::Utc;
+use uuid::Uuid;
 
 use crate::authentication::{
-    SessionType,
+    SessionType, StaffSession,
     {
         credential::Credentials,
-        schemas::{LoginIdentifier, LoginRequest},
+        models::{ClientInfo, session_touch_due},
+        oidc::{OIDC_LOGIN_KEY, OidcProvider, PendingLogin},
+        repo::AuthRepository,
+        schemas::{
+            LoginIdentifier, LoginRequest, ResendActivationRequest, SessionResponse, SsoCallback,
+        },
         session_handler,
+        token::SessionClaims,
     },
 };
-use crate::base::error::{AppError, AuthError, SqlErrorExt};
+use crate::base::Email;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
 use crate::config::state::AppState;
 use crate::infra::pgdb::UnitofWork;
-use crate::user::models::UpdateUserEntity;
+use crate::user::models::{AccessRole, UpdateUserEntity};
+use crate::user::service::UserService;
+
+// At most three activation emails per address every fifteen minutes
+const RESEND_LIMIT: u64 = 3;
+const RESEND_WINDOW_SECS: u64 = 900;
 
 pub struct AuthService<'a> {
     app_state: &'a AppState,
@@ -22,24 +37,57 @@ impl<'a> AuthService<'a> {
         Self { app_state }
     }
 
+    #[tracing::instrument("Verify user email", skip(self, token))]
     pub async fn verify_user_email(&self, token: String) -> Result<(), AppError> {
-        let mut uow = UnitofWork::from(&self.app_state.pgpool)
-            .await
-            .to_app_err("Failed to start postgres uow")?;
-
         let activate_claims = self
             .app_state
             .activate_handler
             .verify_activate_token(&token)?;
 
-        let update_user_entity =
-            UpdateUserEntity::activate_email_update(activate_claims.get_user_id());
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        // Gone once used, superseded by a resend or purged after expiry
+        let user_id = match uow
+            .authentication()
+            .consume_token(&token)
+            .await
+            .to_app_err("Failed to consume activate token")?
+        {
+            Some(id) if id == activate_claims.get_user_id() => id,
+            Some(_) => Err(AuthError::Unauthorized)?,
+            None => Err(AuthError::Expired("activation token".into()))?,
+        };
+
+        let user = match uow
+            .authentication()
+            .fetch_user_by_id(user_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) => u,
+            None => Err(DomainError::NotFound("user".into()))?,
+        };
+
+        if user.is_confirmed {
+            Err(DomainError::InvalidState(
+                "user is already confirmed".into(),
+            ))?
+        }
+
+        let update_user_entity = UpdateUserEntity::activate_email_update(user_id);
 
         uow.users()
             .update(&update_user_entity)
             .await
             .to_app_err("Failed to update user")?;
 
+        uow.authentication()
+            .delete_user_tokens(user_id)
+            .await
+            .to_app_err("Failed to remove activate tokens")?;
+
         uow.commit()
             .await
             .to_app_err("Failed to commit user email verificaion")?;
@@ -47,11 +95,81 @@ impl<'a> AuthService<'a> {
         Ok(())
     }
 
-    #[tracing::instrument("Customer login", skip(self, login_req, session))]
+    #[tracing::instrument("Resend activation", skip(self, request))]
+    pub async fn resend_activation(
+        &self,
+        request: ResendActivationRequest,
+    ) -> Result<(), AppError> {
+        let email = Email::parse(request.email.trim().to_lowercase())?;
+
+        let attempts = self
+            .app_state
+            .redis_pool
+            .incr_with_ttl(
+                &format!("activation_resend:{}", email.as_ref()),
+                RESEND_WINDOW_SECS,
+            )
+            .await?;
+
+        if attempts > RESEND_LIMIT {
+            Err(DomainError::RateLimited("activation resend".into()))?
+        }
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        // Unknown and confirmed addresses get the same answer so accounts can't be enumerated
+        let user = match uow
+            .authentication()
+            .fetch_password_by_email(email.as_ref())
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) if !u.is_confirmed => u,
+            _ => return Ok(()),
+        };
+
+        let activate_token = self.app_state.activate_handler.generate_activate_token(
+            user.id,
+            email,
+            user.access_role.clone(),
+        )?;
+
+        // Only the latest token stays valid
+        uow.authentication()
+            .delete_user_tokens(user.id)
+            .await
+            .to_app_err("Failed to remove activate tokens")?;
+
+        uow.authentication()
+            .store_token(
+                &activate_token,
+                user.id,
+                &user.email,
+                self.app_state.activate_handler.activate_ttl(),
+            )
+            .await
+            .to_app_err("Failed to store activate token")?;
+
+        // The link is only sent once its token is saved
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit activation resend")?;
+
+        UserService::from(self.app_state)
+            .send_activation_email(&user, &activate_token, "Confirm your Thalia Corp. account")
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Customer login", skip(self, login_req, session, client))]
     pub async fn authenticate_user<T: SessionType>(
         &self,
         login_req: LoginRequest,
         session: T,
+        client: ClientInfo,
     ) -> Result<(String, String), AppError> {
         let mut uow = UnitofWork::from(&self.app_state.pgpool)
             .await
@@ -87,9 +205,9 @@ impl<'a> AuthService<'a> {
         };
 
         let (pair, _) = session_handler(
-            &self.app_state.token_handler,
-            &self.app_state.redis_pool,
+            self.app_state,
             &session,
+            &client,
             creds.id,
             creds.email,
             creds.role,
@@ -98,4 +216,285 @@ impl<'a> AuthService<'a> {
 
         Ok((pair.access_token, pair.refresh_token))
     }
+
+    fn oidc_provider(&self) -> Result<&OidcProvider, AppError> {
+        match &self.app_state.oidc {
+            Some(provider) => Ok(provider),
+            None => Err(DomainError::NotFound("Staff single sign-on".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Staff sso redirect", skip(self, session))]
+    pub async fn begin_staff_sso(&self, session: &StaffSession) -> Result<String, AppError> {
+        let (url, pending) = self.oidc_provider()?.authorization_url().await?;
+
+        session
+            .value()
+            .insert(OIDC_LOGIN_KEY, &pending)
+            .context("Failed to store pending sso login")?;
+
+        Ok(url)
+    }
+
+    #[tracing::instrument("Staff sso callback", skip(self, callback, session, client))]
+    pub async fn complete_staff_sso(
+        &self,
+        callback: SsoCallback,
+        session: StaffSession,
+        client: ClientInfo,
+    ) -> Result<(String, String), AppError> {
+        let provider = self.oidc_provider()?;
+
+        let pending = session
+            .value()
+            .get::<PendingLogin>(OIDC_LOGIN_KEY)
+            .context("Failed to read pending sso login")?
+            .ok_or(AuthError::MissingAuth("sso login".into()))?;
+
+        // A pending login is only ever good for one callback
+        session.value().remove(OIDC_LOGIN_KEY);
+
+        if pending.state != callback.state {
+            Err(AuthError::Unauthorized)?
+        }
+
+        let claims = provider.exchange_code(&callback.code, &pending).await?;
+
+        let role = provider
+            .map_role(&claims.groups)
+            .ok_or(AuthError::InsufficientPermissions)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let linked_user = uow
+            .authentication()
+            .fetch_user_by_external_identity(claims.issuer(), claims.subject())
+            .await
+            .to_app_err("Failed to fetch user by external identity")?;
+
+        let user = match linked_user {
+            Some(user) => user,
+            None => match uow
+                .authentication()
+                .fetch_password_by_email(claims.verified_email()?)
+                .await
+                .to_app_err("Failed to fetch user entity")?
+            {
+                Some(user) => user,
+                None => {
+                    let user = claims.to_user_entity(role.clone())?;
+
+                    uow.users()
+                        .create(&user)
+                        .await
+                        .to_app_err("Failed to provision sso staff")?;
+
+                    user
+                }
+            },
+        };
+
+        // Customers can never be promoted to staff through the IdP
+        if user.access_role == AccessRole::Customer {
+            Err(AuthError::InsufficientPermissions)?
+        }
+
+        if !user.is_active {
+            Err(AuthError::Unauthorized)?
+        }
+
+        uow.authentication()
+            .link_external_identity(claims.issuer(), claims.subject(), user.id)
+            .await
+            .to_app_err("Failed to link external identity")?;
+
+        // The IdP is the source of truth for staff roles
+        if user.access_role != role {
+            uow.users()
+                .update(&UpdateUserEntity::access_role_update(user.id, role.clone()))
+                .await
+                .to_app_err("Failed to update staff role")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit sso login")?;
+
+        let (pair, _) = session_handler(
+            self.app_state,
+            &session,
+            &client,
+            user.id,
+            Email::parse(user.email)?,
+            role,
+        )
+        .await?;
+
+        Ok((pair.access_token, pair.refresh_token))
+    }
+
+    // Called on every authenticated request, a session ended elsewhere stops working straight away
+    #[tracing::instrument("Check session", skip(self, claims))]
+    pub async fn check_session(&self, claims: &SessionClaims) -> Result<(), AppError> {
+        let is_live = self
+            .app_state
+            .token_handler
+            .is_session_live(&self.app_state.redis_pool, *claims.get_sid())
+            .await?;
+
+        if !is_live {
+            Err(AuthError::Expired("session".into()))?
+        }
+
+        let last_seen_at =
+            AuthRepository::fetch_session_last_seen(&self.app_state.pgpool, *claims.get_sid())
+                .await
+                .to_app_err("Failed to fetch session activity")?;
+
+        // Only write when the recorded activity is stale, most requests stop at the read
+        match last_seen_at {
+            Some(last_seen_at) if session_touch_due(last_seen_at, Utc::now()) => {}
+            _ => return Ok(()),
+        }
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        uow.authentication()
+            .touch_session(*claims.get_sid())
+            .await
+            .to_app_err("Failed to record session activity")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit session activity")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("List sessions", skip(self, claims))]
+    pub async fn list_sessions(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<Vec<SessionResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let sessions = uow
+            .authentication()
+            .fetch_active_sessions(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch user sessions")?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|s| SessionResponse::from(s, *claims.get_sid()))
+            .collect())
+    }
+
+    #[tracing::instrument("End session", skip(self, claims))]
+    pub async fn end_session(
+        &self,
+        claims: &SessionClaims,
+        session_id: Uuid,
+    ) -> Result<(), AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        // Scoped to the caller so nobody can end another user's session by id
+        let n_ended = uow
+            .authentication()
+            .end_session(session_id, *claims.get_user_id(), None)
+            .await
+            .to_app_err("Failed to end user session")?;
+
+        if n_ended == 0 {
+            Err(DomainError::NotFound("active session".into()))?
+        }
+
+        self.app_state
+            .token_handler
+            .end_session(&self.app_state.redis_pool, session_id)
+            .await?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit ended session")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("End all sessions", skip(self, claims))]
+    pub async fn end_all_sessions(&self, claims: &SessionClaims) -> Result<usize, AppError> {
+        self.end_user_sessions(*claims.get_user_id(), None).await
+    }
+
+    // Fraud response, a customer is signed out of every device at once
+    #[tracing::instrument("End customer sessions", skip(self, claims))]
+    pub async fn end_customer_sessions(
+        &self,
+        claims: &SessionClaims,
+        customer_id: Uuid,
+    ) -> Result<usize, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let customer = match uow
+            .authentication()
+            .fetch_user_by_id(customer_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) => u,
+            None => Err(DomainError::NotFound("customer".into()))?,
+        };
+
+        if customer.access_role != AccessRole::Customer {
+            Err(AuthError::InsufficientPermissions)?
+        }
+
+        let n_ended = self
+            .end_user_sessions(customer_id, Some(*claims.get_user_id()))
+            .await?;
+
+        tracing::info!(staff_id = %claims.get_user_id(), %customer_id, n_ended, "Customer sessions terminated");
+
+        Ok(n_ended)
+    }
+
+    async fn end_user_sessions(
+        &self,
+        user_id: Uuid,
+        ended_by: Option<Uuid>,
+    ) -> Result<usize, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let ended = uow
+            .authentication()
+            .end_user_sessions(user_id, ended_by)
+            .await
+            .to_app_err("Failed to end user sessions")?;
+
+        // Redis goes first, a failed commit leaves sessions listed but never usable
+        for session_id in &ended {
+            self.app_state
+                .token_handler
+                .end_session(&self.app_state.redis_pool, *session_id)
+                .await?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit ended sessions")?;
+
+        Ok(ended.len())
+    }
 }
@@ -5,9 +5,11 @@ use anyhow::Context;
 use std::future::{Ready, ready};
 use uuid::Uuid;
 
-use crate::authentication::token::{SessionClaims, TokenHandler, TokenPair};
+use crate::authentication::models::{ClientInfo, UserSessionEntity};
+use crate::authentication::token::{SessionClaims, TokenPair};
 use crate::base::Email;
-use crate::infra::redis::RedisPool;
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
 use crate::user::models::AccessRole;
 
 pub struct StaffSession(Session);
@@ -101,15 +103,31 @@ impl FromRequest for StaffSession {
 }
 
 pub async fn session_handler<T: SessionType>(
-    token_handler: &TokenHandler,
-    redis_pool: &RedisPool,
+    app_state: &AppState,
     session: &T,
+    client: &ClientInfo,
     id: Uuid,
     email: Email,
     role: AccessRole,
 ) -> Result<(TokenPair, SessionClaims), anyhow::Error> {
+    let token_handler = &app_state.token_handler;
+    let user_session = UserSessionEntity::new(id, client, token_handler.refresh_ttl());
+
+    let mut uow = UnitofWork::from(&app_state.pgpool)
+        .await
+        .context("Failed to start postgres uow")?;
+
+    uow.authentication()
+        .create_session(&user_session)
+        .await
+        .context("Failed to register user session")?;
+
+    uow.commit()
+        .await
+        .context("Failed to commit user session")?;
+
     let (pair, session_claims) = token_handler
-        .generate_tokens(redis_pool, id, email, role)
+        .generate_tokens(&app_state.redis_pool, user_session.id, id, email, role)
         .await?;
 
     session.renew();
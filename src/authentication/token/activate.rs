@@ -1,12 +1,13 @@
 use anyhow::Context;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::authentication::token::{KeyPurpose, KeyStore};
 use crate::base::{Email, error::AuthError};
-use crate::config::state::SecretKey;
-use crate::infra::redis::RedisPool;
-use crate::user::{models::AccessRole, schemas::User};
+use crate::user::models::AccessRole;
+
+pub const ACTIVATE_TOKEN_USE: &str = "activate";
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, getset::CloneGetters)]
 #[get_clone = "pub with_prefix"]
@@ -38,41 +39,43 @@ impl ActivateClaims {
 
 #[derive(Debug, Clone)]
 pub struct ActivateHandler {
-    secret: String,
+    keys: Arc<KeyStore>,
     activate_ttl: usize,
 }
 
 impl ActivateHandler {
-    pub fn new(secret: SecretKey, activate_ttl: u64) -> Self {
+    pub fn new(keys: Arc<KeyStore>, activate_ttl: u64) -> Self {
         Self {
-            secret: secret.0,
+            keys,
             activate_ttl: activate_ttl as usize,
         }
     }
 
-    pub async fn generate_activate_token(
+    pub fn activate_ttl(&self) -> u64 {
+        self.activate_ttl as u64
+    }
+
+    // The token is one-time: it is stored in `activate_token` and removed on confirmation
+    pub fn generate_activate_token(
         &self,
-        user: &User,
-        pool: &RedisPool,
+        user_id: Uuid,
+        email: Email,
+        role: AccessRole,
     ) -> Result<String, anyhow::Error> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .context("Failed to get now as epoch")?
             .as_secs() as usize;
 
-        let claims = user.to_activate_claims(now + self.activate_ttl);
-
-        let activate_token = encode(
-            &Header::new(Algorithm::HS256),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_ref()),
-        )
-        .context("Failed to encode activate token")?;
-
-        pool.set_token(&activate_token.clone(), user.id, self.activate_ttl as u64)
-            .await?;
+        let claims = ActivateClaims::from(
+            email,
+            user_id,
+            now + self.activate_ttl,
+            role,
+            ACTIVATE_TOKEN_USE.into(),
+        );
 
-        Ok(activate_token)
+        self.keys.encode(KeyPurpose::Activate, &claims)
     }
 
     pub fn verify_activate_token(&self, token: &str) -> Result<ActivateClaims, anyhow::Error> {
@@ -80,17 +83,12 @@ impl ActivateHandler {
             .duration_since(UNIX_EPOCH)
             .context("Failed to get now as epoch")?
             .as_secs() as usize;
-        let token_data = decode::<ActivateClaims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_ref()),
-            &Validation::new(Algorithm::HS256),
-        )
-        .context("Failed to decode activate token")?;
+        let claims: ActivateClaims = self.keys.decode(KeyPurpose::Activate, token)?;
 
-        if token_data.claims.token_use != "activate" || token_data.claims.exp < now {
+        if claims.token_use != ACTIVATE_TOKEN_USE || claims.exp < now {
             return Err(anyhow::anyhow!(AuthError::Unauthorized));
         }
 
-        Ok(token_data.claims)
+        Ok(claims)
     }
 }
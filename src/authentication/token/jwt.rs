@@ -1,17 +1,16 @@
 use actix_web::dev::ServiceRequest;
 use anyhow::Context;
 use derive_more::{Constructor, Deref};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 use crate::{
-    authentication::token::TokenExtractor,
+    authentication::token::{KeyPurpose, KeyStore, TokenExtractor},
     base::{
         Email,
         error::{AppError, AuthError},
     },
-    config::state::SecretKey,
     infra::redis::RedisPool,
     user::models::AccessRole,
 };
@@ -41,12 +40,17 @@ pub struct SessionClaims {
     role: AccessRole,
     jti: Uuid,
     token_use: String,
+    // Claims stored before sessions were tracked carry no id and are never live
+    #[serde(default)]
+    sid: Uuid,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RefreshToken {
     pub user_id: Uuid,
     pub sub: Uuid,
+    #[serde(default)]
+    pub sid: Uuid,
     pub email: Email,
     pub iat: usize,
     pub jti: Uuid,
@@ -59,6 +63,11 @@ pub struct RefreshToken {
     pub token_use: String,
 }
 
+// Holds the jti of the latest refresh token, the session is live for as long as the key exists
+pub fn session_key(sid: Uuid) -> String {
+    format!("session:{}", sid)
+}
+
 pub struct TokenPair {
     pub access_token: String,
     pub refresh_token: String,
@@ -66,23 +75,28 @@ pub struct TokenPair {
 
 #[derive(Debug, Clone)]
 pub struct TokenHandler {
-    secret: String,
+    keys: Arc<KeyStore>,
     access_ttl: usize,
     refresh_ttl: usize,
 }
 
 impl TokenHandler {
-    pub fn new(secret: SecretKey, access_ttl: u64, refresh_ttl: u64) -> Self {
+    pub fn new(keys: Arc<KeyStore>, access_ttl: u64, refresh_ttl: u64) -> Self {
         Self {
-            secret: secret.0,
+            keys,
             access_ttl: access_ttl as usize,
             refresh_ttl: refresh_ttl as usize,
         }
     }
 
+    pub fn refresh_ttl(&self) -> u64 {
+        self.refresh_ttl as u64
+    }
+
     pub async fn generate_tokens(
         &self,
         pool: &RedisPool,
+        sid: Uuid,
         user_id: Uuid,
         email: Email,
         role: AccessRole,
@@ -104,18 +118,15 @@ impl TokenHandler {
             role: role.clone(),
             jti,
             token_use: "access".into(),
+            sid,
         };
 
-        let access_token = encode(
-            &Header::new(Algorithm::HS256),
-            &access_claims,
-            &EncodingKey::from_secret(self.secret.as_ref()),
-        )
-        .context("Falied to encode access claims")?;
+        let access_token = self.keys.encode(KeyPurpose::Access, &access_claims)?;
 
         let refresh_claims = RefreshToken {
             sub: user_id,
             user_id,
+            sid,
             email,
             role,
             iat: now,
@@ -128,14 +139,9 @@ impl TokenHandler {
             token_use: "refresh".into(),
         };
 
-        let refresh_token = encode(
-            &Header::new(Algorithm::HS256),
-            &refresh_claims,
-            &EncodingKey::from_secret(self.secret.as_ref()),
-        )
-        .context("Falied to encode refresh claims")?;
+        let refresh_token = self.keys.encode(KeyPurpose::Refresh, &refresh_claims)?;
 
-        pool.set_token(&refresh_token.clone(), jti, self.refresh_ttl as u64)
+        pool.set_token(&session_key(sid), jti, self.refresh_ttl as u64)
             .await?;
 
         Ok((
@@ -153,18 +159,13 @@ impl TokenHandler {
             .context("Failed to convert seconds expirty to unix time")?
             .as_secs() as usize;
 
-        let token_data = decode::<SessionClaims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_ref()),
-            &Validation::new(Algorithm::HS256),
-        )
-        .context("Failed to decode access token")?;
+        let claims: SessionClaims = self.keys.decode(KeyPurpose::Access, token)?;
 
-        if token_data.claims.token_use != "access" || token_data.claims.exp <= now {
+        if claims.token_use != "access" || claims.exp <= now {
             return Err(anyhow::anyhow!(AuthError::Unauthorized));
         }
 
-        Ok(token_data.claims)
+        Ok(claims)
     }
 
     pub fn verify_from_service_req(
@@ -177,7 +178,7 @@ impl TokenHandler {
 
     pub async fn refresh_tokens(
         &self,
-        pool: RedisPool,
+        pool: &RedisPool,
         refresh_token: &str,
     ) -> Result<(TokenPair, SessionClaims), anyhow::Error> {
         let now = SystemTime::now()
@@ -185,41 +186,36 @@ impl TokenHandler {
             .context("Failed to get now as epoch")?
             .as_secs() as usize;
 
-        let token_data = decode::<RefreshToken>(
-            refresh_token,
-            &DecodingKey::from_secret(self.secret.as_ref()),
-            &Validation::new(Algorithm::HS256),
-        )
-        .context("Failed to decode refresh token")?;
+        let claims: RefreshToken = self.keys.decode(KeyPurpose::Refresh, refresh_token)?;
 
-        if token_data.claims.token_use != "refresh" || token_data.claims.exp < now {
+        if claims.token_use != "refresh" || claims.exp < now {
             return Err(anyhow::anyhow!(AppError::Auth(AuthError::Unauthorized)));
         }
 
-        match pool.get_token(refresh_token).await? {
-            Some(_) => {
-                pool.remove_token(refresh_token).await?;
+        // Only the latest refresh token of a live session can be exchanged
+        match pool.get_token(&session_key(claims.sid)).await? {
+            Some(jti) if jti == claims.jti.to_string() => {
                 let tokens = self
-                    .generate_tokens(
-                        &pool,
-                        token_data.claims.user_id,
-                        token_data.claims.email,
-                        token_data.claims.role,
-                    )
+                    .generate_tokens(pool, claims.sid, claims.user_id, claims.email, claims.role)
                     .await?;
 
                 Ok(tokens)
             }
-            None => Err(anyhow::anyhow!(AppError::Auth(AuthError::Unauthorized))),
+            _ => Err(anyhow::anyhow!(AppError::Auth(AuthError::Unauthorized))),
         }
     }
 
-    pub async fn revoke_refresh_token(
+    pub async fn is_session_live(
         &self,
-        pool: RedisPool,
-        refresh_token: &str,
-    ) -> Result<(), anyhow::Error> {
-        pool.remove_token(refresh_token).await?;
+        pool: &RedisPool,
+        sid: Uuid,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(pool.get_token(&session_key(sid)).await?.is_some())
+    }
+
+    // Ends the session behind every access and refresh token issued for it
+    pub async fn end_session(&self, pool: &RedisPool, sid: Uuid) -> Result<(), anyhow::Error> {
+        pool.remove_token(&session_key(sid)).await?;
 
         Ok(())
     }
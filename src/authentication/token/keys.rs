@@ -0,0 +1,347 @@
+use anyhow::Context;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header,
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+use serde::{Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::base::error::AuthError;
+
+// Each token purpose is signed and verified with its own keys so that
+// an activation token can never be replayed as an access token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum KeyPurpose {
+    Access,
+    Refresh,
+    Activate,
+}
+
+pub struct SigningKey {
+    kid: String,
+    key: EncodingKey,
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("kid", &self.kid)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct VerifyingKey {
+    key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl std::fmt::Debug for VerifyingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VerifyingKey")
+            .field("jwk", &self.jwk)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub struct KeyRing {
+    signing: SigningKey,
+    // All keys still accepted for verification, keyed by kid
+    verifying: HashMap<String, VerifyingKey>,
+}
+
+#[derive(Debug)]
+pub struct KeyStore {
+    algorithm: Algorithm,
+    access: KeyRing,
+    refresh: KeyRing,
+    activate: KeyRing,
+}
+
+fn parse_algorithm(algorithm: &str) -> Result<Algorithm, anyhow::Error> {
+    let algorithm = Algorithm::from_str(algorithm).context("Unknown jwt signing algorithm")?;
+
+    match algorithm {
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512
+        | Algorithm::EdDSA => Ok(algorithm),
+        other => Err(anyhow::anyhow!(
+            "{:?} is not supported. Use an RSA or EdDSA algorithm",
+            other
+        )),
+    }
+}
+
+fn to_jwk(kid: &str, algorithm: Algorithm, public_pem: &str) -> Result<Jwk, anyhow::Error> {
+    let key_algorithm = KeyAlgorithm::from_str(&format!("{:?}", algorithm))
+        .context("Failed to map jwt algorithm to jwk algorithm")?;
+
+    let algorithm = match algorithm {
+        Algorithm::EdDSA => {
+            let der = pem::parse(public_pem).context("Failed to parse ed25519 public key")?;
+            // SubjectPublicKeyInfo for ed25519 ends with the 32 byte raw key
+            let raw = der
+                .contents()
+                .get(der.contents().len().saturating_sub(32)..)
+                .context("Invalid ed25519 public key length")?;
+
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(raw),
+            })
+        }
+        _ => {
+            let public_key = RsaPublicKey::from_public_key_pem(public_pem)
+                .context("Failed to parse rsa public key")?;
+
+            AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+            })
+        }
+    };
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm,
+    })
+}
+
+impl KeyRing {
+    // Keys live under `{keys_dir}/{purpose}/` as `{kid}.pem` (private) and `{kid}.pub.pem` (public).
+    // Every public key in the folder stays valid for verification which allows rotation:
+    // add a new pair, point the signing kid at it and remove the old public key once
+    // the tokens it signed have expired.
+    pub fn load(
+        keys_dir: &Path,
+        purpose: KeyPurpose,
+        signing_kid: &str,
+        algorithm: Algorithm,
+    ) -> Result<Self, anyhow::Error> {
+        let dir = keys_dir.join(purpose.to_string());
+
+        let private_pem = std::fs::read(dir.join(format!("{}.pem", signing_kid)))
+            .context(format!("Failed to read {} signing key", purpose))?;
+
+        let key = match algorithm {
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(&private_pem),
+            _ => EncodingKey::from_rsa_pem(&private_pem),
+        }
+        .context(format!("Failed to parse {} signing key", purpose))?;
+
+        let mut verifying = HashMap::new();
+
+        for entry in std::fs::read_dir(&dir).context(format!("Failed to read {} keys", purpose))? {
+            let path = entry?.path();
+
+            let kid = match path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .and_then(|f| f.strip_suffix(".pub.pem"))
+            {
+                Some(kid) => kid.to_string(),
+                None => continue,
+            };
+
+            let public_pem = std::fs::read_to_string(&path)
+                .context(format!("Failed to read {} verification key", purpose))?;
+
+            let key = match algorithm {
+                Algorithm::EdDSA => DecodingKey::from_ed_pem(public_pem.as_bytes()),
+                _ => DecodingKey::from_rsa_pem(public_pem.as_bytes()),
+            }
+            .context(format!(
+                "Failed to parse {} verification key {}",
+                purpose, kid
+            ))?;
+
+            let jwk = to_jwk(&kid, algorithm, &public_pem)?;
+
+            verifying.insert(kid, VerifyingKey { key, jwk });
+        }
+
+        if !verifying.contains_key(signing_kid) {
+            return Err(anyhow::anyhow!(
+                "Missing public key for {} signing kid {}",
+                purpose,
+                signing_kid
+            ));
+        }
+
+        Ok(Self {
+            signing: SigningKey {
+                kid: signing_kid.to_string(),
+                key,
+            },
+            verifying,
+        })
+    }
+}
+
+impl KeyStore {
+    pub fn load(
+        keys_dir: &str,
+        algorithm: &str,
+        access_kid: &str,
+        refresh_kid: &str,
+        activate_kid: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let keys_dir = Path::new(keys_dir);
+        let algorithm = parse_algorithm(algorithm)?;
+
+        Ok(Self {
+            algorithm,
+            access: KeyRing::load(keys_dir, KeyPurpose::Access, access_kid, algorithm)?,
+            refresh: KeyRing::load(keys_dir, KeyPurpose::Refresh, refresh_kid, algorithm)?,
+            activate: KeyRing::load(keys_dir, KeyPurpose::Activate, activate_kid, algorithm)?,
+        })
+    }
+
+    fn ring(&self, purpose: KeyPurpose) -> &KeyRing {
+        match purpose {
+            KeyPurpose::Access => &self.access,
+            KeyPurpose::Refresh => &self.refresh,
+            KeyPurpose::Activate => &self.activate,
+        }
+    }
+
+    pub fn encode<T: Serialize>(
+        &self,
+        purpose: KeyPurpose,
+        claims: &T,
+    ) -> Result<String, anyhow::Error> {
+        let ring = self.ring(purpose);
+
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(ring.signing.kid.clone());
+
+        jsonwebtoken::encode(&header, claims, &ring.signing.key)
+            .context(format!("Failed to encode {} claims", purpose))
+    }
+
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        purpose: KeyPurpose,
+        token: &str,
+    ) -> Result<T, anyhow::Error> {
+        let header = decode_header(token).context("Failed to decode token header")?;
+
+        // Reject anything not signed with our configured algorithm (e.g. `none` or HS256)
+        if header.alg != self.algorithm {
+            return Err(anyhow::anyhow!(AuthError::InvalidTokenScheme));
+        }
+
+        let verifying_key = header
+            .kid
+            .as_ref()
+            .and_then(|kid| self.ring(purpose).verifying.get(kid))
+            .ok_or(anyhow::anyhow!(AuthError::Unauthorized))?;
+
+        let token_data = decode::<T>(token, &verifying_key.key, &Validation::new(self.algorithm))
+            .context(format!("Failed to decode {} token", purpose))?;
+
+        Ok(token_data.claims)
+    }
+
+    // Only access and activation keys are published, refresh tokens never leave Thalia
+    pub fn jwks(&self) -> JwkSet {
+        let keys = [KeyPurpose::Access, KeyPurpose::Activate]
+            .into_iter()
+            .flat_map(|purpose| self.ring(purpose).verifying.values())
+            .map(|k| k.jwk.clone())
+            .collect();
+
+        JwkSet { keys }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyPurpose, KeyStore};
+    use claims::{assert_err, assert_ok};
+
+    const KEYS_DIR: &str = "tests/fixtures/jwt";
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: usize,
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "thalia".into(),
+            exp: jsonwebtoken::get_current_timestamp() as usize + 60,
+        }
+    }
+
+    fn store() -> KeyStore {
+        KeyStore::load(KEYS_DIR, "EdDSA", "access-k1", "refresh-k1", "activate-k1").unwrap()
+    }
+
+    #[test]
+    fn token_signed_for_a_purpose_is_verified_for_that_purpose() {
+        let store = store();
+        let token = store.encode(KeyPurpose::Access, &claims()).unwrap();
+
+        assert_ok!(store.decode::<Claims>(KeyPurpose::Access, &token));
+    }
+
+    #[test]
+    fn token_signed_for_another_purpose_is_rejected() {
+        let store = store();
+        let token = store.encode(KeyPurpose::Activate, &claims()).unwrap();
+
+        let _ = assert_err!(store.decode::<Claims>(KeyPurpose::Access, &token));
+    }
+
+    #[test]
+    fn retired_signing_key_is_still_accepted_for_verification() {
+        let old = store();
+        let token = old.encode(KeyPurpose::Access, &claims()).unwrap();
+
+        let rotated =
+            KeyStore::load(KEYS_DIR, "EdDSA", "access-k2", "refresh-k1", "activate-k1").unwrap();
+
+        assert_ok!(rotated.decode::<Claims>(KeyPurpose::Access, &token));
+    }
+
+    #[test]
+    fn hmac_signed_token_is_rejected() {
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims(),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let _ = assert_err!(store().decode::<Claims>(KeyPurpose::Access, &token));
+    }
+
+    #[test]
+    fn jwks_publishes_access_and_activate_keys_only() {
+        let jwks = store().jwks();
+
+        // access-k1, access-k2 and activate-k1
+        assert_eq!(jwks.keys.len(), 3);
+    }
+}
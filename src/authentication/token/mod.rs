@@ -1,7 +1,9 @@
 mod activate;
 mod extractor;
 mod jwt;
+mod keys;
 
 pub use activate::*;
 pub use extractor::*;
 pub use jwt::*;
+pub use keys::*;
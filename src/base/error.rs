@@ -129,6 +129,9 @@ pub enum DomainError {
 
     #[error("Invalid state: {0}")]
     InvalidState(String),
+
+    #[error("Too many requests: {0}")]
+    RateLimited(String),
 }
 
 impl actix_web::ResponseError for DomainError {
@@ -140,6 +143,7 @@ impl actix_web::ResponseError for DomainError {
                 actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
             }
             DomainError::InvalidState(_) => actix_web::http::StatusCode::CONFLICT,
+            DomainError::RateLimited(_) => actix_web::http::StatusCode::TOO_MANY_REQUESTS,
         }
     }
 
@@ -226,6 +230,11 @@ impl<T> SqlErrorExt<T> for std::result::Result<T, sqlx::Error> {
                     AppError::Domain(DomainError::Duplicate(context))
                 }
 
+                // Exclusion constraint, a row overlapping one already saved
+                _ if v.code().as_deref() == Some("23P01") => {
+                    AppError::Domain(DomainError::Duplicate(context))
+                }
+
                 _ => AppError::Internal(anyhow::anyhow!(v).context(context)),
             },
 
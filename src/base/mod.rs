@@ -10,3 +10,5 @@ pub use email::Email;
 pub mod ids;
 mod password;
 pub use password::Password;
+#[cfg(test)]
+pub mod xsd;
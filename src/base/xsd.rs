@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Validates a generated ISO 20022 message against the official schema checked
+/// in under `tests/fixtures/iso20022`, using `xmllint` from libxml2.
+pub fn assert_schema_valid(schema: &str, xml: &[u8]) {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/iso20022");
+    let schema = fixtures.join(schema);
+    assert!(
+        schema.is_file(),
+        "{} is missing, see tests/fixtures/iso20022/README.md",
+        schema.display()
+    );
+
+    let document = std::env::temp_dir().join(format!("{}.xml", uuid::Uuid::now_v7()));
+    std::fs::write(&document, xml).unwrap();
+    let output = Command::new("xmllint")
+        .arg("--noout")
+        .arg("--schema")
+        .arg(&schema)
+        .arg(&document)
+        .output()
+        .unwrap_or_else(|e| {
+            panic!(
+                "xmllint from libxml2 is needed to check {}: {e}",
+                schema.display()
+            )
+        });
+    std::fs::remove_file(&document).unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
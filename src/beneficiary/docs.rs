@@ -0,0 +1,12 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::beneficiary::routes::request_beneficiary_challenge,
+    crate::beneficiary::routes::create_beneficiary,
+    crate::beneficiary::routes::list_beneficiaries,
+    crate::beneficiary::routes::fetch_beneficiary,
+    crate::beneficiary::routes::update_beneficiary,
+    crate::beneficiary::routes::delete_beneficiary,
+))]
+pub struct BeneficiaryApi;
@@ -0,0 +1,575 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::base::error::{AuthError, DomainError, ValidationError};
+use crate::beneficiary::schemas::BeneficiaryRequest;
+
+// Longest name a SEPA credit transfer carries for the creditor
+const MAX_NAME_LEN: usize = 70;
+const MAX_ACCOUNT_NUMBER_LEN: usize = 34;
+
+const CHALLENGE_TTL_MINS: i64 = 10;
+// Wrong codes allowed before the challenge has to be requested again
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+// IBAN length by issuing country, from the SWIFT IBAN registry
+const IBAN_LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24),
+    ("AE", 23),
+    ("AL", 28),
+    ("AT", 20),
+    ("AZ", 28),
+    ("BA", 20),
+    ("BE", 16),
+    ("BG", 22),
+    ("BH", 22),
+    ("BR", 29),
+    ("CH", 21),
+    ("CR", 22),
+    ("CY", 28),
+    ("CZ", 24),
+    ("DE", 22),
+    ("DK", 18),
+    ("DO", 28),
+    ("EE", 20),
+    ("EG", 29),
+    ("ES", 24),
+    ("FI", 18),
+    ("FO", 18),
+    ("FR", 27),
+    ("GB", 22),
+    ("GE", 22),
+    ("GI", 23),
+    ("GL", 18),
+    ("GR", 27),
+    ("GT", 28),
+    ("HR", 21),
+    ("HU", 28),
+    ("IE", 22),
+    ("IL", 23),
+    ("IS", 26),
+    ("IT", 27),
+    ("JO", 30),
+    ("KW", 30),
+    ("KZ", 20),
+    ("LB", 28),
+    ("LI", 21),
+    ("LT", 20),
+    ("LU", 20),
+    ("LV", 21),
+    ("MC", 27),
+    ("MD", 24),
+    ("ME", 22),
+    ("MK", 19),
+    ("MR", 27),
+    ("MT", 31),
+    ("MU", 30),
+    ("NL", 18),
+    ("NO", 15),
+    ("PK", 24),
+    ("PL", 28),
+    ("PS", 29),
+    ("PT", 25),
+    ("QA", 29),
+    ("RO", 24),
+    ("RS", 22),
+    ("SA", 24),
+    ("SE", 24),
+    ("SI", 19),
+    ("SK", 24),
+    ("SM", 27),
+    ("TN", 24),
+    ("TR", 26),
+    ("UA", 29),
+    ("VG", 24),
+    ("XK", 20),
+];
+
+fn invalid(field: &str, reason: &str) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+// Spaces are dropped and letters upper cased, the check digits must give a remainder of 1
+// when the IBAN is read as a number modulo 97
+pub fn parse_iban(iban: &str) -> Result<String, ValidationError> {
+    let iban: String = iban
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+
+    if !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ValidationError::InvalidFormat("iban".into()));
+    }
+
+    let country = iban.get(..2).unwrap_or_default();
+    let length = match IBAN_LENGTHS.iter().find(|(c, _)| *c == country) {
+        Some((_, length)) => *length,
+        None => return Err(invalid("iban", "Country does not issue IBANs")),
+    };
+
+    if iban.len() != length {
+        return Err(invalid(
+            "iban",
+            &format!("{} IBANs are {} characters long", country, length),
+        ));
+    }
+
+    if !iban[2..4].chars().all(|c| c.is_ascii_digit()) {
+        return Err(ValidationError::InvalidFormat("iban".into()));
+    }
+
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |acc, c| {
+            // Letters count as two digits, A being 10
+            let value = c.to_digit(36).unwrap_or_default();
+            if value > 9 {
+                (acc * 100 + value) % 97
+            } else {
+                (acc * 10 + value) % 97
+            }
+        });
+
+    if remainder != 1 {
+        return Err(invalid("iban", "Check digits do not match"));
+    }
+
+    Ok(iban)
+}
+
+pub fn parse_bic(bic: &str, bank_country: &str) -> Result<String, ValidationError> {
+    let bic = bic.trim().to_uppercase();
+
+    let well_formed = bic.is_ascii()
+        && matches!(bic.len(), 8 | 11)
+        && bic[..6].chars().all(|c| c.is_ascii_alphabetic())
+        && bic[6..].chars().all(|c| c.is_ascii_alphanumeric());
+    if !well_formed {
+        return Err(ValidationError::InvalidFormat("bic".into()));
+    }
+
+    if &bic[4..6] != bank_country {
+        return Err(invalid("bic", "Bank is not in the beneficiary's country"));
+    }
+
+    Ok(bic)
+}
+
+// ABA routing numbers are nine digits whose weighted sum, by 3, 7 and 1 in turn, is a
+// multiple of ten
+pub fn parse_routing_number(value: &str) -> Result<String, ValidationError> {
+    let number: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+
+    if number.len() != 9 || !number.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ValidationError::InvalidFormat("routing_number".into()));
+    }
+
+    let checksum: u32 = number
+        .chars()
+        .zip([3, 7, 1].iter().cycle())
+        .map(|(c, weight)| c.to_digit(10).unwrap_or_default() * weight)
+        .sum();
+    if !checksum.is_multiple_of(10) {
+        return Err(invalid("routing_number", "Check digit does not match"));
+    }
+
+    Ok(number)
+}
+
+fn parse_code(value: &str, field: &str, len: usize) -> Result<String, ValidationError> {
+    let value = value.trim().to_uppercase();
+
+    if value.len() != len || !value.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ValidationError::InvalidFormat(field.into()));
+    }
+
+    Ok(value)
+}
+
+#[derive(Debug, Clone)]
+pub struct BeneficiaryPolicy {
+    cooling_off: Duration,
+    large_amount_cents: i64,
+}
+
+impl BeneficiaryPolicy {
+    pub fn new(cooling_off_hours: i64, large_amount_cents: i64) -> Self {
+        Self {
+            cooling_off: Duration::hours(cooling_off_hours.max(0)),
+            large_amount_cents,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct BeneficiaryEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub iban: Option<String>,
+    pub account_number: Option<String>,
+    pub bic: Option<String>,
+    pub routing_number: Option<String>,
+    pub bank_country: String,
+    pub currency: String,
+    pub cooling_off_until: DateTime<Utc>,
+    // Set when a payment to the beneficiary came back for a reason that rules out trying again
+    pub flagged_reason: Option<String>,
+    pub flagged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BeneficiaryEntity {
+    pub fn new(
+        user_id: Uuid,
+        request: &BeneficiaryRequest,
+        policy: &BeneficiaryPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ValidationError> {
+        let name = parse_name(&request.name)?;
+        let currency = parse_code(&request.currency, "currency", 3)?;
+        let bank_country = match &request.bank_country {
+            Some(c) => Some(parse_code(c, "bank_country", 2)?),
+            None => None,
+        };
+
+        let (iban, account_number, bank_country) = match (&request.iban, &request.account_number) {
+            (Some(iban), None) => {
+                let iban = parse_iban(iban)?;
+                let country = iban[..2].to_string();
+                if bank_country.as_ref().is_some_and(|c| *c != country) {
+                    return Err(invalid("bank_country", "Does not match the IBAN"));
+                }
+                (Some(iban), None, country)
+            }
+            (None, Some(number)) => {
+                let number: String = number
+                    .chars()
+                    .filter(|c| !c.is_whitespace() && *c != '-')
+                    .collect();
+                if number.is_empty() || !number.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(ValidationError::InvalidFormat("account_number".into()));
+                }
+                if number.len() > MAX_ACCOUNT_NUMBER_LEN {
+                    return Err(ValidationError::TooLong {
+                        field: "account_number".into(),
+                        max: MAX_ACCOUNT_NUMBER_LEN,
+                    });
+                }
+                if request.bic.is_none() && request.routing_number.is_none() {
+                    return Err(ValidationError::MissingField("bic".into()));
+                }
+                match bank_country {
+                    Some(c) => (None, Some(number), c),
+                    None => return Err(ValidationError::MissingField("bank_country".into())),
+                }
+            }
+            _ => {
+                return Err(invalid(
+                    "iban",
+                    "Give either an IBAN or an account number, not both",
+                ));
+            }
+        };
+
+        let bic = match &request.bic {
+            Some(bic) => Some(parse_bic(bic, &bank_country)?),
+            None => None,
+        };
+
+        let routing_number = match &request.routing_number {
+            Some(_) if iban.is_some() => {
+                return Err(invalid("routing_number", "Not used with an IBAN"));
+            }
+            Some(_) if bank_country != "US" => {
+                return Err(invalid(
+                    "routing_number",
+                    "Only US banks have routing numbers",
+                ));
+            }
+            Some(number) => Some(parse_routing_number(number)?),
+            None => None,
+        };
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            user_id,
+            name,
+            iban,
+            account_number,
+            bic,
+            routing_number,
+            bank_country,
+            currency,
+            cooling_off_until: now + policy.cooling_off,
+            flagged_reason: None,
+            flagged_at: None,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn check_not_flagged(&self) -> Result<(), DomainError> {
+        match &self.flagged_reason {
+            Some(reason) => Err(DomainError::ConstraintViolation(format!(
+                "beneficiary can't be paid, a payment came back with: {}",
+                reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    // Amounts above the large amount threshold wait out the cooling-off period
+    pub fn check_amount(
+        &self,
+        amount_cents: i64,
+        policy: &BeneficiaryPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        if amount_cents > policy.large_amount_cents && now < self.cooling_off_until {
+            return Err(DomainError::ConstraintViolation(format!(
+                "new beneficiaries can't be sent more than {} cents before {}",
+                policy.large_amount_cents,
+                self.cooling_off_until.to_rfc3339()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn parse_name(name: &str) -> Result<String, ValidationError> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if name.is_empty() {
+        return Err(ValidationError::MissingField("name".into()));
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(ValidationError::TooLong {
+            field: "name".into(),
+            max: MAX_NAME_LEN,
+        });
+    }
+
+    Ok(name)
+}
+
+// Step-up check before a beneficiary is added, the code goes to the customer's email
+#[derive(Debug, sqlx::FromRow)]
+pub struct ChallengeEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code_hash: String,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ChallengeEntity {
+    // The code is only ever returned here, a hash salted with the challenge id is kept
+    pub fn new(user_id: Uuid, now: DateTime<Utc>) -> (Self, String) {
+        let id = Uuid::now_v7();
+        let code = format!("{:06}", rand::rng().random_range(0..1_000_000));
+
+        let challenge = Self {
+            id,
+            user_id,
+            code_hash: Self::hash(id, &code),
+            attempts: 0,
+            expires_at: now + Duration::minutes(CHALLENGE_TTL_MINS),
+            consumed_at: None,
+            created_at: now,
+        };
+
+        (challenge, code)
+    }
+
+    fn hash(id: Uuid, code: &str) -> String {
+        hex::encode(Sha256::digest(format!("{}:{}", id, code.trim()).as_bytes()))
+    }
+
+    pub fn check_usable(&self, now: DateTime<Utc>) -> Result<(), AuthError> {
+        if self.consumed_at.is_some()
+            || self.expires_at <= now
+            || self.attempts >= MAX_CHALLENGE_ATTEMPTS
+        {
+            return Err(AuthError::Expired("verification code".into()));
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, code: &str) -> bool {
+        Self::hash(self.id, code) == self.code_hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        BeneficiaryEntity, BeneficiaryPolicy, ChallengeEntity, parse_bic, parse_iban,
+        parse_routing_number,
+    };
+    use crate::beneficiary::schemas::BeneficiaryRequest;
+
+    fn request(iban: Option<&str>, account_number: Option<&str>) -> BeneficiaryRequest {
+        BeneficiaryRequest {
+            name: "  Jane   Doe ".into(),
+            iban: iban.map(Into::into),
+            account_number: account_number.map(Into::into),
+            bic: None,
+            routing_number: None,
+            bank_country: None,
+            currency: "eur".into(),
+            challenge_id: Uuid::now_v7(),
+            code: "000000".into(),
+        }
+    }
+
+    #[test]
+    fn ibans_are_normalized_and_checked() {
+        assert_eq!(
+            parse_iban("de89 3704 0044 0532 0130 00").unwrap(),
+            "DE89370400440532013000"
+        );
+        assert_ok!(parse_iban("GB82 WEST 1234 5698 7654 32"));
+
+        let _ = assert_err!(parse_iban("DE88 3704 0044 0532 0130 00"));
+        let _ = assert_err!(parse_iban("DE89 3704 0044 0532 0130"));
+        let _ = assert_err!(parse_iban("US89 3704 0044 0532 0130 00"));
+    }
+
+    #[test]
+    fn bics_must_be_in_the_bank_country() {
+        assert_eq!(parse_bic("cobadeffxxx", "DE").unwrap(), "COBADEFFXXX");
+        assert_ok!(parse_bic("DEUTDEFF", "DE"));
+
+        let _ = assert_err!(parse_bic("COBADEFFXXX", "FR"));
+        let _ = assert_err!(parse_bic("COBADEFFXX", "DE"));
+    }
+
+    #[test]
+    fn bics_with_non_ascii_characters_are_refused() {
+        let _ = assert_err!(parse_bic("AAAAAÄB", "AA"));
+        let _ = assert_err!(parse_bic("COBADEFÜXX", "DE"));
+        let _ = assert_err!(parse_bic("ÄÖÜÄDEFF", "DE"));
+    }
+
+    #[test]
+    fn beneficiaries_have_either_an_iban_or_an_account_number() {
+        let policy = BeneficiaryPolicy::new(24, 100_000);
+        let now = Utc::now();
+
+        let beneficiary = assert_ok!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &request(Some("DE89370400440532013000"), None),
+            &policy,
+            now
+        ));
+        assert_eq!(beneficiary.name, "Jane Doe");
+        assert_eq!(beneficiary.bank_country, "DE");
+        assert_eq!(beneficiary.currency, "EUR");
+
+        let _ = assert_err!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &request(Some("DE89370400440532013000"), Some("12345678")),
+            &policy,
+            now
+        ));
+
+        // Without an IBAN the bank has to be named
+        let mut domestic = request(None, Some("026-009-593"));
+        let _ = assert_err!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &domestic,
+            &policy,
+            now
+        ));
+        domestic.bic = Some("BOFAUS3N".into());
+        domestic.bank_country = Some("us".into());
+        let beneficiary = assert_ok!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &domestic,
+            &policy,
+            now
+        ));
+        assert_eq!(beneficiary.account_number.as_deref(), Some("026009593"));
+
+        // A US bank can be named by its routing number instead
+        domestic.bic = None;
+        domestic.routing_number = Some("0260-0959-3".into());
+        let beneficiary = assert_ok!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &domestic,
+            &policy,
+            now
+        ));
+        assert_eq!(beneficiary.routing_number.as_deref(), Some("026009593"));
+
+        domestic.bank_country = Some("ca".into());
+        let _ = assert_err!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &domestic,
+            &policy,
+            now
+        ));
+    }
+
+    #[test]
+    fn routing_numbers_must_carry_their_check_digit() {
+        assert_eq!(parse_routing_number("026009593").unwrap(), "026009593");
+        assert_ok!(parse_routing_number("011000015"));
+        assert_ok!(parse_routing_number("1210-0035-8"));
+
+        let _ = assert_err!(parse_routing_number("026009594"));
+        let _ = assert_err!(parse_routing_number("02600959"));
+        let _ = assert_err!(parse_routing_number("02600959A"));
+    }
+
+    #[test]
+    fn large_amounts_wait_for_the_cooling_off_period() {
+        let policy = BeneficiaryPolicy::new(24, 100_000);
+        let now = Utc::now();
+        let beneficiary = assert_ok!(BeneficiaryEntity::new(
+            Uuid::now_v7(),
+            &request(Some("DE89370400440532013000"), None),
+            &policy,
+            now
+        ));
+
+        assert_ok!(beneficiary.check_amount(100_000, &policy, now));
+        let _ = assert_err!(beneficiary.check_amount(100_001, &policy, now));
+        assert_ok!(beneficiary.check_amount(100_001, &policy, now + Duration::hours(24)));
+    }
+
+    #[test]
+    fn challenges_accept_only_their_own_code() {
+        let now = Utc::now();
+        let (mut challenge, code) = ChallengeEntity::new(Uuid::now_v7(), now);
+
+        assert_eq!(code.len(), 6);
+        assert!(challenge.matches(&code));
+        assert!(!challenge.matches("1234567"));
+        assert_ok!(challenge.check_usable(now));
+        let _ = assert_err!(challenge.check_usable(now + Duration::minutes(10)));
+
+        challenge.attempts = 5;
+        let _ = assert_err!(challenge.check_usable(now));
+    }
+}
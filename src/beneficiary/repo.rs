@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::beneficiary::models::{BeneficiaryEntity, ChallengeEntity};
+
+const BENEFICIARY_COLUMNS: &str = "id, user_id, name, iban, account_number, bic, routing_number,
+    bank_country, currency, cooling_off_until, flagged_reason, flagged_at, created_at, updated_at";
+
+pub struct BeneficiaryRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> BeneficiaryRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    // Earlier codes stop working once a new one is sent
+    #[tracing::instrument("Saving beneficiary challenge", skip(self, challenge))]
+    pub async fn insert_challenge(
+        &mut self,
+        challenge: &ChallengeEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE beneficiary_challenge SET consumed_at=$2
+                WHERE user_id=$1 AND consumed_at IS NULL",
+        )
+        .bind(challenge.user_id)
+        .bind(challenge.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO beneficiary_challenge(id, user_id, code_hash, attempts, expires_at, created_at)
+                VALUES($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(challenge.id)
+        .bind(challenge.user_id)
+        .bind(&challenge.code_hash)
+        .bind(challenge.attempts)
+        .bind(challenge.expires_at)
+        .bind(challenge.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Locking beneficiary challenge", skip(self))]
+    pub async fn fetch_challenge_for_update(
+        &mut self,
+        user_id: Uuid,
+        challenge_id: Uuid,
+    ) -> Result<Option<ChallengeEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ChallengeEntity>(
+            "SELECT id, user_id, code_hash, attempts, expires_at, consumed_at, created_at
+                FROM beneficiary_challenge
+                WHERE id=$1 AND user_id=$2
+                FOR UPDATE",
+        )
+        .bind(challenge_id)
+        .bind(user_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Recording beneficiary challenge attempt", skip(self))]
+    pub async fn record_challenge_attempt(
+        &mut self,
+        challenge_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE beneficiary_challenge SET attempts=attempts + 1 WHERE id=$1")
+            .bind(challenge_id)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Consuming beneficiary challenge", skip(self))]
+    pub async fn consume_challenge(
+        &mut self,
+        challenge_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE beneficiary_challenge SET consumed_at=$2 WHERE id=$1")
+            .bind(challenge_id)
+            .bind(now)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving beneficiary", skip(self, beneficiary))]
+    pub async fn insert_beneficiary(
+        &mut self,
+        beneficiary: &BeneficiaryEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO beneficiary(id, user_id, name, iban, account_number, bic, routing_number,
+                    bank_country, currency, cooling_off_until, created_at, updated_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(beneficiary.id)
+        .bind(beneficiary.user_id)
+        .bind(&beneficiary.name)
+        .bind(&beneficiary.iban)
+        .bind(&beneficiary.account_number)
+        .bind(&beneficiary.bic)
+        .bind(&beneficiary.routing_number)
+        .bind(&beneficiary.bank_country)
+        .bind(&beneficiary.currency)
+        .bind(beneficiary.cooling_off_until)
+        .bind(beneficiary.created_at)
+        .bind(beneficiary.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving beneficiaries", skip(self))]
+    pub async fn fetch_beneficiaries(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BeneficiaryEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, BeneficiaryEntity>(&format!(
+            "SELECT {} FROM beneficiary
+                WHERE user_id=$1 AND removed_at IS NULL
+                ORDER BY name, created_at",
+            BENEFICIARY_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving beneficiary", skip(self))]
+    pub async fn fetch_beneficiary(
+        &mut self,
+        user_id: Uuid,
+        beneficiary_id: Uuid,
+    ) -> Result<Option<BeneficiaryEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, BeneficiaryEntity>(&format!(
+            "SELECT {} FROM beneficiary
+                WHERE id=$1 AND user_id=$2 AND removed_at IS NULL",
+            BENEFICIARY_COLUMNS
+        ))
+        .bind(beneficiary_id)
+        .bind(user_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Only the name can change, a different account is a new beneficiary
+    #[tracing::instrument("Renaming beneficiary", skip(self))]
+    pub async fn update_name(
+        &mut self,
+        user_id: Uuid,
+        beneficiary_id: Uuid,
+        name: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<BeneficiaryEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, BeneficiaryEntity>(&format!(
+            "UPDATE beneficiary SET name=$3, updated_at=$4
+                WHERE id=$1 AND user_id=$2 AND removed_at IS NULL
+                RETURNING {}",
+            BENEFICIARY_COLUMNS
+        ))
+        .bind(beneficiary_id)
+        .bind(user_id)
+        .bind(name)
+        .bind(now)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Removing beneficiary", skip(self))]
+    pub async fn remove_beneficiary(
+        &mut self,
+        user_id: Uuid,
+        beneficiary_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_removed = sqlx::query(
+            "UPDATE beneficiary SET removed_at=$3, updated_at=$3
+                WHERE id=$1 AND user_id=$2 AND removed_at IS NULL",
+        )
+        .bind(beneficiary_id)
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_removed)
+    }
+
+    // Removed beneficiaries are flagged too, the customer may add the same account again
+    #[tracing::instrument("Flagging beneficiary", skip(self))]
+    pub async fn flag_beneficiary(
+        &mut self,
+        beneficiary_id: Uuid,
+        reason: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE beneficiary SET flagged_reason=$2, flagged_at=$3, updated_at=$3
+                WHERE id=$1 AND flagged_at IS NULL",
+        )
+        .bind(beneficiary_id)
+        .bind(reason)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+}
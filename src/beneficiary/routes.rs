@@ -0,0 +1,103 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::beneficiary::{
+    schemas::{
+        BeneficiaryRequest, BeneficiaryResponse, BeneficiaryUpdateRequest, ChallengeResponse,
+    },
+    service::BeneficiaryService,
+};
+use crate::config::state::AppState;
+
+#[tracing::instrument("Request beneficiary challenge", skip(app_state, claims))]
+#[utoipa::path(post, path="/beneficiaries/challenge", responses((status=200, body=ChallengeResponse, description="Verification code emailed to the customer, earlier codes no longer work")))]
+pub async fn request_beneficiary_challenge(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    let response = beneficiary_service.challenge(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Add beneficiary", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/beneficiaries", request_body=BeneficiaryRequest, responses((status=200, body=BeneficiaryResponse, description="Beneficiary added, large amounts wait for the cooling-off period"), (status=400, description="Invalid name, IBAN, account number, BIC or currency"), (status=401, description="Verification code wrong, used or expired"), (status=409, description="Account already saved as a beneficiary")))]
+pub async fn create_beneficiary(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<BeneficiaryRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    let response = beneficiary_service
+        .create(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List beneficiaries", skip(app_state, claims))]
+#[utoipa::path(get, path="/beneficiaries", responses((status=200, body=Vec<BeneficiaryResponse>, description="Customer's beneficiaries by name")))]
+pub async fn list_beneficiaries(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    let response = beneficiary_service.list(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch beneficiary", skip(app_state, claims))]
+#[utoipa::path(get, path="/beneficiaries/{beneficiary_id}", params(("beneficiary_id"=Uuid, Path, description="Beneficiary id")), responses((status=200, body=BeneficiaryResponse, description="Beneficiary"), (status=404, description="Beneficiary not found")))]
+pub async fn fetch_beneficiary(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    beneficiary_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    let response = beneficiary_service
+        .fetch(&claims, beneficiary_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Rename beneficiary", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/beneficiaries/{beneficiary_id}", params(("beneficiary_id"=Uuid, Path, description="Beneficiary id")), request_body=BeneficiaryUpdateRequest, responses((status=200, body=BeneficiaryResponse, description="Beneficiary renamed"), (status=400, description="Invalid name"), (status=404, description="Beneficiary not found")))]
+pub async fn update_beneficiary(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    beneficiary_id: web::Path<Uuid>,
+    payload: web::Json<BeneficiaryUpdateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    let response = beneficiary_service
+        .rename(&claims, beneficiary_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Remove beneficiary", skip(app_state, claims))]
+#[utoipa::path(delete, path="/beneficiaries/{beneficiary_id}", params(("beneficiary_id"=Uuid, Path, description="Beneficiary id")), responses((status=200, body=StdResponse, description="Beneficiary removed"), (status=404, description="Beneficiary not found")))]
+pub async fn delete_beneficiary(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    beneficiary_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let beneficiary_service = BeneficiaryService::from(&app_state);
+
+    beneficiary_service
+        .remove(&claims, beneficiary_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Beneficiary removed")))
+}
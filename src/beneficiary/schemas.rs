@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::beneficiary::models::BeneficiaryEntity;
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ChallengeResponse {
+    pub challenge_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct BeneficiaryRequest {
+    #[schema(example = "Jane Doe")]
+    pub name: String,
+    // Either an IBAN or an account number with the bank's BIC, or its routing number in the US
+    #[schema(example = "DE89 3704 0044 0532 0130 00")]
+    pub iban: Option<String>,
+    pub account_number: Option<String>,
+    #[schema(example = "COBADEFFXXX")]
+    pub bic: Option<String>,
+    #[schema(example = "026009593")]
+    pub routing_number: Option<String>,
+    // Taken from the IBAN when there is one
+    #[schema(example = "DE")]
+    pub bank_country: Option<String>,
+    #[schema(example = "EUR")]
+    pub currency: String,
+    // From the challenge whose code was emailed to the customer
+    pub challenge_id: Uuid,
+    #[schema(example = "042917")]
+    pub code: String,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct BeneficiaryUpdateRequest {
+    #[schema(example = "Jane Doe (rent)")]
+    pub name: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct BeneficiaryResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub iban: Option<String>,
+    pub account_number: Option<String>,
+    pub bic: Option<String>,
+    pub routing_number: Option<String>,
+    pub bank_country: String,
+    pub currency: String,
+    // Large amounts can't be sent before then
+    pub cooling_off_until: DateTime<Utc>,
+    // Payments are refused once set
+    pub flagged_reason: Option<String>,
+    pub flagged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<BeneficiaryEntity> for BeneficiaryResponse {
+    fn from(value: BeneficiaryEntity) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            iban: value.iban,
+            account_number: value.account_number,
+            bic: value.bic,
+            routing_number: value.routing_number,
+            bank_country: value.bank_country,
+            currency: value.currency,
+            cooling_off_until: value.cooling_off_until,
+            flagged_reason: value.flagged_reason,
+            flagged_at: value.flagged_at,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::beneficiary::{
+    models::{BeneficiaryEntity, ChallengeEntity, parse_name},
+    schemas::{
+        BeneficiaryRequest, BeneficiaryResponse, BeneficiaryUpdateRequest, ChallengeResponse,
+    },
+};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+
+pub struct BeneficiaryService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> BeneficiaryService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // For outbound payments, large amounts are refused during the cooling-off period
+    pub async fn check_payment(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        user_id: Uuid,
+        beneficiary_id: Uuid,
+        amount_cents: i64,
+        now: DateTime<Utc>,
+    ) -> Result<BeneficiaryEntity, AppError> {
+        let beneficiary = match uow
+            .beneficiaries()
+            .fetch_beneficiary(user_id, beneficiary_id)
+            .await
+            .to_app_err("Failed to fetch beneficiary")?
+        {
+            Some(b) => b,
+            None => Err(DomainError::NotFound("beneficiary".into()))?,
+        };
+
+        beneficiary.check_not_flagged()?;
+        beneficiary.check_amount(amount_cents, &self.app_state.beneficiary_policy, now)?;
+
+        Ok(beneficiary)
+    }
+
+    #[tracing::instrument("Request beneficiary challenge", skip(self, claims))]
+    pub async fn challenge(&self, claims: &SessionClaims) -> Result<ChallengeResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let user = match uow
+            .authentication()
+            .fetch_user_by_id(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) => u,
+            None => Err(DomainError::NotFound("customer".into()))?,
+        };
+
+        let (challenge, code) = ChallengeEntity::new(user.id, Utc::now());
+
+        uow.beneficiaries()
+            .insert_challenge(&challenge)
+            .await
+            .to_app_err("Failed to save beneficiary challenge")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit beneficiary challenge")?;
+
+        self.app_state
+            .email_client
+            .send_verification_code_email(
+                &user.email,
+                &user.first_name,
+                "add a new beneficiary",
+                &code,
+                "Thalia Corp.",
+            )
+            .await?;
+
+        Ok(ChallengeResponse {
+            challenge_id: challenge.id,
+            expires_at: challenge.expires_at,
+        })
+    }
+
+    #[tracing::instrument("Add beneficiary", skip(self, claims, request))]
+    pub async fn create(
+        &self,
+        claims: &SessionClaims,
+        request: BeneficiaryRequest,
+    ) -> Result<BeneficiaryResponse, AppError> {
+        let now = Utc::now();
+        let user_id = *claims.get_user_id();
+        let beneficiary =
+            BeneficiaryEntity::new(user_id, &request, &self.app_state.beneficiary_policy, now)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let challenge = match uow
+            .beneficiaries()
+            .fetch_challenge_for_update(user_id, request.challenge_id)
+            .await
+            .to_app_err("Failed to fetch beneficiary challenge")?
+        {
+            Some(c) => c,
+            None => Err(AuthError::Expired("verification code".into()))?,
+        };
+        challenge.check_usable(now)?;
+
+        // The failed attempt is kept so codes can't be guessed
+        if !challenge.matches(&request.code) {
+            uow.beneficiaries()
+                .record_challenge_attempt(challenge.id)
+                .await
+                .to_app_err("Failed to record beneficiary challenge attempt")?;
+
+            uow.commit()
+                .await
+                .to_app_err("Failed to commit beneficiary challenge attempt")?;
+
+            return Err(AuthError::InvalidCredentials("verification code".into()).into());
+        }
+
+        uow.beneficiaries()
+            .consume_challenge(challenge.id, now)
+            .await
+            .to_app_err("Failed to consume beneficiary challenge")?;
+
+        uow.beneficiaries()
+            .insert_beneficiary(&beneficiary)
+            .await
+            .to_app_err("beneficiary")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit beneficiary")?;
+
+        Ok(beneficiary.into())
+    }
+
+    #[tracing::instrument("List beneficiaries", skip(self, claims))]
+    pub async fn list(&self, claims: &SessionClaims) -> Result<Vec<BeneficiaryResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let beneficiaries = uow
+            .beneficiaries()
+            .fetch_beneficiaries(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch beneficiaries")?;
+
+        Ok(beneficiaries
+            .into_iter()
+            .map(BeneficiaryResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch beneficiary", skip(self, claims))]
+    pub async fn fetch(
+        &self,
+        claims: &SessionClaims,
+        beneficiary_id: Uuid,
+    ) -> Result<BeneficiaryResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        match uow
+            .beneficiaries()
+            .fetch_beneficiary(*claims.get_user_id(), beneficiary_id)
+            .await
+            .to_app_err("Failed to fetch beneficiary")?
+        {
+            Some(b) => Ok(b.into()),
+            None => Err(DomainError::NotFound("beneficiary".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Rename beneficiary", skip(self, claims, request))]
+    pub async fn rename(
+        &self,
+        claims: &SessionClaims,
+        beneficiary_id: Uuid,
+        request: BeneficiaryUpdateRequest,
+    ) -> Result<BeneficiaryResponse, AppError> {
+        let name = parse_name(&request.name)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let beneficiary = match uow
+            .beneficiaries()
+            .update_name(*claims.get_user_id(), beneficiary_id, &name, Utc::now())
+            .await
+            .to_app_err("Failed to rename beneficiary")?
+        {
+            Some(b) => b,
+            None => Err(DomainError::NotFound("beneficiary".into()))?,
+        };
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit beneficiary")?;
+
+        Ok(beneficiary.into())
+    }
+
+    #[tracing::instrument("Remove beneficiary", skip(self, claims))]
+    pub async fn remove(
+        &self,
+        claims: &SessionClaims,
+        beneficiary_id: Uuid,
+    ) -> Result<(), AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let n_removed = uow
+            .beneficiaries()
+            .remove_beneficiary(*claims.get_user_id(), beneficiary_id, Utc::now())
+            .await
+            .to_app_err("Failed to remove beneficiary")?;
+
+        if n_removed == 0 {
+            Err(DomainError::NotFound("beneficiary".into()))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit beneficiary removal")?;
+
+        Ok(())
+    }
+}
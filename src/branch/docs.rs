@@ -0,0 +1,14 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::branch::routes::create_legal_entity,
+    crate::branch::routes::list_legal_entities,
+    crate::branch::routes::create_branch,
+    crate::branch::routes::list_branches,
+    crate::branch::routes::assign_branch_staff,
+    crate::branch::routes::list_branch_staff,
+    crate::branch::routes::unassign_branch_staff,
+    crate::branch::routes::branch_report,
+))]
+pub struct BranchApi;
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use isocountry::CountryCode;
+use uuid::Uuid;
+
+use crate::base::error::{AuthError, ValidationError};
+use crate::branch::schemas::{BranchRequest, LegalEntityRequest};
+use crate::user::models::AccessRole;
+
+fn parse_name(value: &str) -> Result<String, ValidationError> {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return Err(ValidationError::MissingField("name".into()));
+    }
+
+    if value.chars().count() > 128 {
+        return Err(ValidationError::TooLong {
+            field: "name".into(),
+            max: 128,
+        });
+    }
+
+    Ok(value.to_string())
+}
+
+// Codes show up on statements and reports, so they are kept short and uppercase
+pub fn parse_code(code: &str) -> Result<String, ValidationError> {
+    let code = code.trim().to_uppercase();
+
+    if code.is_empty() {
+        return Err(ValidationError::MissingField("code".into()));
+    }
+
+    if code.len() > 16 {
+        return Err(ValidationError::TooLong {
+            field: "code".into(),
+            max: 16,
+        });
+    }
+
+    if !code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ValidationError::InvalidFormat("code".into()));
+    }
+
+    Ok(code)
+}
+
+// ISO 3166-1 alpha-2
+pub fn parse_country(code: &str) -> Result<String, ValidationError> {
+    let code = code.trim().to_uppercase();
+
+    match CountryCode::for_alpha2(&code) {
+        Ok(_) => Ok(code),
+        Err(_) => Err(ValidationError::InvalidValue {
+            field: "country_code".into(),
+            reason: "Unknown ISO 3166 country code".into(),
+        }),
+    }
+}
+
+// Superusers work across every branch, managers only where they are assigned
+pub fn check_branch_access(role: &AccessRole, is_assigned: bool) -> Result<(), AuthError> {
+    match role {
+        AccessRole::Superuser => Ok(()),
+        AccessRole::Manager if is_assigned => Ok(()),
+        _ => Err(AuthError::InsufficientPermissions),
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct LegalEntityEntity {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub country_code: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LegalEntityEntity {
+    pub fn new(request: &LegalEntityRequest) -> Result<Self, ValidationError> {
+        Ok(Self {
+            id: Uuid::now_v7(),
+            code: parse_code(&request.code)?,
+            name: parse_name(&request.name)?,
+            country_code: parse_country(&request.country_code)?,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct BranchEntity {
+    pub id: Uuid,
+    pub legal_entity_id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub country_code: String,
+    pub timezone: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BranchEntity {
+    // The timezone name is checked against the database's zone list by the service
+    pub fn new(request: &BranchRequest) -> Result<Self, ValidationError> {
+        let timezone = request.timezone.trim();
+        if timezone.is_empty() {
+            return Err(ValidationError::MissingField("timezone".into()));
+        }
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            legal_entity_id: request.legal_entity_id,
+            code: parse_code(&request.code)?,
+            name: parse_name(&request.name)?,
+            country_code: parse_country(&request.country_code)?,
+            timezone: timezone.to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct StaffBranchEntity {
+    pub staff_id: Uuid,
+    pub branch_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct BranchReportEntity {
+    pub accounts_opened: i64,
+    pub deposit_count: i64,
+    pub deposits_cents: i64,
+    pub open_accounts: i64,
+    pub total_balance_cents: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+
+    use super::{check_branch_access, parse_code, parse_country};
+    use crate::user::models::AccessRole;
+
+    #[test]
+    fn codes_are_uppercased_and_restricted() {
+        assert_eq!(assert_ok!(parse_code(" ber-01 ")), "BER-01");
+        let _ = assert_err!(parse_code("berlin mitte"));
+        let _ = assert_err!(parse_code("   "));
+    }
+
+    #[test]
+    fn countries_must_be_iso_alpha2() {
+        assert_eq!(assert_ok!(parse_country("de")), "DE");
+        let _ = assert_err!(parse_country("XX"));
+        let _ = assert_err!(parse_country("DEU"));
+    }
+
+    #[test]
+    fn managers_need_an_assignment() {
+        assert_ok!(check_branch_access(&AccessRole::Superuser, false));
+        assert_ok!(check_branch_access(&AccessRole::Manager, true));
+        let _ = assert_err!(check_branch_access(&AccessRole::Manager, false));
+        let _ = assert_err!(check_branch_access(&AccessRole::Customer, true));
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::branch::models::{
+    BranchEntity, BranchReportEntity, LegalEntityEntity, StaffBranchEntity,
+};
+
+const BRANCH_COLUMNS: &str =
+    "id, legal_entity_id, code, name, country_code, timezone, is_active, created_at";
+
+pub struct BranchRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> BranchRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Inserting legal entity", skip(self, entity))]
+    pub async fn create_legal_entity(
+        &mut self,
+        entity: &LegalEntityEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO legal_entity(id, code, name, country_code, created_at) VALUES($1, $2, $3, $4, $5)",
+        )
+        .bind(entity.id)
+        .bind(&entity.code)
+        .bind(&entity.name)
+        .bind(&entity.country_code)
+        .bind(entity.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving legal entities", skip(self))]
+    pub async fn fetch_legal_entities(&self) -> Result<Vec<LegalEntityEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, LegalEntityEntity>(
+            "SELECT id, code, name, country_code, created_at FROM legal_entity ORDER BY code",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Names are those shipped with the database's tz data, the same ones AT TIME ZONE accepts
+    #[tracing::instrument("Checking timezone name", skip(self))]
+    pub async fn fetch_timezone_exists(&self, timezone: &str) -> Result<bool, sqlx::Error> {
+        let result: bool =
+            sqlx::query("SELECT EXISTS(SELECT 1 FROM pg_timezone_names WHERE name=$1) AS found")
+                .bind(timezone)
+                .fetch_one(self.pool)
+                .await?
+                .get("found");
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Inserting branch", skip(self, branch))]
+    pub async fn create_branch(&mut self, branch: &BranchEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO branch({}) VALUES($1, $2, $3, $4, $5, $6, $7, $8)",
+            BRANCH_COLUMNS
+        ))
+        .bind(branch.id)
+        .bind(branch.legal_entity_id)
+        .bind(&branch.code)
+        .bind(&branch.name)
+        .bind(&branch.country_code)
+        .bind(&branch.timezone)
+        .bind(branch.is_active)
+        .bind(branch.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving branch", skip(self))]
+    pub async fn fetch_branch(&self, branch_id: Uuid) -> Result<Option<BranchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, BranchEntity>(&format!(
+            "SELECT {} FROM branch WHERE id=$1",
+            BRANCH_COLUMNS
+        ))
+        .bind(branch_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving branches", skip(self))]
+    pub async fn fetch_branches(&self) -> Result<Vec<BranchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, BranchEntity>(&format!(
+            "SELECT {} FROM branch ORDER BY code",
+            BRANCH_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Assigning someone twice leaves the original assignment in place
+    #[tracing::instrument("Assigning staff to branch", skip(self))]
+    pub async fn assign_staff(
+        &mut self,
+        staff_id: Uuid,
+        branch_id: Uuid,
+        assigned_by: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO staff_branch(staff_id, branch_id, assigned_by) VALUES($1, $2, $3)
+                ON CONFLICT (staff_id, branch_id) DO NOTHING",
+        )
+        .bind(staff_id)
+        .bind(branch_id)
+        .bind(assigned_by)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Removing staff from branch", skip(self))]
+    pub async fn unassign_staff(
+        &mut self,
+        staff_id: Uuid,
+        branch_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let n_deleted = sqlx::query("DELETE FROM staff_branch WHERE staff_id=$1 AND branch_id=$2")
+            .bind(staff_id)
+            .bind(branch_id)
+            .execute(&mut **self.tx)
+            .await?
+            .rows_affected();
+
+        Ok(n_deleted)
+    }
+
+    #[tracing::instrument("Retrieving branch staff", skip(self))]
+    pub async fn fetch_staff(
+        &self,
+        branch_id: Uuid,
+    ) -> Result<Vec<StaffBranchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, StaffBranchEntity>(
+            "SELECT staff_id, branch_id, assigned_by, assigned_at FROM staff_branch
+                WHERE branch_id=$1 ORDER BY assigned_at",
+        )
+        .bind(branch_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Checking staff branch assignment", skip(self))]
+    pub async fn fetch_is_assigned(
+        &self,
+        staff_id: Uuid,
+        branch_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result: bool = sqlx::query(
+            "SELECT EXISTS(SELECT 1 FROM staff_branch WHERE staff_id=$1 AND branch_id=$2) AS assigned",
+        )
+        .bind(staff_id)
+        .bind(branch_id)
+        .fetch_one(self.pool)
+        .await?
+        .get("assigned");
+
+        Ok(result)
+    }
+
+    // Deposits are entries crediting a liability chart account, the zero amount opening entry is left out.
+    // Journal timestamps are stored in UTC and shifted to the branch's local day.
+    #[tracing::instrument("Calculating branch report", skip(self))]
+    pub async fn fetch_report(
+        &self,
+        branch_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<BranchReportEntity, sqlx::Error> {
+        let result = sqlx::query_as::<_, BranchReportEntity>(
+            "WITH b AS (SELECT id, timezone FROM branch WHERE id=$1),
+                opened AS (
+                    SELECT COUNT(*) AS n FROM user_account a JOIN b ON a.branch_id = b.id
+                        WHERE (a.created_at AT TIME ZONE b.timezone)::date BETWEEN $2 AND $3
+                ),
+                deposits AS (
+                    SELECT lb.journal_entry_id AS id, SUM(lb.credit_cents) AS amount_cents
+                        FROM journal_line_balance lb
+                        JOIN user_account a ON a.id = lb.user_account_id
+                        JOIN b ON a.branch_id = b.id
+                        WHERE lb.coa_type = 'liability' AND lb.credit_cents > 0
+                            AND (lb.created_date AT TIME ZONE 'UTC' AT TIME ZONE b.timezone)::date BETWEEN $2 AND $3
+                        GROUP BY lb.journal_entry_id
+                ),
+                book AS (
+                    SELECT COUNT(*) AS n,
+                        COALESCE((
+                            SELECT SUM(lb.credit_cents)
+                                FROM journal_line_balance lb
+                                JOIN user_account la ON la.id = lb.user_account_id
+                                JOIN b ON la.branch_id = b.id
+                                WHERE lb.coa_type = 'liability' AND la.status <> 'closed'
+                        ), 0) AS balance_cents
+                        FROM user_account a JOIN b ON a.branch_id = b.id
+                        WHERE a.status <> 'closed'
+                )
+            SELECT (SELECT n FROM opened) AS accounts_opened,
+                   (SELECT COUNT(*) FROM deposits) AS deposit_count,
+                   (SELECT COALESCE(SUM(amount_cents), 0) FROM deposits)::BIGINT AS deposits_cents,
+                   (SELECT n FROM book) AS open_accounts,
+                   (SELECT balance_cents FROM book)::BIGINT AS total_balance_cents",
+        )
+        .bind(branch_id)
+        .bind(from)
+        .bind(to)
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
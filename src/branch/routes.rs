@@ -0,0 +1,131 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::branch::{
+    schemas::{
+        BranchReportQuery, BranchReportResponse, BranchRequest, BranchResponse, LegalEntityRequest,
+        LegalEntityResponse, StaffBranchRequest, StaffBranchResponse,
+    },
+    service::BranchService,
+};
+use crate::config::state::AppState;
+
+#[tracing::instrument("Create legal entity", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/legal-entities", request_body=LegalEntityRequest, responses((status=200, body=LegalEntityResponse, description="Legal entity created"), (status=400, description="Invalid code, name or country"), (status=403, description="Only superusers can create legal entities"), (status=409, description="Code already in use")))]
+pub async fn create_legal_entity(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<LegalEntityRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service
+        .create_legal_entity(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List legal entities", skip(app_state))]
+#[utoipa::path(get, path="/legal-entities", responses((status=200, body=Vec<LegalEntityResponse>, description="Legal entities by code")))]
+pub async fn list_legal_entities(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service.list_legal_entities().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Create branch", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/branches", request_body=BranchRequest, responses((status=200, body=BranchResponse, description="Branch created"), (status=400, description="Invalid code, country or timezone"), (status=403, description="Only superusers can create branches"), (status=404, description="Legal entity not found"), (status=409, description="Code already in use")))]
+pub async fn create_branch(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<BranchRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service
+        .create_branch(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List branches", skip(app_state))]
+#[utoipa::path(get, path="/branches", responses((status=200, body=Vec<BranchResponse>, description="Branches by code")))]
+pub async fn list_branches(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service.list_branches().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Assign staff to branch", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/branches/{branch_id}/staff", request_body=StaffBranchRequest, responses((status=200, body=Vec<StaffBranchResponse>, description="Staff now assigned to the branch"), (status=403, description="Only superusers can assign staff"), (status=404, description="Branch or staff member not found")))]
+pub async fn assign_branch_staff(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    branch_id: web::Path<Uuid>,
+    payload: web::Json<StaffBranchRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service
+        .assign_staff(&claims, branch_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List branch staff", skip(app_state))]
+#[utoipa::path(get, path="/branches/{branch_id}/staff", responses((status=200, body=Vec<StaffBranchResponse>, description="Staff assigned to the branch"), (status=404, description="Branch not found")))]
+pub async fn list_branch_staff(
+    app_state: web::Data<AppState>,
+    branch_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service.list_staff(branch_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Remove staff from branch", skip(app_state, claims))]
+#[utoipa::path(delete, path="/branches/{branch_id}/staff/{staff_id}", responses((status=200, body=StdResponse, description="Staff removed from the branch"), (status=403, description="Only superusers can remove staff"), (status=404, description="Staff member is not assigned to the branch")))]
+pub async fn unassign_branch_staff(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+    let (branch_id, staff_id) = path.into_inner();
+
+    branch_service
+        .unassign_staff(&claims, branch_id, staff_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Staff removed from branch")))
+}
+
+#[tracing::instrument("Branch report", skip(app_state, claims))]
+#[utoipa::path(get, path="/branches/{branch_id}/report", params(BranchReportQuery), responses((status=200, body=BranchReportResponse, description="Accounts opened and deposits taken over the period"), (status=400, description="Invalid period"), (status=403, description="Managers can only report on their own branches"), (status=404, description="Branch not found")))]
+pub async fn branch_report(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    branch_id: web::Path<Uuid>,
+    query: web::Query<BranchReportQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let branch_service = BranchService::from(&app_state);
+
+    let response = branch_service
+        .branch_report(&claims, branch_id.into_inner(), query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
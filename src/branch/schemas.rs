@@ -0,0 +1,136 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::branch::models::{
+    BranchEntity, BranchReportEntity, LegalEntityEntity, StaffBranchEntity,
+};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct LegalEntityRequest {
+    #[schema(example = "THA-DE")]
+    pub code: String,
+    #[schema(example = "Thalia Bank AG")]
+    pub name: String,
+    #[schema(example = "DE")]
+    pub country_code: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct LegalEntityResponse {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub country_code: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<LegalEntityEntity> for LegalEntityResponse {
+    fn from(value: LegalEntityEntity) -> Self {
+        Self {
+            id: value.id,
+            code: value.code,
+            name: value.name,
+            country_code: value.country_code,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct BranchRequest {
+    pub legal_entity_id: Uuid,
+    #[schema(example = "BER-01")]
+    pub code: String,
+    #[schema(example = "Berlin Mitte")]
+    pub name: String,
+    #[schema(example = "DE")]
+    pub country_code: String,
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct BranchResponse {
+    pub id: Uuid,
+    pub legal_entity_id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub country_code: String,
+    pub timezone: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<BranchEntity> for BranchResponse {
+    fn from(value: BranchEntity) -> Self {
+        Self {
+            id: value.id,
+            legal_entity_id: value.legal_entity_id,
+            code: value.code,
+            name: value.name,
+            country_code: value.country_code,
+            timezone: value.timezone,
+            is_active: value.is_active,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct StaffBranchRequest {
+    pub staff_id: Uuid,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct StaffBranchResponse {
+    pub staff_id: Uuid,
+    pub branch_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+impl From<StaffBranchEntity> for StaffBranchResponse {
+    fn from(value: StaffBranchEntity) -> Self {
+        Self {
+            staff_id: value.staff_id,
+            branch_id: value.branch_id,
+            assigned_by: value.assigned_by,
+            assigned_at: value.assigned_at,
+        }
+    }
+}
+
+// Both days are inclusive and read in the branch's own timezone
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct BranchReportQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct BranchReportResponse {
+    pub branch_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub accounts_opened: i64,
+    pub deposit_count: i64,
+    pub deposits_cents: i64,
+    // As of now, not as of the end of the period
+    pub open_accounts: i64,
+    pub total_balance_cents: i64,
+}
+
+impl BranchReportResponse {
+    pub fn new(branch_id: Uuid, query: &BranchReportQuery, report: BranchReportEntity) -> Self {
+        Self {
+            branch_id,
+            from: query.from,
+            to: query.to,
+            accounts_opened: report.accounts_opened,
+            deposit_count: report.deposit_count,
+            deposits_cents: report.deposits_cents,
+            open_accounts: report.open_accounts,
+            total_balance_cents: report.total_balance_cents,
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::branch::{
+    models::{BranchEntity, LegalEntityEntity, check_branch_access},
+    schemas::{
+        BranchReportQuery, BranchReportResponse, BranchRequest, BranchResponse, LegalEntityRequest,
+        LegalEntityResponse, StaffBranchRequest, StaffBranchResponse,
+    },
+};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::user::models::AccessRole;
+
+pub struct BranchService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> BranchService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // The branch network and who works where is managed centrally
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    async fn fetch_branch(
+        uow: &mut UnitofWork<'_>,
+        branch_id: Uuid,
+    ) -> Result<BranchEntity, AppError> {
+        match uow
+            .branches()
+            .fetch_branch(branch_id)
+            .await
+            .to_app_err("Failed to fetch branch")?
+        {
+            Some(b) => Ok(b),
+            None => Err(DomainError::NotFound("branch".into()))?,
+        }
+    }
+
+    pub async fn check_access(
+        uow: &mut UnitofWork<'_>,
+        claims: &SessionClaims,
+        branch_id: Uuid,
+    ) -> Result<(), AppError> {
+        let is_assigned = match claims.get_role() {
+            AccessRole::Manager => uow
+                .branches()
+                .fetch_is_assigned(*claims.get_user_id(), branch_id)
+                .await
+                .to_app_err("Failed to fetch branch assignment")?,
+            _ => false,
+        };
+
+        check_branch_access(claims.get_role(), is_assigned)?;
+
+        Ok(())
+    }
+
+    // Customers may open at any active branch, managers only at their own
+    pub async fn check_account_opening(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        claims: &SessionClaims,
+        branch_id: Uuid,
+    ) -> Result<(), AppError> {
+        let branch = Self::fetch_branch(uow, branch_id).await?;
+
+        if !branch.is_active {
+            Err(DomainError::InvalidState(format!(
+                "branch {} is closed for new accounts",
+                branch.code
+            )))?
+        }
+
+        if *claims.get_role() != AccessRole::Customer {
+            Self::check_access(uow, claims, branch_id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Create legal entity", skip(self, claims, request))]
+    pub async fn create_legal_entity(
+        &self,
+        claims: &SessionClaims,
+        request: LegalEntityRequest,
+    ) -> Result<LegalEntityResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let entity = LegalEntityEntity::new(&request)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        uow.branches()
+            .create_legal_entity(&entity)
+            .await
+            .to_app_err("legal entity code")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit legal entity creation")?;
+
+        Ok(LegalEntityResponse::from(entity))
+    }
+
+    #[tracing::instrument("List legal entities", skip(self))]
+    pub async fn list_legal_entities(&self) -> Result<Vec<LegalEntityResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let entities = uow
+            .branches()
+            .fetch_legal_entities()
+            .await
+            .to_app_err("Failed to fetch legal entities")?;
+
+        Ok(entities
+            .into_iter()
+            .map(LegalEntityResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Create branch", skip(self, claims, request))]
+    pub async fn create_branch(
+        &self,
+        claims: &SessionClaims,
+        request: BranchRequest,
+    ) -> Result<BranchResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let branch = BranchEntity::new(&request)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if !uow
+            .branches()
+            .fetch_timezone_exists(&branch.timezone)
+            .await
+            .to_app_err("Failed to check timezone")?
+        {
+            Err(ValidationError::InvalidValue {
+                field: "timezone".into(),
+                reason: "Expected an IANA timezone name such as Europe/Berlin".into(),
+            })?
+        }
+
+        // Unique violation on the code, foreign key violation on an unknown legal entity
+        uow.branches()
+            .create_branch(&branch)
+            .await
+            .to_app_err("branch code or legal entity")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit branch creation")?;
+
+        Ok(BranchResponse::from(branch))
+    }
+
+    #[tracing::instrument("List branches", skip(self))]
+    pub async fn list_branches(&self) -> Result<Vec<BranchResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let branches = uow
+            .branches()
+            .fetch_branches()
+            .await
+            .to_app_err("Failed to fetch branches")?;
+
+        Ok(branches.into_iter().map(BranchResponse::from).collect())
+    }
+
+    #[tracing::instrument("Assign staff to branch", skip(self, claims, request))]
+    pub async fn assign_staff(
+        &self,
+        claims: &SessionClaims,
+        branch_id: Uuid,
+        request: StaffBranchRequest,
+    ) -> Result<Vec<StaffBranchResponse>, AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_branch(&mut uow, branch_id).await?;
+
+        match uow
+            .authentication()
+            .fetch_user_by_id(request.staff_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) if u.access_role != AccessRole::Customer => (),
+            _ => Err(DomainError::NotFound("staff".into()))?,
+        };
+
+        uow.branches()
+            .assign_staff(request.staff_id, branch_id, *claims.get_user_id())
+            .await
+            .to_app_err("Failed to assign staff to branch")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit staff assignment")?;
+
+        self.list_staff(branch_id).await
+    }
+
+    #[tracing::instrument("Remove staff from branch", skip(self, claims))]
+    pub async fn unassign_staff(
+        &self,
+        claims: &SessionClaims,
+        branch_id: Uuid,
+        staff_id: Uuid,
+    ) -> Result<(), AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .branches()
+            .unassign_staff(staff_id, branch_id)
+            .await
+            .to_app_err("Failed to remove staff from branch")?
+            == 0
+        {
+            Err(DomainError::NotFound("branch assignment".into()))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit staff removal")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("List branch staff", skip(self))]
+    pub async fn list_staff(&self, branch_id: Uuid) -> Result<Vec<StaffBranchResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_branch(&mut uow, branch_id).await?;
+
+        let staff = uow
+            .branches()
+            .fetch_staff(branch_id)
+            .await
+            .to_app_err("Failed to fetch branch staff")?;
+
+        Ok(staff.into_iter().map(StaffBranchResponse::from).collect())
+    }
+
+    #[tracing::instrument("Branch report", skip(self, claims))]
+    pub async fn branch_report(
+        &self,
+        claims: &SessionClaims,
+        branch_id: Uuid,
+        query: BranchReportQuery,
+    ) -> Result<BranchReportResponse, AppError> {
+        if query.from > query.to {
+            Err(ValidationError::InvalidValue {
+                field: "from".into(),
+                reason: "The period starts after it ends".into(),
+            })?
+        }
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_branch(&mut uow, branch_id).await?;
+        Self::check_access(&mut uow, claims, branch_id).await?;
+
+        let report = uow
+            .branches()
+            .fetch_report(branch_id, query.from, query.to)
+            .await
+            .to_app_err("Failed to calculate branch report")?;
+
+        Ok(BranchReportResponse::new(branch_id, &query, report))
+    }
+}
@@ -7,6 +7,7 @@ use runtime::{Config, DatabaseConfig};
 use state::{AppBaseUri, AppState, DefaultPassword, RedisUri, SecretKey};
 
 use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::sync::Arc;
 
 pub fn get_pgconnect_pool(config: &DatabaseConfig) -> PgPool {
     PgPoolOptions::new().connect_lazy_with(config.with_db())
@@ -19,15 +20,17 @@ impl Config {
         let secret = SecretKey(self.application.secret_key.clone());
         let redis_uri = RedisUri(self.redis_uri.clone());
         let default_password = DefaultPassword(self.application.default_password.clone());
+        let key_store = Arc::new(self.jwt.key_store()?);
         let token_handler = TokenHandler::new(
-            secret.clone(),
+            key_store.clone(),
             self.ttl.access_ttl_secs,
             self.ttl.refresh_ttl_secs,
         );
         let redis_pool = RedisPool::new(self.redis_uri.clone())?;
-        let activate_handler = ActivateHandler::new(secret.clone(), self.ttl.activate_ttl_secs);
+        let activate_handler = ActivateHandler::new(key_store.clone(), self.ttl.activate_ttl_secs);
 
         let s3_client = self.s3_client.client().await;
+        let oidc = self.oidc.provider(&self.application.app_uri);
 
         Ok(AppState {
             pgpool,
@@ -40,6 +43,18 @@ impl Config {
             redis_pool,
             activate_handler,
             s3_client,
+            key_store,
+            oidc,
+            kyc_policy: self.kyc.policy(),
+            name_matcher: self.screening.matcher(),
+            payment_retry: self.payments.retry_policy(),
+            beneficiary_policy: self.beneficiaries.policy(),
+            sepa: self.sepa.profile(),
+            inbound: self.inbound.profile(),
+            ach: self.ach.profile(),
+            swift: self.swift.profile(),
+            reconciliation: self.nostro.policy(),
+            reporting: self.reporting.profile(),
         })
     }
 }
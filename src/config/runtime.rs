@@ -1,11 +1,24 @@
+use aws_sdk_s3::config::Credentials;
 use envconfig::Envconfig;
 use sqlx::ConnectOptions;
 use sqlx::postgres::PgConnectOptions;
 use sqlx::postgres::PgSslMode;
 
+use crate::ach::models::AchProfile;
+use crate::authentication::oidc::OidcProvider;
+use crate::authentication::token::KeyStore;
 use crate::base::Email;
+use crate::beneficiary::models::BeneficiaryPolicy;
+use crate::identity_verify::models::KycPolicy;
+use crate::inbound_payment::models::InboundProfile;
 use crate::infra::aws::S3Client;
+use crate::nostro::models::ReconciliationPolicy;
 use crate::notification::email_client::EmailClient;
+use crate::reporting::models::ReportingProfile;
+use crate::scheduled_payment::models::RetryPolicy;
+use crate::screening::matching::NameMatcher;
+use crate::sepa::models::SepaProfile;
+use crate::swift::models::SwiftProfile;
 
 #[derive(serde::Deserialize, Envconfig, Debug)]
 pub struct DatabaseConfig {
@@ -97,17 +110,279 @@ pub struct Ttl {
     pub session_ttl: u64,
 }
 
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct JwtSettings {
+    #[envconfig(from = "JWT_KEYS_DIR")]
+    pub keys_dir: String,
+    #[envconfig(from = "JWT_ALGORITHM", default = "RS256")]
+    pub algorithm: String,
+    #[envconfig(from = "ACCESS_SIGNING_KID")]
+    pub access_kid: String,
+    #[envconfig(from = "REFRESH_SIGNING_KID")]
+    pub refresh_kid: String,
+    #[envconfig(from = "ACTIVATE_SIGNING_KID")]
+    pub activate_kid: String,
+}
+
+impl JwtSettings {
+    pub fn key_store(&self) -> Result<KeyStore, anyhow::Error> {
+        KeyStore::load(
+            &self.keys_dir,
+            &self.algorithm,
+            &self.access_kid,
+            &self.refresh_kid,
+            &self.activate_kid,
+        )
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct OidcSettings {
+    // Staff single sign-on is disabled unless an issuer is configured
+    #[envconfig(from = "OIDC_ISSUER_URL")]
+    pub issuer_url: Option<String>,
+    #[envconfig(from = "OIDC_CLIENT_ID", default = "thalia")]
+    pub client_id: String,
+    #[envconfig(from = "OIDC_CLIENT_SECRET")]
+    pub client_secret: Option<String>,
+    #[envconfig(from = "OIDC_SUPERUSER_GROUPS", default = "thalia-superusers")]
+    pub superuser_groups: String,
+    #[envconfig(from = "OIDC_MANAGER_GROUPS", default = "thalia-managers")]
+    pub manager_groups: String,
+}
+
+impl OidcSettings {
+    pub fn provider(&self, app_uri: &str) -> Option<OidcProvider> {
+        self.issuer_url.as_ref().map(|issuer_url| {
+            OidcProvider::new(
+                issuer_url.clone(),
+                self.client_id.clone(),
+                self.client_secret.clone(),
+                format!("{}/staff/sso/callback", app_uri.trim_end_matches('/')),
+                &self.superuser_groups,
+                &self.manager_groups,
+            )
+        })
+    }
+}
+
 #[derive(serde::Deserialize, Envconfig, Debug)]
 pub struct S3Settings {
     #[envconfig(from = "AWS_REGION")]
     pub region: String,
     #[envconfig(from = "IMAGE_BUCKET")]
     pub image_bucket: String,
+    // Only set for an S3 compatible store, AWS itself is found from the region
+    #[envconfig(from = "S3_ENDPOINT_URL")]
+    pub endpoint_url: Option<String>,
+    #[envconfig(from = "S3_ACCESS_KEY_ID")]
+    pub access_key_id: Option<String>,
+    #[envconfig(from = "S3_SECRET_ACCESS_KEY")]
+    pub secret_access_key: Option<String>,
 }
 
 impl S3Settings {
     pub async fn client(&self) -> S3Client {
-        S3Client::default(self.region.clone(), self.image_bucket.clone()).await
+        // Static keys win over the default AWS credential chain when both are given
+        let credentials = match (&self.access_key_id, &self.secret_access_key) {
+            (Some(id), Some(secret)) => Some(Credentials::new(id, secret, None, None, "thalia")),
+            _ => None,
+        };
+
+        S3Client::default(
+            self.region.clone(),
+            self.image_bucket.clone(),
+            self.endpoint_url.clone(),
+            credentials,
+        )
+        .await
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct KycSettings {
+    // Largest single deposit or withdrawal allowed before a customer is verified
+    #[envconfig(from = "KYC_UNVERIFIED_TRANSACTION_LIMIT", default = "1000")]
+    pub unverified_transaction_limit: f64,
+}
+
+impl KycSettings {
+    pub fn policy(&self) -> KycPolicy {
+        KycPolicy::new(self.unverified_transaction_limit)
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct ScreeningSettings {
+    // Name similarity from 0 to 1 at which a customer is raised for review
+    #[envconfig(from = "SCREENING_MATCH_THRESHOLD", default = "0.88")]
+    pub match_threshold: f64,
+}
+
+impl ScreeningSettings {
+    pub fn matcher(&self) -> NameMatcher {
+        NameMatcher::new(self.match_threshold)
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct PaymentSettings {
+    // Attempts at a scheduled payment before it is failed and the customer told
+    #[envconfig(from = "PAYMENT_RETRY_ATTEMPTS", default = "3")]
+    pub retry_attempts: i32,
+    #[envconfig(from = "PAYMENT_RETRY_INTERVAL_MINS", default = "240")]
+    pub retry_interval_mins: i64,
+}
+
+impl PaymentSettings {
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(self.retry_attempts, self.retry_interval_mins)
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct BeneficiarySettings {
+    // How long a new beneficiary can only be sent amounts up to the large amount
+    #[envconfig(from = "BENEFICIARY_COOLING_OFF_HOURS", default = "24")]
+    pub cooling_off_hours: i64,
+    #[envconfig(from = "BENEFICIARY_LARGE_AMOUNT_CENTS", default = "100000")]
+    pub large_amount_cents: i64,
+}
+
+impl BeneficiarySettings {
+    pub fn policy(&self) -> BeneficiaryPolicy {
+        BeneficiaryPolicy::new(self.cooling_off_hours, self.large_amount_cents)
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct SepaSettings {
+    #[envconfig(from = "SEPA_INITIATING_PARTY", default = "Thalia Corp.")]
+    pub initiating_party: String,
+    #[envconfig(from = "SEPA_DEBTOR_AGENT_BIC", default = "THALDEFFXXX")]
+    pub debtor_agent_bic: String,
+    // pain.001 files are written here when set, otherwise uploaded to the S3 bucket
+    #[envconfig(from = "SEPA_OUTBOUND_DIR")]
+    pub outbound_dir: Option<String>,
+    // Where the clearing house drops its pain.002 status reports
+    #[envconfig(from = "SEPA_STATUS_REPORT_DIR")]
+    pub status_report_dir: Option<String>,
+}
+
+impl SepaSettings {
+    pub fn profile(&self) -> SepaProfile {
+        SepaProfile::new(
+            &self.initiating_party,
+            &self.debtor_agent_bic,
+            self.outbound_dir.as_deref(),
+            self.status_report_dir.as_deref(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct InboundSettings {
+    // Where the bank drops camt.054 notifications and pacs.008 transfers for us
+    #[envconfig(from = "INBOUND_PAYMENT_DIR")]
+    pub drop_dir: Option<String>,
+}
+
+impl InboundSettings {
+    pub fn profile(&self) -> InboundProfile {
+        InboundProfile::new(self.drop_dir.as_deref())
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct AchSettings {
+    // Our routing number at the ACH operator, the first eight digits start every trace number
+    #[envconfig(from = "ACH_ODFI_ROUTING", default = "026009593")]
+    pub odfi_routing: String,
+    #[envconfig(from = "ACH_IMMEDIATE_DESTINATION", default = "011000015")]
+    pub immediate_destination: String,
+    #[envconfig(from = "ACH_DESTINATION_NAME", default = "Federal Reserve Bank")]
+    pub destination_name: String,
+    #[envconfig(from = "ACH_ORIGIN_NAME", default = "Thalia Bank")]
+    pub origin_name: String,
+    #[envconfig(from = "ACH_COMPANY_ID", default = "1234567890")]
+    pub company_id: String,
+    #[envconfig(from = "ACH_COMPANY_NAME", default = "Thalia Corp.")]
+    pub company_name: String,
+    // NACHA files are written here when set, otherwise uploaded to the S3 bucket
+    #[envconfig(from = "ACH_OUTBOUND_DIR")]
+    pub outbound_dir: Option<String>,
+    // Where the ACH operator drops its return files
+    #[envconfig(from = "ACH_RETURN_DIR")]
+    pub return_dir: Option<String>,
+}
+
+impl AchSettings {
+    pub fn profile(&self) -> AchProfile {
+        AchProfile::new(
+            &self.odfi_routing,
+            &self.immediate_destination,
+            &self.destination_name,
+            &self.origin_name,
+            &self.company_id,
+            &self.company_name,
+            self.outbound_dir.as_deref(),
+            self.return_dir.as_deref(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct SwiftSettings {
+    #[envconfig(from = "SWIFT_SENDER_BIC", default = "THALUS33XXX")]
+    pub sender_bic: String,
+    // The bank holding our nostro account, every MT103 we send goes to them
+    #[envconfig(from = "SWIFT_CORRESPONDENT_BIC", default = "CHASUS33XXX")]
+    pub correspondent_bic: String,
+    // Taken off the mid rate when a transfer is paid from an account in another currency
+    #[envconfig(from = "SWIFT_FX_MARGIN_BPS", default = "50")]
+    pub fx_margin_bps: i64,
+    // MT103s are written here when set, otherwise uploaded to the S3 bucket
+    #[envconfig(from = "SWIFT_OUTBOUND_DIR")]
+    pub outbound_dir: Option<String>,
+}
+
+impl SwiftSettings {
+    pub fn profile(&self) -> SwiftProfile {
+        SwiftProfile::new(
+            &self.sender_bic,
+            &self.correspondent_bic,
+            self.fx_margin_bps,
+            self.outbound_dir.as_deref(),
+        )
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct NostroSettings {
+    // How many days a statement entry's value date may be off the day we booked the payment
+    #[envconfig(from = "NOSTRO_DATE_TOLERANCE_DAYS", default = "3")]
+    pub date_tolerance_days: i64,
+    // Charges a correspondent takes out on the way, reference matches only
+    #[envconfig(from = "NOSTRO_AMOUNT_TOLERANCE_CENTS", default = "0")]
+    pub amount_tolerance_cents: i64,
+}
+
+impl NostroSettings {
+    pub fn policy(&self) -> ReconciliationPolicy {
+        ReconciliationPolicy::new(self.date_tolerance_days, self.amount_tolerance_cents)
+    }
+}
+
+#[derive(serde::Deserialize, Envconfig, Debug)]
+pub struct ReportingSettings {
+    // Balances in other currencies are converted to this one on financial statements
+    #[envconfig(from = "REPORTING_BASE_CURRENCY", default = "USD")]
+    pub base_currency: String,
+}
+
+impl ReportingSettings {
+    pub fn profile(&self) -> ReportingProfile {
+        ReportingProfile::new(&self.base_currency)
     }
 }
 
@@ -129,6 +404,30 @@ pub struct Config {
     pub email_client: EmailClientSettings,
     #[envconfig(nested)]
     pub s3_client: S3Settings,
+    #[envconfig(nested)]
+    pub jwt: JwtSettings,
+    #[envconfig(nested)]
+    pub oidc: OidcSettings,
+    #[envconfig(nested)]
+    pub kyc: KycSettings,
+    #[envconfig(nested)]
+    pub screening: ScreeningSettings,
+    #[envconfig(nested)]
+    pub payments: PaymentSettings,
+    #[envconfig(nested)]
+    pub beneficiaries: BeneficiarySettings,
+    #[envconfig(nested)]
+    pub sepa: SepaSettings,
+    #[envconfig(nested)]
+    pub inbound: InboundSettings,
+    #[envconfig(nested)]
+    pub ach: AchSettings,
+    #[envconfig(nested)]
+    pub swift: SwiftSettings,
+    #[envconfig(nested)]
+    pub nostro: NostroSettings,
+    #[envconfig(nested)]
+    pub reporting: ReportingSettings,
 }
 
 pub fn get_config() -> Result<Config, anyhow::Error> {
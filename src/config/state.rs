@@ -1,8 +1,20 @@
 use sqlx::PgPool;
+use std::sync::Arc;
 
-use crate::authentication::token::{ActivateHandler, TokenHandler};
+use crate::ach::models::AchProfile;
+use crate::authentication::oidc::OidcProvider;
+use crate::authentication::token::{ActivateHandler, KeyStore, TokenHandler};
+use crate::beneficiary::models::BeneficiaryPolicy;
+use crate::identity_verify::models::KycPolicy;
+use crate::inbound_payment::models::InboundProfile;
 use crate::infra::{aws::S3Client, redis::RedisPool};
+use crate::nostro::models::ReconciliationPolicy;
 use crate::notification::email_client::EmailClient;
+use crate::reporting::models::ReportingProfile;
+use crate::scheduled_payment::models::RetryPolicy;
+use crate::screening::matching::NameMatcher;
+use crate::sepa::models::SepaProfile;
+use crate::swift::models::SwiftProfile;
 
 #[derive(Debug, Clone)]
 pub struct SecretKey(pub String);
@@ -28,4 +40,16 @@ pub struct AppState {
     pub redis_pool: RedisPool,
     pub activate_handler: ActivateHandler,
     pub s3_client: S3Client,
+    pub key_store: Arc<KeyStore>,
+    pub oidc: Option<OidcProvider>,
+    pub kyc_policy: KycPolicy,
+    pub name_matcher: NameMatcher,
+    pub payment_retry: RetryPolicy,
+    pub beneficiary_policy: BeneficiaryPolicy,
+    pub sepa: SepaProfile,
+    pub inbound: InboundProfile,
+    pub ach: AchProfile,
+    pub swift: SwiftProfile,
+    pub reconciliation: ReconciliationPolicy,
+    pub reporting: ReportingProfile,
 }
@@ -6,6 +6,14 @@ use utoipa::OpenApi;
     crate::customer::routes::confirm_customer,
     crate::customer::routes::customer_login,
     crate::customer::routes::upload_user_docs,
+    crate::customer::routes::fetch_user_docs,
+    crate::customer::routes::submit_kyc_case,
     crate::customer::routes::customer_profile_status,
+    crate::customer::routes::fetch_customer_profile,
+    crate::customer::routes::request_email_change,
+    crate::customer::routes::confirm_email_change,
+    crate::customer::routes::update_customer_address,
+    crate::customer::routes::update_customer_phone,
+    crate::customer::routes::customer_profile_history,
 ))]
 pub struct CustomerApi;
@@ -1,5 +1,6 @@
 pub mod docs;
-mod models;
+pub mod models;
+pub mod repo;
 pub mod routes;
-mod schemas;
+pub mod schemas;
 pub mod service;
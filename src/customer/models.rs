@@ -1 +1,556 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
 
+use crate::account::models::UserAccountStatus;
+use crate::base::{Email, error::ValidationError};
+use crate::customer::schemas::{AddressRequest, CustomerSearchQuery};
+
+const EMAIL_CHANGE_TOKEN_LEN: usize = 43;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "address_kind", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AddressKind {
+    Residential,
+    Mailing,
+}
+
+impl FromStr for AddressKind {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "residential" => Ok(AddressKind::Residential),
+            "mailing" => Ok(AddressKind::Mailing),
+            _ => Err(ValidationError::InvalidValue {
+                field: "kind".into(),
+                reason: "Unknown address kind".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "phone_kind", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PhoneKind {
+    Mobile,
+    Home,
+    Work,
+}
+
+impl FromStr for PhoneKind {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "mobile" => Ok(PhoneKind::Mobile),
+            "home" => Ok(PhoneKind::Home),
+            "work" => Ok(PhoneKind::Work),
+            _ => Err(ValidationError::InvalidValue {
+                field: "kind".into(),
+                reason: "Unknown phone kind".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "profile_change_reason", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ProfileChangeReason {
+    CustomerRequest,
+    LegalNameChange,
+    Marriage,
+    DataCorrection,
+    KycReverification,
+}
+
+impl FromStr for ProfileChangeReason {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "customer_request" => Ok(ProfileChangeReason::CustomerRequest),
+            "legal_name_change" => Ok(ProfileChangeReason::LegalNameChange),
+            "marriage" => Ok(ProfileChangeReason::Marriage),
+            "data_correction" => Ok(ProfileChangeReason::DataCorrection),
+            "kyc_reverification" => Ok(ProfileChangeReason::KycReverification),
+            _ => Err(ValidationError::InvalidValue {
+                field: "reason".into(),
+                reason: "Unknown reason code".into(),
+            }),
+        }
+    }
+}
+
+fn required_text(value: &str, field: &str, max: usize) -> Result<String, ValidationError> {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return Err(ValidationError::MissingField(field.into()));
+    }
+
+    if value.chars().count() > max {
+        return Err(ValidationError::TooLong {
+            field: field.into(),
+            max,
+        });
+    }
+
+    Ok(value.to_string())
+}
+
+fn optional_text(
+    value: Option<&str>,
+    field: &str,
+    max: usize,
+) -> Result<Option<String>, ValidationError> {
+    match value.map(str::trim) {
+        Some(v) if !v.is_empty() => required_text(v, field, max).map(Some),
+        _ => Ok(None),
+    }
+}
+
+// Numbers are kept in E.164 so the same phone always compares equal
+pub fn parse_phone(number: &str) -> Result<String, ValidationError> {
+    let compact: String = number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+
+    let is_e164 = match compact.strip_prefix('+') {
+        Some(digits) => {
+            (8..=15).contains(&digits.len())
+                && digits.chars().all(|c| c.is_ascii_digit())
+                && !digits.starts_with('0')
+        }
+        None => false,
+    };
+
+    if !is_e164 {
+        return Err(ValidationError::InvalidValue {
+            field: "number".into(),
+            reason: "Phone numbers must be in international format, e.g. +4915112345678".into(),
+        });
+    }
+
+    Ok(compact)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct CustomerAddressEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: AddressKind,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: String,
+    pub country_code: String,
+    pub created_at: DateTime<Utc>,
+    pub superseded_at: Option<DateTime<Utc>>,
+}
+
+impl CustomerAddressEntity {
+    pub fn new(user_id: Uuid, request: &AddressRequest) -> Result<Self, ValidationError> {
+        let postal_code = required_text(&request.postal_code, "postal_code", 16)?.to_uppercase();
+        if !postal_code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+        {
+            return Err(ValidationError::InvalidFormat("postal_code".into()));
+        }
+
+        // ISO 3166-1 alpha-2
+        let country_code = request.country_code.trim().to_uppercase();
+        if country_code.len() != 2 || !country_code.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(ValidationError::InvalidValue {
+                field: "country_code".into(),
+                reason: "Expected a two letter ISO country code".into(),
+            });
+        }
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            user_id,
+            kind: AddressKind::from_str(&request.kind)?,
+            line1: required_text(&request.line1, "line1", 128)?,
+            line2: optional_text(request.line2.as_deref(), "line2", 128)?,
+            city: required_text(&request.city, "city", 64)?,
+            region: optional_text(request.region.as_deref(), "region", 64)?,
+            postal_code,
+            country_code,
+            created_at: Utc::now(),
+            superseded_at: None,
+        })
+    }
+
+    // One line form recorded in the change history
+    pub fn summary(&self) -> String {
+        [
+            Some(self.line1.as_str()),
+            self.line2.as_deref(),
+            Some(self.city.as_str()),
+            self.region.as_deref(),
+            Some(self.postal_code.as_str()),
+            Some(self.country_code.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct CustomerPhoneEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: PhoneKind,
+    pub number: String,
+    pub created_at: DateTime<Utc>,
+    pub superseded_at: Option<DateTime<Utc>>,
+}
+
+impl CustomerPhoneEntity {
+    pub fn new(user_id: Uuid, kind: PhoneKind, number: String) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            kind,
+            number,
+            created_at: Utc::now(),
+            superseded_at: None,
+        }
+    }
+}
+
+// Sent to the new address, only a hash of it is stored
+pub struct EmailChangeToken(String);
+
+impl EmailChangeToken {
+    pub fn generate() -> Self {
+        Self(Alphanumeric.sample_string(&mut rand::rng(), EMAIL_CHANGE_TOKEN_LEN))
+    }
+
+    pub fn hash(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+}
+
+impl AsRef<str> for EmailChangeToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct EmailChangeEntity {
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub new_email: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl EmailChangeEntity {
+    pub fn new(user_id: Uuid, new_email: &Email, token: &EmailChangeToken, ttl_secs: u64) -> Self {
+        let created_at = Utc::now();
+
+        Self {
+            token_hash: EmailChangeToken::hash(token.as_ref()),
+            user_id,
+            new_email: new_email.to_string(),
+            created_at,
+            expires_at: created_at + chrono::Duration::seconds(ttl_secs as i64),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ProfileChangeEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub reason: ProfileChangeReason,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl ProfileChangeEntity {
+    pub fn new(
+        user_id: Uuid,
+        field: &str,
+        old_value: Option<String>,
+        new_value: Option<String>,
+        reason: ProfileChangeReason,
+        changed_by: Uuid,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            field: field.into(),
+            old_value,
+            new_value,
+            reason,
+            changed_by: Some(changed_by),
+            changed_at: Utc::now(),
+        }
+    }
+}
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 100;
+
+// Input is matched with LIKE, so wildcards typed by staff are taken literally
+fn like_prefix(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 1);
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('%');
+    escaped
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CustomerSearch {
+    pub text: Option<String>,
+    pub email_prefix: Option<String>,
+    pub username_prefix: Option<String>,
+    pub account_number_prefix: Option<String>,
+    pub iban_prefix: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub limit: i64,
+}
+
+impl CustomerSearch {
+    pub fn parse(query: CustomerSearchQuery) -> Result<Self, ValidationError> {
+        fn non_empty(value: Option<String>) -> Option<String> {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        }
+
+        let text = non_empty(query.q);
+        if let Some(text) = &text
+            && text.chars().count() < 2
+        {
+            return Err(ValidationError::TooShort {
+                field: "q".into(),
+                min: 2,
+            });
+        }
+
+        let search = Self {
+            text,
+            email_prefix: non_empty(query.email).map(|e| like_prefix(&e.to_lowercase())),
+            username_prefix: non_empty(query.username).map(|u| like_prefix(&u.to_lowercase())),
+            account_number_prefix: non_empty(query.account_number).map(|n| like_prefix(&n)),
+            // IBANs are often written in groups of four
+            iban_prefix: non_empty(query.iban)
+                .map(|i| like_prefix(&i.replace(' ', "").to_uppercase())),
+            date_of_birth: query.date_of_birth,
+            limit: query
+                .limit
+                .unwrap_or(DEFAULT_SEARCH_LIMIT)
+                .clamp(1, MAX_SEARCH_LIMIT),
+        };
+
+        if search.text.is_none()
+            && search.email_prefix.is_none()
+            && search.username_prefix.is_none()
+            && search.account_number_prefix.is_none()
+            && search.iban_prefix.is_none()
+            && search.date_of_birth.is_none()
+        {
+            return Err(ValidationError::MissingField(
+                "q, email, username, account_number, iban or date_of_birth".into(),
+            ));
+        }
+
+        Ok(search)
+    }
+
+    // Free text can appear anywhere in the name, username or email
+    pub fn text_pattern(&self) -> Option<String> {
+        self.text.as_ref().map(|t| format!("%{}", like_prefix(t)))
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct CustomerSummaryEntity {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub is_active: bool,
+    pub is_verified: bool,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AccountOverviewEntity {
+    pub id: Uuid,
+    pub account_number: String,
+    pub iban: String,
+    pub currency: String,
+    pub status: UserAccountStatus,
+    pub class_name: Option<String>,
+    pub balance_cents: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+// A journal entry seen from the customer account it was posted to
+#[derive(Debug, sqlx::FromRow)]
+pub struct AccountEntryEntity {
+    pub id: Uuid,
+    pub user_account_id: Uuid,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    pub amount_cents: i64,
+    pub created_date: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CustomerAddressEntity, CustomerSearch, EmailChangeToken, ProfileChangeReason, parse_phone,
+    };
+    use crate::customer::schemas::{AddressRequest, CustomerSearchQuery};
+    use claims::{assert_err, assert_ok};
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    fn address() -> AddressRequest {
+        AddressRequest {
+            kind: "residential".into(),
+            line1: " 221B Baker Street ".into(),
+            line2: Some("  ".into()),
+            city: "London".into(),
+            region: None,
+            postal_code: "nw1 6xe".into(),
+            country_code: "gb".into(),
+        }
+    }
+
+    #[test]
+    fn phone_numbers_are_normalized_to_e164() {
+        assert_eq!(
+            parse_phone("+49 (151) 123-456.78").unwrap(),
+            "+4915112345678"
+        );
+    }
+
+    #[test]
+    fn phone_numbers_without_a_country_code_are_rejected() {
+        for number in ["015112345678", "+0151123456", "+49151abc", "+1234567", ""] {
+            let _ = assert_err!(parse_phone(number));
+        }
+    }
+
+    #[test]
+    fn addresses_are_trimmed_and_codes_uppercased() {
+        let entity = assert_ok!(CustomerAddressEntity::new(Uuid::now_v7(), &address()));
+
+        assert_eq!(entity.line1, "221B Baker Street");
+        assert_eq!(entity.line2, None);
+        assert_eq!(entity.postal_code, "NW1 6XE");
+        assert_eq!(entity.country_code, "GB");
+        assert_eq!(entity.summary(), "221B Baker Street, London, NW1 6XE, GB");
+    }
+
+    #[test]
+    fn addresses_with_missing_or_invalid_parts_are_rejected() {
+        let mut request = address();
+        request.line1 = " ".into();
+        let _ = assert_err!(CustomerAddressEntity::new(Uuid::now_v7(), &request));
+
+        let mut request = address();
+        request.country_code = "GBR".into();
+        let _ = assert_err!(CustomerAddressEntity::new(Uuid::now_v7(), &request));
+
+        let mut request = address();
+        request.kind = "holiday".into();
+        let _ = assert_err!(CustomerAddressEntity::new(Uuid::now_v7(), &request));
+    }
+
+    #[test]
+    fn email_change_tokens_are_hashed_consistently() {
+        let token = EmailChangeToken::generate();
+
+        assert_eq!(
+            EmailChangeToken::hash(token.as_ref()),
+            EmailChangeToken::hash(token.as_ref())
+        );
+        assert_ne!(
+            EmailChangeToken::hash(token.as_ref()),
+            EmailChangeToken::hash(EmailChangeToken::generate().as_ref())
+        );
+    }
+
+    #[test]
+    fn unknown_reason_codes_are_rejected() {
+        assert_ok!(ProfileChangeReason::from_str("legal_name_change"));
+        let _ = assert_err!(ProfileChangeReason::from_str("because"));
+    }
+
+    fn search() -> CustomerSearchQuery {
+        CustomerSearchQuery {
+            q: None,
+            email: None,
+            username: None,
+            account_number: None,
+            iban: None,
+            date_of_birth: None,
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn search_needs_at_least_one_criterion() {
+        let mut query = search();
+        query.q = Some("   ".into());
+
+        let _ = assert_err!(CustomerSearch::parse(query));
+    }
+
+    #[test]
+    fn search_input_is_normalized_for_prefix_matching() {
+        let mut query = search();
+        query.email = Some(" Jane_Doe%@".into());
+        query.iban = Some("de89 3704 0044".into());
+        query.limit = Some(10_000);
+
+        let parsed = assert_ok!(CustomerSearch::parse(query));
+
+        assert_eq!(parsed.email_prefix.as_deref(), Some(r"jane\_doe\%@%"));
+        assert_eq!(parsed.iban_prefix.as_deref(), Some("DE8937040044%"));
+        assert_eq!(parsed.limit, 100);
+    }
+
+    #[test]
+    fn free_text_search_needs_two_characters() {
+        let mut query = search();
+        query.q = Some("j".into());
+
+        let _ = assert_err!(CustomerSearch::parse(query));
+
+        let mut query = search();
+        query.q = Some(" 50% ".into());
+
+        let parsed = assert_ok!(CustomerSearch::parse(query));
+        assert_eq!(parsed.text_pattern().as_deref(), Some(r"%50\%%"));
+    }
+}
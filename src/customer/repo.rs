@@ -0,0 +1,392 @@
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::customer::models::{
+    AccountEntryEntity, AccountOverviewEntity, AddressKind, CustomerAddressEntity,
+    CustomerPhoneEntity, CustomerSearch, CustomerSummaryEntity, EmailChangeEntity, PhoneKind,
+    ProfileChangeEntity,
+};
+
+const ADDRESS_COLUMNS: &str = "id, user_id, kind, line1, line2, city, region, postal_code, country_code, created_at, superseded_at";
+
+const PHONE_COLUMNS: &str = "id, user_id, kind, number, created_at, superseded_at";
+
+// Must match the expression of idx_tuser_full_name_trgm for the index to be used
+const FULL_NAME: &str = "(u.first_name || ' ' || u.last_name)";
+
+const CHANGE_COLUMNS: &str =
+    "id, user_id, field, old_value, new_value, reason, changed_by, changed_at";
+
+pub struct CustomerRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> CustomerRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Retrieving current addresses", skip(self))]
+    pub async fn fetch_addresses(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<CustomerAddressEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CustomerAddressEntity>(&format!(
+            "SELECT {} FROM customer_address WHERE user_id=$1 AND superseded_at IS NULL ORDER BY kind",
+            ADDRESS_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Reads inside the transaction so the superseded row is the one being replaced
+    #[tracing::instrument("Retrieving current address of a kind", skip(self))]
+    pub async fn fetch_current_address(
+        &mut self,
+        user_id: Uuid,
+        kind: AddressKind,
+    ) -> Result<Option<CustomerAddressEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CustomerAddressEntity>(&format!(
+            "SELECT {} FROM customer_address WHERE user_id=$1 AND kind=$2 AND superseded_at IS NULL FOR UPDATE",
+            ADDRESS_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(kind)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving customer address", skip(self, address))]
+    pub async fn create_address(
+        &mut self,
+        address: &CustomerAddressEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE customer_address SET superseded_at=$3
+                WHERE user_id=$1 AND kind=$2 AND superseded_at IS NULL",
+        )
+        .bind(address.user_id)
+        .bind(address.kind)
+        .bind(address.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO customer_address(id, user_id, kind, line1, line2, city, region, postal_code, country_code, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(address.id)
+        .bind(address.user_id)
+        .bind(address.kind)
+        .bind(&address.line1)
+        .bind(&address.line2)
+        .bind(&address.city)
+        .bind(&address.region)
+        .bind(&address.postal_code)
+        .bind(&address.country_code)
+        .bind(address.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving current phone numbers", skip(self))]
+    pub async fn fetch_phones(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<CustomerPhoneEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CustomerPhoneEntity>(&format!(
+            "SELECT {} FROM customer_phone WHERE user_id=$1 AND superseded_at IS NULL ORDER BY kind",
+            PHONE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving current phone number of a kind", skip(self))]
+    pub async fn fetch_current_phone(
+        &mut self,
+        user_id: Uuid,
+        kind: PhoneKind,
+    ) -> Result<Option<CustomerPhoneEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CustomerPhoneEntity>(&format!(
+            "SELECT {} FROM customer_phone WHERE user_id=$1 AND kind=$2 AND superseded_at IS NULL FOR UPDATE",
+            PHONE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(kind)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving customer phone number", skip(self, phone))]
+    pub async fn create_phone(&mut self, phone: &CustomerPhoneEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE customer_phone SET superseded_at=$3
+                WHERE user_id=$1 AND kind=$2 AND superseded_at IS NULL",
+        )
+        .bind(phone.user_id)
+        .bind(phone.kind)
+        .bind(phone.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO customer_phone(id, user_id, kind, number, created_at)
+                VALUES($1, $2, $3, $4, $5)",
+        )
+        .bind(phone.id)
+        .bind(phone.user_id)
+        .bind(phone.kind)
+        .bind(&phone.number)
+        .bind(phone.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Only the latest request stays valid
+    #[tracing::instrument("Saving email change", skip(self, change))]
+    pub async fn create_email_change(
+        &mut self,
+        change: &EmailChangeEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM email_change WHERE user_id=$1")
+            .bind(change.user_id)
+            .execute(&mut **self.tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO email_change(token_hash, user_id, new_email, created_at, expires_at)
+                VALUES($1, $2, $3, $4, $5)",
+        )
+        .bind(&change.token_hash)
+        .bind(change.user_id)
+        .bind(&change.new_email)
+        .bind(change.created_at)
+        .bind(change.expires_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving pending email change", skip(self))]
+    pub async fn fetch_pending_email(&self, user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        let result = sqlx::query_scalar::<_, String>(
+            "SELECT new_email FROM email_change WHERE user_id=$1 AND expires_at > CURRENT_TIMESTAMP",
+        )
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Returns the change only if it was still outstanding, so a link can be used once
+    #[tracing::instrument("Consuming email change", skip(self, token_hash))]
+    pub async fn consume_email_change(
+        &mut self,
+        token_hash: &str,
+    ) -> Result<Option<EmailChangeEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, EmailChangeEntity>(
+            "DELETE FROM email_change WHERE token_hash=$1 AND expires_at > CURRENT_TIMESTAMP
+                RETURNING token_hash, user_id, new_email, created_at, expires_at",
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving profile changes", skip(self, changes), fields(count = changes.len()))]
+    pub async fn create_changes(
+        &mut self,
+        changes: &[ProfileChangeEntity],
+    ) -> Result<(), sqlx::Error> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        QueryBuilder::<Postgres>::new(
+            "INSERT INTO profile_change(id, user_id, field, old_value, new_value, reason, changed_by, changed_at) ",
+        )
+        .push_values(changes, |mut row, change| {
+            row.push_bind(change.id)
+                .push_bind(change.user_id)
+                .push_bind(&change.field)
+                .push_bind(&change.old_value)
+                .push_bind(&change.new_value)
+                .push_bind(change.reason)
+                .push_bind(change.changed_by)
+                .push_bind(change.changed_at);
+        })
+        .build()
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving profile changes", skip(self))]
+    pub async fn fetch_changes(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ProfileChangeEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProfileChangeEntity>(&format!(
+            "SELECT {} FROM profile_change WHERE user_id=$1 ORDER BY changed_at DESC, id DESC",
+            CHANGE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Searching customers", skip(self))]
+    pub async fn search(
+        &self,
+        search: &CustomerSearch,
+    ) -> Result<Vec<CustomerSummaryEntity>, sqlx::Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT u.id, u.first_name, u.last_name, u.username, u.email, u.date_of_birth, u.is_active, u.is_verified
+                FROM tuser u WHERE u.access_role='customer'",
+        );
+
+        if let Some(text) = &search.text
+            && let Some(pattern) = search.text_pattern()
+        {
+            builder
+                .push(format!(" AND ({} ILIKE ", FULL_NAME))
+                .push_bind(pattern.clone())
+                .push(" OR u.username ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR u.email ILIKE ")
+                .push_bind(pattern)
+                .push(format!(" OR {} % ", FULL_NAME))
+                .push_bind(text)
+                .push(")");
+        }
+
+        if let Some(prefix) = &search.email_prefix {
+            builder.push(" AND lower(u.email) LIKE ").push_bind(prefix);
+        }
+
+        if let Some(prefix) = &search.username_prefix {
+            builder
+                .push(" AND lower(u.username) LIKE ")
+                .push_bind(prefix);
+        }
+
+        if let Some(date_of_birth) = search.date_of_birth {
+            builder
+                .push(" AND u.date_of_birth = ")
+                .push_bind(date_of_birth);
+        }
+
+        if let Some(prefix) = &search.account_number_prefix {
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM user_account a WHERE a.user_id = u.id AND a.account_number LIKE ")
+                .push_bind(prefix)
+                .push(")");
+        }
+
+        if let Some(prefix) = &search.iban_prefix {
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM user_account a WHERE a.user_id = u.id AND a.iban LIKE ")
+                .push_bind(prefix)
+                .push(")");
+        }
+
+        builder.push(" ORDER BY ");
+        if let Some(text) = &search.text {
+            builder
+                .push(format!("GREATEST(similarity({}, ", FULL_NAME))
+                .push_bind(text)
+                .push("), similarity(u.username, ")
+                .push_bind(text)
+                .push("), similarity(u.email, ")
+                .push_bind(text)
+                .push(")) DESC, ");
+        }
+        builder
+            .push("u.last_name, u.first_name, u.id LIMIT ")
+            .push_bind(search.limit);
+
+        let result = builder
+            .build_query_as::<CustomerSummaryEntity>()
+            .fetch_all(self.pool)
+            .await?;
+
+        Ok(result)
+    }
+
+    // Balances are what each account holds on deposit liability chart accounts, credits positive
+    #[tracing::instrument("Retrieving customer accounts with balances", skip(self))]
+    pub async fn fetch_account_overviews(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<AccountOverviewEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AccountOverviewEntity>(
+            "SELECT a.id, a.account_number, a.iban, a.currency, a.status, c.name AS class_name,
+                    COALESCE(b.balance_cents, 0)::BIGINT AS balance_cents, a.created_at
+                FROM user_account a
+                LEFT JOIN account_class c ON c.id = a.account_class
+                LEFT JOIN (
+                    SELECT lb.user_account_id AS account_id, SUM(lb.credit_cents) AS balance_cents
+                        FROM journal_line_balance lb
+                        JOIN user_account ua ON ua.id = lb.user_account_id
+                        WHERE ua.user_id=$1 AND lb.coa_type = 'liability'
+                        GROUP BY lb.user_account_id
+                ) b ON b.account_id = a.id
+                WHERE a.user_id=$1 ORDER BY a.created_at",
+        )
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Amounts are signed against each account's own chart account, credits positive
+    #[tracing::instrument("Retrieving recent customer journal entries", skip(self))]
+    pub async fn fetch_recent_entries(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<AccountEntryEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AccountEntryEntity>(
+            "SELECT je.id, je.user_account_id, je.transaction_ref, je.description, je.created_date,
+                    COALESCE(SUM(CASE WHEN lb.coa_id = a.coa_id THEN lb.credit_cents ELSE 0 END), 0)::BIGINT
+                        AS amount_cents
+                FROM journal_entry je
+                JOIN user_account a ON a.id = je.user_account_id
+                LEFT JOIN journal_line_balance lb ON lb.journal_entry_id = je.id
+                WHERE a.user_id=$1
+                GROUP BY je.id
+                ORDER BY je.created_date DESC, je.id DESC
+                LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -1,12 +1,25 @@
+use actix_multipart::form::MultipartForm;
 use actix_web::{HttpResponse, cookie::Cookie, http::header, web};
 use uuid::Uuid;
 
 use crate::account::{schemas::UserAccountBalance, service::AccountService};
 use crate::authentication::{
-    schemas::LoginRequest, service::AuthService, session_state::CustomerSession,
+    models::ClientInfo, schemas::LoginRequest, service::AuthService,
+    session_state::CustomerSession, token::SessionClaims,
 };
 use crate::base::StdResponse;
 use crate::config::state::AppState;
+use crate::customer::{
+    schemas::{
+        AddressRequest, AddressResponse, CustomerProfileResponse, EmailChangeRequest, PhoneRequest,
+        PhoneResponse, ProfileChangeResponse,
+    },
+    service::CustomerService,
+};
+use crate::identity_verify::{
+    schemas::{KycCaseResponse, KycDocumentResponse, KycStatusResponse, KycUploadForm},
+    service::KycService,
+};
 use crate::user::{schemas::UserRegisterRequest, service::UserService};
 
 // Create account
@@ -37,17 +50,18 @@ pub async fn confirm_customer(
     Ok(HttpResponse::Ok().json(StdResponse::from("Successful activation")))
 }
 
-#[tracing::instrument("Customer login", skip(app_state, session))]
+#[tracing::instrument("Customer login", skip(app_state, session, client))]
 #[utoipa::path(post, path="/login", responses((status=200, body=StdResponse, description="Customer login successful"), (status=401, description="Customer login unsuccessful")))]
 pub async fn customer_login(
     app_state: web::Data<AppState>,
     payload: web::Json<LoginRequest>,
     session: CustomerSession,
+    client: ClientInfo,
 ) -> actix_web::Result<HttpResponse> {
     let auth_service = AuthService::from(&app_state);
 
     let (access_token, refresh_token) = auth_service
-        .authenticate_user(payload.into_inner(), session)
+        .authenticate_user(payload.into_inner(), session, client)
         .await?;
 
     Ok(HttpResponse::Ok()
@@ -61,14 +75,155 @@ pub async fn customer_login(
 }
 
 // KYC
-#[tracing::instrument("Upload user docs")]
-#[utoipa::path(post, path="/{id}/kyc", responses((status=200, body=StdResponse, description="Docs uploaded successfully"), (status=409, description="Docs failed to upload")))]
-pub async fn upload_user_docs() {}
+#[tracing::instrument("Upload user docs", skip(app_state, claims, form))]
+#[utoipa::path(post, path="/kyc/documents", request_body(content=KycUploadForm, content_type="multipart/form-data"), responses((status=200, body=KycDocumentResponse, description="Docs uploaded successfully"), (status=400, description="Unsupported or invalid document"), (status=409, description="Document already uploaded")))]
+pub async fn upload_user_docs(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    form: MultipartForm<KycUploadForm>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service
+        .upload_document(&claims, form.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch user docs", skip(app_state, claims))]
+#[utoipa::path(get, path="/kyc/documents", responses((status=200, body=Vec<KycDocumentResponse>, description="Documents uploaded by the customer")))]
+pub async fn fetch_user_docs(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service.list_documents(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Submit kyc case", skip(app_state, claims))]
+#[utoipa::path(post, path="/kyc/submit", responses((status=200, body=KycCaseResponse, description="Documents submitted for review"), (status=400, description="Identity document or selfie missing"), (status=409, description="A case is already open or decided")))]
+pub async fn submit_kyc_case(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
 
-#[tracing::instrument("Customer profile status")]
+    let response = kyc_service.submit_case(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Customer profile status", skip(app_state, claims))]
 // Used to confirm if a user has been verified
-#[utoipa::path(post, path="/user/{id}/kyc", responses((status=200, body=StdResponse, description="Successfull verification"), (status=409, description="Verification failed")))]
-pub async fn customer_profile_status() {}
+#[utoipa::path(get, path="/kyc/status", responses((status=200, body=KycStatusResponse, description="Verification status of the customer")))]
+pub async fn customer_profile_status(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service.case_status(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+// Profile
+#[tracing::instrument("Fetch customer profile", skip(app_state, claims))]
+#[utoipa::path(get, path="/profile", responses((status=200, body=CustomerProfileResponse, description="Profile with current addresses and phone numbers")))]
+pub async fn fetch_customer_profile(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .fetch_profile(*claims.get_user_id())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Request email change", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/profile/email", request_body=EmailChangeRequest, responses((status=200, body=StdResponse, description="Confirmation link sent to the new address"), (status=400, description="Invalid or unchanged email"), (status=409, description="Email already in use")))]
+pub async fn request_email_change(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<EmailChangeRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    customer_service
+        .request_email_change(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from(
+        "Check your new inbox to confirm the change",
+    )))
+}
+
+#[tracing::instrument("Confirm email change", skip(app_state, token))]
+#[utoipa::path(get, path="/customer/email/confirm/{token}", responses((status=200, body=StdResponse, description="Email changed"), (status=401, description="Link expired or already used"), (status=409, description="Email already in use")))]
+pub async fn confirm_email_change(
+    app_state: web::Data<AppState>,
+    token: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    customer_service
+        .confirm_email_change(token.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Email successfully changed")))
+}
+
+#[tracing::instrument("Update customer address", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/profile/address", request_body=AddressRequest, responses((status=200, body=AddressResponse, description="Address replaced, the previous one is kept in the history"), (status=400, description="Invalid address")))]
+pub async fn update_customer_address(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<AddressRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .update_address(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Update customer phone", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/profile/phone", request_body=PhoneRequest, responses((status=200, body=PhoneResponse, description="Phone number replaced, the previous one is kept in the history"), (status=400, description="Invalid phone number")))]
+pub async fn update_customer_phone(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<PhoneRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .update_phone(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Customer profile history", skip(app_state, claims))]
+#[utoipa::path(get, path="/profile/history", responses((status=200, body=Vec<ProfileChangeResponse>, description="Changes to the profile, newest first")))]
+pub async fn customer_profile_history(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service.list_changes(*claims.get_user_id()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
 
 #[tracing::instrument("Fetch balance", skip(app_state))]
 #[utoipa::path(get, path="/balance/{account_id}", responses((status=200, body=UserAccountBalance, description="Successfull balance check"), (status=409, description="Failed balance check")))]
@@ -1 +1,255 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use uuid::Uuid;
 
+use crate::customer::models::{
+    AccountEntryEntity, AccountOverviewEntity, CustomerAddressEntity, CustomerPhoneEntity,
+    CustomerSummaryEntity, ProfileChangeEntity,
+};
+use crate::identity_verify::schemas::KycCaseResponse;
+use crate::screening::schemas::ScreeningHitResponse;
+use crate::user::models::UserEntity;
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct EmailChangeRequest {
+    #[schema(example = "new.address@example.com")]
+    pub email: String,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct AddressRequest {
+    #[schema(example = "residential")]
+    pub kind: String,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: String,
+    #[schema(example = "DE")]
+    pub country_code: String,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct PhoneRequest {
+    #[schema(example = "mobile")]
+    pub kind: String,
+    #[schema(example = "+4915112345678")]
+    pub number: String,
+}
+
+// Identity details can only be corrected by staff, every change needs a reason code
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct StaffProfileUpdateRequest {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    #[schema(example = "legal_name_change")]
+    pub reason: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AddressResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub region: Option<String>,
+    pub postal_code: String,
+    pub country_code: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CustomerAddressEntity> for AddressResponse {
+    fn from(value: CustomerAddressEntity) -> Self {
+        Self {
+            id: value.id,
+            kind: value.kind.to_string(),
+            line1: value.line1,
+            line2: value.line2,
+            city: value.city,
+            region: value.region,
+            postal_code: value.postal_code,
+            country_code: value.country_code,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct PhoneResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub number: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CustomerPhoneEntity> for PhoneResponse {
+    fn from(value: CustomerPhoneEntity) -> Self {
+        Self {
+            id: value.id,
+            kind: value.kind.to_string(),
+            number: value.number,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct CustomerProfileResponse {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub email: String,
+    // Waiting for the customer to follow the link sent to it
+    pub pending_email: Option<String>,
+    pub is_verified: bool,
+    pub addresses: Vec<AddressResponse>,
+    pub phones: Vec<PhoneResponse>,
+}
+
+impl CustomerProfileResponse {
+    pub fn new(
+        user: UserEntity,
+        pending_email: Option<String>,
+        addresses: Vec<CustomerAddressEntity>,
+        phones: Vec<CustomerPhoneEntity>,
+    ) -> Self {
+        Self {
+            id: user.id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            date_of_birth: user.date_of_birth,
+            email: user.email,
+            pending_email,
+            is_verified: user.is_verified,
+            addresses: addresses.into_iter().map(AddressResponse::from).collect(),
+            phones: phones.into_iter().map(PhoneResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ProfileChangeResponse {
+    pub id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub reason: String,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl From<ProfileChangeEntity> for ProfileChangeResponse {
+    fn from(value: ProfileChangeEntity) -> Self {
+        Self {
+            id: value.id,
+            field: value.field,
+            old_value: value.old_value,
+            new_value: value.new_value,
+            reason: value.reason.to_string(),
+            changed_by: value.changed_by,
+            changed_at: value.changed_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct CustomerSearchQuery {
+    // Matched against full name, username and email, tolerates typos
+    pub q: Option<String>,
+    pub email: Option<String>,
+    pub username: Option<String>,
+    pub account_number: Option<String>,
+    pub iban: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct CustomerSummaryResponse {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub is_active: bool,
+    pub is_verified: bool,
+}
+
+impl From<CustomerSummaryEntity> for CustomerSummaryResponse {
+    fn from(value: CustomerSummaryEntity) -> Self {
+        Self {
+            id: value.id,
+            first_name: value.first_name,
+            last_name: value.last_name,
+            username: value.username,
+            email: value.email,
+            date_of_birth: value.date_of_birth,
+            is_active: value.is_active,
+            is_verified: value.is_verified,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AccountOverviewResponse {
+    pub id: Uuid,
+    pub account_number: String,
+    pub iban: String,
+    pub currency: String,
+    pub status: String,
+    pub class_name: Option<String>,
+    pub balance_cents: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AccountOverviewEntity> for AccountOverviewResponse {
+    fn from(value: AccountOverviewEntity) -> Self {
+        Self {
+            id: value.id,
+            account_number: value.account_number,
+            iban: value.iban,
+            currency: value.currency,
+            status: value.status.to_string(),
+            class_name: value.class_name,
+            balance_cents: value.balance_cents,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AccountEntryResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    // Credits to the account are positive
+    pub amount_cents: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<AccountEntryEntity> for AccountEntryResponse {
+    fn from(value: AccountEntryEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.user_account_id,
+            transaction_ref: value.transaction_ref,
+            description: value.description,
+            amount_cents: value.amount_cents,
+            created_at: value.created_date,
+        }
+    }
+}
+
+// Everything staff need to handle a customer enquiry in one call
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct CustomerOverviewResponse {
+    pub profile: CustomerProfileResponse,
+    pub kyc_case: Option<KycCaseResponse>,
+    pub accounts: Vec<AccountOverviewResponse>,
+    pub recent_entries: Vec<AccountEntryResponse>,
+    pub open_alerts: Vec<ScreeningHitResponse>,
+}
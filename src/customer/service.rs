@@ -1,11 +1,523 @@
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::base::{Email, Name};
 use crate::config::state::AppState;
+use crate::customer::{
+    models::{
+        CustomerAddressEntity, CustomerPhoneEntity, CustomerSearch, EmailChangeEntity,
+        EmailChangeToken, PhoneKind, ProfileChangeEntity, ProfileChangeReason, parse_phone,
+    },
+    schemas::{
+        AccountEntryResponse, AccountOverviewResponse, AddressRequest, AddressResponse,
+        CustomerOverviewResponse, CustomerProfileResponse, CustomerSearchQuery,
+        CustomerSummaryResponse, EmailChangeRequest, PhoneRequest, PhoneResponse,
+        ProfileChangeResponse, StaffProfileUpdateRequest,
+    },
+};
+use crate::identity_verify::schemas::KycCaseResponse;
+use crate::infra::pgdb::UnitofWork;
+use crate::screening::{
+    models::{ScreeningHitStatus, ScreeningSubject},
+    schemas::ScreeningHitResponse,
+    service::ScreeningService,
+};
+use crate::user::models::{AccessRole, UpdateUserEntity, UserEntity, check_minimum_age};
+
+// Enough to see what moved on the accounts lately without paging through the ledger
+const OVERVIEW_ENTRY_LIMIT: i64 = 20;
 
 pub struct CustomerService<'a> {
-    _app_state: &'a AppState,
+    app_state: &'a AppState,
 }
 
 impl<'a> CustomerService<'a> {
-    pub fn from(_app_state: &'a AppState) -> Self {
-        Self { _app_state }
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // Staff ids are never valid here, profiles only exist for customers
+    async fn fetch_customer(
+        uow: &mut UnitofWork<'_>,
+        customer_id: Uuid,
+    ) -> Result<UserEntity, AppError> {
+        match uow
+            .authentication()
+            .fetch_user_by_id(customer_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) if u.access_role == AccessRole::Customer => Ok(u),
+            _ => Err(DomainError::NotFound("customer".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Fetch customer profile", skip(self))]
+    pub async fn fetch_profile(
+        &self,
+        customer_id: Uuid,
+    ) -> Result<CustomerProfileResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let user = Self::fetch_customer(&mut uow, customer_id).await?;
+
+        let pending_email = uow
+            .customers()
+            .fetch_pending_email(customer_id)
+            .await
+            .to_app_err("Failed to fetch pending email change")?;
+
+        let addresses = uow
+            .customers()
+            .fetch_addresses(customer_id)
+            .await
+            .to_app_err("Failed to fetch customer addresses")?;
+
+        let phones = uow
+            .customers()
+            .fetch_phones(customer_id)
+            .await
+            .to_app_err("Failed to fetch customer phone numbers")?;
+
+        Ok(CustomerProfileResponse::new(
+            user,
+            pending_email,
+            addresses,
+            phones,
+        ))
+    }
+
+    #[tracing::instrument("Search customers", skip(self))]
+    pub async fn search_customers(
+        &self,
+        query: CustomerSearchQuery,
+    ) -> Result<Vec<CustomerSummaryResponse>, AppError> {
+        let search = CustomerSearch::parse(query)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let customers = uow
+            .customers()
+            .search(&search)
+            .await
+            .to_app_err("Failed to search customers")?;
+
+        Ok(customers
+            .into_iter()
+            .map(CustomerSummaryResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch customer overview", skip(self))]
+    pub async fn fetch_overview(
+        &self,
+        customer_id: Uuid,
+    ) -> Result<CustomerOverviewResponse, AppError> {
+        let profile = self.fetch_profile(customer_id).await?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let kyc_case = match uow
+            .kyc()
+            .fetch_case_by_user(customer_id)
+            .await
+            .to_app_err("Failed to fetch kyc case")?
+        {
+            Some(case) => {
+                let notes = uow
+                    .kyc()
+                    .fetch_notes(case.id)
+                    .await
+                    .to_app_err("Failed to fetch kyc case notes")?;
+                Some(KycCaseResponse::from(case, notes))
+            }
+            None => None,
+        };
+
+        let accounts = uow
+            .customers()
+            .fetch_account_overviews(customer_id)
+            .await
+            .to_app_err("Failed to fetch customer accounts")?;
+
+        let recent_entries = uow
+            .customers()
+            .fetch_recent_entries(customer_id, OVERVIEW_ENTRY_LIMIT)
+            .await
+            .to_app_err("Failed to fetch recent journal entries")?;
+
+        let open_alerts = uow
+            .screening()
+            .fetch_user_hits(customer_id, ScreeningHitStatus::Open)
+            .await
+            .to_app_err("Failed to fetch screening hits")?;
+
+        Ok(CustomerOverviewResponse {
+            profile,
+            kyc_case,
+            accounts: accounts
+                .into_iter()
+                .map(AccountOverviewResponse::from)
+                .collect(),
+            recent_entries: recent_entries
+                .into_iter()
+                .map(AccountEntryResponse::from)
+                .collect(),
+            open_alerts: open_alerts
+                .into_iter()
+                .map(ScreeningHitResponse::from)
+                .collect(),
+        })
+    }
+
+    #[tracing::instrument("Fetch profile history", skip(self))]
+    pub async fn list_changes(
+        &self,
+        customer_id: Uuid,
+    ) -> Result<Vec<ProfileChangeResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_customer(&mut uow, customer_id).await?;
+
+        let changes = uow
+            .customers()
+            .fetch_changes(customer_id)
+            .await
+            .to_app_err("Failed to fetch profile changes")?;
+
+        Ok(changes
+            .into_iter()
+            .map(ProfileChangeResponse::from)
+            .collect())
+    }
+
+    // The current address keeps working until the new one is confirmed
+    #[tracing::instrument("Request email change", skip(self, claims, request))]
+    pub async fn request_email_change(
+        &self,
+        claims: &SessionClaims,
+        request: EmailChangeRequest,
+    ) -> Result<(), AppError> {
+        let email = Email::parse(request.email.trim().to_lowercase())?;
+        let user_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let user = Self::fetch_customer(&mut uow, user_id).await?;
+
+        if user.email == email.as_ref() {
+            Err(ValidationError::InvalidValue {
+                field: "email".into(),
+                reason: "This is already the email on the profile".into(),
+            })?
+        }
+
+        if uow
+            .authentication()
+            .fetch_password_by_email(email.as_ref())
+            .await
+            .to_app_err("Failed to fetch user entity")?
+            .is_some()
+        {
+            Err(DomainError::Duplicate("email".into()))?
+        }
+
+        let token = EmailChangeToken::generate();
+        let change = EmailChangeEntity::new(
+            user_id,
+            &email,
+            &token,
+            self.app_state.activate_handler.activate_ttl(),
+        );
+
+        uow.customers()
+            .create_email_change(&change)
+            .await
+            .to_app_err("Failed to save email change")?;
+
+        // The link is only sent once its token is saved
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit email change request")?;
+
+        self.app_state
+            .email_client
+            .send_email_change_email(
+                &format!("{}/customer", self.app_state.base_uri.0),
+                email.as_ref(),
+                &user.first_name,
+                token.as_ref(),
+                "Thalia Corp.",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Confirm email change", skip(self, token))]
+    pub async fn confirm_email_change(&self, token: String) -> Result<(), AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        // Gone once used, superseded by a newer request or past its expiry
+        let change = match uow
+            .customers()
+            .consume_email_change(&EmailChangeToken::hash(&token))
+            .await
+            .to_app_err("Failed to consume email change")?
+        {
+            Some(c) => c,
+            None => Err(AuthError::Expired("email change link".into()))?,
+        };
+
+        let user = Self::fetch_customer(&mut uow, change.user_id).await?;
+
+        // The address may have been taken by someone else since the request
+        uow.users()
+            .update(&UpdateUserEntity::email_update(
+                user.id,
+                change.new_email.clone(),
+            ))
+            .await
+            .to_app_err("Failed to update customer email")?;
+
+        uow.customers()
+            .create_changes(&[ProfileChangeEntity::new(
+                user.id,
+                "email",
+                Some(user.email),
+                Some(change.new_email),
+                ProfileChangeReason::CustomerRequest,
+                user.id,
+            )])
+            .await
+            .to_app_err("Failed to save profile change")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit email change")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Update customer address", skip(self, claims, request))]
+    pub async fn update_address(
+        &self,
+        claims: &SessionClaims,
+        request: AddressRequest,
+    ) -> Result<AddressResponse, AppError> {
+        let user_id = *claims.get_user_id();
+        let address = CustomerAddressEntity::new(user_id, &request)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_customer(&mut uow, user_id).await?;
+
+        let previous = uow
+            .customers()
+            .fetch_current_address(user_id, address.kind)
+            .await
+            .to_app_err("Failed to fetch customer address")?;
+
+        uow.customers()
+            .create_address(&address)
+            .await
+            .to_app_err("Failed to save customer address")?;
+
+        uow.customers()
+            .create_changes(&[ProfileChangeEntity::new(
+                user_id,
+                &format!("{}_address", address.kind),
+                previous.map(|a| a.summary()),
+                Some(address.summary()),
+                ProfileChangeReason::CustomerRequest,
+                user_id,
+            )])
+            .await
+            .to_app_err("Failed to save profile change")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit address update")?;
+
+        Ok(AddressResponse::from(address))
+    }
+
+    #[tracing::instrument("Update customer phone", skip(self, claims, request))]
+    pub async fn update_phone(
+        &self,
+        claims: &SessionClaims,
+        request: PhoneRequest,
+    ) -> Result<PhoneResponse, AppError> {
+        let user_id = *claims.get_user_id();
+        let kind = PhoneKind::from_str(&request.kind)?;
+        let phone = CustomerPhoneEntity::new(user_id, kind, parse_phone(&request.number)?);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_customer(&mut uow, user_id).await?;
+
+        let previous = uow
+            .customers()
+            .fetch_current_phone(user_id, kind)
+            .await
+            .to_app_err("Failed to fetch customer phone number")?;
+
+        uow.customers()
+            .create_phone(&phone)
+            .await
+            .to_app_err("Failed to save customer phone number")?;
+
+        uow.customers()
+            .create_changes(&[ProfileChangeEntity::new(
+                user_id,
+                &format!("{}_phone", kind),
+                previous.map(|p| p.number),
+                Some(phone.number.clone()),
+                ProfileChangeReason::CustomerRequest,
+                user_id,
+            )])
+            .await
+            .to_app_err("Failed to save profile change")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit phone update")?;
+
+        Ok(PhoneResponse::from(phone))
+    }
+
+    // Corrections to identity details are made by staff against the customer's documents
+    #[tracing::instrument("Staff update customer profile", skip(self, claims, request))]
+    pub async fn update_details(
+        &self,
+        claims: &SessionClaims,
+        customer_id: Uuid,
+        request: StaffProfileUpdateRequest,
+    ) -> Result<CustomerProfileResponse, AppError> {
+        let reason = ProfileChangeReason::from_str(&request.reason)?;
+
+        let first_name = request
+            .first_name
+            .map(|n| Name::parse(n.trim().to_string(), "first_name"))
+            .transpose()?;
+        let last_name = request
+            .last_name
+            .map(|n| Name::parse(n.trim().to_string(), "last_name"))
+            .transpose()?;
+
+        if let Some(date_of_birth) = request.date_of_birth {
+            check_minimum_age(date_of_birth)?;
+        }
+
+        if first_name.is_none() && last_name.is_none() && request.date_of_birth.is_none() {
+            Err(ValidationError::MissingField(
+                "first_name, last_name or date_of_birth".into(),
+            ))?
+        }
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let user = Self::fetch_customer(&mut uow, customer_id).await?;
+        let staff_id = *claims.get_user_id();
+        let mut changes = Vec::new();
+
+        let first_name = first_name
+            .map(|n| n.as_ref().to_string())
+            .filter(|n| *n != user.first_name);
+        if let Some(new) = &first_name {
+            changes.push(ProfileChangeEntity::new(
+                customer_id,
+                "first_name",
+                Some(user.first_name.clone()),
+                Some(new.clone()),
+                reason,
+                staff_id,
+            ));
+        }
+
+        let last_name = last_name
+            .map(|n| n.as_ref().to_string())
+            .filter(|n| *n != user.last_name);
+        if let Some(new) = &last_name {
+            changes.push(ProfileChangeEntity::new(
+                customer_id,
+                "last_name",
+                Some(user.last_name.clone()),
+                Some(new.clone()),
+                reason,
+                staff_id,
+            ));
+        }
+
+        let date_of_birth = request
+            .date_of_birth
+            .filter(|d| Some(*d) != user.date_of_birth);
+        if let Some(new) = date_of_birth {
+            changes.push(ProfileChangeEntity::new(
+                customer_id,
+                "date_of_birth",
+                user.date_of_birth.map(|d| d.to_string()),
+                Some(new.to_string()),
+                reason,
+                staff_id,
+            ));
+        }
+
+        if !changes.is_empty() {
+            uow.users()
+                .update(&UpdateUserEntity::details_update(
+                    customer_id,
+                    first_name.clone(),
+                    last_name.clone(),
+                    date_of_birth,
+                ))
+                .await
+                .to_app_err("Failed to update customer")?;
+
+            uow.customers()
+                .create_changes(&changes)
+                .await
+                .to_app_err("Failed to save profile changes")?;
+
+            // A new name or date of birth can match a listing the old one didn't
+            let subject = ScreeningSubject {
+                user_id: customer_id,
+                first_name: first_name.unwrap_or(user.first_name),
+                last_name: last_name.unwrap_or(user.last_name),
+                date_of_birth: date_of_birth.or(user.date_of_birth),
+            };
+
+            ScreeningService::from(self.app_state)
+                .screen_customer(&mut uow, &subject)
+                .await?;
+
+            uow.commit()
+                .await
+                .to_app_err("Failed to commit customer profile update")?;
+
+            tracing::info!(%customer_id, %staff_id, %reason, fields = changes.len(), "Customer profile updated");
+        }
+
+        self.fetch_profile(customer_id).await
     }
 }
@@ -0,0 +1,10 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::fiscal::routes::create_fiscal_year,
+    crate::fiscal::routes::list_fiscal_years,
+    crate::fiscal::routes::set_fiscal_period_status,
+    crate::fiscal::routes::close_fiscal_year,
+))]
+pub struct FiscalApi;
@@ -0,0 +1,473 @@
+use chrono::{DateTime, Days, Months, NaiveDate, NaiveDateTime, Utc};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::base::error::{DomainError, ValidationError};
+use crate::ledger::models::EntryKind;
+use crate::reporting::financials::FxTable;
+use crate::staff::models::CoaType;
+
+// Income and expenses are swept here when a year is closed
+pub const RETAINED_EARNINGS_COA: &str = "3100";
+const PERIODS_PER_YEAR: u32 = 12;
+const MAX_NAME_LEN: usize = 32;
+
+// The year's closing entry, one per fiscal year
+pub fn closing_reference(year_name: &str) -> String {
+    format!("YEC-{}", year_name)
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "fiscal_period_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FiscalPeriodStatus {
+    Open,
+    // Only adjusting entries, and the year's closing entry
+    SoftClosed,
+    HardClosed,
+}
+
+impl FromStr for FiscalPeriodStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "open" => Ok(Self::Open),
+            "soft_closed" => Ok(Self::SoftClosed),
+            "hard_closed" => Ok(Self::HardClosed),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Use open, soft_closed or hard_closed".into(),
+            }),
+        }
+    }
+}
+
+impl FiscalPeriodStatus {
+    pub fn accepts(&self, kind: EntryKind) -> bool {
+        match self {
+            Self::Open => true,
+            Self::SoftClosed => kind != EntryKind::Regular,
+            Self::HardClosed => false,
+        }
+    }
+
+    // A soft-closed period can be opened again, a hard-closed one is final
+    pub fn check_change(&self, next: FiscalPeriodStatus) -> Result<(), DomainError> {
+        match self {
+            Self::HardClosed if next != Self::HardClosed => Err(DomainError::InvalidState(
+                "a hard-closed period can't be reopened".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FiscalPeriodEntity {
+    pub id: Uuid,
+    pub fiscal_year_id: Uuid,
+    pub period_number: i16,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: FiscalPeriodStatus,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FiscalYearEntity {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    // None on a year that is still open, or one closed with nothing to sweep
+    pub closing_entry_id: Option<Uuid>,
+    pub closed_by: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Twelve months from the first day, named after the calendar year it ends in unless a name
+// is given
+pub fn parse_fiscal_year(
+    name: Option<&str>,
+    start_date: NaiveDate,
+) -> Result<(String, NaiveDate), ValidationError> {
+    let end_date = start_date
+        .checked_add_months(Months::new(PERIODS_PER_YEAR))
+        .and_then(|d| d.checked_sub_days(Days::new(1)))
+        .ok_or(ValidationError::InvalidValue {
+            field: "start_date".into(),
+            reason: "Out of range".into(),
+        })?;
+
+    let name = match name.map(str::trim) {
+        Some(n) if n.is_empty() || n.len() > MAX_NAME_LEN => {
+            return Err(ValidationError::InvalidValue {
+                field: "name".into(),
+                reason: format!("Between 1 and {} characters", MAX_NAME_LEN),
+            });
+        }
+        Some(n) => n.to_string(),
+        None => format!("FY{}", end_date.format("%Y")),
+    };
+
+    Ok((name, end_date))
+}
+
+impl FiscalYearEntity {
+    pub fn new(
+        name: String,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        created_by: Uuid,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            name,
+            start_date,
+            end_date,
+            closing_entry_id: None,
+            closed_by: None,
+            closed_at: None,
+            created_by: Some(created_by),
+            created_at: now,
+        }
+    }
+
+    // A period a month, the last one ends with the year
+    pub fn periods(&self, now: DateTime<Utc>) -> Vec<FiscalPeriodEntity> {
+        (0..PERIODS_PER_YEAR)
+            .filter_map(|n| {
+                let start_date = self.start_date.checked_add_months(Months::new(n))?;
+                let end_date = self
+                    .start_date
+                    .checked_add_months(Months::new(n + 1))?
+                    .checked_sub_days(Days::new(1))?;
+
+                Some(FiscalPeriodEntity {
+                    id: Uuid::now_v7(),
+                    fiscal_year_id: self.id,
+                    period_number: n as i16 + 1,
+                    start_date,
+                    end_date,
+                    status: FiscalPeriodStatus::Open,
+                    updated_by: None,
+                    updated_at: now,
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+    }
+
+    // The last moment of the year, where its closing entry is dated
+    pub fn closing_time(&self) -> NaiveDateTime {
+        self.end_date.and_hms_opt(23, 59, 59).unwrap_or_default()
+    }
+
+    // Closing is done once, after the year is over and none of its periods are open
+    pub fn check_closable(
+        &self,
+        periods: &[FiscalPeriodEntity],
+        today: NaiveDate,
+    ) -> Result<(), DomainError> {
+        if self.is_closed() {
+            return Err(DomainError::InvalidState(format!(
+                "fiscal year {} is already closed",
+                self.name
+            )));
+        }
+        if self.end_date >= today {
+            return Err(DomainError::InvalidState(format!(
+                "fiscal year {} hasn't ended",
+                self.name
+            )));
+        }
+        if let Some(open) = periods
+            .iter()
+            .find(|p| p.status == FiscalPeriodStatus::Open)
+        {
+            return Err(DomainError::InvalidState(format!(
+                "period {} of fiscal year {} is still open",
+                open.period_number, self.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn close(&mut self, closing_entry_id: Option<Uuid>, closed_by: Uuid, now: DateTime<Utc>) {
+        self.closing_entry_id = closing_entry_id;
+        self.closed_by = Some(closed_by);
+        self.closed_at = Some(now);
+    }
+}
+
+// What an income or expense chart account took over the year, credits less debits
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClosingBalanceEntity {
+    pub coa_id: Uuid,
+    pub code: String,
+    pub coa_type: CoaType,
+    pub currency: String,
+    pub credit_cents: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClosingLine {
+    pub debit_coa_id: Uuid,
+    pub credit_coa_id: Uuid,
+    pub amount_cents: i64,
+}
+
+// Each balance is brought to zero against retained earnings, income left in credit is
+// debited and expenses credited. The closing entry has no customer account so its lines are
+// in the base currency, what was posted in other currencies is converted as the reports do.
+pub fn closing_lines(
+    balances: &[ClosingBalanceEntity],
+    retained_earnings_id: Uuid,
+    fx: &FxTable,
+) -> Result<Vec<ClosingLine>, DomainError> {
+    let mut credits: BTreeMap<&str, (Uuid, i64)> = BTreeMap::new();
+    for b in balances
+        .iter()
+        .filter(|b| matches!(b.coa_type, CoaType::Income | CoaType::Expense))
+    {
+        let cents = fx.to_base(b.currency.trim(), b.credit_cents)?;
+        credits.entry(&b.code).or_insert((b.coa_id, 0)).1 += cents;
+    }
+
+    let lines = credits
+        .into_values()
+        .filter(|(_, credit_cents)| *credit_cents != 0)
+        .map(|(coa_id, credit_cents)| match credit_cents > 0 {
+            true => ClosingLine {
+                debit_coa_id: coa_id,
+                credit_coa_id: retained_earnings_id,
+                amount_cents: credit_cents,
+            },
+            false => ClosingLine {
+                debit_coa_id: retained_earnings_id,
+                credit_coa_id: coa_id,
+                amount_cents: -credit_cents,
+            },
+        })
+        .collect();
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        ClosingBalanceEntity, FiscalPeriodStatus, FiscalYearEntity, closing_lines,
+        parse_fiscal_year,
+    };
+    use crate::ledger::models::EntryKind;
+    use crate::reporting::financials::{FinancialStatement, FxTable, ReportColumn, base_balances};
+    use crate::reporting::models::CoaBalanceEntity;
+    use crate::staff::models::CoaType;
+    use crate::swift::models::FxRateEntity;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn year(start_date: NaiveDate) -> FiscalYearEntity {
+        let (name, end_date) = assert_ok!(parse_fiscal_year(None, start_date));
+        FiscalYearEntity::new(name, start_date, end_date, Uuid::now_v7(), Utc::now())
+    }
+
+    #[test]
+    fn a_year_is_twelve_monthly_periods() {
+        let fiscal_year = year(date(2025, 4, 1));
+        assert_eq!(fiscal_year.name, "FY2026");
+        assert_eq!(fiscal_year.end_date, date(2026, 3, 31));
+
+        let periods = fiscal_year.periods(Utc::now());
+        assert_eq!(periods.len(), 12);
+        assert_eq!(periods[0].end_date, date(2025, 4, 30));
+        assert_eq!(periods[10].start_date, date(2026, 2, 1));
+        assert_eq!(periods[10].end_date, date(2026, 2, 28));
+        assert_eq!(periods[11].period_number, 12);
+        assert_eq!(periods[11].end_date, fiscal_year.end_date);
+
+        assert_err!(parse_fiscal_year(Some(" "), date(2025, 1, 1)));
+        let (name, _) = assert_ok!(parse_fiscal_year(Some("2025"), date(2025, 1, 1)));
+        assert_eq!(name, "2025");
+    }
+
+    #[test]
+    fn closed_periods_only_take_what_their_status_allows() {
+        assert!(FiscalPeriodStatus::Open.accepts(EntryKind::Regular));
+        assert!(!FiscalPeriodStatus::SoftClosed.accepts(EntryKind::Regular));
+        assert!(FiscalPeriodStatus::SoftClosed.accepts(EntryKind::Adjusting));
+        assert!(FiscalPeriodStatus::SoftClosed.accepts(EntryKind::Closing));
+        assert!(!FiscalPeriodStatus::HardClosed.accepts(EntryKind::Adjusting));
+
+        assert_ok!(FiscalPeriodStatus::SoftClosed.check_change(FiscalPeriodStatus::Open));
+        assert_err!(FiscalPeriodStatus::HardClosed.check_change(FiscalPeriodStatus::SoftClosed));
+        assert_err!("closed".parse::<FiscalPeriodStatus>());
+    }
+
+    #[test]
+    fn a_year_closes_once_it_is_over_and_no_period_is_open() {
+        let mut fiscal_year = year(date(2025, 1, 1));
+        let mut periods = fiscal_year.periods(Utc::now());
+
+        assert_err!(fiscal_year.check_closable(&periods, date(2025, 12, 31)));
+        assert_err!(fiscal_year.check_closable(&periods, date(2026, 1, 5)));
+
+        periods
+            .iter_mut()
+            .for_each(|p| p.status = FiscalPeriodStatus::SoftClosed);
+        assert_ok!(fiscal_year.check_closable(&periods, date(2026, 1, 5)));
+
+        fiscal_year.close(None, Uuid::now_v7(), Utc::now());
+        assert_err!(fiscal_year.check_closable(&periods, date(2026, 1, 5)));
+    }
+
+    #[test]
+    fn income_and_expenses_are_swept_to_retained_earnings() {
+        let retained_earnings = Uuid::now_v7();
+        let balance = |code: &str, coa_type, credit_cents| ClosingBalanceEntity {
+            coa_id: Uuid::now_v7(),
+            code: code.into(),
+            coa_type,
+            currency: "USD".into(),
+            credit_cents,
+        };
+        let balances = vec![
+            balance("4120", CoaType::Income, 3_000),
+            balance("4220", CoaType::Income, 0),
+            balance("5010", CoaType::Expense, -500),
+            balance("1040", CoaType::Asset, -2_500),
+        ];
+
+        let lines = assert_ok!(closing_lines(
+            &balances,
+            retained_earnings,
+            &FxTable::new("USD", vec![])
+        ));
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].debit_coa_id, balances[0].coa_id);
+        assert_eq!(lines[0].credit_coa_id, retained_earnings);
+        assert_eq!(lines[0].amount_cents, 3_000);
+        assert_eq!(lines[1].debit_coa_id, retained_earnings);
+        assert_eq!(lines[1].credit_coa_id, balances[2].coa_id);
+        assert_eq!(lines[1].amount_cents, 500);
+    }
+
+    #[test]
+    fn balances_in_other_currencies_are_swept_in_the_base_currency() {
+        let retained_earnings = Uuid::now_v7();
+        let fees = Uuid::now_v7();
+        let balance = |currency: &str, credit_cents| ClosingBalanceEntity {
+            coa_id: fees,
+            code: "4120".into(),
+            coa_type: CoaType::Income,
+            currency: currency.into(),
+            credit_cents,
+        };
+        let fx = FxTable::new(
+            "USD",
+            vec![FxRateEntity {
+                base_currency: "USD".into(),
+                quote_currency: "EUR".into(),
+                rate_micros: 800_000,
+                updated_by: None,
+                updated_at: Utc::now(),
+            }],
+        );
+
+        let lines = assert_ok!(closing_lines(
+            &[balance("USD", 3_000), balance("EUR", 2_000)],
+            retained_earnings,
+            &fx
+        ));
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].debit_coa_id, fees);
+        assert_eq!(lines[0].amount_cents, 5_500);
+
+        let _ = assert_err!(closing_lines(
+            &[balance("JPY", 1_000)],
+            retained_earnings,
+            &fx
+        ));
+    }
+
+    #[test]
+    fn the_close_sweeps_the_net_income_the_income_statement_reports() {
+        let retained_earnings = Uuid::now_v7();
+        let fx = FxTable::new(
+            "USD",
+            vec![FxRateEntity {
+                base_currency: "USD".into(),
+                quote_currency: "EUR".into(),
+                rate_micros: 800_000,
+                updated_by: None,
+                updated_at: Utc::now(),
+            }],
+        );
+        // Lines as the ledger holds them, income booked on a EUR account among them
+        let lines = [
+            ("4120", CoaType::Income, "USD", 3_000),
+            ("4120", CoaType::Income, "EUR", 2_001),
+            ("4220", CoaType::Income, "EUR", 777),
+            ("5010", CoaType::Expense, "EUR", -1_333),
+        ];
+
+        let closing = lines.map(
+            |(code, coa_type, currency, credit_cents)| ClosingBalanceEntity {
+                coa_id: Uuid::now_v7(),
+                code: code.into(),
+                coa_type,
+                currency: currency.into(),
+                credit_cents,
+            },
+        );
+        let swept: i64 = assert_ok!(closing_lines(&closing, retained_earnings, &fx))
+            .iter()
+            .map(|l| match l.credit_coa_id == retained_earnings {
+                true => l.amount_cents,
+                false => -l.amount_cents,
+            })
+            .sum();
+
+        let rows = lines.map(
+            |(code, coa_type, currency, credit_cents)| CoaBalanceEntity {
+                code: code.into(),
+                name: code.into(),
+                coa_type,
+                currency: currency.into(),
+                debit_cents: -credit_cents,
+            },
+        );
+        let balances = assert_ok!(base_balances(rows.to_vec(), &fx));
+        let column = ReportColumn {
+            from: Some(date(2025, 1, 1)),
+            to: date(2025, 12, 31),
+        };
+        let statement = FinancialStatement::income_statement("USD", column, column, &balances, &[]);
+
+        assert_eq!(statement.totals[0].current_cents, swept);
+        assert_eq!(swept, 3_000 + 2_501 + 971 - 1_666);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::fiscal::models::{
+    ClosingBalanceEntity, FiscalPeriodEntity, FiscalPeriodStatus, FiscalYearEntity,
+};
+
+const YEAR_COLUMNS: &str = "id, name, start_date, end_date, closing_entry_id, closed_by, closed_at,
+    created_by, created_at";
+const PERIOD_COLUMNS: &str =
+    "id, fiscal_year_id, period_number, start_date, end_date, status, updated_by, updated_at";
+
+pub struct FiscalRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> FiscalRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Checking for overlapping fiscal years", skip(self))]
+    pub async fn overlapping_year_exists(
+        &mut self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<bool, sqlx::Error> {
+        let result: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM fiscal_year WHERE start_date <= $2 AND end_date >= $1)",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_one(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving fiscal year", skip(self, year))]
+    pub async fn insert_year(&mut self, year: &FiscalYearEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO fiscal_year({}) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            YEAR_COLUMNS
+        ))
+        .bind(year.id)
+        .bind(&year.name)
+        .bind(year.start_date)
+        .bind(year.end_date)
+        .bind(year.closing_entry_id)
+        .bind(year.closed_by)
+        .bind(year.closed_at)
+        .bind(year.created_by)
+        .bind(year.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving fiscal period", skip(self, period))]
+    pub async fn insert_period(&mut self, period: &FiscalPeriodEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO fiscal_period({}) VALUES($1, $2, $3, $4, $5, $6, $7, $8)",
+            PERIOD_COLUMNS
+        ))
+        .bind(period.id)
+        .bind(period.fiscal_year_id)
+        .bind(period.period_number)
+        .bind(period.start_date)
+        .bind(period.end_date)
+        .bind(period.status)
+        .bind(period.updated_by)
+        .bind(period.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving fiscal years", skip(self))]
+    pub async fn fetch_years(&self) -> Result<Vec<FiscalYearEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FiscalYearEntity>(&format!(
+            "SELECT {} FROM fiscal_year ORDER BY start_date DESC",
+            YEAR_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving fiscal periods", skip(self))]
+    pub async fn fetch_periods(&self) -> Result<Vec<FiscalPeriodEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FiscalPeriodEntity>(&format!(
+            "SELECT {} FROM fiscal_period ORDER BY fiscal_year_id, period_number",
+            PERIOD_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking fiscal year", skip(self))]
+    pub async fn fetch_year_for_update(
+        &mut self,
+        year_id: Uuid,
+    ) -> Result<Option<FiscalYearEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FiscalYearEntity>(&format!(
+            "SELECT {} FROM fiscal_year WHERE id=$1 FOR UPDATE",
+            YEAR_COLUMNS
+        ))
+        .bind(year_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Locked so nothing is posted into them while they are being closed
+    #[tracing::instrument("Locking fiscal year periods", skip(self))]
+    pub async fn fetch_year_periods_for_update(
+        &mut self,
+        year_id: Uuid,
+    ) -> Result<Vec<FiscalPeriodEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FiscalPeriodEntity>(&format!(
+            "SELECT {} FROM fiscal_period WHERE fiscal_year_id=$1 ORDER BY period_number
+                FOR UPDATE",
+            PERIOD_COLUMNS
+        ))
+        .bind(year_id)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking fiscal period", skip(self))]
+    pub async fn fetch_period_for_update(
+        &mut self,
+        period_id: Uuid,
+    ) -> Result<Option<FiscalPeriodEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FiscalPeriodEntity>(&format!(
+            "SELECT {} FROM fiscal_period WHERE id=$1 FOR UPDATE",
+            PERIOD_COLUMNS
+        ))
+        .bind(period_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Updating fiscal period status", skip(self))]
+    pub async fn update_period_status(
+        &mut self,
+        period_id: Uuid,
+        status: FiscalPeriodStatus,
+        updated_by: Uuid,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE fiscal_period SET status=$2, updated_by=$3, updated_at=$4 WHERE id=$1")
+            .bind(period_id)
+            .bind(status)
+            .bind(updated_by)
+            .bind(updated_at)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Marking fiscal year closed", skip(self, year))]
+    pub async fn update_year_closed(&mut self, year: &FiscalYearEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE fiscal_year SET closing_entry_id=$2, closed_by=$3, closed_at=$4 WHERE id=$1",
+        )
+        .bind(year.id)
+        .bind(year.closing_entry_id)
+        .bind(year.closed_by)
+        .bind(year.closed_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Read in the closing tx, the year's periods are locked so the balances can't move
+    #[tracing::instrument("Retrieving income and expense balances", skip(self))]
+    pub async fn fetch_closing_balances(
+        &mut self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<ClosingBalanceEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ClosingBalanceEntity>(
+            "SELECT c.id AS coa_id, c.code, c.coa_type, l.currency,
+                    SUM(l.credit_cents)::BIGINT AS credit_cents
+                FROM journal_line_balance l
+                JOIN chart_of_account c ON c.id = l.coa_id
+                WHERE l.coa_type IN ('income', 'expense')
+                    AND l.created_date >= $1 AND l.created_date < $2::DATE + 1
+                GROUP BY c.id, c.code, c.coa_type, l.currency
+                ORDER BY c.code",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,68 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::fiscal::{
+    schemas::{FiscalPeriodResponse, FiscalYearRequest, FiscalYearResponse, PeriodStatusRequest},
+    service::FiscalService,
+};
+
+#[tracing::instrument("Create fiscal year", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/fiscal-years", request_body=FiscalYearRequest, responses((status=200, body=FiscalYearResponse, description="Fiscal year created with its twelve monthly periods open"), (status=400, description="Name too long or start date out of range"), (status=403, description="Only superusers can create fiscal years"), (status=409, description="Overlaps a fiscal year or the name is taken")))]
+pub async fn create_fiscal_year(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<FiscalYearRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let fiscal_service = FiscalService::from(&app_state);
+
+    let response = fiscal_service
+        .create_year(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List fiscal years", skip(app_state))]
+#[utoipa::path(get, path="/fiscal-years", responses((status=200, body=Vec<FiscalYearResponse>, description="Fiscal years with their periods, latest first")))]
+pub async fn list_fiscal_years(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let fiscal_service = FiscalService::from(&app_state);
+
+    let response = fiscal_service.list_years().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Change fiscal period status", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/fiscal-periods/{period_id}/status", params(("period_id"=Uuid, Path, description="Fiscal period id")), request_body=PeriodStatusRequest, responses((status=200, body=FiscalPeriodResponse, description="Period opened, soft-closed to all but adjusting entries or hard-closed"), (status=400, description="Unknown status"), (status=403, description="Only superusers can close periods"), (status=404, description="Fiscal period not found"), (status=409, description="A hard-closed period can't be reopened")))]
+pub async fn set_fiscal_period_status(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    period_id: web::Path<Uuid>,
+    payload: web::Json<PeriodStatusRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let fiscal_service = FiscalService::from(&app_state);
+
+    let response = fiscal_service
+        .set_period_status(&claims, period_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Close fiscal year", skip(app_state, claims))]
+#[utoipa::path(post, path="/fiscal-years/{year_id}/close", params(("year_id"=Uuid, Path, description="Fiscal year id")), responses((status=200, body=FiscalYearResponse, description="Income and expenses swept to retained earnings and every period hard-closed"), (status=403, description="Only superusers can close a year"), (status=404, description="Fiscal year or retained earnings chart account not found"), (status=409, description="Already closed, not over yet, a period still open or the last one hard-closed")))]
+pub async fn close_fiscal_year(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    year_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let fiscal_service = FiscalService::from(&app_state);
+
+    let response = fiscal_service
+        .close_year(&claims, year_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::fiscal::models::{FiscalPeriodEntity, FiscalPeriodStatus, FiscalYearEntity};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct FiscalYearRequest {
+    // The year runs twelve months from here, a period a month
+    #[schema(example = "2026-01-01")]
+    pub start_date: NaiveDate,
+    // FY and the calendar year it ends in when not given
+    pub name: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct PeriodStatusRequest {
+    // open, soft_closed or hard_closed
+    #[schema(example = "soft_closed")]
+    pub status: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct FiscalPeriodResponse {
+    pub id: Uuid,
+    pub period_number: i16,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: FiscalPeriodStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<FiscalPeriodEntity> for FiscalPeriodResponse {
+    fn from(value: FiscalPeriodEntity) -> Self {
+        Self {
+            id: value.id,
+            period_number: value.period_number,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            status: value.status,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct FiscalYearResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    // The entry sweeping income and expenses to retained earnings, once closed
+    pub closing_entry_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub periods: Vec<FiscalPeriodResponse>,
+}
+
+impl FiscalYearResponse {
+    pub fn new(year: FiscalYearEntity, periods: Vec<FiscalPeriodEntity>) -> Self {
+        Self {
+            id: year.id,
+            name: year.name,
+            start_date: year.start_date,
+            end_date: year.end_date,
+            closing_entry_id: year.closing_entry_id,
+            closed_at: year.closed_at,
+            periods: periods
+                .into_iter()
+                .map(FiscalPeriodResponse::from)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::config::state::AppState;
+use crate::fiscal::{
+    models::{
+        FiscalPeriodEntity, FiscalPeriodStatus, FiscalYearEntity, RETAINED_EARNINGS_COA,
+        closing_lines, closing_reference, parse_fiscal_year,
+    },
+    schemas::{FiscalPeriodResponse, FiscalYearRequest, FiscalYearResponse, PeriodStatusRequest},
+};
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::models::{
+    CreditLine, DebitLine, EntryKind, IntoJournalLine, JournalEntry, LineType,
+};
+use crate::reporting::financials::FxTable;
+use crate::transaction::service::TransactionService;
+use crate::user::models::AccessRole;
+
+pub struct FiscalService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> FiscalService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    #[tracing::instrument("Create fiscal year", skip(self, claims))]
+    pub async fn create_year(
+        &self,
+        claims: &SessionClaims,
+        request: FiscalYearRequest,
+    ) -> Result<FiscalYearResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let (name, end_date) = parse_fiscal_year(request.name.as_deref(), request.start_date)?;
+        let now = Utc::now();
+        let year = FiscalYearEntity::new(
+            name,
+            request.start_date,
+            end_date,
+            *claims.get_user_id(),
+            now,
+        );
+        let periods = year.periods(now);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .fiscal()
+            .overlapping_year_exists(year.start_date, year.end_date)
+            .await
+            .to_app_err("Failed to check fiscal years")?
+        {
+            Err(DomainError::Duplicate(
+                "fiscal year overlapping these dates".into(),
+            ))?;
+        }
+
+        uow.fiscal()
+            .insert_year(&year)
+            .await
+            .to_app_err("fiscal year with this name or overlapping these dates")?;
+        for period in &periods {
+            uow.fiscal()
+                .insert_period(period)
+                .await
+                .to_app_err("Failed to save fiscal period")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit fiscal year")?;
+
+        Ok(FiscalYearResponse::new(year, periods))
+    }
+
+    #[tracing::instrument("List fiscal years", skip(self))]
+    pub async fn list_years(&self) -> Result<Vec<FiscalYearResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let years = uow
+            .fiscal()
+            .fetch_years()
+            .await
+            .to_app_err("Failed to fetch fiscal years")?;
+        let periods = uow
+            .fiscal()
+            .fetch_periods()
+            .await
+            .to_app_err("Failed to fetch fiscal periods")?;
+
+        let mut by_year: HashMap<Uuid, Vec<FiscalPeriodEntity>> = HashMap::new();
+        for period in periods {
+            by_year
+                .entry(period.fiscal_year_id)
+                .or_default()
+                .push(period);
+        }
+
+        Ok(years
+            .into_iter()
+            .map(|year| {
+                let periods = by_year.remove(&year.id).unwrap_or_default();
+                FiscalYearResponse::new(year, periods)
+            })
+            .collect())
+    }
+
+    // Soft-closing leaves the period to adjusting entries, hard-closing shuts it for good
+    #[tracing::instrument("Change fiscal period status", skip(self, claims))]
+    pub async fn set_period_status(
+        &self,
+        claims: &SessionClaims,
+        period_id: Uuid,
+        request: PeriodStatusRequest,
+    ) -> Result<FiscalPeriodResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let status = request.status.parse::<FiscalPeriodStatus>()?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut period = match uow
+            .fiscal()
+            .fetch_period_for_update(period_id)
+            .await
+            .to_app_err("Failed to fetch fiscal period")?
+        {
+            Some(p) => p,
+            None => Err(DomainError::NotFound("fiscal period".into()))?,
+        };
+
+        period.status.check_change(status)?;
+
+        period.status = status;
+        period.updated_by = Some(*claims.get_user_id());
+        period.updated_at = Utc::now();
+
+        uow.fiscal()
+            .update_period_status(period.id, status, *claims.get_user_id(), period.updated_at)
+            .await
+            .to_app_err("Failed to update fiscal period")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit fiscal period")?;
+
+        Ok(period.into())
+    }
+
+    // Sweeps the year's income and expenses into retained earnings with one entry dated on its
+    // last day, then hard-closes every period
+    #[tracing::instrument("Close fiscal year", skip(self, claims))]
+    pub async fn close_year(
+        &self,
+        claims: &SessionClaims,
+        year_id: Uuid,
+    ) -> Result<FiscalYearResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let now = Utc::now();
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut year = match uow
+            .fiscal()
+            .fetch_year_for_update(year_id)
+            .await
+            .to_app_err("Failed to fetch fiscal year")?
+        {
+            Some(y) => y,
+            None => Err(DomainError::NotFound("fiscal year".into()))?,
+        };
+        let mut periods = uow
+            .fiscal()
+            .fetch_year_periods_for_update(year.id)
+            .await
+            .to_app_err("Failed to fetch fiscal periods")?;
+
+        year.check_closable(&periods, now.date_naive())?;
+
+        let retained_earnings_id = match uow
+            .staffs()
+            .fetch_coa_id_by_code(RETAINED_EARNINGS_COA)
+            .await
+            .to_app_err("Failed to fetch retained earnings chart account")?
+        {
+            Some(id) => id,
+            None => Err(DomainError::NotFound(
+                "Missing retained earnings chart account".into(),
+            ))?,
+        };
+        let balances = uow
+            .fiscal()
+            .fetch_closing_balances(year.start_date, year.end_date)
+            .await
+            .to_app_err("Failed to fetch income and expense balances")?;
+        let rates = uow
+            .swift()
+            .fetch_fx_rates()
+            .await
+            .to_app_err("Failed to fetch FX rates")?;
+        let fx = FxTable::new(&self.app_state.reporting.base_currency, rates);
+        let lines = closing_lines(&balances, retained_earnings_id, &fx)?;
+
+        // Nothing to sweep, the year closes without an entry
+        let closing_entry_id = match lines.is_empty() {
+            true => None,
+            false => {
+                let journal_entry = JournalEntry::general(
+                    EntryKind::Closing,
+                    year.closing_time(),
+                    TransactionService::from(self.app_state).generate_transaction_id(),
+                    closing_reference(&year.name),
+                    format!("Year-end close {}", year.name),
+                );
+
+                uow.ledgers()
+                    .create_ledger_journal_entry(&journal_entry)
+                    .await
+                    .to_app_err("year-end closing entry")?;
+                for line in lines {
+                    uow.ledgers()
+                        .create_ledger_journal_line(IntoJournalLine::from_cents(
+                            *journal_entry.get_id(),
+                            line.amount_cents,
+                            DebitLine::new(line.debit_coa_id, LineType::Debit),
+                            CreditLine::new(line.credit_coa_id, LineType::Credit),
+                        ))
+                        .await
+                        .to_app_err("Failed to create closing journal line")?;
+                }
+
+                Some(*journal_entry.get_id())
+            }
+        };
+
+        for period in periods.iter_mut() {
+            period.status = FiscalPeriodStatus::HardClosed;
+            period.updated_by = Some(*claims.get_user_id());
+            period.updated_at = now;
+
+            uow.fiscal()
+                .update_period_status(period.id, period.status, *claims.get_user_id(), now)
+                .await
+                .to_app_err("Failed to hard-close fiscal period")?;
+        }
+
+        year.close(closing_entry_id, *claims.get_user_id(), now);
+        uow.fiscal()
+            .update_year_closed(&year)
+            .await
+            .to_app_err("Failed to close fiscal year")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit year-end close")?;
+
+        Ok(FiscalYearResponse::new(year, periods))
+    }
+}
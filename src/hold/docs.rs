@@ -0,0 +1,13 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::hold::routes::place_hold,
+    crate::hold::routes::list_holds,
+    crate::hold::routes::release_hold,
+))]
+pub struct HoldApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(crate::hold::routes::authorize_card))]
+pub struct CardAuthorizationApi;
@@ -0,0 +1,324 @@
+use chrono::{DateTime, Duration, Utc};
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::base::error::{DomainError, ValidationError};
+use crate::hold::schemas::{CardAuthorizationRequest, HoldRequest};
+
+const MAX_REASON_LEN: usize = 200;
+// Settled by a posting carrying the same reference, and forced debits allow no more than this
+const MAX_REFERENCE_LEN: usize = 40;
+
+// Long enough for hotel and car hire authorizations to be settled
+const CARD_HOLD_DAYS: i64 = 7;
+const MAX_CARD_HOLD_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "hold_source", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HoldSource {
+    Manual,
+    CardAuthorization,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "hold_release_reason", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HoldReleaseReason {
+    Manual,
+    Expired,
+    // The posting the hold was waiting for arrived
+    Settled,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct HoldEntity {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub amount_cents: i64,
+    pub reason: String,
+    pub reference: String,
+    pub source: HoldSource,
+    pub expires_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    pub api_key_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub released_by: Option<Uuid>,
+    pub release_reason: Option<HoldReleaseReason>,
+    pub journal_entry_id: Option<Uuid>,
+}
+
+impl HoldEntity {
+    pub fn manual(
+        account_id: Uuid,
+        request: &HoldRequest,
+        created_by: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ValidationError> {
+        if request.expires_at <= now {
+            return Err(ValidationError::InvalidValue {
+                field: "expires_at".into(),
+                reason: "Must be in the future".into(),
+            });
+        }
+
+        let mut hold = Self::parse(
+            account_id,
+            request.amount_cents,
+            ("reason", &request.reason),
+            ("reference", &request.reference),
+            request.expires_at,
+            now,
+        )?;
+        hold.created_by = Some(created_by);
+
+        Ok(hold)
+    }
+
+    pub fn card_authorization(
+        account_id: Uuid,
+        request: &CardAuthorizationRequest,
+        api_key_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ValidationError> {
+        let expires_at = request
+            .expires_at
+            .unwrap_or(now + Duration::days(CARD_HOLD_DAYS));
+
+        if expires_at <= now || expires_at > now + Duration::days(MAX_CARD_HOLD_DAYS) {
+            return Err(ValidationError::OutOfRange {
+                field: "expires_at".into(),
+                min: "now".into(),
+                max: format!("{} days from now", MAX_CARD_HOLD_DAYS),
+            });
+        }
+
+        let mut hold = Self::parse(
+            account_id,
+            request.amount_cents,
+            ("merchant", &request.merchant),
+            ("authorization_ref", &request.authorization_ref),
+            expires_at,
+            now,
+        )?;
+        hold.source = HoldSource::CardAuthorization;
+        hold.api_key_id = Some(api_key_id);
+
+        Ok(hold)
+    }
+
+    fn parse(
+        account_id: Uuid,
+        amount_cents: i64,
+        reason: (&str, &str),
+        reference: (&str, &str),
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ValidationError> {
+        if amount_cents <= 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: "Must be greater than zero".into(),
+            });
+        }
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            account_id,
+            amount_cents,
+            reason: required_text(reason, MAX_REASON_LEN)?,
+            reference: required_text(reference, MAX_REFERENCE_LEN)?,
+            source: HoldSource::Manual,
+            expires_at,
+            created_by: None,
+            api_key_id: None,
+            created_at: now,
+            released_at: None,
+            released_by: None,
+            release_reason: None,
+            journal_entry_id: None,
+        })
+    }
+
+    pub fn check_open(&self) -> Result<(), DomainError> {
+        match self.release_reason {
+            Some(reason) => Err(DomainError::InvalidState(format!(
+                "hold {} was already released ({})",
+                self.reference, reason
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn required_text((field, value): (&str, &str), max: usize) -> Result<String, ValidationError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(ValidationError::MissingField(field.into()));
+    }
+    if value.len() > max {
+        return Err(ValidationError::TooLong {
+            field: field.into(),
+            max,
+        });
+    }
+
+    Ok(value.to_string())
+}
+
+// The ledger balance less holds, plus any overdraft limit in force
+#[derive(Debug)]
+pub struct AvailableBalance {
+    pub balance_cents: i64,
+    pub limit_cents: i64,
+    pub held_cents: i64,
+}
+
+impl AvailableBalance {
+    pub fn available_cents(&self) -> i64 {
+        self.balance_cents + self.limit_cents - self.held_cents
+    }
+}
+
+// Cards are declined on anything but an active account, staff can still hold funds on frozen ones
+pub fn check_card_account(status: &UserAccountStatus) -> Result<(), DomainError> {
+    match status {
+        UserAccountStatus::Active => Ok(()),
+        status => Err(DomainError::InvalidState(format!(
+            "card payments are declined on {} accounts",
+            status
+        ))),
+    }
+}
+
+pub fn check_funds(amount_cents: i64, available_cents: i64) -> Result<(), DomainError> {
+    if amount_cents > available_cents {
+        return Err(DomainError::ConstraintViolation(
+            "insufficient available funds".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        AvailableBalance, HoldEntity, HoldReleaseReason, HoldSource, check_card_account,
+        check_funds,
+    };
+    use crate::account::models::UserAccountStatus;
+    use crate::hold::schemas::{CardAuthorizationRequest, HoldRequest};
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-06-15T10:00:00Z")
+            .unwrap()
+            .to_utc()
+    }
+
+    fn card_request(expires_at: Option<DateTime<Utc>>) -> CardAuthorizationRequest {
+        CardAuthorizationRequest {
+            amount_cents: 4_599,
+            authorization_ref: " AUTH-839201 ".into(),
+            merchant: "ACME GROCERIES".into(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn manual_holds_need_a_reason_and_a_future_expiry() {
+        let request = HoldRequest {
+            amount_cents: 25_000,
+            reason: "Garnishment order".into(),
+            reference: "GARN-114".into(),
+            expires_at: now() + Duration::days(30),
+        };
+        let hold = assert_ok!(HoldEntity::manual(
+            Uuid::now_v7(),
+            &request,
+            Uuid::now_v7(),
+            now()
+        ));
+        assert_eq!(hold.source, HoldSource::Manual);
+
+        let expired = HoldRequest {
+            expires_at: now(),
+            ..request
+        };
+        let _ = assert_err!(HoldEntity::manual(
+            Uuid::now_v7(),
+            &expired,
+            Uuid::now_v7(),
+            now()
+        ));
+
+        let unexplained = HoldRequest {
+            reason: "  ".into(),
+            expires_at: now() + Duration::days(30),
+            ..expired
+        };
+        let _ = assert_err!(HoldEntity::manual(
+            Uuid::now_v7(),
+            &unexplained,
+            Uuid::now_v7(),
+            now()
+        ));
+    }
+
+    #[test]
+    fn card_holds_last_a_week_by_default() {
+        let hold = assert_ok!(HoldEntity::card_authorization(
+            Uuid::now_v7(),
+            &card_request(None),
+            Uuid::now_v7(),
+            now()
+        ));
+        assert_eq!(hold.source, HoldSource::CardAuthorization);
+        assert_eq!(hold.reference, "AUTH-839201");
+        assert_eq!(hold.expires_at, now() + Duration::days(7));
+
+        let _ = assert_err!(HoldEntity::card_authorization(
+            Uuid::now_v7(),
+            &card_request(Some(now() + Duration::days(31))),
+            Uuid::now_v7(),
+            now()
+        ));
+    }
+
+    #[test]
+    fn released_holds_cannot_be_released_again() {
+        let mut hold = assert_ok!(HoldEntity::card_authorization(
+            Uuid::now_v7(),
+            &card_request(None),
+            Uuid::now_v7(),
+            now()
+        ));
+        assert_ok!(hold.check_open());
+
+        hold.release_reason = Some(HoldReleaseReason::Settled);
+        let _ = assert_err!(hold.check_open());
+    }
+
+    #[test]
+    fn cards_are_declined_past_the_available_balance() {
+        assert_ok!(check_funds(10_000, 10_000));
+        let _ = assert_err!(check_funds(10_001, 10_000));
+        let _ = assert_err!(check_funds(1, -500));
+
+        let balance = AvailableBalance {
+            balance_cents: -2_000,
+            limit_cents: 50_000,
+            held_cents: 8_000,
+        };
+        assert_eq!(balance.available_cents(), 40_000);
+
+        assert_ok!(check_card_account(&UserAccountStatus::Active));
+        let _ = assert_err!(check_card_account(&UserAccountStatus::Frozen));
+    }
+}
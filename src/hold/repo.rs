@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::hold::models::HoldEntity;
+
+const HOLD_COLUMNS: &str = "id, account_id, amount_cents, reason, reference, source, expires_at,
+    created_by, api_key_id, created_at, released_at, released_by, release_reason, journal_entry_id";
+
+pub struct HoldRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> HoldRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Placing hold", skip(self, hold))]
+    pub async fn insert_hold(&mut self, hold: &HoldEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO account_hold(id, account_id, amount_cents, reason, reference, source,
+                    expires_at, created_by, api_key_id, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(hold.id)
+        .bind(hold.account_id)
+        .bind(hold.amount_cents)
+        .bind(&hold.reason)
+        .bind(&hold.reference)
+        .bind(hold.source)
+        .bind(hold.expires_at)
+        .bind(hold.created_by)
+        .bind(hold.api_key_id)
+        .bind(hold.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving hold", skip(self))]
+    pub async fn fetch_hold(
+        &mut self,
+        account_id: Uuid,
+        hold_id: Uuid,
+    ) -> Result<Option<HoldEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, HoldEntity>(&format!(
+            "SELECT {} FROM account_hold WHERE id=$1 AND account_id=$2",
+            HOLD_COLUMNS
+        ))
+        .bind(hold_id)
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving account holds", skip(self))]
+    pub async fn fetch_holds(
+        &mut self,
+        account_id: Uuid,
+        include_released: bool,
+    ) -> Result<Vec<HoldEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, HoldEntity>(&format!(
+            "SELECT {} FROM account_hold
+                WHERE account_id=$1 AND ($2 OR released_at IS NULL)
+                ORDER BY created_at DESC",
+            HOLD_COLUMNS
+        ))
+        .bind(account_id)
+        .bind(include_released)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Releasing hold", skip(self))]
+    pub async fn release_hold(
+        &mut self,
+        hold_id: Uuid,
+        released_by: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE account_hold SET released_at=$2, released_by=$3, release_reason='manual'
+                WHERE id=$1 AND released_at IS NULL",
+        )
+        .bind(hold_id)
+        .bind(now)
+        .bind(released_by)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Stamped with the time they lapsed rather than the time anyone noticed
+    #[tracing::instrument("Expiring holds", skip(self))]
+    pub async fn expire_holds(
+        &mut self,
+        account_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE account_hold SET released_at=expires_at, release_reason='expired'
+                WHERE account_id=$1 AND released_at IS NULL AND expires_at <= $2",
+        )
+        .bind(account_id)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    #[tracing::instrument("Settling holds", skip(self))]
+    pub async fn settle_holds(
+        &mut self,
+        account_id: Uuid,
+        reference: &str,
+        journal_entry_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE account_hold
+                SET released_at=CURRENT_TIMESTAMP, release_reason='settled', journal_entry_id=$3
+                WHERE account_id=$1 AND reference=$2 AND released_at IS NULL",
+        )
+        .bind(account_id)
+        .bind(reference)
+        .bind(journal_entry_id)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    // Holds past their expiry no longer count even before they are marked expired
+    #[tracing::instrument("Calculating held funds", skip(self))]
+    pub async fn fetch_held_cents(
+        &mut self,
+        account_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        let held: i64 = sqlx::query(
+            "SELECT COALESCE(SUM(amount_cents), 0)::BIGINT AS held_cents FROM account_hold
+                WHERE account_id=$1 AND released_at IS NULL AND expires_at > $2",
+        )
+        .bind(account_id)
+        .bind(now)
+        .fetch_one(&mut **self.tx)
+        .await?
+        .get("held_cents");
+
+        Ok(held)
+    }
+
+    #[tracing::instrument("Fetching account branch", skip(self))]
+    pub async fn fetch_account_branch(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let branch_id: Option<Uuid> =
+            sqlx::query_scalar("SELECT branch_id FROM user_account WHERE id=$1")
+                .bind(account_id)
+                .fetch_optional(self.pool)
+                .await?;
+
+        Ok(branch_id)
+    }
+}
@@ -0,0 +1,84 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::api_key::models::{ApiKeyPrincipal, ApiScope};
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+use crate::hold::{
+    schemas::{
+        CardAuthorizationRequest, CardAuthorizationResponse, HoldListResponse, HoldQuery,
+        HoldRequest, HoldResponse,
+    },
+    service::HoldService,
+};
+
+#[tracing::instrument("Place hold", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/accounts/{account_id}/holds", params(("account_id"=Uuid, Path, description="Account id")), request_body=HoldRequest, responses((status=200, body=HoldResponse, description="Hold placed"), (status=400, description="Invalid amount, reason, reference or expiry"), (status=403, description="Managers can only hold funds at their own branches"), (status=404, description="Account not found"), (status=409, description="Account closed or reference already held")))]
+pub async fn place_hold(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+    payload: web::Json<HoldRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let hold_service = HoldService::from(&app_state);
+
+    let response = hold_service
+        .place(&claims, account_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List holds", skip(app_state, claims))]
+#[utoipa::path(get, path="/accounts/{account_id}/holds", params(("account_id"=Uuid, Path, description="Account id"), HoldQuery), responses((status=200, body=HoldListResponse, description="Holds, newest first, with the balance they leave available"), (status=403, description="Managers can only see holds at their own branches"), (status=404, description="Account not found")))]
+pub async fn list_holds(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+    query: web::Query<HoldQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let hold_service = HoldService::from(&app_state);
+
+    let response = hold_service
+        .list(&claims, account_id.into_inner(), query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Release hold", skip(app_state, claims))]
+#[utoipa::path(delete, path="/accounts/{account_id}/holds/{hold_id}", params(("account_id"=Uuid, Path, description="Account id"), ("hold_id"=Uuid, Path, description="Hold id")), responses((status=200, body=StdResponse, description="Hold released"), (status=403, description="Managers can only release holds at their own branches"), (status=404, description="Account or hold not found"), (status=409, description="Hold already released, expired or settled")))]
+pub async fn release_hold(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> actix_web::Result<HttpResponse> {
+    let (account_id, hold_id) = path.into_inner();
+    let hold_service = HoldService::from(&app_state);
+
+    hold_service.release(&claims, account_id, hold_id).await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Hold released")))
+}
+
+// Authorizations from the card processor, authenticated with an api key
+#[tracing::instrument("Card authorization", skip(app_state, payload, principal))]
+#[utoipa::path(post, path="/authorizations/{account_id}", params(("account_id"=Uuid, Path, description="Account id")), request_body=CardAuthorizationRequest, responses((status=200, body=CardAuthorizationResponse, description="Authorization approved and the amount held"), (status=400, description="Invalid amount, reference or expiry"), (status=401, description="Missing or invalid api key"), (status=403, description="Key lacks the card:authorize scope"), (status=404, description="Account not found"), (status=409, description="Account not active or reference already authorized"), (status=422, description="Declined for insufficient available funds")))]
+pub async fn authorize_card(
+    app_state: web::Data<AppState>,
+    payload: web::Json<CardAuthorizationRequest>,
+    account_id: web::Path<Uuid>,
+    principal: web::ReqData<ApiKeyPrincipal>,
+) -> actix_web::Result<HttpResponse> {
+    let principal = principal.into_inner();
+    principal.require(ApiScope::CardAuthorize)?;
+
+    let hold_service = HoldService::from(&app_state);
+
+    let response = hold_service
+        .authorize_card(&principal, account_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::hold::models::HoldEntity;
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct HoldRequest {
+    #[schema(example = 25000)]
+    pub amount_cents: i64,
+    #[schema(example = "Garnishment order 2025/114")]
+    pub reason: String,
+    #[schema(example = "GARN-2025-114")]
+    pub reference: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Sent by the card processor, the hold lasts a week unless an expiry is given
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct CardAuthorizationRequest {
+    #[schema(example = 4599)]
+    pub amount_cents: i64,
+    #[schema(example = "AUTH-839201")]
+    pub authorization_ref: String,
+    #[schema(example = "ACME GROCERIES")]
+    pub merchant: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct HoldQuery {
+    // Released and expired holds are left out unless asked for
+    #[serde(default)]
+    pub include_released: bool,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct HoldResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub amount_cents: i64,
+    pub reason: String,
+    pub reference: String,
+    pub source: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub release_reason: Option<String>,
+    pub journal_entry_id: Option<Uuid>,
+}
+
+impl From<HoldEntity> for HoldResponse {
+    fn from(value: HoldEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            amount_cents: value.amount_cents,
+            reason: value.reason,
+            reference: value.reference,
+            source: value.source.to_string(),
+            expires_at: value.expires_at,
+            created_at: value.created_at,
+            released_at: value.released_at,
+            release_reason: value.release_reason.map(|r| r.to_string()),
+            journal_entry_id: value.journal_entry_id,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct HoldListResponse {
+    pub account_id: Uuid,
+    pub balance_cents: i64,
+    pub held_cents: i64,
+    // The balance plus any overdraft limit in force, less what is held
+    pub available_cents: i64,
+    pub holds: Vec<HoldResponse>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct CardAuthorizationResponse {
+    pub hold_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub available_cents: i64,
+}
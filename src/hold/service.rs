@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::api_key::models::ApiKeyPrincipal;
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, DomainError, SqlErrorExt};
+use crate::branch::service::BranchService;
+use crate::config::state::AppState;
+use crate::hold::{
+    models::{AvailableBalance, HoldEntity, check_card_account, check_funds},
+    schemas::{
+        CardAuthorizationRequest, CardAuthorizationResponse, HoldListResponse, HoldQuery,
+        HoldRequest, HoldResponse,
+    },
+};
+use crate::infra::pgdb::UnitofWork;
+use crate::overdraft::models::{OverdraftAccountEntity, active_limit};
+
+pub struct HoldService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> HoldService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // Locked so two authorizations can't both spend the same available balance
+    async fn lock_account(
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<OverdraftAccountEntity, AppError> {
+        let account = match uow
+            .overdrafts()
+            .fetch_account_for_update(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        uow.holds()
+            .expire_holds(account_id, now)
+            .await
+            .to_app_err("Failed to expire holds")?;
+
+        Ok(account)
+    }
+
+    pub async fn available_balance(
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<AvailableBalance, AppError> {
+        let balance_cents = uow
+            .overdrafts()
+            .fetch_balance(account_id)
+            .await
+            .to_app_err("Failed to calculate account balance")?;
+
+        let facility = uow
+            .overdrafts()
+            .fetch_overdraft(account_id)
+            .await
+            .to_app_err("Failed to fetch overdraft facility")?;
+
+        let held_cents = uow
+            .holds()
+            .fetch_held_cents(account_id, now)
+            .await
+            .to_app_err("Failed to calculate held funds")?;
+
+        Ok(AvailableBalance {
+            balance_cents,
+            limit_cents: active_limit(facility.as_ref(), now.date_naive()),
+            held_cents,
+        })
+    }
+
+    #[tracing::instrument("Place hold", skip(self, claims, request))]
+    pub async fn place(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        request: HoldRequest,
+    ) -> Result<HoldResponse, AppError> {
+        let now = Utc::now();
+        let hold = HoldEntity::manual(account_id, &request, *claims.get_user_id(), now)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = Self::lock_account(&mut uow, account_id, now).await?;
+        BranchService::check_access(&mut uow, claims, account.branch_id).await?;
+        account.check_open()?;
+
+        uow.holds()
+            .insert_hold(&hold)
+            .await
+            .to_app_err("hold reference")?;
+
+        uow.commit().await.to_app_err("Failed to commit hold")?;
+
+        Ok(hold.into())
+    }
+
+    #[tracing::instrument("List holds", skip(self, claims))]
+    pub async fn list(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        query: HoldQuery,
+    ) -> Result<HoldListResponse, AppError> {
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let branch_id = match uow
+            .holds()
+            .fetch_account_branch(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(b) => b,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+        BranchService::check_access(&mut uow, claims, branch_id).await?;
+
+        uow.holds()
+            .expire_holds(account_id, now)
+            .await
+            .to_app_err("Failed to expire holds")?;
+
+        let balance = Self::available_balance(&mut uow, account_id, now).await?;
+
+        let holds = uow
+            .holds()
+            .fetch_holds(account_id, query.include_released)
+            .await
+            .to_app_err("Failed to fetch holds")?;
+
+        uow.commit().await.to_app_err("Failed to commit holds")?;
+
+        Ok(HoldListResponse {
+            account_id,
+            available_cents: balance.available_cents(),
+            balance_cents: balance.balance_cents,
+            held_cents: balance.held_cents,
+            holds: holds.into_iter().map(HoldResponse::from).collect(),
+        })
+    }
+
+    #[tracing::instrument("Release hold", skip(self, claims))]
+    pub async fn release(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        hold_id: Uuid,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = Self::lock_account(&mut uow, account_id, now).await?;
+        BranchService::check_access(&mut uow, claims, account.branch_id).await?;
+
+        let hold = match uow
+            .holds()
+            .fetch_hold(account_id, hold_id)
+            .await
+            .to_app_err("Failed to fetch hold")?
+        {
+            Some(h) => h,
+            None => Err(DomainError::NotFound("hold".into()))?,
+        };
+        hold.check_open()?;
+
+        uow.holds()
+            .release_hold(hold_id, *claims.get_user_id(), now)
+            .await
+            .to_app_err("Failed to release hold")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit hold release")?;
+
+        Ok(())
+    }
+
+    // Approved authorizations hold the amount until the card scheme settles it
+    #[tracing::instrument("Card authorization", skip(self, principal, request))]
+    pub async fn authorize_card(
+        &self,
+        principal: &ApiKeyPrincipal,
+        account_id: Uuid,
+        request: CardAuthorizationRequest,
+    ) -> Result<CardAuthorizationResponse, AppError> {
+        let now = Utc::now();
+        let hold =
+            HoldEntity::card_authorization(account_id, &request, *principal.get_key_id(), now)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = Self::lock_account(&mut uow, account_id, now).await?;
+        check_card_account(&account.status)?;
+
+        let available_cents = Self::available_balance(&mut uow, account_id, now)
+            .await?
+            .available_cents();
+        check_funds(hold.amount_cents, available_cents)?;
+
+        uow.holds()
+            .insert_hold(&hold)
+            .await
+            .to_app_err("authorization reference")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit card authorization")?;
+
+        Ok(CardAuthorizationResponse {
+            hold_id: hold.id,
+            expires_at: hold.expires_at,
+            available_cents: available_cents - hold.amount_cents,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::identity_verify::routes::customer_kyc_documents,
+    crate::identity_verify::routes::list_kyc_cases,
+    crate::identity_verify::routes::fetch_kyc_case,
+    crate::identity_verify::routes::review_kyc_case,
+))]
+pub struct KycApi;
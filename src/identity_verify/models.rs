@@ -0,0 +1,487 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::base::error::{DomainError, ValidationError};
+
+pub const MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+
+// A case needs one of these plus a selfie before it can be submitted
+pub const IDENTITY_DOCUMENTS: [KycDocumentType; 3] = [
+    KycDocumentType::Passport,
+    KycDocumentType::NationalId,
+    KycDocumentType::DriversLicense,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "kyc_document_type", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum KycDocumentType {
+    Passport,
+    NationalId,
+    DriversLicense,
+    ProofOfAddress,
+    Selfie,
+}
+
+impl FromStr for KycDocumentType {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "passport" => Ok(KycDocumentType::Passport),
+            "national_id" => Ok(KycDocumentType::NationalId),
+            "drivers_license" => Ok(KycDocumentType::DriversLicense),
+            "proof_of_address" => Ok(KycDocumentType::ProofOfAddress),
+            "selfie" => Ok(KycDocumentType::Selfie),
+            _ => Err(ValidationError::InvalidValue {
+                field: "document_type".into(),
+                reason: "Unknown document_type".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "kyc_document_status", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum KycDocumentStatus {
+    Uploaded,
+    Accepted,
+    Rejected,
+}
+
+// Formats are recognised from the file's magic bytes, the declared content type is only cross checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Jpeg,
+    Png,
+    Pdf,
+}
+
+impl DocumentFormat {
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xFF, 0xD8, 0xFF, ..] => Some(DocumentFormat::Jpeg),
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(DocumentFormat::Png),
+            [b'%', b'P', b'D', b'F', b'-', ..] => Some(DocumentFormat::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            DocumentFormat::Jpeg => "image/jpeg",
+            DocumentFormat::Png => "image/png",
+            DocumentFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocumentFormat::Jpeg => "jpg",
+            DocumentFormat::Png => "png",
+            DocumentFormat::Pdf => "pdf",
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        matches!(self, DocumentFormat::Jpeg | DocumentFormat::Png)
+    }
+}
+
+pub fn validate_document(
+    document_type: KycDocumentType,
+    declared_content_type: Option<&str>,
+    bytes: &[u8],
+) -> Result<DocumentFormat, ValidationError> {
+    if bytes.is_empty() {
+        return Err(ValidationError::MissingField("file".into()));
+    }
+
+    if bytes.len() > MAX_DOCUMENT_BYTES {
+        return Err(ValidationError::TooLong {
+            field: "file".into(),
+            max: MAX_DOCUMENT_BYTES,
+        });
+    }
+
+    let format = DocumentFormat::sniff(bytes).ok_or(ValidationError::InvalidValue {
+        field: "file".into(),
+        reason: "Only JPEG, PNG and PDF documents are accepted".into(),
+    })?;
+
+    // "image/jpg" is not registered but some clients still send it
+    let declared_matches = match declared_content_type {
+        Some("image/jpg") => format == DocumentFormat::Jpeg,
+        Some(declared) => declared == format.content_type(),
+        None => true,
+    };
+
+    if !declared_matches {
+        return Err(ValidationError::Mismatch(
+            "file content does not match its content type".into(),
+        ));
+    }
+
+    if document_type == KycDocumentType::Selfie && !format.is_image() {
+        return Err(ValidationError::InvalidValue {
+            field: "file".into(),
+            reason: "A selfie must be a JPEG or PNG image".into(),
+        });
+    }
+
+    Ok(format)
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct KycDocumentEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub document_type: KycDocumentType,
+    pub status: KycDocumentStatus,
+    pub object_key: String,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+impl KycDocumentEntity {
+    pub fn new(
+        user_id: Uuid,
+        document_type: KycDocumentType,
+        format: DocumentFormat,
+        file_name: Option<String>,
+        bytes: &[u8],
+    ) -> Self {
+        let id = Uuid::now_v7();
+
+        Self {
+            id,
+            user_id,
+            document_type,
+            status: KycDocumentStatus::Uploaded,
+            // Every customer gets their own prefix so access can be scoped per customer
+            object_key: format!("kyc/{}/{}.{}", user_id, id, format.extension()),
+            file_name,
+            content_type: format.content_type().into(),
+            size_bytes: bytes.len() as i64,
+            checksum: hex::encode(Sha256::digest(bytes)),
+            uploaded_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "kyc_case_status", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum KycCaseStatus {
+    Submitted,
+    InReview,
+    MoreInfoNeeded,
+    Approved,
+    Rejected,
+}
+
+impl FromStr for KycCaseStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "submitted" => Ok(KycCaseStatus::Submitted),
+            "in_review" => Ok(KycCaseStatus::InReview),
+            "more_info_needed" => Ok(KycCaseStatus::MoreInfoNeeded),
+            "approved" => Ok(KycCaseStatus::Approved),
+            "rejected" => Ok(KycCaseStatus::Rejected),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Unknown kyc case status".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum KycDecision {
+    StartReview,
+    RequestInfo,
+    Approve,
+    Reject,
+}
+
+impl FromStr for KycDecision {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "start_review" => Ok(KycDecision::StartReview),
+            "request_info" => Ok(KycDecision::RequestInfo),
+            "approve" => Ok(KycDecision::Approve),
+            "reject" => Ok(KycDecision::Reject),
+            _ => Err(ValidationError::InvalidValue {
+                field: "decision".into(),
+                reason: "Unknown kyc decision".into(),
+            }),
+        }
+    }
+}
+
+impl KycDecision {
+    // The customer is told why, so these can't go without a note
+    pub fn requires_note(&self) -> bool {
+        matches!(self, KycDecision::RequestInfo | KycDecision::Reject)
+    }
+}
+
+impl KycCaseStatus {
+    pub fn apply(self, decision: KycDecision) -> Result<Self, DomainError> {
+        match (self, decision) {
+            (KycCaseStatus::Submitted, KycDecision::StartReview) => Ok(KycCaseStatus::InReview),
+            (KycCaseStatus::InReview, KycDecision::RequestInfo) => {
+                Ok(KycCaseStatus::MoreInfoNeeded)
+            }
+            (KycCaseStatus::InReview, KycDecision::Approve) => Ok(KycCaseStatus::Approved),
+            (KycCaseStatus::InReview, KycDecision::Reject) => Ok(KycCaseStatus::Rejected),
+            (status, decision) => Err(DomainError::InvalidState(format!(
+                "cannot {} a kyc case that is {}",
+                decision, status
+            ))),
+        }
+    }
+
+    // Customers can only send a case back once more information was asked for
+    pub fn resubmit(self) -> Result<Self, DomainError> {
+        match self {
+            KycCaseStatus::MoreInfoNeeded => Ok(KycCaseStatus::Submitted),
+            status => Err(DomainError::InvalidState(format!(
+                "kyc case is already {}",
+                status
+            ))),
+        }
+    }
+
+    pub fn is_decided(&self) -> bool {
+        matches!(self, KycCaseStatus::Approved | KycCaseStatus::Rejected)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct KycCaseEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: KycCaseStatus,
+    pub reviewer_id: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl KycCaseEntity {
+    pub fn new(user_id: Uuid) -> Self {
+        let now = Utc::now();
+
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            status: KycCaseStatus::Submitted,
+            reviewer_id: None,
+            decided_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct KycCaseNoteEntity {
+    pub id: Uuid,
+    pub case_id: Uuid,
+    pub author_id: Uuid,
+    pub status_from: Option<KycCaseStatus>,
+    pub status_to: KycCaseStatus,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl KycCaseNoteEntity {
+    pub fn new(
+        case_id: Uuid,
+        author_id: Uuid,
+        status_from: Option<KycCaseStatus>,
+        status_to: KycCaseStatus,
+        note: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            case_id,
+            author_id,
+            status_from,
+            status_to,
+            note,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// What an unverified customer is still allowed to do
+#[derive(Debug, Clone)]
+pub struct KycPolicy {
+    unverified_transaction_limit: f64,
+}
+
+impl KycPolicy {
+    pub fn new(unverified_transaction_limit: f64) -> Self {
+        Self {
+            unverified_transaction_limit,
+        }
+    }
+
+    pub fn check_account_opening(&self, is_verified: bool) -> Result<(), DomainError> {
+        if is_verified {
+            Ok(())
+        } else {
+            Err(DomainError::ConstraintViolation(
+                "customer identity must be verified before opening an account".into(),
+            ))
+        }
+    }
+
+    pub fn check_transaction(
+        &self,
+        is_verified: bool,
+        amount_cents: i64,
+    ) -> Result<(), DomainError> {
+        let limit_cents = (self.unverified_transaction_limit * 100.0).round() as i64;
+
+        if is_verified || amount_cents <= limit_cents {
+            Ok(())
+        } else {
+            Err(DomainError::ConstraintViolation(format!(
+                "transactions above {} need a verified customer identity",
+                self.unverified_transaction_limit
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DocumentFormat, KycCaseStatus, KycDecision, KycDocumentEntity, KycDocumentType, KycPolicy,
+        validate_document,
+    };
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+    const PDF: &[u8] = b"%PDF-1.7 ...";
+
+    #[test]
+    fn formats_are_recognised_from_magic_bytes() {
+        assert_eq!(
+            DocumentFormat::sniff(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(DocumentFormat::Jpeg)
+        );
+        assert_eq!(DocumentFormat::sniff(PNG), Some(DocumentFormat::Png));
+        assert_eq!(DocumentFormat::sniff(PDF), Some(DocumentFormat::Pdf));
+        assert_eq!(DocumentFormat::sniff(b"MZ\x90\x00"), None);
+    }
+
+    #[test]
+    fn declared_type_must_match_the_content() {
+        assert_ok!(validate_document(
+            KycDocumentType::Passport,
+            Some("image/png"),
+            PNG
+        ));
+
+        let _ = assert_err!(validate_document(
+            KycDocumentType::Passport,
+            Some("image/png"),
+            PDF
+        ));
+    }
+
+    #[test]
+    fn selfie_must_be_an_image() {
+        let _ = assert_err!(validate_document(KycDocumentType::Selfie, None, PDF));
+        assert_ok!(validate_document(
+            KycDocumentType::ProofOfAddress,
+            None,
+            PDF
+        ));
+    }
+
+    #[test]
+    fn empty_and_oversized_files_are_rejected() {
+        let _ = assert_err!(validate_document(KycDocumentType::Passport, None, &[]));
+
+        let mut oversized = PDF.to_vec();
+        oversized.resize(super::MAX_DOCUMENT_BYTES + 1, 0);
+        let _ = assert_err!(validate_document(
+            KycDocumentType::Passport,
+            None,
+            &oversized
+        ));
+    }
+
+    #[test]
+    fn object_key_is_scoped_to_the_customer() {
+        let user_id = Uuid::now_v7();
+        let document = KycDocumentEntity::new(
+            user_id,
+            KycDocumentType::Passport,
+            DocumentFormat::Pdf,
+            None,
+            PDF,
+        );
+
+        assert!(
+            document
+                .object_key
+                .starts_with(&format!("kyc/{}/", user_id))
+        );
+        assert!(document.object_key.ends_with(".pdf"));
+        assert_eq!(document.checksum.len(), 64);
+    }
+
+    #[test]
+    fn review_moves_a_case_through_its_states() {
+        let status = assert_ok!(KycCaseStatus::Submitted.apply(KycDecision::StartReview));
+        assert_eq!(status, KycCaseStatus::InReview);
+
+        let status = assert_ok!(status.apply(KycDecision::RequestInfo));
+        assert_eq!(status, KycCaseStatus::MoreInfoNeeded);
+
+        let status = assert_ok!(status.resubmit());
+        assert_eq!(status, KycCaseStatus::Submitted);
+    }
+
+    #[test]
+    fn cases_can_only_be_decided_while_in_review() {
+        let _ = assert_err!(KycCaseStatus::Submitted.apply(KycDecision::Approve));
+        let _ = assert_err!(KycCaseStatus::Approved.apply(KycDecision::Reject));
+        let _ = assert_err!(KycCaseStatus::Rejected.apply(KycDecision::StartReview));
+        let _ = assert_err!(KycCaseStatus::InReview.resubmit());
+    }
+
+    #[test]
+    fn unverified_customers_are_held_to_the_limit() {
+        let policy = KycPolicy::new(1000.0);
+
+        assert_ok!(policy.check_transaction(false, 100_000));
+        let _ = assert_err!(policy.check_transaction(false, 100_001));
+        assert_ok!(policy.check_transaction(true, 5_000_000));
+
+        let _ = assert_err!(policy.check_account_opening(false));
+        assert_ok!(policy.check_account_opening(true));
+    }
+}
@@ -0,0 +1,218 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::identity_verify::models::{
+    KycCaseEntity, KycCaseNoteEntity, KycCaseStatus, KycDocumentEntity, KycDocumentStatus,
+};
+
+const CASE_COLUMNS: &str = "id, user_id, status, reviewer_id, decided_at, created_at, updated_at";
+
+const DOCUMENT_COLUMNS: &str = "id, user_id, document_type, status, object_key, file_name, content_type, size_bytes, checksum, uploaded_at";
+
+pub struct KycRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> KycRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Saving kyc document in the database", skip(self, document))]
+    pub async fn create_document(
+        &mut self,
+        document: &KycDocumentEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO kyc_document(id, user_id, document_type, status, object_key, file_name, content_type, size_bytes, checksum, uploaded_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(document.id)
+        .bind(document.user_id)
+        .bind(document.document_type)
+        .bind(document.status)
+        .bind(&document.object_key)
+        .bind(&document.file_name)
+        .bind(&document.content_type)
+        .bind(document.size_bytes)
+        .bind(&document.checksum)
+        .bind(document.uploaded_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving kyc document by checksum", skip(self))]
+    pub async fn fetch_document_by_checksum(
+        &self,
+        user_id: Uuid,
+        checksum: &str,
+    ) -> Result<Option<KycDocumentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycDocumentEntity>(&format!(
+            "SELECT {} FROM kyc_document WHERE user_id=$1 AND checksum=$2",
+            DOCUMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(checksum)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving kyc documents for a user", skip(self))]
+    pub async fn fetch_documents(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<KycDocumentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycDocumentEntity>(&format!(
+            "SELECT {} FROM kyc_document WHERE user_id=$1 ORDER BY uploaded_at DESC",
+            DOCUMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Updating kyc document statuses", skip(self))]
+    pub async fn update_document_status(
+        &mut self,
+        user_id: Uuid,
+        from: KycDocumentStatus,
+        to: KycDocumentStatus,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE kyc_document SET status=$3 WHERE user_id=$1 AND status=$2")
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving kyc case in the database", skip(self, case))]
+    pub async fn create_case(&mut self, case: &KycCaseEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO kyc_case(id, user_id, status, created_at, updated_at) VALUES($1, $2, $3, $4, $5)",
+        )
+        .bind(case.id)
+        .bind(case.user_id)
+        .bind(case.status)
+        .bind(case.created_at)
+        .bind(case.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving kyc case by id", skip(self))]
+    pub async fn fetch_case(&self, case_id: Uuid) -> Result<Option<KycCaseEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycCaseEntity>(&format!(
+            "SELECT {} FROM kyc_case WHERE id=$1",
+            CASE_COLUMNS
+        ))
+        .bind(case_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving kyc case for a user", skip(self))]
+    pub async fn fetch_case_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<KycCaseEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycCaseEntity>(&format!(
+            "SELECT {} FROM kyc_case WHERE user_id=$1",
+            CASE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Oldest first so the review queue is worked in order
+    #[tracing::instrument("Retrieving kyc cases", skip(self))]
+    pub async fn fetch_cases(
+        &self,
+        status: Option<KycCaseStatus>,
+    ) -> Result<Vec<KycCaseEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycCaseEntity>(&format!(
+            "SELECT {} FROM kyc_case WHERE $1::kyc_case_status IS NULL OR status=$1 ORDER BY updated_at",
+            CASE_COLUMNS
+        ))
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Only moves the case if nobody changed it in the meantime
+    #[tracing::instrument("Updating kyc case status", skip(self))]
+    pub async fn update_case_status(
+        &mut self,
+        case_id: Uuid,
+        from: KycCaseStatus,
+        to: KycCaseStatus,
+        reviewer_id: Option<Uuid>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE kyc_case SET status=$3,
+                                reviewer_id=COALESCE($4, reviewer_id),
+                                decided_at=CASE WHEN $3 IN ('approved', 'rejected') THEN CURRENT_TIMESTAMP ELSE decided_at END,
+                                updated_at=CURRENT_TIMESTAMP
+                WHERE id=$1 AND status=$2",
+        )
+        .bind(case_id)
+        .bind(from)
+        .bind(to)
+        .bind(reviewer_id)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    #[tracing::instrument("Saving kyc case note in the database", skip(self, note))]
+    pub async fn create_note(&mut self, note: &KycCaseNoteEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO kyc_case_note(id, case_id, author_id, status_from, status_to, note, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(note.id)
+        .bind(note.case_id)
+        .bind(note.author_id)
+        .bind(note.status_from)
+        .bind(note.status_to)
+        .bind(&note.note)
+        .bind(note.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving kyc case notes", skip(self))]
+    pub async fn fetch_notes(&self, case_id: Uuid) -> Result<Vec<KycCaseNoteEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, KycCaseNoteEntity>(
+            "SELECT id, case_id, author_id, status_from, status_to, note, created_at
+                FROM kyc_case_note WHERE case_id=$1 ORDER BY created_at, id",
+        )
+        .bind(case_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,67 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::identity_verify::{
+    schemas::{KycCaseQuery, KycCaseResponse, KycDocumentResponse, KycReviewRequest},
+    service::KycService,
+};
+
+#[tracing::instrument("Staff fetch customer kyc documents", skip(app_state))]
+#[utoipa::path(get, path="/customers/{customer_id}/kyc/documents", responses((status=200, body=Vec<KycDocumentResponse>, description="Customer documents with short lived download links"), (status=404, description="Customer not found")))]
+pub async fn customer_kyc_documents(
+    app_state: web::Data<AppState>,
+    customer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service
+        .list_customer_documents(customer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff list kyc cases", skip(app_state))]
+#[utoipa::path(get, path="/kyc/cases", params(KycCaseQuery), responses((status=200, body=Vec<KycCaseResponse>, description="Kyc cases, oldest first"), (status=400, description="Unknown status")))]
+pub async fn list_kyc_cases(
+    app_state: web::Data<AppState>,
+    query: web::Query<KycCaseQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service.list_cases(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff fetch kyc case", skip(app_state))]
+#[utoipa::path(get, path="/kyc/cases/{case_id}", responses((status=200, body=KycCaseResponse, description="Kyc case with its review history"), (status=404, description="Case not found")))]
+pub async fn fetch_kyc_case(
+    app_state: web::Data<AppState>,
+    case_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service.fetch_case(case_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff review kyc case", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/kyc/cases/{case_id}/review", responses((status=200, body=KycCaseResponse, description="Decision recorded"), (status=400, description="Unknown decision or missing note"), (status=404, description="Case not found"), (status=409, description="Decision not allowed from the current status")))]
+pub async fn review_kyc_case(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    case_id: web::Path<Uuid>,
+    payload: web::Json<KycReviewRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let kyc_service = KycService::from(&app_state);
+
+    let response = kyc_service
+        .review_case(&claims, case_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,144 @@
+use actix_multipart::form::{MultipartForm, bytes::Bytes, text::Text};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::identity_verify::models::{
+    KycCaseEntity, KycCaseNoteEntity, KycCaseStatus, KycDocumentEntity,
+};
+
+#[derive(Debug, MultipartForm, utoipa::ToSchema)]
+pub struct KycUploadForm {
+    #[multipart(limit = "10MiB")]
+    #[schema(value_type = String, format = Binary)]
+    pub file: Bytes,
+    #[schema(value_type = String, example = "passport")]
+    pub document_type: Text<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct KycDocumentResponse {
+    pub id: Uuid,
+    pub document_type: String,
+    pub status: String,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub uploaded_at: DateTime<Utc>,
+    // Short lived link, only handed out to staff
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+impl From<KycDocumentEntity> for KycDocumentResponse {
+    fn from(value: KycDocumentEntity) -> Self {
+        Self {
+            id: value.id,
+            document_type: value.document_type.to_string(),
+            status: value.status.to_string(),
+            file_name: value.file_name,
+            content_type: value.content_type,
+            size_bytes: value.size_bytes,
+            checksum: value.checksum,
+            uploaded_at: value.uploaded_at,
+            download_url: None,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct KycReviewRequest {
+    #[schema(example = "approve")]
+    pub decision: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct KycCaseQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct KycCaseNoteResponse {
+    pub author_id: Uuid,
+    pub status_from: Option<String>,
+    pub status_to: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<KycCaseNoteEntity> for KycCaseNoteResponse {
+    fn from(value: KycCaseNoteEntity) -> Self {
+        Self {
+            author_id: value.author_id,
+            status_from: value.status_from.map(|s| s.to_string()),
+            status_to: value.status_to.to_string(),
+            note: value.note,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct KycCaseResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub reviewer_id: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub notes: Vec<KycCaseNoteResponse>,
+}
+
+impl KycCaseResponse {
+    pub fn from(value: KycCaseEntity, notes: Vec<KycCaseNoteEntity>) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            status: value.status.to_string(),
+            reviewer_id: value.reviewer_id,
+            decided_at: value.decided_at,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            notes: notes.into_iter().map(KycCaseNoteResponse::from).collect(),
+        }
+    }
+}
+
+// What the customer sees, internal notes are left out
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct KycStatusResponse {
+    pub status: Option<String>,
+    pub is_verified: bool,
+    // Why more information was asked for or the case was rejected
+    pub reason: Option<String>,
+}
+
+impl KycStatusResponse {
+    pub fn from(case: Option<(KycCaseEntity, Vec<KycCaseNoteEntity>)>, is_verified: bool) -> Self {
+        match case {
+            Some((case, notes)) => {
+                let reason = match case.status {
+                    KycCaseStatus::MoreInfoNeeded | KycCaseStatus::Rejected => notes
+                        .into_iter()
+                        .rev()
+                        .find(|n| n.status_to == case.status)
+                        .and_then(|n| n.note),
+                    _ => None,
+                };
+
+                Self {
+                    status: Some(case.status.to_string()),
+                    is_verified,
+                    reason,
+                }
+            }
+            None => Self {
+                status: None,
+                is_verified,
+                reason: None,
+            },
+        }
+    }
+}
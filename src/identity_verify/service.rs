@@ -0,0 +1,477 @@
+use anyhow::Context;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, DomainError, SqlErrorExt, ValidationError};
+use crate::config::state::AppState;
+use crate::identity_verify::{
+    models::{
+        IDENTITY_DOCUMENTS, KycCaseEntity, KycCaseNoteEntity, KycCaseStatus, KycDecision,
+        KycDocumentEntity, KycDocumentStatus, KycDocumentType, validate_document,
+    },
+    schemas::{
+        KycCaseQuery, KycCaseResponse, KycDocumentResponse, KycReviewRequest, KycStatusResponse,
+        KycUploadForm,
+    },
+};
+use crate::infra::pgdb::UnitofWork;
+use crate::user::models::UpdateUserEntity;
+
+// Staff links to a document stop working after five minutes
+const DOCUMENT_URL_TTL_SECS: u64 = 300;
+
+pub struct KycService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> KycService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // Every posting held to the unverified limit comes through here, inside the uow that posts
+    // it so the owner's verification is read in the same transaction as the debit
+    pub async fn check_transaction(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        amount_cents: i64,
+    ) -> Result<(), AppError> {
+        let is_verified = match uow
+            .accounts()
+            .fetch_owner_is_verified(account_id)
+            .await
+            .to_app_err("Failed to fetch account owner")?
+        {
+            Some(v) => v,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        self.app_state
+            .kyc_policy
+            .check_transaction(is_verified, amount_cents)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Upload kyc document", skip(self, claims, form))]
+    pub async fn upload_document(
+        &self,
+        claims: &SessionClaims,
+        form: KycUploadForm,
+    ) -> Result<KycDocumentResponse, AppError> {
+        let document_type = KycDocumentType::from_str(&form.document_type)?;
+
+        let file = form.file;
+        let format = validate_document(
+            document_type,
+            file.content_type.as_ref().map(|m| m.essence_str()),
+            &file.data,
+        )?;
+
+        let document = KycDocumentEntity::new(
+            *claims.get_user_id(),
+            document_type,
+            format,
+            file.file_name,
+            &file.data,
+        );
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .kyc()
+            .fetch_document_by_checksum(document.user_id, &document.checksum)
+            .await
+            .to_app_err("Failed to fetch kyc document by checksum")?
+            .is_some()
+        {
+            Err(DomainError::Duplicate(
+                "document was already uploaded".into(),
+            ))?
+        }
+
+        let s3_client = &self.app_state.s3_client;
+
+        s3_client
+            .upload_to_s3(
+                file.data.to_vec(),
+                &s3_client.bucket,
+                &document.object_key,
+                &document.content_type,
+            )
+            .await
+            .context("Failed to upload kyc document")?;
+
+        let saved = match uow.kyc().create_document(&document).await {
+            Ok(()) => uow.commit().await,
+            Err(e) => Err(e),
+        };
+
+        // Don't leave an object behind that no row points to
+        if let Err(e) = saved {
+            if let Err(cleanup) = s3_client
+                .delete_from_s3(&s3_client.bucket, &document.object_key)
+                .await
+            {
+                tracing::error!(error.message = %cleanup, object_key = %document.object_key, "Failed to remove orphaned kyc document");
+            }
+
+            return Err(e).to_app_err("Failed to save kyc document");
+        }
+
+        Ok(KycDocumentResponse::from(document))
+    }
+
+    #[tracing::instrument("List own kyc documents", skip(self, claims))]
+    pub async fn list_documents(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<Vec<KycDocumentResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let documents = uow
+            .kyc()
+            .fetch_documents(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch kyc documents")?;
+
+        Ok(documents
+            .into_iter()
+            .map(KycDocumentResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("List customer kyc documents", skip(self))]
+    pub async fn list_customer_documents(
+        &self,
+        customer_id: Uuid,
+    ) -> Result<Vec<KycDocumentResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .authentication()
+            .fetch_user_by_id(customer_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+            .is_none()
+        {
+            Err(DomainError::NotFound("customer".into()))?
+        }
+
+        let documents = uow
+            .kyc()
+            .fetch_documents(customer_id)
+            .await
+            .to_app_err("Failed to fetch kyc documents")?;
+
+        let s3_client = &self.app_state.s3_client;
+        let mut responses = Vec::with_capacity(documents.len());
+
+        for document in documents {
+            let download_url = s3_client
+                .fetch_presigned_uri(
+                    &s3_client.bucket,
+                    &document.object_key,
+                    DOCUMENT_URL_TTL_SECS,
+                )
+                .await?;
+
+            let mut response = KycDocumentResponse::from(document);
+            response.download_url = Some(download_url);
+            responses.push(response);
+        }
+
+        Ok(responses)
+    }
+
+    #[tracing::instrument("Submit kyc case", skip(self, claims))]
+    pub async fn submit_case(&self, claims: &SessionClaims) -> Result<KycCaseResponse, AppError> {
+        let user_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let documents = uow
+            .kyc()
+            .fetch_documents(user_id)
+            .await
+            .to_app_err("Failed to fetch kyc documents")?;
+
+        let usable = |d: &&KycDocumentEntity| d.status != KycDocumentStatus::Rejected;
+
+        if !documents
+            .iter()
+            .filter(usable)
+            .any(|d| IDENTITY_DOCUMENTS.contains(&d.document_type))
+        {
+            Err(ValidationError::MissingField(
+                "identity document (passport, national_id or drivers_license)".into(),
+            ))?
+        }
+
+        if !documents
+            .iter()
+            .filter(usable)
+            .any(|d| d.document_type == KycDocumentType::Selfie)
+        {
+            Err(ValidationError::MissingField("selfie".into()))?
+        }
+
+        let case = match uow
+            .kyc()
+            .fetch_case_by_user(user_id)
+            .await
+            .to_app_err("Failed to fetch kyc case")?
+        {
+            Some(mut case) => {
+                let status = case.status.resubmit()?;
+
+                if uow
+                    .kyc()
+                    .update_case_status(case.id, case.status, status, None)
+                    .await
+                    .to_app_err("Failed to resubmit kyc case")?
+                    == 0
+                {
+                    Err(DomainError::InvalidState(
+                        "kyc case changed, try again".into(),
+                    ))?
+                }
+
+                uow.kyc()
+                    .create_note(&KycCaseNoteEntity::new(
+                        case.id,
+                        user_id,
+                        Some(case.status),
+                        status,
+                        None,
+                    ))
+                    .await
+                    .to_app_err("Failed to save kyc case note")?;
+
+                case.status = status;
+                case
+            }
+            None => {
+                let case = KycCaseEntity::new(user_id);
+
+                uow.kyc()
+                    .create_case(&case)
+                    .await
+                    .to_app_err("Failed to create kyc case")?;
+
+                uow.kyc()
+                    .create_note(&KycCaseNoteEntity::new(
+                        case.id,
+                        user_id,
+                        None,
+                        case.status,
+                        None,
+                    ))
+                    .await
+                    .to_app_err("Failed to save kyc case note")?;
+
+                case
+            }
+        };
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit kyc case submission")?;
+
+        Ok(KycCaseResponse::from(case, vec![]))
+    }
+
+    #[tracing::instrument("Kyc status", skip(self, claims))]
+    pub async fn case_status(&self, claims: &SessionClaims) -> Result<KycStatusResponse, AppError> {
+        let user_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let user = match uow
+            .authentication()
+            .fetch_user_by_id(user_id)
+            .await
+            .to_app_err("Failed to fetch user entity")?
+        {
+            Some(u) => u,
+            None => Err(DomainError::NotFound("user".into()))?,
+        };
+
+        let case = match uow
+            .kyc()
+            .fetch_case_by_user(user_id)
+            .await
+            .to_app_err("Failed to fetch kyc case")?
+        {
+            Some(case) => {
+                let notes = uow
+                    .kyc()
+                    .fetch_notes(case.id)
+                    .await
+                    .to_app_err("Failed to fetch kyc case notes")?;
+
+                Some((case, notes))
+            }
+            None => None,
+        };
+
+        Ok(KycStatusResponse::from(case, user.is_verified))
+    }
+
+    #[tracing::instrument("List kyc cases", skip(self))]
+    pub async fn list_cases(&self, query: KycCaseQuery) -> Result<Vec<KycCaseResponse>, AppError> {
+        let status = query
+            .status
+            .as_deref()
+            .map(KycCaseStatus::from_str)
+            .transpose()?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let cases = uow
+            .kyc()
+            .fetch_cases(status)
+            .await
+            .to_app_err("Failed to fetch kyc cases")?;
+
+        Ok(cases
+            .into_iter()
+            .map(|c| KycCaseResponse::from(c, vec![]))
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch kyc case", skip(self))]
+    pub async fn fetch_case(&self, case_id: Uuid) -> Result<KycCaseResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let case = match uow
+            .kyc()
+            .fetch_case(case_id)
+            .await
+            .to_app_err("Failed to fetch kyc case")?
+        {
+            Some(c) => c,
+            None => Err(DomainError::NotFound("kyc case".into()))?,
+        };
+
+        let notes = uow
+            .kyc()
+            .fetch_notes(case.id)
+            .await
+            .to_app_err("Failed to fetch kyc case notes")?;
+
+        Ok(KycCaseResponse::from(case, notes))
+    }
+
+    #[tracing::instrument("Review kyc case", skip(self, claims, request))]
+    pub async fn review_case(
+        &self,
+        claims: &SessionClaims,
+        case_id: Uuid,
+        request: KycReviewRequest,
+    ) -> Result<KycCaseResponse, AppError> {
+        let decision = KycDecision::from_str(&request.decision)?;
+        let note = request
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        if decision.requires_note() && note.is_none() {
+            Err(ValidationError::MissingField("note".into()))?
+        }
+
+        let reviewer_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let case = match uow
+            .kyc()
+            .fetch_case(case_id)
+            .await
+            .to_app_err("Failed to fetch kyc case")?
+        {
+            Some(c) => c,
+            None => Err(DomainError::NotFound("kyc case".into()))?,
+        };
+
+        let status = case.status.apply(decision)?;
+
+        if uow
+            .kyc()
+            .update_case_status(case.id, case.status, status, Some(reviewer_id))
+            .await
+            .to_app_err("Failed to update kyc case")?
+            == 0
+        {
+            Err(DomainError::InvalidState(
+                "kyc case was changed by someone else".into(),
+            ))?
+        }
+
+        uow.kyc()
+            .create_note(&KycCaseNoteEntity::new(
+                case.id,
+                reviewer_id,
+                Some(case.status),
+                status,
+                note,
+            ))
+            .await
+            .to_app_err("Failed to save kyc case note")?;
+
+        match status {
+            KycCaseStatus::Approved => {
+                uow.users()
+                    .update(&UpdateUserEntity::verified_update(case.user_id))
+                    .await
+                    .to_app_err("Failed to mark customer as verified")?;
+
+                uow.kyc()
+                    .update_document_status(
+                        case.user_id,
+                        KycDocumentStatus::Uploaded,
+                        KycDocumentStatus::Accepted,
+                    )
+                    .await
+                    .to_app_err("Failed to accept kyc documents")?;
+            }
+            KycCaseStatus::Rejected => {
+                uow.kyc()
+                    .update_document_status(
+                        case.user_id,
+                        KycDocumentStatus::Uploaded,
+                        KycDocumentStatus::Rejected,
+                    )
+                    .await
+                    .to_app_err("Failed to reject kyc documents")?;
+            }
+            _ => {}
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit kyc review")?;
+
+        tracing::info!(%case_id, %reviewer_id, %decision, "Kyc case reviewed");
+
+        self.fetch_case(case_id).await
+    }
+}
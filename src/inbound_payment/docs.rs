@@ -0,0 +1,11 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::inbound_payment::routes::import_inbound_payments,
+    crate::inbound_payment::routes::list_inbound_payments,
+    crate::inbound_payment::routes::fetch_inbound_payment,
+    crate::inbound_payment::routes::resolve_inbound_payment,
+    crate::inbound_payment::routes::reject_inbound_payment,
+))]
+pub struct InboundPaymentApi;
@@ -0,0 +1,453 @@
+use chrono::NaiveDate;
+use quick_xml::events::Event;
+use strum::Display;
+
+use crate::base::error::ValidationError;
+
+// What senders without an end-to-end id of their own have to put in its place
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+#[derive(Debug, Clone, Copy, PartialEq, Display)]
+pub enum MessageType {
+    #[strum(serialize = "camt.054")]
+    Camt054,
+    #[strum(serialize = "pacs.008")]
+    Pacs008,
+}
+
+// One credit to a customer, from a camt.054 entry or a pacs.008 transaction
+#[derive(Debug, Default, PartialEq)]
+pub struct InboundCredit {
+    pub end_to_end_id: Option<String>,
+    pub transaction_id: Option<String>,
+    pub servicer_reference: Option<String>,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub creditor_iban: Option<String>,
+    pub creditor_name: Option<String>,
+    pub debtor_name: Option<String>,
+    pub debtor_iban: Option<String>,
+    pub remittance_info: Option<String>,
+    pub value_date: Option<NaiveDate>,
+}
+
+impl InboundCredit {
+    // The end-to-end id when the sender gave one, otherwise the ids the banks on the way assigned
+    pub fn reference(&self) -> Option<&str> {
+        self.end_to_end_id
+            .as_deref()
+            .filter(|id| *id != NOT_PROVIDED)
+            .or(self.transaction_id.as_deref())
+            .or(self.servicer_reference.as_deref())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CreditNotification {
+    pub message_type: MessageType,
+    pub message_id: String,
+    pub credits: Vec<InboundCredit>,
+}
+
+// A camt.054 entry, its transaction details are only credits to us once the entry is booked
+#[derive(Default)]
+struct Entry {
+    credit: bool,
+    booked: bool,
+    amount_cents: i64,
+    currency: String,
+    value_date: Option<NaiveDate>,
+    servicer_reference: Option<String>,
+    has_details: bool,
+}
+
+fn invalid_file(reason: String) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: "file".into(),
+        reason,
+    }
+}
+
+fn at(path: &[String], suffix: &[&str]) -> bool {
+    path.len() >= suffix.len()
+        && path[path.len() - suffix.len()..]
+            .iter()
+            .zip(suffix)
+            .all(|(element, name)| element == name)
+}
+
+// ISO amounts allow five decimals, anything past the cent has to be zero
+pub fn parse_amount(text: &str) -> Option<i64> {
+    let (units, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    if units.is_empty()
+        || fraction.len() > 2
+        || !units.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let cents: i64 = format!("{:0<2}", fraction).parse().ok()?;
+    units
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(cents)
+}
+
+pub fn normalize_iban(iban: &str) -> String {
+    iban.split_whitespace().collect::<String>().to_uppercase()
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, ValidationError> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map_err(|_| invalid_file(format!("Invalid date {}", text)))
+}
+
+fn finish(credit: InboundCredit) -> Result<InboundCredit, ValidationError> {
+    let reference = match credit.reference() {
+        Some(reference) => reference,
+        None => {
+            return Err(invalid_file(
+                "Credit without an end-to-end id, transaction id or servicer reference".into(),
+            ));
+        }
+    };
+
+    if credit.amount_cents <= 0 || credit.currency.len() != 3 {
+        return Err(invalid_file(format!(
+            "Credit {} has no amount in a valid currency",
+            reference
+        )));
+    }
+
+    Ok(credit)
+}
+
+// Any camt.054 or pacs.008 version, elements are matched by local name and the namespace ignored
+pub fn parse_credit_notification(bytes: &[u8]) -> Result<CreditNotification, ValidationError> {
+    let mut reader = quick_xml::Reader::from_reader(bytes);
+    reader.config_mut().trim_text(true);
+
+    let mut message_type = None;
+    let mut message_id = String::new();
+    let mut credits = Vec::new();
+
+    let mut path: Vec<String> = Vec::new();
+    let mut amount_currency = String::new();
+    // Notified account of a camt.054, stands in when a transaction doesn't name the creditor account
+    let mut account_iban: Option<String> = None;
+    let mut settlement_date: Option<NaiveDate> = None;
+    let mut entry = Entry::default();
+    let mut credit: Option<InboundCredit> = None;
+    let mut credit_indicator: Option<bool> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| invalid_file(format!("Malformed credit notification: {}", e)))?;
+
+        match event {
+            Event::Start(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "BkToCstmrDbtCdtNtfctn" => message_type = Some(MessageType::Camt054),
+                    "FIToFICstmrCdtTrf" => message_type = Some(MessageType::Pacs008),
+                    "Ntry" => entry = Entry::default(),
+                    "TxDtls" | "CdtTrfTxInf" => {
+                        credit = Some(InboundCredit::default());
+                        credit_indicator = None;
+                    }
+                    "Amt" | "IntrBkSttlmAmt" => {
+                        amount_currency = e
+                            .try_get_attribute("Ccy")
+                            .ok()
+                            .flatten()
+                            .map(|a| String::from_utf8_lossy(&a.value).to_uppercase())
+                            .unwrap_or_default();
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Text(ref e) => {
+                let text = e
+                    .decode()
+                    .map_err(|e| invalid_file(format!("Malformed credit notification: {}", e)))?
+                    .trim()
+                    .to_string();
+
+                if at(&path, &["GrpHdr", "MsgId"]) {
+                    message_id = text;
+                } else if at(&path, &["GrpHdr", "IntrBkSttlmDt"]) {
+                    settlement_date = Some(parse_date(&text)?);
+                } else if let Some(credit) = credit.as_mut() {
+                    if at(&path, &["EndToEndId"]) {
+                        credit.end_to_end_id = Some(text);
+                    } else if at(&path, &["TxId"]) {
+                        credit.transaction_id = Some(text);
+                    } else if at(&path, &["Refs", "AcctSvcrRef"]) {
+                        credit.servicer_reference = Some(text);
+                    } else if at(&path, &["TxDtls", "Amt"])
+                        || at(&path, &["AmtDtls", "TxAmt", "Amt"])
+                        || at(&path, &["CdtTrfTxInf", "IntrBkSttlmAmt"])
+                    {
+                        if credit.amount_cents == 0 {
+                            credit.amount_cents = parse_amount(&text)
+                                .ok_or_else(|| invalid_file(format!("Invalid amount {}", text)))?;
+                            credit.currency = amount_currency.clone();
+                        }
+                    } else if at(&path, &["TxDtls", "CdtDbtInd"]) {
+                        credit_indicator = Some(text == "CRDT");
+                    } else if at(&path, &["CdtTrfTxInf", "IntrBkSttlmDt"]) {
+                        credit.value_date = Some(parse_date(&text)?);
+                    } else if at(&path, &["Dbtr", "Nm"]) || at(&path, &["Dbtr", "Pty", "Nm"]) {
+                        credit.debtor_name = Some(text);
+                    } else if at(&path, &["Cdtr", "Nm"]) || at(&path, &["Cdtr", "Pty", "Nm"]) {
+                        credit.creditor_name = Some(text);
+                    } else if at(&path, &["DbtrAcct", "Id", "IBAN"]) {
+                        credit.debtor_iban = Some(normalize_iban(&text));
+                    } else if at(&path, &["CdtrAcct", "Id", "IBAN"]) {
+                        credit.creditor_iban = Some(normalize_iban(&text));
+                    } else if at(&path, &["RmtInf", "Ustrd"]) {
+                        // Several unstructured lines read as one
+                        match credit.remittance_info.as_mut() {
+                            Some(info) => {
+                                info.push(' ');
+                                info.push_str(&text);
+                            }
+                            None => credit.remittance_info = Some(text),
+                        }
+                    }
+                } else if at(&path, &["Ntfctn", "Acct", "Id", "IBAN"]) {
+                    account_iban = Some(normalize_iban(&text));
+                } else if at(&path, &["Ntry", "Amt"]) {
+                    entry.amount_cents = parse_amount(&text)
+                        .ok_or_else(|| invalid_file(format!("Invalid amount {}", text)))?;
+                    entry.currency = amount_currency.clone();
+                } else if at(&path, &["Ntry", "CdtDbtInd"]) {
+                    entry.credit = text == "CRDT";
+                } else if at(&path, &["Ntry", "Sts"]) || at(&path, &["Ntry", "Sts", "Cd"]) {
+                    entry.booked = text == "BOOK";
+                } else if at(&path, &["Ntry", "BookgDt", "Dt"]) {
+                    entry.value_date.get_or_insert(parse_date(&text)?);
+                } else if at(&path, &["Ntry", "ValDt", "Dt"]) {
+                    entry.value_date = Some(parse_date(&text)?);
+                } else if at(&path, &["Ntry", "AcctSvcrRef"]) {
+                    entry.servicer_reference = Some(text);
+                }
+            }
+            Event::End(ref e) => {
+                match e.local_name().as_ref() {
+                    b"TxDtls" => {
+                        entry.has_details = true;
+                        if let Some(mut details) = credit.take()
+                            && entry.booked
+                            && credit_indicator.unwrap_or(entry.credit)
+                        {
+                            if details.amount_cents == 0 {
+                                details.amount_cents = entry.amount_cents;
+                                details.currency = entry.currency.clone();
+                            }
+                            details.value_date = details.value_date.or(entry.value_date);
+                            details.servicer_reference = details
+                                .servicer_reference
+                                .or_else(|| entry.servicer_reference.clone());
+                            details.creditor_iban =
+                                details.creditor_iban.or_else(|| account_iban.clone());
+                            credits.push(finish(details)?);
+                        }
+                    }
+                    // An entry without transaction details is a single credit
+                    b"Ntry" if !entry.has_details && entry.booked && entry.credit => {
+                        let single = InboundCredit {
+                            servicer_reference: entry.servicer_reference.take(),
+                            amount_cents: entry.amount_cents,
+                            currency: std::mem::take(&mut entry.currency),
+                            creditor_iban: account_iban.clone(),
+                            value_date: entry.value_date,
+                            ..Default::default()
+                        };
+                        credits.push(finish(single)?);
+                    }
+                    b"CdtTrfTxInf" => {
+                        if let Some(mut transaction) = credit.take() {
+                            transaction.value_date = transaction.value_date.or(settlement_date);
+                            credits.push(finish(transaction)?);
+                        }
+                    }
+                    _ => {}
+                }
+                path.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let message_type = match message_type {
+        Some(message_type) => message_type,
+        None => {
+            return Err(invalid_file(
+                "Neither a camt.054 notification nor a pacs.008 transfer".into(),
+            ));
+        }
+    };
+
+    if message_id.is_empty() {
+        return Err(invalid_file(format!("No message id in {}", message_type)));
+    }
+
+    Ok(CreditNotification {
+        message_type,
+        message_id,
+        credits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAMT054: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.054.001.08">
+  <BkToCstmrDbtCdtNtfctn>
+    <GrpHdr><MsgId>NTF-20251217-1</MsgId><CreDtTm>2025-12-17T07:00:00Z</CreDtTm></GrpHdr>
+    <Ntfctn>
+      <Id>NTF-1</Id>
+      <Acct><Id><IBAN>DE02100100100006820101</IBAN></Id></Acct>
+      <Ntry>
+        <Amt Ccy="EUR">350.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+        <BookgDt><Dt>2025-12-16</Dt></BookgDt>
+        <ValDt><Dt>2025-12-17</Dt></ValDt>
+        <AcctSvcrRef>SVC-1</AcctSvcrRef>
+        <NtryDtls>
+          <TxDtls>
+            <Refs><EndToEndId>INV-7781</EndToEndId><TxId>TX-1</TxId></Refs>
+            <Amt Ccy="EUR">250.00</Amt>
+            <RltdPties>
+              <Dbtr><Pty><Nm>ACME GmbH</Nm></Pty></Dbtr>
+              <DbtrAcct><Id><IBAN>FR14 2004 1010 0505 0001 3M02 606</IBAN></Id></DbtrAcct>
+              <CdtrAcct><Id><IBAN>de12 3456 7890 1234 5678 90</IBAN></Id></CdtrAcct>
+            </RltdPties>
+            <RmtInf><Ustrd>Invoice 7781</Ustrd><Ustrd>December</Ustrd></RmtInf>
+          </TxDtls>
+          <TxDtls>
+            <Refs><EndToEndId>NOTPROVIDED</EndToEndId><TxId>TX-2</TxId></Refs>
+            <AmtDtls><TxAmt><Amt Ccy="EUR">100.00</Amt></TxAmt></AmtDtls>
+          </TxDtls>
+        </NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">80.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">12.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>PDNG</Cd></Sts>
+      </Ntry>
+    </Ntfctn>
+  </BkToCstmrDbtCdtNtfctn>
+</Document>"#;
+
+    const PACS008: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pacs.008.001.08">
+  <FIToFICstmrCdtTrf>
+    <GrpHdr>
+      <MsgId>PACS-1</MsgId>
+      <NbOfTxs>1</NbOfTxs>
+      <IntrBkSttlmDt>2025-12-18</IntrBkSttlmDt>
+    </GrpHdr>
+    <CdtTrfTxInf>
+      <PmtId><EndToEndId>E2E-PACS-1</EndToEndId><TxId>PACS-TX-1</TxId></PmtId>
+      <IntrBkSttlmAmt Ccy="EUR">1234.5</IntrBkSttlmAmt>
+      <Dbtr><Nm>Jane Doe</Nm></Dbtr>
+      <DbtrAcct><Id><IBAN>NL91ABNA0417164300</IBAN></Id></DbtrAcct>
+      <Cdtr><Nm>John Roe</Nm></Cdtr>
+      <CdtrAcct><Id><IBAN>DE12345678901234567890</IBAN></Id></CdtrAcct>
+    </CdtTrfTxInf>
+  </FIToFICstmrCdtTrf>
+</Document>"#;
+
+    #[test]
+    fn booked_credits_are_read_from_camt054() {
+        let notification = parse_credit_notification(CAMT054.as_bytes()).unwrap();
+
+        assert_eq!(notification.message_type, MessageType::Camt054);
+        assert_eq!(notification.message_id, "NTF-20251217-1");
+        assert_eq!(notification.credits.len(), 2);
+
+        let first = &notification.credits[0];
+        assert_eq!(first.reference(), Some("INV-7781"));
+        assert_eq!(first.amount_cents, 25_000);
+        assert_eq!(first.currency, "EUR");
+        assert_eq!(first.debtor_name.as_deref(), Some("ACME GmbH"));
+        assert_eq!(
+            first.debtor_iban.as_deref(),
+            Some("FR1420041010050500013M02606")
+        );
+        assert_eq!(
+            first.creditor_iban.as_deref(),
+            Some("DE12345678901234567890")
+        );
+        assert_eq!(
+            first.remittance_info.as_deref(),
+            Some("Invoice 7781 December")
+        );
+        assert_eq!(first.value_date, NaiveDate::from_ymd_opt(2025, 12, 17));
+
+        // No end-to-end id from the sender and no creditor account on the transaction
+        let second = &notification.credits[1];
+        assert_eq!(second.reference(), Some("TX-2"));
+        assert_eq!(second.amount_cents, 10_000);
+        assert_eq!(
+            second.creditor_iban.as_deref(),
+            Some("DE02100100100006820101")
+        );
+    }
+
+    #[test]
+    fn transactions_are_read_from_pacs008() {
+        let notification = parse_credit_notification(PACS008.as_bytes()).unwrap();
+
+        assert_eq!(notification.message_type, MessageType::Pacs008);
+        assert_eq!(
+            notification.credits,
+            vec![InboundCredit {
+                end_to_end_id: Some("E2E-PACS-1".into()),
+                transaction_id: Some("PACS-TX-1".into()),
+                servicer_reference: None,
+                amount_cents: 123_450,
+                currency: "EUR".into(),
+                creditor_iban: Some("DE12345678901234567890".into()),
+                creditor_name: Some("John Roe".into()),
+                debtor_name: Some("Jane Doe".into()),
+                debtor_iban: Some("NL91ABNA0417164300".into()),
+                remittance_info: None,
+                value_date: NaiveDate::from_ymd_opt(2025, 12, 18),
+            }]
+        );
+    }
+
+    #[test]
+    fn amounts_are_whole_cents() {
+        assert_eq!(parse_amount("0.01"), Some(1));
+        assert_eq!(parse_amount("12"), Some(1_200));
+        assert_eq!(parse_amount("12.50000"), Some(1_250));
+        assert_eq!(parse_amount("12.505"), None);
+        assert_eq!(parse_amount("-5.00"), None);
+        assert_eq!(parse_amount(".5"), None);
+    }
+
+    #[test]
+    fn other_messages_are_refused() {
+        assert!(parse_credit_notification(b"<Document><CstmrPmtStsRpt/></Document>").is_err());
+        assert!(parse_credit_notification(b"<Document><FIToFICstmrCdtTrf>").is_err());
+    }
+}
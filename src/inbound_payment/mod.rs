@@ -0,0 +1,7 @@
+pub mod docs;
+pub mod iso20022;
+pub mod models;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,226 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::path::PathBuf;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::base::error::{DomainError, ValidationError};
+use crate::inbound_payment::iso20022::{CreditNotification, InboundCredit};
+
+// Received funds sit here from the moment the bank tells us until they reach a customer
+pub const INBOUND_CLEARING_COA: &str = "1040";
+
+#[derive(Debug, Clone)]
+pub struct InboundProfile {
+    // Where the bank drops its camt.054 notifications and pacs.008 transfers
+    pub drop_dir: Option<PathBuf>,
+}
+
+impl InboundProfile {
+    pub fn new(drop_dir: Option<&str>) -> Self {
+        Self {
+            drop_dir: drop_dir.map(PathBuf::from),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "inbound_payment_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum InboundPaymentStatus {
+    Posted,
+    Repair,
+    Resolved,
+    Rejected,
+}
+
+impl FromStr for InboundPaymentStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "posted" => Ok(Self::Posted),
+            "repair" => Ok(Self::Repair),
+            "resolved" => Ok(Self::Resolved),
+            "rejected" => Ok(Self::Rejected),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Use posted, repair, resolved or rejected".into(),
+            }),
+        }
+    }
+}
+
+// Why a credit could not be posted on arrival and went to the repair queue
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum RepairReason {
+    #[error("no creditor account given")]
+    MissingAccount,
+    #[error("no account with IBAN {0}")]
+    UnknownAccount(String),
+    #[error("account is {0}")]
+    AccountNotActive(String),
+    #[error("account is held in {account}, credit is in {credit}")]
+    CurrencyMismatch { account: String, credit: String },
+}
+
+impl From<RepairReason> for DomainError {
+    fn from(value: RepairReason) -> Self {
+        match value {
+            RepairReason::AccountNotActive(_) => DomainError::InvalidState(value.to_string()),
+            _ => DomainError::ConstraintViolation(value.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct CreditAccountEntity {
+    pub id: Uuid,
+    pub currency: String,
+    pub status: UserAccountStatus,
+}
+
+impl CreditAccountEntity {
+    pub fn check_credit(&self, currency: &str) -> Result<(), RepairReason> {
+        if !matches!(self.status, UserAccountStatus::Active) {
+            return Err(RepairReason::AccountNotActive(self.status.to_string()));
+        }
+
+        if self.currency != currency {
+            return Err(RepairReason::CurrencyMismatch {
+                account: self.currency.clone(),
+                credit: currency.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct InboundPaymentEntity {
+    pub id: Uuid,
+    pub reference: String,
+    pub message_type: String,
+    pub message_id: String,
+    pub source_file: String,
+    pub creditor_iban: Option<String>,
+    pub creditor_name: Option<String>,
+    pub debtor_name: Option<String>,
+    pub debtor_iban: Option<String>,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub remittance_info: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    pub status: InboundPaymentStatus,
+    pub account_id: Option<Uuid>,
+    pub journal_entry_id: Option<Uuid>,
+    pub repair_reason: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl InboundPaymentEntity {
+    // Received but not yet posted, the caller settles it as posted or for repair
+    pub fn received(
+        notification: &CreditNotification,
+        source_file: &str,
+        reference: &str,
+        credit: InboundCredit,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            reference: reference.to_string(),
+            message_type: notification.message_type.to_string(),
+            message_id: notification.message_id.clone(),
+            source_file: source_file.to_string(),
+            creditor_iban: credit.creditor_iban,
+            creditor_name: credit.creditor_name,
+            debtor_name: credit.debtor_name,
+            debtor_iban: credit.debtor_iban,
+            amount_cents: credit.amount_cents,
+            currency: credit.currency,
+            remittance_info: credit.remittance_info,
+            value_date: credit.value_date,
+            status: InboundPaymentStatus::Repair,
+            account_id: None,
+            journal_entry_id: None,
+            repair_reason: None,
+            resolved_by: None,
+            resolution_note: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn posted(mut self, account_id: Uuid, journal_entry_id: Uuid) -> Self {
+        self.status = InboundPaymentStatus::Posted;
+        self.account_id = Some(account_id);
+        self.journal_entry_id = Some(journal_entry_id);
+        self
+    }
+
+    pub fn repair(mut self, account_id: Option<Uuid>, reason: RepairReason) -> Self {
+        self.status = InboundPaymentStatus::Repair;
+        self.account_id = account_id;
+        self.repair_reason = Some(reason.to_string());
+        self
+    }
+
+    pub fn description(&self) -> String {
+        match &self.debtor_name {
+            Some(name) => format!("Inbound credit from {}", name),
+            None => "Inbound credit".into(),
+        }
+    }
+
+    pub fn check_repair(&self) -> Result<(), DomainError> {
+        if self.status != InboundPaymentStatus::Repair {
+            return Err(DomainError::InvalidState(format!(
+                "inbound payment is {}",
+                self.status
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(status: UserAccountStatus) -> CreditAccountEntity {
+        CreditAccountEntity {
+            id: Uuid::now_v7(),
+            currency: "EUR".into(),
+            status,
+        }
+    }
+
+    #[test]
+    fn only_active_accounts_in_the_credit_currency_are_credited() {
+        assert_eq!(
+            account(UserAccountStatus::Active).check_credit("EUR"),
+            Ok(())
+        );
+        assert_eq!(
+            account(UserAccountStatus::Closed).check_credit("EUR"),
+            Err(RepairReason::AccountNotActive("closed".into()))
+        );
+        assert_eq!(
+            account(UserAccountStatus::Active)
+                .check_credit("USD")
+                .unwrap_err()
+                .to_string(),
+            "account is held in EUR, credit is in USD"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::inbound_payment::models::{
+    CreditAccountEntity, InboundPaymentEntity, InboundPaymentStatus,
+};
+
+const PAYMENT_COLUMNS: &str = "id, reference, message_type, message_id, source_file,
+    creditor_iban, creditor_name, debtor_name, debtor_iban, amount_cents, currency,
+    remittance_info, value_date, status, account_id, journal_entry_id, repair_reason,
+    resolved_by, resolution_note, created_at, updated_at";
+
+pub struct InboundPaymentRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> InboundPaymentRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Retrieving account by IBAN", skip(self))]
+    pub async fn fetch_account_by_iban(
+        &mut self,
+        iban: &str,
+    ) -> Result<Option<CreditAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CreditAccountEntity>(
+            "SELECT id, currency, status FROM user_account WHERE iban=$1",
+        )
+        .bind(iban)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving account for credit", skip(self))]
+    pub async fn fetch_account(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<CreditAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CreditAccountEntity>(
+            "SELECT id, currency, status FROM user_account WHERE id=$1",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Read in the transaction so a reference repeated within one file is seen too
+    #[tracing::instrument("Checking inbound payment reference", skip(self))]
+    pub async fn reference_exists(&mut self, reference: &str) -> Result<bool, sqlx::Error> {
+        let result: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM inbound_payment WHERE reference=$1)")
+                .bind(reference)
+                .fetch_one(&mut **self.tx)
+                .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving inbound payment", skip(self, payment))]
+    pub async fn insert_payment(
+        &mut self,
+        payment: &InboundPaymentEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO inbound_payment(id, reference, message_type, message_id, source_file,
+                    creditor_iban, creditor_name, debtor_name, debtor_iban, amount_cents, currency,
+                    remittance_info, value_date, status, account_id, journal_entry_id,
+                    repair_reason, created_at, updated_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                    $18, $19)",
+        )
+        .bind(payment.id)
+        .bind(&payment.reference)
+        .bind(&payment.message_type)
+        .bind(&payment.message_id)
+        .bind(&payment.source_file)
+        .bind(&payment.creditor_iban)
+        .bind(&payment.creditor_name)
+        .bind(&payment.debtor_name)
+        .bind(&payment.debtor_iban)
+        .bind(payment.amount_cents)
+        .bind(&payment.currency)
+        .bind(&payment.remittance_info)
+        .bind(payment.value_date)
+        .bind(payment.status)
+        .bind(payment.account_id)
+        .bind(payment.journal_entry_id)
+        .bind(&payment.repair_reason)
+        .bind(payment.created_at)
+        .bind(payment.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving inbound payments", skip(self))]
+    pub async fn fetch_payments(
+        &self,
+        status: Option<InboundPaymentStatus>,
+    ) -> Result<Vec<InboundPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, InboundPaymentEntity>(&format!(
+            "SELECT {} FROM inbound_payment
+                WHERE $1::inbound_payment_status IS NULL OR status = $1
+                ORDER BY created_at DESC",
+            PAYMENT_COLUMNS
+        ))
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving inbound payment", skip(self))]
+    pub async fn fetch_payment(
+        &self,
+        payment_id: Uuid,
+    ) -> Result<Option<InboundPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, InboundPaymentEntity>(&format!(
+            "SELECT {} FROM inbound_payment WHERE id=$1",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking inbound payment", skip(self))]
+    pub async fn fetch_payment_for_update(
+        &mut self,
+        payment_id: Uuid,
+    ) -> Result<Option<InboundPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, InboundPaymentEntity>(&format!(
+            "SELECT {} FROM inbound_payment WHERE id=$1 FOR UPDATE",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Settling inbound payment from repair", skip(self, payment))]
+    pub async fn settle_repair(
+        &mut self,
+        payment: &InboundPaymentEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE inbound_payment
+                SET status=$2, account_id=$3, journal_entry_id=$4, resolved_by=$5,
+                    resolution_note=$6, updated_at=$7
+                WHERE id=$1",
+        )
+        .bind(payment.id)
+        .bind(payment.status)
+        .bind(payment.account_id)
+        .bind(payment.journal_entry_id)
+        .bind(payment.resolved_by)
+        .bind(&payment.resolution_note)
+        .bind(payment.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::inbound_payment::{
+    schemas::{
+        InboundImportResponse, InboundPaymentQuery, InboundPaymentResponse, RejectRepairRequest,
+        ResolveRepairRequest,
+    },
+    service::InboundPaymentService,
+};
+
+#[tracing::instrument("Import inbound payments", skip(app_state, claims))]
+#[utoipa::path(post, path="/inbound-payments/import", responses((status=200, body=InboundImportResponse, description="camt.054 and pacs.008 files read, credits posted or queued for repair"), (status=403, description="Only superusers can import inbound payments"), (status=409, description="No drop directory configured")))]
+pub async fn import_inbound_payments(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let inbound_service = InboundPaymentService::from(&app_state);
+
+    let response = inbound_service.import(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List inbound payments", skip(app_state))]
+#[utoipa::path(get, path="/inbound-payments", params(InboundPaymentQuery), responses((status=200, body=Vec<InboundPaymentResponse>, description="Inbound payments, newest first"), (status=400, description="Unknown status")))]
+pub async fn list_inbound_payments(
+    app_state: web::Data<AppState>,
+    query: web::Query<InboundPaymentQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let inbound_service = InboundPaymentService::from(&app_state);
+
+    let response = inbound_service.list(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch inbound payment", skip(app_state))]
+#[utoipa::path(get, path="/inbound-payments/{payment_id}", params(("payment_id"=Uuid, Path, description="Inbound payment id")), responses((status=200, body=InboundPaymentResponse, description="Inbound payment"), (status=404, description="Inbound payment not found")))]
+pub async fn fetch_inbound_payment(
+    app_state: web::Data<AppState>,
+    payment_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let inbound_service = InboundPaymentService::from(&app_state);
+
+    let response = inbound_service.fetch(payment_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Resolve inbound payment", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/inbound-payments/{payment_id}/resolve", params(("payment_id"=Uuid, Path, description="Inbound payment id")), request_body=ResolveRepairRequest, responses((status=200, body=InboundPaymentResponse, description="Credit posted to the named account"), (status=403, description="Only superusers can repair inbound payments"), (status=404, description="Inbound payment or account not found"), (status=409, description="Payment is not waiting for repair or the account is not active"), (status=422, description="Account is held in another currency")))]
+pub async fn resolve_inbound_payment(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payment_id: web::Path<Uuid>,
+    payload: web::Json<ResolveRepairRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let inbound_service = InboundPaymentService::from(&app_state);
+
+    let response = inbound_service
+        .resolve(&claims, payment_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Reject inbound payment", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/inbound-payments/{payment_id}/reject", params(("payment_id"=Uuid, Path, description="Inbound payment id")), request_body=RejectRepairRequest, responses((status=200, body=InboundPaymentResponse, description="Credit marked for return to the sender"), (status=400, description="Missing note"), (status=403, description="Only superusers can repair inbound payments"), (status=404, description="Inbound payment not found"), (status=409, description="Payment is not waiting for repair")))]
+pub async fn reject_inbound_payment(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payment_id: web::Path<Uuid>,
+    payload: web::Json<RejectRepairRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let inbound_service = InboundPaymentService::from(&app_state);
+
+    let response = inbound_service
+        .reject(&claims, payment_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::inbound_payment::models::{InboundPaymentEntity, InboundPaymentStatus};
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct InboundPaymentQuery {
+    // posted, repair, resolved or rejected
+    pub status: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct InboundPaymentResponse {
+    pub id: Uuid,
+    // End-to-end id, or the transaction id when the sender gave none
+    #[schema(example = "INV-7781")]
+    pub reference: String,
+    #[schema(example = "camt.054")]
+    pub message_type: String,
+    pub message_id: String,
+    pub source_file: String,
+    pub creditor_iban: Option<String>,
+    pub creditor_name: Option<String>,
+    pub debtor_name: Option<String>,
+    pub debtor_iban: Option<String>,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub remittance_info: Option<String>,
+    pub value_date: Option<NaiveDate>,
+    pub status: InboundPaymentStatus,
+    pub account_id: Option<Uuid>,
+    pub journal_entry_id: Option<Uuid>,
+    #[schema(example = "account is closed")]
+    pub repair_reason: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<InboundPaymentEntity> for InboundPaymentResponse {
+    fn from(value: InboundPaymentEntity) -> Self {
+        Self {
+            id: value.id,
+            reference: value.reference,
+            message_type: value.message_type,
+            message_id: value.message_id,
+            source_file: value.source_file,
+            creditor_iban: value.creditor_iban,
+            creditor_name: value.creditor_name,
+            debtor_name: value.debtor_name,
+            debtor_iban: value.debtor_iban,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            remittance_info: value.remittance_info,
+            value_date: value.value_date,
+            status: value.status,
+            account_id: value.account_id,
+            journal_entry_id: value.journal_entry_id,
+            repair_reason: value.repair_reason,
+            resolved_by: value.resolved_by,
+            resolution_note: value.resolution_note,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct InboundImportResponse {
+    pub files_processed: u32,
+    // Unreadable or not a credit notification, moved aside for a look
+    pub files_failed: u32,
+    pub credits_posted: u32,
+    pub credits_for_repair: u32,
+    // Already received, from an earlier file or earlier in the same one
+    pub credits_skipped: u32,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ResolveRepairRequest {
+    // The customer account the credit was meant for
+    pub account_id: Uuid,
+    #[schema(example = "Sender used the old account IBAN")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct RejectRepairRequest {
+    #[schema(example = "Beneficiary unknown, return to sender")]
+    pub note: String,
+}
@@ -0,0 +1,348 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::config::state::AppState;
+use crate::inbound_payment::{
+    iso20022::parse_credit_notification,
+    models::{INBOUND_CLEARING_COA, InboundPaymentEntity, InboundPaymentStatus, RepairReason},
+    schemas::{
+        InboundImportResponse, InboundPaymentQuery, InboundPaymentResponse, RejectRepairRequest,
+        ResolveRepairRequest,
+    },
+};
+use crate::infra::files::{archive_drop_file, list_drop_files};
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::{models::ContraAccount, service::LedgerService};
+use crate::user::models::AccessRole;
+
+// What one file added to the import summary
+#[derive(Default)]
+struct FileOutcome {
+    posted: u32,
+    for_repair: u32,
+    skipped: u32,
+}
+
+pub struct InboundPaymentService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> InboundPaymentService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // Files are taken in name order. Unreadable ones go to failed/ and the rest to processed/.
+    // A database error leaves the file in place for the next run.
+    #[tracing::instrument("Import inbound payments", skip(self, claims))]
+    pub async fn import(&self, claims: &SessionClaims) -> Result<InboundImportResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let dir = match &self.app_state.inbound.drop_dir {
+            Some(dir) => dir.clone(),
+            None => Err(DomainError::InvalidState(
+                "no inbound payment drop directory is configured".into(),
+            ))?,
+        };
+
+        let files = list_drop_files(&dir, &["xml"])?;
+
+        let mut summary = InboundImportResponse::default();
+
+        for path in files {
+            let bytes = std::fs::read(&path).context("Failed to read inbound payment file")?;
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let folder = match self.ingest_file(&file_name, &bytes, Utc::now()).await {
+                Ok(outcome) => {
+                    summary.files_processed += 1;
+                    summary.credits_posted += outcome.posted;
+                    summary.credits_for_repair += outcome.for_repair;
+                    summary.credits_skipped += outcome.skipped;
+                    "processed"
+                }
+                Err(e @ AppError::Validation(_)) => {
+                    tracing::error!("Inbound payment file {} not read: {}", path.display(), e);
+                    summary.files_failed += 1;
+                    "failed"
+                }
+                Err(e) => return Err(e),
+            };
+
+            archive_drop_file(&dir, &path, folder)?;
+        }
+
+        Ok(summary)
+    }
+
+    // Each credit is posted to the account its creditor IBAN names, against clearing. Credits
+    // for no account, or one that can't take them, wait in the repair queue instead. A
+    // reference already received or already in the journal is skipped.
+    async fn ingest_file(
+        &self,
+        file_name: &str,
+        bytes: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<FileOutcome, AppError> {
+        let mut notification = parse_credit_notification(bytes)?;
+        let credits = std::mem::take(&mut notification.credits);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let ledger = LedgerService::from(self.app_state);
+        let mut outcome = FileOutcome::default();
+
+        for credit in credits {
+            let reference = match credit.reference() {
+                Some(reference) => reference.to_string(),
+                None => Err(ValidationError::MissingField("EndToEndId".into()))?,
+            };
+
+            let received = uow
+                .inbound_payments()
+                .reference_exists(&reference)
+                .await
+                .to_app_err("Failed to check inbound payment reference")?;
+            let journaled = uow
+                .ledgers()
+                .transaction_ref_exists(&reference)
+                .await
+                .to_app_err("Failed to check journal reference")?;
+            if received || journaled {
+                outcome.skipped += 1;
+                continue;
+            }
+
+            let payment =
+                InboundPaymentEntity::received(&notification, file_name, &reference, credit, now);
+
+            let account = match &payment.creditor_iban {
+                Some(iban) => uow
+                    .inbound_payments()
+                    .fetch_account_by_iban(iban)
+                    .await
+                    .to_app_err("Failed to fetch account by IBAN")?,
+                None => None,
+            };
+
+            let routing = match (&payment.creditor_iban, account) {
+                (None, _) => Err((None, RepairReason::MissingAccount)),
+                (Some(iban), None) => Err((None, RepairReason::UnknownAccount(iban.clone()))),
+                (Some(_), Some(account)) => account
+                    .check_credit(&payment.currency)
+                    .map(|_| account.id)
+                    .map_err(|reason| (Some(account.id), reason)),
+            };
+
+            let payment = match routing {
+                Ok(account_id) => {
+                    let journal_entry_id = ledger
+                        .post_account_credit(
+                            &mut uow,
+                            account_id,
+                            reference,
+                            payment.description(),
+                            payment.amount_cents,
+                            ContraAccount::Code(INBOUND_CLEARING_COA),
+                        )
+                        .await?;
+
+                    outcome.posted += 1;
+                    payment.posted(account_id, journal_entry_id)
+                }
+                Err((account_id, reason)) => {
+                    outcome.for_repair += 1;
+                    payment.repair(account_id, reason)
+                }
+            };
+
+            uow.inbound_payments()
+                .insert_payment(&payment)
+                .await
+                .to_app_err("Failed to save inbound payment")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit inbound payments")?;
+
+        Ok(outcome)
+    }
+
+    #[tracing::instrument("List inbound payments", skip(self))]
+    pub async fn list(
+        &self,
+        query: InboundPaymentQuery,
+    ) -> Result<Vec<InboundPaymentResponse>, AppError> {
+        let status = match query.status {
+            Some(s) => Some(s.parse::<InboundPaymentStatus>()?),
+            None => None,
+        };
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payments = uow
+            .inbound_payments()
+            .fetch_payments(status)
+            .await
+            .to_app_err("Failed to fetch inbound payments")?;
+
+        Ok(payments
+            .into_iter()
+            .map(InboundPaymentResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch inbound payment", skip(self))]
+    pub async fn fetch(&self, payment_id: Uuid) -> Result<InboundPaymentResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payment = match uow
+            .inbound_payments()
+            .fetch_payment(payment_id)
+            .await
+            .to_app_err("Failed to fetch inbound payment")?
+        {
+            Some(p) => p,
+            None => Err(DomainError::NotFound("inbound payment".into()))?,
+        };
+
+        Ok(payment.into())
+    }
+
+    // Staff name the account the credit was meant for, it has to be able to take it
+    #[tracing::instrument("Resolve inbound payment", skip(self, claims, request))]
+    pub async fn resolve(
+        &self,
+        claims: &SessionClaims,
+        payment_id: Uuid,
+        request: ResolveRepairRequest,
+    ) -> Result<InboundPaymentResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut payment = Self::fetch_repair_for_update(&mut uow, payment_id).await?;
+
+        let account = match uow
+            .inbound_payments()
+            .fetch_account(request.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+        account
+            .check_credit(&payment.currency)
+            .map_err(DomainError::from)?;
+
+        let journal_entry_id = LedgerService::from(self.app_state)
+            .post_account_credit(
+                &mut uow,
+                account.id,
+                payment.reference.clone(),
+                payment.description(),
+                payment.amount_cents,
+                ContraAccount::Code(INBOUND_CLEARING_COA),
+            )
+            .await?;
+
+        payment.status = InboundPaymentStatus::Resolved;
+        payment.account_id = Some(account.id);
+        payment.journal_entry_id = Some(journal_entry_id);
+        payment.resolved_by = Some(*claims.get_user_id());
+        payment.resolution_note = request.note;
+        payment.updated_at = now;
+
+        uow.inbound_payments()
+            .settle_repair(&payment)
+            .await
+            .to_app_err("Failed to update inbound payment")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit inbound payment resolution")?;
+
+        Ok(payment.into())
+    }
+
+    // The funds stay in clearing until operations send them back to the paying bank
+    #[tracing::instrument("Reject inbound payment", skip(self, claims, request))]
+    pub async fn reject(
+        &self,
+        claims: &SessionClaims,
+        payment_id: Uuid,
+        request: RejectRepairRequest,
+    ) -> Result<InboundPaymentResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let note = request.note.trim();
+        if note.is_empty() {
+            Err(ValidationError::MissingField("note".into()))?
+        }
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut payment = Self::fetch_repair_for_update(&mut uow, payment_id).await?;
+
+        payment.status = InboundPaymentStatus::Rejected;
+        payment.resolved_by = Some(*claims.get_user_id());
+        payment.resolution_note = Some(note.to_string());
+        payment.updated_at = now;
+
+        uow.inbound_payments()
+            .settle_repair(&payment)
+            .await
+            .to_app_err("Failed to update inbound payment")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit inbound payment rejection")?;
+
+        Ok(payment.into())
+    }
+
+    async fn fetch_repair_for_update(
+        uow: &mut UnitofWork<'_>,
+        payment_id: Uuid,
+    ) -> Result<InboundPaymentEntity, AppError> {
+        let payment = match uow
+            .inbound_payments()
+            .fetch_payment_for_update(payment_id)
+            .await
+            .to_app_err("Failed to fetch inbound payment")?
+        {
+            Some(p) => p,
+            None => Err(DomainError::NotFound("inbound payment".into()))?,
+        };
+        payment.check_repair()?;
+
+        Ok(payment)
+    }
+}
@@ -1,6 +1,9 @@
 use anyhow::Context;
 use aws_config::BehaviorVersion;
-use aws_sdk_s3::{Client, Error as S3Error, presigning::PresigningConfig, primitives::ByteStream};
+use aws_sdk_s3::{
+    Client, Error as S3Error, config::Credentials, presigning::PresigningConfig,
+    primitives::ByteStream,
+};
 use aws_types::region::Region;
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,13 +15,31 @@ pub struct S3Client {
 }
 
 impl S3Client {
-    pub async fn default(region: String, bucket: String) -> Self {
+    pub async fn default(
+        region: String,
+        bucket: String,
+        endpoint_url: Option<String>,
+        credentials: Option<Credentials>,
+    ) -> Self {
         let region = Region::new(region);
-        let config = aws_config::defaults(BehaviorVersion::v2025_08_07())
-            .region(region)
-            .load()
-            .await;
-        let client = Arc::new(Client::new(&config));
+        let mut loader = aws_config::defaults(BehaviorVersion::v2025_08_07()).region(region);
+
+        if let Some(credentials) = credentials {
+            loader = loader.credentials_provider(credentials);
+        }
+
+        let config = loader.load().await;
+
+        // S3 compatible stores (MinIO and friends) only serve path style urls
+        let s3_config = match endpoint_url {
+            Some(url) => aws_sdk_s3::config::Builder::from(&config)
+                .endpoint_url(url)
+                .force_path_style(true)
+                .build(),
+            None => aws_sdk_s3::config::Builder::from(&config).build(),
+        };
+
+        let client = Arc::new(Client::from_conf(s3_config));
 
         Self { client, bucket }
     }
@@ -26,15 +47,17 @@ impl S3Client {
     pub async fn upload_to_s3(
         &self,
         file: Vec<u8>,
-        bucket: Arc<String>,
-        key: String,
+        bucket: &str,
+        key: &str,
+        content_type: &str,
     ) -> Result<(), S3Error> {
         let body = ByteStream::from(file);
 
         self.client
             .put_object()
-            .bucket(bucket.as_str())
+            .bucket(bucket)
             .key(key)
+            .content_type(content_type)
             .body(body)
             .send()
             .await?;
@@ -0,0 +1,101 @@
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+use crate::infra::aws::S3Client;
+
+// Where a file for a clearing system is written, a local directory when one is configured or S3
+#[derive(Debug)]
+pub enum OutboundFile {
+    Local(PathBuf),
+    S3 { bucket: String, key: String },
+}
+
+impl OutboundFile {
+    pub fn new(dir: Option<&Path>, bucket: &str, key_prefix: &str, file_name: &str) -> Self {
+        match dir {
+            Some(dir) => Self::Local(dir.join(file_name)),
+            None => Self::S3 {
+                bucket: bucket.to_string(),
+                key: format!("{}/{}", key_prefix, file_name),
+            },
+        }
+    }
+
+    pub fn location(&self) -> String {
+        match self {
+            Self::Local(path) => path.display().to_string(),
+            Self::S3 { bucket, key } => format!("s3://{}/{}", bucket, key),
+        }
+    }
+
+    pub async fn store(
+        &self,
+        s3_client: &S3Client,
+        contents: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Self::Local(path) => {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).context("Failed to create outbound directory")?;
+                }
+                std::fs::write(path, contents).context("Failed to write outbound file")?;
+            }
+            Self::S3 { bucket, key } => {
+                s3_client
+                    .upload_to_s3(contents, bucket, key, content_type)
+                    .await
+                    .context("Failed to upload outbound file")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // For a file whose batch never committed, failing here only leaves a stray file behind
+    pub async fn discard(&self, s3_client: &S3Client) {
+        let result = match self {
+            Self::Local(path) => std::fs::remove_file(path).map_err(anyhow::Error::from),
+            Self::S3 { bucket, key } => s3_client.delete_from_s3(bucket, key).await,
+        };
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Uncommitted outbound file {} left behind: {:?}",
+                self.location(),
+                e
+            );
+        }
+    }
+}
+
+// Files waiting in a drop directory with one of the given extensions, in name order
+pub fn list_drop_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read drop directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| {
+                    extensions
+                        .iter()
+                        .any(|wanted| ext.eq_ignore_ascii_case(wanted))
+                })
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+// Moves a handled file into a sub folder of its drop directory, processed/ or failed/
+pub fn archive_drop_file(dir: &Path, path: &Path, folder: &str) -> Result<(), anyhow::Error> {
+    let target = dir.join(folder);
+    std::fs::create_dir_all(&target).context("Failed to create drop directory archive")?;
+
+    if let Some(name) = path.file_name() {
+        std::fs::rename(path, target.join(name)).context("Failed to archive drop file")?;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
 pub mod aws;
+pub mod files;
 pub mod pgdb;
 pub mod redis;
@@ -1,9 +1,17 @@
 use sqlx::{PgPool, Postgres, Transaction};
 
 use crate::{
-    account::repo::AccountRepository, authentication::repo::AuthRepository,
-    ledger::repo::LedgerRepository, staff::repo::StaffRepository,
-    transaction::repo::TransactionRepository, user::repo::UserRepository,
+    account::repo::AccountRepository, ach::repo::AchRepository, api_key::repo::ApiKeyRepository,
+    authentication::repo::AuthRepository, beneficiary::repo::BeneficiaryRepository,
+    branch::repo::BranchRepository, customer::repo::CustomerRepository,
+    fiscal::repo::FiscalRepository, hold::repo::HoldRepository,
+    identity_verify::repo::KycRepository, inbound_payment::repo::InboundPaymentRepository,
+    ledger::repo::LedgerRepository, nostro::repo::NostroRepository,
+    overdraft::repo::OverdraftRepository, product::repo::ProductRepository,
+    reporting::repo::StatementRepository, scheduled_payment::repo::ScheduledPaymentRepository,
+    screening::repo::ScreeningRepository, sepa::repo::SepaRepository, staff::repo::StaffRepository,
+    swift::repo::SwiftRepository, transaction::repo::TransactionRepository,
+    user::repo::UserRepository,
 };
 
 pub struct UnitofWork<'a> {
@@ -27,7 +35,7 @@ impl<'a> UnitofWork<'a> {
     }
 
     pub fn users(&mut self) -> UserRepository<'a, '_> {
-        UserRepository::from(self.pool, &mut self.tx)
+        UserRepository::from(&mut self.tx)
     }
 
     pub fn accounts(&mut self) -> AccountRepository<'a, '_> {
@@ -44,4 +52,72 @@ impl<'a> UnitofWork<'a> {
     pub fn transactions(&mut self) -> TransactionRepository<'a, '_> {
         TransactionRepository::from(self.pool, &mut self.tx)
     }
+
+    pub fn api_keys(&mut self) -> ApiKeyRepository<'a, '_> {
+        ApiKeyRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn kyc(&mut self) -> KycRepository<'a, '_> {
+        KycRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn screening(&mut self) -> ScreeningRepository<'a, '_> {
+        ScreeningRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn customers(&mut self) -> CustomerRepository<'a, '_> {
+        CustomerRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn branches(&mut self) -> BranchRepository<'a, '_> {
+        BranchRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn overdrafts(&mut self) -> OverdraftRepository<'a, '_> {
+        OverdraftRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn holds(&mut self) -> HoldRepository<'a, '_> {
+        HoldRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn products(&mut self) -> ProductRepository<'a, '_> {
+        ProductRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn beneficiaries(&mut self) -> BeneficiaryRepository<'a, '_> {
+        BeneficiaryRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn scheduled_payments(&mut self) -> ScheduledPaymentRepository<'a, '_> {
+        ScheduledPaymentRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn sepa(&mut self) -> SepaRepository<'a, '_> {
+        SepaRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn ach(&mut self) -> AchRepository<'a, '_> {
+        AchRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn swift(&mut self) -> SwiftRepository<'a, '_> {
+        SwiftRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn nostro(&mut self) -> NostroRepository<'a, '_> {
+        NostroRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn inbound_payments(&mut self) -> InboundPaymentRepository<'a, '_> {
+        InboundPaymentRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn statements(&mut self) -> StatementRepository<'a, '_> {
+        StatementRepository::from(self.pool, &mut self.tx)
+    }
+
+    pub fn fiscal(&mut self) -> FiscalRepository<'a, '_> {
+        FiscalRepository::from(self.pool, &mut self.tx)
+    }
 }
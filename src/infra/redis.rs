@@ -55,4 +55,27 @@ impl RedisPool {
 
         Ok(())
     }
+
+    // Counter for fixed window rate limits, the window starts on the first hit
+    pub async fn incr_with_ttl(&self, key: &str, ttl: u64) -> Result<u64, anyhow::Error> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get redis connection")?;
+
+        let count: u64 = conn
+            .incr(key, 1)
+            .await
+            .context("Failed to increment counter in redis")?;
+
+        if count == 1 {
+            let _: bool = conn
+                .expire(key, ttl as i64)
+                .await
+                .context("Failed to set counter expiry in redis")?;
+        }
+
+        Ok(count)
+    }
 }
@@ -3,6 +3,7 @@ use utoipa::OpenApi;
 #[derive(OpenApi)]
 #[openapi(paths(
     crate::ledger::routes::journal_entry_by_id,
-    crate::ledger::routes::journal_entry
+    crate::ledger::routes::journal_entry,
+    crate::ledger::routes::post_adjusting_entry
 ))]
 pub struct LedgerApi;
@@ -1,14 +1,42 @@
+use chrono::{NaiveDate, NaiveDateTime};
 use getset::Getters;
+use strum::Display;
 use uuid::Uuid;
 
+use crate::base::error::{AppError, DomainError, SqlErrorExt, ValidationError};
+use crate::fiscal::models::FiscalPeriodStatus;
+use crate::ledger::schemas::AdjustingEntryRequest;
+use crate::staff::models::CoaType;
+
+const MAX_REFERENCE_LEN: usize = 50;
+
+// What a fiscal period closed to regular postings still takes
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "journal_entry_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum EntryKind {
+    Regular,
+    Adjusting,
+    Closing,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Getters)]
 #[get = "pub with_prefix"]
 pub struct JournalEntry {
     id: Uuid,
-    user_account_id: Uuid,
+    // None for the bank's own entries
+    user_account_id: Option<Uuid>,
     transaction_id: String,
     transaction_ref: String,
     description: String,
+    api_key_id: Option<Uuid>,
+    device_id: Option<String>,
+    entry_kind: EntryKind,
+    // Dated now when not set
+    posted_at: Option<NaiveDateTime>,
 }
 
 impl JournalEntry {
@@ -20,10 +48,137 @@ impl JournalEntry {
     ) -> Self {
         JournalEntry {
             id: Uuid::now_v7(),
-            user_account_id,
+            user_account_id: Some(user_account_id),
             transaction_id,
             transaction_ref,
             description,
+            api_key_id: None,
+            device_id: None,
+            entry_kind: EntryKind::Regular,
+            posted_at: None,
+        }
+    }
+
+    // An adjusting or closing entry between the bank's own chart accounts, dated into the
+    // period it corrects or closes
+    pub fn general(
+        entry_kind: EntryKind,
+        posted_at: NaiveDateTime,
+        transaction_id: String,
+        transaction_ref: String,
+        description: String,
+    ) -> Self {
+        JournalEntry {
+            id: Uuid::now_v7(),
+            user_account_id: None,
+            transaction_id,
+            transaction_ref,
+            description,
+            api_key_id: None,
+            device_id: None,
+            entry_kind,
+            posted_at: Some(posted_at),
+        }
+    }
+
+    // Records the integration and device that posted the entry
+    pub fn posted_by(mut self, api_key_id: Uuid, device_id: Option<String>) -> Self {
+        self.api_key_id = Some(api_key_id);
+        self.device_id = device_id;
+        self
+    }
+}
+
+// A correction the bank books between two chart accounts, dated on the last second of the
+// day it belongs to so it lands in that day's fiscal period
+#[derive(Debug)]
+pub struct AdjustingEntry {
+    pub posted_at: NaiveDateTime,
+    pub transaction_ref: String,
+    pub description: String,
+    pub debit_code: String,
+    pub credit_code: String,
+    pub amount_cents: i64,
+}
+
+impl AdjustingEntry {
+    pub fn parse(
+        request: &AdjustingEntryRequest,
+        today: NaiveDate,
+    ) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: "Must be greater than zero".into(),
+            });
+        }
+        if request.posted_on > today {
+            return Err(ValidationError::InvalidValue {
+                field: "posted_on".into(),
+                reason: "Can't be in the future".into(),
+            });
+        }
+
+        let transaction_ref = request.transaction_ref.trim();
+        if transaction_ref.is_empty() {
+            return Err(ValidationError::MissingField("transaction_ref".into()));
+        }
+        if transaction_ref.len() > MAX_REFERENCE_LEN {
+            return Err(ValidationError::TooLong {
+                field: "transaction_ref".into(),
+                max: MAX_REFERENCE_LEN,
+            });
+        }
+
+        let description = request.description.trim();
+        if description.is_empty() {
+            return Err(ValidationError::MissingField("description".into()));
+        }
+
+        let debit_code = request.debit_code.trim();
+        let credit_code = request.credit_code.trim();
+        if debit_code == credit_code {
+            return Err(ValidationError::InvalidValue {
+                field: "credit_code".into(),
+                reason: "Must differ from the debit chart account".into(),
+            });
+        }
+
+        Ok(Self {
+            posted_at: request
+                .posted_on
+                .and_hms_opt(23, 59, 59)
+                .unwrap_or_default(),
+            transaction_ref: transaction_ref.to_string(),
+            description: description.to_string(),
+            debit_code: debit_code.to_string(),
+            credit_code: credit_code.to_string(),
+            amount_cents: request.amount_cents,
+        })
+    }
+}
+
+// Why an entry can't go into the ledger
+#[derive(Debug, thiserror::Error)]
+pub enum PostingError {
+    #[error("Fiscal period {start} to {end} is {status}, {kind} entries can't be posted to it")]
+    PeriodClosed {
+        start: NaiveDate,
+        end: NaiveDate,
+        status: FiscalPeriodStatus,
+        kind: EntryKind,
+    },
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl<T> SqlErrorExt<T> for Result<T, PostingError> {
+    fn to_app_err(self, context: &str) -> Result<T, AppError> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(PostingError::Database(e)) => Err::<T, sqlx::Error>(e).to_app_err(context),
+            Err(e) => Err(DomainError::InvalidState(e.to_string()))?,
         }
     }
 }
@@ -104,4 +259,64 @@ impl IntoJournalLine {
             credit_line,
         }
     }
+
+    pub fn from_cents(
+        journal_entry_id: Uuid,
+        amount_cents: i64,
+        debit_line: DebitLine,
+        credit_line: CreditLine,
+    ) -> Self {
+        IntoJournalLine {
+            amount_cents,
+            journal_entry_id,
+            debit_line,
+            credit_line,
+        }
+    }
+}
+
+// The other side of a customer posting, any chart account of a type or one picked by its code
+#[derive(Debug, Clone, Copy)]
+pub enum ContraAccount<'c> {
+    Type(CoaType),
+    Code(&'c str),
+}
+
+impl From<CoaType> for ContraAccount<'_> {
+    fn from(coa_type: CoaType) -> Self {
+        ContraAccount::Type(coa_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    use super::AdjustingEntry;
+    use crate::ledger::schemas::AdjustingEntryRequest;
+
+    fn request(posted_on: NaiveDate, credit_code: &str) -> AdjustingEntryRequest {
+        AdjustingEntryRequest {
+            posted_on,
+            transaction_ref: " ADJ-1001 ".into(),
+            description: "Accrued audit fees".into(),
+            debit_code: "5220".into(),
+            credit_code: credit_code.into(),
+            amount_cents: 150_000,
+        }
+    }
+
+    #[test]
+    fn adjustments_are_dated_at_the_end_of_their_day() {
+        let today = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+
+        let adjustment = assert_ok!(AdjustingEntry::parse(&request(today, "2300"), today));
+        assert_eq!(adjustment.transaction_ref, "ADJ-1001");
+        assert_eq!(adjustment.posted_at, today.and_hms_opt(23, 59, 59).unwrap());
+
+        let _ = assert_err!(AdjustingEntry::parse(&request(today, "5220"), today));
+        let tomorrow = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let _ = assert_err!(AdjustingEntry::parse(&request(tomorrow, "2300"), today));
+    }
 }
@@ -1,6 +1,7 @@
 use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
 
-use crate::ledger::models::{IntoJournalLine, JournalEntry};
+use crate::fiscal::models::FiscalPeriodEntity;
+use crate::ledger::models::{IntoJournalLine, JournalEntry, PostingError};
 use crate::ledger::schemas::{JournalEntryLine, JournalIdRequest, JournalRequest};
 
 pub struct LedgerRepository<'a, 'b> {
@@ -77,16 +78,60 @@ where
         Ok(result)
     }
 
+    // Checked before posting with a reference someone else chose, the insert would abort the tx
+    #[tracing::instrument("Check journal entry reference in db", skip(self))]
+    pub async fn transaction_ref_exists(
+        &mut self,
+        transaction_ref: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM journal_entry WHERE transaction_ref=$1)",
+        )
+        .bind(transaction_ref)
+        .fetch_one(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Rejected when the day it's dated falls in a fiscal period closed to its kind of entry.
+    // The period stays locked until the tx ends, it can't be closed under the posting.
     pub async fn create_ledger_journal_entry(
         &mut self,
         journal_entry: &JournalEntry,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT INTO journal_entry(id, user_account_id, transaction_id, transaction_ref, description) VALUES($1, $2, $3, $4, $5)")
+    ) -> Result<(), PostingError> {
+        let period = sqlx::query_as::<_, FiscalPeriodEntity>(
+            "SELECT id, fiscal_year_id, period_number, start_date, end_date, status, updated_by,
+                    updated_at
+                FROM fiscal_period
+                WHERE COALESCE($1, LOCALTIMESTAMP)::DATE BETWEEN start_date AND end_date
+                FOR SHARE",
+        )
+        .bind(journal_entry.get_posted_at())
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        if let Some(period) = period
+            && !period.status.accepts(*journal_entry.get_entry_kind())
+        {
+            return Err(PostingError::PeriodClosed {
+                start: period.start_date,
+                end: period.end_date,
+                status: period.status,
+                kind: *journal_entry.get_entry_kind(),
+            });
+        }
+
+        sqlx::query("INSERT INTO journal_entry(id, user_account_id, transaction_id, transaction_ref, description, api_key_id, device_id, entry_kind, created_date) VALUES($1, $2, $3, $4, $5, $6, $7, $8, COALESCE($9, LOCALTIMESTAMP))")
         .bind(journal_entry.get_id())
         .bind(journal_entry.get_user_account_id())
         .bind(journal_entry.get_transaction_id())
         .bind(journal_entry.get_transaction_ref())
         .bind(journal_entry.get_description())
+        .bind(journal_entry.get_api_key_id())
+        .bind(journal_entry.get_device_id())
+        .bind(journal_entry.get_entry_kind())
+        .bind(journal_entry.get_posted_at())
         .execute(&mut **self.tx)
         .await?;
 
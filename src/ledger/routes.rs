@@ -1,9 +1,13 @@
 use actix_web::{HttpResponse, web};
 
 use crate::{
+    authentication::token::SessionClaims,
     config::state::AppState,
     ledger::{
-        schemas::{JournalIdRequest, JournalRequest, JournalResponse},
+        schemas::{
+            AdjustingEntryRequest, AdjustingEntryResponse, JournalIdRequest, JournalRequest,
+            JournalResponse,
+        },
         service::LedgerService,
     },
 };
@@ -35,6 +39,22 @@ pub async fn journal_entry_by_id(
     Ok(HttpResponse::Ok().json(response))
 }
 
+#[tracing::instrument("Post adjusting entry", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/adjustments", request_body=AdjustingEntryRequest, responses((status=200, body=AdjustingEntryResponse, description="Adjusting entry posted"), (status=400, description="Invalid amount, reference, date or chart accounts"), (status=403, description="Only superusers can post adjusting entries"), (status=404, description="Chart account not found"), (status=409, description="Reference already used or the fiscal period is hard-closed")))]
+pub async fn post_adjusting_entry(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<AdjustingEntryRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let ledger_service = LedgerService::from(&app_state);
+
+    let response = ledger_service
+        .post_adjusting_entry(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
 pub async fn get_balance() {}
 
 pub async fn get_trial_balance() {}
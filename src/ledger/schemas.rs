@@ -90,3 +90,26 @@ impl FromIterator<JEntryLineResponse> for JournalResponse {
 pub struct JournalIdRequest {
     pub journal_id: Uuid,
 }
+
+// Booked by the bank against two chart accounts, goes into soft-closed periods as well
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct AdjustingEntryRequest {
+    #[schema(example = "2025-12-31")]
+    pub posted_on: chrono::NaiveDate,
+    #[schema(example = "ADJ-2025-001")]
+    pub transaction_ref: String,
+    #[schema(example = "Accrued audit fees")]
+    pub description: String,
+    #[schema(example = "5220")]
+    pub debit_code: String,
+    #[schema(example = "2300")]
+    pub credit_code: String,
+    #[schema(example = 150000)]
+    pub amount_cents: i64,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AdjustingEntryResponse {
+    pub journal_entry_id: Uuid,
+    pub posted_at: chrono::NaiveDateTime,
+}
@@ -1,8 +1,24 @@
+use chrono::Utc;
+use uuid::Uuid;
+
 use crate::{
-    base::error::{AppError, SqlErrorExt},
+    authentication::token::SessionClaims,
+    base::error::{AppError, AuthError, DomainError, SqlErrorExt},
     config::state::AppState,
     infra::pgdb::UnitofWork,
-    ledger::schemas::{JournalIdRequest, JournalRequest, JournalResponse},
+    ledger::{
+        models::{
+            AdjustingEntry, ContraAccount, CreditLine, DebitLine, EntryKind, IntoJournalLine,
+            JournalEntry, LineType,
+        },
+        schemas::{
+            AdjustingEntryRequest, AdjustingEntryResponse, JournalIdRequest, JournalRequest,
+            JournalResponse,
+        },
+    },
+    staff::models::CoaType,
+    transaction::service::TransactionService,
+    user::models::AccessRole,
 };
 
 pub struct LedgerService<'a> {
@@ -51,4 +67,224 @@ impl<'a> LedgerService<'a> {
 
         Ok(response)
     }
+
+    // Corrections go through even once the period is soft-closed, only a hard close stops them
+    #[tracing::instrument("Post adjusting entry", skip(self, claims))]
+    pub async fn post_adjusting_entry(
+        &self,
+        claims: &SessionClaims,
+        request: AdjustingEntryRequest,
+    ) -> Result<AdjustingEntryResponse, AppError> {
+        match claims.get_role() {
+            AccessRole::Superuser => {}
+            _ => Err(AuthError::InsufficientPermissions)?,
+        }
+
+        let adjustment = AdjustingEntry::parse(&request, Utc::now().date_naive())?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let debit_coa_id =
+            Self::fetch_coa_id(&mut uow, ContraAccount::Code(&adjustment.debit_code)).await?;
+        let credit_coa_id =
+            Self::fetch_coa_id(&mut uow, ContraAccount::Code(&adjustment.credit_code)).await?;
+
+        let (debit_coa_id, credit_coa_id) = match (debit_coa_id, credit_coa_id) {
+            (Some(dc), Some(cc)) => (dc, cc),
+            (None, _) => Err(DomainError::NotFound("Debit chart account".into()))?,
+            (_, None) => Err(DomainError::NotFound("Credit chart account".into()))?,
+        };
+
+        let journal_entry = JournalEntry::general(
+            EntryKind::Adjusting,
+            adjustment.posted_at,
+            TransactionService::from(self.app_state).generate_transaction_id(),
+            adjustment.transaction_ref,
+            adjustment.description,
+        );
+
+        uow.ledgers()
+            .create_ledger_journal_entry(&journal_entry)
+            .await
+            .to_app_err("transaction reference")?;
+
+        uow.ledgers()
+            .create_ledger_journal_line(IntoJournalLine::from_cents(
+                *journal_entry.get_id(),
+                adjustment.amount_cents,
+                DebitLine::new(debit_coa_id, LineType::Debit),
+                CreditLine::new(credit_coa_id, LineType::Credit),
+            ))
+            .await
+            .to_app_err("Failed to create journal line")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit adjusting entry")?;
+
+        Ok(AdjustingEntryResponse {
+            journal_entry_id: *journal_entry.get_id(),
+            posted_at: adjustment.posted_at,
+        })
+    }
+
+    // Takes money out of a customer account, the customer's deposit liability is debited
+    // and the contra chart account credited. An open hold with the same reference is
+    // settled by the posting.
+    pub async fn post_account_debit<'c>(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        transaction_ref: String,
+        description: String,
+        amount_cents: i64,
+        contra: impl Into<ContraAccount<'c>>,
+    ) -> Result<Uuid, AppError> {
+        let journal_entry_id = self
+            .post_account_entry(
+                uow,
+                account_id,
+                transaction_ref.clone(),
+                description,
+                amount_cents,
+                (CoaType::Liability.into(), contra.into()),
+            )
+            .await?;
+
+        uow.holds()
+            .settle_holds(account_id, &transaction_ref, journal_entry_id)
+            .await
+            .to_app_err("Failed to settle holds")?;
+
+        Ok(journal_entry_id)
+    }
+
+    // Pays money into a customer account against the contra chart account
+    pub async fn post_account_credit<'c>(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        transaction_ref: String,
+        description: String,
+        amount_cents: i64,
+        contra: impl Into<ContraAccount<'c>>,
+    ) -> Result<Uuid, AppError> {
+        self.post_account_entry(
+            uow,
+            account_id,
+            transaction_ref,
+            description,
+            amount_cents,
+            (contra.into(), CoaType::Liability.into()),
+        )
+        .await
+    }
+
+    // Moves money between two customer accounts through clearing. Each side gets its own
+    // entry, the credit carries the reference with a `-CR` suffix.
+    pub async fn post_internal_transfer(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        from_account_id: Uuid,
+        to_account_id: Uuid,
+        transaction_ref: String,
+        description: String,
+        amount_cents: i64,
+    ) -> Result<(Uuid, Uuid), AppError> {
+        let credit_ref = format!("{}-CR", transaction_ref);
+
+        let debit_entry_id = self
+            .post_account_debit(
+                uow,
+                from_account_id,
+                transaction_ref,
+                description.clone(),
+                amount_cents,
+                CoaType::Asset,
+            )
+            .await?;
+
+        let credit_entry_id = self
+            .post_account_credit(
+                uow,
+                to_account_id,
+                credit_ref,
+                description,
+                amount_cents,
+                CoaType::Asset,
+            )
+            .await?;
+
+        Ok((debit_entry_id, credit_entry_id))
+    }
+
+    async fn fetch_coa_id(
+        uow: &mut UnitofWork<'_>,
+        contra: ContraAccount<'_>,
+    ) -> Result<Option<Uuid>, AppError> {
+        match contra {
+            ContraAccount::Type(coa_type) => uow
+                .staffs()
+                .fetch_coa_id_by_coa_type(coa_type)
+                .await
+                .to_app_err("Failed to fetch coa_id by coa type"),
+            ContraAccount::Code(code) => uow
+                .staffs()
+                .fetch_coa_id_by_code(code)
+                .await
+                .to_app_err("Failed to fetch coa_id by code"),
+        }
+    }
+
+    async fn post_account_entry(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        transaction_ref: String,
+        description: String,
+        amount_cents: i64,
+        (debit, credit): (ContraAccount<'_>, ContraAccount<'_>),
+    ) -> Result<Uuid, AppError> {
+        let journal_entry = JournalEntry::new(
+            account_id,
+            TransactionService::from(self.app_state).generate_transaction_id(),
+            transaction_ref,
+            description,
+        );
+
+        let debit_coa_id = Self::fetch_coa_id(uow, debit).await?;
+        let credit_coa_id = Self::fetch_coa_id(uow, credit).await?;
+
+        let (debit_coa_id, credit_coa_id) = match (debit_coa_id, credit_coa_id) {
+            (Some(dc), Some(cc)) => (dc, cc),
+            (None, _) => Err(DomainError::NotFound(
+                "Missing associated Debit chart account".into(),
+            ))?,
+            (_, None) => Err(DomainError::NotFound(
+                "Missing associated Credit chart account".into(),
+            ))?,
+        };
+
+        let journal_line = IntoJournalLine::from_cents(
+            *journal_entry.get_id(),
+            amount_cents,
+            DebitLine::new(debit_coa_id, LineType::Debit),
+            CreditLine::new(credit_coa_id, LineType::Credit),
+        );
+
+        // Unique violation when the reference has been used before
+        uow.ledgers()
+            .create_ledger_journal_entry(&journal_entry)
+            .await
+            .to_app_err("transaction reference")?;
+
+        uow.ledgers()
+            .create_ledger_journal_line(journal_line)
+            .await
+            .to_app_err("Failed to create journal line")?;
+
+        Ok(*journal_entry.get_id())
+    }
 }
@@ -1,21 +1,35 @@
 pub mod account;
+pub mod ach;
 pub mod analytics;
+pub mod api_key;
 pub mod authentication;
 pub mod base;
+pub mod beneficiary;
+pub mod branch;
 pub mod card;
 pub mod config;
 pub mod credit_risk;
 pub mod customer;
+pub mod fiscal;
+pub mod hold;
 pub mod identity_verify;
+pub mod inbound_payment;
 pub mod index;
 pub mod infra;
 pub mod ledger;
 pub mod loan;
+pub mod nostro;
 pub mod notification;
 pub mod openapi_docs;
+pub mod overdraft;
+pub mod product;
 pub mod reporting;
+pub mod scheduled_payment;
+pub mod screening;
+pub mod sepa;
 pub mod staff;
 pub mod startup;
+pub mod swift;
 pub mod telemetry;
 pub mod transaction;
 pub mod transaction_monitor;
@@ -1,6 +1,8 @@
 use std::fmt::{Debug, Display};
 use std::sync::Arc;
+use thalia::authentication::cleanup::run_cleanup_until_stopped;
 use thalia::config::runtime::get_config;
+use thalia::scheduled_payment::scheduler::run_scheduler_until_stopped;
 use thalia::startup::Application;
 use thalia::telemetry::{get_tracing_subscriber, init_tracing_subscriber};
 use tokio::task::JoinError;
@@ -33,9 +35,13 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let app = Application::build(&config.clone()).await?;
     let app_task = tokio::spawn(app.run_until_stopped());
+    let cleanup_task = tokio::spawn(run_cleanup_until_stopped(config.clone()));
+    let scheduler_task = tokio::spawn(run_scheduler_until_stopped(config.clone()));
 
     tokio::select! {
         o = app_task => {report_exit("api", o);}
+        o = cleanup_task => {report_exit("activate token cleanup", o);}
+        o = scheduler_task => {report_exit("payment scheduler", o);}
     }
 
     Ok(())
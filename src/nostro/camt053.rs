@@ -0,0 +1,356 @@
+use chrono::NaiveDate;
+use quick_xml::events::Event;
+
+use crate::base::error::ValidationError;
+use crate::inbound_payment::iso20022::{normalize_iban, parse_amount};
+use crate::nostro::models::{BankStatement, Direction, StatementEntry, StatementFormat};
+
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+#[derive(Default)]
+struct Balance {
+    code: String,
+    amount_cents: i64,
+    currency: String,
+    debit: bool,
+    date: Option<NaiveDate>,
+}
+
+impl Balance {
+    fn signed_cents(&self) -> i64 {
+        if self.debit {
+            -self.amount_cents
+        } else {
+            self.amount_cents
+        }
+    }
+}
+
+// A statement entry as read, only booked ones make it onto the statement
+#[derive(Default)]
+struct Entry {
+    amount_cents: i64,
+    debit: Option<bool>,
+    reversal: bool,
+    booked: bool,
+    booking_date: Option<NaiveDate>,
+    value_date: Option<NaiveDate>,
+    servicer_reference: Option<String>,
+    reference: Option<String>,
+    narrative: Option<String>,
+}
+
+fn invalid_statement(reason: String) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: "statement".into(),
+        reason,
+    }
+}
+
+fn at(path: &[String], suffix: &[&str]) -> bool {
+    path.len() >= suffix.len()
+        && path[path.len() - suffix.len()..]
+            .iter()
+            .zip(suffix)
+            .all(|(element, name)| element == name)
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, ValidationError> {
+    // Date and time elements carry the date first
+    NaiveDate::parse_from_str(text.get(..10).unwrap_or(text), "%Y-%m-%d")
+        .map_err(|_| invalid_statement(format!("Invalid date {}", text)))
+}
+
+fn amount(text: &str) -> Result<i64, ValidationError> {
+    parse_amount(text).ok_or_else(|| invalid_statement(format!("Invalid amount {}", text)))
+}
+
+fn finish(entry: Entry) -> Result<StatementEntry, ValidationError> {
+    let value_date = match entry.value_date.or(entry.booking_date) {
+        Some(date) => date,
+        None => {
+            return Err(invalid_statement(
+                "Entry without a value or booking date".into(),
+            ));
+        }
+    };
+
+    let debit = match entry.debit {
+        Some(debit) => debit,
+        None => return Err(ValidationError::MissingField("CdtDbtInd".into())),
+    };
+    if entry.amount_cents <= 0 {
+        return Err(invalid_statement("Entry without an amount".into()));
+    }
+
+    // A reversal undoes an entry the other way, so it moves the balance against its indicator
+    let direction = match debit != entry.reversal {
+        true => Direction::Debit,
+        false => Direction::Credit,
+    };
+
+    Ok(StatementEntry {
+        value_date,
+        booking_date: entry.booking_date,
+        direction,
+        amount_cents: entry.amount_cents,
+        reference: entry.reference,
+        bank_reference: entry.servicer_reference,
+        narrative: entry.narrative,
+    })
+}
+
+// The first statement of any camt.053 version, elements are matched by local name
+pub fn parse(bytes: &[u8]) -> Result<BankStatement, ValidationError> {
+    let mut reader = quick_xml::Reader::from_reader(bytes);
+    reader.config_mut().trim_text(true);
+
+    let mut found = false;
+    let mut statement_ref = String::new();
+    let mut sequence = None;
+    let mut account = String::new();
+    let mut currency = String::new();
+    let mut opening: Option<Balance> = None;
+    let mut closing: Option<Balance> = None;
+    let mut entries = Vec::new();
+
+    let mut path: Vec<String> = Vec::new();
+    let mut amount_currency = String::new();
+    let mut balance = Balance::default();
+    let mut entry: Option<Entry> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| invalid_statement(format!("Malformed statement: {}", e)))?;
+
+        match event {
+            Event::Start(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "BkToCstmrStmt" => found = true,
+                    "Bal" => balance = Balance::default(),
+                    "Ntry" => entry = Some(Entry::default()),
+                    "Amt" => {
+                        amount_currency = e
+                            .try_get_attribute("Ccy")
+                            .ok()
+                            .flatten()
+                            .map(|a| String::from_utf8_lossy(&a.value).to_uppercase())
+                            .unwrap_or_default();
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Text(ref e) => {
+                let text = e
+                    .decode()
+                    .map_err(|e| invalid_statement(format!("Malformed statement: {}", e)))?
+                    .trim()
+                    .to_string();
+
+                if let Some(entry) = entry.as_mut() {
+                    if at(&path, &["Ntry", "Amt"]) {
+                        entry.amount_cents = amount(&text)?;
+                    } else if at(&path, &["Ntry", "CdtDbtInd"]) {
+                        entry.debit = Some(text == "DBIT");
+                    } else if at(&path, &["Ntry", "RvslInd"]) {
+                        entry.reversal = text == "true";
+                    } else if at(&path, &["Ntry", "Sts"]) || at(&path, &["Ntry", "Sts", "Cd"]) {
+                        entry.booked = text == "BOOK";
+                    } else if at(&path, &["Ntry", "BookgDt", "Dt"])
+                        || at(&path, &["Ntry", "BookgDt", "DtTm"])
+                    {
+                        entry.booking_date = Some(parse_date(&text)?);
+                    } else if at(&path, &["Ntry", "ValDt", "Dt"])
+                        || at(&path, &["Ntry", "ValDt", "DtTm"])
+                    {
+                        entry.value_date = Some(parse_date(&text)?);
+                    } else if at(&path, &["Ntry", "AcctSvcrRef"]) {
+                        entry.servicer_reference = Some(text);
+                    } else if at(&path, &["Refs", "EndToEndId"])
+                        || at(&path, &["Refs", "InstrId"])
+                        || at(&path, &["Refs", "MsgId"])
+                    {
+                        // The first reference we gave wins, in the order they appear
+                        if text != NOT_PROVIDED && entry.reference.is_none() {
+                            entry.reference = Some(text);
+                        }
+                    } else if at(&path, &["Ntry", "AddtlNtryInf"])
+                        || at(&path, &["RmtInf", "Ustrd"])
+                    {
+                        match entry.narrative.as_mut() {
+                            Some(narrative) => {
+                                narrative.push(' ');
+                                narrative.push_str(&text);
+                            }
+                            None => entry.narrative = Some(text),
+                        }
+                    }
+                } else if at(&path, &["Stmt", "Id"]) {
+                    statement_ref = text;
+                } else if at(&path, &["Stmt", "ElctrncSeqNb"]) {
+                    sequence = Some(text);
+                } else if at(&path, &["Stmt", "Acct", "Id", "IBAN"]) {
+                    account = normalize_iban(&text);
+                } else if at(&path, &["Stmt", "Acct", "Id", "Othr", "Id"]) {
+                    account = text;
+                } else if at(&path, &["Stmt", "Acct", "Ccy"]) {
+                    currency = text.to_uppercase();
+                } else if at(&path, &["Bal", "Tp", "CdOrPrtry", "Cd"]) {
+                    balance.code = text;
+                } else if at(&path, &["Bal", "Amt"]) {
+                    balance.amount_cents = amount(&text)?;
+                    balance.currency = amount_currency.clone();
+                } else if at(&path, &["Bal", "CdtDbtInd"]) {
+                    balance.debit = text == "DBIT";
+                } else if at(&path, &["Bal", "Dt", "Dt"]) || at(&path, &["Bal", "Dt", "DtTm"]) {
+                    balance.date = Some(parse_date(&text)?);
+                }
+            }
+            Event::End(ref e) => {
+                match e.local_name().as_ref() {
+                    // An opening balance is the booked one at the start, or the previous closing
+                    b"Bal" => match balance.code.as_str() {
+                        "OPBD" | "PRCD" if opening.is_none() => {
+                            opening = Some(std::mem::take(&mut balance));
+                        }
+                        "CLBD" => closing = Some(std::mem::take(&mut balance)),
+                        _ => {}
+                    },
+                    b"Ntry" => {
+                        if let Some(done) = entry.take()
+                            && done.booked
+                        {
+                            entries.push(finish(done)?);
+                        }
+                    }
+                    // Only the first statement of a message is taken
+                    b"Stmt" => break,
+                    _ => {}
+                }
+                path.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !found {
+        return Err(invalid_statement("Not a camt.053 statement".into()));
+    }
+    if statement_ref.is_empty() {
+        return Err(ValidationError::MissingField("Stmt/Id".into()));
+    }
+    if account.is_empty() {
+        return Err(ValidationError::MissingField("Stmt/Acct/Id".into()));
+    }
+
+    let opening = opening.ok_or(ValidationError::MissingField("OPBD balance".into()))?;
+    let closing = closing.ok_or(ValidationError::MissingField("CLBD balance".into()))?;
+    if currency.is_empty() {
+        currency = closing.currency.clone();
+    }
+    if currency.len() != 3 || opening.currency != currency || closing.currency != currency {
+        return Err(invalid_statement(
+            "Balances must be in the account currency".into(),
+        ));
+    }
+
+    let statement_date = match closing.date {
+        Some(date) => date,
+        None => return Err(invalid_statement("Closing balance without a date".into())),
+    };
+
+    Ok(BankStatement {
+        format: StatementFormat::Camt053,
+        statement_ref,
+        account,
+        sequence,
+        currency,
+        opening_cents: opening.signed_cents(),
+        closing_cents: closing.signed_cents(),
+        statement_date,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::assert_ok;
+
+    const CAMT053: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08">
+  <BkToCstmrStmt>
+    <GrpHdr><MsgId>STM-20251219</MsgId><CreDtTm>2025-12-20T06:30:00Z</CreDtTm></GrpHdr>
+    <Stmt>
+      <Id>STMT-251219</Id>
+      <ElctrncSeqNb>245</ElctrncSeqNb>
+      <Acct><Id><Othr><Id>400123456</Id></Othr></Id><Ccy>USD</Ccy></Acct>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="USD">100.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><Dt><Dt>2025-12-18</Dt></Dt>
+      </Bal>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="USD">1275.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2025-12-19</Dt></Dt>
+      </Bal>
+      <Ntry>
+        <Amt Ccy="USD">125.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+        <BookgDt><Dt>2025-12-19</Dt></BookgDt>
+        <ValDt><Dt>2025-12-19</Dt></ValDt>
+        <AcctSvcrRef>CHS-88120</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <Refs><InstrId>THL0000000000042</InstrId><EndToEndId>NOTPROVIDED</EndToEndId></Refs>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="USD">1500.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+        <ValDt><Dt>2025-12-19</Dt></ValDt>
+        <AddtlNtryInf>ACME EXPORTS LTD</AddtlNtryInf>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="USD">80.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>PDNG</Cd></Sts>
+        <ValDt><Dt>2025-12-22</Dt></ValDt>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    #[test]
+    fn booked_entries_and_balances_are_read() {
+        let statement = assert_ok!(parse(CAMT053.as_bytes()));
+
+        assert_eq!(statement.statement_ref, "STMT-251219");
+        assert_eq!(statement.account, "400123456");
+        assert_eq!(statement.sequence.as_deref(), Some("245"));
+        assert_eq!(statement.opening_cents, -10_000);
+        assert_eq!(statement.closing_cents, 127_500);
+
+        assert_eq!(statement.entries.len(), 2);
+        assert_eq!(statement.entries[0].direction, Direction::Debit);
+        assert_eq!(
+            statement.entries[0].reference.as_deref(),
+            Some("THL0000000000042")
+        );
+        assert_eq!(
+            statement.entries[0].bank_reference.as_deref(),
+            Some("CHS-88120")
+        );
+        assert_eq!(statement.entries[1].direction, Direction::Credit);
+        assert_eq!(
+            statement.entries[1].narrative.as_deref(),
+            Some("ACME EXPORTS LTD")
+        );
+
+        assert_ok!(statement.check_balances());
+    }
+}
@@ -0,0 +1,14 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::nostro::routes::import_nostro_statement,
+    crate::nostro::routes::list_nostro_statements,
+    crate::nostro::routes::reconcile_nostro,
+    crate::nostro::routes::list_nostro_items,
+    crate::nostro::routes::list_open_nostro_lines,
+    crate::nostro::routes::match_nostro_item,
+    crate::nostro::routes::unmatch_nostro_item,
+    crate::nostro::routes::nostro_aging,
+))]
+pub struct NostroApi;
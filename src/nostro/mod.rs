@@ -0,0 +1,8 @@
+pub mod camt053;
+pub mod docs;
+pub mod models;
+pub mod mt940;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,683 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::base::error::{DomainError, ValidationError};
+use crate::nostro::{camt053, mt940};
+
+// References correspondents put in when they have none, useless for matching
+const NO_REFERENCE: &[&str] = &["NONREF", "NOTPROVIDED"];
+// Shorter references turn up inside too many unrelated transaction references
+const MIN_REFERENCE_LEN: usize = 6;
+const MAX_NOTE_LEN: usize = 500;
+// Upper bounds in days of the aging buckets, anything older falls in the last
+const AGING_BUCKETS: &[(i64, &str)] = &[(7, "0-7"), (30, "8-30"), (60, "31-60"), (90, "61-90")];
+const OLDEST_BUCKET: &str = "over 90";
+
+// How far a statement entry and a journal line may be apart and still be taken for each other
+#[derive(Debug, Clone)]
+pub struct ReconciliationPolicy {
+    pub date_tolerance_days: i64,
+    pub amount_tolerance_cents: i64,
+}
+
+impl ReconciliationPolicy {
+    pub fn new(date_tolerance_days: i64, amount_tolerance_cents: i64) -> Self {
+        Self {
+            date_tolerance_days: date_tolerance_days.max(0),
+            amount_tolerance_cents: amount_tolerance_cents.max(0),
+        }
+    }
+
+    fn dates_agree(&self, a: NaiveDate, b: NaiveDate) -> bool {
+        (a - b).num_days().abs() <= self.date_tolerance_days
+    }
+
+    fn amounts_agree(&self, a: i64, b: i64) -> bool {
+        (a - b).abs() <= self.amount_tolerance_cents
+    }
+
+    // Entries are first matched on a reference our journal line carries, within the amount
+    // and date tolerance. What is left is matched on the exact amount within the date
+    // tolerance, but only where the entry and the line have no other candidate.
+    pub fn auto_match(
+        &self,
+        items: &[NostroItemEntity],
+        lines: &[NostroLineEntity],
+    ) -> Vec<AutoMatch> {
+        let mut matches = Vec::new();
+        let mut taken_items: HashSet<Uuid> = HashSet::new();
+        let mut taken_lines: HashSet<Uuid> = HashSet::new();
+
+        for item in items {
+            let references = item.references();
+            if references.is_empty() {
+                continue;
+            }
+
+            let best = lines
+                .iter()
+                .filter(|line| !taken_lines.contains(&line.id))
+                .filter(|line| {
+                    line.currency == item.currency
+                        && line.direction == item.direction
+                        && self.amounts_agree(line.amount_cents, item.amount_cents)
+                        && self.dates_agree(line.entry_date, item.value_date)
+                        && references.iter().any(|r| line.carries_reference(r))
+                })
+                .min_by_key(|line| {
+                    (
+                        (line.amount_cents - item.amount_cents).abs(),
+                        (line.entry_date - item.value_date).num_days().abs(),
+                    )
+                });
+
+            if let Some(line) = best {
+                taken_items.insert(item.id);
+                taken_lines.insert(line.id);
+                matches.push(AutoMatch {
+                    item_id: item.id,
+                    line_id: line.id,
+                    rule: MatchRule::Reference,
+                });
+            }
+        }
+
+        let candidates = |item: &NostroItemEntity, taken_lines: &HashSet<Uuid>| {
+            lines
+                .iter()
+                .filter(|line| {
+                    !taken_lines.contains(&line.id)
+                        && line.currency == item.currency
+                        && line.direction == item.direction
+                        && line.amount_cents == item.amount_cents
+                        && self.dates_agree(line.entry_date, item.value_date)
+                })
+                .map(|line| line.id)
+                .collect::<Vec<_>>()
+        };
+
+        let open_items: Vec<&NostroItemEntity> = items
+            .iter()
+            .filter(|item| !taken_items.contains(&item.id))
+            .collect();
+        let mut proposals = Vec::new();
+        for item in &open_items {
+            if let [line_id] = candidates(item, &taken_lines)[..] {
+                proposals.push((item.id, line_id));
+            }
+        }
+
+        for (item_id, line_id) in proposals {
+            // Another open entry that could be this line as well makes the pairing a guess
+            let contested = open_items.iter().any(|other| {
+                other.id != item_id && candidates(other, &taken_lines).contains(&line_id)
+            });
+            if contested {
+                continue;
+            }
+
+            taken_lines.insert(line_id);
+            matches.push(AutoMatch {
+                item_id,
+                line_id,
+                rule: MatchRule::Amount,
+            });
+        }
+
+        matches
+    }
+
+    // Staff may pair anything going the same way, but a difference past the tolerance has to
+    // be explained
+    pub fn check_manual(
+        &self,
+        item: &NostroItemEntity,
+        line: &NostroLineEntity,
+        note: Option<&str>,
+    ) -> Result<(), DomainError> {
+        if item.status == NostroItemStatus::Matched {
+            return Err(DomainError::InvalidState(
+                "statement item is already matched".into(),
+            ));
+        }
+
+        if item.currency != line.currency {
+            return Err(DomainError::ConstraintViolation(format!(
+                "a {} statement item can't match a {} journal line",
+                item.currency, line.currency
+            )));
+        }
+
+        if item.direction != line.direction {
+            return Err(DomainError::ConstraintViolation(format!(
+                "a statement {} can't match a nostro {}",
+                item.direction,
+                line.direction.ledger_side()
+            )));
+        }
+
+        if !self.amounts_agree(item.amount_cents, line.amount_cents) && note.is_none() {
+            return Err(DomainError::ConstraintViolation(format!(
+                "amounts differ by {} cents, a note is required",
+                (item.amount_cents - line.amount_cents).abs()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AutoMatch {
+    pub item_id: Uuid,
+    pub line_id: Uuid,
+    pub rule: MatchRule,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "nostro_statement_format", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StatementFormat {
+    Mt940,
+    Camt053,
+}
+
+// As the correspondent books it, a credit is money into our nostro account
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "nostro_direction", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Direction {
+    Credit,
+    Debit,
+}
+
+impl Direction {
+    // Our books mirror the correspondent's, their credit is a debit to the nostro asset
+    pub fn ledger_side(self) -> &'static str {
+        match self {
+            Self::Credit => "debit",
+            Self::Debit => "credit",
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "nostro_item_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum NostroItemStatus {
+    Unmatched,
+    Matched,
+}
+
+impl FromStr for NostroItemStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unmatched" => Ok(Self::Unmatched),
+            "matched" => Ok(Self::Matched),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Use unmatched or matched".into(),
+            }),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "nostro_match_rule", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum MatchRule {
+    Reference,
+    Amount,
+    Manual,
+}
+
+// One booked entry of a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementEntry {
+    pub value_date: NaiveDate,
+    pub booking_date: Option<NaiveDate>,
+    pub direction: Direction,
+    pub amount_cents: i64,
+    // Ours, as we gave it on the payment
+    pub reference: Option<String>,
+    // The correspondent's own
+    pub bank_reference: Option<String>,
+    pub narrative: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BankStatement {
+    pub format: StatementFormat,
+    pub statement_ref: String,
+    pub account: String,
+    pub sequence: Option<String>,
+    pub currency: String,
+    // Signed, negative when the account is overdrawn
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub statement_date: NaiveDate,
+    pub entries: Vec<StatementEntry>,
+}
+
+impl BankStatement {
+    // XML is taken for camt.053, anything else for MT940
+    pub fn parse(text: &str) -> Result<Self, ValidationError> {
+        let statement = if text.trim_start().starts_with('<') {
+            camt053::parse(text.as_bytes())?
+        } else {
+            mt940::parse(text)?
+        };
+
+        statement.check_balances()?;
+        Ok(statement)
+    }
+
+    // A statement whose entries don't take the opening balance to the closing one is missing
+    // entries or was cut off
+    pub fn check_balances(&self) -> Result<(), ValidationError> {
+        let movement: i64 = self
+            .entries
+            .iter()
+            .map(|entry| match entry.direction {
+                Direction::Credit => entry.amount_cents,
+                Direction::Debit => -entry.amount_cents,
+            })
+            .sum();
+
+        if self.opening_cents + movement != self.closing_cents {
+            return Err(ValidationError::Mismatch(format!(
+                "entries move the opening balance {} to {}, the closing balance is {}",
+                self.opening_cents,
+                self.opening_cents + movement,
+                self.closing_cents
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct NostroStatementEntity {
+    pub id: Uuid,
+    pub format: StatementFormat,
+    pub statement_ref: String,
+    pub account: String,
+    pub sequence: Option<String>,
+    pub currency: String,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub statement_date: NaiveDate,
+    pub imported_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NostroStatementEntity {
+    pub fn new(statement: &BankStatement, imported_by: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            format: statement.format,
+            statement_ref: statement.statement_ref.clone(),
+            account: statement.account.clone(),
+            sequence: statement.sequence.clone(),
+            currency: statement.currency.clone(),
+            opening_cents: statement.opening_cents,
+            closing_cents: statement.closing_cents,
+            statement_date: statement.statement_date,
+            imported_by: Some(imported_by),
+            created_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NostroItemEntity {
+    pub id: Uuid,
+    pub statement_id: Uuid,
+    pub value_date: NaiveDate,
+    pub booking_date: Option<NaiveDate>,
+    pub direction: Direction,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub reference: Option<String>,
+    pub bank_reference: Option<String>,
+    pub narrative: Option<String>,
+    pub status: NostroItemStatus,
+    pub journal_line_id: Option<Uuid>,
+    pub match_rule: Option<MatchRule>,
+    pub matched_by: Option<Uuid>,
+    pub matched_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NostroItemEntity {
+    pub fn new(
+        statement: &NostroStatementEntity,
+        entry: StatementEntry,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            statement_id: statement.id,
+            value_date: entry.value_date,
+            booking_date: entry.booking_date,
+            direction: entry.direction,
+            amount_cents: entry.amount_cents,
+            currency: statement.currency.clone(),
+            reference: entry.reference,
+            bank_reference: entry.bank_reference,
+            narrative: entry.narrative,
+            status: NostroItemStatus::Unmatched,
+            journal_line_id: None,
+            match_rule: None,
+            matched_by: None,
+            matched_at: None,
+            note: None,
+            created_at: now,
+        }
+    }
+
+    // Both references, upper-cased, when they are specific enough to search for
+    fn references(&self) -> Vec<String> {
+        [&self.reference, &self.bank_reference]
+            .into_iter()
+            .flatten()
+            .map(|r| r.trim().to_uppercase())
+            .filter(|r| r.len() >= MIN_REFERENCE_LEN && !NO_REFERENCE.contains(&r.as_str()))
+            .collect()
+    }
+
+    pub fn match_to(
+        &mut self,
+        line_id: Uuid,
+        rule: MatchRule,
+        matched_by: Option<Uuid>,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        self.status = NostroItemStatus::Matched;
+        self.journal_line_id = Some(line_id);
+        self.match_rule = Some(rule);
+        self.matched_by = matched_by;
+        self.matched_at = Some(now);
+        self.note = note;
+    }
+
+    pub fn unmatch(&mut self) -> Result<(), DomainError> {
+        if self.status != NostroItemStatus::Matched {
+            return Err(DomainError::InvalidState(
+                "statement item is not matched".into(),
+            ));
+        }
+
+        self.status = NostroItemStatus::Unmatched;
+        self.journal_line_id = None;
+        self.match_rule = None;
+        self.matched_by = None;
+        self.matched_at = None;
+        self.note = None;
+        Ok(())
+    }
+}
+
+pub fn parse_note(note: Option<&str>) -> Result<Option<String>, ValidationError> {
+    match note.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(n) if n.len() > MAX_NOTE_LEN => Err(ValidationError::TooLong {
+            field: "note".into(),
+            max: MAX_NOTE_LEN,
+        }),
+        other => Ok(other.map(str::to_string)),
+    }
+}
+
+// A journal line on the nostro account no statement entry has been matched to yet, its
+// direction given as the correspondent would book it
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NostroLineEntity {
+    pub id: Uuid,
+    pub journal_entry_id: Uuid,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    pub direction: Direction,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub entry_date: NaiveDate,
+}
+
+impl NostroLineEntity {
+    fn carries_reference(&self, reference: &str) -> bool {
+        self.transaction_ref
+            .as_deref()
+            .is_some_and(|r| r.to_uppercase().contains(reference))
+    }
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, utoipa::ToSchema)]
+pub struct AgingBucket {
+    #[schema(example = "8-30")]
+    pub days: &'static str,
+    pub count: i64,
+    pub amount_cents: i64,
+}
+
+// Counts and totals of open items by how many days before `as_of` they are dated, every
+// bucket listed even when empty
+pub fn age(
+    as_of: NaiveDate,
+    items: impl IntoIterator<Item = (NaiveDate, i64)>,
+) -> Vec<AgingBucket> {
+    let mut buckets: Vec<AgingBucket> = AGING_BUCKETS
+        .iter()
+        .map(|(_, days)| *days)
+        .chain([OLDEST_BUCKET])
+        .map(|days| AgingBucket {
+            days,
+            count: 0,
+            amount_cents: 0,
+        })
+        .collect();
+
+    for (date, amount_cents) in items {
+        let days = (as_of - date).num_days().max(0);
+        let index = AGING_BUCKETS
+            .iter()
+            .position(|(upper, _)| days <= *upper)
+            .unwrap_or(AGING_BUCKETS.len());
+
+        buckets[index].count += 1;
+        buckets[index].amount_cents += amount_cents;
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::assert_err;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 12, day).unwrap()
+    }
+
+    fn item(
+        direction: Direction,
+        amount_cents: i64,
+        day: u32,
+        reference: Option<&str>,
+    ) -> NostroItemEntity {
+        let statement = NostroStatementEntity::new(
+            &BankStatement {
+                format: StatementFormat::Mt940,
+                statement_ref: "STMT1".into(),
+                account: "400123456".into(),
+                sequence: None,
+                currency: "USD".into(),
+                opening_cents: 0,
+                closing_cents: 0,
+                statement_date: date(day),
+                entries: Vec::new(),
+            },
+            Uuid::now_v7(),
+            Utc::now(),
+        );
+
+        NostroItemEntity::new(
+            &statement,
+            StatementEntry {
+                value_date: date(day),
+                booking_date: None,
+                direction,
+                amount_cents,
+                reference: reference.map(str::to_string),
+                bank_reference: Some("NONREF".into()),
+                narrative: None,
+            },
+            Utc::now(),
+        )
+    }
+
+    fn line(
+        direction: Direction,
+        amount_cents: i64,
+        day: u32,
+        reference: &str,
+    ) -> NostroLineEntity {
+        NostroLineEntity {
+            id: Uuid::now_v7(),
+            journal_entry_id: Uuid::now_v7(),
+            transaction_ref: Some(reference.into()),
+            description: None,
+            direction,
+            amount_cents,
+            currency: "USD".into(),
+            entry_date: date(day),
+        }
+    }
+
+    #[test]
+    fn references_match_first_and_amounts_only_when_unambiguous() {
+        let policy = ReconciliationPolicy::new(3, 0);
+
+        let by_reference = item(Direction::Debit, 12_500, 19, Some("THL0000000000042"));
+        let by_amount = item(Direction::Credit, 150_000, 18, None);
+        let ambiguous = [
+            item(Direction::Debit, 9_900, 17, Some("NONREF")),
+            item(Direction::Debit, 9_900, 18, None),
+        ];
+        let too_late = item(Direction::Credit, 4_000, 10, None);
+
+        let lines = [
+            line(Direction::Debit, 12_500, 18, "SWFTHL0000000000042"),
+            line(Direction::Credit, 150_000, 19, "SWIBARCGB22XXXPAY7781"),
+            line(Direction::Debit, 9_900, 17, "SWFTHL0000000000043"),
+            line(Direction::Credit, 4_000, 16, "SWIBARCGB22XXXPAY7782"),
+            // Same amount and reference but going the other way
+            line(Direction::Credit, 12_500, 19, "SWFTHL0000000000042-R"),
+        ];
+
+        let items = [
+            by_reference.clone(),
+            by_amount.clone(),
+            ambiguous[0].clone(),
+            ambiguous[1].clone(),
+            too_late,
+        ];
+        let matches = policy.auto_match(&items, &lines);
+
+        assert_eq!(
+            matches,
+            vec![
+                AutoMatch {
+                    item_id: by_reference.id,
+                    line_id: lines[0].id,
+                    rule: MatchRule::Reference,
+                },
+                AutoMatch {
+                    item_id: by_amount.id,
+                    line_id: lines[1].id,
+                    rule: MatchRule::Amount,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn amount_tolerance_applies_to_reference_matches_and_notes_to_manual_ones() {
+        let policy = ReconciliationPolicy::new(3, 100);
+        let short = item(Direction::Debit, 12_450, 19, Some("THL0000000000042"));
+        let lines = [line(Direction::Debit, 12_500, 19, "SWFTHL0000000000042")];
+
+        assert_eq!(
+            policy
+                .auto_match(std::slice::from_ref(&short), &lines)
+                .len(),
+            1
+        );
+        assert!(
+            ReconciliationPolicy::new(3, 0)
+                .auto_match(&[short], &lines)
+                .is_empty()
+        );
+
+        let charged = item(Direction::Debit, 12_000, 19, None);
+        assert_eq!(
+            assert_err!(policy.check_manual(&charged, &lines[0], None)).to_string(),
+            "Constraint violation: amounts differ by 500 cents, a note is required"
+        );
+        assert!(
+            policy
+                .check_manual(&charged, &lines[0], Some("Correspondent charges"))
+                .is_ok()
+        );
+
+        let credit = item(Direction::Credit, 12_500, 19, None);
+        let _ = assert_err!(policy.check_manual(&credit, &lines[0], Some("x")));
+    }
+
+    #[test]
+    fn open_items_are_aged_into_buckets() {
+        let buckets = age(
+            date(31),
+            [
+                (date(31), 100),
+                (date(24), 200),
+                (date(1), 300),
+                (NaiveDate::from_ymd_opt(2025, 8, 1).unwrap(), 400),
+            ],
+        );
+
+        let counts: Vec<(&str, i64, i64)> = buckets
+            .iter()
+            .map(|b| (b.days, b.count, b.amount_cents))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("0-7", 2, 300),
+                ("8-30", 1, 300),
+                ("31-60", 0, 0),
+                ("61-90", 0, 0),
+                ("over 90", 1, 400),
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::base::error::ValidationError;
+use crate::nostro::models::{BankStatement, Direction, StatementEntry, StatementFormat};
+use crate::swift::fin::{
+    FinMessage, invalid_field, parse_amount, parse_currency, parse_date, parse_fields,
+};
+
+// What correspondents write in :61: when there is no reference to give
+const NO_REFERENCE: &str = "NONREF";
+
+// 60F, 60M, 62F and 62M, a mark, the date, currency and amount, C250101USD1250,00
+fn parse_balance(tag: &str, value: &str) -> Result<(i64, NaiveDate, String), ValidationError> {
+    let (sign, rest) = match value.split_at_checked(1) {
+        Some(("C", rest)) => (1, rest),
+        Some(("D", rest)) => (-1, rest),
+        _ => return Err(invalid_field(tag, "Balance must start with C or D")),
+    };
+
+    let (date, rest) = match rest.split_at_checked(6) {
+        Some(parts) => parts,
+        None => return Err(invalid_field(tag, "Balance date must be YYMMDD")),
+    };
+    let (currency, amount) = match rest.split_at_checked(3) {
+        Some(parts) => parts,
+        None => return Err(invalid_field(tag, "Balance needs a currency and an amount")),
+    };
+
+    Ok((
+        sign * parse_amount(tag, amount)?,
+        parse_date(tag, date)?,
+        parse_currency(tag, currency)?,
+    ))
+}
+
+// The optional entry date is only MMDD, in the value date's year unless that puts it more than
+// half a year away
+fn entry_date(value_date: NaiveDate, mmdd: &str) -> Result<NaiveDate, ValidationError> {
+    let on = |year: i32| NaiveDate::parse_from_str(&format!("{}{}", year, mmdd), "%Y%m%d").ok();
+
+    let year = value_date.year();
+    [year, year - 1, year + 1]
+        .into_iter()
+        .filter_map(on)
+        .min_by_key(|date| (*date - value_date).num_days().abs())
+        .filter(|date| (*date - value_date).num_days().abs() <= 183)
+        .ok_or_else(|| invalid_field("61", "Entry date does not exist"))
+}
+
+// 61, value date, optional entry date, mark, amount, type, our reference, the bank's reference
+// after //, and supplementary details on a second line:
+// 2512191219D125,00NTRFTHL0000000000042//CHS-88120
+fn parse_entry(value: &str) -> Result<StatementEntry, ValidationError> {
+    let (first, details) = value.split_once('\n').unwrap_or((value, ""));
+
+    let (date, mut rest) = match first.split_at_checked(6) {
+        Some(parts) => parts,
+        None => return Err(invalid_field("61", "Value date must be YYMMDD")),
+    };
+    let value_date = parse_date("61", date)?;
+
+    let mut booking_date = None;
+    if let Some((mmdd, after)) = rest.split_at_checked(4)
+        && mmdd.chars().all(|c| c.is_ascii_digit())
+    {
+        booking_date = Some(entry_date(value_date, mmdd)?);
+        rest = after;
+    }
+
+    // A reversal of a credit takes money out again, of a debit puts it back
+    let (direction, mut rest) = if let Some(after) = rest.strip_prefix("RC") {
+        (Direction::Debit, after)
+    } else if let Some(after) = rest.strip_prefix("RD") {
+        (Direction::Credit, after)
+    } else if let Some(after) = rest.strip_prefix('C') {
+        (Direction::Credit, after)
+    } else if let Some(after) = rest.strip_prefix('D') {
+        (Direction::Debit, after)
+    } else {
+        return Err(invalid_field("61", "Mark must be C, D, RC or RD"));
+    };
+
+    // The third letter of the currency code, when given
+    if rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+        rest = &rest[1..];
+    }
+
+    let amount_len = rest
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(rest.len());
+    let amount_cents = parse_amount("61", &rest[..amount_len])?;
+    if amount_cents <= 0 {
+        return Err(invalid_field("61", "Amount must be more than zero"));
+    }
+
+    let rest = match rest[amount_len..].split_at_checked(4) {
+        Some((kind, after)) if kind.starts_with(['N', 'F', 'S']) => after,
+        _ => {
+            return Err(invalid_field(
+                "61",
+                "Transaction type must follow the amount",
+            ));
+        }
+    };
+
+    let (reference, bank_reference) = rest.split_once("//").unwrap_or((rest, ""));
+    let present = |s: &str| {
+        Some(s.trim().to_string()).filter(|s| !s.is_empty() && s.as_str() != NO_REFERENCE)
+    };
+
+    Ok(StatementEntry {
+        value_date,
+        booking_date,
+        direction,
+        amount_cents,
+        reference: present(reference),
+        bank_reference: present(bank_reference),
+        narrative: present(details),
+    })
+}
+
+// An MT940 as received over FIN, or only its fields as some correspondents mail them
+pub fn parse(text: &str) -> Result<BankStatement, ValidationError> {
+    let message = if text.contains("{4:") {
+        FinMessage::parse(text)?
+    } else {
+        let body = text.trim_end();
+        let mut message = FinMessage::new("940", "", "");
+        message.fields = parse_fields(body.strip_suffix('-').unwrap_or(body))?;
+        message
+    };
+
+    if message.message_type != "940" {
+        return Err(ValidationError::InvalidValue {
+            field: "message_type".into(),
+            reason: format!("Expected an MT940, got MT{}", message.message_type),
+        });
+    }
+
+    let statement_ref = message.required("20")?.trim().to_string();
+    let account = message.required("25")?.trim().to_string();
+    let sequence = message.field("28C").map(|s| s.trim().to_string());
+
+    let (opening_cents, _, currency) = match message.field_option("60", "FM") {
+        Some((tag, value)) => parse_balance(&tag, value)?,
+        None => return Err(ValidationError::MissingField("60F".into())),
+    };
+    let (closing_cents, statement_date, closing_currency) = match message.field_option("62", "FM") {
+        Some((tag, value)) => parse_balance(&tag, value)?,
+        None => return Err(ValidationError::MissingField("62F".into())),
+    };
+    if closing_currency != currency {
+        return Err(invalid_field(
+            "62F",
+            "Currency differs from the opening balance",
+        ));
+    }
+
+    // An 86 right after a 61 carries more about that entry, elsewhere it is about the statement
+    let mut entries: Vec<StatementEntry> = Vec::new();
+    let mut last_tag = "";
+    for (tag, value) in &message.fields {
+        match tag.as_str() {
+            "61" => entries.push(parse_entry(value)?),
+            "86" if last_tag == "61" => {
+                if let Some(entry) = entries.last_mut() {
+                    let information = value.replace('\n', " ");
+                    entry.narrative = Some(match entry.narrative.take() {
+                        Some(details) => format!("{} {}", details, information),
+                        None => information,
+                    });
+                }
+            }
+            _ => {}
+        }
+        last_tag = tag;
+    }
+
+    Ok(BankStatement {
+        format: StatementFormat::Mt940,
+        statement_ref,
+        account,
+        sequence,
+        currency,
+        opening_cents,
+        closing_cents,
+        statement_date,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    const MT940: &str = "{1:F01THALUS33AXXX0000000000}{2:O9400630251220CHASUS33AXXX00000000002512200630N}{4:\r\n:20:STMT251219\r\n:25:CHASUS33/400123456\r\n:28C:00245/001\r\n:60F:C251218USD10000,00\r\n:61:2512191219D125,00NTRFTHL0000000000042//CHS-88120\r\n:86:SWIFT TRANSFER TO SIOBHAN O'CONNOR\r\n:61:2512191220C1500,NTRFPAY7781//CHS-88121\r\nACME EXPORTS LTD\r\n:61:251231RD10,00NCHGNONREF\r\n:62F:C251219USD11385,00\r\n-}";
+
+    #[test]
+    fn entries_and_balances_are_read() {
+        let statement = assert_ok!(parse(MT940));
+
+        assert_eq!(statement.statement_ref, "STMT251219");
+        assert_eq!(statement.account, "CHASUS33/400123456");
+        assert_eq!(statement.sequence.as_deref(), Some("00245/001"));
+        assert_eq!(statement.currency, "USD");
+        assert_eq!(statement.opening_cents, 1_000_000);
+        assert_eq!(statement.closing_cents, 1_138_500);
+        assert_eq!(
+            statement.statement_date,
+            NaiveDate::from_ymd_opt(2025, 12, 19).unwrap()
+        );
+
+        let entries = &statement.entries;
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].direction, Direction::Debit);
+        assert_eq!(entries[0].amount_cents, 12_500);
+        assert_eq!(entries[0].reference.as_deref(), Some("THL0000000000042"));
+        assert_eq!(entries[0].bank_reference.as_deref(), Some("CHS-88120"));
+        assert_eq!(
+            entries[0].narrative.as_deref(),
+            Some("SWIFT TRANSFER TO SIOBHAN O'CONNOR")
+        );
+        assert_eq!(
+            entries[1].booking_date,
+            NaiveDate::from_ymd_opt(2025, 12, 20)
+        );
+        assert_eq!(entries[1].amount_cents, 150_000);
+        assert_eq!(entries[1].narrative.as_deref(), Some("ACME EXPORTS LTD"));
+        // A reversed debit puts the money back
+        assert_eq!(entries[2].direction, Direction::Credit);
+        assert_eq!(entries[2].reference, None);
+
+        assert_ok!(statement.check_balances());
+    }
+
+    #[test]
+    fn bare_fields_are_taken_and_broken_entries_named() {
+        let body = &MT940[MT940.find(":20:").unwrap()..MT940.len() - 1];
+        assert_eq!(assert_ok!(parse(body)), assert_ok!(parse(MT940)));
+
+        let broken = MT940.replace(":61:2512191219D125,00", ":61:2512191219X125,00");
+        assert_eq!(
+            assert_err!(parse(&broken)).to_string(),
+            "Invalid value for 61: Mark must be C, D, RC or RD"
+        );
+
+        let short = assert_ok!(parse(
+            &MT940.replace(":62F:C251219USD11385,00", ":62F:C251219USD11000,00")
+        ));
+        let _ = assert_err!(short.check_balances());
+    }
+
+    #[test]
+    fn non_ascii_tags_and_headers_are_refused() {
+        let _ = assert_err!(parse(&MT940.replace(":86:", ":8é:")));
+        let _ = assert_err!(parse(&MT940.replace("CHASUS33AXXX", "CHASUS3ÉAXX")));
+
+        let body = &MT940[MT940.find(":20:").unwrap()..MT940.len() - 1];
+        let _ = assert_err!(parse(&body.replace(":20:", ":2é:")));
+    }
+}
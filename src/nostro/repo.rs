@@ -0,0 +1,208 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::nostro::models::{
+    NostroItemEntity, NostroItemStatus, NostroLineEntity, NostroStatementEntity,
+};
+use crate::swift::models::NOSTRO_COA;
+
+const STATEMENT_COLUMNS: &str = "id, format, statement_ref, account, sequence, currency,
+    opening_cents, closing_cents, statement_date, imported_by, created_at";
+
+const ITEM_COLUMNS: &str = "id, statement_id, value_date, booking_date, direction, amount_cents,
+    currency, reference, bank_reference, narrative, status, journal_line_id, match_rule,
+    matched_by, matched_at, note, created_at";
+
+// Nostro journal lines in the currency of the customer account they were posted for, turned
+// round to the correspondent's side, that no statement entry is matched to
+const OPEN_LINES: &str = "SELECT l.id, l.journal_entry_id, e.transaction_ref, e.description,
+        (CASE l.line_type WHEN 'debit' THEN 'credit' ELSE 'debit' END)::nostro_direction
+            AS direction,
+        l.amount_cents, a.currency, e.created_date::date AS entry_date
+    FROM journal_line l
+    JOIN chart_of_account c ON c.id = l.coa_id
+    JOIN journal_entry e ON e.id = l.journal_entry_id
+    JOIN user_account a ON a.id = e.user_account_id
+    WHERE c.code = $1 AND l.amount_cents > 0
+        AND NOT EXISTS (SELECT 1 FROM nostro_item i WHERE i.journal_line_id = l.id)";
+
+pub struct NostroRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> NostroRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Saving nostro statement", skip(self, statement))]
+    pub async fn insert_statement(
+        &mut self,
+        statement: &NostroStatementEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO nostro_statement({})
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            STATEMENT_COLUMNS
+        ))
+        .bind(statement.id)
+        .bind(statement.format)
+        .bind(&statement.statement_ref)
+        .bind(&statement.account)
+        .bind(&statement.sequence)
+        .bind(&statement.currency)
+        .bind(statement.opening_cents)
+        .bind(statement.closing_cents)
+        .bind(statement.statement_date)
+        .bind(statement.imported_by)
+        .bind(statement.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving nostro statements", skip(self))]
+    pub async fn fetch_statements(&self) -> Result<Vec<NostroStatementEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, NostroStatementEntity>(&format!(
+            "SELECT {} FROM nostro_statement ORDER BY statement_date DESC, created_at DESC",
+            STATEMENT_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving nostro statement item", skip(self, item))]
+    pub async fn insert_item(&mut self, item: &NostroItemEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO nostro_item({})
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                    $17)",
+            ITEM_COLUMNS
+        ))
+        .bind(item.id)
+        .bind(item.statement_id)
+        .bind(item.value_date)
+        .bind(item.booking_date)
+        .bind(item.direction)
+        .bind(item.amount_cents)
+        .bind(&item.currency)
+        .bind(&item.reference)
+        .bind(&item.bank_reference)
+        .bind(&item.narrative)
+        .bind(item.status)
+        .bind(item.journal_line_id)
+        .bind(item.match_rule)
+        .bind(item.matched_by)
+        .bind(item.matched_at)
+        .bind(&item.note)
+        .bind(item.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving nostro statement item match", skip(self, item))]
+    pub async fn update_item_match(&mut self, item: &NostroItemEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE nostro_item
+                SET status=$2, journal_line_id=$3, match_rule=$4, matched_by=$5, matched_at=$6,
+                    note=$7
+                WHERE id=$1",
+        )
+        .bind(item.id)
+        .bind(item.status)
+        .bind(item.journal_line_id)
+        .bind(item.match_rule)
+        .bind(item.matched_by)
+        .bind(item.matched_at)
+        .bind(&item.note)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving nostro statement items", skip(self))]
+    pub async fn fetch_items(
+        &self,
+        status: Option<NostroItemStatus>,
+    ) -> Result<Vec<NostroItemEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, NostroItemEntity>(&format!(
+            "SELECT {} FROM nostro_item
+                WHERE $1::nostro_item_status IS NULL OR status = $1
+                ORDER BY value_date, created_at",
+            ITEM_COLUMNS
+        ))
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Locked so a reconciliation run and a manual match can't pair the same entry twice
+    #[tracing::instrument("Locking unmatched nostro statement items", skip(self))]
+    pub async fn fetch_unmatched_items_for_update(
+        &mut self,
+    ) -> Result<Vec<NostroItemEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, NostroItemEntity>(&format!(
+            "SELECT {} FROM nostro_item WHERE status = 'unmatched'
+                ORDER BY value_date, created_at
+                FOR UPDATE",
+            ITEM_COLUMNS
+        ))
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking nostro statement item", skip(self))]
+    pub async fn fetch_item_for_update(
+        &mut self,
+        item_id: Uuid,
+    ) -> Result<Option<NostroItemEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, NostroItemEntity>(&format!(
+            "SELECT {} FROM nostro_item WHERE id=$1 FOR UPDATE",
+            ITEM_COLUMNS
+        ))
+        .bind(item_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving open nostro journal lines", skip(self))]
+    pub async fn fetch_open_lines(&mut self) -> Result<Vec<NostroLineEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, NostroLineEntity>(&format!(
+            "{} ORDER BY e.created_date, l.id",
+            OPEN_LINES
+        ))
+        .bind(NOSTRO_COA)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving open nostro journal line", skip(self))]
+    pub async fn fetch_open_line(
+        &mut self,
+        line_id: Uuid,
+    ) -> Result<Option<NostroLineEntity>, sqlx::Error> {
+        let result =
+            sqlx::query_as::<_, NostroLineEntity>(&format!("{} AND l.id = $2", OPEN_LINES))
+                .bind(NOSTRO_COA)
+                .bind(line_id)
+                .fetch_optional(&mut **self.tx)
+                .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,123 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::nostro::{
+    schemas::{
+        AgingQuery, AgingReportResponse, ManualMatchRequest, NostroItemQuery, NostroItemResponse,
+        NostroLineResponse, NostroStatementResponse, ReconcileResponse, StatementImportRequest,
+        StatementImportResponse,
+    },
+    service::NostroService,
+};
+
+#[tracing::instrument("Import nostro statement", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/nostro/statements", request_body=StatementImportRequest, responses((status=200, body=StatementImportResponse, description="Statement stored and its entries matched where possible"), (status=400, description="Malformed statement, the field is named, or entries don't add up to the closing balance"), (status=403, description="Only superusers can import statements"), (status=409, description="Statement already imported for the account")))]
+pub async fn import_nostro_statement(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<StatementImportRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.import(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List nostro statements", skip(app_state))]
+#[utoipa::path(get, path="/nostro/statements", responses((status=200, body=Vec<NostroStatementResponse>, description="Imported statements, latest first")))]
+pub async fn list_nostro_statements(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.list_statements().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Reconcile nostro accounts", skip(app_state, claims))]
+#[utoipa::path(post, path="/nostro/reconcile", responses((status=200, body=ReconcileResponse, description="Unmatched entries matched against journal lines booked since"), (status=403, description="Only superusers can reconcile")))]
+pub async fn reconcile_nostro(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.reconcile(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List nostro statement items", skip(app_state))]
+#[utoipa::path(get, path="/nostro/items", params(NostroItemQuery), responses((status=200, body=Vec<NostroItemResponse>, description="Statement entries, oldest first"), (status=400, description="Unknown status")))]
+pub async fn list_nostro_items(
+    app_state: web::Data<AppState>,
+    query: web::Query<NostroItemQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.list_items(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List open nostro journal lines", skip(app_state))]
+#[utoipa::path(get, path="/nostro/ledger-lines", responses((status=200, body=Vec<NostroLineResponse>, description="Nostro journal lines no statement entry is matched to, oldest first")))]
+pub async fn list_open_nostro_lines(
+    app_state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.list_open_lines().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Match nostro statement item", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/nostro/items/{item_id}/match", params(("item_id"=Uuid, Path, description="Statement item id")), request_body=ManualMatchRequest, responses((status=200, body=NostroItemResponse, description="Statement entry matched to the journal line"), (status=400, description="Note too long"), (status=403, description="Only superusers can match"), (status=404, description="Statement item or open journal line not found"), (status=409, description="Statement item or journal line already matched"), (status=422, description="Currency or direction differ, or amounts differ without a note")))]
+pub async fn match_nostro_item(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    item_id: web::Path<Uuid>,
+    payload: web::Json<ManualMatchRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service
+        .match_item(&claims, item_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Unmatch nostro statement item", skip(app_state, claims))]
+#[utoipa::path(post, path="/nostro/items/{item_id}/unmatch", params(("item_id"=Uuid, Path, description="Statement item id")), responses((status=200, body=NostroItemResponse, description="Statement entry open again"), (status=403, description="Only superusers can unmatch"), (status=404, description="Statement item not found"), (status=409, description="Statement item is not matched")))]
+pub async fn unmatch_nostro_item(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    item_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service
+        .unmatch_item(&claims, item_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Age nostro items", skip(app_state))]
+#[utoipa::path(get, path="/nostro/aging", params(AgingQuery), responses((status=200, body=Vec<AgingReportResponse>, description="Unmatched statement entries and journal lines by age, per currency")))]
+pub async fn nostro_aging(
+    app_state: web::Data<AppState>,
+    query: web::Query<AgingQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let nostro_service = NostroService::from(&app_state);
+
+    let response = nostro_service.aging(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::nostro::models::{
+    AgingBucket, Direction, MatchRule, NostroItemEntity, NostroItemStatus, NostroLineEntity,
+    NostroStatementEntity, StatementFormat,
+};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct StatementImportRequest {
+    // An MT940, with or without its FIN envelope, or a camt.053 document
+    #[schema(
+        example = ":20:STMT251219\r\n:25:400123456\r\n:28C:00245/001\r\n:60F:C251218USD10000,00\r\n:61:2512191219D125,00NTRFTHL0000000000042//CHS-88120\r\n:62F:C251219USD9875,00\r\n-"
+    )]
+    pub statement: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct StatementImportResponse {
+    #[serde(flatten)]
+    pub statement: NostroStatementResponse,
+    pub entry_count: usize,
+    // Entries of this statement and earlier ones the import matched
+    pub matched_count: usize,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct NostroStatementResponse {
+    pub id: Uuid,
+    pub format: StatementFormat,
+    pub statement_ref: String,
+    // Our account number at the correspondent
+    pub account: String,
+    pub sequence: Option<String>,
+    pub currency: String,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub statement_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<NostroStatementEntity> for NostroStatementResponse {
+    fn from(value: NostroStatementEntity) -> Self {
+        Self {
+            id: value.id,
+            format: value.format,
+            statement_ref: value.statement_ref,
+            account: value.account,
+            sequence: value.sequence,
+            currency: value.currency,
+            opening_cents: value.opening_cents,
+            closing_cents: value.closing_cents,
+            statement_date: value.statement_date,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct NostroItemQuery {
+    // unmatched or matched
+    pub status: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct NostroItemResponse {
+    pub id: Uuid,
+    pub statement_id: Uuid,
+    pub value_date: NaiveDate,
+    pub booking_date: Option<NaiveDate>,
+    // As the correspondent booked it, a credit is money into our account with them
+    pub direction: Direction,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub reference: Option<String>,
+    pub bank_reference: Option<String>,
+    pub narrative: Option<String>,
+    pub status: NostroItemStatus,
+    pub journal_line_id: Option<Uuid>,
+    pub match_rule: Option<MatchRule>,
+    pub matched_by: Option<Uuid>,
+    pub matched_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+impl From<NostroItemEntity> for NostroItemResponse {
+    fn from(value: NostroItemEntity) -> Self {
+        Self {
+            id: value.id,
+            statement_id: value.statement_id,
+            value_date: value.value_date,
+            booking_date: value.booking_date,
+            direction: value.direction,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            reference: value.reference,
+            bank_reference: value.bank_reference,
+            narrative: value.narrative,
+            status: value.status,
+            journal_line_id: value.journal_line_id,
+            match_rule: value.match_rule,
+            matched_by: value.matched_by,
+            matched_at: value.matched_at,
+            note: value.note,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ManualMatchRequest {
+    // A journal line on the nostro account no other statement entry is matched to
+    pub journal_line_id: Uuid,
+    // Required when the amounts differ by more than the tolerance
+    #[schema(example = "Correspondent deducted 5.00 charges")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ReconcileResponse {
+    pub matched_count: usize,
+    pub unmatched_count: usize,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct AgingQuery {
+    // Today when not given
+    pub as_of: Option<NaiveDate>,
+}
+
+// Unmatched nostro journal lines, what we booked and the correspondent hasn't
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct NostroLineResponse {
+    pub journal_line_id: Uuid,
+    pub journal_entry_id: Uuid,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    pub direction: Direction,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub entry_date: NaiveDate,
+}
+
+impl From<NostroLineEntity> for NostroLineResponse {
+    fn from(value: NostroLineEntity) -> Self {
+        Self {
+            journal_line_id: value.id,
+            journal_entry_id: value.journal_entry_id,
+            transaction_ref: value.transaction_ref,
+            description: value.description,
+            direction: value.direction,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            entry_date: value.entry_date,
+        }
+    }
+}
+
+// One currency's open items on both sides, aged by how long they have been open
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AgingReportResponse {
+    pub currency: String,
+    pub as_of: NaiveDate,
+    // Entries on the correspondent's statements we have no journal line for
+    pub statement_items: Vec<AgingBucket>,
+    // Journal lines on the nostro account no statement has shown yet
+    pub ledger_lines: Vec<AgingBucket>,
+}
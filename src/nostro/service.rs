@@ -0,0 +1,338 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::nostro::{
+    models::{
+        BankStatement, MatchRule, NostroItemEntity, NostroItemStatus, NostroStatementEntity, age,
+        parse_note,
+    },
+    schemas::{
+        AgingQuery, AgingReportResponse, ManualMatchRequest, NostroItemQuery, NostroItemResponse,
+        NostroLineResponse, NostroStatementResponse, ReconcileResponse, StatementImportRequest,
+        StatementImportResponse,
+    },
+};
+use crate::user::models::AccessRole;
+
+pub struct NostroService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> NostroService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // Pairs every unmatched statement entry the policy can with an open nostro journal line,
+    // returns how many were matched and how many are left
+    async fn auto_match(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        now: DateTime<Utc>,
+    ) -> Result<(usize, usize), AppError> {
+        let mut items = uow
+            .nostro()
+            .fetch_unmatched_items_for_update()
+            .await
+            .to_app_err("Failed to fetch nostro statement items")?;
+        let lines = uow
+            .nostro()
+            .fetch_open_lines()
+            .await
+            .to_app_err("Failed to fetch nostro journal lines")?;
+
+        let matches = self.app_state.reconciliation.auto_match(&items, &lines);
+        for found in &matches {
+            if let Some(item) = items.iter_mut().find(|item| item.id == found.item_id) {
+                item.match_to(found.line_id, found.rule, None, None, now);
+                uow.nostro()
+                    .update_item_match(item)
+                    .await
+                    .to_app_err("Failed to save nostro match")?;
+            }
+        }
+
+        Ok((matches.len(), items.len() - matches.len()))
+    }
+
+    // The statement has to add up from its opening to its closing balance, its entries are
+    // then matched along with any left open from earlier statements
+    #[tracing::instrument("Import nostro statement", skip(self, claims, request))]
+    pub async fn import(
+        &self,
+        claims: &SessionClaims,
+        request: StatementImportRequest,
+    ) -> Result<StatementImportResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let parsed = BankStatement::parse(&request.statement)?;
+        let statement = NostroStatementEntity::new(&parsed, *claims.get_user_id(), now);
+        let entry_count = parsed.entries.len();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        uow.nostro()
+            .insert_statement(&statement)
+            .await
+            .to_app_err("Failed to save nostro statement")?;
+
+        for entry in parsed.entries {
+            uow.nostro()
+                .insert_item(&NostroItemEntity::new(&statement, entry, now))
+                .await
+                .to_app_err("Failed to save nostro statement item")?;
+        }
+
+        let (matched_count, _) = self.auto_match(&mut uow, now).await?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit nostro statement")?;
+
+        Ok(StatementImportResponse {
+            statement: statement.into(),
+            entry_count,
+            matched_count,
+        })
+    }
+
+    #[tracing::instrument("List nostro statements", skip(self))]
+    pub async fn list_statements(&self) -> Result<Vec<NostroStatementResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let statements = uow
+            .nostro()
+            .fetch_statements()
+            .await
+            .to_app_err("Failed to fetch nostro statements")?;
+
+        Ok(statements
+            .into_iter()
+            .map(NostroStatementResponse::from)
+            .collect())
+    }
+
+    // Journal lines booked since the last run may match entries that found nothing before
+    #[tracing::instrument("Reconcile nostro accounts", skip(self, claims))]
+    pub async fn reconcile(&self, claims: &SessionClaims) -> Result<ReconcileResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let (matched_count, unmatched_count) = self.auto_match(&mut uow, Utc::now()).await?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit nostro reconciliation")?;
+
+        Ok(ReconcileResponse {
+            matched_count,
+            unmatched_count,
+        })
+    }
+
+    #[tracing::instrument("List nostro statement items", skip(self))]
+    pub async fn list_items(
+        &self,
+        query: NostroItemQuery,
+    ) -> Result<Vec<NostroItemResponse>, AppError> {
+        let status = match query.status {
+            Some(s) => Some(s.parse::<NostroItemStatus>()?),
+            None => None,
+        };
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let items = uow
+            .nostro()
+            .fetch_items(status)
+            .await
+            .to_app_err("Failed to fetch nostro statement items")?;
+
+        Ok(items.into_iter().map(NostroItemResponse::from).collect())
+    }
+
+    #[tracing::instrument("List open nostro journal lines", skip(self))]
+    pub async fn list_open_lines(&self) -> Result<Vec<NostroLineResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let lines = uow
+            .nostro()
+            .fetch_open_lines()
+            .await
+            .to_app_err("Failed to fetch nostro journal lines")?;
+
+        Ok(lines.into_iter().map(NostroLineResponse::from).collect())
+    }
+
+    #[tracing::instrument("Match nostro statement item", skip(self, claims, request))]
+    pub async fn match_item(
+        &self,
+        claims: &SessionClaims,
+        item_id: Uuid,
+        request: ManualMatchRequest,
+    ) -> Result<NostroItemResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let note = parse_note(request.note.as_deref())?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut item = match uow
+            .nostro()
+            .fetch_item_for_update(item_id)
+            .await
+            .to_app_err("Failed to fetch nostro statement item")?
+        {
+            Some(i) => i,
+            None => Err(DomainError::NotFound("statement item".into()))?,
+        };
+
+        // Lines already matched to another entry are not open
+        let line = match uow
+            .nostro()
+            .fetch_open_line(request.journal_line_id)
+            .await
+            .to_app_err("Failed to fetch nostro journal line")?
+        {
+            Some(l) => l,
+            None => Err(DomainError::NotFound("open nostro journal line".into()))?,
+        };
+
+        self.app_state
+            .reconciliation
+            .check_manual(&item, &line, note.as_deref())?;
+
+        item.match_to(
+            line.id,
+            MatchRule::Manual,
+            Some(*claims.get_user_id()),
+            note,
+            Utc::now(),
+        );
+        uow.nostro()
+            .update_item_match(&item)
+            .await
+            .to_app_err("Journal line is already matched")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit nostro match")?;
+
+        Ok(item.into())
+    }
+
+    #[tracing::instrument("Unmatch nostro statement item", skip(self, claims))]
+    pub async fn unmatch_item(
+        &self,
+        claims: &SessionClaims,
+        item_id: Uuid,
+    ) -> Result<NostroItemResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let mut item = match uow
+            .nostro()
+            .fetch_item_for_update(item_id)
+            .await
+            .to_app_err("Failed to fetch nostro statement item")?
+        {
+            Some(i) => i,
+            None => Err(DomainError::NotFound("statement item".into()))?,
+        };
+
+        item.unmatch()?;
+        uow.nostro()
+            .update_item_match(&item)
+            .await
+            .to_app_err("Failed to save nostro match")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit nostro unmatch")?;
+
+        Ok(item.into())
+    }
+
+    // Open items as they stand now, aged by their date against `as_of`, one report per currency
+    #[tracing::instrument("Age nostro items", skip(self))]
+    pub async fn aging(&self, query: AgingQuery) -> Result<Vec<AgingReportResponse>, AppError> {
+        let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let items: Vec<NostroItemEntity> = uow
+            .nostro()
+            .fetch_items(Some(NostroItemStatus::Unmatched))
+            .await
+            .to_app_err("Failed to fetch nostro statement items")?
+            .into_iter()
+            .filter(|item| item.value_date <= as_of)
+            .collect();
+        let lines: Vec<_> = uow
+            .nostro()
+            .fetch_open_lines()
+            .await
+            .to_app_err("Failed to fetch nostro journal lines")?
+            .into_iter()
+            .filter(|line| line.entry_date <= as_of)
+            .collect();
+
+        let currencies: BTreeSet<&str> = items
+            .iter()
+            .map(|item| item.currency.as_str())
+            .chain(lines.iter().map(|line| line.currency.as_str()))
+            .collect();
+
+        Ok(currencies
+            .into_iter()
+            .map(|currency| AgingReportResponse {
+                currency: currency.to_string(),
+                as_of,
+                statement_items: age(
+                    as_of,
+                    items
+                        .iter()
+                        .filter(|item| item.currency == currency)
+                        .map(|item| (item.value_date, item.amount_cents)),
+                ),
+                ledger_lines: age(
+                    as_of,
+                    lines
+                        .iter()
+                        .filter(|line| line.currency == currency)
+                        .map(|line| (line.entry_date, line.amount_cents)),
+                ),
+            })
+            .collect())
+    }
+}
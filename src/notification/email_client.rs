@@ -1,6 +1,10 @@
 use crate::base::Email;
+use crate::notification::schemas::{
+    EmailChangeTemplate, EmailChangeTemplateTxt, PaymentFailedTemplate, PaymentFailedTemplateTxt,
+    StatementReadyTemplate, StatementReadyTemplateTxt, VerificationCodeTemplate,
+    VerificationCodeTemplateTxt, WelcomeEmailTemplate, WelcomeEmailTemplateTxt,
+};
 use crate::notification::schemas::{Recipient, SendEmailRequest};
-use crate::notification::schemas::{WelcomeEmailTemplate, WelcomeEmailTemplateTxt};
 use anyhow::Context;
 use askama::Template;
 use reqwest::{Client, Url};
@@ -94,6 +98,136 @@ impl EmailClient {
 
         Ok(())
     }
+
+    pub async fn send_email_change_email(
+        &self,
+        app_address: &str,
+        recipient: &str,
+        first_name: &str,
+        change_token: &str,
+        company_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        let confirmation_link = format!("{}/email/confirm/{}", app_address, change_token);
+        let change_email = EmailChangeTemplate::new(first_name, &confirmation_link, company_name)
+            .render()
+            .context("Failed to render email change template (html)")?;
+
+        let change_email_txt =
+            EmailChangeTemplateTxt::new(first_name, &confirmation_link, company_name)
+                .render()
+                .context("Failed to render email change template (txt)")?;
+
+        self.send_email(
+            recipient,
+            "Confirm your new email address",
+            &change_email,
+            &change_email_txt,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn send_verification_code_email(
+        &self,
+        recipient: &str,
+        first_name: &str,
+        action: &str,
+        code: &str,
+        company_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        let code_email = VerificationCodeTemplate::new(first_name, action, code, company_name)
+            .render()
+            .context("Failed to render verification code template (html)")?;
+
+        let code_email_txt =
+            VerificationCodeTemplateTxt::new(first_name, action, code, company_name)
+                .render()
+                .context("Failed to render verification code template (txt)")?;
+
+        self.send_email(
+            recipient,
+            "Your verification code",
+            &code_email,
+            &code_email_txt,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_payment_failed_email(
+        &self,
+        recipient: &str,
+        first_name: &str,
+        reference: &str,
+        amount: &str,
+        due_date: &str,
+        reason: &str,
+        company_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        let failed_email = PaymentFailedTemplate::new(
+            first_name,
+            reference,
+            amount,
+            due_date,
+            reason,
+            company_name,
+        )
+        .render()
+        .context("Failed to render payment failed template (html)")?;
+
+        let failed_email_txt = PaymentFailedTemplateTxt::new(
+            first_name,
+            reference,
+            amount,
+            due_date,
+            reason,
+            company_name,
+        )
+        .render()
+        .context("Failed to render payment failed template (txt)")?;
+
+        self.send_email(
+            recipient,
+            "Your scheduled payment was not made",
+            &failed_email,
+            &failed_email_txt,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn send_statement_ready_email(
+        &self,
+        recipient: &str,
+        first_name: &str,
+        account_suffix: &str,
+        period: &str,
+        company_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        let statement_email =
+            StatementReadyTemplate::new(first_name, account_suffix, period, company_name)
+                .render()
+                .context("Failed to render statement ready template (html)")?;
+
+        let statement_email_txt =
+            StatementReadyTemplateTxt::new(first_name, account_suffix, period, company_name)
+                .render()
+                .context("Failed to render statement ready template (txt)")?;
+
+        self.send_email(
+            recipient,
+            "Your statement is ready",
+            &statement_email,
+            &statement_email_txt,
+        )
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
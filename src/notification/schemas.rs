@@ -82,3 +82,191 @@ impl<'a> WelcomeEmailTemplateTxt<'a> {
         }
     }
 }
+
+#[derive(Template)]
+#[template(path = "email_change.html")]
+pub struct EmailChangeTemplate<'a> {
+    first_name: &'a str,
+    confirmation_link: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> EmailChangeTemplate<'a> {
+    pub fn new(first_name: &'a str, confirmation_link: &'a str, company_name: &'a str) -> Self {
+        Self {
+            first_name,
+            confirmation_link,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "email_change.txt")]
+pub struct EmailChangeTemplateTxt<'a> {
+    first_name: &'a str,
+    confirmation_link: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> EmailChangeTemplateTxt<'a> {
+    pub fn new(first_name: &'a str, confirmation_link: &'a str, company_name: &'a str) -> Self {
+        Self {
+            first_name,
+            confirmation_link,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "payment_failed.html")]
+pub struct PaymentFailedTemplate<'a> {
+    first_name: &'a str,
+    reference: &'a str,
+    amount: &'a str,
+    due_date: &'a str,
+    reason: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> PaymentFailedTemplate<'a> {
+    pub fn new(
+        first_name: &'a str,
+        reference: &'a str,
+        amount: &'a str,
+        due_date: &'a str,
+        reason: &'a str,
+        company_name: &'a str,
+    ) -> Self {
+        Self {
+            first_name,
+            reference,
+            amount,
+            due_date,
+            reason,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "payment_failed.txt")]
+pub struct PaymentFailedTemplateTxt<'a> {
+    first_name: &'a str,
+    reference: &'a str,
+    amount: &'a str,
+    due_date: &'a str,
+    reason: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> PaymentFailedTemplateTxt<'a> {
+    pub fn new(
+        first_name: &'a str,
+        reference: &'a str,
+        amount: &'a str,
+        due_date: &'a str,
+        reason: &'a str,
+        company_name: &'a str,
+    ) -> Self {
+        Self {
+            first_name,
+            reference,
+            amount,
+            due_date,
+            reason,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "verification_code.html")]
+pub struct VerificationCodeTemplate<'a> {
+    first_name: &'a str,
+    action: &'a str,
+    code: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> VerificationCodeTemplate<'a> {
+    pub fn new(first_name: &'a str, action: &'a str, code: &'a str, company_name: &'a str) -> Self {
+        Self {
+            first_name,
+            action,
+            code,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "verification_code.txt")]
+pub struct VerificationCodeTemplateTxt<'a> {
+    first_name: &'a str,
+    action: &'a str,
+    code: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> VerificationCodeTemplateTxt<'a> {
+    pub fn new(first_name: &'a str, action: &'a str, code: &'a str, company_name: &'a str) -> Self {
+        Self {
+            first_name,
+            action,
+            code,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "statement_ready.html")]
+pub struct StatementReadyTemplate<'a> {
+    first_name: &'a str,
+    account_suffix: &'a str,
+    period: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> StatementReadyTemplate<'a> {
+    pub fn new(
+        first_name: &'a str,
+        account_suffix: &'a str,
+        period: &'a str,
+        company_name: &'a str,
+    ) -> Self {
+        Self {
+            first_name,
+            account_suffix,
+            period,
+            company_name,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "statement_ready.txt")]
+pub struct StatementReadyTemplateTxt<'a> {
+    first_name: &'a str,
+    account_suffix: &'a str,
+    period: &'a str,
+    company_name: &'a str,
+}
+
+impl<'a> StatementReadyTemplateTxt<'a> {
+    pub fn new(
+        first_name: &'a str,
+        account_suffix: &'a str,
+        period: &'a str,
+        company_name: &'a str,
+    ) -> Self {
+        Self {
+            first_name,
+            account_suffix,
+            period,
+            company_name,
+        }
+    }
+}
@@ -1,8 +1,24 @@
 use crate::account::docs::AccountApi;
+use crate::ach::docs::{AchFileApi, AchTransferApi};
+use crate::api_key::docs::ApiKeyApi;
+use crate::beneficiary::docs::BeneficiaryApi;
+use crate::branch::docs::BranchApi;
 use crate::customer::docs::CustomerApi;
+use crate::fiscal::docs::FiscalApi;
+use crate::hold::docs::{CardAuthorizationApi, HoldApi};
+use crate::identity_verify::docs::KycApi;
+use crate::inbound_payment::docs::InboundPaymentApi;
 use crate::ledger::docs::LedgerApi;
+use crate::nostro::docs::NostroApi;
+use crate::overdraft::docs::OverdraftApi;
+use crate::product::docs::ProductApi;
+use crate::reporting::docs::{FinancialsApi, StatementApi, StatementRunApi};
+use crate::scheduled_payment::docs::{PaymentSchedulerApi, ScheduledPaymentApi};
+use crate::screening::docs::ScreeningApi;
+use crate::sepa::docs::{SepaBatchApi, SepaTransferApi};
 use crate::staff::docs::StaffApi;
-use crate::transaction::docs::TransactionApi;
+use crate::swift::docs::{SwiftApi, SwiftTransferApi};
+use crate::transaction::docs::{IntegrationApi, TransactionApi};
 use utoipa::OpenApi;
 // API Configuration and Documentation
 #[derive(OpenApi)]
@@ -17,7 +33,39 @@ use utoipa::OpenApi;
         (path="/staff", api =StaffApi), 
         (path="/customer", api=CustomerApi),
             (path="/ledger", api=LedgerApi),
-            (path="/transaction", api=TransactionApi)),
-    paths(crate::index::health_check)
+            (path="/reporting", api=FinancialsApi),
+            (path="/transaction", api=TransactionApi),
+            (path="/staff", api=ApiKeyApi),
+            (path="/staff", api=KycApi),
+            (path="/staff", api=ScreeningApi),
+            (path="/staff", api=BranchApi),
+            (path="/staff", api=ProductApi),
+            (path="/staff", api=OverdraftApi),
+            (path="/staff", api=HoldApi),
+            (path="/customer", api=ScheduledPaymentApi),
+            (path="/customer", api=BeneficiaryApi),
+            (path="/staff", api=PaymentSchedulerApi),
+            (path="/customer", api=SepaTransferApi),
+            (path="/staff", api=SepaBatchApi),
+            (path="/staff", api=InboundPaymentApi),
+            (path="/customer", api=AchTransferApi),
+            (path="/staff", api=AchFileApi),
+            (path="/customer", api=SwiftTransferApi),
+            (path="/staff", api=SwiftApi),
+            (path="/staff", api=NostroApi),
+            (path="/customer", api=StatementApi),
+            (path="/staff", api=StatementRunApi),
+            (path="/staff", api=FiscalApi),
+            (path="/integration/transaction", api=IntegrationApi),
+            (path="/integration/card", api=CardAuthorizationApi)),
+    paths(
+        crate::index::health_check,
+        crate::authentication::routes::jwks,
+        crate::authentication::routes::resend_activation,
+        crate::authentication::routes::list_sessions,
+        crate::authentication::routes::end_session,
+        crate::authentication::routes::end_all_sessions,
+        crate::authentication::routes::end_customer_sessions
+    )
 )]
 pub struct ApiDoc;
@@ -0,0 +1,12 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::overdraft::routes::grant_overdraft,
+    crate::overdraft::routes::fetch_overdraft,
+    crate::overdraft::routes::revoke_overdraft,
+    crate::overdraft::routes::post_forced_debit,
+    crate::overdraft::routes::run_overdraft_accruals,
+    crate::overdraft::routes::overdraft_utilization,
+))]
+pub struct OverdraftApi;
@@ -0,0 +1,6 @@
+pub mod docs;
+pub mod models;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
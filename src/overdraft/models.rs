@@ -0,0 +1,343 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::account::models::{AccountKind, UserAccountStatus};
+use crate::base::error::{DomainError, ValidationError};
+use crate::overdraft::schemas::{ForcedDebitRequest, OverdraftRequest};
+
+// Fee code looked up in the account's product fee schedule when a forced debit goes past the limit
+pub const UNARRANGED_FEE_CODE: &str = "unarranged_overdraft";
+
+// Room left on a 50 character journal reference for the fee entry's suffix
+const MAX_REFERENCE_LEN: usize = 40;
+
+const DAYS_PER_YEAR: i64 = 365;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct OverdraftEntity {
+    pub account_id: Uuid,
+    pub limit_cents: i64,
+    pub expires_on: NaiveDate,
+    pub granted_by: Option<Uuid>,
+    pub granted_at: DateTime<Utc>,
+}
+
+impl OverdraftEntity {
+    pub fn new(
+        account_id: Uuid,
+        request: &OverdraftRequest,
+        granted_by: Uuid,
+        today: NaiveDate,
+    ) -> Result<Self, ValidationError> {
+        if request.limit_cents <= 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "limit_cents".into(),
+                reason: "Must be greater than zero".into(),
+            });
+        }
+
+        if request.expires_on <= today {
+            return Err(ValidationError::InvalidValue {
+                field: "expires_on".into(),
+                reason: "Must be in the future".into(),
+            });
+        }
+
+        Ok(Self {
+            account_id,
+            limit_cents: request.limit_cents,
+            expires_on: request.expires_on,
+            granted_by: Some(granted_by),
+            granted_at: Utc::now(),
+        })
+    }
+
+    // The facility covers the whole of its expiry day
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        self.expires_on >= today
+    }
+}
+
+pub fn active_limit(facility: Option<&OverdraftEntity>, today: NaiveDate) -> i64 {
+    facility
+        .filter(|f| f.is_active(today))
+        .map(|f| f.limit_cents)
+        .unwrap_or(0)
+}
+
+// Locked for the length of the posting so concurrent debits see each other's balance
+#[derive(Debug, sqlx::FromRow)]
+pub struct OverdraftAccountEntity {
+    pub id: Uuid,
+    pub account_number: String,
+    pub branch_id: Uuid,
+    pub status: UserAccountStatus,
+    pub kind: Option<AccountKind>,
+    pub product_version_id: Option<Uuid>,
+    pub max_limit_cents: Option<i64>,
+}
+
+impl OverdraftAccountEntity {
+    pub fn check_open(&self) -> Result<(), DomainError> {
+        match self.status {
+            UserAccountStatus::Closed => Err(DomainError::InvalidState(format!(
+                "account {} is closed",
+                self.account_number
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // Only current accounts opened on product terms carry a facility, capped by those terms
+    pub fn check_limit(&self, limit_cents: i64) -> Result<(), DomainError> {
+        if self.kind != Some(AccountKind::Deposit) {
+            return Err(DomainError::ConstraintViolation(
+                "overdrafts are only offered on deposit accounts".into(),
+            ));
+        }
+
+        match self.max_limit_cents {
+            Some(max) if limit_cents <= max => Ok(()),
+            Some(max) => Err(DomainError::ConstraintViolation(format!(
+                "the account's product allows an overdraft of at most {} cents",
+                max
+            ))),
+            None => Err(DomainError::ConstraintViolation(
+                "the account was not opened on product terms that allow an overdraft".into(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ForcedDebit {
+    pub amount_cents: i64,
+    pub transaction_ref: String,
+    pub description: String,
+}
+
+impl ForcedDebit {
+    pub fn parse(request: &ForcedDebitRequest) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: "Must be greater than zero".into(),
+            });
+        }
+
+        let transaction_ref = request.transaction_ref.trim();
+        if transaction_ref.is_empty() {
+            return Err(ValidationError::MissingField("transaction_ref".into()));
+        }
+        if transaction_ref.len() > MAX_REFERENCE_LEN {
+            return Err(ValidationError::TooLong {
+                field: "transaction_ref".into(),
+                max: MAX_REFERENCE_LEN,
+            });
+        }
+
+        let description = request.description.trim();
+        if description.is_empty() {
+            return Err(ValidationError::MissingField("description".into()));
+        }
+
+        Ok(Self {
+            amount_cents: request.amount_cents,
+            transaction_ref: transaction_ref.to_string(),
+            description: description.to_string(),
+        })
+    }
+
+    pub fn fee_reference(&self) -> String {
+        format!("{}-UOF", self.transaction_ref)
+    }
+}
+
+// Forced debits are allowed past the limit, the customer pays for each one that ends up there
+pub fn exceeds_limit(balance_cents: i64, limit_cents: i64) -> bool {
+    balance_cents < -limit_cents
+}
+
+// Sum over each accrued day of the overdrawn balance times its rate in basis points
+#[derive(Debug, sqlx::FromRow)]
+pub struct UnpostedAccrualEntity {
+    pub account_id: Uuid,
+    pub account_number: String,
+    pub weighted_cents_bps: i64,
+}
+
+impl UnpostedAccrualEntity {
+    // Rounded once over the whole period rather than per day, half a cent rounds up
+    pub fn interest_cents(&self) -> i64 {
+        let divisor = 10_000 * DAYS_PER_YEAR;
+        (self.weighted_cents_bps + divisor / 2) / divisor
+    }
+}
+
+pub fn interest_reference(account_number: &str, month_end: NaiveDate) -> String {
+    format!("ODI-{}-{}", account_number, month_end.format("%Y%m"))
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct UtilizationEntity {
+    pub account_id: Uuid,
+    pub account_number: String,
+    pub branch_id: Uuid,
+    pub limit_cents: i64,
+    pub expires_on: NaiveDate,
+    pub balance_cents: i64,
+}
+
+impl UtilizationEntity {
+    pub fn used_cents(&self) -> i64 {
+        (-self.balance_cents).max(0)
+    }
+
+    pub fn utilization_bps(&self) -> i64 {
+        self.used_cents() * 10_000 / self.limit_cents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        ForcedDebit, OverdraftAccountEntity, OverdraftEntity, UnpostedAccrualEntity, active_limit,
+        exceeds_limit, interest_reference,
+    };
+    use crate::account::models::{AccountKind, UserAccountStatus};
+    use crate::overdraft::schemas::{ForcedDebitRequest, OverdraftRequest};
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()
+    }
+
+    fn account(kind: AccountKind, max_limit_cents: Option<i64>) -> OverdraftAccountEntity {
+        OverdraftAccountEntity {
+            id: Uuid::now_v7(),
+            account_number: "1000000001".into(),
+            branch_id: Uuid::now_v7(),
+            status: UserAccountStatus::Active,
+            kind: Some(kind),
+            product_version_id: max_limit_cents.map(|_| Uuid::now_v7()),
+            max_limit_cents,
+        }
+    }
+
+    #[test]
+    fn facilities_must_expire_in_the_future() {
+        let request = OverdraftRequest {
+            limit_cents: 50_000,
+            expires_on: today(),
+        };
+        let _ = assert_err!(OverdraftEntity::new(
+            Uuid::now_v7(),
+            &request,
+            Uuid::now_v7(),
+            today()
+        ));
+
+        let request = OverdraftRequest {
+            limit_cents: 0,
+            expires_on: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+        };
+        let _ = assert_err!(OverdraftEntity::new(
+            Uuid::now_v7(),
+            &request,
+            Uuid::now_v7(),
+            today()
+        ));
+    }
+
+    #[test]
+    fn expired_facilities_give_no_limit() {
+        let request = OverdraftRequest {
+            limit_cents: 50_000,
+            expires_on: NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+        };
+        let facility = assert_ok!(OverdraftEntity::new(
+            Uuid::now_v7(),
+            &request,
+            Uuid::now_v7(),
+            today()
+        ));
+
+        assert_eq!(active_limit(Some(&facility), today()), 50_000);
+        assert_eq!(
+            active_limit(
+                Some(&facility),
+                NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()
+            ),
+            50_000
+        );
+        assert_eq!(
+            active_limit(
+                Some(&facility),
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap()
+            ),
+            0
+        );
+        assert_eq!(active_limit(None, today()), 0);
+    }
+
+    #[test]
+    fn limits_are_capped_by_the_product() {
+        assert_ok!(account(AccountKind::Deposit, Some(100_000)).check_limit(100_000));
+        let _ = assert_err!(account(AccountKind::Deposit, Some(100_000)).check_limit(100_001));
+        let _ = assert_err!(account(AccountKind::Deposit, None).check_limit(1));
+        let _ = assert_err!(account(AccountKind::Loan, Some(100_000)).check_limit(1));
+    }
+
+    #[test]
+    fn only_balances_below_the_limit_exceed_it() {
+        assert!(!exceeds_limit(-50_000, 50_000));
+        assert!(exceeds_limit(-50_001, 50_000));
+        assert!(exceeds_limit(-1, 0));
+    }
+
+    #[test]
+    fn interest_is_rounded_once_over_the_period() {
+        // 30 days overdrawn by $1,000 at 18.25% is 15 dollars
+        let accrual = UnpostedAccrualEntity {
+            account_id: Uuid::now_v7(),
+            account_number: "1000000001".into(),
+            weighted_cents_bps: 30 * 100_000 * 1_825,
+        };
+        assert_eq!(accrual.interest_cents(), 1_500);
+
+        // $10 at 3.65% is a tenth of a cent a day, thirty days come to three cents rather than zero
+        let accrual = UnpostedAccrualEntity {
+            weighted_cents_bps: 30 * 1_000 * 365,
+            ..accrual
+        };
+        assert_eq!(accrual.interest_cents(), 3);
+    }
+
+    #[test]
+    fn forced_debits_need_a_short_reference() {
+        let request = ForcedDebitRequest {
+            amount_cents: 2_500,
+            transaction_ref: " CHQ-1001 ".into(),
+            description: "Returned cheque".into(),
+        };
+        let debit = assert_ok!(ForcedDebit::parse(&request));
+        assert_eq!(debit.fee_reference(), "CHQ-1001-UOF");
+
+        let request = ForcedDebitRequest {
+            transaction_ref: "X".repeat(41),
+            ..request
+        };
+        let _ = assert_err!(ForcedDebit::parse(&request));
+    }
+
+    #[test]
+    fn interest_references_are_unique_per_month() {
+        assert_eq!(
+            interest_reference("1000000001", NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()),
+            "ODI-1000000001-202506"
+        );
+    }
+}
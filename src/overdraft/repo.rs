@@ -0,0 +1,221 @@
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, Transaction};
+use uuid::Uuid;
+
+use crate::overdraft::models::{
+    OverdraftAccountEntity, OverdraftEntity, UNARRANGED_FEE_CODE, UnpostedAccrualEntity,
+    UtilizationEntity,
+};
+
+// An account's balance is what it holds on deposit liability chart accounts, credits positive
+const BALANCES: &str =
+    "SELECT lb.user_account_id AS account_id, SUM(lb.credit_cents)::BIGINT AS balance_cents
+    FROM journal_line_balance lb
+    WHERE lb.coa_type = 'liability'";
+
+pub struct OverdraftRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> OverdraftRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Locking account for overdraft", skip(self))]
+    pub async fn fetch_account_for_update(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<OverdraftAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, OverdraftAccountEntity>(
+            "SELECT a.id, a.account_number, a.branch_id, a.status, c.kind, a.product_version_id,
+                    v.overdraft_limit_cents AS max_limit_cents
+                FROM user_account a
+                LEFT JOIN account_class c ON c.id = a.account_class
+                LEFT JOIN product_version v ON v.id = a.product_version_id
+                WHERE a.id=$1
+                FOR UPDATE OF a",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Read inside the transaction so entries posted earlier in it are counted
+    #[tracing::instrument("Calculating account ledger balance", skip(self))]
+    pub async fn fetch_balance(&mut self, account_id: Uuid) -> Result<i64, sqlx::Error> {
+        let result: Option<i64> = sqlx::query(&format!(
+            "SELECT b.balance_cents FROM ({} AND lb.user_account_id=$1 GROUP BY lb.user_account_id) b",
+            BALANCES
+        ))
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?
+        .map(|r| r.get("balance_cents"));
+
+        Ok(result.unwrap_or(0))
+    }
+
+    #[tracing::instrument("Saving overdraft facility", skip(self, facility))]
+    pub async fn upsert_overdraft(
+        &mut self,
+        facility: &OverdraftEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO account_overdraft(account_id, limit_cents, expires_on, granted_by, granted_at)
+                VALUES($1, $2, $3, $4, $5)
+                ON CONFLICT (account_id) DO UPDATE
+                SET limit_cents=EXCLUDED.limit_cents, expires_on=EXCLUDED.expires_on,
+                    granted_by=EXCLUDED.granted_by, granted_at=EXCLUDED.granted_at",
+        )
+        .bind(facility.account_id)
+        .bind(facility.limit_cents)
+        .bind(facility.expires_on)
+        .bind(facility.granted_by)
+        .bind(facility.granted_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Removing overdraft facility", skip(self))]
+    pub async fn delete_overdraft(&mut self, account_id: Uuid) -> Result<u64, sqlx::Error> {
+        let n_deleted = sqlx::query("DELETE FROM account_overdraft WHERE account_id=$1")
+            .bind(account_id)
+            .execute(&mut **self.tx)
+            .await?
+            .rows_affected();
+
+        Ok(n_deleted)
+    }
+
+    #[tracing::instrument("Retrieving overdraft facility", skip(self))]
+    pub async fn fetch_overdraft(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<OverdraftEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, OverdraftEntity>(
+            "SELECT account_id, limit_cents, expires_on, granted_by, granted_at
+                FROM account_overdraft WHERE account_id=$1",
+        )
+        .bind(account_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving unarranged overdraft fee", skip(self))]
+    pub async fn fetch_unarranged_fee(&self, version_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+        let result: Option<i64> =
+            sqlx::query("SELECT amount_cents FROM product_fee WHERE version_id=$1 AND code=$2")
+                .bind(version_id)
+                .bind(UNARRANGED_FEE_CODE)
+                .fetch_optional(self.pool)
+                .await?
+                .map(|r| r.get("amount_cents"));
+
+        Ok(result)
+    }
+
+    // End of day is midnight UTC, the same clock journal entries are stamped with.
+    // Running the same day twice leaves the first run's figures in place.
+    #[tracing::instrument("Accruing overdraft interest", skip(self))]
+    pub async fn accrue(&mut self, accrual_date: NaiveDate) -> Result<u64, sqlx::Error> {
+        let n_inserted = sqlx::query(&format!(
+            "INSERT INTO overdraft_accrual(account_id, accrual_date, balance_cents, rate_bps)
+                SELECT a.id, $1, b.balance_cents, v.debit_rate_bps
+                FROM user_account a
+                JOIN product_version v ON v.id = a.product_version_id AND v.debit_rate_bps > 0
+                JOIN ({} AND lb.created_date < $1::date + 1 GROUP BY lb.user_account_id) b
+                    ON b.account_id = a.id
+                WHERE a.status <> 'closed' AND b.balance_cents < 0
+                ON CONFLICT (account_id, accrual_date) DO NOTHING",
+            BALANCES
+        ))
+        .bind(accrual_date)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_inserted)
+    }
+
+    #[tracing::instrument("Retrieving unposted overdraft accruals", skip(self))]
+    pub async fn fetch_unposted_accruals(
+        &mut self,
+        until: NaiveDate,
+    ) -> Result<Vec<UnpostedAccrualEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, UnpostedAccrualEntity>(
+            "SELECT o.account_id, a.account_number,
+                    SUM(-o.balance_cents * o.rate_bps)::BIGINT AS weighted_cents_bps
+                FROM overdraft_accrual o
+                JOIN user_account a ON a.id = o.account_id
+                WHERE o.journal_entry_id IS NULL AND o.accrual_date <= $1
+                GROUP BY o.account_id, a.account_number",
+        )
+        .bind(until)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Marking overdraft accruals posted", skip(self))]
+    pub async fn mark_accruals_posted(
+        &mut self,
+        account_id: Uuid,
+        until: NaiveDate,
+        journal_entry_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE overdraft_accrual SET journal_entry_id=$3
+                WHERE account_id=$1 AND accrual_date <= $2 AND journal_entry_id IS NULL",
+        )
+        .bind(account_id)
+        .bind(until)
+        .bind(journal_entry_id)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    #[tracing::instrument("Retrieving overdraft utilization", skip(self))]
+    pub async fn fetch_utilization(
+        &self,
+        branch_id: Option<Uuid>,
+        today: NaiveDate,
+    ) -> Result<Vec<UtilizationEntity>, sqlx::Error> {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(format!(
+            "SELECT o.account_id, a.account_number, a.branch_id, o.limit_cents, o.expires_on,
+                    COALESCE(b.balance_cents, 0) AS balance_cents
+                FROM account_overdraft o
+                JOIN user_account a ON a.id = o.account_id
+                LEFT JOIN ({} GROUP BY lb.user_account_id) b ON b.account_id = o.account_id
+                WHERE o.expires_on >= ",
+            BALANCES
+        ));
+        builder.push_bind(today);
+
+        if let Some(branch_id) = branch_id {
+            builder.push(" AND a.branch_id = ").push_bind(branch_id);
+        }
+
+        builder.push(
+            " ORDER BY GREATEST(-COALESCE(b.balance_cents, 0), 0)::FLOAT8 / o.limit_cents DESC, a.account_number",
+        );
+
+        let result = builder
+            .build_query_as::<UtilizationEntity>()
+            .fetch_all(self.pool)
+            .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,108 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+use crate::overdraft::{
+    schemas::{
+        AccrualRunRequest, AccrualRunResponse, ForcedDebitRequest, ForcedDebitResponse,
+        OverdraftRequest, OverdraftResponse, UtilizationQuery, UtilizationResponse,
+    },
+    service::OverdraftService,
+};
+
+#[tracing::instrument("Grant overdraft", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/accounts/{account_id}/overdraft", params(("account_id"=Uuid, Path, description="Account id")), request_body=OverdraftRequest, responses((status=200, body=OverdraftResponse, description="Facility granted, replacing any earlier one"), (status=400, description="Invalid limit or expiry"), (status=403, description="Managers can only grant overdrafts at their own branches"), (status=404, description="Account not found"), (status=409, description="Account closed"), (status=422, description="Limit not allowed by the account's product")))]
+pub async fn grant_overdraft(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+    payload: web::Json<OverdraftRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    let response = overdraft_service
+        .grant(&claims, account_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch overdraft", skip(app_state))]
+#[utoipa::path(get, path="/accounts/{account_id}/overdraft", params(("account_id"=Uuid, Path, description="Account id")), responses((status=200, body=OverdraftResponse, description="Balance, available balance and facility"), (status=404, description="Account not found")))]
+pub async fn fetch_overdraft(
+    app_state: web::Data<AppState>,
+    account_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    let response = overdraft_service.fetch(account_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Revoke overdraft", skip(app_state, claims))]
+#[utoipa::path(delete, path="/accounts/{account_id}/overdraft", params(("account_id"=Uuid, Path, description="Account id")), responses((status=200, body=StdResponse, description="Facility removed"), (status=403, description="Managers can only revoke overdrafts at their own branches"), (status=404, description="Account or facility not found")))]
+pub async fn revoke_overdraft(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    overdraft_service
+        .revoke(&claims, account_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Overdraft removed")))
+}
+
+#[tracing::instrument("Forced debit", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/accounts/{account_id}/forced-debits", params(("account_id"=Uuid, Path, description="Account id")), request_body=ForcedDebitRequest, responses((status=200, body=ForcedDebitResponse, description="Debit posted regardless of the available balance"), (status=400, description="Invalid amount or reference"), (status=403, description="Managers can only post to accounts at their own branches"), (status=404, description="Account not found"), (status=409, description="Account closed or reference already used")))]
+pub async fn post_forced_debit(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+    payload: web::Json<ForcedDebitRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    let response = overdraft_service
+        .forced_debit(&claims, account_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Overdraft accrual run", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/overdrafts/accruals", request_body=AccrualRunRequest, responses((status=200, body=AccrualRunResponse, description="Day accrued, interest charged at month end"), (status=400, description="Day has not ended yet"), (status=403, description="Only superusers can run accruals")))]
+pub async fn run_overdraft_accruals(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<AccrualRunRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    let response = overdraft_service
+        .run_accruals(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Overdraft utilization", skip(app_state, claims))]
+#[utoipa::path(get, path="/overdrafts/utilization", params(UtilizationQuery), responses((status=200, body=Vec<UtilizationResponse>, description="Facilities in force, most used first"), (status=403, description="Managers can only report on their own branches"), (status=404, description="Branch not found")))]
+pub async fn overdraft_utilization(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    query: web::Query<UtilizationQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let overdraft_service = OverdraftService::from(&app_state);
+
+    let response = overdraft_service
+        .utilization(&claims, query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
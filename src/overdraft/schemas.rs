@@ -0,0 +1,113 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::overdraft::models::{OverdraftEntity, UtilizationEntity};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct OverdraftRequest {
+    #[schema(example = 50000)]
+    pub limit_cents: i64,
+    // Inclusive, the facility lapses the day after
+    #[schema(example = "2026-12-31")]
+    pub expires_on: NaiveDate,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct OverdraftFacilityResponse {
+    pub limit_cents: i64,
+    pub expires_on: NaiveDate,
+    pub is_active: bool,
+    pub granted_by: Option<Uuid>,
+    pub granted_at: DateTime<Utc>,
+}
+
+impl OverdraftFacilityResponse {
+    pub fn new(facility: OverdraftEntity, today: NaiveDate) -> Self {
+        Self {
+            is_active: facility.is_active(today),
+            limit_cents: facility.limit_cents,
+            expires_on: facility.expires_on,
+            granted_by: facility.granted_by,
+            granted_at: facility.granted_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct OverdraftResponse {
+    pub account_id: Uuid,
+    pub balance_cents: i64,
+    pub held_cents: i64,
+    // The balance plus any limit still in force, less what is held
+    pub available_cents: i64,
+    pub facility: Option<OverdraftFacilityResponse>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ForcedDebitRequest {
+    #[schema(example = 2500)]
+    pub amount_cents: i64,
+    #[schema(example = "CHQ-1001")]
+    pub transaction_ref: String,
+    #[schema(example = "Returned cheque")]
+    pub description: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ForcedDebitResponse {
+    pub journal_entry_id: Uuid,
+    // Charged when the debit took the balance past the arranged limit
+    pub unarranged_fee_cents: Option<i64>,
+    pub balance_cents: i64,
+    pub available_cents: i64,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct AccrualRunRequest {
+    #[schema(example = "2025-06-30")]
+    pub accrual_date: NaiveDate,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AccrualRunResponse {
+    pub accrual_date: NaiveDate,
+    pub accounts_accrued: u64,
+    // Only set on the last day of a month when accrued interest is charged
+    pub accounts_charged: u64,
+    pub interest_charged_cents: i64,
+}
+
+// Without a branch the report covers every branch and is limited to superusers
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct UtilizationQuery {
+    pub branch_id: Option<Uuid>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct UtilizationResponse {
+    pub account_id: Uuid,
+    pub account_number: String,
+    pub branch_id: Uuid,
+    pub limit_cents: i64,
+    pub expires_on: NaiveDate,
+    pub balance_cents: i64,
+    pub used_cents: i64,
+    pub utilization_bps: i64,
+    pub is_exceeded: bool,
+}
+
+impl From<UtilizationEntity> for UtilizationResponse {
+    fn from(value: UtilizationEntity) -> Self {
+        Self {
+            used_cents: value.used_cents(),
+            utilization_bps: value.utilization_bps(),
+            is_exceeded: value.used_cents() > value.limit_cents,
+            account_id: value.account_id,
+            account_number: value.account_number,
+            branch_id: value.branch_id,
+            limit_cents: value.limit_cents,
+            expires_on: value.expires_on,
+            balance_cents: value.balance_cents,
+        }
+    }
+}
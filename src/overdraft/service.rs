@@ -0,0 +1,366 @@
+use chrono::{Datelike, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::branch::service::BranchService;
+use crate::config::state::AppState;
+use crate::hold::service::HoldService;
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::service::LedgerService;
+use crate::overdraft::{
+    models::{
+        ForcedDebit, OverdraftAccountEntity, OverdraftEntity, active_limit, exceeds_limit,
+        interest_reference,
+    },
+    schemas::{
+        AccrualRunRequest, AccrualRunResponse, ForcedDebitRequest, ForcedDebitResponse,
+        OverdraftFacilityResponse, OverdraftRequest, OverdraftResponse, UtilizationQuery,
+        UtilizationResponse,
+    },
+};
+use crate::staff::models::CoaType;
+use crate::user::models::AccessRole;
+
+pub struct OverdraftService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> OverdraftService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // Credit decisions and postings on an account are made at its own branch
+    async fn lock_account(
+        uow: &mut UnitofWork<'_>,
+        claims: &SessionClaims,
+        account_id: Uuid,
+    ) -> Result<OverdraftAccountEntity, AppError> {
+        let account = match uow
+            .overdrafts()
+            .fetch_account_for_update(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        BranchService::check_access(uow, claims, account.branch_id).await?;
+
+        Ok(account)
+    }
+
+    #[tracing::instrument("Grant overdraft", skip(self, claims, request))]
+    pub async fn grant(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        request: OverdraftRequest,
+    ) -> Result<OverdraftResponse, AppError> {
+        let today = Utc::now().date_naive();
+        let facility = OverdraftEntity::new(account_id, &request, *claims.get_user_id(), today)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = Self::lock_account(&mut uow, claims, account_id).await?;
+        account.check_open()?;
+        account.check_limit(facility.limit_cents)?;
+
+        uow.overdrafts()
+            .upsert_overdraft(&facility)
+            .await
+            .to_app_err("Failed to save overdraft facility")?;
+
+        let balance_cents = uow
+            .overdrafts()
+            .fetch_balance(account_id)
+            .await
+            .to_app_err("Failed to calculate account balance")?;
+
+        let held_cents = uow
+            .holds()
+            .fetch_held_cents(account_id, Utc::now())
+            .await
+            .to_app_err("Failed to calculate held funds")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit overdraft facility")?;
+
+        Ok(OverdraftResponse {
+            account_id,
+            balance_cents,
+            held_cents,
+            available_cents: balance_cents + facility.limit_cents - held_cents,
+            facility: Some(OverdraftFacilityResponse::new(facility, today)),
+        })
+    }
+
+    // Balances already past zero stay where they are, further debits are refused or charged
+    #[tracing::instrument("Revoke overdraft", skip(self, claims))]
+    pub async fn revoke(&self, claims: &SessionClaims, account_id: Uuid) -> Result<(), AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::lock_account(&mut uow, claims, account_id).await?;
+
+        if uow
+            .overdrafts()
+            .delete_overdraft(account_id)
+            .await
+            .to_app_err("Failed to remove overdraft facility")?
+            == 0
+        {
+            Err(DomainError::NotFound("overdraft".into()))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit overdraft removal")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Fetch overdraft", skip(self))]
+    pub async fn fetch(&self, account_id: Uuid) -> Result<OverdraftResponse, AppError> {
+        let now = Utc::now();
+        let today = now.date_naive();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .accounts()
+            .fetch_coa_id_by_account_id(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+            .is_none()
+        {
+            Err(DomainError::NotFound("account".into()))?
+        }
+
+        let facility = uow
+            .overdrafts()
+            .fetch_overdraft(account_id)
+            .await
+            .to_app_err("Failed to fetch overdraft facility")?;
+
+        let balance = HoldService::available_balance(&mut uow, account_id, now).await?;
+
+        Ok(OverdraftResponse {
+            account_id,
+            balance_cents: balance.balance_cents,
+            held_cents: balance.held_cents,
+            available_cents: balance.available_cents(),
+            facility: facility.map(|f| OverdraftFacilityResponse::new(f, today)),
+        })
+    }
+
+    // Debits the bank can't refuse, such as returned cheques and card settlements
+    #[tracing::instrument("Forced debit", skip(self, claims, request))]
+    pub async fn forced_debit(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        request: ForcedDebitRequest,
+    ) -> Result<ForcedDebitResponse, AppError> {
+        let debit = ForcedDebit::parse(&request)?;
+        let today = Utc::now().date_naive();
+        let ledger = LedgerService::from(self.app_state);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = Self::lock_account(&mut uow, claims, account_id).await?;
+        account.check_open()?;
+
+        let journal_entry_id = ledger
+            .post_account_debit(
+                &mut uow,
+                account_id,
+                debit.transaction_ref.clone(),
+                debit.description.clone(),
+                debit.amount_cents,
+                CoaType::Asset,
+            )
+            .await?;
+
+        let facility = uow
+            .overdrafts()
+            .fetch_overdraft(account_id)
+            .await
+            .to_app_err("Failed to fetch overdraft facility")?;
+        let limit_cents = active_limit(facility.as_ref(), today);
+
+        let mut balance_cents = uow
+            .overdrafts()
+            .fetch_balance(account_id)
+            .await
+            .to_app_err("Failed to calculate account balance")?;
+
+        let mut unarranged_fee_cents = None;
+
+        if exceeds_limit(balance_cents, limit_cents)
+            && let Some(version_id) = account.product_version_id
+            && let Some(fee_cents) = uow
+                .overdrafts()
+                .fetch_unarranged_fee(version_id)
+                .await
+                .to_app_err("Failed to fetch unarranged overdraft fee")?
+            && fee_cents > 0
+        {
+            ledger
+                .post_account_debit(
+                    &mut uow,
+                    account_id,
+                    debit.fee_reference(),
+                    format!("Unarranged overdraft fee for {}", debit.transaction_ref),
+                    fee_cents,
+                    CoaType::Income,
+                )
+                .await?;
+
+            balance_cents -= fee_cents;
+            unarranged_fee_cents = Some(fee_cents);
+        }
+
+        let held_cents = uow
+            .holds()
+            .fetch_held_cents(account_id, Utc::now())
+            .await
+            .to_app_err("Failed to calculate held funds")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit forced debit")?;
+
+        Ok(ForcedDebitResponse {
+            journal_entry_id,
+            unarranged_fee_cents,
+            balance_cents,
+            available_cents: balance_cents + limit_cents - held_cents,
+        })
+    }
+
+    // Meant to run once a day after close of business, any day can be run again or caught up on
+    #[tracing::instrument("Overdraft accrual run", skip(self, claims))]
+    pub async fn run_accruals(
+        &self,
+        claims: &SessionClaims,
+        request: AccrualRunRequest,
+    ) -> Result<AccrualRunResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let accrual_date = request.accrual_date;
+        if accrual_date >= Utc::now().date_naive() {
+            Err(ValidationError::InvalidValue {
+                field: "accrual_date".into(),
+                reason: "Only days that have ended can be accrued".into(),
+            })?
+        }
+
+        let ledger = LedgerService::from(self.app_state);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let accounts_accrued = uow
+            .overdrafts()
+            .accrue(accrual_date)
+            .await
+            .to_app_err("Failed to accrue overdraft interest")?;
+
+        let mut accounts_charged = 0;
+        let mut interest_charged_cents = 0;
+
+        // Interest is charged on the last day of the month for everything accrued up to it
+        if accrual_date.succ_opt().is_some_and(|d| d.day() == 1) {
+            let unposted = uow
+                .overdrafts()
+                .fetch_unposted_accruals(accrual_date)
+                .await
+                .to_app_err("Failed to fetch overdraft accruals")?;
+
+            for accrual in unposted {
+                let interest_cents = accrual.interest_cents();
+                // Less than half a cent is carried into next month
+                if interest_cents == 0 {
+                    continue;
+                }
+
+                let journal_entry_id = ledger
+                    .post_account_debit(
+                        &mut uow,
+                        accrual.account_id,
+                        interest_reference(&accrual.account_number, accrual_date),
+                        format!("Overdraft interest to {}", accrual_date),
+                        interest_cents,
+                        CoaType::Income,
+                    )
+                    .await?;
+
+                uow.overdrafts()
+                    .mark_accruals_posted(accrual.account_id, accrual_date, journal_entry_id)
+                    .await
+                    .to_app_err("Failed to mark overdraft accruals posted")?;
+
+                accounts_charged += 1;
+                interest_charged_cents += interest_cents;
+            }
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit overdraft accrual run")?;
+
+        Ok(AccrualRunResponse {
+            accrual_date,
+            accounts_accrued,
+            accounts_charged,
+            interest_charged_cents,
+        })
+    }
+
+    // Most used facilities first
+    #[tracing::instrument("Overdraft utilization", skip(self, claims))]
+    pub async fn utilization(
+        &self,
+        claims: &SessionClaims,
+        query: UtilizationQuery,
+    ) -> Result<Vec<UtilizationResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        match query.branch_id {
+            Some(branch_id) => BranchService::check_access(&mut uow, claims, branch_id).await?,
+            None => Self::require_superuser(claims)?,
+        }
+
+        let facilities = uow
+            .overdrafts()
+            .fetch_utilization(query.branch_id, Utc::now().date_naive())
+            .await
+            .to_app_err("Failed to fetch overdraft utilization")?;
+
+        Ok(facilities
+            .into_iter()
+            .map(UtilizationResponse::from)
+            .collect())
+    }
+}
@@ -0,0 +1,12 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::product::routes::create_product,
+    crate::product::routes::list_products,
+    crate::product::routes::fetch_product,
+    crate::product::routes::update_product,
+    crate::product::routes::retire_product,
+    crate::product::routes::list_product_versions,
+))]
+pub struct ProductApi;
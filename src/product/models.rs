@@ -0,0 +1,517 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::AccountKind;
+use crate::base::error::{DomainError, ValidationError};
+use crate::product::schemas::{ProductCreateRequest, ProductTermsRequest};
+
+const MAX_RATE_BPS: i32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, sqlx::Type, Display)]
+#[sqlx(type_name = "kyc_level", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum KycLevel {
+    // Nothing beyond the bank-wide kyc policy, which already asks for a verified identity
+    None,
+    Verified,
+}
+
+impl FromStr for KycLevel {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "none" => Ok(KycLevel::None),
+            "verified" => Ok(KycLevel::Verified),
+            _ => Err(ValidationError::InvalidValue {
+                field: "min_kyc_level".into(),
+                reason: "Unknown kyc level".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "statement_frequency", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StatementFrequency {
+    Monthly,
+    Quarterly,
+    Annual,
+}
+
+impl FromStr for StatementFrequency {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "monthly" => Ok(StatementFrequency::Monthly),
+            "quarterly" => Ok(StatementFrequency::Quarterly),
+            "annual" => Ok(StatementFrequency::Annual),
+            _ => Err(ValidationError::InvalidValue {
+                field: "statement_frequency".into(),
+                reason: "Unknown statement frequency".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "fee_frequency", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FeeFrequency {
+    OneOff,
+    PerTransaction,
+    Monthly,
+    Quarterly,
+    Annual,
+}
+
+impl FromStr for FeeFrequency {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "one_off" => Ok(FeeFrequency::OneOff),
+            "per_transaction" => Ok(FeeFrequency::PerTransaction),
+            "monthly" => Ok(FeeFrequency::Monthly),
+            "quarterly" => Ok(FeeFrequency::Quarterly),
+            "annual" => Ok(FeeFrequency::Annual),
+            _ => Err(ValidationError::InvalidValue {
+                field: "frequency".into(),
+                reason: "Unknown fee frequency".into(),
+            }),
+        }
+    }
+}
+
+pub fn parse_product_code(code: &str) -> Result<String, ValidationError> {
+    let code = code.trim().to_uppercase();
+
+    if code.is_empty() {
+        return Err(ValidationError::MissingField("code".into()));
+    }
+
+    if code.len() > 20 {
+        return Err(ValidationError::TooLong {
+            field: "code".into(),
+            max: 20,
+        });
+    }
+
+    if !code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ValidationError::InvalidFormat("code".into()));
+    }
+
+    Ok(code)
+}
+
+pub fn parse_product_name(name: &str) -> Result<String, ValidationError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(ValidationError::MissingField("name".into()));
+    }
+
+    if name.chars().count() > 100 {
+        return Err(ValidationError::TooLong {
+            field: "name".into(),
+            max: 100,
+        });
+    }
+
+    Ok(name.to_string())
+}
+
+// Whole years, a birthday counts from the day itself
+pub fn age_on(date_of_birth: NaiveDate, today: NaiveDate) -> i32 {
+    let mut age = today.year() - date_of_birth.year();
+    if (today.month(), today.day()) < (date_of_birth.month(), date_of_birth.day()) {
+        age -= 1;
+    }
+    age
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ProductEntity {
+    pub id: Uuid,
+    pub code: String,
+    pub kind: AccountKind,
+    pub name: String,
+    pub description: Option<String>,
+    pub coa_id: Uuid,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProductEntity {
+    pub fn new(request: &ProductCreateRequest) -> Result<Self, ValidationError> {
+        Ok(Self {
+            id: Uuid::now_v7(),
+            code: parse_product_code(&request.code)?,
+            kind: AccountKind::from_str(&request.kind)?,
+            name: parse_product_name(&request.name)?,
+            description: request
+                .description
+                .as_deref()
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(String::from),
+            coa_id: request.coa_id,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct InterestTier {
+    pub version_id: Uuid,
+    pub min_balance_cents: i64,
+    pub rate_bps: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct ProductFee {
+    pub version_id: Uuid,
+    pub code: String,
+    pub amount_cents: i64,
+    pub frequency: FeeFrequency,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ProductVersionEntity {
+    pub id: Uuid,
+    pub account_class_id: Uuid,
+    pub version: i32,
+    pub currencies: Vec<String>,
+    pub min_age: i16,
+    pub max_age: Option<i16>,
+    pub min_kyc_level: KycLevel,
+    pub overdraft_limit_cents: i64,
+    pub debit_rate_bps: i32,
+    pub dormancy_days: i32,
+    pub statement_frequency: StatementFrequency,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub interest_tiers: Vec<InterestTier>,
+    #[sqlx(skip)]
+    pub fees: Vec<ProductFee>,
+}
+
+impl ProductVersionEntity {
+    pub fn new(
+        account_class_id: Uuid,
+        version: i32,
+        terms: &ProductTermsRequest,
+        created_by: Uuid,
+    ) -> Result<Self, ValidationError> {
+        let id = Uuid::now_v7();
+
+        let mut currencies = Vec::with_capacity(terms.currencies.len());
+        for code in &terms.currencies {
+            let code = code.trim().to_uppercase();
+            if iso_currency::Currency::from_code(&code).is_none() {
+                return Err(ValidationError::InvalidValue {
+                    field: "currencies".into(),
+                    reason: format!("Unknown ISO 4217 currency {}", code),
+                });
+            }
+            if !currencies.contains(&code) {
+                currencies.push(code);
+            }
+        }
+        if currencies.is_empty() {
+            return Err(ValidationError::MissingField("currencies".into()));
+        }
+
+        let min_age = terms.min_age.unwrap_or(18);
+        if !(0..=120).contains(&min_age) {
+            return Err(ValidationError::OutOfRange {
+                field: "min_age".into(),
+                min: "0".into(),
+                max: "120".into(),
+            });
+        }
+        if let Some(max_age) = terms.max_age
+            && !(min_age..=120).contains(&max_age)
+        {
+            return Err(ValidationError::OutOfRange {
+                field: "max_age".into(),
+                min: min_age.to_string(),
+                max: "120".into(),
+            });
+        }
+
+        if terms.overdraft_limit_cents < 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "overdraft_limit_cents".into(),
+                reason: "Cannot be negative".into(),
+            });
+        }
+
+        if !(0..=MAX_RATE_BPS).contains(&terms.debit_rate_bps) {
+            return Err(ValidationError::OutOfRange {
+                field: "debit_rate_bps".into(),
+                min: "0".into(),
+                max: MAX_RATE_BPS.to_string(),
+            });
+        }
+
+        if terms.dormancy_days < 1 {
+            return Err(ValidationError::InvalidValue {
+                field: "dormancy_days".into(),
+                reason: "Must be at least one day".into(),
+            });
+        }
+
+        // Tiers are kept in ascending order so the applicable one is the last at or below a balance
+        let mut interest_tiers = Vec::with_capacity(terms.interest_tiers.len());
+        for tier in &terms.interest_tiers {
+            if tier.min_balance_cents < 0 {
+                return Err(ValidationError::InvalidValue {
+                    field: "interest_tiers".into(),
+                    reason: "min_balance_cents cannot be negative".into(),
+                });
+            }
+            if !(0..=MAX_RATE_BPS).contains(&tier.rate_bps) {
+                return Err(ValidationError::OutOfRange {
+                    field: "rate_bps".into(),
+                    min: "0".into(),
+                    max: MAX_RATE_BPS.to_string(),
+                });
+            }
+            interest_tiers.push(InterestTier {
+                version_id: id,
+                min_balance_cents: tier.min_balance_cents,
+                rate_bps: tier.rate_bps,
+            });
+        }
+        interest_tiers.sort_by_key(|t| t.min_balance_cents);
+        if interest_tiers
+            .windows(2)
+            .any(|w| w[0].min_balance_cents == w[1].min_balance_cents)
+        {
+            return Err(ValidationError::InvalidValue {
+                field: "interest_tiers".into(),
+                reason: "Two tiers start at the same balance".into(),
+            });
+        }
+
+        let mut codes = HashSet::new();
+        let mut fees = Vec::with_capacity(terms.fees.len());
+        for fee in &terms.fees {
+            let code = fee.code.trim().to_lowercase();
+            if code.is_empty() || code.len() > 32 {
+                return Err(ValidationError::InvalidFormat("fees.code".into()));
+            }
+            if !codes.insert(code.clone()) {
+                return Err(ValidationError::InvalidValue {
+                    field: "fees".into(),
+                    reason: format!("Fee {} is listed twice", code),
+                });
+            }
+            if fee.amount_cents < 0 {
+                return Err(ValidationError::InvalidValue {
+                    field: "fees".into(),
+                    reason: "amount_cents cannot be negative".into(),
+                });
+            }
+            fees.push(ProductFee {
+                version_id: id,
+                code,
+                amount_cents: fee.amount_cents,
+                frequency: FeeFrequency::from_str(&fee.frequency)?,
+            });
+        }
+
+        Ok(Self {
+            id,
+            account_class_id,
+            version,
+            currencies,
+            min_age,
+            max_age: terms.max_age,
+            min_kyc_level: KycLevel::from_str(&terms.min_kyc_level)?,
+            overdraft_limit_cents: terms.overdraft_limit_cents,
+            debit_rate_bps: terms.debit_rate_bps,
+            dormancy_days: terms.dormancy_days,
+            statement_frequency: StatementFrequency::from_str(&terms.statement_frequency)?,
+            created_by: Some(created_by),
+            created_at: Utc::now(),
+            interest_tiers,
+            fees,
+        })
+    }
+
+    // Checked against the version current at opening, later versions don't apply retroactively
+    pub fn check_eligibility(
+        &self,
+        age: Option<i32>,
+        kyc_level: KycLevel,
+        currency: &str,
+    ) -> Result<(), DomainError> {
+        match age {
+            Some(age)
+                if age < i32::from(self.min_age)
+                    || self.max_age.is_some_and(|m| age > i32::from(m)) =>
+            {
+                return Err(DomainError::ConstraintViolation(
+                    "customer's age is outside the product's eligibility".into(),
+                ));
+            }
+            None if self.min_age > 0 || self.max_age.is_some() => {
+                return Err(DomainError::ConstraintViolation(
+                    "customer's date of birth is needed to open this product".into(),
+                ));
+            }
+            _ => (),
+        }
+
+        if kyc_level < self.min_kyc_level {
+            return Err(DomainError::ConstraintViolation(
+                "customer identity must be verified before opening this product".into(),
+            ));
+        }
+
+        if !self.currencies.iter().any(|c| c == currency) {
+            return Err(DomainError::ConstraintViolation(format!(
+                "product is not offered in {}",
+                currency
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{KycLevel, ProductVersionEntity, age_on, parse_product_code};
+    use crate::product::schemas::{FeeRequest, InterestTierRequest, ProductTermsRequest};
+
+    fn terms() -> ProductTermsRequest {
+        ProductTermsRequest {
+            currencies: vec!["usd".into(), "EUR".into()],
+            min_age: None,
+            max_age: None,
+            min_kyc_level: "verified".into(),
+            interest_tiers: vec![
+                InterestTierRequest {
+                    min_balance_cents: 1_000_000,
+                    rate_bps: 150,
+                },
+                InterestTierRequest {
+                    min_balance_cents: 0,
+                    rate_bps: 50,
+                },
+            ],
+            fees: vec![FeeRequest {
+                code: "Maintenance".into(),
+                amount_cents: 500,
+                frequency: "monthly".into(),
+            }],
+            overdraft_limit_cents: 0,
+            debit_rate_bps: 0,
+            dormancy_days: 365,
+            statement_frequency: "monthly".into(),
+        }
+    }
+
+    #[test]
+    fn terms_are_normalized() {
+        let version = assert_ok!(ProductVersionEntity::new(
+            Uuid::now_v7(),
+            1,
+            &terms(),
+            Uuid::now_v7()
+        ));
+
+        assert_eq!(version.currencies, vec!["USD", "EUR"]);
+        assert_eq!(version.min_age, 18);
+        assert_eq!(version.interest_tiers[0].min_balance_cents, 0);
+        assert_eq!(version.fees[0].code, "maintenance");
+    }
+
+    #[test]
+    fn invalid_terms_are_rejected() {
+        let mut request = terms();
+        request.currencies = vec!["XYZ".into()];
+        let _ = assert_err!(ProductVersionEntity::new(
+            Uuid::now_v7(),
+            1,
+            &request,
+            Uuid::now_v7()
+        ));
+
+        let mut request = terms();
+        request.interest_tiers[1].min_balance_cents = 1_000_000;
+        let _ = assert_err!(ProductVersionEntity::new(
+            Uuid::now_v7(),
+            1,
+            &request,
+            Uuid::now_v7()
+        ));
+
+        let mut request = terms();
+        request.min_age = Some(25);
+        request.max_age = Some(21);
+        let _ = assert_err!(ProductVersionEntity::new(
+            Uuid::now_v7(),
+            1,
+            &request,
+            Uuid::now_v7()
+        ));
+    }
+
+    #[test]
+    fn eligibility_checks_age_kyc_and_currency() {
+        let mut request = terms();
+        request.max_age = Some(30);
+        let version = assert_ok!(ProductVersionEntity::new(
+            Uuid::now_v7(),
+            1,
+            &request,
+            Uuid::now_v7()
+        ));
+
+        assert_ok!(version.check_eligibility(Some(25), KycLevel::Verified, "USD"));
+        let _ = assert_err!(version.check_eligibility(Some(31), KycLevel::Verified, "USD"));
+        let _ = assert_err!(version.check_eligibility(None, KycLevel::Verified, "USD"));
+        let _ = assert_err!(version.check_eligibility(Some(25), KycLevel::None, "USD"));
+        let _ = assert_err!(version.check_eligibility(Some(25), KycLevel::Verified, "GBP"));
+    }
+
+    #[test]
+    fn age_counts_whole_years() {
+        let dob = NaiveDate::from_ymd_opt(2000, 6, 15).unwrap();
+
+        assert_eq!(
+            age_on(dob, NaiveDate::from_ymd_opt(2018, 6, 14).unwrap()),
+            17
+        );
+        assert_eq!(
+            age_on(dob, NaiveDate::from_ymd_opt(2018, 6, 15).unwrap()),
+            18
+        );
+    }
+
+    #[test]
+    fn product_codes_are_uppercased() {
+        assert_eq!(assert_ok!(parse_product_code(" sav-plus ")), "SAV-PLUS");
+        let _ = assert_err!(parse_product_code("savings plus"));
+    }
+}
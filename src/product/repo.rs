@@ -0,0 +1,261 @@
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, Transaction};
+use uuid::Uuid;
+
+use crate::product::models::{InterestTier, ProductEntity, ProductFee, ProductVersionEntity};
+
+const PRODUCT_COLUMNS: &str =
+    "id, code, kind, name, description, coa_id, is_active, created_at, updated_at";
+
+const VERSION_COLUMNS: &str = "id, account_class_id, version, currencies, min_age, max_age, min_kyc_level, overdraft_limit_cents, debit_rate_bps, dormancy_days, statement_frequency, created_by, created_at";
+
+pub struct ProductRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> ProductRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Inserting product", skip(self, product))]
+    pub async fn create_product(&mut self, product: &ProductEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO account_class({}) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            PRODUCT_COLUMNS
+        ))
+        .bind(product.id)
+        .bind(&product.code)
+        .bind(product.kind)
+        .bind(&product.name)
+        .bind(&product.description)
+        .bind(product.coa_id)
+        .bind(product.is_active)
+        .bind(product.created_at)
+        .bind(product.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Updating product", skip(self))]
+    pub async fn update_product(
+        &mut self,
+        product_id: Uuid,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE account_class SET name=COALESCE($2, name), description=COALESCE($3, description),
+                updated_at=CURRENT_TIMESTAMP WHERE id=$1",
+        )
+        .bind(product_id)
+        .bind(name)
+        .bind(description)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    #[tracing::instrument("Retiring product", skip(self))]
+    pub async fn retire_product(&mut self, product_id: Uuid) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE account_class SET is_active=FALSE, updated_at=CURRENT_TIMESTAMP WHERE id=$1",
+        )
+        .bind(product_id)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+
+    #[tracing::instrument("Retrieving product", skip(self))]
+    pub async fn fetch_product(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Option<ProductEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProductEntity>(&format!(
+            "SELECT {} FROM account_class WHERE id=$1",
+            PRODUCT_COLUMNS
+        ))
+        .bind(product_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving products", skip(self))]
+    pub async fn fetch_products(&self) -> Result<Vec<ProductEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProductEntity>(&format!(
+            "SELECT {} FROM account_class ORDER BY code",
+            PRODUCT_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Read inside the transaction so two concurrent updates can't both pick the same number,
+    // the unique constraint on (account_class_id, version) catches the loser
+    #[tracing::instrument("Retrieving next product version", skip(self))]
+    pub async fn fetch_next_version(&mut self, product_id: Uuid) -> Result<i32, sqlx::Error> {
+        let result: i32 = sqlx::query(
+            "SELECT COALESCE(MAX(version), 0) + 1 AS next FROM product_version WHERE account_class_id=$1",
+        )
+        .bind(product_id)
+        .fetch_one(&mut **self.tx)
+        .await?
+        .get("next");
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Inserting product version", skip(self, version))]
+    pub async fn create_version(
+        &mut self,
+        version: &ProductVersionEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO product_version({}) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+            VERSION_COLUMNS
+        ))
+        .bind(version.id)
+        .bind(version.account_class_id)
+        .bind(version.version)
+        .bind(&version.currencies)
+        .bind(version.min_age)
+        .bind(version.max_age)
+        .bind(version.min_kyc_level)
+        .bind(version.overdraft_limit_cents)
+        .bind(version.debit_rate_bps)
+        .bind(version.dormancy_days)
+        .bind(version.statement_frequency)
+        .bind(version.created_by)
+        .bind(version.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        if !version.interest_tiers.is_empty() {
+            let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO product_interest_tier(version_id, min_balance_cents, rate_bps) ",
+            );
+            builder.push_values(&version.interest_tiers, |mut b, tier| {
+                b.push_bind(tier.version_id)
+                    .push_bind(tier.min_balance_cents)
+                    .push_bind(tier.rate_bps);
+            });
+            builder.build().execute(&mut **self.tx).await?;
+        }
+
+        if !version.fees.is_empty() {
+            let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                "INSERT INTO product_fee(version_id, code, amount_cents, frequency) ",
+            );
+            builder.push_values(&version.fees, |mut b, fee| {
+                b.push_bind(fee.version_id)
+                    .push_bind(&fee.code)
+                    .push_bind(fee.amount_cents)
+                    .push_bind(fee.frequency);
+            });
+            builder.build().execute(&mut **self.tx).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving current product version", skip(self))]
+    pub async fn fetch_current_version(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Option<ProductVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProductVersionEntity>(&format!(
+            "SELECT {} FROM product_version WHERE account_class_id=$1 ORDER BY version DESC LIMIT 1",
+            VERSION_COLUMNS
+        ))
+        .bind(product_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        match result {
+            Some(v) => Ok(self.attach_terms(vec![v]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument("Retrieving current product versions", skip(self))]
+    pub async fn fetch_current_versions(&self) -> Result<Vec<ProductVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProductVersionEntity>(&format!(
+            "SELECT DISTINCT ON (account_class_id) {} FROM product_version
+                ORDER BY account_class_id, version DESC",
+            VERSION_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        self.attach_terms(result).await
+    }
+
+    #[tracing::instrument("Retrieving product versions", skip(self))]
+    pub async fn fetch_versions(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<ProductVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ProductVersionEntity>(&format!(
+            "SELECT {} FROM product_version WHERE account_class_id=$1 ORDER BY version DESC",
+            VERSION_COLUMNS
+        ))
+        .bind(product_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        self.attach_terms(result).await
+    }
+
+    async fn attach_terms(
+        &self,
+        mut versions: Vec<ProductVersionEntity>,
+    ) -> Result<Vec<ProductVersionEntity>, sqlx::Error> {
+        if versions.is_empty() {
+            return Ok(versions);
+        }
+
+        let ids: Vec<Uuid> = versions.iter().map(|v| v.id).collect();
+
+        let tiers = sqlx::query_as::<_, InterestTier>(
+            "SELECT version_id, min_balance_cents, rate_bps FROM product_interest_tier
+                WHERE version_id = ANY($1) ORDER BY min_balance_cents",
+        )
+        .bind(&ids)
+        .fetch_all(self.pool)
+        .await?;
+
+        let fees = sqlx::query_as::<_, ProductFee>(
+            "SELECT version_id, code, amount_cents, frequency FROM product_fee
+                WHERE version_id = ANY($1) ORDER BY code",
+        )
+        .bind(&ids)
+        .fetch_all(self.pool)
+        .await?;
+
+        for version in versions.iter_mut() {
+            version.interest_tiers = tiers
+                .iter()
+                .filter(|t| t.version_id == version.id)
+                .cloned()
+                .collect();
+            version.fees = fees
+                .iter()
+                .filter(|f| f.version_id == version.id)
+                .cloned()
+                .collect();
+        }
+
+        Ok(versions)
+    }
+}
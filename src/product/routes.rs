@@ -0,0 +1,99 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+use crate::product::{
+    schemas::{
+        ProductCreateRequest, ProductResponse, ProductUpdateRequest, ProductVersionResponse,
+    },
+    service::ProductService,
+};
+
+#[tracing::instrument("Create product", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/products", request_body=ProductCreateRequest, responses((status=200, body=ProductResponse, description="Product created with its first version of terms"), (status=400, description="Invalid code, kind or terms"), (status=403, description="Only superusers can manage products"), (status=404, description="Chart account not found"), (status=409, description="Code already in use")))]
+pub async fn create_product(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<ProductCreateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    let response = product_service
+        .create_product(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List products", skip(app_state))]
+#[utoipa::path(get, path="/products", responses((status=200, body=Vec<ProductResponse>, description="Products by code with their current terms")))]
+pub async fn list_products(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    let response = product_service.list_products().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch product", skip(app_state))]
+#[utoipa::path(get, path="/products/{product_id}", params(("product_id"=Uuid, Path, description="Product id")), responses((status=200, body=ProductResponse, description="Product with its current terms"), (status=404, description="Product not found")))]
+pub async fn fetch_product(
+    app_state: web::Data<AppState>,
+    product_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    let response = product_service.fetch(product_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Update product", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/products/{product_id}", params(("product_id"=Uuid, Path, description="Product id")), request_body=ProductUpdateRequest, responses((status=200, body=ProductResponse, description="Product updated, new terms become the next version"), (status=400, description="Invalid name or terms"), (status=403, description="Only superusers can manage products"), (status=404, description="Product not found"), (status=409, description="Product has been retired")))]
+pub async fn update_product(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    product_id: web::Path<Uuid>,
+    payload: web::Json<ProductUpdateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    let response = product_service
+        .update_product(&claims, product_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Retire product", skip(app_state, claims))]
+#[utoipa::path(delete, path="/products/{product_id}", params(("product_id"=Uuid, Path, description="Product id")), responses((status=200, body=StdResponse, description="Product retired, existing accounts keep their terms"), (status=403, description="Only superusers can manage products"), (status=404, description="Product not found")))]
+pub async fn retire_product(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    product_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    product_service
+        .retire_product(&claims, product_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Product retired")))
+}
+
+#[tracing::instrument("List product versions", skip(app_state))]
+#[utoipa::path(get, path="/products/{product_id}/versions", params(("product_id"=Uuid, Path, description="Product id")), responses((status=200, body=Vec<ProductVersionResponse>, description="Every version of the product's terms, newest first"), (status=404, description="Product not found")))]
+pub async fn list_product_versions(
+    app_state: web::Data<AppState>,
+    product_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let product_service = ProductService::from(&app_state);
+
+    let response = product_service
+        .list_versions(product_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::product::models::{InterestTier, ProductEntity, ProductFee, ProductVersionEntity};
+
+#[derive(Debug, Clone, serde::Deserialize, utoipa::ToSchema)]
+pub struct InterestTierRequest {
+    #[schema(example = 0)]
+    pub min_balance_cents: i64,
+    #[schema(example = 125)]
+    pub rate_bps: i32,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, utoipa::ToSchema)]
+pub struct FeeRequest {
+    #[schema(example = "maintenance")]
+    pub code: String,
+    #[schema(example = 500)]
+    pub amount_cents: i64,
+    #[schema(example = "monthly")]
+    pub frequency: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, utoipa::ToSchema)]
+pub struct ProductTermsRequest {
+    #[schema(example = json!(["USD", "EUR"]))]
+    pub currencies: Vec<String>,
+    // Defaults to 18
+    pub min_age: Option<i16>,
+    pub max_age: Option<i16>,
+    #[schema(example = "verified")]
+    pub min_kyc_level: String,
+    #[serde(default)]
+    pub interest_tiers: Vec<InterestTierRequest>,
+    #[serde(default)]
+    pub fees: Vec<FeeRequest>,
+    #[serde(default)]
+    pub overdraft_limit_cents: i64,
+    // Annual rate on the overdrawn part of a balance
+    #[serde(default)]
+    pub debit_rate_bps: i32,
+    #[schema(example = 365)]
+    pub dormancy_days: i32,
+    #[schema(example = "monthly")]
+    pub statement_frequency: String,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ProductCreateRequest {
+    #[schema(example = "SAV-PLUS")]
+    pub code: String,
+    #[schema(example = "deposit")]
+    pub kind: String,
+    #[schema(example = "Savings Plus")]
+    pub name: String,
+    pub description: Option<String>,
+    pub coa_id: Uuid,
+    pub terms: ProductTermsRequest,
+}
+
+// New terms are added as the next version, accounts already open keep theirs
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ProductUpdateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub terms: Option<ProductTermsRequest>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct InterestTierResponse {
+    pub min_balance_cents: i64,
+    pub rate_bps: i32,
+}
+
+impl From<InterestTier> for InterestTierResponse {
+    fn from(value: InterestTier) -> Self {
+        Self {
+            min_balance_cents: value.min_balance_cents,
+            rate_bps: value.rate_bps,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct FeeResponse {
+    pub code: String,
+    pub amount_cents: i64,
+    pub frequency: String,
+}
+
+impl From<ProductFee> for FeeResponse {
+    fn from(value: ProductFee) -> Self {
+        Self {
+            code: value.code,
+            amount_cents: value.amount_cents,
+            frequency: value.frequency.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ProductVersionResponse {
+    pub id: Uuid,
+    pub version: i32,
+    pub currencies: Vec<String>,
+    pub min_age: i16,
+    pub max_age: Option<i16>,
+    pub min_kyc_level: String,
+    pub interest_tiers: Vec<InterestTierResponse>,
+    pub fees: Vec<FeeResponse>,
+    pub overdraft_limit_cents: i64,
+    pub debit_rate_bps: i32,
+    pub dormancy_days: i32,
+    pub statement_frequency: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ProductVersionEntity> for ProductVersionResponse {
+    fn from(value: ProductVersionEntity) -> Self {
+        Self {
+            id: value.id,
+            version: value.version,
+            currencies: value.currencies,
+            min_age: value.min_age,
+            max_age: value.max_age,
+            min_kyc_level: value.min_kyc_level.to_string(),
+            interest_tiers: value
+                .interest_tiers
+                .into_iter()
+                .map(InterestTierResponse::from)
+                .collect(),
+            fees: value.fees.into_iter().map(FeeResponse::from).collect(),
+            overdraft_limit_cents: value.overdraft_limit_cents,
+            debit_rate_bps: value.debit_rate_bps,
+            dormancy_days: value.dormancy_days,
+            statement_frequency: value.statement_frequency.to_string(),
+            created_by: value.created_by,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ProductResponse {
+    pub id: Uuid,
+    pub code: String,
+    pub kind: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub coa_id: Uuid,
+    pub is_active: bool,
+    // Empty for classes created before the catalogue that were never given terms
+    pub current: Option<ProductVersionResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProductResponse {
+    pub fn new(product: ProductEntity, current: Option<ProductVersionEntity>) -> Self {
+        Self {
+            id: product.id,
+            code: product.code,
+            kind: product.kind.to_string(),
+            name: product.name,
+            description: product.description,
+            coa_id: product.coa_id,
+            is_active: product.is_active,
+            current: current.map(ProductVersionResponse::from),
+            created_at: product.created_at,
+            updated_at: product.updated_at,
+        }
+    }
+}
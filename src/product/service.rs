@@ -0,0 +1,283 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::product::{
+    models::{KycLevel, ProductEntity, ProductVersionEntity, age_on, parse_product_name},
+    schemas::{
+        ProductCreateRequest, ProductResponse, ProductUpdateRequest, ProductVersionResponse,
+    },
+};
+use crate::user::models::{AccessRole, UserEntity};
+
+pub struct ProductService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> ProductService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // The catalogue is managed centrally, branches only sell what's in it
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    async fn fetch_product(
+        uow: &mut UnitofWork<'_>,
+        product_id: Uuid,
+    ) -> Result<ProductEntity, AppError> {
+        match uow
+            .products()
+            .fetch_product(product_id)
+            .await
+            .to_app_err("Failed to fetch product")?
+        {
+            Some(p) => Ok(p),
+            None => Err(DomainError::NotFound("product".into()))?,
+        }
+    }
+
+    // Returns the version the account is opened on, None for classes that predate the catalogue.
+    // The kyc policy applies to every account, product terms can only add to it.
+    pub async fn check_account_opening(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        product_id: Uuid,
+        owner: &UserEntity,
+        currency: &str,
+    ) -> Result<Option<Uuid>, AppError> {
+        let product = Self::fetch_product(uow, product_id).await?;
+
+        if !product.is_active {
+            Err(DomainError::InvalidState(format!(
+                "product {} is no longer offered",
+                product.code
+            )))?
+        }
+
+        self.app_state
+            .kyc_policy
+            .check_account_opening(owner.is_verified)?;
+
+        let current = uow
+            .products()
+            .fetch_current_version(product_id)
+            .await
+            .to_app_err("Failed to fetch product terms")?;
+
+        match current {
+            Some(version) => {
+                let age = owner
+                    .date_of_birth
+                    .map(|dob| age_on(dob, Utc::now().date_naive()));
+                let kyc_level = match owner.is_verified {
+                    true => KycLevel::Verified,
+                    false => KycLevel::None,
+                };
+
+                version.check_eligibility(age, kyc_level, currency)?;
+
+                Ok(Some(version.id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument("Create product", skip(self, claims, request))]
+    pub async fn create_product(
+        &self,
+        claims: &SessionClaims,
+        request: ProductCreateRequest,
+    ) -> Result<ProductResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let product = ProductEntity::new(&request)?;
+        let version =
+            ProductVersionEntity::new(product.id, 1, &request.terms, *claims.get_user_id())?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        // Unique violation on the code, foreign key violation on an unknown chart account
+        uow.products()
+            .create_product(&product)
+            .await
+            .to_app_err("product code or chart account")?;
+
+        uow.products()
+            .create_version(&version)
+            .await
+            .to_app_err("Failed to create product version")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit product creation")?;
+
+        Ok(ProductResponse::new(product, Some(version)))
+    }
+
+    #[tracing::instrument("List products", skip(self))]
+    pub async fn list_products(&self) -> Result<Vec<ProductResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let products = uow
+            .products()
+            .fetch_products()
+            .await
+            .to_app_err("Failed to fetch products")?;
+
+        let mut versions = uow
+            .products()
+            .fetch_current_versions()
+            .await
+            .to_app_err("Failed to fetch product terms")?;
+
+        Ok(products
+            .into_iter()
+            .map(|p| {
+                let current = versions
+                    .iter()
+                    .position(|v| v.account_class_id == p.id)
+                    .map(|i| versions.swap_remove(i));
+                ProductResponse::new(p, current)
+            })
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch product", skip(self))]
+    pub async fn fetch(&self, product_id: Uuid) -> Result<ProductResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let product = Self::fetch_product(&mut uow, product_id).await?;
+
+        let current = uow
+            .products()
+            .fetch_current_version(product_id)
+            .await
+            .to_app_err("Failed to fetch product terms")?;
+
+        Ok(ProductResponse::new(product, current))
+    }
+
+    #[tracing::instrument("Update product", skip(self, claims, request))]
+    pub async fn update_product(
+        &self,
+        claims: &SessionClaims,
+        product_id: Uuid,
+        request: ProductUpdateRequest,
+    ) -> Result<ProductResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let name = request
+            .name
+            .as_deref()
+            .map(parse_product_name)
+            .transpose()?;
+        let description = request.description.as_deref().map(str::trim);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let product = Self::fetch_product(&mut uow, product_id).await?;
+
+        if !product.is_active {
+            Err(DomainError::InvalidState(format!(
+                "product {} has been retired",
+                product.code
+            )))?
+        }
+
+        uow.products()
+            .update_product(product_id, name.as_deref(), description)
+            .await
+            .to_app_err("Failed to update product")?;
+
+        if let Some(terms) = &request.terms {
+            let next = uow
+                .products()
+                .fetch_next_version(product_id)
+                .await
+                .to_app_err("Failed to fetch product version")?;
+
+            let version =
+                ProductVersionEntity::new(product_id, next, terms, *claims.get_user_id())?;
+
+            uow.products()
+                .create_version(&version)
+                .await
+                .to_app_err("product version")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit product update")?;
+
+        self.fetch(product_id).await
+    }
+
+    #[tracing::instrument("Retire product", skip(self, claims))]
+    pub async fn retire_product(
+        &self,
+        claims: &SessionClaims,
+        product_id: Uuid,
+    ) -> Result<(), AppError> {
+        Self::require_superuser(claims)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .products()
+            .retire_product(product_id)
+            .await
+            .to_app_err("Failed to retire product")?
+            == 0
+        {
+            Err(DomainError::NotFound("product".into()))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit product retirement")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("List product versions", skip(self))]
+    pub async fn list_versions(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<ProductVersionResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::fetch_product(&mut uow, product_id).await?;
+
+        let versions = uow
+            .products()
+            .fetch_versions(product_id)
+            .await
+            .to_app_err("Failed to fetch product versions")?;
+
+        Ok(versions
+            .into_iter()
+            .map(ProductVersionResponse::from)
+            .collect())
+    }
+}
@@ -0,0 +1,529 @@
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use std::io;
+use uuid::Uuid;
+
+use crate::reporting::models::{
+    CamtPostingEntity, PostingCategory, StatementAccountEntity, StatementPeriod, decimal_cents,
+};
+
+const NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.08";
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+const MAX_ID_LEN: usize = 35;
+const MAX_NAME_LEN: usize = 140;
+const MAX_INFO_LEN: usize = 500;
+
+// Domain, family and sub-family from the ISO external bank transaction code list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankTransactionCode {
+    pub domain: &'static str,
+    pub family: &'static str,
+    pub sub_family: &'static str,
+}
+
+impl BankTransactionCode {
+    fn new(domain: &'static str, family: &'static str, sub_family: &'static str) -> Self {
+        Self {
+            domain,
+            family,
+            sub_family,
+        }
+    }
+
+    // Interest and charges are account management, the rest payments told apart by scheme.
+    // Payments we could not trace to a scheme, cash and transfers between our own accounts
+    // among them, are OTHR.
+    pub fn of(posting: &CamtPostingEntity) -> Self {
+        let credit = posting.amount_cents > 0;
+        let operations = if credit { "MCOP" } else { "MDOP" };
+
+        match PostingCategory::of(posting.transaction_ref.as_deref(), posting.contra_type) {
+            PostingCategory::Interest => Self::new("ACMT", operations, "INTR"),
+            PostingCategory::Fee => Self::new("ACMT", operations, "CHRG"),
+            PostingCategory::Transaction if posting.returned => Self::new("PMNT", "ICDT", "RRTN"),
+            PostingCategory::Transaction => {
+                let family = if credit { "RCDT" } else { "ICDT" };
+                let sub_family = match posting.scheme.as_deref() {
+                    Some("sepa") => "ESCT",
+                    Some("ach") => "DMCT",
+                    Some("swift") => "XBCT",
+                    _ => "OTHR",
+                };
+                Self::new("PMNT", family, sub_family)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Camt053Entry {
+    // Credits positive
+    pub amount_cents: i64,
+    pub booking_date: NaiveDate,
+    pub value_date: NaiveDate,
+    pub servicer_reference: Option<String>,
+    pub instruction_id: Option<String>,
+    pub end_to_end_id: Option<String>,
+    pub uetr: Option<String>,
+    pub code: BankTransactionCode,
+    pub information: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Camt053 {
+    pub message_id: String,
+    pub created_at: DateTime<Utc>,
+    pub statement_id: String,
+    pub iban: String,
+    pub account_number: String,
+    pub currency: String,
+    pub owner_name: String,
+    pub servicer_bic: String,
+    pub period: StatementPeriod,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub entries: Vec<Camt053Entry>,
+}
+
+// Max35Text, Max140Text and the like only bound the length, nothing may be empty
+fn max_text(value: Option<&str>, max: usize) -> Option<String> {
+    value
+        .map(|v| v.trim().chars().take(max).collect::<String>())
+        .filter(|v| !v.is_empty())
+}
+
+// UUIDv4Identifier
+fn is_uetr(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let hex = |b: &u8| b.is_ascii_digit() || (b'a'..=b'f').contains(b);
+
+    value.len() == 36
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => *b == b'-',
+            14 => *b == b'4',
+            19 => matches!(b, b'8' | b'9' | b'a' | b'b'),
+            _ => hex(b),
+        })
+}
+
+// IBAN2007Identifier, [A-Z]{2}[0-9]{2}[a-zA-Z0-9]{1,30}
+fn is_iban(value: &str) -> bool {
+    (5..=34).contains(&value.len())
+        && value[..2].chars().all(|c| c.is_ascii_uppercase())
+        && value[2..4].chars().all(|c| c.is_ascii_digit())
+        && value[4..].chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl Camt053 {
+    pub fn new(
+        account: &StatementAccountEntity,
+        period: StatementPeriod,
+        opening_cents: i64,
+        postings: Vec<CamtPostingEntity>,
+        servicer_bic: &str,
+        created_at: DateTime<Utc>,
+    ) -> Self {
+        let closing_cents = opening_cents + postings.iter().map(|p| p.amount_cents).sum::<i64>();
+
+        let entries = postings
+            .into_iter()
+            .map(|posting| Camt053Entry {
+                amount_cents: posting.amount_cents,
+                booking_date: posting.posted_at.date(),
+                value_date: posting.value_date.unwrap_or(posting.posted_at.date()),
+                servicer_reference: max_text(posting.transaction_id.as_deref(), MAX_ID_LEN),
+                instruction_id: max_text(posting.transaction_ref.as_deref(), MAX_ID_LEN),
+                end_to_end_id: max_text(posting.end_to_end_id.as_deref(), MAX_ID_LEN),
+                uetr: posting
+                    .uetr
+                    .as_deref()
+                    .map(str::to_lowercase)
+                    .filter(|u| is_uetr(u)),
+                code: BankTransactionCode::of(&posting),
+                information: max_text(posting.description.as_deref(), MAX_INFO_LEN),
+            })
+            .collect();
+
+        let statement_id = format!("{}-{}", account.account_number, period.end.format("%Y%m%d"));
+
+        Self {
+            message_id: Uuid::now_v7().simple().to_string(),
+            created_at,
+            statement_id: statement_id.chars().take(MAX_ID_LEN).collect(),
+            iban: account.iban.clone(),
+            account_number: account.account_number.clone(),
+            currency: account.currency.clone(),
+            owner_name: account.holder_name().chars().take(MAX_NAME_LEN).collect(),
+            servicer_bic: servicer_bic.to_string(),
+            period,
+            opening_cents,
+            closing_cents,
+            entries,
+        }
+    }
+
+    pub fn to_xml(&self) -> io::Result<Vec<u8>> {
+        let mut xml = XmlWriter(Writer::new_with_indent(Vec::new(), b' ', 2));
+
+        xml.0
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        xml.0.write_event(Event::Start(
+            BytesStart::new("Document")
+                .with_attributes([("xmlns", NAMESPACE), ("xmlns:xsi", XSI_NAMESPACE)]),
+        ))?;
+        xml.start("BkToCstmrStmt")?;
+
+        let created_at = self.created_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        xml.start("GrpHdr")?;
+        xml.text("MsgId", &self.message_id)?;
+        xml.text("CreDtTm", &created_at)?;
+        xml.end("GrpHdr")?;
+
+        xml.start("Stmt")?;
+        xml.text("Id", &self.statement_id)?;
+        xml.text("CreDtTm", &created_at)?;
+        xml.start("FrToDt")?;
+        xml.text("FrDtTm", &format!("{}T00:00:00Z", self.period.start))?;
+        xml.text("ToDtTm", &format!("{}T23:59:59Z", self.period.end))?;
+        xml.end("FrToDt")?;
+
+        xml.start("Acct")?;
+        xml.start("Id")?;
+        if is_iban(&self.iban) {
+            xml.text("IBAN", &self.iban)?;
+        } else {
+            xml.start("Othr")?;
+            xml.text("Id", &self.account_number)?;
+            xml.end("Othr")?;
+        }
+        xml.end("Id")?;
+        xml.text("Ccy", &self.currency)?;
+        if !self.owner_name.is_empty() {
+            xml.start("Ownr")?;
+            xml.text("Nm", &self.owner_name)?;
+            xml.end("Ownr")?;
+        }
+        xml.start("Svcr")?;
+        xml.start("FinInstnId")?;
+        xml.text("BICFI", &self.servicer_bic)?;
+        xml.end("FinInstnId")?;
+        xml.end("Svcr")?;
+        xml.end("Acct")?;
+
+        xml.balance(
+            "OPBD",
+            self.opening_cents,
+            self.period.start,
+            &self.currency,
+        )?;
+        xml.balance("CLBD", self.closing_cents, self.period.end, &self.currency)?;
+        self.write_summary(&mut xml)?;
+
+        for entry in &self.entries {
+            xml.start("Ntry")?;
+            xml.amount(entry.amount_cents, &self.currency)?;
+            xml.text("CdtDbtInd", indicator(entry.amount_cents))?;
+            xml.start("Sts")?;
+            xml.text("Cd", "BOOK")?;
+            xml.end("Sts")?;
+            xml.date("BookgDt", entry.booking_date)?;
+            xml.date("ValDt", entry.value_date)?;
+            if let Some(reference) = &entry.servicer_reference {
+                xml.text("AcctSvcrRef", reference)?;
+            }
+            xml.start("BkTxCd")?;
+            xml.start("Domn")?;
+            xml.text("Cd", entry.code.domain)?;
+            xml.start("Fmly")?;
+            xml.text("Cd", entry.code.family)?;
+            xml.text("SubFmlyCd", entry.code.sub_family)?;
+            xml.end("Fmly")?;
+            xml.end("Domn")?;
+            xml.end("BkTxCd")?;
+
+            xml.start("NtryDtls")?;
+            xml.start("TxDtls")?;
+            xml.start("Refs")?;
+            if let Some(reference) = &entry.servicer_reference {
+                xml.text("AcctSvcrRef", reference)?;
+            }
+            if let Some(instruction_id) = &entry.instruction_id {
+                xml.text("InstrId", instruction_id)?;
+            }
+            xml.text(
+                "EndToEndId",
+                entry.end_to_end_id.as_deref().unwrap_or(NOT_PROVIDED),
+            )?;
+            if let Some(uetr) = &entry.uetr {
+                xml.text("UETR", uetr)?;
+            }
+            xml.end("Refs")?;
+            xml.amount(entry.amount_cents, &self.currency)?;
+            xml.text("CdtDbtInd", indicator(entry.amount_cents))?;
+            xml.end("TxDtls")?;
+            xml.end("NtryDtls")?;
+
+            if let Some(information) = &entry.information {
+                xml.text("AddtlNtryInf", information)?;
+            }
+            xml.end("Ntry")?;
+        }
+
+        xml.end("Stmt")?;
+        xml.end("BkToCstmrStmt")?;
+        xml.end("Document")?;
+
+        Ok(xml.0.into_inner())
+    }
+
+    fn write_summary(&self, xml: &mut XmlWriter) -> io::Result<()> {
+        let credits: Vec<i64> = self
+            .entries
+            .iter()
+            .map(|e| e.amount_cents)
+            .filter(|a| *a > 0)
+            .collect();
+        let debits: Vec<i64> = self
+            .entries
+            .iter()
+            .map(|e| -e.amount_cents)
+            .filter(|a| *a > 0)
+            .collect();
+        let net_cents = self.closing_cents - self.opening_cents;
+
+        xml.start("TxsSummry")?;
+        xml.start("TtlNtries")?;
+        xml.text("NbOfNtries", &self.entries.len().to_string())?;
+        xml.text(
+            "Sum",
+            &decimal_cents(credits.iter().sum::<i64>() + debits.iter().sum::<i64>()),
+        )?;
+        xml.start("TtlNetNtry")?;
+        xml.text("Amt", &decimal_cents(net_cents.abs()))?;
+        xml.text("CdtDbtInd", indicator(net_cents))?;
+        xml.end("TtlNetNtry")?;
+        xml.end("TtlNtries")?;
+        for (name, amounts) in [("TtlCdtNtries", &credits), ("TtlDbtNtries", &debits)] {
+            xml.start(name)?;
+            xml.text("NbOfNtries", &amounts.len().to_string())?;
+            xml.text("Sum", &decimal_cents(amounts.iter().sum()))?;
+            xml.end(name)?;
+        }
+        xml.end("TxsSummry")
+    }
+}
+
+// Zero balances are reported as credits
+fn indicator(signed_cents: i64) -> &'static str {
+    if signed_cents < 0 { "DBIT" } else { "CRDT" }
+}
+
+struct XmlWriter(Writer<Vec<u8>>);
+
+impl XmlWriter {
+    fn start(&mut self, name: &str) -> io::Result<()> {
+        self.0.write_event(Event::Start(BytesStart::new(name)))
+    }
+
+    fn end(&mut self, name: &str) -> io::Result<()> {
+        self.0.write_event(Event::End(BytesEnd::new(name)))
+    }
+
+    fn text(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.0
+            .create_element(name)
+            .write_text_content(BytesText::new(value))?;
+        Ok(())
+    }
+
+    fn amount(&mut self, signed_cents: i64, currency: &str) -> io::Result<()> {
+        self.0
+            .create_element("Amt")
+            .with_attribute(("Ccy", currency))
+            .write_text_content(BytesText::new(&decimal_cents(signed_cents.abs())))?;
+        Ok(())
+    }
+
+    fn date(&mut self, name: &str, date: NaiveDate) -> io::Result<()> {
+        self.start(name)?;
+        self.text("Dt", &date.to_string())?;
+        self.end(name)
+    }
+
+    fn balance(
+        &mut self,
+        code: &str,
+        signed_cents: i64,
+        date: NaiveDate,
+        currency: &str,
+    ) -> io::Result<()> {
+        self.start("Bal")?;
+        self.start("Tp")?;
+        self.start("CdOrPrtry")?;
+        self.text("Cd", code)?;
+        self.end("CdOrPrtry")?;
+        self.end("Tp")?;
+        self.amount(signed_cents, currency)?;
+        self.text("CdtDbtInd", indicator(signed_cents))?;
+        self.date("Dt", date)?;
+        self.end("Bal")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::xsd::assert_schema_valid;
+    use crate::nostro::models::{BankStatement, Direction};
+    use crate::staff::models::CoaType;
+    use claims::assert_ok;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, d).unwrap()
+    }
+
+    fn posting(day: u32, reference: &str, amount_cents: i64) -> CamtPostingEntity {
+        CamtPostingEntity {
+            journal_entry_id: Uuid::now_v7(),
+            transaction_id: Some(format!("THA{:011}", day)),
+            transaction_ref: Some(reference.into()),
+            description: Some(format!("Posting {} & co", reference)),
+            posted_at: date(day).and_hms_opt(9, 0, 0).unwrap(),
+            amount_cents,
+            contra_type: Some(CoaType::Asset),
+            scheme: None,
+            returned: false,
+            end_to_end_id: None,
+            uetr: None,
+            value_date: None,
+        }
+    }
+
+    fn statement(postings: Vec<CamtPostingEntity>) -> Camt053 {
+        let account = StatementAccountEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            account_number: "1000000001".into(),
+            iban: "US64THAL00001000000001".into(),
+            currency: "USD".into(),
+            first_name: Some("Acme".into()),
+            last_name: Some("Exports".into()),
+            email: "treasury@acme.example".into(),
+        };
+        let period = StatementPeriod {
+            start: date(1),
+            end: date(30),
+        };
+
+        Camt053::new(
+            &account,
+            period,
+            50_000,
+            postings,
+            "THALUS33XXX",
+            "2025-12-01T06:00:00Z".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn payments_get_their_scheme_codes_and_charges_their_own() {
+        let mut sepa = posting(3, "INV-1", -10_000);
+        sepa.scheme = Some("sepa".into());
+        let mut returned = posting(5, "INV-1-RT", 10_000);
+        returned.scheme = Some("sepa".into());
+        returned.returned = true;
+        let mut swift = posting(7, "PAY7781", 150_000);
+        swift.scheme = Some("swift".into());
+        let mut fee = posting(7, "THL1-FE", -2_500);
+        fee.contra_type = Some(CoaType::Income);
+
+        let codes: Vec<(&str, &str, &str)> = [sepa, returned, swift, fee, posting(9, "DEP", 100)]
+            .iter()
+            .map(BankTransactionCode::of)
+            .map(|c| (c.domain, c.family, c.sub_family))
+            .collect();
+
+        assert_eq!(
+            codes,
+            vec![
+                ("PMNT", "ICDT", "ESCT"),
+                ("PMNT", "ICDT", "RRTN"),
+                ("PMNT", "RCDT", "XBCT"),
+                ("ACMT", "MDOP", "CHRG"),
+                ("PMNT", "RCDT", "OTHR"),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_export_reads_back_as_a_balanced_statement() {
+        let mut swift = posting(7, "PAY7781", 150_000);
+        swift.end_to_end_id = Some("PAY7781".into());
+        swift.uetr = Some("8A562C67-CA16-48BA-B074-65581BE6F001".into());
+        swift.value_date = Some(date(6));
+
+        let camt = statement(vec![swift, posting(12, "CARD-1001", -30_000)]);
+        let xml = String::from_utf8(camt.to_xml().unwrap()).unwrap();
+
+        assert!(xml.contains("<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.08\""));
+        assert!(xml.contains("<IBAN>US64THAL00001000000001</IBAN>"));
+        assert!(xml.contains("<UETR>8a562c67-ca16-48ba-b074-65581be6f001</UETR>"));
+        assert!(xml.contains("<EndToEndId>NOTPROVIDED</EndToEndId>"));
+        assert!(xml.contains("<AddtlNtryInf>Posting CARD-1001 &amp; co</AddtlNtryInf>"));
+        assert!(xml.contains("<NbOfNtries>2</NbOfNtries>"));
+
+        let order = [
+            "<GrpHdr>",
+            "<Stmt>",
+            "<FrToDt>",
+            "<Acct>",
+            "<Ownr>",
+            "<Svcr>",
+            "<Bal>",
+            "<TxsSummry>",
+            "<Ntry>",
+            "<Sts>",
+            "<BookgDt>",
+            "<ValDt>",
+            "<AcctSvcrRef>",
+            "<BkTxCd>",
+            "<NtryDtls>",
+            "<Refs>",
+            "<InstrId>",
+            "<EndToEndId>",
+            "<UETR>",
+            "<AddtlNtryInf>",
+        ];
+        let positions: Vec<usize> = order.iter().map(|tag| xml.find(tag).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        let parsed = assert_ok!(BankStatement::parse(&xml));
+        assert_eq!(parsed.opening_cents, 50_000);
+        assert_eq!(parsed.closing_cents, 170_000);
+        assert_eq!(parsed.entries[0].value_date, date(6));
+        assert_eq!(parsed.entries[0].booking_date, Some(date(7)));
+        assert_eq!(parsed.entries[1].direction, Direction::Debit);
+        assert_eq!(parsed.entries[1].reference.as_deref(), Some("CARD-1001"));
+    }
+
+    #[test]
+    #[ignore = "needs tests/fixtures/iso20022/camt.053.001.08.xsd"]
+    fn the_export_is_valid_against_the_official_schema() {
+        let mut swift = posting(7, "PAY7781", 150_000);
+        swift.scheme = Some("swift".into());
+        swift.end_to_end_id = Some("PAY7781".into());
+        swift.uetr = Some("8a562c67-ca16-48ba-b074-65581be6f001".into());
+        swift.value_date = Some(date(6));
+        let mut fee = posting(7, "THL1-FE", -2_500);
+        fee.contra_type = Some(CoaType::Income);
+
+        let camt = statement(vec![swift, fee, posting(12, "CARD-1001", -30_000)]);
+        assert_schema_valid("camt.053.001.08.xsd", &camt.to_xml().unwrap());
+
+        let empty = statement(vec![]);
+        assert_schema_valid("camt.053.001.08.xsd", &empty.to_xml().unwrap());
+    }
+}
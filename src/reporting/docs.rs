@@ -0,0 +1,23 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::reporting::routes::list_account_statements,
+    crate::reporting::routes::download_account_statement,
+    crate::reporting::routes::export_account_camt053,
+))]
+pub struct StatementApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::reporting::routes::run_account_statements,
+    crate::reporting::routes::staff_export_account_camt053,
+))]
+pub struct StatementRunApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::reporting::routes::balance_sheet,
+    crate::reporting::routes::income_statement,
+))]
+pub struct FinancialsApi;
@@ -0,0 +1,614 @@
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::base::error::{DomainError, ValidationError};
+use crate::reporting::models::{CoaBalanceEntity, decimal_cents};
+use crate::staff::models::CoaType;
+use crate::swift::models::{FxRateEntity, RATE_SCALE};
+
+// Chart codes are positional, 1411 sits under 1410 under 1400. The first two digits name the
+// group and are never cleared.
+const GROUP_DIGITS: usize = 2;
+
+// The days a report column covers, from the start of the ledger when there's no start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportColumn {
+    pub from: Option<NaiveDate>,
+    pub to: NaiveDate,
+}
+
+impl ReportColumn {
+    pub fn from_time(&self) -> Option<NaiveDateTime> {
+        self.from.and_then(|d| d.and_hms_opt(0, 0, 0))
+    }
+
+    // Exclusive
+    pub fn to_time(&self) -> NaiveDateTime {
+        self.to
+            .succ_opt()
+            .unwrap_or(self.to)
+            .and_time(Default::default())
+    }
+
+    // The same days a year earlier
+    fn year_before(&self) -> Self {
+        let back = |d: NaiveDate| d.checked_sub_months(Months::new(12)).unwrap_or(d);
+        Self {
+            from: self.from.map(back),
+            to: back(self.to),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.from {
+            Some(from) => format!("{} to {}", from, self.to),
+            None => self.to.to_string(),
+        }
+    }
+}
+
+// Today when no day is given, against the same day the year before
+pub fn parse_balance_sheet_dates(
+    as_of: Option<NaiveDate>,
+    compare_as_of: Option<NaiveDate>,
+    today: NaiveDate,
+) -> (ReportColumn, ReportColumn) {
+    let current = ReportColumn {
+        from: None,
+        to: as_of.unwrap_or(today),
+    };
+    let comparative = match compare_as_of {
+        Some(to) => ReportColumn { from: None, to },
+        None => current.year_before(),
+    };
+
+    (current, comparative)
+}
+
+// The year to date when no days are given, against the same days the year before
+pub fn parse_income_statement_dates(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    compare_from: Option<NaiveDate>,
+    compare_to: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<(ReportColumn, ReportColumn), ValidationError> {
+    let to = to.unwrap_or(today);
+    let from = from.unwrap_or(to.with_ordinal(1).unwrap_or(to));
+    let current = ReportColumn {
+        from: Some(from),
+        to,
+    };
+
+    let comparative = match (compare_from, compare_to) {
+        (None, None) => current.year_before(),
+        (Some(from), Some(to)) => ReportColumn {
+            from: Some(from),
+            to,
+        },
+        _ => {
+            return Err(ValidationError::InvalidValue {
+                field: "compare_from".into(),
+                reason: "Give both compare_from and compare_to or neither".into(),
+            });
+        }
+    };
+
+    for (field, column) in [("from", current), ("compare_from", comparative)] {
+        if column.from > Some(column.to) {
+            return Err(ValidationError::InvalidValue {
+                field: field.into(),
+                reason: "Must not be after the end of the period".into(),
+            });
+        }
+    }
+
+    Ok((current, comparative))
+}
+
+// Rates as kept for SWIFT transfers, looked up either way round
+#[derive(Debug)]
+pub struct FxTable {
+    base_currency: String,
+    rates: HashMap<(String, String), i64>,
+}
+
+impl FxTable {
+    pub fn new(base_currency: &str, rates: Vec<FxRateEntity>) -> Self {
+        Self {
+            base_currency: base_currency.to_string(),
+            rates: rates
+                .into_iter()
+                .map(|r| ((r.base_currency, r.quote_currency), r.rate_micros))
+                .collect(),
+        }
+    }
+
+    pub fn base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    // Today's rate, the ledger doesn't keep the rate of the day a line was posted
+    pub fn to_base(&self, currency: &str, cents: i64) -> Result<i64, DomainError> {
+        let base = &self.base_currency;
+        if currency == base {
+            return Ok(cents);
+        }
+
+        let direct = self.rates.get(&(base.clone(), currency.to_string()));
+        let inverse = self.rates.get(&(currency.to_string(), base.clone()));
+        let converted = match (direct, inverse) {
+            (Some(rate), _) => div_round(cents as i128 * RATE_SCALE as i128, *rate as i128),
+            (None, Some(rate)) => div_round(cents as i128 * *rate as i128, RATE_SCALE as i128),
+            (None, None) => Err(DomainError::ConstraintViolation(format!(
+                "no exchange rate between {} and {}",
+                currency, base
+            )))?,
+        };
+
+        Ok(converted)
+    }
+}
+
+// Halves away from zero
+fn div_round(numerator: i128, denominator: i128) -> i64 {
+    let half = denominator / 2;
+    let rounded = if numerator < 0 {
+        (numerator - half) / denominator
+    } else {
+        (numerator + half) / denominator
+    };
+
+    rounded as i64
+}
+
+// Assets and expenses grow with debits, the rest with credits
+fn normal_sign(coa_type: CoaType) -> i64 {
+    match coa_type {
+        CoaType::Asset | CoaType::Expense | CoaType::Memoranda => 1,
+        CoaType::Liability | CoaType::Equity | CoaType::Income => -1,
+    }
+}
+
+// A chart account's balance in the base currency, positive on its normal side
+#[derive(Debug, Clone)]
+pub struct CoaBalance {
+    pub code: String,
+    pub name: String,
+    pub coa_type: CoaType,
+    pub cents: i64,
+}
+
+// One per chart account, whatever currencies its lines were posted in
+pub fn base_balances(
+    rows: Vec<CoaBalanceEntity>,
+    fx: &FxTable,
+) -> Result<Vec<CoaBalance>, DomainError> {
+    let mut balances: BTreeMap<String, CoaBalance> = BTreeMap::new();
+
+    for row in rows {
+        let cents = fx.to_base(row.currency.trim(), row.debit_cents)? * normal_sign(row.coa_type);
+        balances
+            .entry(row.code.clone())
+            .or_insert(CoaBalance {
+                code: row.code,
+                name: row.name,
+                coa_type: row.coa_type,
+                cents: 0,
+            })
+            .cents += cents;
+    }
+
+    Ok(balances.into_values().collect())
+}
+
+fn total(balances: &[CoaBalance], coa_type: CoaType) -> i64 {
+    balances
+        .iter()
+        .filter(|b| b.coa_type == coa_type)
+        .map(|b| b.cents)
+        .sum()
+}
+
+// The closest account above a code that is in the chart
+fn parent_code(code: &str, codes: &[&str]) -> Option<String> {
+    let mut digits: Vec<char> = code.chars().collect();
+
+    loop {
+        let last = digits.iter().rposition(|c| *c != '0')?;
+        if last < GROUP_DIGITS {
+            return None;
+        }
+        digits[last] = '0';
+
+        let parent: String = digits.iter().collect();
+        if codes.contains(&parent.as_str()) {
+            return Some(parent);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportLine {
+    // None for lines that are worked out rather than posted to, like current earnings
+    pub code: Option<String>,
+    pub name: String,
+    pub depth: usize,
+    // With everything below it
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportSection {
+    pub name: &'static str,
+    pub lines: Vec<ReportLine>,
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+impl ReportSection {
+    // Accounts in code order so each is listed under its parent, leaving out the ones with
+    // nothing in either column
+    pub fn build(
+        name: &'static str,
+        coa_type: CoaType,
+        current: &[CoaBalance],
+        comparative: &[CoaBalance],
+    ) -> Self {
+        let mut lines: BTreeMap<&str, ReportLine> = BTreeMap::new();
+        for balance in current.iter().chain(comparative) {
+            if balance.coa_type == coa_type {
+                lines.entry(&balance.code).or_insert(ReportLine {
+                    code: Some(balance.code.clone()),
+                    name: balance.name.clone(),
+                    depth: 0,
+                    current_cents: 0,
+                    comparative_cents: 0,
+                });
+            }
+        }
+
+        let codes: Vec<&str> = lines.keys().copied().collect();
+        let mut ancestors: HashMap<&str, Vec<String>> = HashMap::new();
+        for code in &codes {
+            let mut chain = Vec::new();
+            let mut at = code.to_string();
+            while let Some(parent) = parent_code(&at, &codes) {
+                chain.push(parent.clone());
+                at = parent;
+            }
+            ancestors.insert(*code, chain);
+        }
+
+        for (column, balances) in [(0, current), (1, comparative)] {
+            for balance in balances.iter().filter(|b| b.coa_type == coa_type) {
+                let code = balance.code.as_str();
+                for at in std::iter::once(code).chain(ancestors[code].iter().map(String::as_str)) {
+                    if let Some(line) = lines.get_mut(at) {
+                        match column {
+                            0 => line.current_cents += balance.cents,
+                            _ => line.comparative_cents += balance.cents,
+                        }
+                    }
+                }
+            }
+        }
+
+        let lines = lines
+            .into_iter()
+            .map(|(code, line)| ReportLine {
+                depth: ancestors[code].len(),
+                ..line
+            })
+            .filter(|l| l.current_cents != 0 || l.comparative_cents != 0)
+            .collect();
+
+        Self {
+            name,
+            lines,
+            current_cents: total(current, coa_type),
+            comparative_cents: total(comparative, coa_type),
+        }
+    }
+
+    fn push_line(&mut self, name: &str, current_cents: i64, comparative_cents: i64) {
+        self.lines.push(ReportLine {
+            code: None,
+            name: name.to_string(),
+            depth: 0,
+            current_cents,
+            comparative_cents,
+        });
+        self.current_cents += current_cents;
+        self.comparative_cents += comparative_cents;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportTotal {
+    pub name: &'static str,
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FinancialStatement {
+    pub title: &'static str,
+    pub currency: String,
+    pub current: ReportColumn,
+    pub comparative: ReportColumn,
+    pub sections: Vec<ReportSection>,
+    pub totals: Vec<ReportTotal>,
+}
+
+impl FinancialStatement {
+    // Income and expenses not yet closed to retained earnings show as current earnings, so
+    // assets come to liabilities and equity
+    pub fn balance_sheet(
+        currency: &str,
+        current: ReportColumn,
+        comparative: ReportColumn,
+        current_balances: &[CoaBalance],
+        comparative_balances: &[CoaBalance],
+    ) -> Self {
+        let earnings = |b: &[CoaBalance]| total(b, CoaType::Income) - total(b, CoaType::Expense);
+        let section = |name, coa_type| {
+            ReportSection::build(name, coa_type, current_balances, comparative_balances)
+        };
+
+        let assets = section("Assets", CoaType::Asset);
+        let liabilities = section("Liabilities", CoaType::Liability);
+        let mut equity = section("Equity", CoaType::Equity);
+        equity.push_line(
+            "Current earnings",
+            earnings(current_balances),
+            earnings(comparative_balances),
+        );
+
+        let totals = vec![
+            ReportTotal {
+                name: "Total assets",
+                current_cents: assets.current_cents,
+                comparative_cents: assets.comparative_cents,
+            },
+            ReportTotal {
+                name: "Total liabilities and equity",
+                current_cents: liabilities.current_cents + equity.current_cents,
+                comparative_cents: liabilities.comparative_cents + equity.comparative_cents,
+            },
+        ];
+
+        Self {
+            title: "Balance sheet",
+            currency: currency.to_string(),
+            current,
+            comparative,
+            sections: vec![assets, liabilities, equity],
+            totals,
+        }
+    }
+
+    pub fn income_statement(
+        currency: &str,
+        current: ReportColumn,
+        comparative: ReportColumn,
+        current_balances: &[CoaBalance],
+        comparative_balances: &[CoaBalance],
+    ) -> Self {
+        let section = |name, coa_type| {
+            ReportSection::build(name, coa_type, current_balances, comparative_balances)
+        };
+
+        let income = section("Income", CoaType::Income);
+        let expenses = section("Expenses", CoaType::Expense);
+        let totals = vec![ReportTotal {
+            name: "Net income",
+            current_cents: income.current_cents - expenses.current_cents,
+            comparative_cents: income.comparative_cents - expenses.comparative_cents,
+        }];
+
+        Self {
+            title: "Income statement",
+            currency: currency.to_string(),
+            current,
+            comparative,
+            sections: vec![income, expenses],
+            totals,
+        }
+    }
+
+    // Section totals follow their lines, names are indented to show the hierarchy
+    pub fn to_csv(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        writer.write_record([
+            "section",
+            "code",
+            "name",
+            &format!("{} ({})", self.current.label(), self.currency),
+            &format!("{} ({})", self.comparative.label(), self.currency),
+        ])?;
+        for section in &self.sections {
+            for line in &section.lines {
+                writer.write_record([
+                    section.name,
+                    line.code.as_deref().unwrap_or_default(),
+                    &format!("{}{}", "  ".repeat(line.depth), line.name),
+                    &decimal_cents(line.current_cents),
+                    &decimal_cents(line.comparative_cents),
+                ])?;
+            }
+            writer.write_record([
+                section.name,
+                "",
+                &format!("Total {}", section.name.to_lowercase()),
+                &decimal_cents(section.current_cents),
+                &decimal_cents(section.comparative_cents),
+            ])?;
+        }
+        for total in &self.totals {
+            writer.write_record([
+                "",
+                "",
+                total.name,
+                &decimal_cents(total.current_cents),
+                &decimal_cents(total.comparative_cents),
+            ])?;
+        }
+
+        let csv = writer.into_inner()?;
+        Ok(csv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+
+    use super::*;
+    use crate::reporting::models::CoaBalanceEntity;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn row(code: &str, coa_type: CoaType, currency: &str, debit_cents: i64) -> CoaBalanceEntity {
+        CoaBalanceEntity {
+            code: code.into(),
+            name: format!("Account {}", code),
+            coa_type,
+            currency: currency.into(),
+            debit_cents,
+        }
+    }
+
+    fn fx() -> FxTable {
+        let rate = |base: &str, quote: &str, rate_micros| FxRateEntity {
+            base_currency: base.into(),
+            quote_currency: quote.into(),
+            rate_micros,
+            updated_by: None,
+            updated_at: chrono::Utc::now(),
+        };
+        FxTable::new(
+            "USD",
+            vec![rate("USD", "EUR", 800_000), rate("GBP", "USD", 1_250_000)],
+        )
+    }
+
+    #[test]
+    fn foreign_balances_are_converted_either_way_round() {
+        let fx = fx();
+
+        assert_eq!(fx.to_base("USD", 1_000).unwrap(), 1_000);
+        assert_eq!(fx.to_base("EUR", 1_000).unwrap(), 1_250);
+        assert_eq!(fx.to_base("EUR", -1).unwrap(), -1);
+        assert_eq!(fx.to_base("GBP", 1_000).unwrap(), 1_250);
+        assert_err!(fx.to_base("JPY", 1_000));
+    }
+
+    #[test]
+    fn accounts_roll_up_into_the_ones_above_them() {
+        let balances = assert_ok!(base_balances(
+            vec![
+                row("1000", CoaType::Asset, "USD", 0),
+                row("1030", CoaType::Asset, "USD", 50_000),
+                row("1400", CoaType::Asset, "USD", 0),
+                row("1410", CoaType::Asset, "USD", 0),
+                row("1411", CoaType::Asset, "USD", 100_000),
+                row("1411", CoaType::Asset, "EUR", 8_000),
+                row("1420", CoaType::Asset, "USD", 0),
+                row("2010", CoaType::Liability, "USD", -160_000),
+            ],
+            &fx(),
+        ));
+        let empty: Vec<CoaBalance> = Vec::new();
+
+        let assets = ReportSection::build("Assets", CoaType::Asset, &balances, &empty);
+        let lines: Vec<(&str, usize, i64)> = assets
+            .lines
+            .iter()
+            .map(|l| (l.code.as_deref().unwrap(), l.depth, l.current_cents))
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                ("1000", 0, 50_000),
+                ("1030", 1, 50_000),
+                ("1400", 0, 110_000),
+                ("1410", 1, 110_000),
+                ("1411", 2, 110_000),
+            ]
+        );
+        assert_eq!(assets.current_cents, 160_000);
+
+        let liabilities =
+            ReportSection::build("Liabilities", CoaType::Liability, &balances, &empty);
+        assert_eq!(liabilities.current_cents, 160_000);
+    }
+
+    #[test]
+    fn unclosed_earnings_balance_the_balance_sheet() {
+        let balances = assert_ok!(base_balances(
+            vec![
+                row("1040", CoaType::Asset, "USD", 100_000),
+                row("2010", CoaType::Liability, "USD", -97_500),
+                row("4120", CoaType::Income, "USD", -3_000),
+                row("5010", CoaType::Expense, "USD", 500),
+            ],
+            &fx(),
+        ));
+        let (current, comparative) = parse_balance_sheet_dates(None, None, date(2025, 12, 31));
+        assert_eq!(comparative.to, date(2024, 12, 31));
+
+        let sheet = FinancialStatement::balance_sheet("USD", current, comparative, &balances, &[]);
+
+        let equity = &sheet.sections[2];
+        assert_eq!(equity.lines[0].name, "Current earnings");
+        assert_eq!(equity.current_cents, 2_500);
+        assert_eq!(sheet.totals[0].current_cents, 100_000);
+        assert_eq!(sheet.totals[1].current_cents, 100_000);
+        assert_eq!(sheet.totals[1].comparative_cents, 0);
+
+        let statement =
+            FinancialStatement::income_statement("USD", current, comparative, &balances, &[]);
+        assert_eq!(statement.totals[0].current_cents, 2_500);
+
+        let csv = String::from_utf8(assert_ok!(statement.to_csv())).unwrap();
+        assert!(csv.starts_with("section,code,name,2025-12-31 (USD),2024-12-31 (USD)\n"));
+        assert!(csv.contains("Income,4120,Account 4120,30.00,0.00\n"));
+        assert!(csv.contains(",,Net income,25.00,0.00\n"));
+    }
+
+    #[test]
+    fn income_statements_cover_the_year_to_date_by_default() {
+        let today = date(2025, 12, 18);
+
+        let (current, comparative) =
+            assert_ok!(parse_income_statement_dates(None, None, None, None, today));
+        assert_eq!(current.from, Some(date(2025, 1, 1)));
+        assert_eq!(current.to, today);
+        assert_eq!(comparative.from, Some(date(2024, 1, 1)));
+        assert_eq!(
+            current.to_time(),
+            date(2025, 12, 19).and_hms_opt(0, 0, 0).unwrap()
+        );
+
+        assert_err!(parse_income_statement_dates(
+            Some(date(2025, 12, 19)),
+            None,
+            None,
+            None,
+            today
+        ));
+        assert_err!(parse_income_statement_dates(
+            None,
+            None,
+            Some(date(2024, 1, 1)),
+            None,
+            today
+        ));
+    }
+}
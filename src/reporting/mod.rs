@@ -1 +1,9 @@
-
+pub mod camt053;
+pub mod docs;
+pub mod financials;
+pub mod models;
+pub mod pdf;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,591 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, Utc};
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::base::error::ValidationError;
+use crate::product::models::StatementFrequency;
+use crate::staff::models::CoaType;
+
+// Customer links to a statement stop working after fifteen minutes
+pub const STATEMENT_URL_TTL_SECS: u64 = 900;
+// Overdraft interest is booked against an income account like a fee, its reference tells them
+// apart. See overdraft::models::interest_reference.
+const OVERDRAFT_INTEREST_PREFIX: &str = "ODI-";
+// What fits the description column of the printed statement
+const DESCRIPTION_COLUMN: usize = 29;
+const MAX_EXPORT_DAYS: i64 = 366;
+
+// How an amount reads on a statement, 1,250.05 or -1,250.05
+pub fn format_cents(cents: i64) -> String {
+    let units = (cents.abs() / 100).to_string();
+    let mut grouped = String::new();
+    for (i, c) in units.chars().enumerate() {
+        if i > 0 && (units.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, grouped, cents.abs() % 100)
+}
+
+// How an amount is written to a CSV, -1250.05
+pub fn decimal_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+// Statements run for months that are over, the last one when none is asked for
+pub fn parse_period_end(
+    requested: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<NaiveDate, ValidationError> {
+    let first_of_month = today.with_day(1).unwrap_or(today);
+
+    let period_end = match requested {
+        Some(date) => date,
+        None => return Ok(first_of_month - Days::new(1)),
+    };
+
+    if (period_end + Days::new(1)).day() != 1 {
+        return Err(ValidationError::InvalidValue {
+            field: "period_end".into(),
+            reason: "Statements end on the last day of a month".into(),
+        });
+    }
+    if period_end >= today {
+        return Err(ValidationError::InvalidValue {
+            field: "period_end".into(),
+            reason: "The month has not ended yet".into(),
+        });
+    }
+
+    Ok(period_end)
+}
+
+// The days a camt.053 export covers, this month so far when none are given
+pub fn parse_export_range(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<StatementPeriod, ValidationError> {
+    let end = to.unwrap_or(today);
+    let start = from.unwrap_or(end.with_day(1).unwrap_or(end));
+
+    if start > end {
+        return Err(ValidationError::InvalidValue {
+            field: "from".into(),
+            reason: "Must not be after to".into(),
+        });
+    }
+    if end > today {
+        return Err(ValidationError::InvalidValue {
+            field: "to".into(),
+            reason: "Must not be in the future".into(),
+        });
+    }
+    if (end - start).num_days() > MAX_EXPORT_DAYS {
+        return Err(ValidationError::InvalidValue {
+            field: "from".into(),
+            reason: "An export covers a year at most".into(),
+        });
+    }
+
+    Ok(StatementPeriod { start, end })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementPeriod {
+    pub start: NaiveDate,
+    // Inclusive
+    pub end: NaiveDate,
+}
+
+impl StatementPeriod {
+    // The period of a statement with this frequency ending on month_end, None when no such
+    // statement ends then. Quarters and years are calendar ones.
+    pub fn ending(frequency: StatementFrequency, month_end: NaiveDate) -> Option<Self> {
+        let months = match frequency {
+            StatementFrequency::Monthly => 1,
+            StatementFrequency::Quarterly if month_end.month().is_multiple_of(3) => 3,
+            StatementFrequency::Annual if month_end.month() == 12 => 12,
+            _ => return None,
+        };
+
+        let start = month_end
+            .with_day(1)?
+            .checked_sub_months(Months::new(months - 1))?;
+
+        Some(Self {
+            start,
+            end: month_end,
+        })
+    }
+
+    // Journal entries are stamped in UTC without a zone
+    pub fn start_time(&self) -> NaiveDateTime {
+        self.start.and_time(chrono::NaiveTime::MIN)
+    }
+
+    pub fn end_time(&self) -> NaiveDateTime {
+        (self.end + Days::new(1)).and_time(chrono::NaiveTime::MIN)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum PostingCategory {
+    Transaction,
+    Interest,
+    Fee,
+}
+
+impl PostingCategory {
+    // Told apart by what the other side of the entry was booked to, interest paid to the
+    // customer is an expense, fees and overdraft interest are income
+    pub fn of(transaction_ref: Option<&str>, contra: Option<CoaType>) -> Self {
+        if transaction_ref.is_some_and(|r| r.starts_with(OVERDRAFT_INTEREST_PREFIX)) {
+            return PostingCategory::Interest;
+        }
+
+        match contra {
+            Some(CoaType::Expense) => PostingCategory::Interest,
+            Some(CoaType::Income) => PostingCategory::Fee,
+            _ => PostingCategory::Transaction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, utoipa::ToSchema, serde::Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StatementFormat {
+    Pdf,
+    Csv,
+    Html,
+}
+
+impl StatementFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatementFormat::Pdf => "pdf",
+            StatementFormat::Csv => "csv",
+            StatementFormat::Html => "html",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            StatementFormat::Pdf => "application/pdf",
+            StatementFormat::Csv => "text/csv",
+            StatementFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+impl FromStr for StatementFormat {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "pdf" => Ok(StatementFormat::Pdf),
+            "csv" => Ok(StatementFormat::Csv),
+            "html" => Ok(StatementFormat::Html),
+            _ => Err(ValidationError::InvalidValue {
+                field: "format".into(),
+                reason: "Statements come as pdf, csv or html".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, utoipa::ToSchema, serde::Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(ValidationError::InvalidValue {
+                field: "format".into(),
+                reason: "Reports come as json or csv".into(),
+            }),
+        }
+    }
+}
+
+// The currency financial statements are drawn up in
+#[derive(Debug, Clone)]
+pub struct ReportingProfile {
+    pub base_currency: String,
+}
+
+impl ReportingProfile {
+    pub fn new(base_currency: &str) -> Self {
+        Self {
+            base_currency: base_currency.trim().to_uppercase(),
+        }
+    }
+}
+
+// What a chart account's lines in one currency come to, debits less credits
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CoaBalanceEntity {
+    pub code: String,
+    pub name: String,
+    pub coa_type: CoaType,
+    pub currency: String,
+    pub debit_cents: i64,
+}
+
+// An account a statement is due for, with who it goes to
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StatementAccountEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_number: String,
+    pub iban: String,
+    pub currency: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: String,
+}
+
+impl StatementAccountEntity {
+    pub fn holder_name(&self) -> String {
+        [self.first_name.as_deref(), self.last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// The account's side of a journal entry, credits positive, with what the other side went to
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PostingEntity {
+    pub journal_entry_id: Uuid,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    pub posted_at: NaiveDateTime,
+    pub amount_cents: i64,
+    pub contra_type: Option<CoaType>,
+}
+
+// A posting with what the payment it came from tells about it, for camt.053
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CamtPostingEntity {
+    pub journal_entry_id: Uuid,
+    pub transaction_id: Option<String>,
+    pub transaction_ref: Option<String>,
+    pub description: Option<String>,
+    pub posted_at: NaiveDateTime,
+    pub amount_cents: i64,
+    pub contra_type: Option<CoaType>,
+    // sepa, ach or swift when the entry belongs to a payment of that scheme
+    pub scheme: Option<String>,
+    // A payment sent back to the account
+    pub returned: bool,
+    pub end_to_end_id: Option<String>,
+    pub uetr: Option<String>,
+    pub value_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementLine {
+    pub date: NaiveDate,
+    pub reference: String,
+    pub description: String,
+    pub category: PostingCategory,
+    pub amount_cents: i64,
+    // After this line
+    pub balance_cents: i64,
+}
+
+impl StatementLine {
+    pub fn money_in(&self) -> String {
+        match self.amount_cents > 0 {
+            true => format_cents(self.amount_cents),
+            false => String::new(),
+        }
+    }
+
+    pub fn money_out(&self) -> String {
+        match self.amount_cents < 0 {
+            true => format_cents(-self.amount_cents),
+            false => String::new(),
+        }
+    }
+
+    pub fn balance(&self) -> String {
+        format_cents(self.balance_cents)
+    }
+
+    pub fn short_description(&self) -> String {
+        self.description.chars().take(DESCRIPTION_COLUMN).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub account: StatementAccountEntity,
+    pub period: StatementPeriod,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub credits_cents: i64,
+    pub debits_cents: i64,
+    pub interest_cents: i64,
+    pub fees_cents: i64,
+    pub lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    // Postings in the order they were made, each line carrying the balance it left
+    pub fn build(
+        account: StatementAccountEntity,
+        period: StatementPeriod,
+        opening_cents: i64,
+        postings: Vec<PostingEntity>,
+    ) -> Self {
+        let mut statement = Self {
+            account,
+            period,
+            opening_cents,
+            closing_cents: opening_cents,
+            credits_cents: 0,
+            debits_cents: 0,
+            interest_cents: 0,
+            fees_cents: 0,
+            lines: Vec::with_capacity(postings.len()),
+        };
+
+        for posting in postings {
+            let category =
+                PostingCategory::of(posting.transaction_ref.as_deref(), posting.contra_type);
+
+            statement.closing_cents += posting.amount_cents;
+            match posting.amount_cents > 0 {
+                true => statement.credits_cents += posting.amount_cents,
+                false => statement.debits_cents -= posting.amount_cents,
+            }
+            match category {
+                PostingCategory::Interest => statement.interest_cents += posting.amount_cents,
+                PostingCategory::Fee => statement.fees_cents += posting.amount_cents,
+                PostingCategory::Transaction => {}
+            }
+
+            statement.lines.push(StatementLine {
+                date: posting.posted_at.date(),
+                reference: posting.transaction_ref.unwrap_or_default(),
+                description: posting.description.unwrap_or_default(),
+                category,
+                amount_cents: posting.amount_cents,
+                balance_cents: statement.closing_cents,
+            });
+        }
+
+        statement
+    }
+
+    pub fn opening(&self) -> String {
+        format_cents(self.opening_cents)
+    }
+
+    pub fn closing(&self) -> String {
+        format_cents(self.closing_cents)
+    }
+
+    pub fn credits(&self) -> String {
+        format_cents(self.credits_cents)
+    }
+
+    pub fn debits(&self) -> String {
+        format_cents(self.debits_cents)
+    }
+
+    pub fn interest(&self) -> String {
+        format_cents(self.interest_cents)
+    }
+
+    pub fn fees(&self) -> String {
+        format_cents(self.fees_cents)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AccountStatementEntity {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub user_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub currency: String,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub credits_cents: i64,
+    pub debits_cents: i64,
+    pub interest_cents: i64,
+    pub fees_cents: i64,
+    pub entry_count: i32,
+    pub html_key: String,
+    pub pdf_key: String,
+    pub csv_key: String,
+    pub emailed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccountStatementEntity {
+    pub fn new(statement: &Statement) -> Self {
+        let key = |format: StatementFormat| {
+            format!(
+                "statements/{}/{}.{}",
+                statement.account.id,
+                statement.period.end.format("%Y-%m"),
+                format.extension()
+            )
+        };
+
+        Self {
+            id: Uuid::now_v7(),
+            account_id: statement.account.id,
+            user_id: statement.account.user_id,
+            period_start: statement.period.start,
+            period_end: statement.period.end,
+            currency: statement.account.currency.clone(),
+            opening_cents: statement.opening_cents,
+            closing_cents: statement.closing_cents,
+            credits_cents: statement.credits_cents,
+            debits_cents: statement.debits_cents,
+            interest_cents: statement.interest_cents,
+            fees_cents: statement.fees_cents,
+            entry_count: statement.lines.len() as i32,
+            html_key: key(StatementFormat::Html),
+            pdf_key: key(StatementFormat::Pdf),
+            csv_key: key(StatementFormat::Csv),
+            emailed_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn object_key(&self, format: StatementFormat) -> &str {
+        match format {
+            StatementFormat::Pdf => &self.pdf_key,
+            StatementFormat::Csv => &self.csv_key,
+            StatementFormat::Html => &self.html_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_none, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        PostingCategory, PostingEntity, Statement, StatementAccountEntity, StatementPeriod,
+        decimal_cents, format_cents, parse_export_range, parse_period_end,
+    };
+    use crate::product::models::StatementFrequency;
+    use crate::staff::models::CoaType;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn posting(day: u32, reference: &str, amount_cents: i64, contra: CoaType) -> PostingEntity {
+        PostingEntity {
+            journal_entry_id: Uuid::now_v7(),
+            transaction_ref: Some(reference.into()),
+            description: Some(format!("Posting {}", reference)),
+            posted_at: date(2025, 11, day).and_hms_opt(10, 30, 0).unwrap(),
+            amount_cents,
+            contra_type: Some(contra),
+        }
+    }
+
+    #[test]
+    fn periods_end_on_month_ends_that_are_over() {
+        let today = date(2025, 12, 3);
+
+        assert_eq!(
+            assert_ok!(parse_period_end(None, today)),
+            date(2025, 11, 30)
+        );
+        assert_ok!(parse_period_end(Some(date(2024, 2, 29)), today));
+        assert_err!(parse_period_end(Some(date(2025, 11, 29)), today));
+        assert_err!(parse_period_end(Some(date(2025, 12, 31)), today));
+
+        let quarter = StatementPeriod::ending(StatementFrequency::Quarterly, date(2025, 9, 30));
+        assert_eq!(quarter.unwrap().start, date(2025, 7, 1));
+        assert_none!(StatementPeriod::ending(
+            StatementFrequency::Quarterly,
+            date(2025, 11, 30)
+        ));
+        let year = StatementPeriod::ending(StatementFrequency::Annual, date(2025, 12, 31));
+        assert_eq!(year.unwrap().start, date(2025, 1, 1));
+
+        let month_so_far = assert_ok!(parse_export_range(None, None, today));
+        assert_eq!(month_so_far.start, date(2025, 12, 1));
+        assert_err!(parse_export_range(
+            Some(date(2025, 12, 2)),
+            Some(date(2025, 12, 1)),
+            today
+        ));
+        assert_err!(parse_export_range(None, Some(date(2025, 12, 4)), today));
+        assert_err!(parse_export_range(Some(date(2024, 11, 1)), None, today));
+    }
+
+    #[test]
+    fn postings_carry_running_balances_and_are_summed_by_category() {
+        let account = StatementAccountEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            account_number: "1000000001".into(),
+            iban: "US00THAL1000000001".into(),
+            currency: "USD".into(),
+            first_name: Some("Siobhan".into()),
+            last_name: None,
+            email: "siobhan@example.com".into(),
+        };
+        let period = StatementPeriod::ending(StatementFrequency::Monthly, date(2025, 11, 30));
+
+        let statement = Statement::build(
+            account,
+            period.unwrap(),
+            10_000,
+            vec![
+                posting(3, "DEP-1", 150_000, CoaType::Asset),
+                posting(9, "CHQ-1002", -200_000, CoaType::Asset),
+                posting(9, "CHQ-1002-UOF", -2_500, CoaType::Income),
+                posting(30, "ODI-1000000001-202511", -1_234, CoaType::Income),
+            ],
+        );
+
+        assert_eq!(statement.account.holder_name(), "Siobhan");
+        assert_eq!(statement.credits_cents, 150_000);
+        assert_eq!(statement.debits_cents, 203_734);
+        assert_eq!(statement.closing_cents, -43_734);
+        assert_eq!(statement.fees_cents, -2_500);
+        assert_eq!(statement.interest_cents, -1_234);
+        assert_eq!(statement.lines[1].balance_cents, -40_000);
+        assert_eq!(statement.lines[2].category, PostingCategory::Fee);
+        assert_eq!(statement.lines[3].category, PostingCategory::Interest);
+        assert_eq!(statement.lines[1].money_out(), "2,000.00");
+        assert_eq!(statement.lines[1].money_in(), "");
+        assert_eq!(statement.closing(), "-437.34");
+        assert_eq!(format_cents(123_456_789), "1,234,567.89");
+        assert_eq!(format_cents(-5), "-0.05");
+        assert_eq!(decimal_cents(-43_734), "-437.34");
+    }
+}
@@ -0,0 +1,164 @@
+// Just enough PDF to lay plain text out on A4 pages in Courier, a font every reader has built
+// in so nothing needs embedding. Text outside Latin-1 prints as a question mark.
+
+const PAGE_WIDTH: usize = 595;
+const PAGE_HEIGHT: usize = 842;
+const MARGIN: usize = 40;
+const FONT_SIZE: usize = 9;
+const LEADING: usize = 11;
+// Courier glyphs are six tenths of the font size wide
+const LINE_CHARS: usize = (PAGE_WIDTH - 2 * MARGIN) * 10 / (FONT_SIZE * 6);
+const PAGE_LINES: usize = (PAGE_HEIGHT - 2 * MARGIN) / LEADING;
+
+// Objects 1 to 4 are the catalog, the page tree, the font and the document information, each
+// page then takes two, itself and its content stream
+const FIRST_PAGE_OBJECT: usize = 5;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\t' => escaped.push(' '),
+            ' '..='~' => escaped.push(c),
+            // WinAnsiEncoding agrees with Latin-1 here
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+fn wrap(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.trim_end().chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+
+    chars
+        .chunks(LINE_CHARS)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn content_stream(lines: &[String]) -> String {
+    let mut stream = format!(
+        "BT\n/F1 {} Tf\n{} TL\n{} {} Td\n",
+        FONT_SIZE,
+        LEADING,
+        MARGIN,
+        PAGE_HEIGHT - MARGIN - FONT_SIZE
+    );
+    for line in lines {
+        stream.push_str(&format!("({}) Tj T*\n", escape(line)));
+    }
+    stream.push_str("ET");
+    stream
+}
+
+pub fn render_text(title: &str, text: &str) -> Vec<u8> {
+    let lines: Vec<String> = text.lines().flat_map(wrap).collect();
+    let pages: Vec<&[String]> = match lines.is_empty() {
+        true => vec![&[]],
+        false => lines.chunks(PAGE_LINES).collect(),
+    };
+
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", FIRST_PAGE_OBJECT + 2 * i))
+        .collect();
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!("<< /Title ({}) /Producer (Thalia Corp.) >>", escape(title)),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        let stream = content_stream(page);
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            FIRST_PAGE_OBJECT + 2 * i + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            stream.len(),
+            stream
+        ));
+    }
+
+    // The binary comment tells transfer tools the file isn't text
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref_offset = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        trailer.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    trailer.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.extend_from_slice(trailer.as_bytes());
+
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PAGE_LINES, render_text};
+
+    fn find(haystack: &[u8], needle: &str) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|w| w == needle.as_bytes())
+    }
+
+    #[test]
+    fn cross_reference_table_points_at_every_object() {
+        let pdf = render_text("Statement", "Opening balance  1,250.00\n\nCafé (card)");
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        assert!(find(&pdf, "(Caf\\351 \\(card\\)) Tj").is_some());
+
+        let text = String::from_utf8_lossy(&pdf).to_string();
+        let startxref: usize = text.rsplit("startxref\n").next().unwrap()[..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref\n0 7\n"));
+
+        let entries = text[text.find("65535 f \n").unwrap() + 9..].lines().take(6);
+        for (i, entry) in entries.enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", i + 1);
+            assert!(pdf[offset..].starts_with(header.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn long_text_runs_onto_more_pages() {
+        let text = vec!["line"; PAGE_LINES + 1].join("\n");
+        let pdf = render_text("Statement", &text);
+
+        assert!(find(&pdf, "/Kids [5 0 R 7 0 R] /Count 2").is_some());
+    }
+}
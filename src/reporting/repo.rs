@@ -0,0 +1,299 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::product::models::StatementFrequency;
+use crate::reporting::models::{
+    AccountStatementEntity, CamtPostingEntity, CoaBalanceEntity, PostingEntity,
+    StatementAccountEntity, StatementPeriod,
+};
+
+const STATEMENT_COLUMNS: &str = "id, account_id, user_id, period_start, period_end, currency,
+    opening_cents, closing_cents, credits_cents, debits_cents, interest_cents, fees_cents,
+    entry_count, html_key, pdf_key, csv_key, emailed_at, created_at";
+
+// The account's own lines, on the liability side as account balances are taken
+const ACCOUNT_LINES: &str = "FROM journal_line_balance l
+    JOIN journal_entry e ON e.id = l.journal_entry_id
+    WHERE l.user_account_id = $1 AND l.coa_type = 'liability'";
+
+pub struct StatementRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> StatementRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    // Accounts on products with this frequency open during the period that don't have its
+    // statement yet. Accounts without a product get one monthly.
+    #[tracing::instrument("Retrieving accounts due a statement", skip(self))]
+    pub async fn fetch_due_accounts(
+        &self,
+        frequency: StatementFrequency,
+        period: &StatementPeriod,
+    ) -> Result<Vec<StatementAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, StatementAccountEntity>(
+            "SELECT a.id, a.user_id, a.account_number, a.iban, a.currency, u.first_name,
+                    u.last_name, u.email
+                FROM user_account a
+                JOIN tuser u ON u.id = a.user_id
+                LEFT JOIN product_version v ON v.id = a.product_version_id
+                WHERE COALESCE(v.statement_frequency, 'monthly') = $1
+                    AND a.status <> 'pending'
+                    AND a.created_at < $3
+                    AND (a.status <> 'closed' OR a.updated_at >= $2)
+                    AND NOT EXISTS (SELECT 1 FROM account_statement s
+                        WHERE s.account_id = a.id AND s.period_start = $2)
+                ORDER BY a.account_number",
+        )
+        .bind(frequency)
+        .bind(period.start)
+        .bind(period.end_time().and_utc())
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving statement opening balance", skip(self))]
+    pub async fn fetch_balance_before(
+        &self,
+        account_id: Uuid,
+        before: NaiveDateTime,
+    ) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(&format!(
+            "SELECT COALESCE(SUM(l.credit_cents), 0)::BIGINT AS balance_cents
+                {} AND e.created_date < $2",
+            ACCOUNT_LINES
+        ))
+        .bind(account_id)
+        .bind(before)
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(result.get("balance_cents"))
+    }
+
+    #[tracing::instrument("Retrieving statement postings", skip(self))]
+    pub async fn fetch_postings(
+        &self,
+        account_id: Uuid,
+        period: &StatementPeriod,
+    ) -> Result<Vec<PostingEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, PostingEntity>(&format!(
+            "SELECT e.id AS journal_entry_id, e.transaction_ref, e.description,
+                    e.created_date AS posted_at, l.credit_cents AS amount_cents,
+                    (SELECT oc.coa_type FROM journal_line o
+                        JOIN chart_of_account oc ON oc.id = o.coa_id
+                        WHERE o.journal_entry_id = e.id AND o.id <> l.journal_line_id
+                        LIMIT 1) AS contra_type
+                {} AND e.created_date >= $2 AND e.created_date < $3 AND l.credit_cents <> 0
+                ORDER BY e.created_date, e.id",
+            ACCOUNT_LINES
+        ))
+        .bind(account_id)
+        .bind(period.start_time())
+        .bind(period.end_time())
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // As fetch_postings, with the references and value date of the payment each entry
+    // belongs to. A return is booked under the transfer's return entry.
+    #[tracing::instrument("Retrieving camt.053 postings", skip(self))]
+    pub async fn fetch_camt_postings(
+        &self,
+        account_id: Uuid,
+        period: &StatementPeriod,
+    ) -> Result<Vec<CamtPostingEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CamtPostingEntity>(
+            "SELECT e.id AS journal_entry_id, e.transaction_id, e.transaction_ref, e.description,
+                    e.created_date AS posted_at, l.credit_cents AS amount_cents,
+                    (SELECT oc.coa_type FROM journal_line o
+                        JOIN chart_of_account oc ON oc.id = o.coa_id
+                        WHERE o.journal_entry_id = e.id AND o.id <> l.journal_line_id
+                        LIMIT 1) AS contra_type,
+                    CASE WHEN st.id IS NOT NULL OR ip.id IS NOT NULL THEN 'sepa'
+                        WHEN ach.id IS NOT NULL THEN 'ach'
+                        WHEN sw.id IS NOT NULL OR si.id IS NOT NULL THEN 'swift'
+                    END AS scheme,
+                    COALESCE(e.id = st.return_entry_id OR e.id = ach.return_entry_id, false)
+                        AS returned,
+                    COALESCE(st.end_to_end_id, ach.trace_number, ip.reference, sw.reference,
+                        si.reference) AS end_to_end_id,
+                    COALESCE(sw.uetr::TEXT, si.uetr) AS uetr,
+                    COALESCE(ip.value_date, sw.value_date, si.value_date) AS value_date
+                FROM journal_line_balance l
+                JOIN journal_entry e ON e.id = l.journal_entry_id
+                LEFT JOIN sepa_transfer st ON e.id IN (st.journal_entry_id, st.return_entry_id)
+                LEFT JOIN ach_transfer ach
+                    ON e.id IN (ach.journal_entry_id, ach.return_entry_id)
+                LEFT JOIN inbound_payment ip ON ip.journal_entry_id = e.id
+                LEFT JOIN swift_transfer sw ON sw.journal_entry_id = e.id
+                LEFT JOIN swift_inbound si ON si.journal_entry_id = e.id
+                WHERE l.user_account_id = $1 AND l.coa_type = 'liability'
+                    AND e.created_date >= $2 AND e.created_date < $3 AND l.credit_cents <> 0
+                ORDER BY e.created_date, e.id",
+        )
+        .bind(account_id)
+        .bind(period.start_time())
+        .bind(period.end_time())
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving account for export", skip(self))]
+    pub async fn fetch_export_account(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<StatementAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, StatementAccountEntity>(
+            "SELECT a.id, a.user_id, a.account_number, a.iban, a.currency, u.first_name,
+                    u.last_name, u.email
+                FROM user_account a
+                JOIN tuser u ON u.id = a.user_id
+                WHERE a.id = $1",
+        )
+        .bind(account_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // False when the period's statement was saved by a run in parallel
+    #[tracing::instrument("Saving account statement", skip(self, statement))]
+    pub async fn insert_statement(
+        &mut self,
+        statement: &AccountStatementEntity,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(&format!(
+            "INSERT INTO account_statement({})
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                    $17, $18)
+                ON CONFLICT (account_id, period_start) DO NOTHING",
+            STATEMENT_COLUMNS
+        ))
+        .bind(statement.id)
+        .bind(statement.account_id)
+        .bind(statement.user_id)
+        .bind(statement.period_start)
+        .bind(statement.period_end)
+        .bind(&statement.currency)
+        .bind(statement.opening_cents)
+        .bind(statement.closing_cents)
+        .bind(statement.credits_cents)
+        .bind(statement.debits_cents)
+        .bind(statement.interest_cents)
+        .bind(statement.fees_cents)
+        .bind(statement.entry_count)
+        .bind(&statement.html_key)
+        .bind(&statement.pdf_key)
+        .bind(&statement.csv_key)
+        .bind(statement.emailed_at)
+        .bind(statement.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[tracing::instrument("Marking account statement emailed", skip(self))]
+    pub async fn update_emailed(
+        &mut self,
+        statement_id: Uuid,
+        emailed_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE account_statement SET emailed_at=$2 WHERE id=$1")
+            .bind(statement_id)
+            .bind(emailed_at)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving account owner", skip(self))]
+    pub async fn fetch_account_user(&self, account_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let result = sqlx::query("SELECT user_id FROM user_account WHERE id=$1")
+            .bind(account_id)
+            .fetch_optional(self.pool)
+            .await?
+            .map(|r| r.get("user_id"));
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving account statements", skip(self))]
+    pub async fn fetch_statements(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<AccountStatementEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AccountStatementEntity>(&format!(
+            "SELECT {} FROM account_statement WHERE account_id=$1 ORDER BY period_end DESC",
+            STATEMENT_COLUMNS
+        ))
+        .bind(account_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving account statement", skip(self))]
+    pub async fn fetch_statement(
+        &self,
+        account_id: Uuid,
+        statement_id: Uuid,
+    ) -> Result<Option<AccountStatementEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, AccountStatementEntity>(&format!(
+            "SELECT {} FROM account_statement WHERE account_id=$1 AND id=$2",
+            STATEMENT_COLUMNS
+        ))
+        .bind(account_id)
+        .bind(statement_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Every chart account, by the currencies posted to it. A customer's entry is in the
+    // account's currency, anything else in the chart account's. Left without the year-end
+    // closing entries, income and expenses show what a closed year earned.
+    #[tracing::instrument("Retrieving chart account balances", skip(self))]
+    pub async fn fetch_coa_balances(
+        &self,
+        from: Option<NaiveDateTime>,
+        before: NaiveDateTime,
+        with_closing: bool,
+    ) -> Result<Vec<CoaBalanceEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CoaBalanceEntity>(
+            "SELECT c.code, c.name, c.coa_type, COALESCE(b.currency, c.currency) AS currency,
+                    COALESCE(b.debit_cents, 0)::BIGINT AS debit_cents
+                FROM chart_of_account c
+                LEFT JOIN (
+                    SELECT l.coa_id, l.currency, SUM(-l.credit_cents) AS debit_cents
+                    FROM journal_line_balance l
+                    WHERE l.created_date < $2 AND ($1::TIMESTAMP IS NULL OR l.created_date >= $1)
+                        AND ($3 OR l.entry_kind <> 'closing')
+                    GROUP BY l.coa_id, l.currency
+                ) b ON b.coa_id = c.id
+                ORDER BY c.code",
+        )
+        .bind(from)
+        .bind(before)
+        .bind(with_closing)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,129 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::reporting::{
+    schemas::{
+        AccountStatementResponse, BalanceSheetQuery, Camt053Query, FinancialReport,
+        FinancialStatementResponse, IncomeStatementQuery, StatementDownloadQuery,
+        StatementDownloadResponse, StatementRunRequest, StatementRunResponse,
+    },
+    service::StatementService,
+};
+
+#[tracing::instrument("Run account statements", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/statements/run", request_body=StatementRunRequest, responses((status=200, body=StatementRunResponse, description="Statements due for the month generated, stored and announced to customers"), (status=400, description="Not the last day of a month that has ended"), (status=403, description="Only superusers can run statements")))]
+pub async fn run_account_statements(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<StatementRunRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let response = statement_service.run(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List account statements", skip(app_state, claims))]
+#[utoipa::path(get, path="/accounts/{account_id}/statements", params(("account_id"=Uuid, Path, description="Account id")), responses((status=200, body=Vec<AccountStatementResponse>, description="Account's statements, latest first"), (status=404, description="Account not found")))]
+pub async fn list_account_statements(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let response = statement_service
+        .list(&claims, account_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Download account statement", skip(app_state, claims))]
+#[utoipa::path(get, path="/accounts/{account_id}/statements/{statement_id}/download", params(("account_id"=Uuid, Path, description="Account id"), ("statement_id"=Uuid, Path, description="Statement id"), StatementDownloadQuery), responses((status=200, body=StatementDownloadResponse, description="Short-lived link to the statement file"), (status=400, description="Unknown format"), (status=404, description="Account or statement not found")))]
+pub async fn download_account_statement(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    path: web::Path<(Uuid, Uuid)>,
+    query: web::Query<StatementDownloadQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+    let (account_id, statement_id) = path.into_inner();
+
+    let response = statement_service
+        .download(&claims, account_id, statement_id, query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Export camt.053 statement", skip(app_state, claims))]
+#[utoipa::path(get, path="/accounts/{account_id}/statements/camt053", params(("account_id"=Uuid, Path, description="Account id"), Camt053Query), responses((status=200, content_type="application/xml", body=String, description="The account's bookings over the days as a camt.053.001.08 statement"), (status=400, description="Days in the future, reversed or more than a year"), (status=404, description="Account not found")))]
+pub async fn export_account_camt053(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    account_id: web::Path<Uuid>,
+    query: web::Query<Camt053Query>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let xml = statement_service
+        .customer_camt053(&claims, account_id.into_inner(), query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().content_type("application/xml").body(xml))
+}
+
+#[tracing::instrument("Export camt.053 statement for staff", skip(app_state))]
+#[utoipa::path(get, path="/accounts/{account_id}/statements/camt053", params(("account_id"=Uuid, Path, description="Account id"), Camt053Query), responses((status=200, content_type="application/xml", body=String, description="The account's bookings over the days as a camt.053.001.08 statement"), (status=400, description="Days in the future, reversed or more than a year"), (status=404, description="Account not found")))]
+pub async fn staff_export_account_camt053(
+    app_state: web::Data<AppState>,
+    account_id: web::Path<Uuid>,
+    query: web::Query<Camt053Query>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let xml = statement_service
+        .staff_camt053(account_id.into_inner(), query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().content_type("application/xml").body(xml))
+}
+
+fn financial_report_response(report: FinancialReport) -> HttpResponse {
+    match report {
+        FinancialReport::Json(statement) => HttpResponse::Ok().json(statement),
+        FinancialReport::Csv(csv) => HttpResponse::Ok().content_type("text/csv").body(csv),
+    }
+}
+
+#[tracing::instrument("Draw up balance sheet", skip(app_state))]
+#[utoipa::path(get, path="/balance-sheet", params(BalanceSheetQuery), responses((status=200, body=FinancialStatementResponse, description="Assets, liabilities and equity by chart account in the base currency, against an earlier day"), (status=200, content_type="text/csv", body=String, description="The same as csv when asked for"), (status=400, description="Unknown format"), (status=422, description="A balance is in a currency without an exchange rate")))]
+pub async fn balance_sheet(
+    app_state: web::Data<AppState>,
+    query: web::Query<BalanceSheetQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let report = statement_service.balance_sheet(query.into_inner()).await?;
+
+    Ok(financial_report_response(report))
+}
+
+#[tracing::instrument("Draw up income statement", skip(app_state))]
+#[utoipa::path(get, path="/income-statement", params(IncomeStatementQuery), responses((status=200, body=FinancialStatementResponse, description="Income and expenses by chart account in the base currency, against earlier days"), (status=200, content_type="text/csv", body=String, description="The same as csv when asked for"), (status=400, description="Unknown format or the days run backwards"), (status=422, description="A balance is in a currency without an exchange rate")))]
+pub async fn income_statement(
+    app_state: web::Data<AppState>,
+    query: web::Query<IncomeStatementQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let statement_service = StatementService::from(&app_state);
+
+    let report = statement_service
+        .income_statement(query.into_inner())
+        .await?;
+
+    Ok(financial_report_response(report))
+}
@@ -0,0 +1,256 @@
+use askama::Template;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::reporting::financials::{
+    FinancialStatement, ReportColumn, ReportLine, ReportSection, ReportTotal,
+};
+use crate::reporting::models::{AccountStatementEntity, Statement, StatementFormat};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct StatementRunRequest {
+    // The last day of the month statements are run for, the month before this one when not given
+    #[schema(example = "2025-11-30")]
+    pub period_end: Option<NaiveDate>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct StatementRunResponse {
+    pub period_end: NaiveDate,
+    pub statements_generated: u64,
+    // Left for the next run, already generated statements are not redone
+    pub failed: u64,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct AccountStatementResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub currency: String,
+    pub opening_cents: i64,
+    pub closing_cents: i64,
+    pub credits_cents: i64,
+    pub debits_cents: i64,
+    // Net of interest paid and charged, negative when the customer paid
+    pub interest_cents: i64,
+    pub fees_cents: i64,
+    pub entry_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AccountStatementEntity> for AccountStatementResponse {
+    fn from(value: AccountStatementEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            period_start: value.period_start,
+            period_end: value.period_end,
+            currency: value.currency,
+            opening_cents: value.opening_cents,
+            closing_cents: value.closing_cents,
+            credits_cents: value.credits_cents,
+            debits_cents: value.debits_cents,
+            interest_cents: value.interest_cents,
+            fees_cents: value.fees_cents,
+            entry_count: value.entry_count,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct StatementDownloadQuery {
+    // pdf, csv or html, pdf when not given
+    pub format: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct Camt053Query {
+    // The first day of the month to when not given
+    pub from: Option<NaiveDate>,
+    // Today when not given
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct StatementDownloadResponse {
+    pub format: StatementFormat,
+    pub download_url: String,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct BalanceSheetQuery {
+    // Today when not given
+    pub as_of: Option<NaiveDate>,
+    // The same day a year earlier when not given
+    pub compare_as_of: Option<NaiveDate>,
+    // json or csv, json when not given
+    pub format: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct IncomeStatementQuery {
+    // The first day of the year to is in when not given
+    pub from: Option<NaiveDate>,
+    // Today when not given
+    pub to: Option<NaiveDate>,
+    // Both or neither, the same days a year earlier when not given
+    pub compare_from: Option<NaiveDate>,
+    pub compare_to: Option<NaiveDate>,
+    // json or csv, json when not given
+    pub format: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ReportColumnResponse {
+    // Not set on a balance sheet, which runs from the start of the ledger
+    pub from: Option<NaiveDate>,
+    pub to: NaiveDate,
+}
+
+impl From<ReportColumn> for ReportColumnResponse {
+    fn from(value: ReportColumn) -> Self {
+        Self {
+            from: value.from,
+            to: value.to,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ReportLineResponse {
+    // Not set on lines worked out rather than posted to, like current earnings
+    pub code: Option<String>,
+    pub name: String,
+    // How far below a top level account this one sits
+    pub depth: usize,
+    // Including every account below this one
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+impl From<ReportLine> for ReportLineResponse {
+    fn from(value: ReportLine) -> Self {
+        Self {
+            code: value.code,
+            name: value.name,
+            depth: value.depth,
+            current_cents: value.current_cents,
+            comparative_cents: value.comparative_cents,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ReportSectionResponse {
+    pub name: String,
+    pub lines: Vec<ReportLineResponse>,
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+impl From<ReportSection> for ReportSectionResponse {
+    fn from(value: ReportSection) -> Self {
+        Self {
+            name: value.name.into(),
+            lines: value
+                .lines
+                .into_iter()
+                .map(ReportLineResponse::from)
+                .collect(),
+            current_cents: value.current_cents,
+            comparative_cents: value.comparative_cents,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ReportTotalResponse {
+    pub name: String,
+    pub current_cents: i64,
+    pub comparative_cents: i64,
+}
+
+impl From<ReportTotal> for ReportTotalResponse {
+    fn from(value: ReportTotal) -> Self {
+        Self {
+            name: value.name.into(),
+            current_cents: value.current_cents,
+            comparative_cents: value.comparative_cents,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct FinancialStatementResponse {
+    pub title: String,
+    // Every amount is in this currency, whatever it was posted in
+    pub currency: String,
+    pub current: ReportColumnResponse,
+    pub comparative: ReportColumnResponse,
+    pub sections: Vec<ReportSectionResponse>,
+    pub totals: Vec<ReportTotalResponse>,
+}
+
+impl From<FinancialStatement> for FinancialStatementResponse {
+    fn from(value: FinancialStatement) -> Self {
+        Self {
+            title: value.title.into(),
+            currency: value.currency,
+            current: value.current.into(),
+            comparative: value.comparative.into(),
+            sections: value
+                .sections
+                .into_iter()
+                .map(ReportSectionResponse::from)
+                .collect(),
+            totals: value
+                .totals
+                .into_iter()
+                .map(ReportTotalResponse::from)
+                .collect(),
+        }
+    }
+}
+
+// A financial statement in the format asked for
+pub enum FinancialReport {
+    Json(FinancialStatementResponse),
+    Csv(Vec<u8>),
+}
+
+#[derive(Template)]
+#[template(path = "statement.html")]
+pub struct StatementTemplate<'a> {
+    statement: &'a Statement,
+    company_name: &'a str,
+}
+
+impl<'a> StatementTemplate<'a> {
+    pub fn new(statement: &'a Statement, company_name: &'a str) -> Self {
+        Self {
+            statement,
+            company_name,
+        }
+    }
+}
+
+// Laid out in fixed columns for the PDF
+#[derive(Template)]
+#[template(path = "statement.txt")]
+pub struct StatementTemplateTxt<'a> {
+    statement: &'a Statement,
+    company_name: &'a str,
+}
+
+impl<'a> StatementTemplateTxt<'a> {
+    pub fn new(statement: &'a Statement, company_name: &'a str) -> Self {
+        Self {
+            statement,
+            company_name,
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use anyhow::Context;
+use askama::Template;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::product::models::StatementFrequency;
+use crate::reporting::{
+    camt053::Camt053,
+    financials::{
+        CoaBalance, FinancialStatement, FxTable, ReportColumn, base_balances,
+        parse_balance_sheet_dates, parse_income_statement_dates,
+    },
+    models::{
+        AccountStatementEntity, ReportFormat, STATEMENT_URL_TTL_SECS, Statement,
+        StatementAccountEntity, StatementFormat, StatementPeriod, decimal_cents,
+        parse_export_range, parse_period_end,
+    },
+    pdf,
+    schemas::{
+        AccountStatementResponse, BalanceSheetQuery, Camt053Query, FinancialReport,
+        IncomeStatementQuery, StatementDownloadQuery, StatementDownloadResponse,
+        StatementRunRequest, StatementRunResponse, StatementTemplate, StatementTemplateTxt,
+    },
+};
+use crate::user::models::AccessRole;
+
+const COMPANY_NAME: &str = "Thalia Corp.";
+
+pub struct StatementService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> StatementService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // Someone else's account is reported as missing rather than forbidden
+    async fn check_owner(
+        uow: &mut UnitofWork<'_>,
+        claims: &SessionClaims,
+        account_id: Uuid,
+    ) -> Result<(), AppError> {
+        match uow
+            .statements()
+            .fetch_account_user(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(user_id) if user_id == *claims.get_user_id() => Ok(()),
+            _ => Err(DomainError::NotFound("account".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Run account statements", skip(self, claims))]
+    pub async fn run(
+        &self,
+        claims: &SessionClaims,
+        request: StatementRunRequest,
+    ) -> Result<StatementRunResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let period_end = parse_period_end(request.period_end, Utc::now().date_naive())?;
+
+        self.run_for(period_end).await
+    }
+
+    // Safe to repeat, an account's statement for a period is only generated once. One that
+    // fails doesn't hold up the rest and is picked up by the next run.
+    pub async fn run_for(&self, period_end: NaiveDate) -> Result<StatementRunResponse, AppError> {
+        let mut summary = StatementRunResponse {
+            period_end,
+            statements_generated: 0,
+            failed: 0,
+        };
+
+        for frequency in [
+            StatementFrequency::Monthly,
+            StatementFrequency::Quarterly,
+            StatementFrequency::Annual,
+        ] {
+            let period = match StatementPeriod::ending(frequency, period_end) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            let accounts = {
+                let mut uow = UnitofWork::from(&self.app_state.pgpool)
+                    .await
+                    .to_app_err("Failed to start postgres uow")?;
+
+                uow.statements()
+                    .fetch_due_accounts(frequency, &period)
+                    .await
+                    .to_app_err("Failed to fetch accounts due a statement")?
+            };
+
+            for account in accounts {
+                let account_id = account.id;
+                match self.generate(account, period).await {
+                    Ok(Some((statement, saved))) => {
+                        summary.statements_generated += 1;
+                        self.notify_ready(&statement, &saved).await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        summary.failed += 1;
+                        tracing::error!(error.message = %e, account_id = %account_id, "Failed to generate account statement");
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // None when a run in parallel saved the statement first. Files are keyed by account and
+    // month, one left behind by a failed run is overwritten by the next.
+    async fn generate(
+        &self,
+        account: StatementAccountEntity,
+        period: StatementPeriod,
+    ) -> Result<Option<(Statement, AccountStatementEntity)>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let opening_cents = uow
+            .statements()
+            .fetch_balance_before(account.id, period.start_time())
+            .await
+            .to_app_err("Failed to fetch statement opening balance")?;
+        let postings = uow
+            .statements()
+            .fetch_postings(account.id, &period)
+            .await
+            .to_app_err("Failed to fetch statement postings")?;
+
+        let statement = Statement::build(account, period, opening_cents, postings);
+        let saved = AccountStatementEntity::new(&statement);
+
+        let s3_client = &self.app_state.s3_client;
+        for (format, contents) in Self::render(&statement)? {
+            s3_client
+                .upload_to_s3(
+                    contents,
+                    &s3_client.bucket,
+                    saved.object_key(format),
+                    format.content_type(),
+                )
+                .await
+                .context("Failed to upload account statement")?;
+        }
+
+        let inserted = uow
+            .statements()
+            .insert_statement(&saved)
+            .await
+            .to_app_err("Failed to save account statement")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit account statement")?;
+
+        Ok(inserted.then_some((statement, saved)))
+    }
+
+    fn render(statement: &Statement) -> Result<Vec<(StatementFormat, Vec<u8>)>, anyhow::Error> {
+        let html = StatementTemplate::new(statement, COMPANY_NAME)
+            .render()
+            .context("Failed to render statement template (html)")?;
+
+        let text = StatementTemplateTxt::new(statement, COMPANY_NAME)
+            .render()
+            .context("Failed to render statement template (txt)")?;
+        let title = format!(
+            "Statement {} {}",
+            statement.account.account_number,
+            statement.period.end.format("%B %Y")
+        );
+
+        Ok(vec![
+            (StatementFormat::Html, html.into_bytes()),
+            (StatementFormat::Pdf, pdf::render_text(&title, &text)),
+            (StatementFormat::Csv, Self::render_csv(statement)?),
+        ])
+    }
+
+    // Opening and closing balances as rows of their own so the file reads on its own
+    fn render_csv(statement: &Statement) -> Result<Vec<u8>, anyhow::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        writer.write_record([
+            "date",
+            "reference",
+            "description",
+            "category",
+            "paid_out",
+            "paid_in",
+            "balance",
+        ])?;
+        writer.write_record([
+            &statement.period.start.to_string(),
+            "",
+            "Opening balance",
+            "",
+            "",
+            "",
+            &decimal_cents(statement.opening_cents),
+        ])?;
+        for line in &statement.lines {
+            let (paid_out, paid_in) = match line.amount_cents < 0 {
+                true => (decimal_cents(-line.amount_cents), String::new()),
+                false => (String::new(), decimal_cents(line.amount_cents)),
+            };
+            writer.write_record([
+                &line.date.to_string(),
+                &line.reference,
+                &line.description,
+                &line.category.to_string(),
+                &paid_out,
+                &paid_in,
+                &decimal_cents(line.balance_cents),
+            ])?;
+        }
+        writer.write_record([
+            &statement.period.end.to_string(),
+            "",
+            "Closing balance",
+            "",
+            "",
+            "",
+            &decimal_cents(statement.closing_cents),
+        ])?;
+
+        writer.into_inner().context("Failed to write statement csv")
+    }
+
+    // The statement is already saved, a notification that doesn't go out is only logged
+    async fn notify_ready(&self, statement: &Statement, saved: &AccountStatementEntity) {
+        let account = &statement.account;
+        let account_number = &account.account_number;
+        let account_suffix = &account_number[account_number.len().saturating_sub(4)..];
+        let period = format!(
+            "{} to {}",
+            statement.period.start.format("%d %B %Y"),
+            statement.period.end.format("%d %B %Y")
+        );
+
+        let sent = self
+            .app_state
+            .email_client
+            .send_statement_ready_email(
+                &account.email,
+                account.first_name.as_deref().unwrap_or_default(),
+                account_suffix,
+                &period,
+                COMPANY_NAME,
+            )
+            .await;
+
+        let outcome = match sent {
+            Ok(()) => match UnitofWork::from(&self.app_state.pgpool).await {
+                Ok(mut uow) => match uow.statements().update_emailed(saved.id, Utc::now()).await {
+                    Ok(()) => uow.commit().await.map_err(anyhow::Error::from),
+                    Err(e) => Err(anyhow::Error::from(e)),
+                },
+                Err(e) => Err(anyhow::Error::from(e)),
+            },
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = outcome {
+            tracing::error!(error.cause_chain = ?e, error.message = %e, statement_id = %saved.id, "Failed to send statement notification");
+        }
+    }
+
+    #[tracing::instrument("List account statements", skip(self, claims))]
+    pub async fn list(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+    ) -> Result<Vec<AccountStatementResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::check_owner(&mut uow, claims, account_id).await?;
+
+        let statements = uow
+            .statements()
+            .fetch_statements(account_id)
+            .await
+            .to_app_err("Failed to fetch account statements")?;
+
+        Ok(statements
+            .into_iter()
+            .map(AccountStatementResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Download account statement", skip(self, claims))]
+    pub async fn download(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        statement_id: Uuid,
+        query: StatementDownloadQuery,
+    ) -> Result<StatementDownloadResponse, AppError> {
+        let format = match query.format {
+            Some(f) => f.parse::<StatementFormat>()?,
+            None => StatementFormat::Pdf,
+        };
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::check_owner(&mut uow, claims, account_id).await?;
+
+        let statement = match uow
+            .statements()
+            .fetch_statement(account_id, statement_id)
+            .await
+            .to_app_err("Failed to fetch account statement")?
+        {
+            Some(s) => s,
+            None => Err(DomainError::NotFound("statement".into()))?,
+        };
+
+        let s3_client = &self.app_state.s3_client;
+        let download_url = s3_client
+            .fetch_presigned_uri(
+                &s3_client.bucket,
+                statement.object_key(format),
+                STATEMENT_URL_TTL_SECS,
+            )
+            .await?;
+
+        Ok(StatementDownloadResponse {
+            format,
+            download_url,
+            expires_in_secs: STATEMENT_URL_TTL_SECS,
+        })
+    }
+
+    #[tracing::instrument("Export camt.053 statement", skip(self, claims))]
+    pub async fn customer_camt053(
+        &self,
+        claims: &SessionClaims,
+        account_id: Uuid,
+        query: Camt053Query,
+    ) -> Result<Vec<u8>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        Self::check_owner(&mut uow, claims, account_id).await?;
+
+        self.camt053(&mut uow, account_id, query).await
+    }
+
+    // Staff routes are already limited to staff, any of them may export any account
+    #[tracing::instrument("Export camt.053 statement for staff", skip(self))]
+    pub async fn staff_camt053(
+        &self,
+        account_id: Uuid,
+        query: Camt053Query,
+    ) -> Result<Vec<u8>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        self.camt053(&mut uow, account_id, query).await
+    }
+
+    async fn camt053(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+        query: Camt053Query,
+    ) -> Result<Vec<u8>, AppError> {
+        let now = Utc::now();
+        let period = parse_export_range(query.from, query.to, now.date_naive())?;
+
+        let account = match uow
+            .statements()
+            .fetch_export_account(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        let opening_cents = uow
+            .statements()
+            .fetch_balance_before(account.id, period.start_time())
+            .await
+            .to_app_err("Failed to fetch statement opening balance")?;
+        let postings = uow
+            .statements()
+            .fetch_camt_postings(account.id, &period)
+            .await
+            .to_app_err("Failed to fetch statement postings")?;
+
+        let camt = Camt053::new(
+            &account,
+            period,
+            opening_cents,
+            postings,
+            &self.app_state.swift.sender_bic,
+            now,
+        );
+
+        let xml = camt
+            .to_xml()
+            .context("Failed to write camt.053 statement")?;
+
+        Ok(xml)
+    }
+
+    #[tracing::instrument("Draw up balance sheet", skip(self))]
+    pub async fn balance_sheet(
+        &self,
+        query: BalanceSheetQuery,
+    ) -> Result<FinancialReport, AppError> {
+        let format = Self::report_format(query.format.as_deref())?;
+        let (current, comparative) =
+            parse_balance_sheet_dates(query.as_of, query.compare_as_of, Utc::now().date_naive());
+
+        self.financial_statement(
+            format,
+            current,
+            comparative,
+            true,
+            FinancialStatement::balance_sheet,
+        )
+        .await
+    }
+
+    #[tracing::instrument("Draw up income statement", skip(self))]
+    pub async fn income_statement(
+        &self,
+        query: IncomeStatementQuery,
+    ) -> Result<FinancialReport, AppError> {
+        let format = Self::report_format(query.format.as_deref())?;
+        let (current, comparative) = parse_income_statement_dates(
+            query.from,
+            query.to,
+            query.compare_from,
+            query.compare_to,
+            Utc::now().date_naive(),
+        )?;
+
+        self.financial_statement(
+            format,
+            current,
+            comparative,
+            false,
+            FinancialStatement::income_statement,
+        )
+        .await
+    }
+
+    fn report_format(format: Option<&str>) -> Result<ReportFormat, AppError> {
+        match format {
+            Some(f) => Ok(f.parse::<ReportFormat>()?),
+            None => Ok(ReportFormat::Json),
+        }
+    }
+
+    async fn financial_statement(
+        &self,
+        format: ReportFormat,
+        current: ReportColumn,
+        comparative: ReportColumn,
+        with_closing: bool,
+        draw_up: fn(
+            &str,
+            ReportColumn,
+            ReportColumn,
+            &[CoaBalance],
+            &[CoaBalance],
+        ) -> FinancialStatement,
+    ) -> Result<FinancialReport, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let rates = uow
+            .swift()
+            .fetch_fx_rates()
+            .await
+            .to_app_err("Failed to fetch FX rates")?;
+        let fx = FxTable::new(&self.app_state.reporting.base_currency, rates);
+
+        let mut balances = Vec::new();
+        for column in [current, comparative] {
+            let rows = uow
+                .statements()
+                .fetch_coa_balances(column.from_time(), column.to_time(), with_closing)
+                .await
+                .to_app_err("Failed to fetch chart account balances")?;
+            balances.push(base_balances(rows, &fx)?);
+        }
+
+        let statement = draw_up(
+            fx.base_currency(),
+            current,
+            comparative,
+            &balances[0],
+            &balances[1],
+        );
+
+        let report = match format {
+            ReportFormat::Json => FinancialReport::Json(statement.into()),
+            ReportFormat::Csv => FinancialReport::Csv(
+                statement
+                    .to_csv()
+                    .context("Failed to write financial statement csv")?,
+            ),
+        };
+
+        Ok(report)
+    }
+}
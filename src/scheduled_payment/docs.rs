@@ -0,0 +1,14 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::scheduled_payment::routes::create_scheduled_payment,
+    crate::scheduled_payment::routes::list_scheduled_payments,
+    crate::scheduled_payment::routes::fetch_scheduled_payment,
+    crate::scheduled_payment::routes::cancel_scheduled_payment,
+))]
+pub struct ScheduledPaymentApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(crate::scheduled_payment::routes::run_payment_scheduler))]
+pub struct PaymentSchedulerApi;
@@ -0,0 +1,7 @@
+pub mod docs;
+pub mod models;
+pub mod repo;
+pub mod routes;
+pub mod scheduler;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,757 @@
+use chrono::{DateTime, Duration, Months, NaiveDate, Utc};
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::base::error::{DomainError, ValidationError};
+use crate::beneficiary::models::{BeneficiaryEntity, BeneficiaryPolicy};
+use crate::scheduled_payment::schemas::ScheduledPaymentRequest;
+use crate::sepa::{
+    models::{Creditor, SEPA_CURRENCY, TransferInstruction},
+    schemas::SepaTransferRequest,
+};
+
+// The longest remittance information a SEPA credit transfer carries in structured form
+const MAX_REFERENCE_LEN: usize = 35;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "payment_frequency", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PaymentFrequency {
+    Once,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annual,
+}
+
+impl FromStr for PaymentFrequency {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "once" => Ok(PaymentFrequency::Once),
+            "weekly" => Ok(PaymentFrequency::Weekly),
+            "monthly" => Ok(PaymentFrequency::Monthly),
+            "quarterly" => Ok(PaymentFrequency::Quarterly),
+            "annual" => Ok(PaymentFrequency::Annual),
+            _ => Err(ValidationError::InvalidValue {
+                field: "frequency".into(),
+                reason: "Unknown payment frequency".into(),
+            }),
+        }
+    }
+}
+
+impl PaymentFrequency {
+    // Counted from the start so a payment on the 31st falls on the last day of shorter months
+    // and returns to the 31st after them
+    pub fn due_date(&self, start_date: NaiveDate, occurrence: u32) -> Option<NaiveDate> {
+        match self {
+            PaymentFrequency::Once => (occurrence == 0).then_some(start_date),
+            PaymentFrequency::Weekly => {
+                start_date.checked_add_signed(Duration::weeks(occurrence.into()))
+            }
+            PaymentFrequency::Monthly => start_date.checked_add_months(Months::new(occurrence)),
+            PaymentFrequency::Quarterly => {
+                start_date.checked_add_months(Months::new(occurrence * 3))
+            }
+            PaymentFrequency::Annual => start_date.checked_add_months(Months::new(occurrence * 12)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "scheduled_payment_status", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ScheduledPaymentStatus {
+    Active,
+    // Every due date has been handed to the scheduler
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "payment_execution_status", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ExecutionStatus {
+    Pending,
+    Retrying,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ScheduledPaymentEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    // Exactly one of an account here or a beneficiary paid by SEPA credit transfer
+    pub destination_account_id: Option<Uuid>,
+    pub destination_account_number: Option<String>,
+    pub beneficiary_id: Option<Uuid>,
+    pub amount_cents: i64,
+    pub reference: String,
+    pub frequency: PaymentFrequency,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub next_occurrence: i32,
+    pub next_due_date: Option<NaiveDate>,
+    pub status: ScheduledPaymentStatus,
+    pub created_at: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledPaymentEntity {
+    pub fn new(
+        user_id: Uuid,
+        request: &ScheduledPaymentRequest,
+        payee: &Payee,
+        today: NaiveDate,
+    ) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: "Must be greater than zero".into(),
+            });
+        }
+
+        let reference = request.reference.trim();
+        if reference.is_empty() {
+            return Err(ValidationError::MissingField("reference".into()));
+        }
+        if reference.len() > MAX_REFERENCE_LEN {
+            return Err(ValidationError::TooLong {
+                field: "reference".into(),
+                max: MAX_REFERENCE_LEN,
+            });
+        }
+
+        let frequency = match &request.frequency {
+            Some(f) => PaymentFrequency::from_str(f)?,
+            None => PaymentFrequency::Once,
+        };
+
+        if request.start_date < today {
+            return Err(ValidationError::InvalidValue {
+                field: "start_date".into(),
+                reason: "Cannot be in the past".into(),
+            });
+        }
+
+        // The reference goes out as the SEPA remittance information
+        if let Payee::Beneficiary(beneficiary) = payee {
+            TransferInstruction::parse(&sepa_request(
+                request.account_id,
+                beneficiary.id,
+                request.amount_cents,
+                reference,
+            ))?;
+        }
+
+        if let Some(end_date) = request.end_date {
+            if frequency == PaymentFrequency::Once {
+                return Err(ValidationError::InvalidValue {
+                    field: "end_date".into(),
+                    reason: "One-off payments have no end date".into(),
+                });
+            }
+            if end_date < request.start_date {
+                return Err(ValidationError::InvalidValue {
+                    field: "end_date".into(),
+                    reason: "Cannot be before the start date".into(),
+                });
+            }
+        }
+
+        Ok(Self {
+            id: Uuid::now_v7(),
+            user_id,
+            account_id: request.account_id,
+            destination_account_id: payee.account().map(|a| a.id),
+            destination_account_number: payee.account().map(|a| a.account_number.clone()),
+            beneficiary_id: payee.beneficiary().map(|b| b.id),
+            amount_cents: request.amount_cents,
+            reference: reference.to_string(),
+            frequency,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            next_occurrence: 0,
+            next_due_date: Some(request.start_date),
+            status: ScheduledPaymentStatus::Active,
+            created_at: Utc::now(),
+            cancelled_at: None,
+        })
+    }
+
+    // Moves on to the following due date, completing the payment when there is none left
+    pub fn advance(&mut self) {
+        self.next_occurrence += 1;
+        self.next_due_date = self
+            .frequency
+            .due_date(self.start_date, self.next_occurrence as u32)
+            .filter(|d| self.end_date.is_none_or(|end| *d <= end));
+
+        if self.next_due_date.is_none() {
+            self.status = ScheduledPaymentStatus::Completed;
+        }
+    }
+
+    pub fn check_cancellable(&self) -> Result<(), DomainError> {
+        match self.status {
+            ScheduledPaymentStatus::Active => Ok(()),
+            status => Err(DomainError::InvalidState(format!(
+                "scheduled payment is already {}",
+                status
+            ))),
+        }
+    }
+}
+
+pub fn execution_key(scheduled_payment_id: Uuid, due_date: NaiveDate) -> String {
+    format!(
+        "SP-{}-{}",
+        scheduled_payment_id.simple(),
+        due_date.format("%Y%m%d")
+    )
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PaymentExecutionEntity {
+    pub scheduled_payment_id: Uuid,
+    pub due_date: NaiveDate,
+    pub execution_key: String,
+    pub status: ExecutionStatus,
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub journal_entry_id: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// An execution that is due together with the payment it belongs to
+#[derive(Debug, sqlx::FromRow)]
+pub struct DueExecutionEntity {
+    pub scheduled_payment_id: Uuid,
+    pub due_date: NaiveDate,
+    pub execution_key: String,
+    pub attempts: i32,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    pub destination_account_id: Option<Uuid>,
+    pub beneficiary_id: Option<Uuid>,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub reference: String,
+}
+
+impl DueExecutionEntity {
+    pub fn sepa_instruction(
+        &self,
+        beneficiary_id: Uuid,
+    ) -> Result<TransferInstruction, ValidationError> {
+        TransferInstruction::parse(&sepa_request(
+            self.account_id,
+            beneficiary_id,
+            self.amount_cents,
+            &self.reference,
+        ))
+    }
+
+    // How the amount reads in customer notifications, e.g. USD 1,250.05
+    pub fn display_amount(&self) -> String {
+        let units = (self.amount_cents / 100).to_string();
+        let mut grouped = String::new();
+        for (i, c) in units.chars().enumerate() {
+            if i > 0 && (units.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+
+        format!(
+            "{} {}.{:02}",
+            self.currency,
+            grouped,
+            self.amount_cents % 100
+        )
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PaymentAccountEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_number: String,
+    pub currency: String,
+    pub status: UserAccountStatus,
+}
+
+impl PaymentAccountEntity {
+    pub fn check_source(&self, user_id: Uuid) -> Result<(), DomainError> {
+        if self.user_id != user_id {
+            return Err(DomainError::NotFound("account".into()));
+        }
+
+        match self.status {
+            UserAccountStatus::Closed => Err(DomainError::InvalidState(format!(
+                "account {} is closed",
+                self.account_number
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // No currency conversion on scheduled payments
+    pub fn check_destination(&self, source: &PaymentAccountEntity) -> Result<(), DomainError> {
+        if self.id == source.id {
+            return Err(DomainError::ConstraintViolation(
+                "payments must go to a different account".into(),
+            ));
+        }
+
+        if self.currency != source.currency {
+            return Err(DomainError::ConstraintViolation(format!(
+                "account {} is held in {}, not {}",
+                self.account_number, self.currency, source.currency
+            )));
+        }
+
+        match self.status {
+            UserAccountStatus::Closed => Err(DomainError::InvalidState(format!(
+                "account {} is closed",
+                self.account_number
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn sepa_request(
+    account_id: Uuid,
+    beneficiary_id: Uuid,
+    amount_cents: i64,
+    reference: &str,
+) -> SepaTransferRequest {
+    SepaTransferRequest {
+        account_id,
+        beneficiary_id,
+        amount_cents,
+        remittance_info: Some(reference.to_string()),
+    }
+}
+
+// Where a payment goes, a beneficiary is paid by SEPA credit transfer from a euro account
+#[derive(Debug)]
+pub enum Payee {
+    Account(PaymentAccountEntity),
+    Beneficiary(BeneficiaryEntity),
+}
+
+impl Payee {
+    pub fn account(&self) -> Option<&PaymentAccountEntity> {
+        match self {
+            Payee::Account(account) => Some(account),
+            Payee::Beneficiary(_) => None,
+        }
+    }
+
+    pub fn beneficiary(&self) -> Option<&BeneficiaryEntity> {
+        match self {
+            Payee::Account(_) => None,
+            Payee::Beneficiary(beneficiary) => Some(beneficiary),
+        }
+    }
+
+    // Cooling-off is left to each execution, it may well be over by the first due date
+    pub fn check_destination(&self, source: &PaymentAccountEntity) -> Result<(), DomainError> {
+        let beneficiary = match self {
+            Payee::Account(account) => return account.check_destination(source),
+            Payee::Beneficiary(beneficiary) => beneficiary,
+        };
+
+        beneficiary.check_not_flagged()?;
+        Creditor::from_beneficiary(beneficiary)?;
+
+        if source.currency != SEPA_CURRENCY {
+            return Err(DomainError::ConstraintViolation(format!(
+                "account {} is held in {}, SEPA transfers are in {}",
+                source.account_number, source.currency, SEPA_CURRENCY
+            )));
+        }
+
+        Ok(())
+    }
+
+    // A beneficiary flagged since the payment was set up, or still cooling off for the amount,
+    // fails the execution
+    pub fn check_beneficiary(
+        &self,
+        amount_cents: i64,
+        policy: &BeneficiaryPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<(), ExecutionFailure> {
+        let beneficiary = match self {
+            Payee::Account(_) => return Ok(()),
+            Payee::Beneficiary(beneficiary) => beneficiary,
+        };
+
+        beneficiary
+            .check_not_flagged()
+            .and_then(|()| beneficiary.check_amount(amount_cents, policy, now))
+            .map_err(|e| match e {
+                DomainError::ConstraintViolation(reason) => {
+                    ExecutionFailure::BeneficiaryUnavailable(reason)
+                }
+                e => ExecutionFailure::BeneficiaryUnavailable(e.to_string()),
+            })
+    }
+}
+
+// Why an attempt did not go through, only a shortage of funds is worth trying again
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExecutionFailure {
+    InsufficientFunds,
+    AccountUnavailable(String),
+    UnverifiedCustomer,
+    BeneficiaryUnavailable(String),
+}
+
+impl ExecutionFailure {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ExecutionFailure::InsufficientFunds)
+    }
+}
+
+impl std::fmt::Display for ExecutionFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionFailure::InsufficientFunds => write!(f, "insufficient available funds"),
+            ExecutionFailure::AccountUnavailable(reason)
+            | ExecutionFailure::BeneficiaryUnavailable(reason) => write!(f, "{}", reason),
+            ExecutionFailure::UnverifiedCustomer => {
+                write!(f, "the amount needs a verified customer identity")
+            }
+        }
+    }
+}
+
+// There is no destination account when the payment goes to a beneficiary
+pub fn check_execution(
+    source: &UserAccountStatus,
+    destination: Option<&UserAccountStatus>,
+    amount_cents: i64,
+    available_cents: i64,
+) -> Result<(), ExecutionFailure> {
+    match (source, destination) {
+        (
+            UserAccountStatus::Active,
+            None | Some(UserAccountStatus::Active | UserAccountStatus::Frozen),
+        ) => {}
+        (UserAccountStatus::Active, Some(status)) => {
+            return Err(ExecutionFailure::AccountUnavailable(format!(
+                "destination account is {}",
+                status
+            )));
+        }
+        (status, _) => {
+            return Err(ExecutionFailure::AccountUnavailable(format!(
+                "source account is {}",
+                status
+            )));
+        }
+    }
+
+    if amount_cents > available_cents {
+        return Err(ExecutionFailure::InsufficientFunds);
+    }
+
+    Ok(())
+}
+
+// How often a payment short of funds is tried before it is given up on
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: i32,
+    retry_interval: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: i32, retry_interval_mins: i64) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            retry_interval: Duration::minutes(retry_interval_mins),
+        }
+    }
+
+    // When to try again after a failed attempt, if at all
+    pub fn next_attempt(
+        &self,
+        failure: &ExecutionFailure,
+        attempts: i32,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        (failure.is_retryable() && attempts < self.max_attempts).then(|| now + self.retry_interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, NaiveDate, Utc};
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use super::{
+        DueExecutionEntity, ExecutionFailure, Payee, PaymentAccountEntity, PaymentFrequency,
+        RetryPolicy, ScheduledPaymentEntity, ScheduledPaymentStatus, check_execution,
+        execution_key,
+    };
+    use crate::account::models::UserAccountStatus;
+    use crate::beneficiary::models::{BeneficiaryEntity, BeneficiaryPolicy};
+    use crate::scheduled_payment::schemas::ScheduledPaymentRequest;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn destination() -> Payee {
+        Payee::Account(PaymentAccountEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            account_number: "1000000002".into(),
+            currency: "USD".into(),
+            status: UserAccountStatus::Active,
+        })
+    }
+
+    fn account(currency: &str) -> PaymentAccountEntity {
+        PaymentAccountEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            account_number: "1000000001".into(),
+            currency: currency.into(),
+            status: UserAccountStatus::Active,
+        }
+    }
+
+    fn beneficiary(iban: &str, cooling_off_until: DateTime<Utc>) -> Payee {
+        Payee::Beneficiary(BeneficiaryEntity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            name: "Jürgen Müller".into(),
+            iban: Some(iban.into()),
+            account_number: None,
+            bic: None,
+            routing_number: None,
+            bank_country: iban[..2].into(),
+            currency: "EUR".into(),
+            cooling_off_until,
+            flagged_reason: None,
+            flagged_at: None,
+            created_at: cooling_off_until,
+            updated_at: cooling_off_until,
+        })
+    }
+
+    fn request(frequency: Option<&str>, end_date: Option<NaiveDate>) -> ScheduledPaymentRequest {
+        ScheduledPaymentRequest {
+            account_id: Uuid::now_v7(),
+            destination_account_number: Some("1000000002".into()),
+            beneficiary_id: None,
+            amount_cents: 50_000,
+            reference: " RENT FLAT 2 ".into(),
+            frequency: frequency.map(Into::into),
+            start_date: date(2025, 1, 31),
+            end_date,
+        }
+    }
+
+    #[test]
+    fn monthly_payments_on_the_31st_return_to_it() {
+        let start = date(2025, 1, 31);
+        let due: Vec<_> = (0..3)
+            .filter_map(|n| PaymentFrequency::Monthly.due_date(start, n))
+            .collect();
+
+        assert_eq!(due, vec![start, date(2025, 2, 28), date(2025, 3, 31)]);
+        assert_eq!(PaymentFrequency::Once.due_date(start, 1), None);
+    }
+
+    #[test]
+    fn payments_complete_after_their_end_date() {
+        let mut payment = assert_ok!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request(Some("monthly"), Some(date(2025, 3, 15))),
+            &destination(),
+            date(2025, 1, 1)
+        ));
+        assert_eq!(payment.reference, "RENT FLAT 2");
+
+        payment.advance();
+        assert_eq!(payment.next_due_date, Some(date(2025, 2, 28)));
+
+        payment.advance();
+        assert_eq!(payment.next_due_date, None);
+        assert_eq!(payment.status, ScheduledPaymentStatus::Completed);
+    }
+
+    #[test]
+    fn one_off_payments_have_no_end_date() {
+        let mut payment = assert_ok!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request(None, None),
+            &destination(),
+            date(2025, 1, 1)
+        ));
+        assert_eq!(payment.frequency, PaymentFrequency::Once);
+
+        payment.advance();
+        assert_eq!(payment.status, ScheduledPaymentStatus::Completed);
+
+        let _ = assert_err!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request(None, Some(date(2025, 6, 1))),
+            &destination(),
+            date(2025, 1, 1)
+        ));
+    }
+
+    #[test]
+    fn payments_cannot_start_in_the_past() {
+        let _ = assert_err!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request(Some("weekly"), None),
+            &destination(),
+            date(2025, 2, 1)
+        ));
+        let _ = assert_err!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request(Some("fortnightly"), None),
+            &destination(),
+            date(2025, 1, 1)
+        ));
+    }
+
+    #[test]
+    fn beneficiaries_are_paid_from_euro_accounts_with_a_sepa_reference() {
+        let now = DateTime::parse_from_rfc3339("2025-06-15T06:00:00Z")
+            .unwrap()
+            .to_utc();
+        let payee = beneficiary("DE89370400440532013000", now);
+
+        assert_ok!(payee.check_destination(&account("EUR")));
+        let _ = assert_err!(payee.check_destination(&account("USD")));
+        let _ = assert_err!(
+            beneficiary("US12345678901234567890", now).check_destination(&account("EUR"))
+        );
+
+        let mut request = request(Some("monthly"), None);
+        let payment = assert_ok!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request,
+            &payee,
+            date(2025, 1, 1)
+        ));
+        assert_eq!(payment.destination_account_id, None);
+        assert_eq!(payment.beneficiary_id, payee.beneficiary().map(|b| b.id));
+
+        request.reference = "RENT €".into();
+        let _ = assert_err!(ScheduledPaymentEntity::new(
+            Uuid::now_v7(),
+            &request,
+            &payee,
+            date(2025, 1, 1)
+        ));
+    }
+
+    #[test]
+    fn beneficiaries_cooling_off_fail_large_executions() {
+        let now = DateTime::parse_from_rfc3339("2025-06-15T06:00:00Z")
+            .unwrap()
+            .to_utc();
+        let policy = BeneficiaryPolicy::new(24, 100_000);
+        let payee = beneficiary("DE89370400440532013000", now + Duration::hours(1));
+
+        assert_ok!(payee.check_beneficiary(100_000, &policy, now));
+        let failure = assert_err!(payee.check_beneficiary(100_001, &policy, now));
+        assert!(matches!(
+            failure,
+            ExecutionFailure::BeneficiaryUnavailable(_)
+        ));
+        assert!(!failure.is_retryable());
+        assert_ok!(payee.check_beneficiary(100_001, &policy, now + Duration::hours(1)));
+        assert_ok!(destination().check_beneficiary(100_001, &policy, now));
+    }
+
+    #[test]
+    fn execution_keys_are_unique_per_due_date() {
+        let id = Uuid::now_v7();
+        let key = execution_key(id, date(2025, 1, 31));
+
+        assert!(key.ends_with("-20250131"));
+        assert!(key.len() <= 47);
+        assert_ne!(key, execution_key(id, date(2025, 2, 28)));
+    }
+
+    #[test]
+    fn only_shortages_of_funds_are_retried() {
+        let now = DateTime::parse_from_rfc3339("2025-06-15T06:00:00Z")
+            .unwrap()
+            .to_utc();
+        let policy = RetryPolicy::new(3, 240);
+
+        let failure = assert_err!(check_execution(
+            &UserAccountStatus::Active,
+            Some(&UserAccountStatus::Active),
+            10_001,
+            10_000
+        ));
+        assert_eq!(failure, ExecutionFailure::InsufficientFunds);
+        assert_eq!(
+            policy.next_attempt(&failure, 1, now),
+            Some(now + chrono::Duration::hours(4))
+        );
+        assert_eq!(policy.next_attempt(&failure, 3, now), None);
+
+        let failure = assert_err!(check_execution(
+            &UserAccountStatus::Active,
+            Some(&UserAccountStatus::Closed),
+            1,
+            10_000
+        ));
+        assert_eq!(policy.next_attempt(&failure, 1, now), None);
+        assert_eq!(
+            policy.next_attempt(&ExecutionFailure::UnverifiedCustomer, 1, now),
+            None
+        );
+
+        assert_ok!(check_execution(
+            &UserAccountStatus::Active,
+            Some(&UserAccountStatus::Active),
+            10_000,
+            10_000
+        ));
+    }
+
+    #[test]
+    fn failure_notices_show_grouped_amounts() {
+        let mut execution = DueExecutionEntity {
+            scheduled_payment_id: Uuid::now_v7(),
+            due_date: date(2025, 1, 31),
+            execution_key: "SP-1".into(),
+            attempts: 3,
+            user_id: Uuid::now_v7(),
+            account_id: Uuid::now_v7(),
+            destination_account_id: Some(Uuid::now_v7()),
+            beneficiary_id: None,
+            amount_cents: 123_456_705,
+            currency: "USD".into(),
+            reference: "RENT".into(),
+        };
+        assert_eq!(execution.display_amount(), "USD 1,234,567.05");
+
+        execution.amount_cents = 99_950;
+        assert_eq!(execution.display_amount(), "USD 999.50");
+    }
+}
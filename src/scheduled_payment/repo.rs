@@ -0,0 +1,351 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::scheduled_payment::models::{
+    DueExecutionEntity, ExecutionStatus, PaymentAccountEntity, PaymentExecutionEntity,
+    ScheduledPaymentEntity,
+};
+
+const PAYMENT_COLUMNS: &str = "p.id, p.user_id, p.account_id, p.destination_account_id,
+    d.account_number AS destination_account_number, p.beneficiary_id, p.amount_cents, p.reference, p.frequency,
+    p.start_date, p.end_date, p.next_occurrence, p.next_due_date, p.status, p.created_at,
+    p.cancelled_at";
+
+pub struct ScheduledPaymentRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> ScheduledPaymentRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Retrieving payment account", skip(self))]
+    pub async fn fetch_account(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<PaymentAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, PaymentAccountEntity>(
+            "SELECT id, user_id, account_number, currency, status FROM user_account WHERE id=$1",
+        )
+        .bind(account_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving payment account by number", skip(self))]
+    pub async fn fetch_account_by_number(
+        &self,
+        account_number: &str,
+    ) -> Result<Option<PaymentAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, PaymentAccountEntity>(
+            "SELECT id, user_id, account_number, currency, status
+                FROM user_account WHERE account_number=$1",
+        )
+        .bind(account_number)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving scheduled payment", skip(self, payment))]
+    pub async fn insert_payment(
+        &mut self,
+        payment: &ScheduledPaymentEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO scheduled_payment(id, user_id, account_id, destination_account_id,
+                    beneficiary_id, amount_cents, reference, frequency, start_date, end_date,
+                    next_occurrence, next_due_date, status, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+        )
+        .bind(payment.id)
+        .bind(payment.user_id)
+        .bind(payment.account_id)
+        .bind(payment.destination_account_id)
+        .bind(payment.beneficiary_id)
+        .bind(payment.amount_cents)
+        .bind(&payment.reference)
+        .bind(payment.frequency)
+        .bind(payment.start_date)
+        .bind(payment.end_date)
+        .bind(payment.next_occurrence)
+        .bind(payment.next_due_date)
+        .bind(payment.status)
+        .bind(payment.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving scheduled payments", skip(self))]
+    pub async fn fetch_payments(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ScheduledPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScheduledPaymentEntity>(&format!(
+            "SELECT {} FROM scheduled_payment p
+                LEFT JOIN user_account d ON d.id = p.destination_account_id
+                WHERE p.user_id=$1
+                ORDER BY p.created_at DESC",
+            PAYMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving scheduled payment", skip(self))]
+    pub async fn fetch_payment(
+        &self,
+        user_id: Uuid,
+        payment_id: Uuid,
+    ) -> Result<Option<ScheduledPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScheduledPaymentEntity>(&format!(
+            "SELECT {} FROM scheduled_payment p
+                LEFT JOIN user_account d ON d.id = p.destination_account_id
+                WHERE p.id=$1 AND p.user_id=$2",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking scheduled payment", skip(self))]
+    pub async fn fetch_payment_for_update(
+        &mut self,
+        user_id: Uuid,
+        payment_id: Uuid,
+    ) -> Result<Option<ScheduledPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScheduledPaymentEntity>(&format!(
+            "SELECT {} FROM scheduled_payment p
+                LEFT JOIN user_account d ON d.id = p.destination_account_id
+                WHERE p.id=$1 AND p.user_id=$2
+                FOR UPDATE OF p",
+            PAYMENT_COLUMNS
+        ))
+        .bind(payment_id)
+        .bind(user_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Open attempts are failed with the payment so the scheduler leaves them alone
+    #[tracing::instrument("Cancelling scheduled payment", skip(self))]
+    pub async fn cancel_payment(
+        &mut self,
+        payment_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE scheduled_payment
+                SET status='cancelled', cancelled_at=$2, next_due_date=NULL
+                WHERE id=$1",
+        )
+        .bind(payment_id)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE scheduled_payment_execution
+                SET status='failed', next_attempt_at=NULL, last_error='payment cancelled', updated_at=$2
+                WHERE scheduled_payment_id=$1 AND status IN ('pending', 'retrying')",
+        )
+        .bind(payment_id)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Skips payments another scheduler instance is already working through
+    #[tracing::instrument("Retrieving due scheduled payments", skip(self))]
+    pub async fn fetch_due_payments_for_update(
+        &mut self,
+        today: NaiveDate,
+    ) -> Result<Vec<ScheduledPaymentEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScheduledPaymentEntity>(&format!(
+            "SELECT {} FROM scheduled_payment p
+                LEFT JOIN user_account d ON d.id = p.destination_account_id
+                WHERE p.status='active' AND p.next_due_date <= $1
+                FOR UPDATE OF p SKIP LOCKED",
+            PAYMENT_COLUMNS
+        ))
+        .bind(today)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Advancing scheduled payment", skip(self, payment))]
+    pub async fn update_schedule(
+        &mut self,
+        payment: &ScheduledPaymentEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE scheduled_payment SET next_occurrence=$2, next_due_date=$3, status=$4
+                WHERE id=$1",
+        )
+        .bind(payment.id)
+        .bind(payment.next_occurrence)
+        .bind(payment.next_due_date)
+        .bind(payment.status)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Scheduling payment execution", skip(self))]
+    pub async fn insert_execution(
+        &mut self,
+        payment_id: Uuid,
+        due_date: NaiveDate,
+        execution_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let n_inserted = sqlx::query(
+            "INSERT INTO scheduled_payment_execution(scheduled_payment_id, due_date, execution_key,
+                    next_attempt_at, created_at, updated_at)
+                VALUES($1, $2, $3, $4, $4, $4)
+                ON CONFLICT (scheduled_payment_id, due_date) DO NOTHING",
+        )
+        .bind(payment_id)
+        .bind(due_date)
+        .bind(execution_key)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_inserted)
+    }
+
+    #[tracing::instrument("Retrieving due payment executions", skip(self))]
+    pub async fn fetch_due_executions(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<DueExecutionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, DueExecutionEntity>(
+            "SELECT e.scheduled_payment_id, e.due_date, e.execution_key, e.attempts, p.user_id,
+                    p.account_id, p.destination_account_id, p.beneficiary_id, p.amount_cents,
+                    a.currency, p.reference
+                FROM scheduled_payment_execution e
+                JOIN scheduled_payment p ON p.id = e.scheduled_payment_id
+                JOIN user_account a ON a.id = p.account_id
+                WHERE e.status IN ('pending', 'retrying') AND e.next_attempt_at <= $1
+                ORDER BY e.due_date, e.created_at",
+        )
+        .bind(now)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // False when the execution was taken by another run or is no longer open
+    #[tracing::instrument("Locking payment execution", skip(self))]
+    pub async fn lock_execution(
+        &mut self,
+        payment_id: Uuid,
+        due_date: NaiveDate,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "SELECT 1 FROM scheduled_payment_execution
+                WHERE scheduled_payment_id=$1 AND due_date=$2 AND status IN ('pending', 'retrying')
+                FOR UPDATE SKIP LOCKED",
+        )
+        .bind(payment_id)
+        .bind(due_date)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result.is_some())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument("Recording payment attempt", skip(self))]
+    pub async fn record_attempt(
+        &mut self,
+        payment_id: Uuid,
+        due_date: NaiveDate,
+        status: ExecutionStatus,
+        attempts: i32,
+        next_attempt_at: Option<DateTime<Utc>>,
+        last_error: Option<String>,
+        journal_entry_id: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE scheduled_payment_execution
+                SET status=$3, attempts=$4, next_attempt_at=$5, last_error=$6, journal_entry_id=$7,
+                    updated_at=CURRENT_TIMESTAMP
+                WHERE scheduled_payment_id=$1 AND due_date=$2",
+        )
+        .bind(payment_id)
+        .bind(due_date)
+        .bind(status)
+        .bind(attempts)
+        .bind(next_attempt_at)
+        .bind(last_error)
+        .bind(journal_entry_id)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving payment executions", skip(self))]
+    pub async fn fetch_executions(
+        &self,
+        payment_id: Uuid,
+    ) -> Result<Vec<PaymentExecutionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, PaymentExecutionEntity>(
+            "SELECT scheduled_payment_id, due_date, execution_key, status, attempts,
+                    next_attempt_at, last_error, journal_entry_id, updated_at
+                FROM scheduled_payment_execution
+                WHERE scheduled_payment_id=$1
+                ORDER BY due_date DESC",
+        )
+        .bind(payment_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving payment notification recipient", skip(self))]
+    pub async fn fetch_recipient(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<(String, String)>, sqlx::Error> {
+        let result = sqlx::query("SELECT first_name, email FROM tuser WHERE id=$1")
+            .bind(user_id)
+            .fetch_optional(self.pool)
+            .await?
+            .map(|r| {
+                (
+                    r.get::<Option<String>, _>("first_name").unwrap_or_default(),
+                    r.get("email"),
+                )
+            });
+
+        Ok(result)
+    }
+}
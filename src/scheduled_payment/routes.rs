@@ -0,0 +1,87 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::StdResponse;
+use crate::config::state::AppState;
+use crate::scheduled_payment::{
+    schemas::{
+        ScheduledPaymentDetailResponse, ScheduledPaymentRequest, ScheduledPaymentResponse,
+        SchedulerRunResponse,
+    },
+    service::ScheduledPaymentService,
+};
+
+#[tracing::instrument("Create scheduled payment", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/scheduled-payments", request_body=ScheduledPaymentRequest, responses((status=200, body=ScheduledPaymentResponse, description="Payment scheduled"), (status=400, description="Invalid amount, reference, frequency or dates, or not exactly one of a destination account and a beneficiary"), (status=404, description="Account, destination account or beneficiary not found"), (status=409, description="Account closed"), (status=422, description="Destination is the same account or held in another currency, or the beneficiary can't be paid by SEPA from the account")))]
+pub async fn create_scheduled_payment(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<ScheduledPaymentRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    let response = payment_service
+        .create(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List scheduled payments", skip(app_state, claims))]
+#[utoipa::path(get, path="/scheduled-payments", responses((status=200, body=Vec<ScheduledPaymentResponse>, description="Customer's scheduled payments, newest first")))]
+pub async fn list_scheduled_payments(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    let response = payment_service.list(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch scheduled payment", skip(app_state, claims))]
+#[utoipa::path(get, path="/scheduled-payments/{payment_id}", params(("payment_id"=Uuid, Path, description="Scheduled payment id")), responses((status=200, body=ScheduledPaymentDetailResponse, description="Payment with its executions"), (status=404, description="Scheduled payment not found")))]
+pub async fn fetch_scheduled_payment(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payment_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    let response = payment_service
+        .fetch(&claims, payment_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Cancel scheduled payment", skip(app_state, claims))]
+#[utoipa::path(delete, path="/scheduled-payments/{payment_id}", params(("payment_id"=Uuid, Path, description="Scheduled payment id")), responses((status=200, body=StdResponse, description="Payment cancelled, attempts not yet made are dropped"), (status=404, description="Scheduled payment not found"), (status=409, description="Payment already completed or cancelled")))]
+pub async fn cancel_scheduled_payment(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payment_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    payment_service
+        .cancel(&claims, payment_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(StdResponse::from("Scheduled payment cancelled")))
+}
+
+#[tracing::instrument("Run payment scheduler", skip(app_state, claims))]
+#[utoipa::path(post, path="/scheduled-payments/run", responses((status=200, body=SchedulerRunResponse, description="Due payments scheduled and attempted"), (status=403, description="Only superusers can run the scheduler")))]
+pub async fn run_payment_scheduler(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    let response = payment_service.run(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::config::runtime::Config;
+use crate::scheduled_payment::service::ScheduledPaymentService;
+
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub async fn run_scheduler_until_stopped(config: Arc<Config>) -> Result<(), anyhow::Error> {
+    let app_state = config.try_into_state().await?;
+    let payment_service = ScheduledPaymentService::from(&app_state);
+
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        // Anything left open is picked up on the next tick
+        match payment_service.run_due(Utc::now()).await {
+            Ok(summary) => tracing::info!(
+                executions_scheduled = summary.executions_scheduled,
+                succeeded = summary.succeeded,
+                retrying = summary.retrying,
+                failed = summary.failed,
+                "Payment scheduler run finished"
+            ),
+            Err(e) => {
+                tracing::error!(error.message = %e, "Payment scheduler run failed")
+            }
+        }
+    }
+}
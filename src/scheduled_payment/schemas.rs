@@ -0,0 +1,105 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::scheduled_payment::models::{PaymentExecutionEntity, ScheduledPaymentEntity};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ScheduledPaymentRequest {
+    pub account_id: Uuid,
+    // Either an account here or a saved beneficiary with an IBAN in the SEPA area
+    #[schema(example = "1000000002")]
+    pub destination_account_number: Option<String>,
+    pub beneficiary_id: Option<Uuid>,
+    #[schema(example = 50000)]
+    pub amount_cents: i64,
+    // Shown to the payee on each payment
+    #[schema(example = "RENT FLAT 2")]
+    pub reference: String,
+    // weekly, monthly, quarterly or annual, left out for a one-off payment
+    #[schema(example = "monthly")]
+    pub frequency: Option<String>,
+    #[schema(example = "2026-01-01")]
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ScheduledPaymentResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub destination_account_number: Option<String>,
+    pub beneficiary_id: Option<Uuid>,
+    pub amount_cents: i64,
+    pub reference: String,
+    pub frequency: String,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    // Not set once the last payment has been scheduled
+    pub next_due_date: Option<NaiveDate>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+impl From<ScheduledPaymentEntity> for ScheduledPaymentResponse {
+    fn from(value: ScheduledPaymentEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            destination_account_number: value.destination_account_number,
+            beneficiary_id: value.beneficiary_id,
+            amount_cents: value.amount_cents,
+            reference: value.reference,
+            frequency: value.frequency.to_string(),
+            start_date: value.start_date,
+            end_date: value.end_date,
+            next_due_date: value.next_due_date,
+            status: value.status.to_string(),
+            created_at: value.created_at,
+            cancelled_at: value.cancelled_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct PaymentExecutionResponse {
+    pub due_date: NaiveDate,
+    pub execution_key: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub journal_entry_id: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<PaymentExecutionEntity> for PaymentExecutionResponse {
+    fn from(value: PaymentExecutionEntity) -> Self {
+        Self {
+            due_date: value.due_date,
+            execution_key: value.execution_key,
+            status: value.status.to_string(),
+            attempts: value.attempts,
+            next_attempt_at: value.next_attempt_at,
+            last_error: value.last_error,
+            journal_entry_id: value.journal_entry_id,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ScheduledPaymentDetailResponse {
+    #[serde(flatten)]
+    pub payment: ScheduledPaymentResponse,
+    // Newest due date first
+    pub executions: Vec<PaymentExecutionResponse>,
+}
+
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct SchedulerRunResponse {
+    pub executions_scheduled: u64,
+    pub succeeded: u64,
+    pub retrying: u64,
+    pub failed: u64,
+}
@@ -0,0 +1,510 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::beneficiary::models::BeneficiaryEntity;
+use crate::config::state::AppState;
+use crate::hold::service::HoldService;
+use crate::identity_verify::service::KycService;
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::service::LedgerService;
+use crate::scheduled_payment::{
+    models::{
+        DueExecutionEntity, ExecutionFailure, ExecutionStatus, Payee, PaymentAccountEntity,
+        ScheduledPaymentEntity, ScheduledPaymentStatus, check_execution, execution_key,
+    },
+    schemas::{
+        ScheduledPaymentDetailResponse, ScheduledPaymentRequest, ScheduledPaymentResponse,
+        SchedulerRunResponse,
+    },
+};
+use crate::sepa::{
+    models::{Creditor, new_end_to_end_id},
+    service::SepaService,
+};
+use crate::user::models::AccessRole;
+
+pub struct ScheduledPaymentService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> ScheduledPaymentService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    async fn fetch_account(
+        uow: &mut UnitofWork<'_>,
+        account_id: Uuid,
+    ) -> Result<PaymentAccountEntity, AppError> {
+        match uow
+            .scheduled_payments()
+            .fetch_account(account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => Ok(a),
+            None => Err(DomainError::NotFound("account".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Create scheduled payment", skip(self, claims, request))]
+    pub async fn create(
+        &self,
+        claims: &SessionClaims,
+        request: ScheduledPaymentRequest,
+    ) -> Result<ScheduledPaymentResponse, AppError> {
+        let user_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let source = Self::fetch_account(&mut uow, request.account_id).await?;
+        source.check_source(user_id)?;
+
+        let payee = match (&request.destination_account_number, request.beneficiary_id) {
+            (Some(account_number), None) => match uow
+                .scheduled_payments()
+                .fetch_account_by_number(account_number.trim())
+                .await
+                .to_app_err("Failed to fetch destination account")?
+            {
+                Some(a) => Payee::Account(a),
+                None => Err(DomainError::NotFound("destination account".into()))?,
+            },
+            (None, Some(beneficiary_id)) => match uow
+                .beneficiaries()
+                .fetch_beneficiary(user_id, beneficiary_id)
+                .await
+                .to_app_err("Failed to fetch beneficiary")?
+            {
+                Some(b) => Payee::Beneficiary(b),
+                None => Err(DomainError::NotFound("beneficiary".into()))?,
+            },
+            _ => Err(ValidationError::InvalidValue {
+                field: "beneficiary_id".into(),
+                reason: "Give either a destination account number or a beneficiary".into(),
+            })?,
+        };
+        payee.check_destination(&source)?;
+
+        let payment =
+            ScheduledPaymentEntity::new(user_id, &request, &payee, Utc::now().date_naive())?;
+
+        uow.scheduled_payments()
+            .insert_payment(&payment)
+            .await
+            .to_app_err("Failed to save scheduled payment")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit scheduled payment")?;
+
+        Ok(payment.into())
+    }
+
+    #[tracing::instrument("List scheduled payments", skip(self, claims))]
+    pub async fn list(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<Vec<ScheduledPaymentResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payments = uow
+            .scheduled_payments()
+            .fetch_payments(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch scheduled payments")?;
+
+        Ok(payments
+            .into_iter()
+            .map(ScheduledPaymentResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch scheduled payment", skip(self, claims))]
+    pub async fn fetch(
+        &self,
+        claims: &SessionClaims,
+        payment_id: Uuid,
+    ) -> Result<ScheduledPaymentDetailResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payment = match uow
+            .scheduled_payments()
+            .fetch_payment(*claims.get_user_id(), payment_id)
+            .await
+            .to_app_err("Failed to fetch scheduled payment")?
+        {
+            Some(p) => p,
+            None => Err(DomainError::NotFound("scheduled payment".into()))?,
+        };
+
+        let executions = uow
+            .scheduled_payments()
+            .fetch_executions(payment_id)
+            .await
+            .to_app_err("Failed to fetch payment executions")?;
+
+        Ok(ScheduledPaymentDetailResponse {
+            payment: payment.into(),
+            executions: executions.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    #[tracing::instrument("Cancel scheduled payment", skip(self, claims))]
+    pub async fn cancel(&self, claims: &SessionClaims, payment_id: Uuid) -> Result<(), AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payment = match uow
+            .scheduled_payments()
+            .fetch_payment_for_update(*claims.get_user_id(), payment_id)
+            .await
+            .to_app_err("Failed to fetch scheduled payment")?
+        {
+            Some(p) => p,
+            None => Err(DomainError::NotFound("scheduled payment".into()))?,
+        };
+        payment.check_cancellable()?;
+
+        uow.scheduled_payments()
+            .cancel_payment(payment_id, Utc::now())
+            .await
+            .to_app_err("Failed to cancel scheduled payment")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit scheduled payment cancellation")?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Run payment scheduler", skip(self, claims))]
+    pub async fn run(&self, claims: &SessionClaims) -> Result<SchedulerRunResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        self.run_due(Utc::now()).await
+    }
+
+    // Safe to run from several places at once, a due date is scheduled once and an attempt
+    // only made by whoever holds the execution's lock
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Result<SchedulerRunResponse, AppError> {
+        let mut summary = SchedulerRunResponse {
+            executions_scheduled: self.schedule_due(now).await?,
+            ..Default::default()
+        };
+
+        let executions = {
+            let mut uow = UnitofWork::from(&self.app_state.pgpool)
+                .await
+                .to_app_err("Failed to start postgres uow")?;
+
+            uow.scheduled_payments()
+                .fetch_due_executions(now)
+                .await
+                .to_app_err("Failed to fetch due payment executions")?
+        };
+
+        // One failed attempt doesn't hold up the rest, it stays open for the next run
+        for execution in executions {
+            match self.attempt(&execution, now).await {
+                Ok(Some(ExecutionStatus::Succeeded)) => summary.succeeded += 1,
+                Ok(Some(ExecutionStatus::Retrying)) => summary.retrying += 1,
+                Ok(Some(ExecutionStatus::Failed)) => summary.failed += 1,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!(error.message = %e, execution_key = %execution.execution_key, "Failed to attempt scheduled payment")
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // Dates missed while the scheduler was down are all caught up on
+    async fn schedule_due(&self, now: DateTime<Utc>) -> Result<u64, AppError> {
+        let today = now.date_naive();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let payments = uow
+            .scheduled_payments()
+            .fetch_due_payments_for_update(today)
+            .await
+            .to_app_err("Failed to fetch due scheduled payments")?;
+
+        let mut n_scheduled = 0;
+
+        for mut payment in payments {
+            while payment.status == ScheduledPaymentStatus::Active
+                && let Some(due_date) = payment.next_due_date
+                && due_date <= today
+            {
+                n_scheduled += uow
+                    .scheduled_payments()
+                    .insert_execution(
+                        payment.id,
+                        due_date,
+                        &execution_key(payment.id, due_date),
+                        now,
+                    )
+                    .await
+                    .to_app_err("Failed to schedule payment execution")?;
+
+                payment.advance();
+            }
+
+            uow.scheduled_payments()
+                .update_schedule(&payment)
+                .await
+                .to_app_err("Failed to advance scheduled payment")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit scheduled payment executions")?;
+
+        Ok(n_scheduled)
+    }
+
+    // None when the execution was picked up elsewhere. The execution key is the journal
+    // reference of an internal transfer so a date can't be paid twice even if the lock is
+    // somehow lost. A transfer to a beneficiary relies on the lock alone, the end-to-end id
+    // has no room for the key.
+    async fn attempt(
+        &self,
+        execution: &DueExecutionEntity,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ExecutionStatus>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if !uow
+            .scheduled_payments()
+            .lock_execution(execution.scheduled_payment_id, execution.due_date)
+            .await
+            .to_app_err("Failed to lock payment execution")?
+        {
+            return Ok(None);
+        }
+
+        let source = match uow
+            .overdrafts()
+            .fetch_account_for_update(execution.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+        let payee = match (execution.destination_account_id, execution.beneficiary_id) {
+            (Some(account_id), _) => Some(Payee::Account(
+                Self::fetch_account(&mut uow, account_id).await?,
+            )),
+            (None, Some(beneficiary_id)) => uow
+                .beneficiaries()
+                .fetch_beneficiary(execution.user_id, beneficiary_id)
+                .await
+                .to_app_err("Failed to fetch beneficiary")?
+                .map(Payee::Beneficiary),
+            (None, None) => None,
+        };
+        let available = HoldService::available_balance(&mut uow, execution.account_id, now).await?;
+        let verified = match KycService::from(self.app_state)
+            .check_transaction(&mut uow, execution.account_id, execution.amount_cents)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(AppError::Domain(DomainError::ConstraintViolation(_))) => {
+                Err(ExecutionFailure::UnverifiedCustomer)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let attempts = execution.attempts + 1;
+
+        let outcome = verified
+            .and_then(|()| {
+                payee.ok_or_else(|| {
+                    ExecutionFailure::BeneficiaryUnavailable("beneficiary has been removed".into())
+                })
+            })
+            .and_then(|payee| {
+                payee.check_beneficiary(
+                    execution.amount_cents,
+                    &self.app_state.beneficiary_policy,
+                    now,
+                )?;
+                check_execution(
+                    &source.status,
+                    payee.account().map(|a| &a.status),
+                    execution.amount_cents,
+                    available.available_cents(),
+                )?;
+
+                Ok(payee)
+            });
+        let failure = match outcome {
+            Ok(payee) => {
+                let journal_entry_id = match payee {
+                    Payee::Account(destination) => {
+                        LedgerService::from(self.app_state)
+                            .post_internal_transfer(
+                                &mut uow,
+                                execution.account_id,
+                                destination.id,
+                                execution.execution_key.clone(),
+                                execution.reference.clone(),
+                                execution.amount_cents,
+                            )
+                            .await?
+                            .0
+                    }
+                    Payee::Beneficiary(beneficiary) => {
+                        self.send_sepa(&mut uow, execution, &beneficiary, now)
+                            .await?
+                    }
+                };
+
+                uow.scheduled_payments()
+                    .record_attempt(
+                        execution.scheduled_payment_id,
+                        execution.due_date,
+                        ExecutionStatus::Succeeded,
+                        attempts,
+                        None,
+                        None,
+                        Some(journal_entry_id),
+                    )
+                    .await
+                    .to_app_err("Failed to record payment attempt")?;
+
+                uow.commit()
+                    .await
+                    .to_app_err("Failed to commit scheduled payment")?;
+
+                return Ok(Some(ExecutionStatus::Succeeded));
+            }
+            Err(failure) => failure,
+        };
+
+        let next_attempt_at = self
+            .app_state
+            .payment_retry
+            .next_attempt(&failure, attempts, now);
+        let status = match next_attempt_at {
+            Some(_) => ExecutionStatus::Retrying,
+            None => ExecutionStatus::Failed,
+        };
+
+        uow.scheduled_payments()
+            .record_attempt(
+                execution.scheduled_payment_id,
+                execution.due_date,
+                status,
+                attempts,
+                next_attempt_at,
+                Some(failure.to_string()),
+                None,
+            )
+            .await
+            .to_app_err("Failed to record payment attempt")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit payment attempt")?;
+
+        if status == ExecutionStatus::Failed {
+            self.notify_failure(execution, &failure).await;
+        }
+
+        Ok(Some(status))
+    }
+
+    // Queued for the next SEPA batch like any other transfer to the beneficiary
+    async fn send_sepa(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        execution: &DueExecutionEntity,
+        beneficiary: &BeneficiaryEntity,
+        now: DateTime<Utc>,
+    ) -> Result<Uuid, AppError> {
+        let account = match uow
+            .sepa()
+            .fetch_debtor_account_for_update(execution.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        let transfer = SepaService::from(self.app_state)
+            .queue(
+                uow,
+                &account,
+                beneficiary.id,
+                Creditor::from_beneficiary(beneficiary)?,
+                execution.sepa_instruction(beneficiary.id)?,
+                new_end_to_end_id(),
+                execution.execution_key.clone(),
+                now,
+            )
+            .await?;
+
+        Ok(transfer.journal_entry_id)
+    }
+
+    // The attempt is already recorded, a notification that doesn't go out is only logged
+    async fn notify_failure(&self, execution: &DueExecutionEntity, failure: &ExecutionFailure) {
+        let recipient = match UnitofWork::from(&self.app_state.pgpool).await {
+            Ok(mut uow) => uow
+                .scheduled_payments()
+                .fetch_recipient(execution.user_id)
+                .await
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(anyhow::Error::from(e)),
+        };
+
+        let outcome = match recipient {
+            Ok(Some((first_name, email))) => {
+                self.app_state
+                    .email_client
+                    .send_payment_failed_email(
+                        &email,
+                        &first_name,
+                        &execution.reference,
+                        &execution.display_amount(),
+                        &execution.due_date.format("%d %B %Y").to_string(),
+                        &failure.to_string(),
+                        "Thalia Corp.",
+                    )
+                    .await
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = outcome {
+            tracing::error!(error.cause_chain = ?e, error.message = %e, execution_key = %execution.execution_key, "Failed to send payment failure notification");
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::screening::routes::load_watchlist,
+    crate::screening::routes::list_watchlists,
+    crate::screening::routes::list_screening_hits,
+    crate::screening::routes::review_screening_hit,
+))]
+pub struct ScreeningApi;
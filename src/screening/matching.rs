@@ -0,0 +1,272 @@
+use chrono::{Datelike, NaiveDate};
+use deunicode::deunicode;
+use strsim::normalized_damerau_levenshtein;
+
+use crate::screening::models::{DobMatch, ScreeningSubject, WatchlistEntryEntity};
+
+// Honorifics and filler that say nothing about who a person is
+const NOISE_TOKENS: [&str; 8] = ["mr", "mrs", "ms", "miss", "dr", "sir", "hon", "jr"];
+
+// Transliterated to ascii, lowercased, punctuation and single letter initials dropped
+pub fn normalize_name(raw: &str) -> String {
+    deunicode(raw)
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .filter(|t| t.len() > 1 && !NOISE_TOKENS.contains(t))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn sorted_tokens(name: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = name.split_whitespace().collect();
+    tokens.sort_unstable();
+    tokens
+}
+
+// Every token of the shorter name has to find a close token in the longer one,
+// so a missing middle name does not hide a match but a shared surname alone is not one
+fn token_coverage(shorter: &[&str], longer: &[&str]) -> f64 {
+    let total_len: usize = shorter.iter().map(|t| t.len()).sum();
+
+    let weighted: f64 = shorter
+        .iter()
+        .map(|token| {
+            let best = longer
+                .iter()
+                .map(|other| normalized_damerau_levenshtein(token, other))
+                .fold(0.0, f64::max);
+
+            best * token.len() as f64
+        })
+        .sum();
+
+    weighted / total_len as f64
+}
+
+// Similarity between two normalized names, 1.0 being identical
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    if a == b {
+        return 1.0;
+    }
+
+    let (ta, tb) = (sorted_tokens(a), sorted_tokens(b));
+
+    // Word order differs between lists, "HUSSEIN, Saddam" against "Saddam Hussein"
+    let reordered = normalized_damerau_levenshtein(&ta.join(" "), &tb.join(" "));
+
+    let (shorter, longer) = if ta.len() <= tb.len() {
+        (&ta, &tb)
+    } else {
+        (&tb, &ta)
+    };
+
+    // A single name on its own matches far too many people
+    let coverage = if shorter.len() >= 2 || ta.len() == tb.len() {
+        token_coverage(shorter, longer)
+    } else {
+        0.0
+    };
+
+    reordered.max(coverage)
+}
+
+pub fn dob_match(
+    date_of_birth: Option<NaiveDate>,
+    entry: &WatchlistEntryEntity,
+) -> Option<DobMatch> {
+    let Some(dob) = date_of_birth else {
+        return Some(DobMatch::Unknown);
+    };
+
+    if entry.birth_dates.is_empty() && entry.birth_years.is_empty() {
+        return Some(DobMatch::Unknown);
+    }
+
+    if entry.birth_dates.contains(&dob) {
+        Some(DobMatch::Exact)
+    } else if entry.birth_years.contains(&dob.year()) {
+        Some(DobMatch::Year)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScreeningMatch {
+    pub entry_index: usize,
+    pub matched_name: String,
+    pub score: f64,
+    pub dob_match: DobMatch,
+}
+
+#[derive(Debug, Clone)]
+pub struct NameMatcher {
+    threshold: f64,
+}
+
+impl NameMatcher {
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold }
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    // Best scoring alias per entry, entries with a conflicting date of birth are ruled out
+    pub fn screen(
+        &self,
+        subject: &ScreeningSubject,
+        entries: &[WatchlistEntryEntity],
+    ) -> Vec<ScreeningMatch> {
+        let name = normalize_name(&format!("{} {}", subject.first_name, subject.last_name));
+
+        entries
+            .iter()
+            .enumerate()
+            .filter_map(|(entry_index, entry)| {
+                let (matched_name, score) = entry
+                    .names
+                    .iter()
+                    .map(|alias| (alias, name_similarity(&name, alias)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+                if score < self.threshold {
+                    return None;
+                }
+
+                let dob_match = dob_match(subject.date_of_birth, entry)?;
+
+                Some(ScreeningMatch {
+                    entry_index,
+                    matched_name: matched_name.clone(),
+                    score,
+                    dob_match,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NameMatcher, name_similarity, normalize_name};
+    use crate::screening::models::{DobMatch, ScreeningSubject, WatchlistEntryEntity};
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn entry(
+        names: &[&str],
+        birth_dates: Vec<NaiveDate>,
+        birth_years: Vec<i32>,
+    ) -> WatchlistEntryEntity {
+        WatchlistEntryEntity {
+            id: Uuid::now_v7(),
+            version_id: Uuid::now_v7(),
+            external_ref: "1".into(),
+            display_name: names[0].into(),
+            names: names.iter().map(|n| normalize_name(n)).collect(),
+            birth_dates,
+            birth_years,
+        }
+    }
+
+    fn subject(first_name: &str, last_name: &str, dob: Option<NaiveDate>) -> ScreeningSubject {
+        ScreeningSubject {
+            user_id: Uuid::now_v7(),
+            first_name: first_name.into(),
+            last_name: last_name.into(),
+            date_of_birth: dob,
+        }
+    }
+
+    #[test]
+    fn names_are_transliterated_and_cleaned() {
+        assert_eq!(
+            normalize_name("Müller-Lüdenscheidt, Dr. José"),
+            "muller ludenscheidt jose"
+        );
+        assert_eq!(normalize_name("Владимир Путин"), "vladimir putin");
+        assert_eq!(normalize_name("  J.  SMITH "), "smith");
+    }
+
+    #[test]
+    fn token_order_does_not_matter() {
+        assert_eq!(
+            name_similarity(
+                &normalize_name("HUSSEIN, Saddam"),
+                &normalize_name("Saddam Hussein")
+            ),
+            1.0
+        );
+    }
+
+    #[test]
+    fn small_spelling_differences_still_score_high() {
+        let score = name_similarity(
+            &normalize_name("Mohammed Al Rashid"),
+            &normalize_name("Muhammad Al-Rashid"),
+        );
+        assert!(score > 0.85, "score was {}", score);
+    }
+
+    #[test]
+    fn a_shared_surname_alone_is_not_a_match() {
+        let score = name_similarity(&normalize_name("Smith"), &normalize_name("John Smith"));
+        assert!(score < 0.7, "score was {}", score);
+
+        let score = name_similarity(&normalize_name("Jane Smith"), &normalize_name("John Smith"));
+        assert!(score < 0.85, "score was {}", score);
+    }
+
+    #[test]
+    fn missing_middle_name_still_matches() {
+        let score = name_similarity(
+            &normalize_name("Ali Hassan"),
+            &normalize_name("Ali Mohamed Hassan"),
+        );
+        assert!(score > 0.95, "score was {}", score);
+    }
+
+    #[test]
+    fn conflicting_date_of_birth_rules_out_a_match() {
+        let matcher = NameMatcher::new(0.85);
+        let entries = vec![entry(
+            &["Ali Hassan"],
+            vec![NaiveDate::from_ymd_opt(1961, 1, 1).unwrap()],
+            vec![1961],
+        )];
+
+        let same = subject("Ali", "Hassan", NaiveDate::from_ymd_opt(1961, 1, 1));
+        let matches = matcher.screen(&same, &entries);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].dob_match, DobMatch::Exact);
+
+        let same_year = subject("Ali", "Hassan", NaiveDate::from_ymd_opt(1961, 6, 30));
+        assert_eq!(
+            matcher.screen(&same_year, &entries)[0].dob_match,
+            DobMatch::Year
+        );
+
+        let other = subject("Ali", "Hassan", NaiveDate::from_ymd_opt(1990, 1, 1));
+        assert!(matcher.screen(&other, &entries).is_empty());
+    }
+
+    #[test]
+    fn unknown_date_of_birth_falls_back_to_the_name() {
+        let matcher = NameMatcher::new(0.85);
+        let entries = vec![entry(&["Ali Hassan", "Abu Ali"], vec![], vec![])];
+
+        let matches = matcher.screen(&subject("Ali", "Hasan", None), &entries);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].matched_name, "ali hassan");
+        assert_eq!(matches[0].dob_match, DobMatch::Unknown);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod docs;
+pub mod matching;
+pub mod models;
+pub mod parser;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
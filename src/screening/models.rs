@@ -0,0 +1,351 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::base::error::{DomainError, ValidationError};
+use crate::screening::{matching::normalize_name, parser::ParsedEntry};
+use crate::user::models::UserEntity;
+
+// Full consolidated lists run to tens of megabytes
+pub const MAX_WATCHLIST_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "watchlist_kind", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum WatchlistKind {
+    Sanctions,
+    Pep,
+}
+
+impl FromStr for WatchlistKind {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "sanctions" => Ok(WatchlistKind::Sanctions),
+            "pep" => Ok(WatchlistKind::Pep),
+            _ => Err(ValidationError::InvalidValue {
+                field: "kind".into(),
+                reason: "Unknown watch list kind".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum WatchlistFormat {
+    // OFAC SDN list, sdn.csv as published by the US Treasury
+    OfacSdnCsv,
+    // EU consolidated financial sanctions list, xml export
+    EuConsolidatedXml,
+    // `id,name,aliases,date_of_birth` with a header row, for PEP and in-house lists
+    SimpleCsv,
+}
+
+impl FromStr for WatchlistFormat {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "ofac_sdn_csv" => Ok(WatchlistFormat::OfacSdnCsv),
+            "eu_consolidated_xml" => Ok(WatchlistFormat::EuConsolidatedXml),
+            "simple_csv" => Ok(WatchlistFormat::SimpleCsv),
+            _ => Err(ValidationError::InvalidValue {
+                field: "format".into(),
+                reason: "Unknown watch list format".into(),
+            }),
+        }
+    }
+}
+
+impl WatchlistFormat {
+    // Published sanctions lists only ever hold sanctions
+    pub fn default_kind(&self) -> Option<WatchlistKind> {
+        match self {
+            WatchlistFormat::OfacSdnCsv | WatchlistFormat::EuConsolidatedXml => {
+                Some(WatchlistKind::Sanctions)
+            }
+            WatchlistFormat::SimpleCsv => None,
+        }
+    }
+}
+
+// Lists are kept apart by source so loading one never retires another
+pub fn parse_source(source: &str) -> Result<String, ValidationError> {
+    let source = source.trim().to_lowercase();
+
+    if source.is_empty() {
+        return Err(ValidationError::MissingField("source".into()));
+    }
+
+    if source.len() > 64 {
+        return Err(ValidationError::TooLong {
+            field: "source".into(),
+            max: 64,
+        });
+    }
+
+    if !source
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(ValidationError::InvalidFormat("source".into()));
+    }
+
+    Ok(source)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "screening_dob_match", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum DobMatch {
+    Exact,
+    Year,
+    // Either side has no date of birth to compare
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
+#[sqlx(type_name = "screening_hit_status", rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ScreeningHitStatus {
+    Open,
+    Confirmed,
+    Dismissed,
+}
+
+impl FromStr for ScreeningHitStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "open" => Ok(ScreeningHitStatus::Open),
+            "confirmed" => Ok(ScreeningHitStatus::Confirmed),
+            "dismissed" => Ok(ScreeningHitStatus::Dismissed),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Unknown screening hit status".into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ScreeningDecision {
+    Confirm,
+    Dismiss,
+}
+
+impl FromStr for ScreeningDecision {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "confirm" => Ok(ScreeningDecision::Confirm),
+            "dismiss" => Ok(ScreeningDecision::Dismiss),
+            _ => Err(ValidationError::InvalidValue {
+                field: "decision".into(),
+                reason: "Unknown screening decision".into(),
+            }),
+        }
+    }
+}
+
+impl ScreeningHitStatus {
+    pub fn apply(self, decision: ScreeningDecision) -> Result<Self, DomainError> {
+        match (self, decision) {
+            (ScreeningHitStatus::Open, ScreeningDecision::Confirm) => {
+                Ok(ScreeningHitStatus::Confirmed)
+            }
+            (ScreeningHitStatus::Open, ScreeningDecision::Dismiss) => {
+                Ok(ScreeningHitStatus::Dismissed)
+            }
+            (status, _) => Err(DomainError::InvalidState(format!(
+                "screening hit is already {}",
+                status
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct WatchlistVersionEntity {
+    pub id: Uuid,
+    pub source: String,
+    pub kind: WatchlistKind,
+    pub checksum: String,
+    pub entry_count: i32,
+    pub is_active: bool,
+    pub loaded_by: Uuid,
+    pub loaded_at: DateTime<Utc>,
+}
+
+impl WatchlistVersionEntity {
+    pub fn new(
+        source: String,
+        kind: WatchlistKind,
+        bytes: &[u8],
+        entry_count: usize,
+        loaded_by: Uuid,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            source,
+            kind,
+            checksum: hex::encode(Sha256::digest(bytes)),
+            entry_count: entry_count as i32,
+            is_active: true,
+            loaded_by,
+            loaded_at: Utc::now(),
+        }
+    }
+}
+
+// Names are stored normalized, the first one is the primary name
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct WatchlistEntryEntity {
+    pub id: Uuid,
+    pub version_id: Uuid,
+    pub external_ref: String,
+    pub display_name: String,
+    pub names: Vec<String>,
+    pub birth_dates: Vec<NaiveDate>,
+    pub birth_years: Vec<i32>,
+}
+
+impl WatchlistEntryEntity {
+    pub fn new(version_id: Uuid, parsed: ParsedEntry) -> Self {
+        let mut names: Vec<String> = Vec::with_capacity(parsed.names.len());
+
+        for name in parsed.names.iter().map(|n| normalize_name(n)) {
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        Self {
+            id: Uuid::now_v7(),
+            version_id,
+            external_ref: parsed.external_ref,
+            display_name: parsed.names.into_iter().next().unwrap_or_default(),
+            names,
+            birth_dates: parsed.birth_dates,
+            birth_years: parsed.birth_years,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ScreeningSubject {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+impl From<&UserEntity> for ScreeningSubject {
+    fn from(value: &UserEntity) -> Self {
+        Self {
+            user_id: value.id,
+            first_name: value.first_name.clone(),
+            last_name: value.last_name.clone(),
+            date_of_birth: value.date_of_birth,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct ScreeningHitEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub version_id: Uuid,
+    pub entry_id: Uuid,
+    pub list_source: String,
+    pub external_ref: String,
+    pub matched_name: String,
+    pub score: f64,
+    pub dob_match: DobMatch,
+    pub status: ScreeningHitStatus,
+    pub reviewer_id: Option<Uuid>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScreeningHitEntity {
+    pub fn new(
+        user_id: Uuid,
+        version: &WatchlistVersionEntity,
+        entry: &WatchlistEntryEntity,
+        matched_name: String,
+        score: f64,
+        dob_match: DobMatch,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            version_id: version.id,
+            entry_id: entry.id,
+            list_source: version.source.clone(),
+            external_ref: entry.external_ref.clone(),
+            matched_name,
+            score,
+            dob_match,
+            status: ScreeningHitStatus::Open,
+            reviewer_id: None,
+            review_note: None,
+            reviewed_at: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScreeningDecision, ScreeningHitStatus, WatchlistEntryEntity, parse_source};
+    use crate::screening::parser::ParsedEntry;
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    #[test]
+    fn source_must_be_a_simple_identifier() {
+        assert_eq!(assert_ok!(parse_source(" OFAC_sdn ")), "ofac_sdn");
+        let _ = assert_err!(parse_source(""));
+        let _ = assert_err!(parse_source("../etc/passwd"));
+    }
+
+    #[test]
+    fn hits_are_decided_once() {
+        let status = assert_ok!(ScreeningHitStatus::Open.apply(ScreeningDecision::Dismiss));
+        assert_eq!(status, ScreeningHitStatus::Dismissed);
+
+        let _ = assert_err!(status.apply(ScreeningDecision::Confirm));
+    }
+
+    #[test]
+    fn entry_names_are_normalized_and_deduplicated() {
+        let entry = WatchlistEntryEntity::new(
+            Uuid::now_v7(),
+            ParsedEntry {
+                external_ref: "306".into(),
+                names: vec![
+                    "ABU AHMAD, Ali".into(),
+                    "Ali Abu Ahmad".into(),
+                    "Ali ABU-AHMAD".into(),
+                ],
+                birth_dates: vec![],
+                birth_years: vec![],
+            },
+        );
+
+        assert_eq!(entry.display_name, "ABU AHMAD, Ali");
+        assert_eq!(entry.names, vec!["abu ahmad ali", "ali abu ahmad"]);
+    }
+}
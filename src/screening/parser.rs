@@ -0,0 +1,415 @@
+use chrono::{Datelike, NaiveDate};
+use quick_xml::events::{BytesStart, Event};
+
+use crate::base::error::ValidationError;
+use crate::screening::models::WatchlistFormat;
+
+// OFAC marks empty columns with this placeholder
+const OFAC_NULL: &str = "-0-";
+
+// One listed individual, names as published
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedEntry {
+    pub external_ref: String,
+    pub names: Vec<String>,
+    pub birth_dates: Vec<NaiveDate>,
+    pub birth_years: Vec<i32>,
+}
+
+impl ParsedEntry {
+    fn add_name(&mut self, name: &str) {
+        let name = name.trim();
+
+        if !name.is_empty() && !self.names.iter().any(|n| n == name) {
+            self.names.push(name.to_string());
+        }
+    }
+
+    fn add_birth_date(&mut self, date: NaiveDate) {
+        if !self.birth_dates.contains(&date) {
+            self.birth_dates.push(date);
+        }
+
+        self.add_birth_year(date.year());
+    }
+
+    fn add_birth_year(&mut self, year: i32) {
+        if !self.birth_years.contains(&year) {
+            self.birth_years.push(year);
+        }
+    }
+}
+
+fn invalid_file(reason: String) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: "file".into(),
+        reason,
+    }
+}
+
+// Only individuals are kept, vessels and companies can't be customers
+pub fn parse_watchlist(
+    format: WatchlistFormat,
+    bytes: &[u8],
+) -> Result<Vec<ParsedEntry>, ValidationError> {
+    let entries = match format {
+        WatchlistFormat::OfacSdnCsv => parse_ofac_sdn(bytes)?,
+        WatchlistFormat::EuConsolidatedXml => parse_eu_consolidated(bytes)?,
+        WatchlistFormat::SimpleCsv => parse_simple_csv(bytes)?,
+    };
+
+    if entries.is_empty() {
+        return Err(invalid_file("No listed individuals found".into()));
+    }
+
+    Ok(entries)
+}
+
+// Remarks hold aliases and dates of birth, e.g. "DOB 01 Jan 1961; alt. DOB 1962; a.k.a. 'ABU ALI';"
+fn parse_ofac_remarks(entry: &mut ParsedEntry, remarks: &str) {
+    let mut rest = remarks;
+    while let Some(start) = rest.find("k.a. '") {
+        rest = &rest[start + "k.a. '".len()..];
+
+        match rest.find('\'') {
+            Some(end) => {
+                entry.add_name(&rest[..end]);
+                rest = &rest[end + 1..];
+            }
+            None => break,
+        }
+    }
+
+    for part in remarks.split(';') {
+        let part = part.trim().trim_start_matches("alt.").trim();
+
+        let Some(dob) = part.strip_prefix("DOB ") else {
+            continue;
+        };
+
+        if let Ok(date) = NaiveDate::parse_from_str(dob.trim(), "%d %b %Y") {
+            entry.add_birth_date(date);
+            continue;
+        }
+
+        let years: Vec<i32> = dob
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|t| t.len() == 4)
+            .filter_map(|t| t.parse().ok())
+            .collect();
+
+        // "DOB 1958 to 1962" lists a range
+        match years[..] {
+            [from, to] if dob.contains(" to ") && from <= to => {
+                (from..=to).for_each(|y| entry.add_birth_year(y))
+            }
+            _ => years.into_iter().for_each(|y| entry.add_birth_year(y)),
+        }
+    }
+}
+
+// sdn.csv has no header: ent_num, name, type, program, title, ..., remarks (12th column)
+fn parse_ofac_sdn(bytes: &[u8]) -> Result<Vec<ParsedEntry>, ValidationError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+
+    let mut entries = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| invalid_file(format!("Unreadable OFAC row: {}", e)))?;
+
+        let field = |i: usize| {
+            record
+                .get(i)
+                .map(str::trim)
+                .filter(|v| !v.is_empty() && *v != OFAC_NULL)
+        };
+
+        // The published file ends with a lone end-of-file marker
+        if record.len() < 3 || field(2) != Some("individual") {
+            continue;
+        }
+
+        let (Some(external_ref), Some(name)) = (field(0), field(1)) else {
+            return Err(invalid_file(
+                "OFAC row without an entry number or name".into(),
+            ));
+        };
+
+        let mut entry = ParsedEntry {
+            external_ref: external_ref.to_string(),
+            ..Default::default()
+        };
+        entry.add_name(name);
+
+        if let Some(remarks) = field(11) {
+            parse_ofac_remarks(&mut entry, remarks);
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, ValidationError> {
+    for attr in element.attributes() {
+        let attr = attr.map_err(|e| invalid_file(format!("Malformed EU list attribute: {}", e)))?;
+
+        if attr.key.local_name().as_ref() == name {
+            let value = attr
+                .unescape_value()
+                .map_err(|e| invalid_file(format!("Malformed EU list attribute: {}", e)))?;
+
+            let value = value.trim();
+            return Ok((!value.is_empty()).then(|| value.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+// <sanctionEntity logicalId> holding <subjectType code>, <nameAlias> and <birthdate> elements
+fn parse_eu_consolidated(bytes: &[u8]) -> Result<Vec<ParsedEntry>, ValidationError> {
+    let mut reader = quick_xml::Reader::from_reader(bytes);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut current: Option<(ParsedEntry, bool)> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| invalid_file(format!("Malformed EU list: {}", e)))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => match e.local_name().as_ref() {
+                b"sanctionEntity" => {
+                    let external_ref = match attribute(e, b"logicalId")? {
+                        Some(id) => id,
+                        None => attribute(e, b"euReferenceNumber")?.unwrap_or_default(),
+                    };
+
+                    current = Some((
+                        ParsedEntry {
+                            external_ref,
+                            ..Default::default()
+                        },
+                        false,
+                    ));
+                }
+                b"subjectType" => {
+                    if let Some((_, is_person)) = current.as_mut() {
+                        *is_person = attribute(e, b"code")?.as_deref() == Some("person");
+                    }
+                }
+                b"nameAlias" => {
+                    if let Some((entry, _)) = current.as_mut() {
+                        let whole_name = match attribute(e, b"wholeName")? {
+                            Some(name) => name,
+                            None => [
+                                attribute(e, b"firstName")?,
+                                attribute(e, b"middleName")?,
+                                attribute(e, b"lastName")?,
+                            ]
+                            .into_iter()
+                            .flatten()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        };
+
+                        entry.add_name(&whole_name);
+                    }
+                }
+                b"birthdate" => {
+                    if let Some((entry, _)) = current.as_mut() {
+                        let date = attribute(e, b"birthdate")?
+                            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+                        match date {
+                            Some(date) => entry.add_birth_date(date),
+                            None => {
+                                if let Some(year) =
+                                    attribute(e, b"year")?.and_then(|y| y.parse().ok())
+                                {
+                                    entry.add_birth_year(year);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::End(ref e) if e.local_name().as_ref() == b"sanctionEntity" => {
+                if let Some((entry, true)) = current.take()
+                    && !entry.names.is_empty()
+                {
+                    entries.push(entry);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SimpleRow {
+    id: String,
+    name: String,
+    #[serde(default)]
+    aliases: Option<String>,
+    #[serde(default)]
+    date_of_birth: Option<String>,
+}
+
+// Aliases are separated by `;`, a date of birth is either YYYY-MM-DD or just the year
+fn parse_simple_csv(bytes: &[u8]) -> Result<Vec<ParsedEntry>, ValidationError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(bytes);
+
+    let mut entries = Vec::new();
+
+    for (line, row) in reader.deserialize::<SimpleRow>().enumerate() {
+        let row = row.map_err(|e| invalid_file(format!("Unreadable row {}: {}", line + 2, e)))?;
+
+        if row.id.is_empty() || row.name.is_empty() {
+            return Err(invalid_file(format!(
+                "Row {} needs an id and a name",
+                line + 2
+            )));
+        }
+
+        let mut entry = ParsedEntry {
+            external_ref: row.id,
+            ..Default::default()
+        };
+        entry.add_name(&row.name);
+
+        for alias in row.aliases.iter().flat_map(|a| a.split(';')) {
+            entry.add_name(alias);
+        }
+
+        match row.date_of_birth.as_deref() {
+            None | Some("") => {}
+            Some(dob) => {
+                if let Ok(date) = NaiveDate::parse_from_str(dob, "%Y-%m-%d") {
+                    entry.add_birth_date(date);
+                } else if let Ok(year) = dob.parse::<i32>() {
+                    entry.add_birth_year(year);
+                } else {
+                    return Err(invalid_file(format!(
+                        "Row {} has an unreadable date_of_birth",
+                        line + 2
+                    )));
+                }
+            }
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_watchlist;
+    use crate::screening::models::WatchlistFormat;
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    const OFAC: &str = r#"36,"AEROCARIBBEAN AIRLINES","-0- ","CUBA","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","-0- "
+306,"ABU AHMAD, Ali","individual","SDGT","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","DOB 01 Jan 1961; alt. DOB 1962; POB Baghdad, Iraq; a.k.a. 'ABU ALI'; a.k.a. 'AL-BAGHDADI, Ali'."
+307,"RASHID, Omar","individual","SDGT","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","-0- ","DOB 1958 to 1960."
+"#;
+
+    const EU: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<export xmlns="http://eu.europa.ec/fpi/fsd/export">
+  <sanctionEntity logicalId="13" euReferenceNumber="EU.27.28">
+    <subjectType code="person" classificationCode="P"/>
+    <nameAlias firstName="Saddam" lastName="Hussein Al-Tikriti" wholeName="Saddam Hussein Al-Tikriti" strong="true"/>
+    <nameAlias wholeName="Abu Ali" strong="false"/>
+    <birthdate birthdate="1937-04-28" year="1937"/>
+  </sanctionEntity>
+  <sanctionEntity logicalId="14">
+    <subjectType code="enterprise" classificationCode="E"/>
+    <nameAlias wholeName="Iraqi Airways"/>
+  </sanctionEntity>
+  <sanctionEntity logicalId="15">
+    <subjectType code="person"/>
+    <nameAlias firstName="Jos&#233;" lastName="Garc&#237;a"/>
+    <birthdate year="1970"/>
+  </sanctionEntity>
+</export>"#;
+
+    #[test]
+    fn ofac_individuals_are_read_with_aliases_and_dates_of_birth() {
+        let entries = assert_ok!(parse_watchlist(
+            WatchlistFormat::OfacSdnCsv,
+            OFAC.as_bytes()
+        ));
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].external_ref, "306");
+        assert_eq!(
+            entries[0].names,
+            vec!["ABU AHMAD, Ali", "ABU ALI", "AL-BAGHDADI, Ali"]
+        );
+        assert_eq!(
+            entries[0].birth_dates,
+            vec![NaiveDate::from_ymd_opt(1961, 1, 1).unwrap()]
+        );
+        assert_eq!(entries[0].birth_years, vec![1961, 1962]);
+        assert_eq!(entries[1].birth_years, vec![1958, 1959, 1960]);
+    }
+
+    #[test]
+    fn eu_persons_are_read_and_entities_skipped() {
+        let entries = assert_ok!(parse_watchlist(
+            WatchlistFormat::EuConsolidatedXml,
+            EU.as_bytes()
+        ));
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].external_ref, "13");
+        assert_eq!(
+            entries[0].names,
+            vec!["Saddam Hussein Al-Tikriti", "Abu Ali"]
+        );
+        assert_eq!(entries[0].birth_years, vec![1937]);
+        assert_eq!(entries[1].names, vec!["José García"]);
+        assert!(entries[1].birth_dates.is_empty());
+        assert_eq!(entries[1].birth_years, vec![1970]);
+    }
+
+    #[test]
+    fn simple_csv_reads_aliases_and_partial_dates() {
+        let csv = "id,name,aliases,date_of_birth\nPEP-1,Jane Roe,Janet Roe;J. Roe,1965-03-02\nPEP-2,John Doe,,1970\n";
+
+        let entries = assert_ok!(parse_watchlist(WatchlistFormat::SimpleCsv, csv.as_bytes()));
+
+        assert_eq!(entries[0].names, vec!["Jane Roe", "Janet Roe", "J. Roe"]);
+        assert_eq!(entries[0].birth_years, vec![1965]);
+        assert_eq!(entries[1].birth_years, vec![1970]);
+        assert!(entries[1].birth_dates.is_empty());
+    }
+
+    #[test]
+    fn unreadable_or_empty_lists_are_rejected() {
+        let _ = assert_err!(parse_watchlist(
+            WatchlistFormat::SimpleCsv,
+            b"id,name,date_of_birth\nPEP-1,Jane Roe,someday\n"
+        ));
+        let _ = assert_err!(parse_watchlist(
+            WatchlistFormat::EuConsolidatedXml,
+            b"<export><sanctionEntity></export>"
+        ));
+        let _ = assert_err!(parse_watchlist(WatchlistFormat::OfacSdnCsv, b""));
+    }
+}
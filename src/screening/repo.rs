@@ -0,0 +1,243 @@
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::screening::models::{
+    ScreeningHitEntity, ScreeningHitStatus, ScreeningSubject, WatchlistEntryEntity,
+    WatchlistVersionEntity,
+};
+
+const VERSION_COLUMNS: &str =
+    "id, source, kind, checksum, entry_count, is_active, loaded_by, loaded_at";
+
+const HIT_COLUMNS: &str = "id, user_id, version_id, entry_id, list_source, external_ref, matched_name, score, dob_match, status, reviewer_id, review_note, reviewed_at, created_at";
+
+// Keeps each insert well under the bind parameter limit
+const ENTRY_BATCH_SIZE: usize = 1000;
+
+pub struct ScreeningRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> ScreeningRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Retrieving watch list version by checksum", skip(self))]
+    pub async fn fetch_version_by_checksum(
+        &self,
+        source: &str,
+        checksum: &str,
+    ) -> Result<Option<WatchlistVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, WatchlistVersionEntity>(&format!(
+            "SELECT {} FROM watchlist_version WHERE source=$1 AND checksum=$2",
+            VERSION_COLUMNS
+        ))
+        .bind(source)
+        .bind(checksum)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving watch list versions", skip(self))]
+    pub async fn fetch_versions(&self) -> Result<Vec<WatchlistVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, WatchlistVersionEntity>(&format!(
+            "SELECT {} FROM watchlist_version ORDER BY loaded_at DESC",
+            VERSION_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // The previous version of the same source is retired in the same transaction
+    #[tracing::instrument("Saving watch list version", skip(self, version))]
+    pub async fn create_version(
+        &mut self,
+        version: &WatchlistVersionEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE watchlist_version SET is_active=false WHERE source=$1 AND is_active")
+            .bind(&version.source)
+            .execute(&mut **self.tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO watchlist_version(id, source, kind, checksum, entry_count, is_active, loaded_by, loaded_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(version.id)
+        .bind(&version.source)
+        .bind(version.kind)
+        .bind(&version.checksum)
+        .bind(version.entry_count)
+        .bind(version.is_active)
+        .bind(version.loaded_by)
+        .bind(version.loaded_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Saving watch list entries", skip(self, entries), fields(count = entries.len()))]
+    pub async fn create_entries(
+        &mut self,
+        entries: &[WatchlistEntryEntity],
+    ) -> Result<(), sqlx::Error> {
+        for batch in entries.chunks(ENTRY_BATCH_SIZE) {
+            QueryBuilder::<Postgres>::new(
+                "INSERT INTO watchlist_entry(id, version_id, external_ref, display_name, names, birth_dates, birth_years) ",
+            )
+            .push_values(batch, |mut row, entry| {
+                row.push_bind(entry.id)
+                    .push_bind(entry.version_id)
+                    .push_bind(&entry.external_ref)
+                    .push_bind(&entry.display_name)
+                    .push_bind(&entry.names)
+                    .push_bind(&entry.birth_dates)
+                    .push_bind(&entry.birth_years);
+            })
+            .build()
+            .execute(&mut **self.tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving active watch list versions", skip(self))]
+    pub async fn fetch_active_versions(&self) -> Result<Vec<WatchlistVersionEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, WatchlistVersionEntity>(&format!(
+            "SELECT {} FROM watchlist_version WHERE is_active",
+            VERSION_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving active watch list entries", skip(self))]
+    pub async fn fetch_active_entries(&self) -> Result<Vec<WatchlistEntryEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, WatchlistEntryEntity>(
+            "SELECT e.id, e.version_id, e.external_ref, e.display_name, e.names, e.birth_dates, e.birth_years
+                FROM watchlist_entry e JOIN watchlist_version v ON v.id = e.version_id WHERE v.is_active",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving customers to screen", skip(self))]
+    pub async fn fetch_subjects(&self) -> Result<Vec<ScreeningSubject>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScreeningSubject>(
+            "SELECT id AS user_id, first_name, last_name, date_of_birth FROM tuser WHERE access_role='customer'",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Returns false when the customer was already raised for this listing
+    #[tracing::instrument("Saving screening hit", skip(self, hit))]
+    pub async fn create_hit(&mut self, hit: &ScreeningHitEntity) -> Result<bool, sqlx::Error> {
+        let n_created = sqlx::query(
+            "INSERT INTO screening_hit(id, user_id, version_id, entry_id, list_source, external_ref, matched_name, score, dob_match, status, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (user_id, list_source, external_ref) DO NOTHING",
+        )
+        .bind(hit.id)
+        .bind(hit.user_id)
+        .bind(hit.version_id)
+        .bind(hit.entry_id)
+        .bind(&hit.list_source)
+        .bind(&hit.external_ref)
+        .bind(&hit.matched_name)
+        .bind(hit.score)
+        .bind(hit.dob_match)
+        .bind(hit.status)
+        .bind(hit.created_at)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_created == 1)
+    }
+
+    #[tracing::instrument("Retrieving screening hit", skip(self))]
+    pub async fn fetch_hit(&self, hit_id: Uuid) -> Result<Option<ScreeningHitEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScreeningHitEntity>(&format!(
+            "SELECT {} FROM screening_hit WHERE id=$1",
+            HIT_COLUMNS
+        ))
+        .bind(hit_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving screening hits", skip(self))]
+    pub async fn fetch_hits(
+        &self,
+        status: Option<ScreeningHitStatus>,
+    ) -> Result<Vec<ScreeningHitEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScreeningHitEntity>(&format!(
+            "SELECT {} FROM screening_hit WHERE ($1::screening_hit_status IS NULL OR status=$1) ORDER BY score DESC, created_at",
+            HIT_COLUMNS
+        ))
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving screening hits for a user", skip(self))]
+    pub async fn fetch_user_hits(
+        &self,
+        user_id: Uuid,
+        status: ScreeningHitStatus,
+    ) -> Result<Vec<ScreeningHitEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, ScreeningHitEntity>(&format!(
+            "SELECT {} FROM screening_hit WHERE user_id=$1 AND status=$2 ORDER BY score DESC, created_at",
+            HIT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Only succeeds while the hit is still open so two reviewers can't both decide it
+    #[tracing::instrument("Deciding screening hit", skip(self, note))]
+    pub async fn decide_hit(
+        &mut self,
+        hit_id: Uuid,
+        status: ScreeningHitStatus,
+        reviewer_id: Uuid,
+        note: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let n_updated = sqlx::query(
+            "UPDATE screening_hit SET status=$2, reviewer_id=$3, review_note=$4, reviewed_at=CURRENT_TIMESTAMP
+                WHERE id=$1 AND status='open'",
+        )
+        .bind(hit_id)
+        .bind(status)
+        .bind(reviewer_id)
+        .bind(note)
+        .execute(&mut **self.tx)
+        .await?
+        .rows_affected();
+
+        Ok(n_updated)
+    }
+}
@@ -0,0 +1,69 @@
+use actix_multipart::form::MultipartForm;
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::screening::{
+    schemas::{
+        ScreeningHitQuery, ScreeningHitResponse, ScreeningReviewRequest, WatchlistLoadResponse,
+        WatchlistUploadForm, WatchlistVersionResponse,
+    },
+    service::ScreeningService,
+};
+
+#[tracing::instrument("Load watch list", skip(app_state, claims, form))]
+#[utoipa::path(post, path="/screening/lists", request_body(content=WatchlistUploadForm, content_type="multipart/form-data"), responses((status=200, body=WatchlistLoadResponse, description="List loaded and every customer screened against it"), (status=400, description="Unknown format or unreadable list"), (status=403, description="Only superusers can load watch lists"), (status=409, description="This version of the list is already loaded")))]
+pub async fn load_watchlist(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    form: MultipartForm<WatchlistUploadForm>,
+) -> actix_web::Result<HttpResponse> {
+    let screening_service = ScreeningService::from(&app_state);
+
+    let response = screening_service
+        .load_watchlist(&claims, form.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List watch lists", skip(app_state))]
+#[utoipa::path(get, path="/screening/lists", responses((status=200, body=Vec<WatchlistVersionResponse>, description="Loaded watch list versions, newest first")))]
+pub async fn list_watchlists(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let screening_service = ScreeningService::from(&app_state);
+
+    let response = screening_service.list_watchlists().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List screening hits", skip(app_state))]
+#[utoipa::path(get, path="/screening/hits", params(ScreeningHitQuery), responses((status=200, body=Vec<ScreeningHitResponse>, description="Screening hits, strongest match first"), (status=400, description="Unknown status")))]
+pub async fn list_screening_hits(
+    app_state: web::Data<AppState>,
+    query: web::Query<ScreeningHitQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let screening_service = ScreeningService::from(&app_state);
+
+    let response = screening_service.list_hits(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Review screening hit", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/screening/hits/{hit_id}/review", responses((status=200, body=ScreeningHitResponse, description="Decision recorded"), (status=400, description="Unknown decision or missing note"), (status=404, description="Hit not found"), (status=409, description="Hit already decided")))]
+pub async fn review_screening_hit(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    hit_id: web::Path<Uuid>,
+    payload: web::Json<ScreeningReviewRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let screening_service = ScreeningService::from(&app_state);
+
+    let response = screening_service
+        .review_hit(&claims, hit_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
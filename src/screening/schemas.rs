@@ -0,0 +1,98 @@
+use actix_multipart::form::{MultipartForm, bytes::Bytes, text::Text};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::screening::models::{ScreeningHitEntity, WatchlistVersionEntity};
+
+#[derive(Debug, MultipartForm, utoipa::ToSchema)]
+pub struct WatchlistUploadForm {
+    #[multipart(limit = "64MiB")]
+    #[schema(value_type = String, format = Binary)]
+    pub file: Bytes,
+    #[schema(value_type = String, example = "ofac_sdn")]
+    pub source: Text<String>,
+    #[schema(value_type = String, example = "ofac_sdn_csv")]
+    pub format: Text<String>,
+    // Defaults to sanctions for the published sanctions formats
+    #[schema(value_type = Option<String>, example = "sanctions")]
+    pub kind: Option<Text<String>>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct WatchlistVersionResponse {
+    pub id: Uuid,
+    pub source: String,
+    pub kind: String,
+    pub entry_count: i32,
+    pub is_active: bool,
+    pub loaded_by: Uuid,
+    pub loaded_at: DateTime<Utc>,
+}
+
+impl From<WatchlistVersionEntity> for WatchlistVersionResponse {
+    fn from(value: WatchlistVersionEntity) -> Self {
+        Self {
+            id: value.id,
+            source: value.source,
+            kind: value.kind.to_string(),
+            entry_count: value.entry_count,
+            is_active: value.is_active,
+            loaded_by: value.loaded_by,
+            loaded_at: value.loaded_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct WatchlistLoadResponse {
+    pub version: WatchlistVersionResponse,
+    pub customers_screened: usize,
+    pub hits_opened: usize,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct ScreeningHitQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct ScreeningReviewRequest {
+    #[schema(example = "dismiss")]
+    pub decision: String,
+    pub note: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ScreeningHitResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub list_source: String,
+    pub external_ref: String,
+    pub matched_name: String,
+    pub score: f64,
+    pub dob_match: String,
+    pub status: String,
+    pub reviewer_id: Option<Uuid>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ScreeningHitEntity> for ScreeningHitResponse {
+    fn from(value: ScreeningHitEntity) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            list_source: value.list_source,
+            external_ref: value.external_ref,
+            matched_name: value.matched_name,
+            score: value.score,
+            dob_match: value.dob_match.to_string(),
+            status: value.status.to_string(),
+            reviewer_id: value.reviewer_id,
+            review_note: value.review_note,
+            reviewed_at: value.reviewed_at,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use chrono::Utc;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::config::state::AppState;
+use crate::infra::pgdb::UnitofWork;
+use crate::screening::{
+    models::{
+        ScreeningDecision, ScreeningHitEntity, ScreeningHitStatus, ScreeningSubject,
+        WatchlistEntryEntity, WatchlistFormat, WatchlistKind, WatchlistVersionEntity, parse_source,
+    },
+    parser::parse_watchlist,
+    schemas::{
+        ScreeningHitQuery, ScreeningHitResponse, ScreeningReviewRequest, WatchlistLoadResponse,
+        WatchlistUploadForm, WatchlistVersionResponse,
+    },
+};
+use crate::user::models::AccessRole;
+
+pub struct ScreeningService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> ScreeningService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    // Replacing a list changes who gets flagged, so only superusers load them
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // Opens a hit for every listed entry the subject matches, returns how many are new
+    async fn open_hits(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        subject: &ScreeningSubject,
+        versions: &[WatchlistVersionEntity],
+        entries: &[WatchlistEntryEntity],
+    ) -> Result<usize, AppError> {
+        let mut hits_opened = 0;
+
+        for found in self.app_state.name_matcher.screen(subject, entries) {
+            let entry = &entries[found.entry_index];
+
+            let Some(version) = versions.iter().find(|v| v.id == entry.version_id) else {
+                continue;
+            };
+
+            let hit = ScreeningHitEntity::new(
+                subject.user_id,
+                version,
+                entry,
+                found.matched_name,
+                found.score,
+                found.dob_match,
+            );
+
+            if uow
+                .screening()
+                .create_hit(&hit)
+                .await
+                .to_app_err("Failed to save screening hit")?
+            {
+                tracing::warn!(
+                    user_id = %subject.user_id,
+                    list_source = %hit.list_source,
+                    external_ref = %hit.external_ref,
+                    score = hit.score,
+                    "Screening hit opened"
+                );
+                hits_opened += 1;
+            }
+        }
+
+        Ok(hits_opened)
+    }
+
+    // Runs inside the onboarding transaction so a customer is never left unscreened
+    #[tracing::instrument("Screen new customer", skip(self, uow, subject), fields(user_id = %subject.user_id))]
+    pub async fn screen_customer(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        subject: &ScreeningSubject,
+    ) -> Result<usize, AppError> {
+        let versions = uow
+            .screening()
+            .fetch_active_versions()
+            .await
+            .to_app_err("Failed to fetch watch list versions")?;
+
+        if versions.is_empty() {
+            return Ok(0);
+        }
+
+        let entries = uow
+            .screening()
+            .fetch_active_entries()
+            .await
+            .to_app_err("Failed to fetch watch list entries")?;
+
+        self.open_hits(uow, subject, &versions, &entries).await
+    }
+
+    #[tracing::instrument("Load watch list", skip(self, claims, form))]
+    pub async fn load_watchlist(
+        &self,
+        claims: &SessionClaims,
+        form: WatchlistUploadForm,
+    ) -> Result<WatchlistLoadResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let source = parse_source(&form.source)?;
+        let format = WatchlistFormat::from_str(&form.format)?;
+        let kind = match form.kind {
+            Some(kind) => WatchlistKind::from_str(&kind)?,
+            None => format
+                .default_kind()
+                .ok_or(ValidationError::MissingField("kind".into()))?,
+        };
+
+        let bytes = form.file.data;
+        let parsed = parse_watchlist(format, &bytes)?;
+
+        let version =
+            WatchlistVersionEntity::new(source, kind, &bytes, parsed.len(), *claims.get_user_id());
+
+        let entries: Vec<WatchlistEntryEntity> = parsed
+            .into_iter()
+            .map(|p| WatchlistEntryEntity::new(version.id, p))
+            .collect();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if uow
+            .screening()
+            .fetch_version_by_checksum(&version.source, &version.checksum)
+            .await
+            .to_app_err("Failed to fetch watch list version")?
+            .is_some()
+        {
+            Err(DomainError::Duplicate("watch list version".into()))?
+        }
+
+        uow.screening()
+            .create_version(&version)
+            .await
+            .to_app_err("Failed to save watch list version")?;
+
+        uow.screening()
+            .create_entries(&entries)
+            .await
+            .to_app_err("Failed to save watch list entries")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit watch list load")?;
+
+        tracing::info!(
+            version_id = %version.id,
+            source = %version.source,
+            entries = entries.len(),
+            "Watch list loaded"
+        );
+
+        // A new version can list people who are already customers
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let subjects = uow
+            .screening()
+            .fetch_subjects()
+            .await
+            .to_app_err("Failed to fetch customers to screen")?;
+
+        let mut hits_opened = 0;
+
+        for subject in &subjects {
+            hits_opened += self
+                .open_hits(&mut uow, subject, std::slice::from_ref(&version), &entries)
+                .await?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit customer rescreening")?;
+
+        Ok(WatchlistLoadResponse {
+            version: WatchlistVersionResponse::from(version),
+            customers_screened: subjects.len(),
+            hits_opened,
+        })
+    }
+
+    #[tracing::instrument("List watch lists", skip(self))]
+    pub async fn list_watchlists(&self) -> Result<Vec<WatchlistVersionResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let versions = uow
+            .screening()
+            .fetch_versions()
+            .await
+            .to_app_err("Failed to fetch watch list versions")?;
+
+        Ok(versions
+            .into_iter()
+            .map(WatchlistVersionResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("List screening hits", skip(self))]
+    pub async fn list_hits(
+        &self,
+        query: ScreeningHitQuery,
+    ) -> Result<Vec<ScreeningHitResponse>, AppError> {
+        let status = query
+            .status
+            .as_deref()
+            .map(ScreeningHitStatus::from_str)
+            .transpose()?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let hits = uow
+            .screening()
+            .fetch_hits(status)
+            .await
+            .to_app_err("Failed to fetch screening hits")?;
+
+        Ok(hits.into_iter().map(ScreeningHitResponse::from).collect())
+    }
+
+    #[tracing::instrument("Review screening hit", skip(self, claims, request))]
+    pub async fn review_hit(
+        &self,
+        claims: &SessionClaims,
+        hit_id: Uuid,
+        request: ScreeningReviewRequest,
+    ) -> Result<ScreeningHitResponse, AppError> {
+        let decision = ScreeningDecision::from_str(&request.decision)?;
+
+        // Both outcomes have to be justified for the audit trail
+        let note = request.note.trim();
+        if note.is_empty() {
+            Err(ValidationError::MissingField("note".into()))?
+        }
+
+        let reviewer_id = *claims.get_user_id();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let hit = match uow
+            .screening()
+            .fetch_hit(hit_id)
+            .await
+            .to_app_err("Failed to fetch screening hit")?
+        {
+            Some(h) => h,
+            None => Err(DomainError::NotFound("screening hit".into()))?,
+        };
+
+        let status = hit.status.apply(decision)?;
+
+        if uow
+            .screening()
+            .decide_hit(hit.id, status, reviewer_id, note)
+            .await
+            .to_app_err("Failed to decide screening hit")?
+            == 0
+        {
+            Err(DomainError::InvalidState(
+                "screening hit was decided by someone else".into(),
+            ))?
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit screening review")?;
+
+        tracing::info!(%hit_id, %reviewer_id, %decision, "Screening hit reviewed");
+
+        Ok(ScreeningHitResponse::from(ScreeningHitEntity {
+            status,
+            reviewer_id: Some(reviewer_id),
+            review_note: Some(note.to_string()),
+            reviewed_at: Some(Utc::now()),
+            ..hit
+        }))
+    }
+}
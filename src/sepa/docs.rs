@@ -0,0 +1,18 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::sepa::routes::create_sepa_transfer,
+    crate::sepa::routes::list_sepa_transfers,
+    crate::sepa::routes::fetch_sepa_transfer,
+))]
+pub struct SepaTransferApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::sepa::routes::generate_sepa_batch,
+    crate::sepa::routes::list_sepa_batches,
+    crate::sepa::routes::fetch_sepa_batch,
+    crate::sepa::routes::import_sepa_status_reports,
+))]
+pub struct SepaBatchApi;
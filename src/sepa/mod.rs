@@ -0,0 +1,8 @@
+pub mod docs;
+pub mod models;
+pub mod pain001;
+pub mod pain002;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,449 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc, Weekday};
+use std::path::PathBuf;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::base::error::{DomainError, ValidationError};
+use crate::beneficiary::models::BeneficiaryEntity;
+use crate::sepa::schemas::SepaTransferRequest;
+
+// Outbound transfers sit here between the customer debit and the clearing house settling them
+pub const SEPA_CLEARING_COA: &str = "1040";
+pub const SEPA_CURRENCY: &str = "EUR";
+
+const MAX_REMITTANCE_LEN: usize = 140;
+// Largest amount the EPC rulebook allows in a single credit transfer
+const MAX_AMOUNT_CENTS: i64 = 99_999_999_999;
+
+// Countries in the SEPA scheme, members of the EEA plus the participating non-EEA states
+const SEPA_COUNTRIES: &[&str] = &[
+    "AD", "AT", "BE", "BG", "CH", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GB", "GI", "GR",
+    "HR", "HU", "IE", "IS", "IT", "LI", "LT", "LU", "LV", "MC", "MT", "NL", "NO", "PL", "PT", "RO",
+    "SE", "SI", "SK", "SM", "VA",
+];
+
+// The EPC basic Latin set, the only characters every SEPA bank has to accept
+pub fn is_sepa_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " /-?:().,'+".contains(c)
+}
+
+// Names come from customer records, accented letters are spelled out and anything else dropped
+pub fn sepa_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            c if is_sepa_char(c) => text.push(c),
+            'À'..='Å' => text.push('A'),
+            'à'..='å' => text.push('a'),
+            'Æ' => text.push_str("AE"),
+            'æ' => text.push_str("ae"),
+            'Ç' => text.push('C'),
+            'ç' => text.push('c'),
+            'È'..='Ë' => text.push('E'),
+            'è'..='ë' => text.push('e'),
+            'Ì'..='Ï' => text.push('I'),
+            'ì'..='ï' => text.push('i'),
+            'Ñ' => text.push('N'),
+            'ñ' => text.push('n'),
+            'Ò'..='Ö' | 'Ø' => text.push('O'),
+            'ò'..='ö' | 'ø' => text.push('o'),
+            'Ù'..='Ü' => text.push('U'),
+            'ù'..='ü' => text.push('u'),
+            'Ý' => text.push('Y'),
+            'ý' | 'ÿ' => text.push('y'),
+            'ß' => text.push_str("ss"),
+            '&' => text.push('+'),
+            _ => text.push(' '),
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 1234 cents is written as 12.34
+pub fn format_amount(amount_cents: i64) -> String {
+    format!("{}.{:02}", amount_cents / 100, amount_cents % 100)
+}
+
+#[derive(Debug, Clone)]
+pub struct SepaProfile {
+    pub initiating_party: String,
+    pub debtor_agent_bic: String,
+    pub outbound_dir: Option<PathBuf>,
+    pub status_report_dir: Option<PathBuf>,
+}
+
+impl SepaProfile {
+    pub fn new(
+        initiating_party: &str,
+        debtor_agent_bic: &str,
+        outbound_dir: Option<&str>,
+        status_report_dir: Option<&str>,
+    ) -> Self {
+        Self {
+            initiating_party: sepa_text(initiating_party),
+            debtor_agent_bic: debtor_agent_bic.trim().to_uppercase(),
+            outbound_dir: outbound_dir.map(PathBuf::from),
+            status_report_dir: status_report_dir.map(PathBuf::from),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "sepa_transfer_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SepaTransferStatus {
+    Queued,
+    Batched,
+    Accepted,
+    Returned,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "sepa_batch_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SepaBatchStatus {
+    Generated,
+    Accepted,
+    PartiallyAccepted,
+    Rejected,
+}
+
+impl FromStr for SepaBatchStatus {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "generated" => Ok(Self::Generated),
+            "accepted" => Ok(Self::Accepted),
+            "partially_accepted" => Ok(Self::PartiallyAccepted),
+            "rejected" => Ok(Self::Rejected),
+            _ => Err(ValidationError::InvalidValue {
+                field: "status".into(),
+                reason: "Use generated, accepted, partially_accepted or rejected".into(),
+            }),
+        }
+    }
+}
+
+impl SepaBatchStatus {
+    // Once every transfer in the batch is settled one way or the other
+    pub fn settled(n_accepted: i64, n_returned: i64) -> Self {
+        match (n_accepted, n_returned) {
+            (_, 0) => Self::Accepted,
+            (0, _) => Self::Rejected,
+            _ => Self::PartiallyAccepted,
+        }
+    }
+}
+
+// What the customer asked for, checked before any account is touched
+#[derive(Debug)]
+pub struct TransferInstruction {
+    pub amount_cents: i64,
+    pub remittance_info: Option<String>,
+}
+
+impl TransferInstruction {
+    pub fn parse(request: &SepaTransferRequest) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 || request.amount_cents > MAX_AMOUNT_CENTS {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: format!("Must be between 1 and {}", MAX_AMOUNT_CENTS),
+            });
+        }
+
+        let remittance_info = match request.remittance_info.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(info) if info.chars().count() > MAX_REMITTANCE_LEN => {
+                return Err(ValidationError::TooLong {
+                    field: "remittance_info".into(),
+                    max: MAX_REMITTANCE_LEN,
+                });
+            }
+            Some(info) if !info.chars().all(is_sepa_char) => {
+                return Err(ValidationError::InvalidValue {
+                    field: "remittance_info".into(),
+                    reason: "Only letters, digits, spaces and / - ? : ( ) . , ' + are allowed"
+                        .into(),
+                });
+            }
+            Some(info) => Some(info.to_string()),
+        };
+
+        Ok(Self {
+            amount_cents: request.amount_cents,
+            remittance_info,
+        })
+    }
+}
+
+// The creditor as it goes on the wire, only euro beneficiaries with an IBAN in the scheme qualify
+#[derive(Debug)]
+pub struct Creditor {
+    pub name: String,
+    pub iban: String,
+    pub bic: Option<String>,
+}
+
+impl Creditor {
+    pub fn from_beneficiary(beneficiary: &BeneficiaryEntity) -> Result<Self, DomainError> {
+        let iban = match &beneficiary.iban {
+            Some(iban) if SEPA_COUNTRIES.contains(&&iban[..2]) => iban.clone(),
+            _ => {
+                return Err(DomainError::ConstraintViolation(
+                    "beneficiary has no IBAN in the SEPA area".into(),
+                ));
+            }
+        };
+
+        if beneficiary.currency != SEPA_CURRENCY {
+            return Err(DomainError::ConstraintViolation(format!(
+                "beneficiary is paid in {}, SEPA transfers are in {}",
+                beneficiary.currency, SEPA_CURRENCY
+            )));
+        }
+
+        Ok(Self {
+            name: sepa_text(&beneficiary.name),
+            iban,
+            bic: beneficiary.bic.clone(),
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct DebtorAccountEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub iban: String,
+    pub currency: String,
+    pub status: UserAccountStatus,
+}
+
+impl DebtorAccountEntity {
+    pub fn check_debit(
+        &self,
+        user_id: Uuid,
+        amount_cents: i64,
+        available_cents: i64,
+    ) -> Result<(), DomainError> {
+        if self.user_id != user_id {
+            return Err(DomainError::NotFound("account".into()));
+        }
+
+        if !matches!(self.status, UserAccountStatus::Active) {
+            return Err(DomainError::InvalidState(format!(
+                "account is {}",
+                self.status
+            )));
+        }
+
+        if self.currency != SEPA_CURRENCY {
+            return Err(DomainError::ConstraintViolation(format!(
+                "account is held in {}, SEPA transfers are in {}",
+                self.currency, SEPA_CURRENCY
+            )));
+        }
+
+        if amount_cents > available_cents {
+            return Err(DomainError::ConstraintViolation(
+                "insufficient available funds".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// A fresh end-to-end id doubles as the journal reference of the customer debit
+pub fn new_end_to_end_id() -> String {
+    format!("E2E{}", Uuid::now_v7().simple().to_string().to_uppercase())
+}
+
+// Reference of the entry crediting a returned transfer back to the customer
+pub fn return_reference(end_to_end_id: &str) -> String {
+    format!("{}-RT", end_to_end_id)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SepaTransferEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub creditor_name: String,
+    pub creditor_iban: String,
+    pub creditor_bic: Option<String>,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub end_to_end_id: String,
+    pub remittance_info: Option<String>,
+    pub status: SepaTransferStatus,
+    pub batch_id: Option<Uuid>,
+    pub payment_info_id: Option<String>,
+    pub journal_entry_id: Uuid,
+    pub return_entry_id: Option<Uuid>,
+    pub return_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SepaTransferEntity {
+    pub fn queued(
+        account: &DebtorAccountEntity,
+        beneficiary_id: Uuid,
+        creditor: Creditor,
+        instruction: TransferInstruction,
+        end_to_end_id: String,
+        journal_entry_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id: account.user_id,
+            account_id: account.id,
+            beneficiary_id,
+            creditor_name: creditor.name,
+            creditor_iban: creditor.iban,
+            creditor_bic: creditor.bic,
+            amount_cents: instruction.amount_cents,
+            currency: SEPA_CURRENCY.into(),
+            end_to_end_id,
+            remittance_info: instruction.remittance_info,
+            status: SepaTransferStatus::Queued,
+            batch_id: None,
+            payment_info_id: None,
+            journal_entry_id,
+            return_entry_id: None,
+            return_reason: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+// A queued transfer with the debtor details the pain.001 file needs
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct QueuedTransferEntity {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub debtor_name: String,
+    pub debtor_iban: String,
+    pub creditor_name: String,
+    pub creditor_iban: String,
+    pub creditor_bic: Option<String>,
+    pub amount_cents: i64,
+    pub end_to_end_id: String,
+    pub remittance_info: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SepaBatchEntity {
+    pub id: Uuid,
+    pub message_id: String,
+    pub status: SepaBatchStatus,
+    pub n_transactions: i32,
+    pub control_sum_cents: i64,
+    pub file_location: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub status_updated_at: Option<DateTime<Utc>>,
+}
+
+impl SepaBatchEntity {
+    pub fn new(transfers: &[QueuedTransferEntity], created_by: Uuid, now: DateTime<Utc>) -> Self {
+        let id = Uuid::now_v7();
+
+        Self {
+            id,
+            // Leaves room for the payment information suffix within the 35 characters allowed
+            message_id: format!("THAL{}", &id.simple().to_string().to_uppercase()[..20]),
+            status: SepaBatchStatus::Generated,
+            n_transactions: transfers.len() as i32,
+            control_sum_cents: transfers.iter().map(|t| t.amount_cents).sum(),
+            file_location: String::new(),
+            created_by: Some(created_by),
+            created_at: now,
+            status_updated_at: None,
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("pain001_{}.xml", self.message_id)
+    }
+
+    // Transfers go out on the next business day, weekends are skipped
+    pub fn execution_date(&self) -> NaiveDate {
+        let mut date = self.created_at.date_naive() + Days::new(1);
+        while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            date = date + Days::new(1);
+        }
+
+        date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(amount_cents: i64, remittance_info: Option<&str>) -> SepaTransferRequest {
+        SepaTransferRequest {
+            account_id: Uuid::now_v7(),
+            beneficiary_id: Uuid::now_v7(),
+            amount_cents,
+            remittance_info: remittance_info.map(String::from),
+        }
+    }
+
+    #[test]
+    fn names_are_reduced_to_the_sepa_character_set() {
+        assert_eq!(sepa_text("Jürgen  Müller & Söhne"), "Jurgen Muller + Sohne");
+        assert_eq!(sepa_text("Straße 5 #2"), "Strasse 5 2");
+        assert_eq!(sepa_text("O'Brien-Smith (Ltd.)"), "O'Brien-Smith (Ltd.)");
+    }
+
+    #[test]
+    fn remittance_information_is_checked_against_the_sepa_character_set() {
+        let instruction =
+            TransferInstruction::parse(&request(1_000, Some(" INV 2025/07 "))).unwrap();
+        assert_eq!(instruction.remittance_info.as_deref(), Some("INV 2025/07"));
+
+        assert!(TransferInstruction::parse(&request(1_000, Some("Rent €"))).is_err());
+        assert!(TransferInstruction::parse(&request(1_000, Some(&"X".repeat(141)))).is_err());
+        assert!(TransferInstruction::parse(&request(0, None)).is_err());
+        assert!(TransferInstruction::parse(&request(MAX_AMOUNT_CENTS + 1, None)).is_err());
+    }
+
+    #[test]
+    fn batches_settle_by_what_was_returned() {
+        assert_eq!(SepaBatchStatus::settled(3, 0), SepaBatchStatus::Accepted);
+        assert_eq!(SepaBatchStatus::settled(0, 3), SepaBatchStatus::Rejected);
+        assert_eq!(
+            SepaBatchStatus::settled(2, 1),
+            SepaBatchStatus::PartiallyAccepted
+        );
+    }
+
+    #[test]
+    fn execution_skips_the_weekend() {
+        let mut batch = SepaBatchEntity::new(&[], Uuid::now_v7(), Utc::now());
+
+        // Friday
+        batch.created_at = "2025-12-12T15:00:00Z".parse().unwrap();
+        assert_eq!(batch.execution_date().to_string(), "2025-12-15");
+
+        batch.created_at = "2025-12-15T15:00:00Z".parse().unwrap();
+        assert_eq!(batch.execution_date().to_string(), "2025-12-16");
+    }
+}
@@ -0,0 +1,462 @@
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use std::io;
+use uuid::Uuid;
+
+use crate::base::error::ValidationError;
+use crate::sepa::models::{
+    QueuedTransferEntity, SEPA_CURRENCY, SepaProfile, format_amount, is_sepa_char, sepa_text,
+};
+
+const NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:pain.001.001.09";
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+// Max35Text, and the EPC guidelines cap party names at 70 of the 140 the schema allows
+const MAX_ID_LEN: usize = 35;
+const MAX_NAME_LEN: usize = 70;
+const MAX_REMITTANCE_LEN: usize = 140;
+
+#[derive(Debug)]
+pub struct CreditTransfer {
+    pub transfer_id: Uuid,
+    pub end_to_end_id: String,
+    pub amount_cents: i64,
+    pub creditor_name: String,
+    pub creditor_iban: String,
+    pub creditor_bic: Option<String>,
+    pub remittance_info: Option<String>,
+}
+
+// One <PmtInf> block per debtor account
+#[derive(Debug)]
+pub struct PaymentInfo {
+    pub id: String,
+    pub account_id: Uuid,
+    pub debtor_name: String,
+    pub debtor_iban: String,
+    pub transfers: Vec<CreditTransfer>,
+}
+
+impl PaymentInfo {
+    fn control_sum_cents(&self) -> i64 {
+        self.transfers.iter().map(|t| t.amount_cents).sum()
+    }
+}
+
+#[derive(Debug)]
+pub struct Pain001 {
+    pub message_id: String,
+    pub created_at: DateTime<Utc>,
+    pub initiating_party: String,
+    pub debtor_agent_bic: String,
+    pub execution_date: NaiveDate,
+    pub payment_infos: Vec<PaymentInfo>,
+}
+
+impl Pain001 {
+    pub fn new(
+        message_id: &str,
+        created_at: DateTime<Utc>,
+        execution_date: NaiveDate,
+        profile: &SepaProfile,
+        transfers: Vec<QueuedTransferEntity>,
+    ) -> Self {
+        let mut payment_infos: Vec<PaymentInfo> = Vec::new();
+
+        for transfer in transfers {
+            let credit_transfer = CreditTransfer {
+                transfer_id: transfer.id,
+                end_to_end_id: transfer.end_to_end_id,
+                amount_cents: transfer.amount_cents,
+                creditor_name: transfer.creditor_name,
+                creditor_iban: transfer.creditor_iban,
+                creditor_bic: transfer.creditor_bic,
+                remittance_info: transfer.remittance_info,
+            };
+
+            match payment_infos
+                .iter_mut()
+                .find(|p| p.account_id == transfer.account_id)
+            {
+                Some(payment_info) => payment_info.transfers.push(credit_transfer),
+                None => payment_infos.push(PaymentInfo {
+                    id: format!("{}-{}", message_id, payment_infos.len() + 1),
+                    account_id: transfer.account_id,
+                    debtor_name: sepa_text(&transfer.debtor_name),
+                    debtor_iban: transfer.debtor_iban,
+                    transfers: vec![credit_transfer],
+                }),
+            }
+        }
+
+        Self {
+            message_id: message_id.to_string(),
+            created_at,
+            initiating_party: profile.initiating_party.clone(),
+            debtor_agent_bic: profile.debtor_agent_bic.clone(),
+            execution_date,
+            payment_infos,
+        }
+    }
+
+    pub fn n_transactions(&self) -> usize {
+        self.payment_infos.iter().map(|p| p.transfers.len()).sum()
+    }
+
+    pub fn control_sum_cents(&self) -> i64 {
+        self.payment_infos
+            .iter()
+            .map(PaymentInfo::control_sum_cents)
+            .sum()
+    }
+
+    // The restrictions pain.001.001.09 puts on the values written, and the tighter ones of the
+    // EPC implementation guidelines, checked before a file leaves the bank
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_id("MsgId", &self.message_id)?;
+        check_text("InitgPty/Nm", &self.initiating_party, MAX_NAME_LEN)?;
+        check_bic("DbtrAgt/BICFI", &self.debtor_agent_bic)?;
+
+        if self.payment_infos.is_empty() {
+            return Err(invalid("PmtInf", "A message needs at least one payment"));
+        }
+
+        for payment_info in &self.payment_infos {
+            check_id("PmtInfId", &payment_info.id)?;
+            check_text("Dbtr/Nm", &payment_info.debtor_name, MAX_NAME_LEN)?;
+            check_iban("DbtrAcct/IBAN", &payment_info.debtor_iban)?;
+
+            if payment_info.transfers.is_empty() {
+                return Err(invalid(
+                    "CdtTrfTxInf",
+                    "A payment needs at least one transfer",
+                ));
+            }
+
+            for transfer in &payment_info.transfers {
+                check_id("EndToEndId", &transfer.end_to_end_id)?;
+                check_text("Cdtr/Nm", &transfer.creditor_name, MAX_NAME_LEN)?;
+                check_iban("CdtrAcct/IBAN", &transfer.creditor_iban)?;
+
+                if let Some(bic) = &transfer.creditor_bic {
+                    check_bic("CdtrAgt/BICFI", bic)?;
+                }
+                if let Some(info) = &transfer.remittance_info {
+                    check_text("RmtInf/Ustrd", info, MAX_REMITTANCE_LEN)?;
+                }
+                if !(1..=99_999_999_999).contains(&transfer.amount_cents) {
+                    return Err(invalid("InstdAmt", "Must be between 0.01 and 999999999.99"));
+                }
+            }
+        }
+
+        // CtrlSum allows 18 digits in total, two of them for the cents
+        if self.control_sum_cents().to_string().len() > 18 {
+            return Err(invalid("CtrlSum", "More than 18 digits"));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_xml(&self) -> io::Result<Vec<u8>> {
+        let mut xml = XmlWriter(Writer::new_with_indent(Vec::new(), b' ', 2));
+
+        xml.0
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        xml.0.write_event(Event::Start(
+            BytesStart::new("Document")
+                .with_attributes([("xmlns", NAMESPACE), ("xmlns:xsi", XSI_NAMESPACE)]),
+        ))?;
+        xml.start("CstmrCdtTrfInitn")?;
+
+        xml.start("GrpHdr")?;
+        xml.text("MsgId", &self.message_id)?;
+        xml.text(
+            "CreDtTm",
+            &self.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )?;
+        xml.text("NbOfTxs", &self.n_transactions().to_string())?;
+        xml.text("CtrlSum", &format_amount(self.control_sum_cents()))?;
+        xml.start("InitgPty")?;
+        xml.text("Nm", &self.initiating_party)?;
+        xml.end("InitgPty")?;
+        xml.end("GrpHdr")?;
+
+        for payment_info in &self.payment_infos {
+            xml.start("PmtInf")?;
+            xml.text("PmtInfId", &payment_info.id)?;
+            xml.text("PmtMtd", "TRF")?;
+            xml.text("NbOfTxs", &payment_info.transfers.len().to_string())?;
+            xml.text("CtrlSum", &format_amount(payment_info.control_sum_cents()))?;
+            xml.start("PmtTpInf")?;
+            xml.start("SvcLvl")?;
+            xml.text("Cd", "SEPA")?;
+            xml.end("SvcLvl")?;
+            xml.end("PmtTpInf")?;
+            xml.start("ReqdExctnDt")?;
+            xml.text("Dt", &self.execution_date.to_string())?;
+            xml.end("ReqdExctnDt")?;
+            xml.start("Dbtr")?;
+            xml.text("Nm", &payment_info.debtor_name)?;
+            xml.end("Dbtr")?;
+            xml.account("DbtrAcct", &payment_info.debtor_iban)?;
+            xml.agent("DbtrAgt", &self.debtor_agent_bic)?;
+            xml.text("ChrgBr", "SLEV")?;
+
+            for transfer in &payment_info.transfers {
+                xml.start("CdtTrfTxInf")?;
+                xml.start("PmtId")?;
+                xml.text("EndToEndId", &transfer.end_to_end_id)?;
+                xml.end("PmtId")?;
+                xml.start("Amt")?;
+                xml.0
+                    .create_element("InstdAmt")
+                    .with_attribute(("Ccy", SEPA_CURRENCY))
+                    .write_text_content(BytesText::new(&format_amount(transfer.amount_cents)))?;
+                xml.end("Amt")?;
+                if let Some(bic) = &transfer.creditor_bic {
+                    xml.agent("CdtrAgt", bic)?;
+                }
+                xml.start("Cdtr")?;
+                xml.text("Nm", &transfer.creditor_name)?;
+                xml.end("Cdtr")?;
+                xml.account("CdtrAcct", &transfer.creditor_iban)?;
+                if let Some(info) = &transfer.remittance_info {
+                    xml.start("RmtInf")?;
+                    xml.text("Ustrd", info)?;
+                    xml.end("RmtInf")?;
+                }
+                xml.end("CdtTrfTxInf")?;
+            }
+
+            xml.end("PmtInf")?;
+        }
+
+        xml.end("CstmrCdtTrfInitn")?;
+        xml.end("Document")?;
+
+        Ok(xml.0.into_inner())
+    }
+}
+
+struct XmlWriter(Writer<Vec<u8>>);
+
+impl XmlWriter {
+    fn start(&mut self, name: &str) -> io::Result<()> {
+        self.0.write_event(Event::Start(BytesStart::new(name)))
+    }
+
+    fn end(&mut self, name: &str) -> io::Result<()> {
+        self.0.write_event(Event::End(BytesEnd::new(name)))
+    }
+
+    fn text(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.0
+            .create_element(name)
+            .write_text_content(BytesText::new(value))?;
+        Ok(())
+    }
+
+    fn account(&mut self, name: &str, iban: &str) -> io::Result<()> {
+        self.start(name)?;
+        self.start("Id")?;
+        self.text("IBAN", iban)?;
+        self.end("Id")?;
+        self.end(name)
+    }
+
+    fn agent(&mut self, name: &str, bic: &str) -> io::Result<()> {
+        self.start(name)?;
+        self.start("FinInstnId")?;
+        self.text("BICFI", bic)?;
+        self.end("FinInstnId")?;
+        self.end(name)
+    }
+}
+
+fn invalid(field: &str, reason: &str) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+fn check_text(field: &str, value: &str, max: usize) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Err(ValidationError::MissingField(field.into()));
+    }
+    if value.chars().count() > max {
+        return Err(ValidationError::TooLong {
+            field: field.into(),
+            max,
+        });
+    }
+    if !value.chars().all(is_sepa_char) {
+        return Err(invalid(field, "Outside the SEPA character set"));
+    }
+
+    Ok(())
+}
+
+// Identifiers also may not start or end with a slash or hold two in a row
+fn check_id(field: &str, value: &str) -> Result<(), ValidationError> {
+    check_text(field, value, MAX_ID_LEN)?;
+
+    if value.starts_with('/') || value.ends_with('/') || value.contains("//") {
+        return Err(invalid(field, "Misplaced slash"));
+    }
+
+    Ok(())
+}
+
+// IBAN2007Identifier, [A-Z]{2}[0-9]{2}[a-zA-Z0-9]{1,30}
+fn check_iban(field: &str, value: &str) -> Result<(), ValidationError> {
+    let well_formed = (5..=34).contains(&value.len())
+        && value[..2].chars().all(|c| c.is_ascii_uppercase())
+        && value[2..4].chars().all(|c| c.is_ascii_digit())
+        && value[4..].chars().all(|c| c.is_ascii_alphanumeric());
+
+    if !well_formed {
+        return Err(ValidationError::InvalidFormat(field.into()));
+    }
+
+    Ok(())
+}
+
+// BICFIDec2014Identifier, [A-Z0-9]{4}[A-Z]{2}[A-Z0-9]{2}([A-Z0-9]{3}){0,1}
+fn check_bic(field: &str, value: &str) -> Result<(), ValidationError> {
+    let upper_alnum = |c: char| c.is_ascii_uppercase() || c.is_ascii_digit();
+    let well_formed = matches!(value.len(), 8 | 11)
+        && value.chars().all(upper_alnum)
+        && value[4..6].chars().all(|c| c.is_ascii_uppercase());
+
+    if !well_formed {
+        return Err(ValidationError::InvalidFormat(field.into()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::xsd::assert_schema_valid;
+
+    fn transfer(account_id: Uuid, amount_cents: i64, creditor: &str) -> QueuedTransferEntity {
+        QueuedTransferEntity {
+            id: Uuid::now_v7(),
+            account_id,
+            debtor_name: "Zoë Ångström".into(),
+            debtor_iban: "DE12345678901234567890".into(),
+            creditor_name: creditor.into(),
+            creditor_iban: "DE89370400440532013000".into(),
+            creditor_bic: Some("COBADEFFXXX".into()),
+            amount_cents,
+            end_to_end_id: format!("E2E{}", Uuid::now_v7().simple()),
+            remittance_info: Some("INV 2025/07".into()),
+        }
+    }
+
+    fn message(transfers: Vec<QueuedTransferEntity>) -> Pain001 {
+        let profile = SepaProfile::new("Thalia Corp.", "THALDEFFXXX", None, None);
+
+        Pain001::new(
+            "THAL0123456789ABCDEF",
+            "2025-12-16T09:30:00Z".parse().unwrap(),
+            "2025-12-17".parse().unwrap(),
+            &profile,
+            transfers,
+        )
+    }
+
+    #[test]
+    fn transfers_are_grouped_by_debtor_account() {
+        let (first, second) = (Uuid::now_v7(), Uuid::now_v7());
+        let pain = message(vec![
+            transfer(first, 10_050, "Jane Doe"),
+            transfer(second, 2_500, "John Roe"),
+            transfer(first, 99, "Max Mustermann"),
+        ]);
+
+        assert_eq!(pain.payment_infos.len(), 2);
+        assert_eq!(pain.payment_infos[0].id, "THAL0123456789ABCDEF-1");
+        assert_eq!(pain.payment_infos[0].transfers.len(), 2);
+        assert_eq!(pain.payment_infos[0].debtor_name, "Zoe Angstrom");
+        assert_eq!(pain.n_transactions(), 3);
+        assert_eq!(pain.control_sum_cents(), 12_649);
+        assert!(pain.validate().is_ok());
+    }
+
+    #[test]
+    fn the_message_follows_the_schema_layout() {
+        let pain = message(vec![transfer(Uuid::now_v7(), 10_050, "Jane Doe")]);
+        let xml = String::from_utf8(pain.to_xml().unwrap()).unwrap();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.001.001.09\""));
+        assert!(xml.contains("<CreDtTm>2025-12-16T09:30:00Z</CreDtTm>"));
+        assert!(xml.contains("<CtrlSum>100.50</CtrlSum>"));
+        assert!(xml.contains("<InstdAmt Ccy=\"EUR\">100.50</InstdAmt>"));
+        assert!(xml.contains("<Dt>2025-12-17</Dt>"));
+
+        let order = [
+            "<GrpHdr>",
+            "<PmtInf>",
+            "<PmtInfId>",
+            "<PmtMtd>TRF</PmtMtd>",
+            "<ReqdExctnDt>",
+            "<Dbtr>",
+            "<DbtrAcct>",
+            "<DbtrAgt>",
+            "<ChrgBr>SLEV</ChrgBr>",
+            "<CdtTrfTxInf>",
+            "<EndToEndId>",
+            "<Amt>",
+            "<CdtrAgt>",
+            "<Cdtr>",
+            "<CdtrAcct>",
+            "<RmtInf>",
+        ];
+        let positions: Vec<usize> = order.iter().map(|tag| xml.find(tag).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    #[ignore = "needs tests/fixtures/iso20022/pain.001.001.09.xsd"]
+    fn the_message_is_valid_against_the_official_schema() {
+        let first = Uuid::now_v7();
+        let mut without_bic = transfer(Uuid::now_v7(), 2_500, "John Roe");
+        without_bic.creditor_bic = None;
+        without_bic.remittance_info = None;
+        let pain = message(vec![
+            transfer(first, 10_050, "Jane Doe"),
+            without_bic,
+            transfer(first, 99, "Max Mustermann"),
+        ]);
+
+        assert!(pain.validate().is_ok());
+        assert_schema_valid("pain.001.001.09.xsd", &pain.to_xml().unwrap());
+    }
+
+    #[test]
+    fn values_outside_the_schema_are_refused() {
+        let mut pain = message(vec![transfer(Uuid::now_v7(), 10_050, "Jane Doe")]);
+        pain.payment_infos[0].transfers[0].creditor_iban = "de89370400440532013000".into();
+        assert!(pain.validate().is_err());
+
+        let mut pain = message(vec![transfer(Uuid::now_v7(), 10_050, "Jane Doe")]);
+        pain.payment_infos[0].transfers[0].creditor_bic = Some("COBADEF".into());
+        assert!(pain.validate().is_err());
+
+        let mut pain = message(vec![transfer(Uuid::now_v7(), 10_050, "Jane Doe")]);
+        pain.payment_infos[0].transfers[0].end_to_end_id = "A".repeat(36);
+        assert!(pain.validate().is_err());
+
+        let mut pain = message(vec![transfer(Uuid::now_v7(), 10_050, "Jane Doe")]);
+        pain.payment_infos[0].transfers[0].creditor_name = "Jane & Doe".into();
+        assert!(pain.validate().is_err());
+
+        assert!(message(vec![]).validate().is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use quick_xml::events::Event;
+
+use crate::base::error::ValidationError;
+
+// Group statuses that only say the file arrived or passed syntax checks, settlement comes later
+const INTERIM_STATUSES: &[&str] = &["RCVD", "PDNG", "ACTC"];
+const REJECTED: &str = "RJCT";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StatusEntry {
+    pub id: String,
+    pub status: Option<String>,
+    pub reason: Option<String>,
+}
+
+// A pain.002 customer payment status report, reduced to what settles the original transfers
+#[derive(Debug, Default, PartialEq)]
+pub struct StatusReport {
+    pub original_message_id: String,
+    pub group: StatusEntry,
+    pub payment_infos: Vec<StatusEntry>,
+    pub transactions: Vec<StatusEntry>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TransferOutcome {
+    Accepted,
+    Returned(Option<String>),
+}
+
+fn is_rejected(entry: &StatusEntry) -> bool {
+    entry.status.as_deref() == Some(REJECTED)
+}
+
+impl StatusReport {
+    // Interim reports can still reject transfers but never accept any
+    pub fn is_final(&self) -> bool {
+        !self
+            .group
+            .status
+            .as_deref()
+            .is_some_and(|s| INTERIM_STATUSES.contains(&s))
+    }
+
+    // Rejections apply from the narrowest level reported, the whole group down to one transaction
+    pub fn outcome(&self, end_to_end_id: &str, payment_info_id: &str) -> Option<TransferOutcome> {
+        if let Some(tx) = self.transactions.iter().find(|t| t.id == end_to_end_id)
+            && is_rejected(tx)
+        {
+            return Some(TransferOutcome::Returned(tx.reason.clone()));
+        }
+
+        if let Some(pmt) = self.payment_infos.iter().find(|p| p.id == payment_info_id)
+            && is_rejected(pmt)
+        {
+            return Some(TransferOutcome::Returned(pmt.reason.clone()));
+        }
+
+        if is_rejected(&self.group) {
+            return Some(TransferOutcome::Returned(self.group.reason.clone()));
+        }
+
+        self.is_final().then_some(TransferOutcome::Accepted)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    Group,
+    PaymentInfo,
+    Transaction,
+}
+
+fn invalid_file(reason: String) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: "file".into(),
+        reason,
+    }
+}
+
+// Any pain.002 version, elements are matched by local name and the namespace ignored
+pub fn parse_status_report(bytes: &[u8]) -> Result<StatusReport, ValidationError> {
+    let mut reader = quick_xml::Reader::from_reader(bytes);
+    reader.config_mut().trim_text(true);
+
+    let mut report = StatusReport::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut scope: Option<Scope> = None;
+    let mut payment_info = StatusEntry::default();
+    let mut transaction = StatusEntry::default();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| invalid_file(format!("Malformed pain.002: {}", e)))?;
+
+        match event {
+            Event::Start(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "OrgnlGrpInfAndSts" => scope = Some(Scope::Group),
+                    "OrgnlPmtInfAndSts" => {
+                        scope = Some(Scope::PaymentInfo);
+                        payment_info = StatusEntry::default();
+                    }
+                    // Transactions may sit inside or outside their payment information block
+                    "TxInfAndSts" => {
+                        scope = Some(Scope::Transaction);
+                        transaction = StatusEntry::default();
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Text(ref e) => {
+                let text = e
+                    .decode()
+                    .map_err(|e| invalid_file(format!("Malformed pain.002: {}", e)))?
+                    .trim()
+                    .to_string();
+                let element = path.last().map(String::as_str).unwrap_or_default();
+                let parent = path
+                    .len()
+                    .checked_sub(2)
+                    .map(|i| path[i].as_str())
+                    .unwrap_or_default();
+
+                match (scope, parent, element) {
+                    (Some(Scope::Group), "OrgnlGrpInfAndSts", "OrgnlMsgId") => {
+                        report.original_message_id = text
+                    }
+                    (Some(Scope::Group), _, "GrpSts") => report.group.status = Some(text),
+                    (Some(Scope::PaymentInfo), _, "OrgnlPmtInfId") => payment_info.id = text,
+                    (Some(Scope::PaymentInfo), _, "PmtInfSts") => payment_info.status = Some(text),
+                    (Some(Scope::Transaction), _, "OrgnlEndToEndId") => transaction.id = text,
+                    (Some(Scope::Transaction), _, "TxSts") => transaction.status = Some(text),
+                    // ExternalStatusReason1Code, proprietary reasons are left out
+                    (Some(scope), "Rsn", "Cd") if text.len() <= 4 => {
+                        let entry = match scope {
+                            Scope::Group => &mut report.group,
+                            Scope::PaymentInfo => &mut payment_info,
+                            Scope::Transaction => &mut transaction,
+                        };
+                        entry.reason.get_or_insert(text);
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(ref e) => {
+                match e.local_name().as_ref() {
+                    b"OrgnlGrpInfAndSts" => scope = None,
+                    b"OrgnlPmtInfAndSts" => {
+                        report.payment_infos.push(std::mem::take(&mut payment_info));
+                        scope = None;
+                    }
+                    b"TxInfAndSts" => {
+                        report.transactions.push(std::mem::take(&mut transaction));
+                        // Back in the payment information block it was nested in, if any
+                        scope = path
+                            .iter()
+                            .any(|p| p == "OrgnlPmtInfAndSts")
+                            .then_some(Scope::PaymentInfo);
+                    }
+                    _ => {}
+                }
+                path.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if report.original_message_id.is_empty() {
+        return Err(invalid_file("No original message id in pain.002".into()));
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARTIAL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.002.001.10">
+  <CstmrPmtStsRpt>
+    <GrpHdr><MsgId>STS-1</MsgId><CreDtTm>2025-12-17T08:00:00Z</CreDtTm></GrpHdr>
+    <OrgnlGrpInfAndSts>
+      <OrgnlMsgId>THAL0123456789ABCDEF</OrgnlMsgId>
+      <OrgnlMsgNmId>pain.001.001.09</OrgnlMsgNmId>
+      <GrpSts>PART</GrpSts>
+    </OrgnlGrpInfAndSts>
+    <OrgnlPmtInfAndSts>
+      <OrgnlPmtInfId>THAL0123456789ABCDEF-1</OrgnlPmtInfId>
+      <PmtInfSts>PART</PmtInfSts>
+      <TxInfAndSts>
+        <OrgnlEndToEndId>E2E1</OrgnlEndToEndId>
+        <TxSts>RJCT</TxSts>
+        <StsRsnInf><Rsn><Cd>AC04</Cd></Rsn></StsRsnInf>
+      </TxInfAndSts>
+      <TxInfAndSts>
+        <OrgnlEndToEndId>E2E2</OrgnlEndToEndId>
+        <TxSts>ACCP</TxSts>
+      </TxInfAndSts>
+    </OrgnlPmtInfAndSts>
+    <OrgnlPmtInfAndSts>
+      <OrgnlPmtInfId>THAL0123456789ABCDEF-2</OrgnlPmtInfId>
+      <PmtInfSts>RJCT</PmtInfSts>
+      <StsRsnInf><Rsn><Cd>AM04</Cd></Rsn></StsRsnInf>
+    </OrgnlPmtInfAndSts>
+  </CstmrPmtStsRpt>
+</Document>"#;
+
+    #[test]
+    fn statuses_are_read_at_every_level() {
+        let report = parse_status_report(PARTIAL.as_bytes()).unwrap();
+
+        assert_eq!(report.original_message_id, "THAL0123456789ABCDEF");
+        assert_eq!(report.group.status.as_deref(), Some("PART"));
+        assert_eq!(report.payment_infos.len(), 2);
+        assert_eq!(report.payment_infos[1].reason.as_deref(), Some("AM04"));
+        assert_eq!(
+            report.transactions,
+            vec![
+                StatusEntry {
+                    id: "E2E1".into(),
+                    status: Some("RJCT".into()),
+                    reason: Some("AC04".into()),
+                },
+                StatusEntry {
+                    id: "E2E2".into(),
+                    status: Some("ACCP".into()),
+                    reason: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejections_apply_from_the_narrowest_level() {
+        let report = parse_status_report(PARTIAL.as_bytes()).unwrap();
+
+        assert_eq!(
+            report.outcome("E2E1", "THAL0123456789ABCDEF-1"),
+            Some(TransferOutcome::Returned(Some("AC04".into())))
+        );
+        assert_eq!(
+            report.outcome("E2E2", "THAL0123456789ABCDEF-1"),
+            Some(TransferOutcome::Accepted)
+        );
+        assert_eq!(
+            report.outcome("E2E3", "THAL0123456789ABCDEF-2"),
+            Some(TransferOutcome::Returned(Some("AM04".into())))
+        );
+    }
+
+    #[test]
+    fn interim_reports_only_return_transfers() {
+        let report = StatusReport {
+            original_message_id: "THAL0123456789ABCDEF".into(),
+            group: StatusEntry {
+                status: Some("ACTC".into()),
+                ..Default::default()
+            },
+            transactions: vec![StatusEntry {
+                id: "E2E1".into(),
+                status: Some("RJCT".into()),
+                reason: None,
+            }],
+            ..Default::default()
+        };
+
+        assert!(!report.is_final());
+        assert_eq!(
+            report.outcome("E2E1", "THAL0123456789ABCDEF-1"),
+            Some(TransferOutcome::Returned(None))
+        );
+        assert_eq!(report.outcome("E2E2", "THAL0123456789ABCDEF-1"), None);
+    }
+
+    #[test]
+    fn reports_without_an_original_message_are_refused() {
+        assert!(parse_status_report(b"<Document><CstmrPmtStsRpt/></Document>").is_err());
+        assert!(parse_status_report(b"<Document><Unclosed>").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::sepa::models::{
+    DebtorAccountEntity, QueuedTransferEntity, SepaBatchEntity, SepaBatchStatus, SepaTransferEntity,
+};
+
+const TRANSFER_COLUMNS: &str = "id, user_id, account_id, beneficiary_id, creditor_name,
+    creditor_iban, creditor_bic, amount_cents, currency, end_to_end_id, remittance_info, status,
+    batch_id, payment_info_id, journal_entry_id, return_entry_id, return_reason, created_at,
+    updated_at";
+
+const BATCH_COLUMNS: &str = "id, message_id, status, n_transactions, control_sum_cents,
+    file_location, created_by, created_at, status_updated_at";
+
+pub struct SepaRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> SepaRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    // Locked so two transfers can't both spend the same available balance
+    #[tracing::instrument("Locking SEPA debtor account", skip(self))]
+    pub async fn fetch_debtor_account_for_update(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<DebtorAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, DebtorAccountEntity>(
+            "SELECT id, user_id, iban, currency, status FROM user_account
+                WHERE id=$1
+                FOR UPDATE",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving SEPA transfer", skip(self, transfer))]
+    pub async fn insert_transfer(
+        &mut self,
+        transfer: &SepaTransferEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO sepa_transfer(id, user_id, account_id, beneficiary_id, creditor_name,
+                    creditor_iban, creditor_bic, amount_cents, currency, end_to_end_id,
+                    remittance_info, status, journal_entry_id, created_at, updated_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+        )
+        .bind(transfer.id)
+        .bind(transfer.user_id)
+        .bind(transfer.account_id)
+        .bind(transfer.beneficiary_id)
+        .bind(&transfer.creditor_name)
+        .bind(&transfer.creditor_iban)
+        .bind(&transfer.creditor_bic)
+        .bind(transfer.amount_cents)
+        .bind(&transfer.currency)
+        .bind(&transfer.end_to_end_id)
+        .bind(&transfer.remittance_info)
+        .bind(transfer.status)
+        .bind(transfer.journal_entry_id)
+        .bind(transfer.created_at)
+        .bind(transfer.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving SEPA transfers", skip(self))]
+    pub async fn fetch_transfers(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<SepaTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaTransferEntity>(&format!(
+            "SELECT {} FROM sepa_transfer WHERE user_id=$1 ORDER BY created_at DESC",
+            TRANSFER_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving SEPA transfer", skip(self))]
+    pub async fn fetch_transfer(
+        &self,
+        user_id: Uuid,
+        transfer_id: Uuid,
+    ) -> Result<Option<SepaTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaTransferEntity>(&format!(
+            "SELECT {} FROM sepa_transfer WHERE id=$1 AND user_id=$2",
+            TRANSFER_COLUMNS
+        ))
+        .bind(transfer_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Transfers another run has already picked up are skipped rather than waited on
+    #[tracing::instrument("Locking queued SEPA transfers", skip(self))]
+    pub async fn fetch_queued_for_update(
+        &mut self,
+    ) -> Result<Vec<QueuedTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, QueuedTransferEntity>(
+            "SELECT t.id, t.account_id, a.iban AS debtor_iban,
+                    concat_ws(' ', u.first_name, u.last_name) AS debtor_name,
+                    t.creditor_name, t.creditor_iban, t.creditor_bic, t.amount_cents,
+                    t.end_to_end_id, t.remittance_info
+                FROM sepa_transfer t
+                JOIN user_account a ON a.id = t.account_id
+                JOIN tuser u ON u.id = t.user_id
+                WHERE t.status = 'queued'
+                ORDER BY t.created_at
+                FOR UPDATE OF t SKIP LOCKED",
+        )
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving SEPA batch", skip(self, batch))]
+    pub async fn insert_batch(&mut self, batch: &SepaBatchEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO sepa_batch(id, message_id, status, n_transactions, control_sum_cents,
+                    file_location, created_by, created_at)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(batch.id)
+        .bind(&batch.message_id)
+        .bind(batch.status)
+        .bind(batch.n_transactions)
+        .bind(batch.control_sum_cents)
+        .bind(&batch.file_location)
+        .bind(batch.created_by)
+        .bind(batch.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    // Each transfer keeps the <PmtInfId> it went out under, pain.002 can reject by it
+    #[tracing::instrument("Marking SEPA transfers batched", skip(self, assignments))]
+    pub async fn mark_batched(
+        &mut self,
+        batch_id: Uuid,
+        assignments: &[(Uuid, String)],
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let (ids, payment_info_ids): (Vec<Uuid>, Vec<String>) = assignments.iter().cloned().unzip();
+
+        sqlx::query(
+            "UPDATE sepa_transfer t
+                SET status='batched', batch_id=$1, payment_info_id=p.payment_info_id, updated_at=$2
+                FROM UNNEST($3::uuid[], $4::varchar[]) AS p(id, payment_info_id)
+                WHERE t.id = p.id",
+        )
+        .bind(batch_id)
+        .bind(now)
+        .bind(&ids)
+        .bind(&payment_info_ids)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving SEPA batches", skip(self))]
+    pub async fn fetch_batches(
+        &self,
+        status: Option<SepaBatchStatus>,
+    ) -> Result<Vec<SepaBatchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaBatchEntity>(&format!(
+            "SELECT {} FROM sepa_batch
+                WHERE $1::sepa_batch_status IS NULL OR status = $1
+                ORDER BY created_at DESC",
+            BATCH_COLUMNS
+        ))
+        .bind(status)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving SEPA batch", skip(self))]
+    pub async fn fetch_batch(
+        &self,
+        batch_id: Uuid,
+    ) -> Result<Option<SepaBatchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaBatchEntity>(&format!(
+            "SELECT {} FROM sepa_batch WHERE id=$1",
+            BATCH_COLUMNS
+        ))
+        .bind(batch_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving SEPA batch transfers", skip(self))]
+    pub async fn fetch_batch_transfers(
+        &self,
+        batch_id: Uuid,
+    ) -> Result<Vec<SepaTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaTransferEntity>(&format!(
+            "SELECT {} FROM sepa_transfer WHERE batch_id=$1 ORDER BY payment_info_id, created_at",
+            TRANSFER_COLUMNS
+        ))
+        .bind(batch_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Locking SEPA batch by message id", skip(self))]
+    pub async fn fetch_batch_by_message_for_update(
+        &mut self,
+        message_id: &str,
+    ) -> Result<Option<SepaBatchEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaBatchEntity>(&format!(
+            "SELECT {} FROM sepa_batch WHERE message_id=$1 FOR UPDATE",
+            BATCH_COLUMNS
+        ))
+        .bind(message_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Only transfers still waiting on the clearing house, settled ones are left alone
+    #[tracing::instrument("Locking unsettled SEPA batch transfers", skip(self))]
+    pub async fn fetch_unsettled_for_update(
+        &mut self,
+        batch_id: Uuid,
+    ) -> Result<Vec<SepaTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SepaTransferEntity>(&format!(
+            "SELECT {} FROM sepa_transfer
+                WHERE batch_id=$1 AND status='batched'
+                ORDER BY created_at
+                FOR UPDATE",
+            TRANSFER_COLUMNS
+        ))
+        .bind(batch_id)
+        .fetch_all(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Marking SEPA transfer returned", skip(self))]
+    pub async fn mark_returned(
+        &mut self,
+        transfer_id: Uuid,
+        return_entry_id: Uuid,
+        reason: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE sepa_transfer
+                SET status='returned', return_entry_id=$2, return_reason=$3, updated_at=$4
+                WHERE id=$1",
+        )
+        .bind(transfer_id)
+        .bind(return_entry_id)
+        .bind(reason)
+        .bind(now)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Marking SEPA transfers accepted", skip(self, transfer_ids))]
+    pub async fn mark_accepted(
+        &mut self,
+        transfer_ids: &[Uuid],
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sepa_transfer SET status='accepted', updated_at=$2 WHERE id = ANY($1)")
+            .bind(transfer_ids)
+            .bind(now)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    // Accepted and returned transfer counts of a batch
+    #[tracing::instrument("Counting SEPA batch outcomes", skip(self))]
+    pub async fn fetch_outcome_counts(
+        &mut self,
+        batch_id: Uuid,
+    ) -> Result<(i64, i64), sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) FILTER (WHERE status='accepted') AS n_accepted,
+                    COUNT(*) FILTER (WHERE status='returned') AS n_returned
+                FROM sepa_transfer WHERE batch_id=$1",
+        )
+        .bind(batch_id)
+        .fetch_one(&mut **self.tx)
+        .await?;
+
+        Ok((row.get("n_accepted"), row.get("n_returned")))
+    }
+
+    #[tracing::instrument("Updating SEPA batch status", skip(self))]
+    pub async fn update_batch_status(
+        &mut self,
+        batch_id: Uuid,
+        status: SepaBatchStatus,
+        now: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sepa_batch SET status=$2, status_updated_at=$3 WHERE id=$1")
+            .bind(batch_id)
+            .bind(status)
+            .bind(now)
+            .execute(&mut **self.tx)
+            .await?;
+
+        Ok(())
+    }
+}
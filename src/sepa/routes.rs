@@ -0,0 +1,107 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::sepa::{
+    schemas::{
+        SepaBatchDetailResponse, SepaBatchQuery, SepaBatchResponse, SepaTransferRequest,
+        SepaTransferResponse, StatusImportResponse,
+    },
+    service::SepaService,
+};
+
+#[tracing::instrument("Queue SEPA transfer", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/sepa-transfers", request_body=SepaTransferRequest, responses((status=200, body=SepaTransferResponse, description="Account debited and transfer queued for the next batch"), (status=400, description="Invalid amount or remittance information"), (status=404, description="Account or beneficiary not found"), (status=409, description="Account is not active"), (status=422, description="Not a euro account or SEPA beneficiary, insufficient funds, or beneficiary still cooling off")))]
+pub async fn create_sepa_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<SepaTransferRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.create(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List SEPA transfers", skip(app_state, claims))]
+#[utoipa::path(get, path="/sepa-transfers", responses((status=200, body=Vec<SepaTransferResponse>, description="Customer's SEPA transfers, newest first")))]
+pub async fn list_sepa_transfers(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.list(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch SEPA transfer", skip(app_state, claims))]
+#[utoipa::path(get, path="/sepa-transfers/{transfer_id}", params(("transfer_id"=Uuid, Path, description="SEPA transfer id")), responses((status=200, body=SepaTransferResponse, description="SEPA transfer"), (status=404, description="SEPA transfer not found")))]
+pub async fn fetch_sepa_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    transfer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service
+        .fetch(&claims, transfer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Generate SEPA batch", skip(app_state, claims))]
+#[utoipa::path(post, path="/sepa/batches", responses((status=200, body=SepaBatchResponse, description="Queued transfers written to a pain.001 file"), (status=403, description="Only superusers can generate batches"), (status=404, description="No queued SEPA transfers"), (status=422, description="Message failed pain.001 validation")))]
+pub async fn generate_sepa_batch(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.generate_batch(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List SEPA batches", skip(app_state))]
+#[utoipa::path(get, path="/sepa/batches", params(SepaBatchQuery), responses((status=200, body=Vec<SepaBatchResponse>, description="Batches, newest first"), (status=400, description="Unknown status")))]
+pub async fn list_sepa_batches(
+    app_state: web::Data<AppState>,
+    query: web::Query<SepaBatchQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.list_batches(query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch SEPA batch", skip(app_state))]
+#[utoipa::path(get, path="/sepa/batches/{batch_id}", params(("batch_id"=Uuid, Path, description="SEPA batch id")), responses((status=200, body=SepaBatchDetailResponse, description="Batch with its transfers"), (status=404, description="SEPA batch not found")))]
+pub async fn fetch_sepa_batch(
+    app_state: web::Data<AppState>,
+    batch_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.fetch_batch(batch_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Import SEPA status reports", skip(app_state, claims))]
+#[utoipa::path(post, path="/sepa/status-reports/import", responses((status=200, body=StatusImportResponse, description="pain.002 reports applied, returned transfers credited back"), (status=403, description="Only superusers can import status reports"), (status=409, description="No status report directory configured")))]
+pub async fn import_sepa_status_reports(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let sepa_service = SepaService::from(&app_state);
+
+    let response = sepa_service.import_status_reports(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::sepa::models::{
+    SepaBatchEntity, SepaBatchStatus, SepaTransferEntity, SepaTransferStatus,
+};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct SepaTransferRequest {
+    // Euro account the transfer is paid from
+    pub account_id: Uuid,
+    // A saved beneficiary with an IBAN in the SEPA area
+    pub beneficiary_id: Uuid,
+    #[schema(example = 125_000)]
+    pub amount_cents: i64,
+    #[schema(example = "INV 2025/07")]
+    pub remittance_info: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SepaTransferResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub creditor_name: String,
+    pub creditor_iban: String,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub end_to_end_id: String,
+    pub remittance_info: Option<String>,
+    pub status: SepaTransferStatus,
+    pub batch_id: Option<Uuid>,
+    // ISO 20022 status reason code given by the clearing house
+    #[schema(example = "AC04")]
+    pub return_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SepaTransferEntity> for SepaTransferResponse {
+    fn from(value: SepaTransferEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            beneficiary_id: value.beneficiary_id,
+            creditor_name: value.creditor_name,
+            creditor_iban: value.creditor_iban,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            end_to_end_id: value.end_to_end_id,
+            remittance_info: value.remittance_info,
+            status: value.status,
+            batch_id: value.batch_id,
+            return_reason: value.return_reason,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct SepaBatchQuery {
+    // generated, accepted, partially_accepted or rejected
+    pub status: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SepaBatchResponse {
+    pub id: Uuid,
+    pub message_id: String,
+    pub status: SepaBatchStatus,
+    pub n_transactions: i32,
+    pub control_sum_cents: i64,
+    // A path on the outbound directory or an s3:// url
+    pub file_location: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub status_updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<SepaBatchEntity> for SepaBatchResponse {
+    fn from(value: SepaBatchEntity) -> Self {
+        Self {
+            id: value.id,
+            message_id: value.message_id,
+            status: value.status,
+            n_transactions: value.n_transactions,
+            control_sum_cents: value.control_sum_cents,
+            file_location: value.file_location,
+            created_by: value.created_by,
+            created_at: value.created_at,
+            status_updated_at: value.status_updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SepaBatchDetailResponse {
+    #[serde(flatten)]
+    pub batch: SepaBatchResponse,
+    pub transfers: Vec<SepaTransferResponse>,
+}
+
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct StatusImportResponse {
+    pub files_processed: u32,
+    // Unreadable or for a message we never sent, moved aside for a look
+    pub files_failed: u32,
+    pub transfers_accepted: u32,
+    pub transfers_returned: u32,
+}
@@ -0,0 +1,466 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
+use crate::beneficiary::service::BeneficiaryService;
+use crate::config::state::AppState;
+use crate::hold::service::HoldService;
+use crate::identity_verify::service::KycService;
+use crate::infra::files::{OutboundFile, archive_drop_file, list_drop_files};
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::{models::ContraAccount, service::LedgerService};
+use crate::sepa::{
+    models::{
+        Creditor, DebtorAccountEntity, SEPA_CLEARING_COA, SepaBatchEntity, SepaBatchStatus,
+        SepaTransferEntity, TransferInstruction, new_end_to_end_id, return_reference,
+    },
+    pain001::Pain001,
+    pain002::{TransferOutcome, parse_status_report},
+    schemas::{
+        SepaBatchDetailResponse, SepaBatchQuery, SepaBatchResponse, SepaTransferRequest,
+        SepaTransferResponse, StatusImportResponse,
+    },
+};
+use crate::user::models::AccessRole;
+
+pub struct SepaService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> SepaService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // The customer is debited straight away against clearing, the transfer then waits for a batch
+    #[tracing::instrument("Queue SEPA transfer", skip(self, claims, request))]
+    pub async fn create(
+        &self,
+        claims: &SessionClaims,
+        request: SepaTransferRequest,
+    ) -> Result<SepaTransferResponse, AppError> {
+        let instruction = TransferInstruction::parse(&request)?;
+        let user_id = *claims.get_user_id();
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = match uow
+            .sepa()
+            .fetch_debtor_account_for_update(request.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) => a,
+            None => Err(DomainError::NotFound("account".into()))?,
+        };
+        if account.user_id != user_id {
+            Err(DomainError::NotFound("account".into()))?
+        }
+
+        let beneficiary = BeneficiaryService::from(self.app_state)
+            .check_payment(
+                &mut uow,
+                user_id,
+                request.beneficiary_id,
+                instruction.amount_cents,
+                now,
+            )
+            .await?;
+        let creditor = Creditor::from_beneficiary(&beneficiary)?;
+        KycService::from(self.app_state)
+            .check_transaction(&mut uow, account.id, instruction.amount_cents)
+            .await?;
+
+        let available = HoldService::available_balance(&mut uow, account.id, now).await?;
+        account.check_debit(
+            user_id,
+            instruction.amount_cents,
+            available.available_cents(),
+        )?;
+
+        let end_to_end_id = new_end_to_end_id();
+        let transfer = self
+            .queue(
+                &mut uow,
+                &account,
+                beneficiary.id,
+                creditor,
+                instruction,
+                end_to_end_id.clone(),
+                end_to_end_id,
+                now,
+            )
+            .await?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit SEPA transfer")?;
+
+        Ok(transfer.into())
+    }
+
+    // Posts the debit and saves the transfer, the caller has done the checks. Scheduled
+    // payments to a beneficiary come through here as well, their debit posted under the
+    // execution key so an execution can't be paid twice.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn queue(
+        &self,
+        uow: &mut UnitofWork<'_>,
+        account: &DebtorAccountEntity,
+        beneficiary_id: Uuid,
+        creditor: Creditor,
+        instruction: TransferInstruction,
+        end_to_end_id: String,
+        transaction_ref: String,
+        now: DateTime<Utc>,
+    ) -> Result<SepaTransferEntity, AppError> {
+        let journal_entry_id = LedgerService::from(self.app_state)
+            .post_account_debit(
+                uow,
+                account.id,
+                transaction_ref,
+                format!("SEPA transfer to {}", creditor.name),
+                instruction.amount_cents,
+                ContraAccount::Code(SEPA_CLEARING_COA),
+            )
+            .await?;
+
+        let transfer = SepaTransferEntity::queued(
+            account,
+            beneficiary_id,
+            creditor,
+            instruction,
+            end_to_end_id,
+            journal_entry_id,
+            now,
+        );
+
+        uow.sepa()
+            .insert_transfer(&transfer)
+            .await
+            .to_app_err("Failed to save SEPA transfer")?;
+
+        Ok(transfer)
+    }
+
+    #[tracing::instrument("List SEPA transfers", skip(self, claims))]
+    pub async fn list(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<Vec<SepaTransferResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let transfers = uow
+            .sepa()
+            .fetch_transfers(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch SEPA transfers")?;
+
+        Ok(transfers
+            .into_iter()
+            .map(SepaTransferResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch SEPA transfer", skip(self, claims))]
+    pub async fn fetch(
+        &self,
+        claims: &SessionClaims,
+        transfer_id: Uuid,
+    ) -> Result<SepaTransferResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        match uow
+            .sepa()
+            .fetch_transfer(*claims.get_user_id(), transfer_id)
+            .await
+            .to_app_err("Failed to fetch SEPA transfer")?
+        {
+            Some(t) => Ok(t.into()),
+            None => Err(DomainError::NotFound("SEPA transfer".into()))?,
+        }
+    }
+
+    // Every queued transfer goes into one pain.001 file, which is stored before the batch
+    // commits and removed again if the commit fails
+    #[tracing::instrument("Generate SEPA batch", skip(self, claims))]
+    pub async fn generate_batch(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<SepaBatchResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let transfers = uow
+            .sepa()
+            .fetch_queued_for_update()
+            .await
+            .to_app_err("Failed to fetch queued SEPA transfers")?;
+        if transfers.is_empty() {
+            Err(DomainError::NotFound("queued SEPA transfers".into()))?
+        }
+
+        let mut batch = SepaBatchEntity::new(&transfers, *claims.get_user_id(), now);
+        let pain = Pain001::new(
+            &batch.message_id,
+            now,
+            batch.execution_date(),
+            &self.app_state.sepa,
+            transfers,
+        );
+        pain.validate().map_err(|e| {
+            DomainError::ConstraintViolation(format!("pain.001 failed validation: {}", e))
+        })?;
+        let xml = pain.to_xml().context("Failed to write pain.001 message")?;
+
+        let file = OutboundFile::new(
+            self.app_state.sepa.outbound_dir.as_deref(),
+            &self.app_state.s3_client.bucket,
+            "sepa/outbound",
+            &batch.file_name(),
+        );
+        batch.file_location = file.location();
+
+        let assignments: Vec<(Uuid, String)> = pain
+            .payment_infos
+            .iter()
+            .flat_map(|p| p.transfers.iter().map(|t| (t.transfer_id, p.id.clone())))
+            .collect();
+
+        uow.sepa()
+            .insert_batch(&batch)
+            .await
+            .to_app_err("Failed to save SEPA batch")?;
+
+        uow.sepa()
+            .mark_batched(batch.id, &assignments, now)
+            .await
+            .to_app_err("Failed to mark SEPA transfers batched")?;
+
+        file.store(&self.app_state.s3_client, xml, "application/xml")
+            .await?;
+
+        let committed = uow.commit().await;
+        if committed.is_err() {
+            file.discard(&self.app_state.s3_client).await;
+        }
+        committed.to_app_err("Failed to commit SEPA batch")?;
+
+        Ok(batch.into())
+    }
+
+    #[tracing::instrument("List SEPA batches", skip(self))]
+    pub async fn list_batches(
+        &self,
+        query: SepaBatchQuery,
+    ) -> Result<Vec<SepaBatchResponse>, AppError> {
+        let status = match query.status {
+            Some(s) => Some(s.parse::<SepaBatchStatus>()?),
+            None => None,
+        };
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let batches = uow
+            .sepa()
+            .fetch_batches(status)
+            .await
+            .to_app_err("Failed to fetch SEPA batches")?;
+
+        Ok(batches.into_iter().map(SepaBatchResponse::from).collect())
+    }
+
+    #[tracing::instrument("Fetch SEPA batch", skip(self))]
+    pub async fn fetch_batch(&self, batch_id: Uuid) -> Result<SepaBatchDetailResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let batch = match uow
+            .sepa()
+            .fetch_batch(batch_id)
+            .await
+            .to_app_err("Failed to fetch SEPA batch")?
+        {
+            Some(b) => b,
+            None => Err(DomainError::NotFound("SEPA batch".into()))?,
+        };
+
+        let transfers = uow
+            .sepa()
+            .fetch_batch_transfers(batch_id)
+            .await
+            .to_app_err("Failed to fetch SEPA batch transfers")?;
+
+        Ok(SepaBatchDetailResponse {
+            batch: batch.into(),
+            transfers: transfers
+                .into_iter()
+                .map(SepaTransferResponse::from)
+                .collect(),
+        })
+    }
+
+    // Reports are taken in file name order. Unreadable ones, or ones for a message we never
+    // sent, go to failed/ and the rest to processed/. A database error leaves the file in
+    // place for the next run.
+    #[tracing::instrument("Import SEPA status reports", skip(self, claims))]
+    pub async fn import_status_reports(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<StatusImportResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let dir = match &self.app_state.sepa.status_report_dir {
+            Some(dir) => dir.clone(),
+            None => Err(DomainError::InvalidState(
+                "no pain.002 status report directory is configured".into(),
+            ))?,
+        };
+
+        let files = list_drop_files(&dir, &["xml"])?;
+
+        let mut summary = StatusImportResponse::default();
+
+        for path in files {
+            let bytes = std::fs::read(&path).context("Failed to read status report")?;
+
+            let folder = match self.apply_status_report(&bytes, Utc::now()).await {
+                Ok((n_accepted, n_returned)) => {
+                    summary.files_processed += 1;
+                    summary.transfers_accepted += n_accepted;
+                    summary.transfers_returned += n_returned;
+                    "processed"
+                }
+                Err(e @ (AppError::Validation(_) | AppError::Domain(DomainError::NotFound(_)))) => {
+                    tracing::error!("Status report {} not applied: {}", path.display(), e);
+                    summary.files_failed += 1;
+                    "failed"
+                }
+                Err(e) => return Err(e),
+            };
+
+            archive_drop_file(&dir, &path, folder)?;
+        }
+
+        Ok(summary)
+    }
+
+    // Returned transfers are credited back to the customer out of clearing. Once none in the
+    // batch are left waiting, the batch takes its final status.
+    async fn apply_status_report(
+        &self,
+        bytes: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<(u32, u32), AppError> {
+        let report = parse_status_report(bytes)?;
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let batch = match uow
+            .sepa()
+            .fetch_batch_by_message_for_update(&report.original_message_id)
+            .await
+            .to_app_err("Failed to fetch SEPA batch")?
+        {
+            Some(b) => b,
+            None => Err(DomainError::NotFound(format!(
+                "SEPA batch {}",
+                report.original_message_id
+            )))?,
+        };
+
+        let transfers = uow
+            .sepa()
+            .fetch_unsettled_for_update(batch.id)
+            .await
+            .to_app_err("Failed to fetch SEPA batch transfers")?;
+        let n_unsettled = transfers.len();
+
+        let ledger = LedgerService::from(self.app_state);
+        let mut accepted = Vec::new();
+        let mut n_returned = 0;
+
+        for transfer in transfers {
+            let payment_info_id = transfer.payment_info_id.as_deref().unwrap_or_default();
+
+            match report.outcome(&transfer.end_to_end_id, payment_info_id) {
+                Some(TransferOutcome::Accepted) => accepted.push(transfer.id),
+                Some(TransferOutcome::Returned(reason)) => {
+                    let return_entry_id = ledger
+                        .post_account_credit(
+                            &mut uow,
+                            transfer.account_id,
+                            return_reference(&transfer.end_to_end_id),
+                            format!("Returned SEPA transfer to {}", transfer.creditor_name),
+                            transfer.amount_cents,
+                            ContraAccount::Code(SEPA_CLEARING_COA),
+                        )
+                        .await?;
+
+                    uow.sepa()
+                        .mark_returned(transfer.id, return_entry_id, reason.as_deref(), now)
+                        .await
+                        .to_app_err("Failed to mark SEPA transfer returned")?;
+
+                    n_returned += 1;
+                }
+                None => {}
+            }
+        }
+
+        if !accepted.is_empty() {
+            uow.sepa()
+                .mark_accepted(&accepted, now)
+                .await
+                .to_app_err("Failed to mark SEPA transfers accepted")?;
+        }
+
+        if n_unsettled > 0 && accepted.len() + n_returned == n_unsettled {
+            let (n_batch_accepted, n_batch_returned) = uow
+                .sepa()
+                .fetch_outcome_counts(batch.id)
+                .await
+                .to_app_err("Failed to count SEPA batch outcomes")?;
+
+            uow.sepa()
+                .update_batch_status(
+                    batch.id,
+                    SepaBatchStatus::settled(n_batch_accepted, n_batch_returned),
+                    now,
+                )
+                .await
+                .to_app_err("Failed to update SEPA batch status")?;
+        }
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit SEPA status report")?;
+
+        Ok((accepted.len() as u32, n_returned as u32))
+    }
+}
@@ -4,12 +4,16 @@ use utoipa::OpenApi;
 #[openapi(paths(
     crate::staff::routes::staff_signup,
     crate::staff::routes::staff_login,
+    crate::staff::routes::staff_sso_login,
+    crate::staff::routes::staff_sso_callback,
     crate::staff::routes::confirm_staff,
     crate::staff::routes::create_customer_account,
+    crate::staff::routes::search_customers,
+    crate::staff::routes::fetch_customer_overview,
     crate::staff::routes::update_customer_account,
+    crate::staff::routes::fetch_customer_account,
+    crate::staff::routes::customer_account_history,
     crate::staff::routes::create_chart_account,
     crate::staff::routes::update_chart_account,
-    crate::staff::routes::create_account_type,
-    crate::staff::routes::update_account_type,
 ))]
 pub struct StaffApi;
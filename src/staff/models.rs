@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 use crate::base::error::ValidationError;
 
-#[derive(Debug, sqlx::Type, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, Display)]
 #[sqlx(type_name = "chart_account_type", rename_all = "lowercase")]
 pub enum CoaType {
     Asset,
@@ -56,10 +56,3 @@ impl ChartAccount {
         }
     }
 }
-
-pub struct CustomerAccountType {
-    pub id: Uuid,
-    pub name: String,
-    pub description: String,
-    pub coa_id: Uuid,
-}
@@ -1,4 +1,4 @@
-use crate::staff::models::{ChartAccount, CoaType, CustomerAccountType};
+use crate::staff::models::{ChartAccount, CoaType};
 use sqlx::{PgPool, Postgres, Row, Transaction};
 use uuid::Uuid;
 
@@ -34,7 +34,7 @@ impl<'a, 'b> StaffRepository<'a, 'b> {
         coa_type: CoaType,
     ) -> Result<Option<Uuid>, sqlx::Error> {
         let result: Option<Uuid> = sqlx::query("SELECT id FROM chart_of_account WHERE coa_type=$1")
-            .bind(&coa_type)
+            .bind(coa_type)
             .fetch_optional(&mut **self.tx)
             .await?
             .map(|r| r.get("id"));
@@ -42,22 +42,15 @@ impl<'a, 'b> StaffRepository<'a, 'b> {
         Ok(result)
     }
 
-    #[tracing::instrument("Insert account type in db", skip(self, acc_type))]
-    pub async fn create_account_type(
-        &self,
-        acc_type: &CustomerAccountType,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            "INSERT INTO chart_of_account(id, name, description, coa_id) VALUES($1, $2, $3, $4)",
-        )
-        .bind(acc_type.id)
-        .bind(&acc_type.name)
-        .bind(&acc_type.description)
-        .bind(acc_type.coa_id)
-        .execute(self.pool)
-        .await?;
+    #[tracing::instrument("Fetch chart account id by code from db", skip(self))]
+    pub async fn fetch_coa_id_by_code(&mut self, code: &str) -> Result<Option<Uuid>, sqlx::Error> {
+        let result: Option<Uuid> = sqlx::query("SELECT id FROM chart_of_account WHERE code=$1")
+            .bind(code)
+            .fetch_optional(&mut **self.tx)
+            .await?
+            .map(|r| r.get("id"));
 
-        Ok(())
+        Ok(result)
     }
 
     pub async fn fetch_coa_by_code(&self, code: &str) -> Result<Option<ChartAccount>, sqlx::Error> {
@@ -1,12 +1,23 @@
 use actix_web::{HttpResponse, cookie::Cookie, http::header, web};
-
-use crate::authentication::{StaffSession, schemas::LoginRequest, service::AuthService};
+use uuid::Uuid;
+
+use crate::authentication::{
+    StaffSession,
+    models::ClientInfo,
+    schemas::{LoginRequest, SsoCallback},
+    service::AuthService,
+    token::SessionClaims,
+};
 use crate::base::StdResponse;
 use crate::config::state::AppState;
-use crate::staff::{
-    schemas::{AccountTypeRequest, ChartAccountRequest},
-    service::StaffService,
+use crate::customer::{
+    schemas::{
+        CustomerOverviewResponse, CustomerProfileResponse, CustomerSearchQuery,
+        CustomerSummaryResponse, ProfileChangeResponse, StaffProfileUpdateRequest,
+    },
+    service::CustomerService,
 };
+use crate::staff::{schemas::ChartAccountRequest, service::StaffService};
 use crate::user::{schemas::UserRegisterRequest, service::UserService};
 
 #[tracing::instrument("Staff signup", skip(app_state, request), fields(username=%request.username, user_email=%request.email))]
@@ -36,18 +47,58 @@ pub async fn confirm_staff(
     Ok(HttpResponse::Ok().json(StdResponse::from("Successful confirmation")))
 }
 
-#[tracing::instrument("Staff login", skip(app_state, payload, session))]
+#[tracing::instrument("Staff login", skip(app_state, payload, session, client))]
 #[utoipa::path(post, path="/login", responses((status=200, body=StdResponse, description="Staff login successful"), (status=401, description="Staff login unsuccessful")))]
 
 pub async fn staff_login(
     app_state: web::Data<AppState>,
     payload: web::Json<LoginRequest>,
     session: StaffSession,
+    client: ClientInfo,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    let (access_token, refresh_token) = auth_service
+        .authenticate_user(payload.into_inner(), session, client)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::AUTHORIZATION, format!("Bearer {}", access_token)))
+        .cookie(
+            Cookie::build("refresh_token", refresh_token)
+                .http_only(true)
+                .finish(),
+        )
+        .json(StdResponse::from("Staff Login Successful")))
+}
+
+#[tracing::instrument("Staff sso login", skip(app_state, session))]
+#[utoipa::path(get, path="/staff/sso/login", responses((status=302, description="Redirect to the identity provider"), (status=404, description="Single sign-on is not configured")))]
+pub async fn staff_sso_login(
+    app_state: web::Data<AppState>,
+    session: StaffSession,
+) -> actix_web::Result<HttpResponse> {
+    let auth_service = AuthService::from(&app_state);
+
+    let url = auth_service.begin_staff_sso(&session).await?;
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url))
+        .finish())
+}
+
+#[tracing::instrument("Staff sso callback", skip(app_state, query, session, client))]
+#[utoipa::path(get, path="/staff/sso/callback", params(SsoCallback), responses((status=200, body=StdResponse, description="Staff login successful"), (status=401, description="Staff login unsuccessful"), (status=403, description="No staff role granted by the identity provider")))]
+pub async fn staff_sso_callback(
+    app_state: web::Data<AppState>,
+    query: web::Query<SsoCallback>,
+    session: StaffSession,
+    client: ClientInfo,
 ) -> actix_web::Result<HttpResponse> {
     let auth_service = AuthService::from(&app_state);
 
     let (access_token, refresh_token) = auth_service
-        .authenticate_user(payload.into_inner(), session)
+        .complete_staff_sso(query.into_inner(), session, client)
         .await?;
 
     Ok(HttpResponse::Ok()
@@ -74,9 +125,82 @@ pub async fn create_customer_account(
     Ok(HttpResponse::Ok().json(StdResponse::from("Customer account created successfully")))
 }
 
-#[tracing::instrument("Staff updating customer profile")]
-#[utoipa::path(put, path="/user/", responses((status=200, body=StdResponse, description="User created successfully"), (status=409, description="User already exists")))]
-pub async fn update_customer_account() {}
+#[tracing::instrument("Staff searching customers", skip(app_state))]
+#[utoipa::path(get, path="/customers", params(CustomerSearchQuery), responses((status=200, body=Vec<CustomerSummaryResponse>, description="Matching customers, best match first"), (status=400, description="No search criteria or search text too short")))]
+pub async fn search_customers(
+    app_state: web::Data<AppState>,
+    query: web::Query<CustomerSearchQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .search_customers(query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff fetching customer overview", skip(app_state))]
+#[utoipa::path(get, path="/customers/{customer_id}", responses((status=200, body=CustomerOverviewResponse, description="Profile, kyc case, accounts, recent entries and open alerts"), (status=404, description="Customer not found")))]
+pub async fn fetch_customer_overview(
+    app_state: web::Data<AppState>,
+    customer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .fetch_overview(customer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff updating customer profile", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/customers/{customer_id}/profile", request_body=StaffProfileUpdateRequest, responses((status=200, body=CustomerProfileResponse, description="Customer profile updated"), (status=400, description="Invalid details or unknown reason code"), (status=404, description="Customer not found"), (status=422, description="Customer would be under 18")))]
+pub async fn update_customer_account(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    customer_id: web::Path<Uuid>,
+    payload: web::Json<StaffProfileUpdateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .update_details(&claims, customer_id.into_inner(), payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff fetching customer profile", skip(app_state))]
+#[utoipa::path(get, path="/customers/{customer_id}/profile", responses((status=200, body=CustomerProfileResponse, description="Customer profile"), (status=404, description="Customer not found")))]
+pub async fn fetch_customer_account(
+    app_state: web::Data<AppState>,
+    customer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .fetch_profile(customer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Staff fetching customer profile history", skip(app_state))]
+#[utoipa::path(get, path="/customers/{customer_id}/profile/history", responses((status=200, body=Vec<ProfileChangeResponse>, description="Changes to the profile, newest first"), (status=404, description="Customer not found")))]
+pub async fn customer_account_history(
+    app_state: web::Data<AppState>,
+    customer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let customer_service = CustomerService::from(&app_state);
+
+    let response = customer_service
+        .list_changes(customer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
 
 #[tracing::instrument("Staff creating new chart account", skip(app_state, payload))]
 #[utoipa::path(post, path="/coa", responses((status=200, body=StdResponse, description="chart account created successfully"), (status=409, description="Chart account creation failed")))]
@@ -96,22 +220,3 @@ pub async fn create_chart_account(
 #[tracing::instrument("Staff creating new chart account")]
 #[utoipa::path(put, path="/coa", responses((status=200, body=StdResponse, description="chart account created successfully"), (status=409, description="Chart account creation failed")))]
 pub async fn update_chart_account() {}
-
-#[tracing::instrument("Staff creating account type", skip(app_state))]
-#[utoipa::path(post, path="/account/type", responses((status=200, body=StdResponse, description="Account type created successfully"), (status=409, description="Account type creation failed")))]
-pub async fn create_account_type(
-    app_state: web::Data<AppState>,
-    payload: web::Json<AccountTypeRequest>,
-) -> actix_web::Result<HttpResponse> {
-    let staff_service = StaffService::from(&app_state);
-
-    staff_service
-        .account_type_creation(payload.into_inner())
-        .await?;
-
-    Ok(HttpResponse::Ok().json(StdResponse::from("Account type created successfully")))
-}
-
-#[tracing::instrument("Staff creating account type")]
-#[utoipa::path(put, path="/account/type", responses((status=200, body=StdResponse, description="User created successfully"), (status=409, description="Account type update failed")))]
-pub async fn update_account_type() {}
@@ -1,5 +1,3 @@
-use uuid::Uuid;
-
 #[derive(Debug, utoipa::ToSchema, serde::Deserialize)]
 pub struct ChartAccountRequest {
     pub name: String,
@@ -7,10 +5,3 @@ pub struct ChartAccountRequest {
     pub coa_type: String,
     pub currency: String,
 }
-
-#[derive(Debug, utoipa::ToSchema, serde::Deserialize)]
-pub struct AccountTypeRequest {
-    pub name: String,
-    pub coa_id: Uuid,
-    pub description: String,
-}
@@ -5,13 +5,14 @@ use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt};
 use crate::config::state::AppState;
 use crate::infra::pgdb::UnitofWork;
 use crate::staff::{
-    models::{ChartAccount, CoaType, CustomerAccountType},
-    schemas::{AccountTypeRequest, ChartAccountRequest},
+    models::{ChartAccount, CoaType},
+    schemas::ChartAccountRequest,
 };
 
 use crate::authentication::{
     StaffSession,
     credential::Credentials,
+    models::ClientInfo,
     schemas::{LoginIdentifier, LoginRequest},
     session_handler,
 };
@@ -25,11 +26,12 @@ impl<'a> StaffService<'a> {
         Self { app_state }
     }
 
-    #[tracing::instrument("Staff Login", skip(self, login_req, session))]
+    #[tracing::instrument("Staff Login", skip(self, login_req, session, client))]
     pub async fn authenticate_staff(
         &self,
         login_req: LoginRequest,
         session: StaffSession,
+        client: ClientInfo,
     ) -> Result<(String, String), AppError> {
         let mut uow = UnitofWork::from(&self.app_state.pgpool)
             .await
@@ -58,9 +60,9 @@ impl<'a> StaffService<'a> {
             .await?;
 
         let (pair, _) = session_handler(
-            &self.app_state.token_handler,
-            &self.app_state.redis_pool,
+            self.app_state,
             &session,
+            &client,
             creds.id,
             creds.email,
             creds.role,
@@ -70,27 +72,6 @@ impl<'a> StaffService<'a> {
         Ok((pair.access_token, pair.refresh_token))
     }
 
-    #[tracing::instrument("Create account type", skip(self))]
-    pub async fn account_type_creation(&self, request: AccountTypeRequest) -> Result<(), AppError> {
-        let mut uow = UnitofWork::from(&self.app_state.pgpool)
-            .await
-            .to_app_err("Failed to start postgres uow")?;
-
-        let acc_type = CustomerAccountType {
-            id: Uuid::now_v7(),
-            coa_id: request.coa_id,
-            description: request.description,
-            name: request.name,
-        };
-
-        uow.staffs()
-            .create_account_type(&acc_type)
-            .await
-            .to_app_err("Failed to create account type")?;
-
-        Ok(())
-    }
-
     #[tracing::instrument("Create chart account", skip(self))]
     pub async fn chart_account_creation(
         &self,
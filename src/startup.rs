@@ -1,3 +1,4 @@
+use actix_multipart::form::MultipartFormConfig;
 use actix_session::{SessionMiddleware, storage::RedisSessionStore};
 use actix_web::{App, HttpServer, cookie::Key, dev::Server, middleware::from_fn, web};
 use actix_web_flash_messages::{FlashMessagesFramework, storage::CookieMessageStore};
@@ -7,17 +8,86 @@ use utoipa::OpenApi;
 use utoipa_scalar::{Scalar, Servable};
 
 use crate::account::routes::open_customer_account;
-use crate::authentication::middleware::{reject_unauthorized_customer, reject_unauthorized_staff};
+use crate::ach::routes::{
+    create_ach_transfer, fetch_ach_file, fetch_ach_transfer, generate_ach_file, import_ach_returns,
+    list_ach_files, list_ach_transfers,
+};
+use crate::api_key::routes::{issue_api_key, list_api_keys, revoke_api_key, rotate_api_key};
+use crate::authentication::middleware::{
+    reject_unauthenticated, reject_unauthorized_api_key, reject_unauthorized_customer,
+    reject_unauthorized_staff,
+};
+use crate::authentication::routes::{
+    end_all_sessions, end_customer_sessions, end_session, jwks, list_sessions, resend_activation,
+};
+use crate::beneficiary::routes::{
+    create_beneficiary, delete_beneficiary, fetch_beneficiary, list_beneficiaries,
+    request_beneficiary_challenge, update_beneficiary,
+};
+use crate::branch::routes::{
+    assign_branch_staff, branch_report, create_branch, create_legal_entity, list_branch_staff,
+    list_branches, list_legal_entities, unassign_branch_staff,
+};
 use crate::config::{runtime::Config, state::AppState};
-use crate::customer::routes::{confirm_customer, customer_login, customer_signup};
+use crate::customer::routes::{
+    confirm_customer, confirm_email_change, customer_login, customer_profile_history,
+    customer_profile_status, customer_signup, fetch_customer_profile, fetch_user_docs,
+    request_email_change, submit_kyc_case, update_customer_address, update_customer_phone,
+    upload_user_docs,
+};
+use crate::fiscal::routes::{
+    close_fiscal_year, create_fiscal_year, list_fiscal_years, set_fiscal_period_status,
+};
+use crate::hold::routes::{authorize_card, list_holds, place_hold, release_hold};
+use crate::identity_verify::{
+    models::MAX_DOCUMENT_BYTES,
+    routes::{customer_kyc_documents, fetch_kyc_case, list_kyc_cases, review_kyc_case},
+};
+use crate::inbound_payment::routes::{
+    fetch_inbound_payment, import_inbound_payments, list_inbound_payments, reject_inbound_payment,
+    resolve_inbound_payment,
+};
 use crate::index::{health_check, index_page};
-use crate::ledger::routes::{journal_entry, journal_entry_by_id};
+use crate::ledger::routes::{journal_entry, journal_entry_by_id, post_adjusting_entry};
+use crate::nostro::routes::{
+    import_nostro_statement, list_nostro_items, list_nostro_statements, list_open_nostro_lines,
+    match_nostro_item, nostro_aging, reconcile_nostro, unmatch_nostro_item,
+};
 use crate::openapi_docs::ApiDoc;
+use crate::overdraft::routes::{
+    fetch_overdraft, grant_overdraft, overdraft_utilization, post_forced_debit, revoke_overdraft,
+    run_overdraft_accruals,
+};
+use crate::product::routes::{
+    create_product, fetch_product, list_product_versions, list_products, retire_product,
+    update_product,
+};
+use crate::reporting::routes::{
+    balance_sheet, download_account_statement, export_account_camt053, income_statement,
+    list_account_statements, run_account_statements, staff_export_account_camt053,
+};
+use crate::scheduled_payment::routes::{
+    cancel_scheduled_payment, create_scheduled_payment, fetch_scheduled_payment,
+    list_scheduled_payments, run_payment_scheduler,
+};
+use crate::screening::{
+    models::MAX_WATCHLIST_BYTES,
+    routes::{list_screening_hits, list_watchlists, load_watchlist, review_screening_hit},
+};
+use crate::sepa::routes::{
+    create_sepa_transfer, fetch_sepa_batch, fetch_sepa_transfer, generate_sepa_batch,
+    import_sepa_status_reports, list_sepa_batches, list_sepa_transfers,
+};
 use crate::staff::routes::{
-    confirm_staff, create_account_type, create_chart_account, create_customer_account, staff_login,
-    staff_signup,
+    confirm_staff, create_chart_account, create_customer_account, customer_account_history,
+    fetch_customer_account, fetch_customer_overview, search_customers, staff_login, staff_signup,
+    staff_sso_callback, staff_sso_login, update_customer_account,
+};
+use crate::swift::routes::{
+    create_swift_transfer, fetch_swift_transfer, list_fx_rates, list_swift_inbound,
+    list_swift_transfers, receive_swift_message, set_fx_rate,
 };
-use crate::transaction::routes::{deposit_funds, withdraw_funds};
+use crate::transaction::routes::{deposit_funds, integration_deposit_funds, withdraw_funds};
 
 async fn run(listener: TcpListener, app_state: AppState) -> Result<Server, anyhow::Error> {
     let secret_key = Key::from(app_state.secret.0.as_bytes());
@@ -39,36 +109,314 @@ async fn run(listener: TcpListener, app_state: AppState) -> Result<Server, anyho
                 secret_key.clone(),
             ))
             .app_data(app_state.clone())
+            // Uploads are kept in memory, they are hashed and sent on to S3 in one go
+            .app_data(
+                MultipartFormConfig::default()
+                    .total_limit(MAX_DOCUMENT_BYTES + 64 * 1024)
+                    .memory_limit(MAX_DOCUMENT_BYTES + 64 * 1024),
+            )
             .service(Scalar::with_url("/docs", openapi))
             .service(
                 web::scope("/home")
                     .route("/health", web::get().to(health_check))
                     .route("/index", web::get().to(index_page)),
             )
+            .route("/.well-known/jwks.json", web::get().to(jwks))
+            .route("/auth/activation/resend", web::post().to(resend_activation))
+            .service(
+                web::scope("/auth/sessions")
+                    .wrap(from_fn(reject_unauthenticated))
+                    .route("", web::get().to(list_sessions))
+                    .route("", web::delete().to(end_all_sessions))
+                    .route("/{session_id}", web::delete().to(end_session)),
+            )
             .route("/staff/signup", web::post().to(staff_signup))
             .route("/staff/login", web::post().to(staff_login))
+            .route("/staff/sso/login", web::get().to(staff_sso_login))
+            .route("/staff/sso/callback", web::get().to(staff_sso_callback))
             .route("/staff/confirm/{token}", web::get().to(confirm_staff))
             .service(
                 web::scope("/staff")
                     .wrap(from_fn(reject_unauthorized_staff))
                     .route("/user/signup", web::post().to(create_customer_account))
                     .route("/coa", web::post().to(create_chart_account))
-                    .route("/type", web::post().to(create_account_type))
-                    .route("/account", web::post().to(open_customer_account)),
+                    .route("/account", web::post().to(open_customer_account))
+                    .route("/api-keys", web::post().to(issue_api_key))
+                    .route("/api-keys", web::get().to(list_api_keys))
+                    .route("/api-keys/{key_id}/rotate", web::post().to(rotate_api_key))
+                    .route("/api-keys/{key_id}", web::delete().to(revoke_api_key))
+                    .route(
+                        "/customers/{customer_id}/sessions",
+                        web::delete().to(end_customer_sessions),
+                    )
+                    .route("/customers", web::get().to(search_customers))
+                    .route(
+                        "/customers/{customer_id}",
+                        web::get().to(fetch_customer_overview),
+                    )
+                    .route(
+                        "/customers/{customer_id}/profile",
+                        web::get().to(fetch_customer_account),
+                    )
+                    .route(
+                        "/customers/{customer_id}/profile",
+                        web::put().to(update_customer_account),
+                    )
+                    .route(
+                        "/customers/{customer_id}/profile/history",
+                        web::get().to(customer_account_history),
+                    )
+                    .route(
+                        "/customers/{customer_id}/kyc/documents",
+                        web::get().to(customer_kyc_documents),
+                    )
+                    .route("/kyc/cases", web::get().to(list_kyc_cases))
+                    .route("/kyc/cases/{case_id}", web::get().to(fetch_kyc_case))
+                    .route(
+                        "/kyc/cases/{case_id}/review",
+                        web::post().to(review_kyc_case),
+                    )
+                    // Published lists are far larger than the app wide upload limit
+                    .service(
+                        web::resource("/screening/lists")
+                            .app_data(
+                                MultipartFormConfig::default()
+                                    .total_limit(MAX_WATCHLIST_BYTES + 64 * 1024)
+                                    .memory_limit(MAX_WATCHLIST_BYTES + 64 * 1024),
+                            )
+                            .route(web::post().to(load_watchlist))
+                            .route(web::get().to(list_watchlists)),
+                    )
+                    .route("/legal-entities", web::post().to(create_legal_entity))
+                    .route("/legal-entities", web::get().to(list_legal_entities))
+                    .route("/branches", web::post().to(create_branch))
+                    .route("/branches", web::get().to(list_branches))
+                    .route(
+                        "/branches/{branch_id}/staff",
+                        web::post().to(assign_branch_staff),
+                    )
+                    .route(
+                        "/branches/{branch_id}/staff",
+                        web::get().to(list_branch_staff),
+                    )
+                    .route(
+                        "/branches/{branch_id}/staff/{staff_id}",
+                        web::delete().to(unassign_branch_staff),
+                    )
+                    .route("/branches/{branch_id}/report", web::get().to(branch_report))
+                    .route("/products", web::post().to(create_product))
+                    .route("/products", web::get().to(list_products))
+                    .route("/products/{product_id}", web::get().to(fetch_product))
+                    .route("/products/{product_id}", web::put().to(update_product))
+                    .route("/products/{product_id}", web::delete().to(retire_product))
+                    .route(
+                        "/products/{product_id}/versions",
+                        web::get().to(list_product_versions),
+                    )
+                    .route(
+                        "/accounts/{account_id}/overdraft",
+                        web::put().to(grant_overdraft),
+                    )
+                    .route(
+                        "/accounts/{account_id}/overdraft",
+                        web::get().to(fetch_overdraft),
+                    )
+                    .route(
+                        "/accounts/{account_id}/overdraft",
+                        web::delete().to(revoke_overdraft),
+                    )
+                    .route(
+                        "/accounts/{account_id}/forced-debits",
+                        web::post().to(post_forced_debit),
+                    )
+                    .route("/accounts/{account_id}/holds", web::post().to(place_hold))
+                    .route("/accounts/{account_id}/holds", web::get().to(list_holds))
+                    .route(
+                        "/accounts/{account_id}/holds/{hold_id}",
+                        web::delete().to(release_hold),
+                    )
+                    .route(
+                        "/overdrafts/accruals",
+                        web::post().to(run_overdraft_accruals),
+                    )
+                    .route(
+                        "/overdrafts/utilization",
+                        web::get().to(overdraft_utilization),
+                    )
+                    .route(
+                        "/scheduled-payments/run",
+                        web::post().to(run_payment_scheduler),
+                    )
+                    .route("/sepa/batches", web::post().to(generate_sepa_batch))
+                    .route("/sepa/batches", web::get().to(list_sepa_batches))
+                    .route("/sepa/batches/{batch_id}", web::get().to(fetch_sepa_batch))
+                    .route(
+                        "/sepa/status-reports/import",
+                        web::post().to(import_sepa_status_reports),
+                    )
+                    .route(
+                        "/inbound-payments/import",
+                        web::post().to(import_inbound_payments),
+                    )
+                    .route("/inbound-payments", web::get().to(list_inbound_payments))
+                    .route(
+                        "/inbound-payments/{payment_id}",
+                        web::get().to(fetch_inbound_payment),
+                    )
+                    .route(
+                        "/inbound-payments/{payment_id}/resolve",
+                        web::post().to(resolve_inbound_payment),
+                    )
+                    .route(
+                        "/inbound-payments/{payment_id}/reject",
+                        web::post().to(reject_inbound_payment),
+                    )
+                    .route("/ach/files", web::post().to(generate_ach_file))
+                    .route("/ach/files", web::get().to(list_ach_files))
+                    .route("/ach/files/{file_id}", web::get().to(fetch_ach_file))
+                    .route("/ach/returns/import", web::post().to(import_ach_returns))
+                    .route("/swift/fx-rates", web::put().to(set_fx_rate))
+                    .route("/swift/fx-rates", web::get().to(list_fx_rates))
+                    .route("/swift/inbound", web::post().to(receive_swift_message))
+                    .route("/swift/inbound", web::get().to(list_swift_inbound))
+                    .route(
+                        "/nostro/statements",
+                        web::post().to(import_nostro_statement),
+                    )
+                    .route("/nostro/statements", web::get().to(list_nostro_statements))
+                    .route("/nostro/reconcile", web::post().to(reconcile_nostro))
+                    .route("/nostro/items", web::get().to(list_nostro_items))
+                    .route(
+                        "/nostro/ledger-lines",
+                        web::get().to(list_open_nostro_lines),
+                    )
+                    .route(
+                        "/nostro/items/{item_id}/match",
+                        web::post().to(match_nostro_item),
+                    )
+                    .route(
+                        "/nostro/items/{item_id}/unmatch",
+                        web::post().to(unmatch_nostro_item),
+                    )
+                    .route("/nostro/aging", web::get().to(nostro_aging))
+                    .route("/statements/run", web::post().to(run_account_statements))
+                    .route(
+                        "/accounts/{account_id}/statements/camt053",
+                        web::get().to(staff_export_account_camt053),
+                    )
+                    .route("/fiscal-years", web::post().to(create_fiscal_year))
+                    .route("/fiscal-years", web::get().to(list_fiscal_years))
+                    .route(
+                        "/fiscal-years/{year_id}/close",
+                        web::post().to(close_fiscal_year),
+                    )
+                    .route(
+                        "/fiscal-periods/{period_id}/status",
+                        web::put().to(set_fiscal_period_status),
+                    )
+                    .route("/screening/hits", web::get().to(list_screening_hits))
+                    .route(
+                        "/screening/hits/{hit_id}/review",
+                        web::post().to(review_screening_hit),
+                    ),
+            )
+            .service(
+                web::scope("/reporting")
+                    .wrap(from_fn(reject_unauthorized_staff))
+                    .route("/balance-sheet", web::get().to(balance_sheet))
+                    .route("/income-statement", web::get().to(income_statement)),
             )
             .service(
                 web::scope("/ledger")
                     .wrap(from_fn(reject_unauthorized_staff))
                     .route("/journal", web::get().to(journal_entry))
-                    .route("/journal/{journal_id}", web::get().to(journal_entry_by_id)),
+                    .route("/journal/{journal_id}", web::get().to(journal_entry_by_id))
+                    .route("/adjustments", web::post().to(post_adjusting_entry)),
             )
             .route("/customer/signup", web::post().to(customer_signup))
             .route("/customer/login", web::post().to(customer_login))
             .route("/customer/confirm/{token}", web::get().to(confirm_customer))
+            .route(
+                "/customer/email/confirm/{token}",
+                web::get().to(confirm_email_change),
+            )
             .service(
                 web::scope("/customer")
                     .wrap(from_fn(reject_unauthorized_customer))
-                    .route("/account", web::post().to(open_customer_account)),
+                    .route("/account", web::post().to(open_customer_account))
+                    .route("/kyc/documents", web::post().to(upload_user_docs))
+                    .route("/kyc/documents", web::get().to(fetch_user_docs))
+                    .route("/kyc/submit", web::post().to(submit_kyc_case))
+                    .route("/kyc/status", web::get().to(customer_profile_status))
+                    .route("/profile", web::get().to(fetch_customer_profile))
+                    .route("/profile/email", web::post().to(request_email_change))
+                    .route("/profile/address", web::put().to(update_customer_address))
+                    .route("/profile/phone", web::put().to(update_customer_phone))
+                    .route("/profile/history", web::get().to(customer_profile_history))
+                    .route(
+                        "/scheduled-payments",
+                        web::post().to(create_scheduled_payment),
+                    )
+                    .route(
+                        "/scheduled-payments",
+                        web::get().to(list_scheduled_payments),
+                    )
+                    .route(
+                        "/scheduled-payments/{payment_id}",
+                        web::get().to(fetch_scheduled_payment),
+                    )
+                    .route(
+                        "/scheduled-payments/{payment_id}",
+                        web::delete().to(cancel_scheduled_payment),
+                    )
+                    .route(
+                        "/beneficiaries/challenge",
+                        web::post().to(request_beneficiary_challenge),
+                    )
+                    .route("/beneficiaries", web::post().to(create_beneficiary))
+                    .route("/beneficiaries", web::get().to(list_beneficiaries))
+                    .route(
+                        "/beneficiaries/{beneficiary_id}",
+                        web::get().to(fetch_beneficiary),
+                    )
+                    .route(
+                        "/beneficiaries/{beneficiary_id}",
+                        web::put().to(update_beneficiary),
+                    )
+                    .route(
+                        "/beneficiaries/{beneficiary_id}",
+                        web::delete().to(delete_beneficiary),
+                    )
+                    .route("/sepa-transfers", web::post().to(create_sepa_transfer))
+                    .route("/sepa-transfers", web::get().to(list_sepa_transfers))
+                    .route(
+                        "/sepa-transfers/{transfer_id}",
+                        web::get().to(fetch_sepa_transfer),
+                    )
+                    .route("/ach-transfers", web::post().to(create_ach_transfer))
+                    .route("/ach-transfers", web::get().to(list_ach_transfers))
+                    .route(
+                        "/ach-transfers/{transfer_id}",
+                        web::get().to(fetch_ach_transfer),
+                    )
+                    .route("/swift-transfers", web::post().to(create_swift_transfer))
+                    .route("/swift-transfers", web::get().to(list_swift_transfers))
+                    .route(
+                        "/swift-transfers/{transfer_id}",
+                        web::get().to(fetch_swift_transfer),
+                    )
+                    .route(
+                        "/accounts/{account_id}/statements",
+                        web::get().to(list_account_statements),
+                    )
+                    .route(
+                        "/accounts/{account_id}/statements/{statement_id}/download",
+                        web::get().to(download_account_statement),
+                    )
+                    .route(
+                        "/accounts/{account_id}/statements/camt053",
+                        web::get().to(export_account_camt053),
+                    ),
             )
             .service(
                 web::scope("/transaction")
@@ -76,6 +424,18 @@ async fn run(listener: TcpListener, app_state: AppState) -> Result<Server, anyho
                     .route("/deposit", web::post().to(deposit_funds))
                     .route("/withdraw", web::post().to(withdraw_funds)),
             )
+            .service(
+                web::scope("/integration")
+                    .wrap(from_fn(reject_unauthorized_api_key))
+                    .route(
+                        "/transaction/deposit/{account_id}",
+                        web::post().to(integration_deposit_funds),
+                    )
+                    .route(
+                        "/card/authorizations/{account_id}",
+                        web::post().to(authorize_card),
+                    ),
+            )
     })
     .listen(listener)?
     .run();
@@ -0,0 +1,18 @@
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::swift::routes::create_swift_transfer,
+    crate::swift::routes::list_swift_transfers,
+    crate::swift::routes::fetch_swift_transfer,
+))]
+pub struct SwiftTransferApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    crate::swift::routes::set_fx_rate,
+    crate::swift::routes::list_fx_rates,
+    crate::swift::routes::receive_swift_message,
+    crate::swift::routes::list_swift_inbound,
+))]
+pub struct SwiftApi;
@@ -0,0 +1,502 @@
+use chrono::NaiveDate;
+
+use crate::base::error::ValidationError;
+use crate::sepa::models::{is_sepa_char, sepa_text};
+
+// Free text fields are made of lines of at most 35 characters
+pub const LINE_LEN: usize = 35;
+// Amounts are at most 15 characters, the decimal comma included
+const MAX_AMOUNT_LEN: usize = 15;
+
+pub fn invalid_field(tag: &str, reason: &str) -> ValidationError {
+    ValidationError::InvalidValue {
+        field: tag.into(),
+        reason: reason.into(),
+    }
+}
+
+// The SWIFT X character set is the same basic Latin set SEPA uses
+pub fn swift_text(value: &str) -> String {
+    sepa_text(value)
+}
+
+// Text is broken into lines of 35 on word boundaries where it can be, whatever doesn't fit in
+// max_lines is cut off
+pub fn wrap_lines(value: &str, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in swift_text(value).split_whitespace() {
+        let mut word = word;
+        loop {
+            let room = if line.is_empty() {
+                LINE_LEN
+            } else {
+                LINE_LEN.saturating_sub(line.len() + 1)
+            };
+
+            if word.len() <= room {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                break;
+            }
+
+            if line.is_empty() {
+                // A word longer than a line is split
+                line.push_str(&word[..LINE_LEN]);
+                word = &word[LINE_LEN..];
+            }
+            lines.push(std::mem::take(&mut line));
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines.truncate(max_lines);
+    lines
+}
+
+// Amounts take a decimal comma and no thousands separator, 1250,00
+pub fn format_amount(amount_cents: i64) -> String {
+    format!("{},{:02}", amount_cents / 100, amount_cents % 100)
+}
+
+pub fn parse_amount(tag: &str, value: &str) -> Result<i64, ValidationError> {
+    if value.is_empty() || value.len() > MAX_AMOUNT_LEN {
+        return Err(invalid_field(tag, "Amount must be 1 to 15 characters"));
+    }
+
+    let (units, fraction) = match value.split_once(',') {
+        Some(parts) => parts,
+        None => return Err(invalid_field(tag, "Amount must have a decimal comma")),
+    };
+
+    let digits_only = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if units.is_empty() || !digits_only(units) || !digits_only(fraction) || fraction.len() > 2 {
+        return Err(invalid_field(
+            tag,
+            "Amount must be digits with at most two decimals",
+        ));
+    }
+
+    let units: i64 = units
+        .parse()
+        .map_err(|_| invalid_field(tag, "Amount is too large"))?;
+    let cents = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<i64>().unwrap_or_default() * 10,
+        _ => fraction.parse::<i64>().unwrap_or_default(),
+    };
+
+    units
+        .checked_mul(100)
+        .and_then(|c| c.checked_add(cents))
+        .ok_or_else(|| invalid_field(tag, "Amount is too large"))
+}
+
+pub fn parse_currency(tag: &str, value: &str) -> Result<String, ValidationError> {
+    if value.len() != 3 || !value.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(invalid_field(tag, "Currency must be three capital letters"));
+    }
+
+    Ok(value.to_string())
+}
+
+// Dates are YYMMDD, all of them in this century
+pub fn parse_date(tag: &str, value: &str) -> Result<NaiveDate, ValidationError> {
+    if value.len() != 6 || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_field(tag, "Date must be YYMMDD"));
+    }
+
+    NaiveDate::parse_from_str(&format!("20{}", value), "%Y%m%d")
+        .map_err(|_| invalid_field(tag, "Date does not exist"))
+}
+
+pub fn parse_bic(tag: &str, value: &str) -> Result<String, ValidationError> {
+    let well_formed = value.is_ascii()
+        && matches!(value.len(), 8 | 11)
+        && value[..6].chars().all(|c| c.is_ascii_uppercase())
+        && value[6..]
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if !well_formed {
+        return Err(invalid_field(tag, "BIC must be 8 or 11 characters"));
+    }
+
+    Ok(value.to_string())
+}
+
+// Logical terminal addresses are the BIC8, a terminal code and the branch code
+fn bic_of_terminal(terminal: &str) -> String {
+    match terminal.len() {
+        12 if terminal.is_ascii() => format!("{}{}", &terminal[..8], &terminal[9..]),
+        _ => terminal.to_string(),
+    }
+}
+
+fn terminal_of_bic(bic: &str) -> String {
+    match bic.len() {
+        8 => format!("{}AXXX", bic),
+        11 if bic.is_ascii() => format!("{}A{}", &bic[..8], &bic[8..]),
+        _ => bic.to_string(),
+    }
+}
+
+// The value of a 32A field, and of 33B and 71F without the date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Money {
+    pub currency: String,
+    pub amount_cents: i64,
+}
+
+impl Money {
+    pub fn parse(tag: &str, value: &str) -> Result<Self, ValidationError> {
+        match (value.get(..3), value.get(3..)) {
+            (Some(currency), Some(amount)) => Ok(Self {
+                currency: parse_currency(tag, currency)?,
+                amount_cents: parse_amount(tag, amount)?,
+            }),
+            _ => Err(invalid_field(tag, "Expected a currency and an amount")),
+        }
+    }
+
+    pub fn to_field(&self) -> String {
+        format!("{}{}", self.currency, format_amount(self.amount_cents))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDated {
+    pub value_date: NaiveDate,
+    pub money: Money,
+}
+
+impl ValueDated {
+    pub fn parse(tag: &str, value: &str) -> Result<Self, ValidationError> {
+        match (value.get(..6), value.get(6..)) {
+            (Some(date), Some(money)) => Ok(Self {
+                value_date: parse_date(tag, date)?,
+                money: Money::parse(tag, money)?,
+            }),
+            _ => Err(invalid_field(
+                tag,
+                "Expected a date, a currency and an amount",
+            )),
+        }
+    }
+
+    pub fn to_field(&self) -> String {
+        format!(
+            "{}{}",
+            self.value_date.format("%y%m%d"),
+            self.money.to_field()
+        )
+    }
+}
+
+// A party given as an optional /account line followed by name and address lines, the way
+// 50K and 59 are written
+#[derive(Debug, Clone, PartialEq)]
+pub struct Party {
+    pub account: Option<String>,
+    pub name_and_address: Vec<String>,
+}
+
+impl Party {
+    pub fn parse(tag: &str, value: &str) -> Result<Self, ValidationError> {
+        let mut lines = value.lines();
+        let mut account = None;
+        let mut name_and_address = Vec::new();
+
+        if let Some(first) = lines.next() {
+            match first.strip_prefix('/') {
+                Some(number) if !number.is_empty() => account = Some(number.to_string()),
+                Some(_) => return Err(invalid_field(tag, "Account line is empty")),
+                None => name_and_address.push(first.to_string()),
+            }
+        }
+        name_and_address.extend(lines.map(str::to_string));
+
+        if name_and_address.is_empty() {
+            return Err(invalid_field(tag, "A name is required"));
+        }
+        if name_and_address.len() > 4 {
+            return Err(invalid_field(tag, "At most four name and address lines"));
+        }
+        if name_and_address.iter().any(|l| l.len() > LINE_LEN) {
+            return Err(invalid_field(tag, "Lines are at most 35 characters"));
+        }
+
+        Ok(Self {
+            account,
+            name_and_address,
+        })
+    }
+
+    pub fn to_field(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(account) = &self.account {
+            lines.push(format!("/{}", account));
+        }
+        lines.extend(self.name_and_address.iter().cloned());
+        lines.join("\r\n")
+    }
+
+    pub fn name(&self) -> &str {
+        self.name_and_address
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+// A FIN message cut into its header blocks and the tagged fields of the text block, in order
+#[derive(Debug, PartialEq)]
+pub struct FinMessage {
+    pub message_type: String,
+    pub sender_bic: String,
+    pub receiver_bic: String,
+    // Unique end-to-end transaction reference from the user header
+    pub uetr: Option<String>,
+    pub fields: Vec<(String, String)>,
+}
+
+impl FinMessage {
+    pub fn new(message_type: &str, sender_bic: &str, receiver_bic: &str) -> Self {
+        Self {
+            message_type: message_type.into(),
+            sender_bic: sender_bic.into(),
+            receiver_bic: receiver_bic.into(),
+            uetr: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, tag: &str, value: String) {
+        self.fields.push((tag.into(), value));
+    }
+
+    pub fn field(&self, tag: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn required(&self, tag: &str) -> Result<&str, ValidationError> {
+        self.field(tag)
+            .ok_or_else(|| ValidationError::MissingField(tag.into()))
+    }
+
+    // Of a field that comes with options, such as 50A, 50F or 50K, the first one present
+    pub fn field_option(&self, tag: &str, options: &str) -> Option<(String, &str)> {
+        options.chars().find_map(|option| {
+            let tag = format!("{}{}", tag, option);
+            self.field(&tag).map(|value| (tag, value))
+        })
+    }
+
+    // Written as an input message from the sender, with CRLF line breaks as FIN requires
+    pub fn to_fin(&self) -> String {
+        let mut text = format!(
+            "{{1:F01{}0000000000}}{{2:I{}{}N}}",
+            terminal_of_bic(&self.sender_bic),
+            self.message_type,
+            terminal_of_bic(&self.receiver_bic)
+        );
+
+        if let Some(uetr) = &self.uetr {
+            text.push_str(&format!("{{3:{{121:{}}}}}", uetr));
+        }
+
+        text.push_str("{4:\r\n");
+        for (tag, value) in &self.fields {
+            text.push_str(&format!(":{}:{}\r\n", tag, value));
+        }
+        text.push_str("-}");
+
+        text
+    }
+
+    // Takes input messages as we would send them and output messages as the network delivers
+    // them. Field values keep their line breaks as \n.
+    pub fn parse(text: &str) -> Result<Self, ValidationError> {
+        let text = text.replace("\r\n", "\n");
+
+        let basic = block(&text, "1").ok_or(ValidationError::MissingField("block 1".into()))?;
+        let application =
+            block(&text, "2").ok_or(ValidationError::MissingField("block 2".into()))?;
+        let body = match (text.find("{4:"), text.rfind("-}")) {
+            (Some(start), Some(end)) if start < end => &text[start + 3..end],
+            _ => return Err(ValidationError::MissingField("block 4".into())),
+        };
+
+        // Header blocks are plain ASCII, the addresses in them are cut by position
+        if !basic.is_ascii() {
+            return Err(ValidationError::InvalidFormat("block 1".into()));
+        }
+        if !application.is_ascii() {
+            return Err(ValidationError::InvalidFormat("block 2".into()));
+        }
+
+        let own_terminal = match basic.get(3..15) {
+            Some(t) if basic.starts_with("F01") => t,
+            _ => return Err(ValidationError::InvalidFormat("block 1".into())),
+        };
+
+        // Input messages name the receiver, output ones carry the sender in the input reference
+        let (message_type, sender, receiver) = match (
+            application.get(..1),
+            application.get(1..4),
+            application.get(4..16),
+            application.get(14..26),
+        ) {
+            (Some("I"), Some(mt), Some(receiver), _) => (mt, own_terminal, receiver),
+            (Some("O"), Some(mt), _, Some(sender)) => (mt, sender, own_terminal),
+            _ => return Err(ValidationError::InvalidFormat("block 2".into())),
+        };
+
+        let uetr = text.find("{121:").and_then(|start| {
+            text[start + 5..]
+                .split_once('}')
+                .map(|(uetr, _)| uetr.to_string())
+        });
+
+        let fields = parse_fields(body)?;
+
+        Ok(Self {
+            message_type: message_type.to_string(),
+            sender_bic: bic_of_terminal(sender),
+            receiver_bic: bic_of_terminal(receiver),
+            uetr,
+            fields,
+        })
+    }
+}
+
+// The fields of block 4, or of a bare message without the envelope; a field runs until the next tag
+pub fn parse_fields(body: &str) -> Result<Vec<(String, String)>, ValidationError> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in body.replace("\r\n", "\n").lines() {
+        if line.is_empty() && fields.is_empty() {
+            continue;
+        }
+
+        match tagged(line) {
+            Some((tag, value)) => fields.push((tag.to_string(), value.to_string())),
+            None => match fields.last_mut() {
+                Some((_, value)) => {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+                None => return Err(ValidationError::InvalidFormat("block 4".into())),
+            },
+        }
+    }
+
+    for (tag, value) in &fields {
+        if !value.chars().all(|c| c == '\n' || is_sepa_char(c)) {
+            return Err(invalid_field(tag, "Only SWIFT X characters are allowed"));
+        }
+    }
+
+    Ok(fields)
+}
+
+fn block<'t>(text: &'t str, id: &str) -> Option<&'t str> {
+    let start = text.find(&format!("{{{}:", id))? + id.len() + 2;
+    let end = text[start..].find('}')?;
+    Some(&text[start..start + end])
+}
+
+// A field starts a line with its tag between colons, two digits and an optional option letter
+fn tagged(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let (tag, value) = rest.split_once(':')?;
+
+    let well_formed = tag.is_ascii()
+        && matches!(tag.len(), 2 | 3)
+        && tag[..2].chars().all(|c| c.is_ascii_digit())
+        && tag[2..].chars().all(|c| c.is_ascii_uppercase());
+
+    well_formed.then_some((tag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    #[test]
+    fn amounts_take_a_decimal_comma() {
+        assert_eq!(format_amount(125_000), "1250,00");
+        assert_eq!(format_amount(5), "0,05");
+        assert_eq!(assert_ok!(parse_amount("32A", "1250,5")), 125_050);
+        assert_eq!(assert_ok!(parse_amount("32A", "1250,")), 125_000);
+
+        let _ = assert_err!(parse_amount("32A", "1250.00"));
+        let _ = assert_err!(parse_amount("32A", "1250,001"));
+        let _ = assert_err!(parse_amount("32A", ",50"));
+    }
+
+    #[test]
+    fn long_text_is_wrapped_into_lines_of_35() {
+        let lines = wrap_lines(
+            "Zoë Hernández-López Enterprises Internacional de Exportación S.A.",
+            4,
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "Zoe Hernandez-Lopez Enterprises",
+                "Internacional de Exportacion S.A.",
+            ]
+        );
+        assert!(
+            wrap_lines(&"A".repeat(200), 4)
+                .iter()
+                .all(|l| l.len() == 35)
+        );
+        assert_eq!(wrap_lines(&"A".repeat(200), 4).len(), 4);
+    }
+
+    #[test]
+    fn output_messages_name_the_sender_in_the_input_reference() {
+        let text = "{1:F01THALGB2LAXXX0000000000}{2:O1031200251219CHASUS33AXXX00000000002512191200N}{4:\r\n:20:REF1\r\n:59:/GB82WEST12345698765432\r\nJANE DOE\r\n-}";
+
+        let message = assert_ok!(FinMessage::parse(text));
+        assert_eq!(message.message_type, "103");
+        assert_eq!(message.sender_bic, "CHASUS33XXX");
+        assert_eq!(message.receiver_bic, "THALGB2LXXX");
+        assert_eq!(
+            message.field("59"),
+            Some("/GB82WEST12345698765432\nJANE DOE")
+        );
+
+        let _ = assert_err!(FinMessage::parse("{1:F01THALGB2LAXXX0000000000}"));
+    }
+
+    #[test]
+    fn non_ascii_input_is_refused_rather_than_cut() {
+        let text = "{1:F01THALGB2LAXXX0000000000}{2:O1031200251219CHASUS33AXXX00000000002512191200N}{4:\r\n:20:REF1\r\n-}";
+
+        let _ = assert_err!(FinMessage::parse(
+            &text.replace("THALGB2LAXXX", "THALGB2LÄXX")
+        ));
+        let _ = assert_err!(FinMessage::parse(
+            &text.replace("CHASUS33AXXX", "CHASUS3ÉAXX")
+        ));
+        let _ = assert_err!(FinMessage::parse(&text.replace(":20:", ":2é:")));
+        let _ = assert_err!(parse_fields(":20:REF1\n:1é:VALUE"));
+        let _ = assert_err!(parse_fields(":1é:VALUE"));
+
+        let _ = assert_err!(parse_bic("57A", "COBADEFÜXX"));
+        let _ = assert_err!(parse_bic("57A", "AAAAAÄB"));
+        assert_eq!(bic_of_terminal("THALGB2LÄXX"), "THALGB2LÄXX");
+        assert_eq!(terminal_of_bic("THALGB2LÄX"), "THALGB2LÄX");
+    }
+}
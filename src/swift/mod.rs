@@ -0,0 +1,9 @@
+pub mod docs;
+pub mod fin;
+pub mod models;
+pub mod mt103;
+pub mod mt202;
+pub mod repo;
+pub mod routes;
+pub mod schemas;
+pub mod service;
@@ -0,0 +1,610 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::path::PathBuf;
+use std::str::FromStr;
+use strum::Display;
+use uuid::Uuid;
+
+use crate::account::models::UserAccountStatus;
+use crate::base::error::{DomainError, ValidationError};
+use crate::beneficiary::models::BeneficiaryEntity;
+use crate::sepa::models::is_sepa_char;
+use crate::swift::fin::{Money, Party, ValueDated, swift_text, wrap_lines};
+use crate::swift::mt103::Mt103;
+use crate::swift::schemas::SwiftTransferRequest;
+
+// Outbound payments leave through our accounts at correspondent banks, and inbound ones arrive there
+pub const NOSTRO_COA: &str = "1030";
+// The margin on the exchange rate a customer pays for a transfer in another currency
+pub const FX_GAINS_COA: &str = "4220";
+// Product fees, ours on every transfer and the correspondents' we prepay when charges are OUR
+pub const TRANSFER_FEE_CODE: &str = "swift_transfer";
+pub const OUR_FEE_CODE: &str = "swift_our";
+
+// Exchange rates are held as units of the quote currency per unit of the base, times a million
+pub const RATE_SCALE: i64 = 1_000_000;
+const RATE_DECIMALS: usize = 6;
+const MAX_AMOUNT_CENTS: i64 = 99_999_999_999_999;
+const MAX_REMITTANCE_LEN: usize = 140;
+const MAX_NAME_LEN: usize = 70;
+const BASIS_POINTS: i64 = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct SwiftProfile {
+    pub sender_bic: String,
+    // Where our nostro account is held, every MT103 we send goes to them
+    pub correspondent_bic: String,
+    pub fx_margin_bps: i64,
+    pub outbound_dir: Option<PathBuf>,
+}
+
+impl SwiftProfile {
+    pub fn new(
+        sender_bic: &str,
+        correspondent_bic: &str,
+        fx_margin_bps: i64,
+        outbound_dir: Option<&str>,
+    ) -> Self {
+        Self {
+            sender_bic: sender_bic.trim().to_uppercase(),
+            correspondent_bic: correspondent_bic.trim().to_uppercase(),
+            fx_margin_bps: fx_margin_bps.clamp(0, BASIS_POINTS - 1),
+            outbound_dir: outbound_dir.map(PathBuf::from),
+        }
+    }
+
+    // Branch codes aside, is a message with this receiver meant for us
+    pub fn is_addressed_to_us(&self, receiver_bic: &str) -> bool {
+        receiver_bic.get(..8) == self.sender_bic.get(..8)
+    }
+
+    // Field 20 of the MT103, sixteen characters
+    pub fn reference(&self, sequence: i64) -> String {
+        format!("THL{:013}", sequence)
+    }
+}
+
+// Who pays the banks' charges, the ordering customer, both sides or the beneficiary
+#[derive(
+    Debug, Clone, Copy, PartialEq, serde::Serialize, utoipa::ToSchema, sqlx::Type, Display,
+)]
+#[sqlx(type_name = "swift_charges", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum ChargeBearer {
+    Our,
+    Sha,
+    Ben,
+}
+
+impl FromStr for ChargeBearer {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "OUR" => Ok(Self::Our),
+            "SHA" => Ok(Self::Sha),
+            "BEN" => Ok(Self::Ben),
+            _ => Err(ValidationError::InvalidValue {
+                field: "charges".into(),
+                reason: "Use OUR, SHA or BEN".into(),
+            }),
+        }
+    }
+}
+
+// Rates are given as decimals, 1.0850, with up to six places
+pub fn parse_rate(value: &str) -> Result<i64, ValidationError> {
+    let invalid = || ValidationError::InvalidValue {
+        field: "rate".into(),
+        reason: "Must be a positive decimal with at most six places".into(),
+    };
+
+    let (units, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+    if units.is_empty()
+        || fraction.len() > RATE_DECIMALS
+        || !units
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let units: i64 = units.parse().map_err(|_| invalid())?;
+    let fraction: i64 = format!("{:0<width$}", fraction, width = RATE_DECIMALS)
+        .parse()
+        .map_err(|_| invalid())?;
+    match units
+        .checked_mul(RATE_SCALE)
+        .and_then(|r| r.checked_add(fraction))
+    {
+        Some(rate) if rate > 0 => Ok(rate),
+        _ => Err(invalid()),
+    }
+}
+
+pub fn format_rate(rate_micros: i64) -> String {
+    format!(
+        "{}.{:06}",
+        rate_micros / RATE_SCALE,
+        rate_micros % RATE_SCALE
+    )
+}
+
+// The mid rate from the account currency to the transfer currency, and the rate the customer
+// gets once our margin is taken off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FxQuote {
+    pub rate_micros: i64,
+    pub customer_rate_micros: i64,
+}
+
+impl FxQuote {
+    pub fn same_currency() -> Self {
+        Self {
+            rate_micros: RATE_SCALE,
+            customer_rate_micros: RATE_SCALE,
+        }
+    }
+
+    pub fn new(rate_micros: i64, margin_bps: i64) -> Self {
+        Self {
+            rate_micros,
+            customer_rate_micros: (rate_micros as i128 * (BASIS_POINTS - margin_bps) as i128
+                / BASIS_POINTS as i128) as i64,
+        }
+    }
+
+    pub fn is_conversion(&self) -> bool {
+        *self != Self::same_currency()
+    }
+
+    // Account currency cents it takes to buy an amount in the transfer currency, rounded up
+    fn cost(amount_cents: i64, rate_micros: i64) -> i64 {
+        let numerator = amount_cents as i128 * RATE_SCALE as i128;
+        let rate = rate_micros.max(1) as i128;
+        ((numerator + rate - 1) / rate) as i64
+    }
+
+    pub fn debit_cents(&self, amount_cents: i64) -> i64 {
+        Self::cost(amount_cents, self.customer_rate_micros)
+    }
+
+    pub fn margin_cents(&self, amount_cents: i64) -> i64 {
+        self.debit_cents(amount_cents) - Self::cost(amount_cents, self.rate_micros)
+    }
+
+    // What account currency cents come to in the transfer currency at the customer's rate
+    pub fn converted(&self, account_cents: i64) -> i64 {
+        (account_cents as i128 * self.customer_rate_micros as i128 / RATE_SCALE as i128) as i64
+    }
+}
+
+// What the customer asked for, checked before any account is touched
+#[derive(Debug)]
+pub struct TransferInstruction {
+    pub amount_cents: i64,
+    pub charges: ChargeBearer,
+    pub remittance_info: Option<String>,
+}
+
+impl TransferInstruction {
+    pub fn parse(request: &SwiftTransferRequest) -> Result<Self, ValidationError> {
+        if request.amount_cents <= 0 || request.amount_cents > MAX_AMOUNT_CENTS {
+            return Err(ValidationError::InvalidValue {
+                field: "amount_cents".into(),
+                reason: format!("Must be between 1 and {}", MAX_AMOUNT_CENTS),
+            });
+        }
+
+        let charges = request.charges.parse()?;
+
+        let remittance_info = match request.remittance_info.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(info) if info.chars().count() > MAX_REMITTANCE_LEN => {
+                return Err(ValidationError::TooLong {
+                    field: "remittance_info".into(),
+                    max: MAX_REMITTANCE_LEN,
+                });
+            }
+            Some(info) if !info.chars().all(is_sepa_char) => {
+                return Err(ValidationError::InvalidValue {
+                    field: "remittance_info".into(),
+                    reason: "Only letters, digits, spaces and / - ? : ( ) . , ' + are allowed"
+                        .into(),
+                });
+            }
+            Some(info) => Some(info.to_string()),
+        };
+
+        Ok(Self {
+            amount_cents: request.amount_cents,
+            charges,
+            remittance_info,
+        })
+    }
+}
+
+// The beneficiary as it goes in field 59, any beneficiary whose bank has a BIC qualifies
+#[derive(Debug)]
+pub struct Creditor {
+    pub name: String,
+    pub account: String,
+    pub bic: String,
+    pub currency: String,
+}
+
+impl Creditor {
+    pub fn from_beneficiary(beneficiary: &BeneficiaryEntity) -> Result<Self, DomainError> {
+        let bic = match &beneficiary.bic {
+            Some(bic) => bic.clone(),
+            None => {
+                return Err(DomainError::ConstraintViolation(
+                    "beneficiary's bank has no BIC".into(),
+                ));
+            }
+        };
+
+        let account = match (&beneficiary.iban, &beneficiary.account_number) {
+            (Some(iban), _) => iban.clone(),
+            (None, Some(number)) => number.clone(),
+            (None, None) => {
+                return Err(DomainError::ConstraintViolation(
+                    "beneficiary has no account".into(),
+                ));
+            }
+        };
+
+        Ok(Self {
+            name: swift_text(&beneficiary.name)
+                .chars()
+                .take(MAX_NAME_LEN)
+                .collect(),
+            account,
+            bic,
+            currency: beneficiary.currency.clone(),
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct OriginatorAccountEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub iban: String,
+    pub holder_name: String,
+    pub currency: String,
+    pub status: UserAccountStatus,
+}
+
+impl OriginatorAccountEntity {
+    pub fn check_debit(&self, amount_cents: i64, available_cents: i64) -> Result<(), DomainError> {
+        if !matches!(self.status, UserAccountStatus::Active) {
+            return Err(DomainError::InvalidState(format!(
+                "account is {}",
+                self.status
+            )));
+        }
+
+        if amount_cents > available_cents {
+            return Err(DomainError::ConstraintViolation(
+                "insufficient available funds".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// How a transfer splits into what reaches the beneficiary and what the customer is debited
+#[derive(Debug, PartialEq)]
+pub struct Pricing {
+    // In the transfer currency
+    pub settlement_cents: i64,
+    pub sender_charge_cents: i64,
+    // In the account currency, the principal includes the FX margin
+    pub principal_cents: i64,
+    pub fx_margin_cents: i64,
+    pub fee_cents: i64,
+}
+
+impl Pricing {
+    // With OUR the customer also pays the correspondents' fee. With BEN our fee comes out of
+    // the amount sent rather than on top of it.
+    pub fn new(
+        instruction: &TransferInstruction,
+        quote: FxQuote,
+        transfer_fee_cents: i64,
+        our_fee_cents: i64,
+    ) -> Result<Self, DomainError> {
+        let (settlement_cents, sender_charge_cents, fee_cents) = match instruction.charges {
+            ChargeBearer::Our => (
+                instruction.amount_cents,
+                0,
+                transfer_fee_cents + our_fee_cents,
+            ),
+            ChargeBearer::Sha => (instruction.amount_cents, 0, transfer_fee_cents),
+            ChargeBearer::Ben => {
+                let charge = quote.converted(transfer_fee_cents);
+                (
+                    instruction.amount_cents - charge,
+                    charge,
+                    transfer_fee_cents,
+                )
+            }
+        };
+
+        if settlement_cents <= 0 {
+            return Err(DomainError::ConstraintViolation(
+                "amount does not cover the charges".into(),
+            ));
+        }
+
+        Ok(Self {
+            settlement_cents,
+            sender_charge_cents,
+            principal_cents: quote.debit_cents(settlement_cents),
+            fx_margin_cents: quote.margin_cents(settlement_cents),
+            fee_cents,
+        })
+    }
+
+    pub fn total_debit_cents(&self) -> i64 {
+        self.principal_cents + self.fee_cents
+    }
+}
+
+// Journal reference of the principal, the FX margin and the fee add a suffix
+pub fn transfer_reference(reference: &str) -> String {
+    format!("SWF{}", reference)
+}
+
+pub fn fx_reference(reference: &str) -> String {
+    format!("{}-FX", transfer_reference(reference))
+}
+
+pub fn fee_reference(reference: &str) -> String {
+    format!("{}-FE", transfer_reference(reference))
+}
+
+// Reference of the entry crediting an inbound MT103, unique to the sending bank
+pub fn inbound_reference(sender_bic: &str, reference: &str) -> String {
+    format!("SWI{}{}", sender_bic, reference)
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct FxRateEntity {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate_micros: i64,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SwiftTransferEntity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    pub reference: String,
+    pub uetr: Uuid,
+    pub beneficiary_name: String,
+    pub beneficiary_account: String,
+    pub beneficiary_bic: String,
+    pub charges: ChargeBearer,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub settlement_cents: i64,
+    pub sender_charge_cents: i64,
+    pub debit_currency: String,
+    pub principal_cents: i64,
+    pub rate_micros: Option<i64>,
+    pub fx_margin_cents: i64,
+    pub fee_cents: i64,
+    pub remittance_info: Option<String>,
+    pub value_date: NaiveDate,
+    pub message_location: String,
+    pub journal_entry_id: Uuid,
+    pub fx_entry_id: Option<Uuid>,
+    pub fee_entry_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SwiftTransferEntity {
+    // Journal entries are filled in by the caller as they're posted
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        account: &OriginatorAccountEntity,
+        beneficiary_id: Uuid,
+        creditor: Creditor,
+        instruction: TransferInstruction,
+        quote: FxQuote,
+        pricing: Pricing,
+        reference: String,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id: account.user_id,
+            account_id: account.id,
+            beneficiary_id,
+            reference,
+            uetr: Uuid::new_v4(),
+            beneficiary_name: creditor.name,
+            beneficiary_account: creditor.account,
+            beneficiary_bic: creditor.bic,
+            charges: instruction.charges,
+            amount_cents: instruction.amount_cents,
+            currency: creditor.currency,
+            settlement_cents: pricing.settlement_cents,
+            sender_charge_cents: pricing.sender_charge_cents,
+            debit_currency: account.currency.clone(),
+            principal_cents: pricing.principal_cents,
+            rate_micros: quote.is_conversion().then_some(quote.customer_rate_micros),
+            fx_margin_cents: pricing.fx_margin_cents,
+            fee_cents: pricing.fee_cents,
+            remittance_info: instruction.remittance_info,
+            value_date: now.date_naive(),
+            message_location: String::new(),
+            journal_entry_id: Uuid::nil(),
+            fx_entry_id: None,
+            fee_entry_id: None,
+            created_at: now,
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("mt103_{}.fin", self.reference)
+    }
+
+    // 33B and 71F only go out when our fee was taken out of the amount
+    pub fn mt103(&self, account: &OriginatorAccountEntity, sender_bic: &str) -> Mt103 {
+        let deducted = self.charges == ChargeBearer::Ben;
+
+        Mt103 {
+            sender_reference: self.reference.clone(),
+            bank_operation_code: "CRED".into(),
+            settlement: ValueDated {
+                value_date: self.value_date,
+                money: Money {
+                    currency: self.currency.clone(),
+                    amount_cents: self.settlement_cents,
+                },
+            },
+            instructed: deducted.then(|| Money {
+                currency: self.currency.clone(),
+                amount_cents: self.amount_cents,
+            }),
+            exchange_rate: None,
+            ordering_customer: Party {
+                account: Some(account.iban.clone()),
+                name_and_address: wrap_lines(&account.holder_name, 4),
+            },
+            ordering_institution: Some(sender_bic.into()),
+            account_with_institution: Some(self.beneficiary_bic.clone()),
+            beneficiary: Party {
+                account: Some(self.beneficiary_account.clone()),
+                name_and_address: wrap_lines(&self.beneficiary_name, 4),
+            },
+            remittance_info: self
+                .remittance_info
+                .as_deref()
+                .map(|info| wrap_lines(info, 4))
+                .unwrap_or_default(),
+            charges: self.charges,
+            sender_charges: if deducted {
+                vec![Money {
+                    currency: self.currency.clone(),
+                    amount_cents: self.sender_charge_cents,
+                }]
+            } else {
+                Vec::new()
+            },
+            receiver_charges: None,
+        }
+    }
+}
+
+// An MT103 credited to a customer or an MT202 cover, as received
+#[derive(Debug, sqlx::FromRow)]
+pub struct SwiftInboundEntity {
+    pub id: Uuid,
+    pub message_type: String,
+    pub sender_bic: String,
+    pub reference: String,
+    pub related_reference: Option<String>,
+    pub uetr: Option<String>,
+    pub value_date: NaiveDate,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub ordering_party: Option<String>,
+    pub beneficiary_account: Option<String>,
+    pub account_id: Option<Uuid>,
+    pub journal_entry_id: Option<Uuid>,
+    pub received_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    fn instruction(amount_cents: i64, charges: ChargeBearer) -> TransferInstruction {
+        TransferInstruction {
+            amount_cents,
+            charges,
+            remittance_info: None,
+        }
+    }
+
+    #[test]
+    fn rates_are_read_to_six_places() {
+        assert_eq!(assert_ok!(parse_rate("0.79")), 790_000);
+        assert_eq!(assert_ok!(parse_rate("149.123456")), 149_123_456);
+        assert_eq!(format_rate(790_000), "0.790000");
+
+        let _ = assert_err!(parse_rate("0"));
+        let _ = assert_err!(parse_rate("1.1234567"));
+        let _ = assert_err!(parse_rate("-1.2"));
+        let _ = assert_err!(parse_rate(".5"));
+    }
+
+    #[test]
+    fn the_fx_margin_is_the_difference_to_the_mid_rate() {
+        // 0.80 pounds to the dollar, less 50 basis points
+        let quote = FxQuote::new(800_000, 50);
+        assert_eq!(quote.customer_rate_micros, 796_000);
+
+        // 1000.00 pounds cost 1256.29 dollars at the customer's rate and 1250.00 at the mid
+        assert_eq!(quote.debit_cents(100_000), 125_629);
+        assert_eq!(quote.margin_cents(100_000), 629);
+        assert_eq!(quote.converted(2_500), 1_990);
+
+        let same = FxQuote::same_currency();
+        assert!(!same.is_conversion());
+        assert_eq!(same.debit_cents(100_000), 100_000);
+        assert_eq!(same.margin_cents(100_000), 0);
+    }
+
+    #[test]
+    fn charge_options_decide_who_pays_which_fee() {
+        let quote = FxQuote::same_currency();
+
+        let our = assert_ok!(Pricing::new(
+            &instruction(100_000, ChargeBearer::Our),
+            quote,
+            2_500,
+            1_500
+        ));
+        assert_eq!(our.settlement_cents, 100_000);
+        assert_eq!(our.fee_cents, 4_000);
+        assert_eq!(our.total_debit_cents(), 104_000);
+
+        let sha = assert_ok!(Pricing::new(
+            &instruction(100_000, ChargeBearer::Sha),
+            quote,
+            2_500,
+            1_500
+        ));
+        assert_eq!(sha.total_debit_cents(), 102_500);
+
+        let ben = assert_ok!(Pricing::new(
+            &instruction(100_000, ChargeBearer::Ben),
+            quote,
+            2_500,
+            1_500
+        ));
+        assert_eq!(ben.settlement_cents, 97_500);
+        assert_eq!(ben.sender_charge_cents, 2_500);
+        assert_eq!(ben.total_debit_cents(), 100_000);
+
+        let _ = assert_err!(Pricing::new(
+            &instruction(2_000, ChargeBearer::Ben),
+            quote,
+            2_500,
+            0
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::base::error::ValidationError;
+use crate::swift::fin::{FinMessage, LINE_LEN, Money, Party, ValueDated, invalid_field, parse_bic};
+use crate::swift::models::ChargeBearer;
+
+const BANK_OPERATION_CODES: &[&str] = &["CRED", "CRTS", "SPAY", "SPRI", "SSTD"];
+const MAX_REFERENCE_LEN: usize = 16;
+
+// References may not start or end with a slash or hold two in a row
+pub fn parse_reference(tag: &str, value: &str) -> Result<String, ValidationError> {
+    if value.is_empty() || value.len() > MAX_REFERENCE_LEN {
+        return Err(invalid_field(tag, "Reference must be 1 to 16 characters"));
+    }
+
+    if value.starts_with('/') || value.ends_with('/') || value.contains("//") {
+        return Err(invalid_field(
+            tag,
+            "Reference may not start or end with / or contain //",
+        ));
+    }
+
+    Ok(value.to_string())
+}
+
+// A bank given by BIC, in 52A, 57A or 58A, behind an optional /account line
+pub fn parse_institution(tag: &str, value: &str) -> Result<String, ValidationError> {
+    match value.lines().last() {
+        Some(bic) => parse_bic(tag, bic),
+        None => Err(invalid_field(tag, "A BIC is required")),
+    }
+}
+
+pub fn parse_narrative(
+    tag: &str,
+    value: &str,
+    max_lines: usize,
+) -> Result<Vec<String>, ValidationError> {
+    let lines: Vec<String> = value.lines().map(str::to_string).collect();
+
+    if lines.len() > max_lines || lines.iter().any(|l| l.len() > LINE_LEN) {
+        return Err(invalid_field(
+            tag,
+            &format!("At most {} lines of 35 characters", max_lines),
+        ));
+    }
+
+    Ok(lines)
+}
+
+// A single customer credit transfer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mt103 {
+    // 20
+    pub sender_reference: String,
+    // 23B
+    pub bank_operation_code: String,
+    // 32A, what the receiver is paid after any charges taken on the way
+    pub settlement: ValueDated,
+    // 33B, what the ordering customer instructed
+    pub instructed: Option<Money>,
+    // 36
+    pub exchange_rate: Option<String>,
+    // 50A, 50F or 50K
+    pub ordering_customer: Party,
+    // 52A
+    pub ordering_institution: Option<String>,
+    // 57A
+    pub account_with_institution: Option<String>,
+    // 59, 59A or 59F
+    pub beneficiary: Party,
+    // 70
+    pub remittance_info: Vec<String>,
+    // 71A
+    pub charges: ChargeBearer,
+    // 71F, charges the banks on the way took out of the amount
+    pub sender_charges: Vec<Money>,
+    // 71G, charges the ordering side prepaid to the receiver
+    pub receiver_charges: Option<Money>,
+}
+
+impl Mt103 {
+    pub fn to_message(&self, sender_bic: &str, receiver_bic: &str, uetr: &str) -> FinMessage {
+        let mut message = FinMessage::new("103", sender_bic, receiver_bic);
+        message.uetr = Some(uetr.into());
+
+        message.push("20", self.sender_reference.clone());
+        message.push("23B", self.bank_operation_code.clone());
+        message.push("32A", self.settlement.to_field());
+        if let Some(instructed) = &self.instructed {
+            message.push("33B", instructed.to_field());
+        }
+        if let Some(rate) = &self.exchange_rate {
+            message.push("36", rate.clone());
+        }
+        message.push("50K", self.ordering_customer.to_field());
+        if let Some(bic) = &self.ordering_institution {
+            message.push("52A", bic.clone());
+        }
+        if let Some(bic) = &self.account_with_institution {
+            message.push("57A", bic.clone());
+        }
+        message.push("59", self.beneficiary.to_field());
+        if !self.remittance_info.is_empty() {
+            message.push("70", self.remittance_info.join("\r\n"));
+        }
+        message.push("71A", self.charges.to_string());
+        for charge in &self.sender_charges {
+            message.push("71F", charge.to_field());
+        }
+        if let Some(charge) = &self.receiver_charges {
+            message.push("71G", charge.to_field());
+        }
+
+        message
+    }
+
+    pub fn from_message(message: &FinMessage) -> Result<Self, ValidationError> {
+        if message.message_type != "103" {
+            return Err(ValidationError::InvalidValue {
+                field: "message_type".into(),
+                reason: format!("Expected an MT103, got MT{}", message.message_type),
+            });
+        }
+
+        let sender_reference = parse_reference("20", message.required("20")?)?;
+
+        let bank_operation_code = message.required("23B")?.to_string();
+        if !BANK_OPERATION_CODES.contains(&bank_operation_code.as_str()) {
+            return Err(invalid_field("23B", "Use CRED, CRTS, SPAY, SPRI or SSTD"));
+        }
+
+        let settlement = ValueDated::parse("32A", message.required("32A")?)?;
+        if settlement.money.amount_cents <= 0 {
+            return Err(invalid_field("32A", "Amount must be more than zero"));
+        }
+
+        let instructed = match message.field("33B") {
+            Some(value) => Some(Money::parse("33B", value)?),
+            None => None,
+        };
+        let exchange_rate = message.field("36").map(str::to_string);
+        if let Some(instructed) = &instructed
+            && instructed.currency != settlement.money.currency
+            && exchange_rate.is_none()
+        {
+            return Err(ValidationError::MissingField("36".into()));
+        }
+
+        let ordering_customer = match message.field_option("50", "AFK") {
+            Some((tag, value)) => Party::parse(&tag, value)?,
+            None => return Err(ValidationError::MissingField("50K".into())),
+        };
+
+        let ordering_institution = match message.field("52A") {
+            Some(value) => Some(parse_institution("52A", value)?),
+            None => None,
+        };
+        let account_with_institution = match message.field("57A") {
+            Some(value) => Some(parse_institution("57A", value)?),
+            None => None,
+        };
+
+        let beneficiary = match message
+            .field("59")
+            .map(|value| ("59".to_string(), value))
+            .or_else(|| message.field_option("59", "AF"))
+        {
+            Some((tag, value)) => Party::parse(&tag, value)?,
+            None => return Err(ValidationError::MissingField("59".into())),
+        };
+
+        let remittance_info = match message.field("70") {
+            Some(value) => parse_narrative("70", value, 4)?,
+            None => Vec::new(),
+        };
+
+        let charges: ChargeBearer = message
+            .required("71A")?
+            .parse()
+            .map_err(|_| invalid_field("71A", "Use OUR, SHA or BEN"))?;
+
+        let sender_charges = message
+            .fields
+            .iter()
+            .filter(|(tag, _)| tag == "71F")
+            .map(|(tag, value)| Money::parse(tag, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let receiver_charges = match message.field("71G") {
+            Some(value) => Some(Money::parse("71G", value)?),
+            None => None,
+        };
+
+        // Who bears the charges decides which charge fields may appear
+        match charges {
+            ChargeBearer::Ben if sender_charges.is_empty() => {
+                return Err(ValidationError::MissingField("71F".into()));
+            }
+            ChargeBearer::Our if !sender_charges.is_empty() => {
+                return Err(invalid_field("71F", "Not allowed when charges are OUR"));
+            }
+            ChargeBearer::Sha | ChargeBearer::Ben if receiver_charges.is_some() => {
+                return Err(invalid_field("71G", "Only allowed when charges are OUR"));
+            }
+            _ => {}
+        }
+
+        Ok(Self {
+            sender_reference,
+            bank_operation_code,
+            settlement,
+            instructed,
+            exchange_rate,
+            ordering_customer,
+            ordering_institution,
+            account_with_institution,
+            beneficiary,
+            remittance_info,
+            charges,
+            sender_charges,
+            receiver_charges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    fn mt103(charges: ChargeBearer) -> Mt103 {
+        Mt103 {
+            sender_reference: "THL0000000000042".into(),
+            bank_operation_code: "CRED".into(),
+            settlement: ValueDated {
+                value_date: NaiveDate::from_ymd_opt(2025, 12, 19).unwrap(),
+                money: Money {
+                    currency: "GBP".into(),
+                    amount_cents: 98_750,
+                },
+            },
+            instructed: Some(Money {
+                currency: "GBP".into(),
+                amount_cents: 100_000,
+            }),
+            exchange_rate: None,
+            ordering_customer: Party {
+                account: Some("US64SVBKUS6S3300958879".into()),
+                name_and_address: vec!["Jane Doe".into()],
+            },
+            ordering_institution: None,
+            account_with_institution: Some("BARCGB22XXX".into()),
+            beneficiary: Party {
+                account: Some("GB82WEST12345698765432".into()),
+                name_and_address: vec!["Jose Alvarez".into(), "1 High Street".into()],
+            },
+            remittance_info: vec!["Invoice 7781".into()],
+            charges,
+            sender_charges: vec![Money {
+                currency: "GBP".into(),
+                amount_cents: 1_250,
+            }],
+            receiver_charges: None,
+        }
+    }
+
+    #[test]
+    fn messages_are_written_field_by_field_and_read_back() {
+        let message = mt103(ChargeBearer::Ben).to_message(
+            "THALUS33XXX",
+            "CHASUS33",
+            "0b7e6a6e-6f55-4d8e-9c4e-3f3c3b0a9d11",
+        );
+        let text = message.to_fin();
+
+        assert!(text.starts_with(
+            "{1:F01THALUS33AXXX0000000000}{2:I103CHASUS33AXXXN}{3:{121:0b7e6a6e-6f55-4d8e-9c4e-3f3c3b0a9d11}}{4:\r\n:20:THL0000000000042\r\n:23B:CRED\r\n:32A:251219GBP987,50\r\n:33B:GBP1000,00\r\n"
+        ));
+        assert!(
+            text.contains("\r\n:59:/GB82WEST12345698765432\r\nJose Alvarez\r\n1 High Street\r\n")
+        );
+        assert!(text.ends_with(":71A:BEN\r\n:71F:GBP12,50\r\n-}"));
+
+        let parsed = assert_ok!(FinMessage::parse(&text));
+        assert_eq!(parsed.sender_bic, "THALUS33XXX");
+        assert_eq!(parsed.receiver_bic, "CHASUS33XXX");
+        assert_eq!(
+            assert_ok!(Mt103::from_message(&parsed)),
+            mt103(ChargeBearer::Ben)
+        );
+    }
+
+    #[test]
+    fn field_errors_name_the_field() {
+        let text = mt103(ChargeBearer::Ben)
+            .to_message("THALUS33XXX", "CHASUS33XXX", "x")
+            .to_fin();
+
+        let broken =
+            FinMessage::parse(&text.replace(":32A:251219GBP987,50", ":32A:251319GBP987,50"))
+                .unwrap();
+        assert_eq!(
+            assert_err!(Mt103::from_message(&broken)).to_string(),
+            "Invalid value for 32A: Date does not exist"
+        );
+
+        let broken = FinMessage::parse(&text.replace(":71F:GBP12,50\r\n", "")).unwrap();
+        assert_eq!(
+            assert_err!(Mt103::from_message(&broken)).to_string(),
+            "Missing required field: 71F"
+        );
+
+        let broken =
+            FinMessage::parse(&text.replace(":20:THL0000000000042", ":20:/THL42")).unwrap();
+        let _ = assert_err!(Mt103::from_message(&broken));
+
+        let broken = FinMessage::parse(&text.replace(":33B:GBP", ":33B:USD")).unwrap();
+        assert_eq!(
+            assert_err!(Mt103::from_message(&broken)).to_string(),
+            "Missing required field: 36"
+        );
+
+        let mut our = mt103(ChargeBearer::Our);
+        our.instructed = None;
+        let text = our.to_message("THALUS33XXX", "CHASUS33XXX", "x").to_fin();
+        let _ = assert_err!(Mt103::from_message(&FinMessage::parse(&text).unwrap()));
+    }
+
+    #[test]
+    fn inbound_messages_with_non_ascii_bics_or_tags_are_refused() {
+        let text = mt103(ChargeBearer::Ben)
+            .to_message("THALUS33XXX", "CHASUS33XXX", "x")
+            .to_fin();
+
+        let broken = FinMessage::parse(&text.replace(":57A:BARCGB22XXX", ":57A:BARCGBÄXX"));
+        assert_eq!(
+            assert_err!(broken).to_string(),
+            "Invalid value for 57A: Only SWIFT X characters are allowed"
+        );
+
+        let _ = assert_err!(FinMessage::parse(&text.replace(":23B:", ":2é:")));
+        let _ = assert_err!(FinMessage::parse(
+            &text.replace("THALUS33AXXX", "THALUS3ÉAXX")
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::base::error::ValidationError;
+use crate::swift::fin::{FinMessage, ValueDated, invalid_field};
+use crate::swift::mt103::{parse_institution, parse_narrative, parse_reference};
+
+// A bank to bank transfer, for us the cover of a customer payment sent to us by MT103
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mt202 {
+    // 20
+    pub transaction_reference: String,
+    // 21, the reference of the MT103 this covers
+    pub related_reference: String,
+    // 32A
+    pub settlement: ValueDated,
+    // 52A
+    pub ordering_institution: Option<String>,
+    // 57A
+    pub account_with_institution: Option<String>,
+    // 58A, or the first line of 58D
+    pub beneficiary_institution: String,
+    // 72
+    pub sender_to_receiver: Vec<String>,
+}
+
+impl Mt202 {
+    pub fn from_message(message: &FinMessage) -> Result<Self, ValidationError> {
+        if message.message_type != "202" {
+            return Err(ValidationError::InvalidValue {
+                field: "message_type".into(),
+                reason: format!("Expected an MT202, got MT{}", message.message_type),
+            });
+        }
+
+        let transaction_reference = parse_reference("20", message.required("20")?)?;
+        let related_reference = parse_reference("21", message.required("21")?)?;
+
+        let settlement = ValueDated::parse("32A", message.required("32A")?)?;
+        if settlement.money.amount_cents <= 0 {
+            return Err(invalid_field("32A", "Amount must be more than zero"));
+        }
+
+        let ordering_institution = match message.field("52A") {
+            Some(value) => Some(parse_institution("52A", value)?),
+            None => None,
+        };
+        let account_with_institution = match message.field("57A") {
+            Some(value) => Some(parse_institution("57A", value)?),
+            None => None,
+        };
+
+        let beneficiary_institution = match message.field_option("58", "AD") {
+            Some((tag, value)) if tag == "58A" => parse_institution(&tag, value)?,
+            Some((tag, value)) => match value.lines().find(|l| !l.starts_with('/')) {
+                Some(name) => name.to_string(),
+                None => return Err(invalid_field(&tag, "A name is required")),
+            },
+            None => return Err(ValidationError::MissingField("58A".into())),
+        };
+
+        let sender_to_receiver = match message.field("72") {
+            Some(value) => parse_narrative("72", value, 6)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            transaction_reference,
+            related_reference,
+            settlement,
+            ordering_institution,
+            account_with_institution,
+            beneficiary_institution,
+            sender_to_receiver,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    const COVER: &str = "{1:F01THALUS33AXXX0000000000}{2:O2021030251219CHASUS33AXXX00000000002512191030N}{4:\r\n:20:COV778120251219\r\n:21:PAY7781\r\n:32A:251219USD25000,00\r\n:52A:BARCGB22\r\n:58A:/11000000\r\nTHALUS33\r\n-}";
+
+    #[test]
+    fn covers_are_read_with_the_reference_they_cover() {
+        let message = assert_ok!(FinMessage::parse(COVER));
+        let cover = assert_ok!(Mt202::from_message(&message));
+
+        assert_eq!(cover.related_reference, "PAY7781");
+        assert_eq!(cover.settlement.money.amount_cents, 2_500_000);
+        assert_eq!(cover.ordering_institution.as_deref(), Some("BARCGB22"));
+        assert_eq!(cover.beneficiary_institution, "THALUS33");
+
+        let message = FinMessage::parse(&COVER.replace(":21:PAY7781\r\n", "")).unwrap();
+        assert_eq!(
+            assert_err!(Mt202::from_message(&message)).to_string(),
+            "Missing required field: 21"
+        );
+
+        let message = FinMessage::parse(&COVER.replace("\r\nTHALUS33", "\r\nTHAL")).unwrap();
+        assert_eq!(
+            assert_err!(Mt202::from_message(&message)).to_string(),
+            "Invalid value for 58A: BIC must be 8 or 11 characters"
+        );
+
+        let _ = assert_err!(FinMessage::parse(
+            &COVER.replace(":52A:BARCGB22", ":52A:BARCGBÄ")
+        ));
+    }
+}
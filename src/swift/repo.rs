@@ -0,0 +1,262 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::inbound_payment::models::CreditAccountEntity;
+use crate::swift::models::{
+    FxRateEntity, OriginatorAccountEntity, SwiftInboundEntity, SwiftTransferEntity,
+};
+
+const TRANSFER_COLUMNS: &str = "id, user_id, account_id, beneficiary_id, reference, uetr,
+    beneficiary_name, beneficiary_account, beneficiary_bic, charges, amount_cents, currency,
+    settlement_cents, sender_charge_cents, debit_currency, principal_cents, rate_micros,
+    fx_margin_cents, fee_cents, remittance_info, value_date, message_location, journal_entry_id,
+    fx_entry_id, fee_entry_id, created_at";
+
+const INBOUND_COLUMNS: &str = "id, message_type, sender_bic, reference, related_reference, uetr,
+    value_date, amount_cents, currency, ordering_party, beneficiary_account, account_id,
+    journal_entry_id, received_by, created_at";
+
+pub struct SwiftRepository<'a, 'b> {
+    pool: &'a PgPool,
+    tx: &'b mut Transaction<'a, Postgres>,
+}
+
+impl<'a, 'b> SwiftRepository<'a, 'b> {
+    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { pool, tx }
+    }
+
+    #[tracing::instrument("Saving FX rate", skip(self, rate))]
+    pub async fn upsert_fx_rate(&mut self, rate: &FxRateEntity) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO fx_rate(base_currency, quote_currency, rate_micros, updated_by, updated_at)
+                VALUES($1, $2, $3, $4, $5)
+                ON CONFLICT (base_currency, quote_currency)
+                DO UPDATE SET rate_micros=$3, updated_by=$4, updated_at=$5",
+        )
+        .bind(&rate.base_currency)
+        .bind(&rate.quote_currency)
+        .bind(rate.rate_micros)
+        .bind(rate.updated_by)
+        .bind(rate.updated_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving FX rates", skip(self))]
+    pub async fn fetch_fx_rates(&self) -> Result<Vec<FxRateEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, FxRateEntity>(
+            "SELECT base_currency, quote_currency, rate_micros, updated_by, updated_at
+                FROM fx_rate ORDER BY base_currency, quote_currency",
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving FX rate", skip(self))]
+    pub async fn fetch_fx_rate(
+        &mut self,
+        base_currency: &str,
+        quote_currency: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let result: Option<i64> = sqlx::query_scalar(
+            "SELECT rate_micros FROM fx_rate WHERE base_currency=$1 AND quote_currency=$2",
+        )
+        .bind(base_currency)
+        .bind(quote_currency)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Locked so two transfers can't both spend the same available balance
+    #[tracing::instrument("Locking SWIFT originator account", skip(self))]
+    pub async fn fetch_originator_account_for_update(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<OriginatorAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, OriginatorAccountEntity>(
+            "SELECT a.id, a.user_id, a.iban,
+                    concat_ws(' ', u.first_name, u.last_name) AS holder_name, a.currency, a.status
+                FROM user_account a
+                JOIN tuser u ON u.id = a.user_id
+                WHERE a.id=$1
+                FOR UPDATE OF a",
+        )
+        .bind(account_id)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    // From the fee schedule of the product version the account is on
+    #[tracing::instrument("Retrieving SWIFT fee", skip(self))]
+    pub async fn fetch_fee(
+        &mut self,
+        account_id: Uuid,
+        code: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let result: Option<i64> = sqlx::query_scalar(
+            "SELECT f.amount_cents FROM user_account a
+                JOIN product_fee f ON f.version_id = a.product_version_id AND f.code=$2
+                WHERE a.id=$1",
+        )
+        .bind(account_id)
+        .bind(code)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Drawing SWIFT reference sequence", skip(self))]
+    pub async fn next_reference_sequence(&mut self) -> Result<i64, sqlx::Error> {
+        let result: i64 = sqlx::query_scalar("SELECT nextval('swift_reference_seq')")
+            .fetch_one(&mut **self.tx)
+            .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving SWIFT transfer", skip(self, transfer))]
+    pub async fn insert_transfer(
+        &mut self,
+        transfer: &SwiftTransferEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO swift_transfer({})
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                    $17, $18, $19, $20, $21, $22, $23, $24, $25, $26)",
+            TRANSFER_COLUMNS
+        ))
+        .bind(transfer.id)
+        .bind(transfer.user_id)
+        .bind(transfer.account_id)
+        .bind(transfer.beneficiary_id)
+        .bind(&transfer.reference)
+        .bind(transfer.uetr)
+        .bind(&transfer.beneficiary_name)
+        .bind(&transfer.beneficiary_account)
+        .bind(&transfer.beneficiary_bic)
+        .bind(transfer.charges)
+        .bind(transfer.amount_cents)
+        .bind(&transfer.currency)
+        .bind(transfer.settlement_cents)
+        .bind(transfer.sender_charge_cents)
+        .bind(&transfer.debit_currency)
+        .bind(transfer.principal_cents)
+        .bind(transfer.rate_micros)
+        .bind(transfer.fx_margin_cents)
+        .bind(transfer.fee_cents)
+        .bind(&transfer.remittance_info)
+        .bind(transfer.value_date)
+        .bind(&transfer.message_location)
+        .bind(transfer.journal_entry_id)
+        .bind(transfer.fx_entry_id)
+        .bind(transfer.fee_entry_id)
+        .bind(transfer.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving SWIFT transfers", skip(self))]
+    pub async fn fetch_transfers(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<SwiftTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SwiftTransferEntity>(&format!(
+            "SELECT {} FROM swift_transfer WHERE user_id=$1 ORDER BY created_at DESC",
+            TRANSFER_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Retrieving SWIFT transfer", skip(self))]
+    pub async fn fetch_transfer(
+        &self,
+        user_id: Uuid,
+        transfer_id: Uuid,
+    ) -> Result<Option<SwiftTransferEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SwiftTransferEntity>(&format!(
+            "SELECT {} FROM swift_transfer WHERE id=$1 AND user_id=$2",
+            TRANSFER_COLUMNS
+        ))
+        .bind(transfer_id)
+        .bind(user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    // Field 59 carries an IBAN or, from outside the IBAN countries, our account number
+    #[tracing::instrument("Retrieving account for SWIFT credit", skip(self))]
+    pub async fn fetch_credit_account(
+        &mut self,
+        account: &str,
+    ) -> Result<Option<CreditAccountEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, CreditAccountEntity>(
+            "SELECT id, currency, status FROM user_account WHERE iban=$1 OR account_number=$1",
+        )
+        .bind(account)
+        .fetch_optional(&mut **self.tx)
+        .await?;
+
+        Ok(result)
+    }
+
+    #[tracing::instrument("Saving inbound SWIFT message", skip(self, inbound))]
+    pub async fn insert_inbound(
+        &mut self,
+        inbound: &SwiftInboundEntity,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "INSERT INTO swift_inbound({})
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+            INBOUND_COLUMNS
+        ))
+        .bind(inbound.id)
+        .bind(&inbound.message_type)
+        .bind(&inbound.sender_bic)
+        .bind(&inbound.reference)
+        .bind(&inbound.related_reference)
+        .bind(&inbound.uetr)
+        .bind(inbound.value_date)
+        .bind(inbound.amount_cents)
+        .bind(&inbound.currency)
+        .bind(&inbound.ordering_party)
+        .bind(&inbound.beneficiary_account)
+        .bind(inbound.account_id)
+        .bind(inbound.journal_entry_id)
+        .bind(inbound.received_by)
+        .bind(inbound.created_at)
+        .execute(&mut **self.tx)
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument("Retrieving inbound SWIFT messages", skip(self))]
+    pub async fn fetch_inbound(&self) -> Result<Vec<SwiftInboundEntity>, sqlx::Error> {
+        let result = sqlx::query_as::<_, SwiftInboundEntity>(&format!(
+            "SELECT {} FROM swift_inbound ORDER BY created_at DESC",
+            INBOUND_COLUMNS
+        ))
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,105 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::config::state::AppState;
+use crate::swift::{
+    schemas::{
+        FxRateRequest, FxRateResponse, InboundMessageRequest, InboundMessageResponse,
+        SwiftTransferRequest, SwiftTransferResponse,
+    },
+    service::SwiftService,
+};
+
+#[tracing::instrument("Send SWIFT transfer", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/swift-transfers", request_body=SwiftTransferRequest, responses((status=200, body=SwiftTransferResponse, description="Account debited and MT103 sent to our correspondent"), (status=400, description="Invalid amount, charges or remittance information"), (status=404, description="Account or beneficiary not found"), (status=409, description="Account is not active"), (status=422, description="Beneficiary's bank has no BIC, no exchange rate, insufficient funds, beneficiary flagged or still cooling off")))]
+pub async fn create_swift_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<SwiftTransferRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service.create(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List SWIFT transfers", skip(app_state, claims))]
+#[utoipa::path(get, path="/swift-transfers", responses((status=200, body=Vec<SwiftTransferResponse>, description="Customer's SWIFT transfers, newest first")))]
+pub async fn list_swift_transfers(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service.list(&claims).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Fetch SWIFT transfer", skip(app_state, claims))]
+#[utoipa::path(get, path="/swift-transfers/{transfer_id}", params(("transfer_id"=Uuid, Path, description="SWIFT transfer id")), responses((status=200, body=SwiftTransferResponse, description="SWIFT transfer"), (status=404, description="SWIFT transfer not found")))]
+pub async fn fetch_swift_transfer(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    transfer_id: web::Path<Uuid>,
+) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service
+        .fetch(&claims, transfer_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Set FX rate", skip(app_state, claims, payload))]
+#[utoipa::path(put, path="/swift/fx-rates", request_body=FxRateRequest, responses((status=200, body=FxRateResponse, description="Mid rate saved for the currency pair"), (status=400, description="Invalid currency or rate"), (status=403, description="Only superusers can set rates")))]
+pub async fn set_fx_rate(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<FxRateRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service
+        .set_fx_rate(&claims, payload.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List FX rates", skip(app_state))]
+#[utoipa::path(get, path="/swift/fx-rates", responses((status=200, body=Vec<FxRateResponse>, description="Mid rates by currency pair")))]
+pub async fn list_fx_rates(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service.list_fx_rates().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("Receive SWIFT message", skip(app_state, claims, payload))]
+#[utoipa::path(post, path="/swift/inbound", request_body=InboundMessageRequest, responses((status=200, body=InboundMessageResponse, description="MT103 credited to the customer or MT202 cover recorded"), (status=400, description="Message or one of its fields is invalid, the field tag is named"), (status=403, description="Only superusers can take in messages"), (status=409, description="Reference already received from the sender, or the account is not active"), (status=422, description="Not addressed to us, unknown account or currency mismatch")))]
+pub async fn receive_swift_message(
+    app_state: web::Data<AppState>,
+    claims: web::ReqData<SessionClaims>,
+    payload: web::Json<InboundMessageRequest>,
+) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service.receive(&claims, payload.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[tracing::instrument("List inbound SWIFT messages", skip(app_state))]
+#[utoipa::path(get, path="/swift/inbound", responses((status=200, body=Vec<InboundMessageResponse>, description="Received MT103 and MT202 messages, newest first")))]
+pub async fn list_swift_inbound(app_state: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    let swift_service = SwiftService::from(&app_state);
+
+    let response = swift_service.list_inbound().await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::swift::models::{
+    ChargeBearer, FxRateEntity, SwiftInboundEntity, SwiftTransferEntity, format_rate,
+};
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct SwiftTransferRequest {
+    // Account the transfer is paid from, in any currency we hold a rate for
+    pub account_id: Uuid,
+    // A saved beneficiary whose bank has a BIC, paid in the beneficiary's currency
+    pub beneficiary_id: Uuid,
+    // In the beneficiary's currency
+    #[schema(example = 100_000)]
+    pub amount_cents: i64,
+    // OUR, SHA or BEN
+    #[schema(example = "SHA")]
+    pub charges: String,
+    #[schema(example = "Invoice 7781")]
+    pub remittance_info: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct SwiftTransferResponse {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub beneficiary_id: Uuid,
+    // Field 20 of the MT103
+    #[schema(example = "THL0000000000042")]
+    pub reference: String,
+    pub uetr: Uuid,
+    pub beneficiary_name: String,
+    pub beneficiary_account: String,
+    pub beneficiary_bic: String,
+    pub charges: ChargeBearer,
+    pub amount_cents: i64,
+    pub currency: String,
+    // What the beneficiary's bank is paid, less our fee when charges are BEN
+    pub settlement_cents: i64,
+    pub sender_charge_cents: i64,
+    pub debit_currency: String,
+    // Debited for the amount sent, the FX margin included
+    pub principal_cents: i64,
+    // The rate the customer got, when the account is in another currency
+    #[schema(example = "0.796000")]
+    pub rate: Option<String>,
+    pub fx_margin_cents: i64,
+    pub fee_cents: i64,
+    pub remittance_info: Option<String>,
+    pub value_date: NaiveDate,
+    // A path on the outbound directory or an s3:// url
+    pub message_location: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SwiftTransferEntity> for SwiftTransferResponse {
+    fn from(value: SwiftTransferEntity) -> Self {
+        Self {
+            id: value.id,
+            account_id: value.account_id,
+            beneficiary_id: value.beneficiary_id,
+            reference: value.reference,
+            uetr: value.uetr,
+            beneficiary_name: value.beneficiary_name,
+            beneficiary_account: value.beneficiary_account,
+            beneficiary_bic: value.beneficiary_bic,
+            charges: value.charges,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            settlement_cents: value.settlement_cents,
+            sender_charge_cents: value.sender_charge_cents,
+            debit_currency: value.debit_currency,
+            principal_cents: value.principal_cents,
+            rate: value.rate_micros.map(format_rate),
+            fx_margin_cents: value.fx_margin_cents,
+            fee_cents: value.fee_cents,
+            remittance_info: value.remittance_info,
+            value_date: value.value_date,
+            message_location: value.message_location,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct FxRateRequest {
+    #[schema(example = "USD")]
+    pub base_currency: String,
+    #[schema(example = "GBP")]
+    pub quote_currency: String,
+    // Mid rate, units of the quote currency for one of the base
+    #[schema(example = "0.79")]
+    pub rate: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct FxRateResponse {
+    pub base_currency: String,
+    pub quote_currency: String,
+    #[schema(example = "0.790000")]
+    pub rate: String,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<FxRateEntity> for FxRateResponse {
+    fn from(value: FxRateEntity) -> Self {
+        Self {
+            base_currency: value.base_currency,
+            quote_currency: value.quote_currency,
+            rate: format_rate(value.rate_micros),
+            updated_by: value.updated_by,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
+pub struct InboundMessageRequest {
+    // The FIN message as received, blocks 1 to 4
+    pub message: String,
+}
+
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct InboundMessageResponse {
+    pub id: Uuid,
+    #[schema(example = "103")]
+    pub message_type: String,
+    pub sender_bic: String,
+    pub reference: String,
+    // For an MT202, the reference of the payment it covers
+    pub related_reference: Option<String>,
+    pub uetr: Option<String>,
+    pub value_date: NaiveDate,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub ordering_party: Option<String>,
+    pub beneficiary_account: Option<String>,
+    // The customer account an MT103 was credited to
+    pub account_id: Option<Uuid>,
+    pub journal_entry_id: Option<Uuid>,
+    pub received_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SwiftInboundEntity> for InboundMessageResponse {
+    fn from(value: SwiftInboundEntity) -> Self {
+        Self {
+            id: value.id,
+            message_type: value.message_type,
+            sender_bic: value.sender_bic,
+            reference: value.reference,
+            related_reference: value.related_reference,
+            uetr: value.uetr,
+            value_date: value.value_date,
+            amount_cents: value.amount_cents,
+            currency: value.currency,
+            ordering_party: value.ordering_party,
+            beneficiary_account: value.beneficiary_account,
+            account_id: value.account_id,
+            journal_entry_id: value.journal_entry_id,
+            received_by: value.received_by,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::authentication::token::SessionClaims;
+use crate::base::error::{AppError, AuthError, DomainError, SqlErrorExt, ValidationError};
+use crate::beneficiary::service::BeneficiaryService;
+use crate::config::state::AppState;
+use crate::hold::service::HoldService;
+use crate::identity_verify::service::KycService;
+use crate::inbound_payment::iso20022::normalize_iban;
+use crate::inbound_payment::models::RepairReason;
+use crate::infra::files::OutboundFile;
+use crate::infra::pgdb::UnitofWork;
+use crate::ledger::{models::ContraAccount, service::LedgerService};
+use crate::staff::models::CoaType;
+use crate::swift::{
+    fin::{FinMessage, parse_currency},
+    models::{
+        Creditor, FX_GAINS_COA, FxQuote, FxRateEntity, NOSTRO_COA, OUR_FEE_CODE, Pricing,
+        SwiftInboundEntity, SwiftTransferEntity, TRANSFER_FEE_CODE, TransferInstruction,
+        fee_reference, fx_reference, inbound_reference, parse_rate, transfer_reference,
+    },
+    mt103::Mt103,
+    mt202::Mt202,
+    schemas::{
+        FxRateRequest, FxRateResponse, InboundMessageRequest, InboundMessageResponse,
+        SwiftTransferRequest, SwiftTransferResponse,
+    },
+};
+use crate::user::models::AccessRole;
+
+pub struct SwiftService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> SwiftService<'a> {
+    pub fn from(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    fn require_superuser(claims: &SessionClaims) -> Result<(), AuthError> {
+        match claims.get_role() {
+            AccessRole::Superuser => Ok(()),
+            _ => Err(AuthError::InsufficientPermissions),
+        }
+    }
+
+    // The customer is debited against the nostro account, the FX margin and our fees are
+    // booked apart. The MT103 is stored before the transfer commits and removed again if the
+    // commit fails.
+    #[tracing::instrument("Send SWIFT transfer", skip(self, claims, request))]
+    pub async fn create(
+        &self,
+        claims: &SessionClaims,
+        request: SwiftTransferRequest,
+    ) -> Result<SwiftTransferResponse, AppError> {
+        let instruction = TransferInstruction::parse(&request)?;
+        let user_id = *claims.get_user_id();
+        let profile = &self.app_state.swift;
+        let now = Utc::now();
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let account = match uow
+            .swift()
+            .fetch_originator_account_for_update(request.account_id)
+            .await
+            .to_app_err("Failed to fetch account")?
+        {
+            Some(a) if a.user_id == user_id => a,
+            _ => Err(DomainError::NotFound("account".into()))?,
+        };
+
+        let beneficiary = BeneficiaryService::from(self.app_state)
+            .check_payment(
+                &mut uow,
+                user_id,
+                request.beneficiary_id,
+                instruction.amount_cents,
+                now,
+            )
+            .await?;
+        let creditor = Creditor::from_beneficiary(&beneficiary)?;
+
+        let quote = if creditor.currency == account.currency {
+            FxQuote::same_currency()
+        } else {
+            match uow
+                .swift()
+                .fetch_fx_rate(&account.currency, &creditor.currency)
+                .await
+                .to_app_err("Failed to fetch FX rate")?
+            {
+                Some(rate) => FxQuote::new(rate, profile.fx_margin_bps),
+                None => Err(DomainError::ConstraintViolation(format!(
+                    "no exchange rate from {} to {}",
+                    account.currency, creditor.currency
+                )))?,
+            }
+        };
+
+        let transfer_fee = uow
+            .swift()
+            .fetch_fee(account.id, TRANSFER_FEE_CODE)
+            .await
+            .to_app_err("Failed to fetch SWIFT fee")?
+            .unwrap_or_default();
+        let our_fee = uow
+            .swift()
+            .fetch_fee(account.id, OUR_FEE_CODE)
+            .await
+            .to_app_err("Failed to fetch SWIFT fee")?
+            .unwrap_or_default();
+        let pricing = Pricing::new(&instruction, quote, transfer_fee, our_fee)?;
+        KycService::from(self.app_state)
+            .check_transaction(&mut uow, account.id, pricing.principal_cents)
+            .await?;
+
+        let available = HoldService::available_balance(&mut uow, account.id, now).await?;
+        account.check_debit(pricing.total_debit_cents(), available.available_cents())?;
+
+        let sequence = uow
+            .swift()
+            .next_reference_sequence()
+            .await
+            .to_app_err("Failed to draw SWIFT reference")?;
+        let reference = profile.reference(sequence);
+
+        let mut transfer = SwiftTransferEntity::new(
+            &account,
+            beneficiary.id,
+            creditor,
+            instruction,
+            quote,
+            pricing,
+            reference,
+            now,
+        );
+
+        let ledger = LedgerService::from(self.app_state);
+        transfer.journal_entry_id = ledger
+            .post_account_debit(
+                &mut uow,
+                account.id,
+                transfer_reference(&transfer.reference),
+                format!("SWIFT transfer to {}", transfer.beneficiary_name),
+                transfer.principal_cents - transfer.fx_margin_cents,
+                ContraAccount::Code(NOSTRO_COA),
+            )
+            .await?;
+
+        if transfer.fx_margin_cents > 0 {
+            transfer.fx_entry_id = Some(
+                ledger
+                    .post_account_debit(
+                        &mut uow,
+                        account.id,
+                        fx_reference(&transfer.reference),
+                        format!("FX margin on SWIFT transfer {}", transfer.reference),
+                        transfer.fx_margin_cents,
+                        ContraAccount::Code(FX_GAINS_COA),
+                    )
+                    .await?,
+            );
+        }
+
+        if transfer.fee_cents > 0 {
+            transfer.fee_entry_id = Some(
+                ledger
+                    .post_account_debit(
+                        &mut uow,
+                        account.id,
+                        fee_reference(&transfer.reference),
+                        format!("SWIFT transfer fee, charges {}", transfer.charges),
+                        transfer.fee_cents,
+                        CoaType::Income,
+                    )
+                    .await?,
+            );
+        }
+
+        let message = transfer
+            .mt103(&account, &profile.sender_bic)
+            .to_message(
+                &profile.sender_bic,
+                &profile.correspondent_bic,
+                &transfer.uetr.to_string(),
+            )
+            .to_fin();
+
+        let outbound = OutboundFile::new(
+            profile.outbound_dir.as_deref(),
+            &self.app_state.s3_client.bucket,
+            "swift/outbound",
+            &transfer.file_name(),
+        );
+        transfer.message_location = outbound.location();
+
+        uow.swift()
+            .insert_transfer(&transfer)
+            .await
+            .to_app_err("Failed to save SWIFT transfer")?;
+
+        outbound
+            .store(
+                &self.app_state.s3_client,
+                message.into_bytes(),
+                "text/plain",
+            )
+            .await?;
+
+        let committed = uow.commit().await;
+        if committed.is_err() {
+            outbound.discard(&self.app_state.s3_client).await;
+        }
+        committed.to_app_err("Failed to commit SWIFT transfer")?;
+
+        Ok(transfer.into())
+    }
+
+    #[tracing::instrument("List SWIFT transfers", skip(self, claims))]
+    pub async fn list(
+        &self,
+        claims: &SessionClaims,
+    ) -> Result<Vec<SwiftTransferResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let transfers = uow
+            .swift()
+            .fetch_transfers(*claims.get_user_id())
+            .await
+            .to_app_err("Failed to fetch SWIFT transfers")?;
+
+        Ok(transfers
+            .into_iter()
+            .map(SwiftTransferResponse::from)
+            .collect())
+    }
+
+    #[tracing::instrument("Fetch SWIFT transfer", skip(self, claims))]
+    pub async fn fetch(
+        &self,
+        claims: &SessionClaims,
+        transfer_id: Uuid,
+    ) -> Result<SwiftTransferResponse, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        match uow
+            .swift()
+            .fetch_transfer(*claims.get_user_id(), transfer_id)
+            .await
+            .to_app_err("Failed to fetch SWIFT transfer")?
+        {
+            Some(t) => Ok(t.into()),
+            None => Err(DomainError::NotFound("SWIFT transfer".into()))?,
+        }
+    }
+
+    #[tracing::instrument("Set FX rate", skip(self, claims, request))]
+    pub async fn set_fx_rate(
+        &self,
+        claims: &SessionClaims,
+        request: FxRateRequest,
+    ) -> Result<FxRateResponse, AppError> {
+        Self::require_superuser(claims)?;
+
+        let base_currency = parse_currency("base_currency", request.base_currency.trim())?;
+        let quote_currency = parse_currency("quote_currency", request.quote_currency.trim())?;
+        if base_currency == quote_currency {
+            Err(ValidationError::Mismatch(
+                "base_currency and quote_currency must differ".into(),
+            ))?
+        }
+
+        let rate = FxRateEntity {
+            base_currency,
+            quote_currency,
+            rate_micros: parse_rate(&request.rate)?,
+            updated_by: Some(*claims.get_user_id()),
+            updated_at: Utc::now(),
+        };
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        uow.swift()
+            .upsert_fx_rate(&rate)
+            .await
+            .to_app_err("Failed to save FX rate")?;
+
+        uow.commit().await.to_app_err("Failed to commit FX rate")?;
+
+        Ok(rate.into())
+    }
+
+    #[tracing::instrument("List FX rates", skip(self))]
+    pub async fn list_fx_rates(&self) -> Result<Vec<FxRateResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let rates = uow
+            .swift()
+            .fetch_fx_rates()
+            .await
+            .to_app_err("Failed to fetch FX rates")?;
+
+        Ok(rates.into_iter().map(FxRateResponse::from).collect())
+    }
+
+    // An MT103 is credited to the account in field 59 against the nostro account, an MT202
+    // cover is only recorded since the MT103 it covers already did the posting. The same
+    // reference from the same sender is refused the second time.
+    #[tracing::instrument("Receive SWIFT message", skip(self, claims, request))]
+    pub async fn receive(
+        &self,
+        claims: &SessionClaims,
+        request: InboundMessageRequest,
+    ) -> Result<InboundMessageResponse, AppError> {
+        Self::require_superuser(claims)?;
+        let now = Utc::now();
+
+        let message = FinMessage::parse(&request.message)?;
+        if !self
+            .app_state
+            .swift
+            .is_addressed_to_us(&message.receiver_bic)
+        {
+            Err(DomainError::ConstraintViolation(format!(
+                "message is addressed to {}",
+                message.receiver_bic
+            )))?
+        }
+
+        let mut inbound = match message.message_type.as_str() {
+            "103" => {
+                let payment = Mt103::from_message(&message)?;
+                SwiftInboundEntity {
+                    id: Uuid::now_v7(),
+                    message_type: message.message_type.clone(),
+                    sender_bic: message.sender_bic.clone(),
+                    reference: payment.sender_reference.clone(),
+                    related_reference: None,
+                    uetr: message.uetr.clone(),
+                    value_date: payment.settlement.value_date,
+                    amount_cents: payment.settlement.money.amount_cents,
+                    currency: payment.settlement.money.currency.clone(),
+                    ordering_party: Some(payment.ordering_customer.name().to_string()),
+                    beneficiary_account: payment.beneficiary.account.clone(),
+                    account_id: None,
+                    journal_entry_id: None,
+                    received_by: Some(*claims.get_user_id()),
+                    created_at: now,
+                }
+            }
+            "202" => {
+                let cover = Mt202::from_message(&message)?;
+                SwiftInboundEntity {
+                    id: Uuid::now_v7(),
+                    message_type: message.message_type.clone(),
+                    sender_bic: message.sender_bic.clone(),
+                    reference: cover.transaction_reference,
+                    related_reference: Some(cover.related_reference),
+                    uetr: message.uetr.clone(),
+                    value_date: cover.settlement.value_date,
+                    amount_cents: cover.settlement.money.amount_cents,
+                    currency: cover.settlement.money.currency,
+                    ordering_party: cover.ordering_institution,
+                    beneficiary_account: None,
+                    account_id: None,
+                    journal_entry_id: None,
+                    received_by: Some(*claims.get_user_id()),
+                    created_at: now,
+                }
+            }
+            other => Err(ValidationError::InvalidValue {
+                field: "message_type".into(),
+                reason: format!("MT{} is not taken, only MT103 and MT202", other),
+            })?,
+        };
+        inbound.uetr = inbound.uetr.filter(|uetr| uetr.len() == 36);
+
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        if inbound.message_type == "103" {
+            let number = match &inbound.beneficiary_account {
+                Some(number) => normalize_iban(number),
+                None => Err(DomainError::from(RepairReason::MissingAccount))?,
+            };
+
+            let account = match uow
+                .swift()
+                .fetch_credit_account(&number)
+                .await
+                .to_app_err("Failed to fetch account")?
+            {
+                Some(a) => a,
+                None => Err(DomainError::from(RepairReason::UnknownAccount(number)))?,
+            };
+            account
+                .check_credit(&inbound.currency)
+                .map_err(DomainError::from)?;
+
+            let description = match &inbound.ordering_party {
+                Some(name) => format!("SWIFT transfer from {}", name),
+                None => "SWIFT transfer".into(),
+            };
+
+            inbound.journal_entry_id = Some(
+                LedgerService::from(self.app_state)
+                    .post_account_credit(
+                        &mut uow,
+                        account.id,
+                        inbound_reference(&inbound.sender_bic, &inbound.reference),
+                        description,
+                        inbound.amount_cents,
+                        ContraAccount::Code(NOSTRO_COA),
+                    )
+                    .await?,
+            );
+            inbound.account_id = Some(account.id);
+        }
+
+        uow.swift()
+            .insert_inbound(&inbound)
+            .await
+            .to_app_err("Failed to save inbound SWIFT message")?;
+
+        uow.commit()
+            .await
+            .to_app_err("Failed to commit inbound SWIFT message")?;
+
+        Ok(inbound.into())
+    }
+
+    #[tracing::instrument("List inbound SWIFT messages", skip(self))]
+    pub async fn list_inbound(&self) -> Result<Vec<InboundMessageResponse>, AppError> {
+        let mut uow = UnitofWork::from(&self.app_state.pgpool)
+            .await
+            .to_app_err("Failed to start postgres uow")?;
+
+        let messages = uow
+            .swift()
+            .fetch_inbound()
+            .await
+            .to_app_err("Failed to fetch inbound SWIFT messages")?;
+
+        Ok(messages
+            .into_iter()
+            .map(InboundMessageResponse::from)
+            .collect())
+    }
+}
@@ -6,3 +6,7 @@ use utoipa::OpenApi;
     crate::transaction::routes::withdraw_funds,
 ))]
 pub struct TransactionApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(crate::transaction::routes::integration_deposit_funds))]
+pub struct IntegrationApi;
@@ -1,3 +1,4 @@
+use crate::api_key::models::{ApiKeyPrincipal, ApiScope};
 use crate::config::state::AppState;
 use crate::transaction::schemas::CashDepositRequest;
 use crate::transaction::service::TransactionService;
@@ -20,7 +21,32 @@ pub async fn deposit_funds(
     let transact_service = TransactionService::from(&app_state);
 
     let response = transact_service
-        .fund_deposit(account_id.into_inner(), payload.into_inner())
+        .fund_deposit(account_id.into_inner(), payload.into_inner(), None)
+        .await?;
+
+    Ok(response)
+}
+
+// Deposits posted by ATMs, teller terminals and gateways authenticated with an api key
+#[tracing::instrument("Integration depositing funds", skip(app_state, payload, principal), fields(api_key=%principal.get_name()))]
+#[utoipa::path(post, path="/deposit/{account_id}", responses((status=200, body=CashResponse, description="Deposit successful"), (status=403, description="Api key lacks the transaction:deposit scope")))]
+pub async fn integration_deposit_funds(
+    app_state: web::Data<AppState>,
+    payload: web::Json<CashDepositRequest>,
+    account_id: web::Path<uuid::Uuid>,
+    principal: web::ReqData<ApiKeyPrincipal>,
+) -> actix_web::Result<HttpResponse> {
+    let principal = principal.into_inner();
+    principal.require(ApiScope::TransactionDeposit)?;
+
+    let transact_service = TransactionService::from(&app_state);
+
+    let response = transact_service
+        .fund_deposit(
+            AccountId(account_id.into_inner()),
+            payload.into_inner(),
+            Some(&principal),
+        )
         .await?;
 
     Ok(response)
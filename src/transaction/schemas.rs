@@ -9,6 +9,15 @@ pub enum DepositMetadata {
     Device { id: Option<String> },
 }
 
+impl DepositMetadata {
+    pub fn device_id(&self) -> Option<String> {
+        match self {
+            DepositMetadata::Teller { id } => id.as_ref().map(|id| format!("teller:{}", id)),
+            DepositMetadata::Device { id } => id.as_ref().map(|id| format!("device:{}", id)),
+        }
+    }
+}
+
 #[derive(Debug, utoipa::ToSchema, serde::Deserialize)]
 pub struct CashDepositRequest {
     pub amount: f64,
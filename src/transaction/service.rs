@@ -3,9 +3,11 @@ use actix_web::{HttpResponse, body::to_bytes};
 use actix_web_flash_messages::FlashMessage;
 use uuid::Uuid;
 
+use crate::api_key::models::ApiKeyPrincipal;
 use crate::base::error::{AppError, DomainError, SqlErrorExt};
 use crate::base::ids::AccountId;
 use crate::config::state::AppState;
+use crate::identity_verify::service::KycService;
 use crate::infra::pgdb::UnitofWork;
 use crate::ledger::models::{CreditLine, DebitLine, IntoJournalLine, JournalEntry, LineType};
 use crate::staff::models::CoaType;
@@ -29,16 +31,19 @@ impl<'a> TransactionService<'a> {
         Self { app_state }
     }
 
+    // The leading digits of a v7 uuid are its timestamp and repeat within a millisecond,
+    // the trailing ones come from its random bits
     pub fn generate_transaction_id(&self) -> String {
-        let u = &Uuid::now_v7().as_u128().to_string()[..11];
+        let u = Uuid::now_v7().as_u128() % 100_000_000_000;
 
-        format!("THA{}", u)
+        format!("THA{:011}", u)
     }
 
     pub async fn fund_deposit(
         &self,
         account_id: AccountId,
         cash_deposit: CashDepositRequest,
+        posted_by: Option<&ApiKeyPrincipal>,
     ) -> Result<HttpResponse, AppError> {
         match self
             .try_transaction_process(
@@ -49,7 +54,9 @@ impl<'a> TransactionService<'a> {
             .await?
         {
             NextAction::StartProcessing => {
-                let response = self.deposit_entry(account_id.0, &cash_deposit).await?;
+                let response = self
+                    .deposit_entry(account_id.0, &cash_deposit, posted_by)
+                    .await?;
                 let response = HttpResponse::Ok().json(response);
                 Ok(response)
             }
@@ -117,20 +124,31 @@ impl<'a> TransactionService<'a> {
         &self,
         user_account_id: Uuid,
         deposit: &CashDepositRequest,
+        posted_by: Option<&ApiKeyPrincipal>,
     ) -> Result<CashResponse, AppError> {
         let mut uow = UnitofWork::from(&self.app_state.pgpool)
             .await
             .to_app_err("Failed to start postgres uow")?;
 
+        let amount_cents = (deposit.amount * 100.0) as i64;
+        KycService::from(self.app_state)
+            .check_transaction(&mut uow, user_account_id, amount_cents)
+            .await?;
+
         let transaction_id = self.generate_transaction_id();
 
-        let journal_entry = JournalEntry::new(
+        let mut journal_entry = JournalEntry::new(
             user_account_id,
             transaction_id.clone(),
             deposit.transaction_ref.clone(),
             deposit.notes.clone(),
         );
 
+        if let Some(principal) = posted_by {
+            journal_entry =
+                journal_entry.posted_by(*principal.get_key_id(), deposit.metadata.device_id());
+        }
+
         let debit_coa_id = uow
             .staffs()
             .fetch_coa_id_by_coa_type(CoaType::Asset)
@@ -157,9 +175,9 @@ impl<'a> TransactionService<'a> {
 
         let credit_line = CreditLine::new(credit_coa_id, LineType::Credit);
 
-        let journal_line = IntoJournalLine::new(
+        let journal_line = IntoJournalLine::from_cents(
             *journal_entry.get_id(),
-            deposit.amount,
+            amount_cents,
             debit_line,
             credit_line,
         );
@@ -177,7 +195,7 @@ impl<'a> TransactionService<'a> {
         let cash_response = CashResponse::new(
             "success",
             transaction_id,
-            *journal_entry.get_user_account_id(),
+            user_account_id,
             0.0,
             "USD".into(),
             chrono::Utc::now(),
@@ -1,9 +1,22 @@
+use chrono::Utc;
 use derive_more::Display;
 use sqlx::types::chrono;
 use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::base::error::ValidationError;
+use crate::base::error::{DomainError, ValidationError};
+
+// Check if a user is age > 18
+pub fn check_minimum_age(date_of_birth: chrono::NaiveDate) -> Result<(), DomainError> {
+    let age_days = (Utc::now().date_naive() - date_of_birth).num_days();
+    if age_days < 18 * 365 {
+        return Err(DomainError::ConstraintViolation(
+            "You need to be 18 or older to use this service. Please try again when you meet the age requirement.".into(),
+        ));
+    }
+
+    Ok(())
+}
 
 #[derive(
     Debug, serde::Deserialize, serde::Serialize, sqlx::Type, PartialEq, Eq, Clone, Hash, Display,
@@ -41,7 +54,7 @@ pub struct UserEntity {
     pub username: String,
     pub password: String,
     pub email: String,
-    pub date_of_birth: chrono::NaiveDate,
+    pub date_of_birth: Option<chrono::NaiveDate>,
     pub is_confirmed: bool,
     pub is_active: bool,
     pub is_verified: bool,
@@ -80,4 +93,73 @@ impl UpdateUserEntity {
             access_role: None,
         }
     }
+
+    pub fn verified_update(user_id: Uuid) -> Self {
+        Self {
+            id: user_id,
+            first_name: None,
+            last_name: None,
+            username: None,
+            password: None,
+            email: None,
+            date_of_birth: None,
+            is_confirmed: None,
+            is_active: None,
+            is_verified: Some(true),
+            access_role: None,
+        }
+    }
+
+    pub fn email_update(user_id: Uuid, email: String) -> Self {
+        Self {
+            id: user_id,
+            first_name: None,
+            last_name: None,
+            username: None,
+            password: None,
+            email: Some(email),
+            date_of_birth: None,
+            is_confirmed: None,
+            is_active: None,
+            is_verified: None,
+            access_role: None,
+        }
+    }
+
+    pub fn details_update(
+        user_id: Uuid,
+        first_name: Option<String>,
+        last_name: Option<String>,
+        date_of_birth: Option<chrono::NaiveDate>,
+    ) -> Self {
+        Self {
+            id: user_id,
+            first_name,
+            last_name,
+            username: None,
+            password: None,
+            email: None,
+            date_of_birth,
+            is_confirmed: None,
+            is_active: None,
+            is_verified: None,
+            access_role: None,
+        }
+    }
+
+    pub fn access_role_update(user_id: Uuid, access_role: AccessRole) -> Self {
+        Self {
+            id: user_id,
+            first_name: None,
+            last_name: None,
+            username: None,
+            password: None,
+            email: None,
+            date_of_birth: None,
+            is_confirmed: None,
+            is_active: None,
+            is_verified: None,
+            access_role: Some(access_role),
+        }
+    }
 }
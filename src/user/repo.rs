@@ -4,13 +4,12 @@ use crate::user::models::{UpdateUserEntity, UserEntity};
 use crate::user::schemas::UserResponse;
 
 pub struct UserRepository<'a, 'b> {
-    pool: &'a PgPool,
     tx: &'b mut Transaction<'a, Postgres>,
 }
 
 impl<'a, 'b> UserRepository<'a, 'b> {
-    pub fn from(pool: &'a PgPool, tx: &'b mut Transaction<'a, Postgres>) -> Self {
-        Self { pool, tx }
+    pub fn from(tx: &'b mut Transaction<'a, Postgres>) -> Self {
+        Self { tx }
     }
 
     #[tracing::instrument("Saving user details in the database", skip(self, user))]
@@ -36,18 +35,22 @@ impl<'a, 'b> UserRepository<'a, 'b> {
         Ok(())
     }
 
-    pub async fn update(&self, update_user_entity: &UpdateUserEntity) -> Result<(), sqlx::Error> {
+    pub async fn update(
+        &mut self,
+        update_user_entity: &UpdateUserEntity,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
-            "UPDATE tuser SET first_name = COALESCE($1, first_name)
-                                            last_name = COALESCE($2, last_name)
-                                            username = COALESCE($3, username)
-                                            password = COALESCE($4, password)
-                                            date_of_birth = COALESCE($5, date_of_birth)
-                                            email = COALESCE($6, email)
-                                            is_confirmed = COALESCE($7, is_confirmed)
-                                            is_active = COALESCE($8, is_active)
-                                            is_verified = COALESCE($9, is_verified)
-                                            access_role = COALESCE($10, access_role)
+            "UPDATE tuser SET first_name = COALESCE($1, first_name),
+                                            last_name = COALESCE($2, last_name),
+                                            username = COALESCE($3, username),
+                                            password = COALESCE($4, password),
+                                            date_of_birth = COALESCE($5, date_of_birth),
+                                            email = COALESCE($6, email),
+                                            is_confirmed = COALESCE($7, is_confirmed),
+                                            is_active = COALESCE($8, is_active),
+                                            is_verified = COALESCE($9, is_verified),
+                                            access_role = COALESCE($10, access_role),
+                                            updated_at = CURRENT_TIMESTAMP
                                         WHERE id = $11",
         )
         .bind(update_user_entity.get_first_name())
@@ -61,7 +64,7 @@ impl<'a, 'b> UserRepository<'a, 'b> {
         .bind(update_user_entity.get_is_verified())
         .bind(update_user_entity.get_access_role())
         .bind(update_user_entity.get_id())
-        .execute(self.pool)
+        .execute(&mut **self.tx)
         .await?;
 
         Ok(())
@@ -1,12 +1,9 @@
-use chrono::Utc;
 use sqlx::types::chrono;
 use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::authentication::token::ActivateClaims;
-use crate::base::error::DomainError;
 use crate::base::{Email, Name, Password, Username, error::AppError};
-use crate::user::models::{AccessRole, UserEntity};
+use crate::user::models::{AccessRole, UserEntity, check_minimum_age};
 
 #[derive(Debug, utoipa::ToSchema, serde::Deserialize)]
 pub struct UserRegisterRequest {
@@ -50,13 +47,7 @@ pub struct User {
 
 impl User {
     pub fn from_register(register_req: UserRegisterRequest) -> Result<User, AppError> {
-        // Check if a user is age > 18
-        let age_days = (Utc::now().date_naive() - register_req.date_of_birth).num_days();
-        if age_days < 18 * 365 {
-            return Err(AppError::Domain(DomainError::ConstraintViolation(
-                "You need to be 18 or older to use this service. Please try again when you meet the age requirement.".into(),
-            )))?;
-        }
+        check_minimum_age(register_req.date_of_birth)?;
 
         let email = Email::parse(register_req.email)?;
 
@@ -84,16 +75,6 @@ impl User {
             date_of_birth: register_req.date_of_birth,
         })
     }
-
-    pub fn to_activate_claims(&self, exp: usize) -> ActivateClaims {
-        ActivateClaims::from(
-            self.email.clone(),
-            self.id,
-            exp,
-            self.access_role.clone(),
-            "sign-up".into(),
-        )
-    }
 }
 
 impl TryFrom<User> for UserEntity {
@@ -107,7 +88,7 @@ impl TryFrom<User> for UserEntity {
             username: value.username.as_ref().to_string(),
             password: value.password.encode_password()?,
             email: value.email.to_string(),
-            date_of_birth: value.date_of_birth,
+            date_of_birth: Some(value.date_of_birth),
             is_confirmed: value.is_confirmed,
             is_active: value.is_active,
             is_verified: value.is_verified,
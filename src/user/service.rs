@@ -2,8 +2,9 @@ use crate::{
     base::error::{AppError, SqlErrorExt},
     config::state::AppState,
     infra::pgdb::UnitofWork,
+    screening::{models::ScreeningSubject, service::ScreeningService},
     user::{
-        models::UserEntity,
+        models::{AccessRole, UserEntity},
         schemas::{User, UserRegisterRequest},
     },
 };
@@ -25,11 +26,11 @@ impl<'a> UserService<'a> {
 
         let user = User::from_register(user_req)?;
 
-        let activate_token = self
-            .app_state
-            .activate_handler
-            .generate_activate_token(&user, &self.app_state.redis_pool)
-            .await?;
+        let activate_token = self.app_state.activate_handler.generate_activate_token(
+            user.id,
+            user.email.clone(),
+            user.access_role.clone(),
+        )?;
 
         let user_entity: UserEntity = user.try_into()?;
 
@@ -38,33 +39,62 @@ impl<'a> UserService<'a> {
             .await
             .to_app_err(&format!("Failed to create {}", user_entity.access_role))?;
 
+        // Hits don't block signup, they are left open for staff to review
+        if user_entity.access_role == AccessRole::Customer {
+            ScreeningService::from(self.app_state)
+                .screen_customer(&mut uow, &ScreeningSubject::from(&user_entity))
+                .await?;
+        }
+
         uow.authentication()
             .store_token(
                 &activate_token,
                 *user_entity.get_id(),
                 user_entity.get_email(),
+                self.app_state.activate_handler.activate_ttl(),
             )
             .await
             .to_app_err("Failed to store activate token")?;
 
-        // Send Activate Email
+        uow.commit().await.to_app_err(&format!(
+            "Failed to commit {} creation",
+            user_entity.access_role
+        ))?;
+
+        // Sent after the commit so the link never points at an unsaved token, a lost email
+        // can be sent again through activation resend
+        self.send_activation_email(&user_entity, &activate_token, "Welcome to Thalia Corp.")
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn send_activation_email(
+        &self,
+        user_entity: &UserEntity,
+        activate_token: &str,
+        subject: &str,
+    ) -> Result<(), AppError> {
+        // Customers and staff confirm through their own routes
+        let confirm_base = match user_entity.access_role {
+            AccessRole::Customer => format!("{}/customer", self.app_state.base_uri.0),
+            AccessRole::Manager | AccessRole::Superuser => {
+                format!("{}/staff", self.app_state.base_uri.0)
+            }
+        };
+
         self.app_state
             .email_client
             .send_welcome_email(
-                &self.app_state.base_uri.0,
+                &confirm_base,
                 &user_entity.email,
-                "Welcome to Thalia Corp.",
+                subject,
                 user_entity.first_name.as_ref(),
-                &activate_token,
+                activate_token,
                 "Thalia Corp.",
             )
             .await?;
 
-        uow.commit().await.to_app_err(&format!(
-            "Failed to commit {} creation",
-            user_entity.access_role
-        ))?;
-
         Ok(())
     }
 }
@@ -6,11 +6,17 @@ async fn valid_user_account_creation_by_logged_in_staff_returns_200() {
     app.get_test_users()
         .store_test_users(&app.get_db_state().pg_pool)
         .await;
+    app.get_test_users()
+        .verify_customer(&app.get_db_state().pg_pool)
+        .await;
 
     app.get_coas().store_coas(&app.get_db_state().pg_pool).await;
     app.get_account_classes()
         .store_account_classes(&app.get_db_state().pg_pool)
         .await;
+    app.get_branches()
+        .store_branches(&app.get_db_state().pg_pool)
+        .await;
 
     // Login
     let login_body = serde_json::json!({"login_id": {"email": app.get_test_users().get_staff().get_email().as_ref()}, 
@@ -18,7 +24,7 @@ async fn valid_user_account_creation_by_logged_in_staff_returns_200() {
     app.post_staff_login(&login_body).await;
 
     let acc_body = serde_json::json!({ "user_id": app.get_test_users().get_customer().get_id(), 
-                                            "branch_id": Uuid::now_v7(), 
+                                            "branch_id": app.get_branches().get_head_office().id, 
                                             "coa_id": Uuid::now_v7(), 
                                             "account_class": app.get_account_classes().get_checking().get_id(), 
     "country_code": 840});
@@ -81,11 +87,17 @@ async fn valid_user_account_creation_by_logged_in_customer_returns_200() {
     app.get_test_users()
         .store_test_users(&app.get_db_state().pg_pool)
         .await;
+    app.get_test_users()
+        .verify_customer(&app.get_db_state().pg_pool)
+        .await;
 
     app.get_coas().store_coas(&app.get_db_state().pg_pool).await;
     app.get_account_classes()
         .store_account_classes(&app.get_db_state().pg_pool)
         .await;
+    app.get_branches()
+        .store_branches(&app.get_db_state().pg_pool)
+        .await;
 
     // Login
     let login_body = serde_json::json!({"login_id": {"email": app.get_test_users().get_customer().get_email().as_ref()}, 
@@ -93,7 +105,7 @@ async fn valid_user_account_creation_by_logged_in_customer_returns_200() {
     app.post_customer_login(&login_body).await;
 
     let acc_body = serde_json::json!({ "user_id": app.get_test_users().get_customer().get_id(), 
-                                            "branch_id": Uuid::now_v7(), 
+                                            "branch_id": app.get_branches().get_head_office().id, 
                                             "coa_id": Uuid::now_v7(), 
                                             "account_class": app.get_account_classes().get_checking().get_id(),
                                             // bad country code
@@ -0,0 +1,281 @@
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+// An active dollar account with a 500.00 overdraft and a 25.00 ACH return fee
+async fn open_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "fees": [{"code": "ach_return", "amount_cents": 2_500,
+                                             "frequency": "per_transaction"}],
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-ACH", terms).await;
+    app.activate_account(account.id).await;
+    app.arrange_overdraft(account.id, 50_000).await;
+
+    account.id
+}
+
+async fn add_beneficiary(app: &TestApp) -> Uuid {
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    let response = app.post_beneficiary_challenge().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let challenge: serde_json::Value = response.json().await.unwrap();
+    let code = app.verification_codes().await.pop().unwrap();
+
+    let response = app
+        .post_beneficiary(&serde_json::json!({"name": "José Álvarez", "account_number": "000123456789",
+                                              "routing_number": "026009593", "bank_country": "US",
+                                              "currency": "USD",
+                                              "challenge_id": challenge["challenge_id"], "code": code}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(beneficiary["routing_number"], "026009593");
+
+    beneficiary["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn send_transfer(
+    app: &TestApp,
+    account_id: Uuid,
+    beneficiary_id: Uuid,
+    amount_cents: i64,
+) -> String {
+    let response = app
+        .post_ach_transfer(&serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                                               "amount_cents": amount_cents, "addenda_info": "Invoice 7781"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let transfer: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(transfer["status"], "queued");
+    transfer["trace_number"].as_str().unwrap().to_string()
+}
+
+async fn generate_file(app: &TestApp) -> serde_json::Value {
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+    let response = app.post_ach_file().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+async fn balance_cents(app: &TestApp, account_id: Uuid) -> i64 {
+    let overdraft: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    overdraft["balance_cents"].as_i64().unwrap()
+}
+
+// A returned entry detail record with its 99 addenda
+fn return_entry(trace_number: &str, amount_cents: i64, return_code: &str) -> String {
+    format!(
+        "621026009593{:<17}{:010}{:<15}{:<22}  1{}\n799{}{}      02600959{:<44}{}",
+        "1000000001",
+        amount_cents,
+        "",
+        "THALIA CORP",
+        "011000010000001",
+        return_code,
+        trace_number,
+        "",
+        "011000010000001"
+    )
+}
+
+fn return_file(entries: &[String]) -> String {
+    format!(
+        "101 026009593 0110000152512220800A094101{:<54}\n{}\n",
+        "",
+        entries.join("\n")
+    )
+}
+
+#[actix_web::test]
+async fn queued_transfers_are_debited_and_written_to_a_nacha_file() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let trace_number = send_transfer(&app, account_id, beneficiary_id, 20_000).await;
+
+    // Act
+    let file = generate_file(&app).await;
+
+    // Assert
+    assert_eq!(file["n_entries"], 1);
+    assert_eq!(file["file_id_modifier"], "A");
+    assert_eq!(file["entry_hash"], 2_600_959);
+    assert_eq!(file["total_credit_cents"], 20_000);
+    let location = file["file_location"].as_str().unwrap();
+    assert!(location.starts_with("s3://thalia-kyc/ach/outbound/ach_"));
+
+    let uploads = app
+        .get_storage_state()
+        .s3_server
+        .received_requests()
+        .await
+        .unwrap();
+    let nacha = String::from_utf8_lossy(&uploads.last().unwrap().body).to_string();
+    let records: Vec<&str> = nacha.lines().collect();
+    assert_eq!(records.len(), 10);
+    assert!(records.iter().all(|r| r.len() == 94));
+    assert!(records[2].starts_with("622026009593000123456789     0000020000"));
+    assert!(records[2].contains("JOSE ALVAREZ"));
+    assert!(records[2].ends_with(&trace_number));
+    assert!(records[3].starts_with("705INVOICE 7781"));
+
+    let detail: serde_json::Value = app
+        .get_ach_file(file["id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["transfers"][0]["status"], "batched");
+
+    assert_eq!(balance_cents(&app, account_id).await, -20_000);
+
+    // Nothing is left to write out
+    let response = app.post_ach_file().await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn returns_reverse_the_transfer_charge_a_fee_and_flag_the_beneficiary() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let closed = send_transfer(&app, account_id, beneficiary_id, 10_000).await;
+    let insufficient = send_transfer(&app, account_id, beneficiary_id, 5_000).await;
+    let kept = send_transfer(&app, account_id, beneficiary_id, 2_000).await;
+    generate_file(&app).await;
+    assert_eq!(balance_cents(&app, account_id).await, -17_000);
+
+    let return_dir = &app.get_storage_state().ach_return_dir;
+    std::fs::create_dir_all(return_dir).unwrap();
+    std::fs::write(
+        return_dir.join("returns_1.ach"),
+        return_file(&[
+            return_entry(&closed, 10_000, "R02"),
+            return_entry(&insufficient, 5_000, "R01"),
+            return_entry("011000019999999", 1_000, "R03"),
+        ]),
+    )
+    .unwrap();
+    std::fs::write(return_dir.join("returns_2.ach"), "<Document/>").unwrap();
+
+    // Act
+    let response = app.post_ach_return_import().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(summary["files_processed"], 1);
+    assert_eq!(summary["files_failed"], 1);
+    assert_eq!(summary["transfers_returned"], 2);
+    assert_eq!(summary["returns_skipped"], 1);
+    assert_eq!(summary["beneficiaries_flagged"], 1);
+
+    assert!(return_dir.join("processed").join("returns_1.ach").exists());
+    assert!(return_dir.join("failed").join("returns_2.ach").exists());
+
+    // Both returns credited back, less a 25.00 fee each
+    assert_eq!(balance_cents(&app, account_id).await, -2_000 - 5_000);
+
+    let (status, return_code): (String, Option<String>) = sqlx::query_as(
+        "SELECT status::text, return_code FROM ach_transfer WHERE trace_number = $1",
+    )
+    .bind(&closed)
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+    assert_eq!(status, "returned");
+    assert_eq!(return_code.as_deref(), Some("R02"));
+    let (status,): (String,) =
+        sqlx::query_as("SELECT status::text FROM ach_transfer WHERE trace_number = $1")
+            .bind(&kept)
+            .fetch_one(&app.get_db_state().pg_pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "batched");
+
+    // The same file again changes nothing
+    std::fs::copy(
+        return_dir.join("processed").join("returns_1.ach"),
+        return_dir.join("returns_3.ach"),
+    )
+    .unwrap();
+    let summary: serde_json::Value = app.post_ach_return_import().await.json().await.unwrap();
+    assert_eq!(summary["transfers_returned"], 0);
+    assert_eq!(summary["returns_skipped"], 3);
+    assert_eq!(balance_cents(&app, account_id).await, -7_000);
+
+    // A closed account can't be paid again
+    app.login(app.get_test_users().get_customer(), false).await;
+    let beneficiaries: serde_json::Value = app.get_beneficiaries().await.json().await.unwrap();
+    assert_eq!(beneficiaries[0]["flagged_reason"], "R02 Account closed");
+    let response = app
+        .post_ach_transfer(&serde_json::json!({"account_id": account_id,
+                                               "beneficiary_id": beneficiary_id,
+                                               "amount_cents": 1_000}))
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    std::fs::remove_dir_all(return_dir).unwrap();
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unverified_customers_are_held_to_the_kyc_limit() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let pool = &app.get_db_state().pg_pool;
+    sqlx::query(
+        "UPDATE beneficiary SET cooling_off_until = now() - interval '1 day' WHERE id = $1",
+    )
+    .bind(beneficiary_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tuser SET is_verified = false WHERE id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .execute(pool)
+        .await
+        .unwrap();
+    let transfer = |amount_cents: i64| {
+        serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                           "amount_cents": amount_cents})
+    };
+
+    // Act
+    let response = app.post_ach_transfer(&transfer(100_001)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let error = response.text().await.unwrap();
+    assert!(error.contains("verified customer identity"));
+    assert_eq!(balance_cents(&app, account_id).await, 0);
+
+    let response = app.post_ach_transfer(&transfer(20_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,160 @@
+use sqlx::Row;
+use thalia::authentication::cleanup::purge_expired_activate_tokens;
+use wiremock::{
+    Mock, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use crate::base::{TestApp, spawn_app};
+
+async fn mount_email(app: &TestApp) {
+    Mock::given(path("v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+}
+
+fn resend_body(app: &TestApp) -> serde_json::Value {
+    serde_json::json!({"email": app.get_test_users().get_customer().get_email().as_ref()})
+}
+
+async fn is_confirmed(app: &TestApp) -> bool {
+    sqlx::query("SELECT is_confirmed FROM tuser WHERE email = $1")
+        .bind(app.get_test_users().get_customer().get_email().as_ref())
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap()
+        .get("is_confirmed")
+}
+
+#[actix_web::test]
+async fn activation_link_confirms_customer_only_once() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_email(&app).await;
+    app.post_customer_signup(&app.customer_to_json()).await;
+
+    let confirmation_path = app.confirmation_paths().await.pop().unwrap();
+    assert!(confirmation_path.starts_with("/customer/confirm/"));
+
+    // Act
+    let first = app.get_confirmation(&confirmation_path).await;
+    let second = app.get_confirmation(&confirmation_path).await;
+
+    // Assert
+    assert_eq!(first.status().as_u16(), 200);
+    assert_eq!(second.status().as_u16(), 401);
+    assert!(is_confirmed(&app).await);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn resend_invalidates_previous_activation_link() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_email(&app).await;
+    app.post_customer_signup(&app.customer_to_json()).await;
+
+    // Act
+    let response = app.post_activation_resend(&resend_body(&app)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let paths = app.confirmation_paths().await;
+    assert_eq!(paths.len(), 2);
+
+    // Assert
+    let stale = app.get_confirmation(&paths[0]).await;
+    assert_eq!(stale.status().as_u16(), 401);
+
+    let fresh = app.get_confirmation(&paths[1]).await;
+    assert_eq!(fresh.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn resend_for_unknown_email_sends_nothing() {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    Mock::given(path("v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    // Act
+    let response = app.post_activation_resend(&resend_body(&app)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn resend_for_confirmed_customer_sends_nothing() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_email(&app).await;
+    app.post_customer_signup(&app.customer_to_json()).await;
+
+    let confirmation_path = app.confirmation_paths().await.pop().unwrap();
+    app.get_confirmation(&confirmation_path).await;
+
+    // Act
+    let response = app.post_activation_resend(&resend_body(&app)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.confirmation_paths().await.len(), 1);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn resend_is_rate_limited() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_email(&app).await;
+    app.post_customer_signup(&app.customer_to_json()).await;
+
+    // Act
+    for _ in 0..3 {
+        let response = app.post_activation_resend(&resend_body(&app)).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.post_activation_resend(&resend_body(&app)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 429);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn expired_activate_tokens_are_purged() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_email(&app).await;
+    app.post_customer_signup(&app.customer_to_json()).await;
+
+    sqlx::query("UPDATE activate_token SET expires_at = CURRENT_TIMESTAMP - INTERVAL '1 minute'")
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let n_deleted = purge_expired_activate_tokens(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(n_deleted, 1);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,194 @@
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+async fn issue_key(app: &TestApp, body: serde_json::Value) -> (Uuid, String) {
+    let response = app.post_api_key(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let issued: serde_json::Value = response.json().await.unwrap();
+
+    (
+        issued["id"].as_str().unwrap().parse().unwrap(),
+        issued["key"].as_str().unwrap().to_string(),
+    )
+}
+
+fn deposit_body() -> serde_json::Value {
+    serde_json::json!({"amount": 120.0, "currency": "USD", "transaction_ref": "ATM-0001",
+                        "source": "atm", "location_id": Uuid::now_v7(), "notes": "Cash deposit",
+                        "metadata": {"Device": {"id": "atm-001"}}})
+}
+
+#[actix_web::test]
+async fn superuser_issues_api_key_stored_as_hash() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    // Act
+    let (key_id, key) = issue_key(
+        &app,
+        serde_json::json!({"name": "atm-001", "scopes": ["transaction:deposit"], "expires_in_days": 90}),
+    )
+    .await;
+
+    // Assert
+    assert!(key.starts_with("tha_"));
+
+    let (key_hash,): (String,) = sqlx::query_as("SELECT key_hash FROM api_key WHERE id = $1")
+        .bind(key_id)
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    assert_ne!(key_hash, key);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn api_key_with_unknown_scope_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_api_key(&serde_json::json!({"name": "atm-001", "scopes": ["vault:open"]}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unauthenticated_staff_issue_api_key_returns_401() {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_api_key(&serde_json::json!({"name": "atm-001", "scopes": ["transaction:deposit"]}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn integration_deposit_without_api_key_returns_401() {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_integration_deposit(Uuid::now_v7(), None, &deposit_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn integration_deposit_without_deposit_scope_returns_403() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let (_, key) = issue_key(
+        &app,
+        serde_json::json!({"name": "atm-001", "scopes": ["transaction:withdraw"]}),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .post_integration_deposit(Uuid::now_v7(), Some(&key), &deposit_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn revoked_api_key_returns_401() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let (key_id, key) = issue_key(
+        &app,
+        serde_json::json!({"name": "atm-001", "scopes": ["transaction:deposit"]}),
+    )
+    .await;
+
+    let response = app
+        .get_run_state()
+        .api_client
+        .delete(format!(
+            "{}/staff/api-keys/{}",
+            app.get_run_state().address,
+            key_id
+        ))
+        .send()
+        .await
+        .expect("Failed to revoke api key");
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .post_integration_deposit(Uuid::now_v7(), Some(&key), &deposit_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn api_key_used_outside_its_ip_allow_list_returns_403() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let (_, key) = issue_key(
+        &app,
+        serde_json::json!({"name": "atm-001", "scopes": ["transaction:deposit"], "allowed_ips": ["10.20.0.0/16"]}),
+    )
+    .await;
+
+    // Act
+    let response = app
+        .post_integration_deposit(Uuid::now_v7(), Some(&key), &deposit_body())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
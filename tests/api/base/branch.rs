@@ -0,0 +1,64 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use thalia::branch::models::{BranchEntity, LegalEntityEntity};
+
+#[derive(Debug, getset::Getters)]
+#[get = "pub with_prefix"]
+pub struct Branches {
+    legal_entity: LegalEntityEntity,
+    head_office: BranchEntity,
+}
+
+impl Branches {
+    pub fn default() -> Self {
+        let legal_entity = LegalEntityEntity {
+            id: Uuid::now_v7(),
+            code: "THA-US".into(),
+            name: "Thalia Bank N.A.".into(),
+            country_code: "US".into(),
+            created_at: Utc::now(),
+        };
+
+        let head_office = BranchEntity {
+            id: Uuid::now_v7(),
+            legal_entity_id: legal_entity.id,
+            code: "NYC-01".into(),
+            name: "New York Head Office".into(),
+            country_code: "US".into(),
+            timezone: "America/New_York".into(),
+            is_active: true,
+            created_at: Utc::now(),
+        };
+
+        Self {
+            legal_entity,
+            head_office,
+        }
+    }
+
+    pub async fn store_branches(&self, pool: &PgPool) {
+        sqlx::query(
+            "INSERT INTO legal_entity(id, code, name, country_code) VALUES($1, $2, $3, $4)",
+        )
+        .bind(self.legal_entity.id)
+        .bind(&self.legal_entity.code)
+        .bind(&self.legal_entity.name)
+        .bind(&self.legal_entity.country_code)
+        .execute(pool)
+        .await
+        .expect("Failed to insert legal entity");
+
+        sqlx::query("INSERT INTO branch(id, legal_entity_id, code, name, country_code, timezone) VALUES($1, $2, $3, $4, $5, $6)")
+            .bind(self.head_office.id)
+            .bind(self.head_office.legal_entity_id)
+            .bind(&self.head_office.code)
+            .bind(&self.head_office.name)
+            .bind(&self.head_office.country_code)
+            .bind(&self.head_office.timezone)
+            .execute(pool)
+            .await
+            .expect("Failed to insert branch");
+    }
+}
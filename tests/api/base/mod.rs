@@ -1,8 +1,10 @@
 mod account_type;
+mod branch;
 mod invalid_user;
 mod test_user;
 use crate::base::{
     account_type::{AccountClasses, Coas},
+    branch::Branches,
     test_user::TestUsers,
 };
 use anyhow::Context;
@@ -10,11 +12,12 @@ use getset::Getters;
 pub use invalid_user::{create_invalid_user, create_underage_user};
 use once_cell::sync::Lazy;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
+use std::path::PathBuf;
 use thalia::{
-    config::runtime::{DatabaseConfig, Ttl, get_config},
-    notification::email_client::EmailClient,
+    config::runtime::{DatabaseConfig, get_config},
     startup::Application,
     telemetry::{get_tracing_subscriber, init_tracing_subscriber},
+    user::schemas::User,
 };
 use uuid::Uuid;
 use wiremock::MockServer;
@@ -27,7 +30,6 @@ pub struct StdResponse {
 #[derive(Debug)]
 pub struct RunState {
     pub address: String,
-    pub port: u16,
     pub api_client: reqwest::Client,
 }
 
@@ -41,7 +43,32 @@ pub struct DbState {
 #[derive(Debug)]
 pub struct MailState {
     pub email_server: MockServer,
-    pub email_client: EmailClient,
+}
+
+#[derive(Debug)]
+pub struct IdpState {
+    pub idp_server: MockServer,
+}
+
+// Stands in for an S3 compatible object store
+#[derive(Debug)]
+pub struct StorageState {
+    pub s3_server: MockServer,
+    pub bucket: String,
+    // Stands in for the clearing house's pain.002 drop folder
+    pub sepa_status_dir: PathBuf,
+    // Stands in for the bank's camt.054 and pacs.008 drop folder
+    pub inbound_dir: PathBuf,
+    // Stands in for the ACH operator's return file drop folder
+    pub ach_return_dir: PathBuf,
+}
+
+// An account opened for the test customer
+#[derive(Debug)]
+pub struct TestAccount {
+    pub id: Uuid,
+    pub iban: String,
+    pub account_number: String,
 }
 
 #[derive(Debug, Getters)]
@@ -50,105 +77,1289 @@ pub struct TestApp {
     pub run_state: RunState,
     pub db_state: DbState,
     pub mail_state: MailState,
+    pub idp_state: IdpState,
+    pub storage_state: StorageState,
     pub test_users: TestUsers,
     pub account_classes: AccountClasses,
     pub coas: Coas,
-    pub ttl: Ttl,
+    pub branches: Branches,
 }
 
-impl TestApp {
-    fn new(
-        run_state: RunState,
-        db_state: DbState,
-        mail_state: MailState,
-        ttl: Ttl,
-        test_users: TestUsers,
-        account_classes: AccountClasses,
-        coas: Coas,
-    ) -> Self {
-        TestApp {
-            run_state,
-            db_state,
-            mail_state,
-            test_users,
-            account_classes,
-            coas,
-            ttl,
-        }
+impl TestApp {
+    pub async fn get_health(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/home/health", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to execute")
+    }
+
+    pub async fn get_jwks(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/.well-known/jwks.json", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to execute")
+    }
+
+    pub async fn get_staff_sso_login(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/staff/sso/login", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to start staff sso")
+    }
+
+    pub async fn get_staff_sso_callback(&self, code: &str, state: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/staff/sso/callback", self.run_state.address))
+            .query(&[("code", code), ("state", state)])
+            .send()
+            .await
+            .expect("Failed to complete staff sso")
+    }
+
+    pub async fn post_customer_signup<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/signup", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to login customer")
+    }
+
+    pub async fn post_customer_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/login", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to login staff")
+    }
+
+    pub async fn post_staff_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/login", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to login staff")
+    }
+
+    pub async fn post_staff_signup<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/signup", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to login staff")
+    }
+
+    pub async fn post_coa_creation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/coa", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create coa")
+    }
+
+    pub async fn post_activation_resend<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/auth/activation/resend", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to resend activation")
+    }
+
+    pub async fn get_confirmation(&self, confirmation_path: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}{}", self.run_state.address, confirmation_path))
+            .send()
+            .await
+            .expect("Failed to confirm user")
+    }
+
+    // `/{role}/confirm/{token}` paths from every email sent so far, oldest first
+    pub async fn confirmation_paths(&self) -> Vec<String> {
+        self.mail_state
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let text = body["Text-part"].as_str().unwrap();
+
+                let confirm_at = text.find("/confirm/").unwrap();
+                let role_at = text[..confirm_at].rfind('/').unwrap();
+                let token: String = text[confirm_at + "/confirm/".len()..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || "-_.".contains(*c))
+                    .collect();
+
+                format!("{}/confirm/{}", &text[role_at..confirm_at], token)
+            })
+            .collect()
+    }
+
+    // `/customer/email/confirm/{token}` paths from every email change link sent so far
+    pub async fn email_change_paths(&self) -> Vec<String> {
+        let marker = "/customer/email/confirm/";
+
+        self.mail_state
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let text = body["Text-part"].as_str().unwrap();
+
+                let confirm_at = text.find(marker)?;
+                let token: String = text[confirm_at + marker.len()..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric())
+                    .collect();
+
+                Some(format!("{}{}", marker, token))
+            })
+            .collect()
+    }
+
+    // Step-up codes from every verification email sent so far, oldest first
+    pub async fn verification_codes(&self) -> Vec<String> {
+        self.mail_state
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                if body["Subject"] != "Your verification code" {
+                    return None;
+                }
+
+                body["Text-part"]
+                    .as_str()
+                    .unwrap()
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| line.len() == 6 && line.chars().all(|c| c.is_ascii_digit()))
+                    .map(String::from)
+            })
+            .collect()
+    }
+
+    pub async fn get_sessions(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/auth/sessions", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to list sessions")
+    }
+
+    pub async fn delete_session(&self, session_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/auth/sessions/{}",
+                self.run_state.address, session_id
+            ))
+            .send()
+            .await
+            .expect("Failed to end session")
+    }
+
+    pub async fn delete_sessions(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!("{}/auth/sessions", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to end sessions")
+    }
+
+    pub async fn delete_customer_sessions(&self, customer_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/staff/customers/{}/sessions",
+                self.run_state.address, customer_id
+            ))
+            .send()
+            .await
+            .expect("Failed to terminate customer sessions")
+    }
+
+    pub async fn post_kyc_document(&self, form: reqwest::multipart::Form) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/kyc/documents", self.run_state.address))
+            .multipart(form)
+            .send()
+            .await
+            .expect("Failed to upload kyc document")
+    }
+
+    pub async fn get_customer_kyc_documents(&self, customer_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/customers/{}/kyc/documents",
+                self.run_state.address, customer_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch customer kyc documents")
+    }
+
+    pub async fn post_kyc_submit(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/kyc/submit", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to submit kyc case")
+    }
+
+    pub async fn get_kyc_status(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/customer/kyc/status", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to fetch kyc status")
+    }
+
+    pub async fn get_kyc_cases(&self, status: Option<&str>) -> reqwest::Response {
+        let mut request = self
+            .run_state
+            .api_client
+            .get(format!("{}/staff/kyc/cases", self.run_state.address));
+
+        if let Some(status) = status {
+            request = request.query(&[("status", status)]);
+        }
+
+        request.send().await.expect("Failed to fetch kyc cases")
+    }
+
+    pub async fn post_kyc_review<Body>(&self, case_id: Uuid, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/kyc/cases/{}/review",
+                self.run_state.address, case_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to review kyc case")
+    }
+
+    pub async fn get_profile(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/customer/profile", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to fetch profile")
+    }
+
+    pub async fn get_profile_history(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/profile/history",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch profile history")
+    }
+
+    pub async fn post_email_change<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/profile/email", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to request email change")
+    }
+
+    pub async fn put_address<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/customer/profile/address",
+                self.run_state.address
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to update address")
+    }
+
+    pub async fn put_phone<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!("{}/customer/profile/phone", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to update phone")
+    }
+
+    pub async fn put_customer_profile<Body>(
+        &self,
+        customer_id: Uuid,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/staff/customers/{}/profile",
+                self.run_state.address, customer_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to update customer profile")
+    }
+
+    pub async fn get_customers(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/staff/customers", self.run_state.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to search customers")
+    }
+
+    pub async fn get_customer_overview(&self, customer_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/customers/{}",
+                self.run_state.address, customer_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch customer overview")
+    }
+
+    pub async fn get_customer_profile_history(&self, customer_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/customers/{}/profile/history",
+                self.run_state.address, customer_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch customer profile history")
+    }
+
+    pub async fn post_legal_entity<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/legal-entities", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create legal entity")
+    }
+
+    pub async fn post_branch<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/branches", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create branch")
+    }
+
+    pub async fn post_staff_account<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/account", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create user account")
+    }
+
+    pub async fn get_branch_report(
+        &self,
+        branch_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/branches/{}/report",
+                self.run_state.address, branch_id
+            ))
+            .query(&[("from", from.to_string()), ("to", to.to_string())])
+            .send()
+            .await
+            .expect("Failed to fetch branch report")
+    }
+
+    pub async fn post_product<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/products", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create product")
+    }
+
+    pub async fn get_product(&self, product_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/products/{}",
+                self.run_state.address, product_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch product")
+    }
+
+    pub async fn put_product<Body>(&self, product_id: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/staff/products/{}",
+                self.run_state.address, product_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to update product")
+    }
+
+    pub async fn delete_product(&self, product_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/staff/products/{}",
+                self.run_state.address, product_id
+            ))
+            .send()
+            .await
+            .expect("Failed to retire product")
+    }
+
+    pub async fn get_product_versions(&self, product_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/products/{}/versions",
+                self.run_state.address, product_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch product versions")
+    }
+
+    pub async fn put_overdraft<Body>(&self, account_id: Uuid, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/staff/accounts/{}/overdraft",
+                self.run_state.address, account_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to grant overdraft")
+    }
+
+    pub async fn get_overdraft(&self, account_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/accounts/{}/overdraft",
+                self.run_state.address, account_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch overdraft")
+    }
+
+    pub async fn post_forced_debit<Body>(&self, account_id: Uuid, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/accounts/{}/forced-debits",
+                self.run_state.address, account_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to post forced debit")
+    }
+
+    pub async fn post_overdraft_accruals<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/overdrafts/accruals",
+                self.run_state.address
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to run overdraft accruals")
+    }
+
+    pub async fn get_overdraft_utilization(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/overdrafts/utilization",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch overdraft utilization")
+    }
+
+    pub async fn post_hold<Body>(&self, account_id: Uuid, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/accounts/{}/holds",
+                self.run_state.address, account_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to place hold")
+    }
+
+    pub async fn get_holds(&self, account_id: Uuid, include_released: bool) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/accounts/{}/holds?include_released={}",
+                self.run_state.address, account_id, include_released
+            ))
+            .send()
+            .await
+            .expect("Failed to list holds")
+    }
+
+    pub async fn delete_hold(&self, account_id: Uuid, hold_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/staff/accounts/{}/holds/{}",
+                self.run_state.address, account_id, hold_id
+            ))
+            .send()
+            .await
+            .expect("Failed to release hold")
+    }
+
+    pub async fn post_card_authorization<Body>(
+        &self,
+        account_id: Uuid,
+        api_key: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/integration/card/authorizations/{}",
+                self.run_state.address, account_id
+            ))
+            .header("X-Api-Key", api_key)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to post card authorization")
+    }
+
+    pub async fn post_beneficiary_challenge(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/customer/beneficiaries/challenge",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to request beneficiary challenge")
+    }
+
+    pub async fn post_beneficiary<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/beneficiaries", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to add beneficiary")
+    }
+
+    pub async fn get_beneficiaries(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/customer/beneficiaries", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to list beneficiaries")
+    }
+
+    pub async fn put_beneficiary<Body>(
+        &self,
+        beneficiary_id: &str,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/customer/beneficiaries/{}",
+                self.run_state.address, beneficiary_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to rename beneficiary")
+    }
+
+    pub async fn delete_beneficiary(&self, beneficiary_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/customer/beneficiaries/{}",
+                self.run_state.address, beneficiary_id
+            ))
+            .send()
+            .await
+            .expect("Failed to remove beneficiary")
+    }
+
+    pub async fn post_scheduled_payment<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/customer/scheduled-payments",
+                self.run_state.address
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to create scheduled payment")
+    }
+
+    pub async fn get_scheduled_payment(&self, payment_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/scheduled-payments/{}",
+                self.run_state.address, payment_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch scheduled payment")
+    }
+
+    pub async fn delete_scheduled_payment(&self, payment_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .delete(format!(
+                "{}/customer/scheduled-payments/{}",
+                self.run_state.address, payment_id
+            ))
+            .send()
+            .await
+            .expect("Failed to cancel scheduled payment")
+    }
+
+    pub async fn post_scheduler_run(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/scheduled-payments/run",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to run payment scheduler")
     }
 
-    pub async fn get_health(&self) -> reqwest::Response {
+    pub async fn post_sepa_transfer(&self, body: &serde_json::Value) -> reqwest::Response {
         self.run_state
             .api_client
-            .get(format!("{}/home/health", self.run_state.address))
+            .post(format!(
+                "{}/customer/sepa-transfers",
+                self.run_state.address
+            ))
+            .json(body)
             .send()
             .await
-            .expect("Failed to execute")
+            .expect("Failed to queue SEPA transfer")
     }
 
-    pub async fn post_customer_signup<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn get_sepa_transfer(&self, transfer_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/sepa-transfers/{}",
+                self.run_state.address, transfer_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch SEPA transfer")
+    }
+
+    pub async fn post_sepa_batch(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/sepa/batches", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to generate SEPA batch")
+    }
+
+    pub async fn get_sepa_batch(&self, batch_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/sepa/batches/{}",
+                self.run_state.address, batch_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch SEPA batch")
+    }
+
+    pub async fn post_sepa_status_import(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/sepa/status-reports/import",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to import SEPA status reports")
+    }
+
+    pub async fn post_inbound_import(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/inbound-payments/import",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to import inbound payments")
+    }
+
+    pub async fn get_inbound_payments(&self, status: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/inbound-payments?status={}",
+                self.run_state.address, status
+            ))
+            .send()
+            .await
+            .expect("Failed to list inbound payments")
+    }
+
+    pub async fn post_inbound_resolution<Body>(
+        &self,
+        payment_id: &str,
+        action: &str,
+        body: &Body,
+    ) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.run_state
             .api_client
-            .post(format!("{}/customer/signup", self.run_state.address))
+            .post(format!(
+                "{}/staff/inbound-payments/{}/{}",
+                self.run_state.address, payment_id, action
+            ))
             .json(body)
             .send()
             .await
-            .expect("Failed to login customer")
+            .expect("Failed to repair inbound payment")
     }
 
-    pub async fn post_customer_login<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn post_ach_transfer(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/customer/ach-transfers", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to queue ACH transfer")
+    }
+
+    pub async fn post_ach_file(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/ach/files", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to generate ACH file")
+    }
+
+    pub async fn get_ach_file(&self, file_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/ach/files/{}",
+                self.run_state.address, file_id
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch ACH file")
+    }
+
+    pub async fn post_ach_return_import(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/ach/returns/import",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to import ACH returns")
+    }
+
+    pub async fn post_swift_transfer(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/customer/swift-transfers",
+                self.run_state.address
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to send SWIFT transfer")
+    }
+
+    pub async fn put_fx_rate(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .put(format!("{}/staff/swift/fx-rates", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to set FX rate")
+    }
+
+    pub async fn post_swift_inbound(&self, message: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/swift/inbound", self.run_state.address))
+            .json(&serde_json::json!({"message": message}))
+            .send()
+            .await
+            .expect("Failed to receive SWIFT message")
+    }
+
+    pub async fn post_nostro_statement(&self, statement: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/nostro/statements",
+                self.run_state.address
+            ))
+            .json(&serde_json::json!({"statement": statement}))
+            .send()
+            .await
+            .expect("Failed to import nostro statement")
+    }
+
+    pub async fn get_nostro_items(&self, status: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/nostro/items?status={}",
+                self.run_state.address, status
+            ))
+            .send()
+            .await
+            .expect("Failed to list nostro statement items")
+    }
+
+    pub async fn get_nostro_ledger_lines(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/nostro/ledger-lines",
+                self.run_state.address
+            ))
+            .send()
+            .await
+            .expect("Failed to list open nostro journal lines")
+    }
+
+    pub async fn post_nostro_match(
+        &self,
+        item_id: &str,
+        body: &serde_json::Value,
+    ) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/nostro/items/{}/match",
+                self.run_state.address, item_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to match nostro statement item")
+    }
+
+    pub async fn get_nostro_aging(&self, as_of: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/nostro/aging?as_of={}",
+                self.run_state.address, as_of
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch nostro aging")
+    }
+
+    pub async fn post_statement_run(&self, body: &serde_json::Value) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/statements/run", self.run_state.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to run account statements")
+    }
+
+    pub async fn get_account_statements(&self, account_id: Uuid) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/accounts/{}/statements",
+                self.run_state.address, account_id
+            ))
+            .send()
+            .await
+            .expect("Failed to list account statements")
+    }
+
+    pub async fn get_statement_download(
+        &self,
+        account_id: Uuid,
+        statement_id: &str,
+        format: &str,
+    ) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/accounts/{}/statements/{}/download?format={}",
+                self.run_state.address, account_id, statement_id, format
+            ))
+            .send()
+            .await
+            .expect("Failed to download account statement")
+    }
+
+    pub async fn get_customer_camt053(&self, account_id: Uuid, query: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/customer/accounts/{}/statements/camt053?{}",
+                self.run_state.address, account_id, query
+            ))
+            .send()
+            .await
+            .expect("Failed to export camt.053 statement")
+    }
+
+    pub async fn get_staff_camt053(&self, account_id: Uuid, query: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/staff/accounts/{}/statements/camt053?{}",
+                self.run_state.address, account_id, query
+            ))
+            .send()
+            .await
+            .expect("Failed to export camt.053 statement")
+    }
+
+    pub async fn get_balance_sheet(&self, query: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/reporting/balance-sheet?{}",
+                self.run_state.address, query
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch balance sheet")
+    }
+
+    pub async fn get_income_statement(&self, query: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!(
+                "{}/reporting/income-statement?{}",
+                self.run_state.address, query
+            ))
+            .send()
+            .await
+            .expect("Failed to fetch income statement")
+    }
+
+    pub async fn post_fiscal_year<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.run_state
             .api_client
-            .post(format!("{}/customer/login", self.run_state.address))
+            .post(format!("{}/staff/fiscal-years", self.run_state.address))
             .json(body)
             .send()
             .await
-            .expect("Failed to login staff")
+            .expect("Failed to create fiscal year")
     }
 
-    pub async fn post_staff_login<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn get_fiscal_years(&self) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .get(format!("{}/staff/fiscal-years", self.run_state.address))
+            .send()
+            .await
+            .expect("Failed to fetch fiscal years")
+    }
+
+    pub async fn put_fiscal_period_status(
+        &self,
+        period_id: &str,
+        status: &str,
+    ) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .put(format!(
+                "{}/staff/fiscal-periods/{}/status",
+                self.run_state.address, period_id
+            ))
+            .json(&serde_json::json!({"status": status}))
+            .send()
+            .await
+            .expect("Failed to change fiscal period status")
+    }
+
+    pub async fn post_fiscal_year_close(&self, year_id: &str) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!(
+                "{}/staff/fiscal-years/{}/close",
+                self.run_state.address, year_id
+            ))
+            .send()
+            .await
+            .expect("Failed to close fiscal year")
+    }
+
+    pub async fn post_adjusting_entry<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.run_state
             .api_client
-            .post(format!("{}/staff/login", self.run_state.address))
+            .post(format!("{}/ledger/adjustments", self.run_state.address))
             .json(body)
             .send()
             .await
-            .expect("Failed to login staff")
+            .expect("Failed to post adjusting entry")
     }
 
-    pub async fn post_staff_signup<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn post_watchlist(&self, form: reqwest::multipart::Form) -> reqwest::Response {
+        self.run_state
+            .api_client
+            .post(format!("{}/staff/screening/lists", self.run_state.address))
+            .multipart(form)
+            .send()
+            .await
+            .expect("Failed to load watch list")
+    }
+
+    pub async fn get_screening_hits(&self, status: Option<&str>) -> reqwest::Response {
+        let mut request = self
+            .run_state
+            .api_client
+            .get(format!("{}/staff/screening/hits", self.run_state.address));
+
+        if let Some(status) = status {
+            request = request.query(&[("status", status)]);
+        }
+
+        request
+            .send()
+            .await
+            .expect("Failed to fetch screening hits")
+    }
+
+    pub async fn post_screening_review<Body>(&self, hit_id: Uuid, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.run_state
             .api_client
-            .post(format!("{}/staff/signup", self.run_state.address))
+            .post(format!(
+                "{}/staff/screening/hits/{}/review",
+                self.run_state.address, hit_id
+            ))
             .json(body)
             .send()
             .await
-            .expect("Failed to login staff")
+            .expect("Failed to review screening hit")
     }
 
-    pub async fn post_coa_creation<Body>(&self, body: &Body) -> reqwest::Response
+    pub async fn post_api_key<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
     {
         self.run_state
             .api_client
-            .post(format!("{}/staff/coa", self.run_state.address))
+            .post(format!("{}/staff/api-keys", self.run_state.address))
             .json(body)
             .send()
             .await
-            .expect("Failed to create coa")
+            .expect("Failed to issue api key")
+    }
+
+    pub async fn post_integration_deposit<Body>(
+        &self,
+        account_id: Uuid,
+        api_key: Option<&str>,
+        body: &Body,
+    ) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        let mut request = self
+            .run_state
+            .api_client
+            .post(format!(
+                "{}/integration/transaction/deposit/{}",
+                self.run_state.address, account_id
+            ))
+            .json(body);
+
+        if let Some(key) = api_key {
+            request = request.header("X-Api-Key", key);
+        }
+
+        request.send().await.expect("Failed to post deposit")
     }
 
     pub async fn clear_test_db(&mut self) {
@@ -158,6 +1369,102 @@ impl TestApp {
             .expect("Failed to drop database");
     }
 
+    pub async fn login(&self, user: &User, staff: bool) {
+        let login_body = serde_json::json!({"login_id": {"email": user.get_email().as_ref()},
+                                            "password": user.get_password().as_ref()});
+
+        let response = if staff {
+            self.post_staff_login(&login_body).await
+        } else {
+            self.post_customer_login(&login_body).await
+        };
+
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    pub async fn login_staff(&self) {
+        self.login(self.get_test_users().get_staff(), true).await;
+    }
+
+    // Pending accounts for the verified test customer on a new deposit product with these
+    // terms, oldest first. The test users, chart of accounts and branches are stored first and
+    // staff stays logged in.
+    pub async fn open_accounts(
+        &self,
+        product_code: &str,
+        terms: serde_json::Value,
+        count: usize,
+    ) -> Vec<TestAccount> {
+        let pool = &self.get_db_state().pg_pool;
+        self.get_test_users().store_test_users(pool).await;
+        self.get_test_users().verify_customer(pool).await;
+        self.get_coas().store_coas(pool).await;
+        self.get_branches().store_branches(pool).await;
+        self.login_staff().await;
+
+        let product = serde_json::json!({"code": product_code, "kind": "deposit", "name": "Current Account",
+                                         "coa_id": self.get_coas().get_store().get("2020").unwrap().get_id(),
+                                         "terms": terms});
+        let response = self.post_product(&product).await;
+        assert_eq!(response.status().as_u16(), 200);
+        let product: serde_json::Value = response.json().await.unwrap();
+
+        for _ in 0..count {
+            let response = self
+                .post_staff_account(
+                    &serde_json::json!({"user_id": self.get_test_users().get_customer().get_id(),
+                                        "branch_id": self.get_branches().get_head_office().id,
+                                        "coa_id": Uuid::now_v7(),
+                                        "account_class": product["id"],
+                                        "country_code": 840}),
+                )
+                .await;
+            assert_eq!(response.status().as_u16(), 200);
+        }
+
+        let accounts: Vec<(Uuid, String, String)> = sqlx::query_as(
+            "SELECT id, iban, account_number FROM user_account WHERE user_id = $1 ORDER BY account_number",
+        )
+        .bind(self.get_test_users().get_customer().get_id())
+        .fetch_all(pool)
+        .await
+        .unwrap();
+
+        accounts
+            .into_iter()
+            .map(|(id, iban, account_number)| TestAccount {
+                id,
+                iban,
+                account_number,
+            })
+            .collect()
+    }
+
+    // The one pending account for the test customer, as `open_accounts`
+    pub async fn open_account(&self, product_code: &str, terms: serde_json::Value) -> TestAccount {
+        self.open_accounts(product_code, terms, 1).await.remove(0)
+    }
+
+    pub async fn activate_account(&self, account_id: Uuid) {
+        sqlx::query("UPDATE user_account SET status = 'active' WHERE id = $1")
+            .bind(account_id)
+            .execute(&self.get_db_state().pg_pool)
+            .await
+            .unwrap();
+    }
+
+    // Arranged for six months, the product has to allow the limit
+    pub async fn arrange_overdraft(&self, account_id: Uuid, limit_cents: i64) {
+        let expires_on = chrono::Utc::now().date_naive() + chrono::Days::new(180);
+        let response = self
+            .put_overdraft(
+                account_id,
+                &serde_json::json!({"limit_cents": limit_cents, "expires_on": expires_on}),
+            )
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
     pub fn staff_to_json(&self) -> serde_json::Value {
         let value = serde_json::json!({"first_name": self.get_test_users().get_staff().get_first_name().as_ref(),
                                             "last_name": self.get_test_users().get_staff().get_last_name().as_ref(),
@@ -228,6 +1535,8 @@ pub async fn spawn_app() -> TestApp {
     Lazy::force(&TRACING);
 
     let email_server = MockServer::start().await;
+    let idp_server = MockServer::start().await;
+    let s3_server = MockServer::start().await;
 
     let config = {
         let mut config = get_config().expect("Failed to load configuration");
@@ -238,6 +1547,35 @@ pub async fn spawn_app() -> TestApp {
 
         config.email_client.email_base_uri = format!("{}/v3/send", email_server.uri());
 
+        // Test key set with a rotated access key (access-k2)
+        config.jwt.keys_dir = "tests/fixtures/jwt".into();
+        config.jwt.algorithm = "EdDSA".into();
+        config.jwt.access_kid = "access-k1".into();
+        config.jwt.refresh_kid = "refresh-k1".into();
+        config.jwt.activate_kid = "activate-k1".into();
+
+        config.oidc.issuer_url = Some(idp_server.uri());
+        config.oidc.client_id = "thalia".into();
+        config.oidc.superuser_groups = "thalia-superusers".into();
+        config.oidc.manager_groups = "thalia-managers".into();
+
+        config.s3_client.endpoint_url = Some(s3_server.uri());
+        config.s3_client.image_bucket = "thalia-kyc".into();
+        config.s3_client.access_key_id = Some("thalia-test".into());
+        config.s3_client.secret_access_key = Some("thalia-test-secret".into());
+
+        let sepa_status_dir =
+            std::env::temp_dir().join(format!("thalia-pain002-{}", config.database.db_name));
+        config.sepa.status_report_dir = Some(sepa_status_dir.display().to_string());
+
+        let inbound_dir =
+            std::env::temp_dir().join(format!("thalia-inbound-{}", config.database.db_name));
+        config.inbound.drop_dir = Some(inbound_dir.display().to_string());
+
+        let ach_return_dir =
+            std::env::temp_dir().join(format!("thalia-ach-returns-{}", config.database.db_name));
+        config.ach.return_dir = Some(ach_return_dir.display().to_string());
+
         config
     };
 
@@ -248,12 +1586,12 @@ pub async fn spawn_app() -> TestApp {
         .await
         .expect("Failed to build application");
 
-    let port = app.port();
-    let address = format!("http://127.0.0.1:{}", port);
+    let address = format!("http://127.0.0.1:{}", app.port());
     tokio::spawn(app.run_until_stopped());
     let test_users = TestUsers::generate_users();
     let coas = Coas::default();
     let account_classes = AccountClasses::default(&coas);
+    let branches = Branches::default();
 
     let api_client = reqwest::Client::builder()
         .cookie_store(true)
@@ -263,7 +1601,6 @@ pub async fn spawn_app() -> TestApp {
     let run_state = RunState {
         address,
         api_client,
-        port,
     };
 
     let db_state = DbState {
@@ -272,18 +1609,39 @@ pub async fn spawn_app() -> TestApp {
         connection,
     };
 
-    let mail_state = MailState {
-        email_client: config.email_client.client().unwrap(),
-        email_server,
+    let mail_state = MailState { email_server };
+
+    let idp_state = IdpState { idp_server };
+
+    let storage_state = StorageState {
+        s3_server,
+        bucket: config.s3_client.image_bucket,
+        sepa_status_dir: config
+            .sepa
+            .status_report_dir
+            .map(PathBuf::from)
+            .expect("Missing pain.002 directory"),
+        inbound_dir: config
+            .inbound
+            .drop_dir
+            .map(PathBuf::from)
+            .expect("Missing inbound payment directory"),
+        ach_return_dir: config
+            .ach
+            .return_dir
+            .map(PathBuf::from)
+            .expect("Missing ACH return directory"),
     };
 
-    TestApp::new(
+    TestApp {
         run_state,
         db_state,
         mail_state,
-        config.ttl,
+        idp_state,
+        storage_state,
         test_users,
         account_classes,
         coas,
-    )
+        branches,
+    }
 }
@@ -81,4 +81,13 @@ impl TestUsers {
         .bind(AccessRole::Customer)
         .execute(pool).await.expect("Failed to store test users");
     }
+
+    // Skips the kyc review for tests that only need a verified customer
+    pub async fn verify_customer(&self, pool: &PgPool) {
+        sqlx::query("UPDATE tuser SET is_verified = true WHERE id = $1")
+            .bind(self.get_customer().get_id())
+            .execute(pool)
+            .await
+            .expect("Failed to verify test customer");
+    }
 }
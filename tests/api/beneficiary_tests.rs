@@ -0,0 +1,170 @@
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+async fn login_customer(app: &TestApp) {
+    let pool = &app.get_db_state().pg_pool;
+    app.get_test_users().store_test_users(pool).await;
+
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+}
+
+// Challenge id and the code emailed for it
+async fn request_challenge(app: &TestApp) -> (Uuid, String) {
+    let response = app.post_beneficiary_challenge().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let challenge: serde_json::Value = response.json().await.unwrap();
+
+    let code = app.verification_codes().await.pop().unwrap();
+
+    (
+        challenge["challenge_id"].as_str().unwrap().parse().unwrap(),
+        code,
+    )
+}
+
+fn beneficiary_body(iban: &str, challenge_id: Uuid, code: &str) -> serde_json::Value {
+    serde_json::json!({"name": "Jane Doe", "iban": iban, "currency": "EUR",
+                       "challenge_id": challenge_id, "code": code})
+}
+
+#[actix_web::test]
+async fn beneficiaries_are_added_with_an_emailed_code() {
+    // Arrange
+    let mut app = spawn_app().await;
+    login_customer(&app).await;
+    let (challenge_id, code) = request_challenge(&app).await;
+
+    // Act
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "DE89 3704 0044 0532 0130 00",
+            challenge_id,
+            &code,
+        ))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(beneficiary["iban"], "DE89370400440532013000");
+    assert_eq!(beneficiary["bank_country"], "DE");
+    assert!(
+        beneficiary["cooling_off_until"].as_str().unwrap()
+            > beneficiary["created_at"].as_str().unwrap()
+    );
+
+    // Codes are single use
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "GB82 WEST 1234 5698 7654 32",
+            challenge_id,
+            &code,
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let (challenge_id, code) = request_challenge(&app).await;
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "DE89370400440532013000",
+            challenge_id,
+            &code,
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let id = beneficiary["id"].as_str().unwrap();
+    let response = app
+        .put_beneficiary(id, &serde_json::json!({"name": "Jane (rent)"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let beneficiaries: serde_json::Value = app.get_beneficiaries().await.json().await.unwrap();
+    assert_eq!(beneficiaries.as_array().unwrap().len(), 1);
+    assert_eq!(beneficiaries[0]["name"], "Jane (rent)");
+
+    let response = app.delete_beneficiary(id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.delete_beneficiary(id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn wrong_codes_are_limited() {
+    // Arrange
+    let mut app = spawn_app().await;
+    login_customer(&app).await;
+    let (challenge_id, code) = request_challenge(&app).await;
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    // Act
+    let mut statuses = vec![];
+    for _ in 0..5 {
+        let response = app
+            .post_beneficiary(&beneficiary_body(
+                "DE89370400440532013000",
+                challenge_id,
+                wrong,
+            ))
+            .await;
+        statuses.push(response.status().as_u16());
+    }
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "DE89370400440532013000",
+            challenge_id,
+            &code,
+        ))
+        .await;
+
+    // Assert
+    assert!(statuses.iter().all(|s| *s == 401));
+    assert_eq!(response.status().as_u16(), 401);
+
+    let beneficiaries: serde_json::Value = app.get_beneficiaries().await.json().await.unwrap();
+    assert_eq!(beneficiaries.as_array().unwrap().len(), 0);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn invalid_ibans_are_rejected_without_using_the_code() {
+    // Arrange
+    let mut app = spawn_app().await;
+    login_customer(&app).await;
+    let (challenge_id, code) = request_challenge(&app).await;
+
+    // Act
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "DE88 3704 0044 0532 0130 00",
+            challenge_id,
+            &code,
+        ))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .post_beneficiary(&beneficiary_body(
+            "DE89 3704 0044 0532 0130 00",
+            challenge_id,
+            &code,
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,211 @@
+use chrono::{Days, Utc};
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+// The stored test staff member is a superuser, demote them before logging in
+async fn demote_staff_to_manager(app: &TestApp) {
+    sqlx::query("UPDATE tuser SET access_role = 'manager' WHERE id = $1")
+        .bind(app.get_test_users().get_staff().get_id())
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .expect("Failed to demote test staff");
+}
+
+async fn store_fixtures(app: &TestApp) {
+    let pool = &app.get_db_state().pg_pool;
+    app.get_test_users().store_test_users(pool).await;
+    app.get_test_users().verify_customer(pool).await;
+    app.get_coas().store_coas(pool).await;
+    app.get_account_classes().store_account_classes(pool).await;
+    app.get_branches().store_branches(pool).await;
+}
+
+fn account_body(app: &TestApp, branch_id: Uuid) -> serde_json::Value {
+    serde_json::json!({"user_id": app.get_test_users().get_customer().get_id(),
+                       "branch_id": branch_id,
+                       "coa_id": Uuid::now_v7(),
+                       "account_class": app.get_account_classes().get_checking().get_id(),
+                       "country_code": 840})
+}
+
+#[actix_web::test]
+async fn superuser_creates_a_legal_entity_and_branch() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let response = app
+        .post_legal_entity(
+            &serde_json::json!({"code": "tha-de", "name": "Thalia Bank AG",
+                                               "country_code": "de"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let entity: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(entity["code"], "THA-DE");
+
+    let branch = serde_json::json!({"legal_entity_id": entity["id"], "code": "ber-01",
+                                    "name": "Berlin Mitte", "country_code": "DE",
+                                    "timezone": "Europe/Berlin"});
+
+    // Act
+    let response = app.post_branch(&branch).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let created: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(created["code"], "BER-01");
+    assert_eq!(created["is_active"], true);
+
+    let response = app.post_branch(&branch).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn branch_with_an_unknown_timezone_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_branch(
+            &serde_json::json!({"legal_entity_id": app.get_branches().get_legal_entity().id,
+                                         "code": "BER-02", "name": "Berlin Ost",
+                                         "country_code": "DE", "timezone": "Europe/Atlantis"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn managers_cannot_create_branches() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    demote_staff_to_manager(&app).await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_branch(
+            &serde_json::json!({"legal_entity_id": app.get_branches().get_legal_entity().id,
+                                         "code": "BOS-01", "name": "Boston",
+                                         "country_code": "US", "timezone": "America/New_York"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn account_opening_at_an_unknown_branch_returns_404() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_staff_account(&account_body(&app, Uuid::now_v7()))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn managers_only_open_accounts_at_their_own_branches() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    demote_staff_to_manager(&app).await;
+    app.login_staff().await;
+
+    let branch_id = app.get_branches().get_head_office().id;
+
+    let response = app.post_staff_account(&account_body(&app, branch_id)).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    sqlx::query("INSERT INTO staff_branch(staff_id, branch_id) VALUES($1, $2)")
+        .bind(app.get_test_users().get_staff().get_id())
+        .bind(branch_id)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .expect("Failed to assign test staff");
+
+    // Act
+    let response = app.post_staff_account(&account_body(&app, branch_id)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn branch_report_counts_accounts_opened() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    let branch_id = app.get_branches().get_head_office().id;
+
+    let response = app.post_staff_account(&account_body(&app, branch_id)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let account_id: Uuid = sqlx::query_scalar("SELECT id FROM user_account WHERE user_id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 2_500, "transaction_ref": "CHQ-3602",
+                                "description": "Returned cheque"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Wide enough to cover today wherever the branch is
+    let today = Utc::now().date_naive();
+    let from = today.checked_sub_days(Days::new(1)).unwrap();
+    let to = today.checked_add_days(Days::new(1)).unwrap();
+
+    // Act
+    let response = app.get_branch_report(branch_id, from, to).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["accounts_opened"], 1);
+    assert_eq!(report["open_accounts"], 1);
+    assert_eq!(report["deposit_count"], 0);
+    assert_eq!(report["total_balance_cents"], -2_500);
+
+    let response = app.get_branch_report(branch_id, to, from).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
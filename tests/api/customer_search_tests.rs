@@ -0,0 +1,214 @@
+use reqwest::multipart::{Form, Part};
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+async fn open_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+
+    app.open_account("CUR-360", terms).await.id
+}
+
+fn customer_ids(customers: &serde_json::Value) -> Vec<String> {
+    customers
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[actix_web::test]
+async fn search_by_email_prefix_finds_the_customer() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let customer = app.get_test_users().get_customer();
+    let prefix = customer.get_email().as_ref()[..4].to_uppercase();
+
+    // Act
+    let response = app.get_customers(&[("email", &prefix)]).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let customers: serde_json::Value = response.json().await.unwrap();
+    assert!(customer_ids(&customers).contains(&customer.get_id().to_string()));
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn search_by_name_tolerates_a_typo() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let customer = app.get_test_users().get_customer();
+    let mut name = format!(
+        "{} {}",
+        customer.get_first_name().as_ref(),
+        customer.get_last_name().as_ref()
+    );
+    name.pop();
+    name.push('q');
+
+    // Act
+    let response = app.get_customers(&[("q", &name)]).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let customers: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(customers[0]["id"], customer.get_id().to_string());
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn search_never_returns_staff() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let staff = app.get_test_users().get_staff();
+
+    // Act
+    let response = app
+        .get_customers(&[("email", staff.get_email().as_ref())])
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let customers: serde_json::Value = response.json().await.unwrap();
+    assert!(customers.as_array().unwrap().is_empty());
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn search_without_criteria_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    // Act
+    let response = app.get_customers(&[("limit", "5")]).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn overview_shows_profile_and_open_alerts() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let customer = app.get_test_users().get_customer();
+    let csv = format!(
+        "id,name,aliases,date_of_birth\nPEP-1,{} {},,{}\n",
+        customer.get_first_name().as_ref(),
+        customer.get_last_name().as_ref(),
+        customer.get_date_of_birth()
+    );
+    let form = Form::new()
+        .text("source", "internal_pep")
+        .text("format", "simple_csv")
+        .text("kind", "pep")
+        .part(
+            "file",
+            Part::bytes(csv.into_bytes())
+                .file_name("pep.csv")
+                .mime_str("text/csv")
+                .unwrap(),
+        );
+    let response = app.post_watchlist(form).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app.get_customer_overview(*customer.get_id()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let overview: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(overview["profile"]["id"], customer.get_id().to_string());
+    assert!(overview["kyc_case"].is_null());
+    assert!(overview["accounts"].as_array().unwrap().is_empty());
+    assert!(overview["recent_entries"].as_array().unwrap().is_empty());
+    assert_eq!(overview["open_alerts"].as_array().unwrap().len(), 1);
+    assert_eq!(overview["open_alerts"][0]["external_ref"], "PEP-1");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn overview_balances_come_from_the_ledger() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 2_500, "transaction_ref": "CHQ-3601",
+                                "description": "Returned cheque"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .get_customer_overview(*app.get_test_users().get_customer().get_id())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let overview: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(overview["accounts"][0]["id"], account_id.to_string());
+    assert_eq!(overview["accounts"][0]["balance_cents"], -2_500);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn overview_of_a_staff_member_returns_404() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .get_customer_overview(*app.get_test_users().get_staff().get_id())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
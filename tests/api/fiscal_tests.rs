@@ -0,0 +1,170 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+async fn open_current_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+
+    app.open_account("CUR-FY", terms).await.id
+}
+
+fn adjustment(reference: &str, posted_on: NaiveDate) -> serde_json::Value {
+    serde_json::json!({"posted_on": posted_on, "transaction_ref": reference,
+                       "description": "Accrued audit fees", "debit_code": "5220",
+                       "credit_code": "2300", "amount_cents": 150_000})
+}
+
+#[actix_web::test]
+async fn closed_periods_reject_postings() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+    let today = Utc::now().date_naive();
+    let start_date = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap();
+
+    let response = app
+        .post_fiscal_year(&serde_json::json!({"start_date": start_date}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let year: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(year["name"], format!("FY{}", today.year()));
+    assert_eq!(year["periods"].as_array().unwrap().len(), 12);
+    let period = &year["periods"][today.month0() as usize];
+    let period_id = period["id"].as_str().unwrap();
+
+    let response = app
+        .post_fiscal_year(&serde_json::json!({"start_date": start_date, "name": "FY-OVERLAP"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    // Act
+    let response = app.put_fiscal_period_status(period_id, "soft_closed").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 2_500, "transaction_ref": "CHQ-2001",
+                                "description": "Returned cheque"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+    let response = app
+        .post_adjusting_entry(&adjustment("ADJ-2001", today))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.put_fiscal_period_status(period_id, "hard_closed").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_adjusting_entry(&adjustment("ADJ-2002", today))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+    let response = app.put_fiscal_period_status(period_id, "open").await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn year_end_close_sweeps_income_and_expenses_into_retained_earnings() {
+    // Arrange
+    let mut app = spawn_app().await;
+    open_current_account(&app).await;
+    let last_year = Utc::now().year() - 1;
+
+    let response = app
+        .post_fiscal_year(
+            &serde_json::json!({"start_date": NaiveDate::from_ymd_opt(last_year, 1, 1)}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let year: serde_json::Value = response.json().await.unwrap();
+    let year_id = year["id"].as_str().unwrap();
+
+    let response = app
+        .post_adjusting_entry(&adjustment(
+            "ADJ-1001",
+            NaiveDate::from_ymd_opt(last_year, 12, 31).unwrap(),
+        ))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_fiscal_year_close(year_id).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    for period in year["periods"].as_array().unwrap() {
+        let response = app
+            .put_fiscal_period_status(period["id"].as_str().unwrap(), "soft_closed")
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    // Act
+    let response = app.post_fiscal_year_close(year_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let closed: serde_json::Value = response.json().await.unwrap();
+    assert!(closed["closing_entry_id"].is_string());
+    assert!(
+        closed["periods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|p| p["status"] == "hard_closed")
+    );
+
+    let (reference, kind): (String, String) =
+        sqlx::query_as("SELECT transaction_ref, entry_kind::TEXT FROM journal_entry WHERE id = $1")
+            .bind(Uuid::parse_str(closed["closing_entry_id"].as_str().unwrap()).unwrap())
+            .fetch_one(&app.get_db_state().pg_pool)
+            .await
+            .unwrap();
+    assert_eq!(reference, format!("YEC-FY{}", last_year));
+    assert_eq!(kind, "closing");
+
+    let response = app.post_fiscal_year_close(year_id).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app.get_fiscal_years().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let years: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(years[0]["id"], year_id);
+    assert_eq!(years[0]["periods"].as_array().unwrap().len(), 12);
+
+    // The closed year still shows what it spent
+    let response = app
+        .get_income_statement(&format!("from={0}-01-01&to={0}-12-31", last_year))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let statement: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(statement["sections"][1]["name"], "Expenses");
+    assert_eq!(statement["sections"][1]["current_cents"], 150_000);
+    assert_eq!(statement["totals"][0]["current_cents"], -150_000);
+
+    app.clear_test_db().await;
+}
+
+#[tokio::test]
+async fn overlapping_years_created_at_once_are_refused() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.login_staff().await;
+    let start_date = NaiveDate::from_ymd_opt(Utc::now().year() - 5, 1, 1).unwrap();
+    let first = serde_json::json!({"start_date": start_date, "name": "FY-A"});
+    let second = serde_json::json!({"start_date": start_date, "name": "FY-B"});
+
+    // Act
+    let (first, second) = tokio::join!(app.post_fiscal_year(&first), app.post_fiscal_year(&second));
+
+    // Assert
+    let mut statuses = [first.status().as_u16(), second.status().as_u16()];
+    statuses.sort();
+    assert_eq!(statuses, [200, 409]);
+
+    app.clear_test_db().await;
+}
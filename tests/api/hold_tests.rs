@@ -0,0 +1,181 @@
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+// An active current account with a $500 arranged overdraft, so there is something to hold against
+async fn open_current_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-HOLD", terms).await;
+    app.activate_account(account.id).await;
+    app.arrange_overdraft(account.id, 50_000).await;
+
+    account.id
+}
+
+fn hold_body(amount_cents: i64, reference: &str) -> serde_json::Value {
+    serde_json::json!({"amount_cents": amount_cents, "reason": "Garnishment order",
+                       "reference": reference, "expires_at": Utc::now() + Duration::days(30)})
+}
+
+async fn list_holds(app: &TestApp, account_id: Uuid, include_released: bool) -> serde_json::Value {
+    let response = app.get_holds(account_id, include_released).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+#[actix_web::test]
+async fn holds_reduce_the_available_balance_until_released() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    // Act
+    let response = app
+        .post_hold(account_id, &hold_body(20_000, "GARN-114"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let hold: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(hold["source"], "manual");
+
+    let holds = list_holds(&app, account_id, false).await;
+    assert_eq!(holds["balance_cents"], 0);
+    assert_eq!(holds["held_cents"], 20_000);
+    assert_eq!(holds["available_cents"], 30_000);
+
+    let response = app
+        .post_hold(account_id, &hold_body(5_000, "GARN-114"))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let hold_id = hold["id"].as_str().unwrap();
+    let response = app.delete_hold(account_id, hold_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let holds = list_holds(&app, account_id, false).await;
+    assert_eq!(holds["available_cents"], 50_000);
+    assert_eq!(holds["holds"].as_array().unwrap().len(), 0);
+
+    let response = app.delete_hold(account_id, hold_id).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn card_authorizations_are_held_until_the_settlement_posts() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    let response = app
+        .post_api_key(&serde_json::json!({"name": "card-processor", "scopes": ["card:authorize"]}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let issued: serde_json::Value = response.json().await.unwrap();
+    let key = issued["key"].as_str().unwrap();
+
+    // Act
+    let approved = app
+        .post_card_authorization(
+            account_id,
+            key,
+            &serde_json::json!({"amount_cents": 40_000, "authorization_ref": "AUTH-839201",
+                                "merchant": "GRAND HOTEL"}),
+        )
+        .await;
+    let declined = app
+        .post_card_authorization(
+            account_id,
+            key,
+            &serde_json::json!({"amount_cents": 20_000, "authorization_ref": "AUTH-839202",
+                                "merchant": "ACME GROCERIES"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(approved.status().as_u16(), 200);
+    let approved: serde_json::Value = approved.json().await.unwrap();
+    assert_eq!(approved["available_cents"], 10_000);
+
+    assert_eq!(declined.status().as_u16(), 422);
+
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 38_500, "transaction_ref": "AUTH-839201",
+                                "description": "GRAND HOTEL"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let holds = list_holds(&app, account_id, true).await;
+    assert_eq!(holds["balance_cents"], -38_500);
+    assert_eq!(holds["held_cents"], 0);
+    assert_eq!(holds["available_cents"], 11_500);
+    assert_eq!(holds["holds"][0]["release_reason"], "settled");
+    assert!(holds["holds"][0]["journal_entry_id"].is_string());
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn expired_holds_stop_counting_and_are_released() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    let response = app
+        .post_hold(account_id, &hold_body(20_000, "GARN-114"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    sqlx::query(
+        "UPDATE account_hold SET expires_at = NOW() - INTERVAL '1 hour' WHERE account_id = $1",
+    )
+    .bind(account_id)
+    .execute(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let holds = list_holds(&app, account_id, true).await;
+
+    // Assert
+    assert_eq!(holds["held_cents"], 0);
+    assert_eq!(holds["available_cents"], 50_000);
+    assert_eq!(holds["holds"][0]["release_reason"], "expired");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn managers_cannot_see_holds_outside_their_branches() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+    let response = app
+        .post_hold(account_id, &hold_body(20_000, "GARN-207"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    sqlx::query("UPDATE tuser SET access_role = 'manager' WHERE id = $1")
+        .bind(app.get_test_users().get_staff().get_id())
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+    app.login_staff().await;
+
+    // Act
+    let response = app.get_holds(account_id, true).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,278 @@
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+// Two euro accounts for the customer, the second one closed. Returned with their IBANs.
+async fn open_accounts(app: &TestApp) -> Vec<(Uuid, String)> {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let accounts = app.open_accounts("CUR-IN", terms, 2).await;
+
+    // Accounts only open in the currency of their country code, euro has none
+    for (account, status) in accounts.iter().zip(["active", "closed"]) {
+        sqlx::query(
+            "UPDATE user_account SET currency = 'EUR', status = $2::user_account_status WHERE id = $1",
+        )
+        .bind(account.id)
+        .bind(status)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+    }
+
+    accounts.into_iter().map(|a| (a.id, a.iban)).collect()
+}
+
+fn camt054(message_id: &str, credits: &[(&str, &str, &str)]) -> String {
+    let details: String = credits
+        .iter()
+        .map(|(end_to_end_id, iban, amount)| {
+            format!(
+                r#"<TxDtls>
+            <Refs><EndToEndId>{}</EndToEndId></Refs>
+            <Amt Ccy="EUR">{}</Amt>
+            <RltdPties>
+              <Dbtr><Pty><Nm>ACME GmbH</Nm></Pty></Dbtr>
+              <CdtrAcct><Id><IBAN>{}</IBAN></Id></CdtrAcct>
+            </RltdPties>
+            <RmtInf><Ustrd>Invoice {}</Ustrd></RmtInf>
+          </TxDtls>"#,
+                end_to_end_id, amount, iban, end_to_end_id
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.054.001.08">
+  <BkToCstmrDbtCdtNtfctn>
+    <GrpHdr><MsgId>{}</MsgId><CreDtTm>2025-12-17T07:00:00Z</CreDtTm></GrpHdr>
+    <Ntfctn>
+      <Id>NTF-1</Id>
+      <Acct><Id><IBAN>DE02100100100006820101</IBAN></Id></Acct>
+      <Ntry>
+        <Amt Ccy="EUR">0.01</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>BOOK</Cd></Sts>
+        <ValDt><Dt>2025-12-17</Dt></ValDt>
+        <NtryDtls>{}</NtryDtls>
+      </Ntry>
+    </Ntfctn>
+  </BkToCstmrDbtCdtNtfctn>
+</Document>"#,
+        message_id, details
+    )
+}
+
+fn pacs008(end_to_end_id: &str, iban: &str, amount: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pacs.008.001.08">
+  <FIToFICstmrCdtTrf>
+    <GrpHdr><MsgId>PACS-1</MsgId><NbOfTxs>1</NbOfTxs><IntrBkSttlmDt>2025-12-18</IntrBkSttlmDt></GrpHdr>
+    <CdtTrfTxInf>
+      <PmtId><EndToEndId>{}</EndToEndId><TxId>PACS-TX-1</TxId></PmtId>
+      <IntrBkSttlmAmt Ccy="EUR">{}</IntrBkSttlmAmt>
+      <Dbtr><Nm>Jane Doe</Nm></Dbtr>
+      <CdtrAcct><Id><IBAN>{}</IBAN></Id></CdtrAcct>
+    </CdtTrfTxInf>
+  </FIToFICstmrCdtTrf>
+</Document>"#,
+        end_to_end_id, amount, iban
+    )
+}
+
+async fn balance_cents(app: &TestApp, account_id: Uuid) -> i64 {
+    let overdraft: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    overdraft["balance_cents"].as_i64().unwrap()
+}
+
+async fn repair_queue(app: &TestApp) -> Vec<serde_json::Value> {
+    let response = app.get_inbound_payments("repair").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+#[actix_web::test]
+async fn credits_are_posted_to_the_account_their_iban_names() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let accounts = open_accounts(&app).await;
+    let (account_id, iban) = &accounts[0];
+    let (_, closed_iban) = &accounts[1];
+
+    let inbound_dir = &app.get_storage_state().inbound_dir;
+    std::fs::create_dir_all(inbound_dir).unwrap();
+    // Senders often write the IBAN in groups of four
+    let spaced_iban = iban
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(4)
+        .map(|c| c.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ");
+    std::fs::write(
+        inbound_dir.join("01_camt054.xml"),
+        camt054(
+            "NTF-1",
+            &[
+                ("INV-7781", &spaced_iban, "250.00"),
+                ("INV-7782", "DE89370400440532013000", "40.00"),
+            ],
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        inbound_dir.join("02_pacs008.xml"),
+        pacs008("E2E-PACS-1", closed_iban, "99.99"),
+    )
+    .unwrap();
+    std::fs::write(inbound_dir.join("03_unknown.xml"), "<Document><Other/>").unwrap();
+
+    // Act
+    let response = app.post_inbound_import().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(summary["files_processed"], 2);
+    assert_eq!(summary["files_failed"], 1);
+    assert_eq!(summary["credits_posted"], 1);
+    assert_eq!(summary["credits_for_repair"], 2);
+    assert_eq!(summary["credits_skipped"], 0);
+
+    assert!(
+        inbound_dir
+            .join("processed")
+            .join("01_camt054.xml")
+            .exists()
+    );
+    assert!(inbound_dir.join("failed").join("03_unknown.xml").exists());
+
+    assert_eq!(balance_cents(&app, *account_id).await, 25_000);
+
+    let queue = repair_queue(&app).await;
+    assert_eq!(queue.len(), 2);
+    let closed = queue
+        .iter()
+        .find(|p| p["reference"] == "E2E-PACS-1")
+        .unwrap();
+    assert_eq!(closed["message_type"], "pacs.008");
+    assert_eq!(closed["repair_reason"], "account is closed");
+    let unknown = queue.iter().find(|p| p["reference"] == "INV-7782").unwrap();
+    assert_eq!(
+        unknown["repair_reason"],
+        "no account with IBAN DE89370400440532013000"
+    );
+
+    // The same file delivered twice changes nothing
+    std::fs::copy(
+        inbound_dir.join("processed").join("01_camt054.xml"),
+        inbound_dir.join("04_camt054.xml"),
+    )
+    .unwrap();
+    let summary: serde_json::Value = app.post_inbound_import().await.json().await.unwrap();
+    assert_eq!(summary["credits_posted"], 0);
+    assert_eq!(summary["credits_skipped"], 2);
+    assert_eq!(balance_cents(&app, *account_id).await, 25_000);
+    assert_eq!(repair_queue(&app).await.len(), 2);
+
+    std::fs::remove_dir_all(inbound_dir).unwrap();
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn repair_queue_credits_are_resolved_or_rejected() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let accounts = open_accounts(&app).await;
+    let (account_id, _) = &accounts[0];
+    let (closed_id, _) = &accounts[1];
+
+    let inbound_dir = &app.get_storage_state().inbound_dir;
+    std::fs::create_dir_all(inbound_dir).unwrap();
+    std::fs::write(
+        inbound_dir.join("camt054.xml"),
+        camt054(
+            "NTF-2",
+            &[
+                ("OLD-IBAN-1", "DE89370400440532013000", "75.00"),
+                ("OLD-IBAN-2", "DE89370400440532013000", "10.00"),
+            ],
+        ),
+    )
+    .unwrap();
+    let response = app.post_inbound_import().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let queue = repair_queue(&app).await;
+    let first = queue
+        .iter()
+        .find(|p| p["reference"] == "OLD-IBAN-1")
+        .unwrap();
+    let second = queue
+        .iter()
+        .find(|p| p["reference"] == "OLD-IBAN-2")
+        .unwrap();
+    let first_id = first["id"].as_str().unwrap();
+    let second_id = second["id"].as_str().unwrap();
+
+    // Act
+    let response = app
+        .post_inbound_resolution(
+            first_id,
+            "resolve",
+            &serde_json::json!({"account_id": closed_id}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app
+        .post_inbound_resolution(
+            first_id,
+            "resolve",
+            &serde_json::json!({"account_id": account_id, "note": "Sender used the old IBAN"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let resolved: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(resolved["status"], "resolved");
+    assert_eq!(resolved["account_id"], account_id.to_string());
+    assert!(resolved["journal_entry_id"].is_string());
+    assert_eq!(balance_cents(&app, *account_id).await, 7_500);
+
+    let response = app
+        .post_inbound_resolution(
+            first_id,
+            "resolve",
+            &serde_json::json!({"account_id": account_id}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app
+        .post_inbound_resolution(second_id, "reject", &serde_json::json!({"note": " "}))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .post_inbound_resolution(
+            second_id,
+            "reject",
+            &serde_json::json!({"note": "Unknown beneficiary, return to sender"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let rejected: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(rejected["status"], "rejected");
+    assert_eq!(balance_cents(&app, *account_id).await, 7_500);
+
+    assert!(repair_queue(&app).await.is_empty());
+
+    std::fs::remove_dir_all(inbound_dir).unwrap();
+    app.clear_test_db().await;
+}
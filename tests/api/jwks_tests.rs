@@ -0,0 +1,60 @@
+use crate::base::spawn_app;
+
+#[actix_web::test]
+async fn jwks_publishes_verification_keys_with_kid() {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    // Act
+    let response = app.get_jwks().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let jwks: serde_json::Value = response.json().await.unwrap();
+    let kids: Vec<&str> = jwks["keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|k| k["kid"].as_str().unwrap())
+        .collect();
+
+    assert!(kids.contains(&"access-k1"));
+    assert!(kids.contains(&"activate-k1"));
+    // Refresh keys are private to Thalia
+    assert!(!kids.contains(&"refresh-k1"));
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn login_issues_access_token_with_kid_header() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    let login_body = serde_json::json!({"login_id": {"email": app.get_test_users().get_staff().get_email().as_ref()},
+                                                "password": app.get_test_users().get_staff().get_password().as_ref()});
+
+    // Act
+    let response = app.post_staff_login(&login_body).await;
+
+    // Assert
+    let access_token = response
+        .headers()
+        .get(reqwest::header::AUTHORIZATION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .strip_prefix("Bearer ")
+        .unwrap()
+        .to_string();
+
+    let header = jsonwebtoken::decode_header(&access_token).unwrap();
+    assert_eq!(header.alg, jsonwebtoken::Algorithm::EdDSA);
+    assert_eq!(header.kid.as_deref(), Some("access-k1"));
+
+    app.clear_test_db().await;
+}
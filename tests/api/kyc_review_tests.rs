@@ -0,0 +1,244 @@
+use reqwest::multipart::{Form, Part};
+use uuid::Uuid;
+use wiremock::{
+    Mock, ResponseTemplate,
+    matchers::{method, path_regex},
+};
+
+use crate::base::{TestApp, spawn_app};
+
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+const PDF: &[u8] = b"%PDF-1.7\n%fake passport scan\n";
+
+// Customer uploads a passport and a selfie then submits them for review
+async fn submit_documents(app: &TestApp) -> Uuid {
+    Mock::given(method("PUT"))
+        .and(path_regex(format!(
+            "^/{}/kyc/.+$",
+            app.get_storage_state().bucket
+        )))
+        .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+
+    for (document_type, bytes, content_type) in [
+        ("passport", PDF, "application/pdf"),
+        ("selfie", PNG, "image/png"),
+    ] {
+        let form = Form::new().text("document_type", document_type).part(
+            "file",
+            Part::bytes(bytes)
+                .file_name("scan")
+                .mime_str(content_type)
+                .unwrap(),
+        );
+
+        let response = app.post_kyc_document(form).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.post_kyc_submit().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let case: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(case["status"], "submitted");
+
+    case["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn customer_is_verified(app: &TestApp) -> bool {
+    sqlx::query_scalar("SELECT is_verified FROM tuser WHERE id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn submitting_without_documents_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    // Act
+    let response = app.post_kyc_submit().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn approved_case_verifies_the_customer() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    let case_id = submit_documents(&app).await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+
+    let response = app.get_kyc_cases(Some("submitted")).await;
+    let cases: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(cases.as_array().unwrap().len(), 1);
+
+    // Act
+    let response = app
+        .post_kyc_review(case_id, &serde_json::json!({"decision": "start_review"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_kyc_review(case_id, &serde_json::json!({"decision": "approve"}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let case: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(case["status"], "approved");
+    assert_eq!(case["notes"].as_array().unwrap().len(), 3);
+    assert!(customer_is_verified(&app).await);
+
+    let accepted: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM kyc_document WHERE user_id = $1 AND status = 'accepted'",
+    )
+    .bind(app.get_test_users().get_customer().get_id())
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+    assert_eq!(accepted, 2);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn customer_resubmits_after_more_info_is_requested() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    let case_id = submit_documents(&app).await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+    app.post_kyc_review(case_id, &serde_json::json!({"decision": "start_review"}))
+        .await;
+    let response = app
+        .post_kyc_review(
+            case_id,
+            &serde_json::json!({"decision": "request_info", "note": "Selfie is blurry"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let status: serde_json::Value = app.get_kyc_status().await.json().await.unwrap();
+    assert_eq!(status["status"], "more_info_needed");
+    assert_eq!(status["reason"], "Selfie is blurry");
+
+    // Act
+    let response = app.post_kyc_submit().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let status: serde_json::Value = app.get_kyc_status().await.json().await.unwrap();
+    assert_eq!(status["status"], "submitted");
+    assert_eq!(status["is_verified"], false);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn rejecting_without_a_note_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    let case_id = submit_documents(&app).await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+    app.post_kyc_review(case_id, &serde_json::json!({"decision": "start_review"}))
+        .await;
+
+    // Act
+    let response = app
+        .post_kyc_review(case_id, &serde_json::json!({"decision": "reject"}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(!customer_is_verified(&app).await);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn approving_a_case_not_in_review_returns_409() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    let case_id = submit_documents(&app).await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+
+    // Act
+    let response = app
+        .post_kyc_review(case_id, &serde_json::json!({"decision": "approve"}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+    assert!(!customer_is_verified(&app).await);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unverified_customer_cannot_open_an_account() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.get_coas().store_coas(&app.get_db_state().pg_pool).await;
+    app.get_account_classes()
+        .store_account_classes(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let acc_body = serde_json::json!({ "user_id": app.get_test_users().get_customer().get_id(),
+                                       "branch_id": Uuid::now_v7(),
+                                       "coa_id": Uuid::now_v7(),
+                                       "account_class": app.get_account_classes().get_checking().get_id(),
+                                       "country_code": 840});
+
+    // Act
+    let response = app
+        .get_run_state()
+        .api_client
+        .post(format!("{}/customer/account", app.get_run_state().address))
+        .json(&acc_body)
+        .send()
+        .await
+        .expect("Failed to create user account");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,185 @@
+use reqwest::multipart::{Form, Part};
+use wiremock::{
+    Mock, ResponseTemplate,
+    matchers::{method, path_regex},
+};
+
+use crate::base::{TestApp, spawn_app};
+
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+const PDF: &[u8] = b"%PDF-1.7\n%fake passport scan\n";
+
+async fn mount_put_object(app: &TestApp, times: u64) {
+    Mock::given(method("PUT"))
+        .and(path_regex(format!(
+            "^/{}/kyc/{}/.+$",
+            app.get_storage_state().bucket,
+            app.get_test_users().get_customer().get_id()
+        )))
+        .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+        .expect(times)
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+}
+
+fn document_form(document_type: &str, bytes: &'static [u8], content_type: &str) -> Form {
+    Form::new()
+        .text("document_type", document_type.to_string())
+        .part(
+            "file",
+            Part::bytes(bytes)
+                .file_name("scan")
+                .mime_str(content_type)
+                .unwrap(),
+        )
+}
+
+#[actix_web::test]
+async fn customer_uploads_kyc_document_to_storage() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    mount_put_object(&app, 1).await;
+
+    // Act
+    let response = app
+        .post_kyc_document(document_form("passport", PNG, "image/png"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (document_type, content_type, size_bytes): (String, String, i64) = sqlx::query_as(
+        "SELECT document_type::TEXT, content_type, size_bytes FROM kyc_document WHERE user_id = $1",
+    )
+    .bind(app.get_test_users().get_customer().get_id())
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(document_type, "passport");
+    assert_eq!(content_type, "image/png");
+    assert_eq!(size_bytes, PNG.len() as i64);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn document_not_matching_its_content_type_is_rejected() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    mount_put_object(&app, 0).await;
+
+    // Act
+    let response = app
+        .post_kyc_document(document_form("passport", PDF, "image/png"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn selfie_as_pdf_is_rejected() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    mount_put_object(&app, 0).await;
+
+    // Act
+    let response = app
+        .post_kyc_document(document_form("selfie", PDF, "application/pdf"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn same_document_uploaded_twice_returns_409() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    mount_put_object(&app, 1).await;
+
+    app.post_kyc_document(document_form("proof_of_address", PDF, "application/pdf"))
+        .await;
+
+    // Act
+    let response = app
+        .post_kyc_document(document_form("proof_of_address", PDF, "application/pdf"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn staff_gets_short_lived_links_to_customer_documents() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+    mount_put_object(&app, 1).await;
+
+    app.post_kyc_document(document_form("passport", PNG, "image/png"))
+        .await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+
+    // Act
+    let response = app
+        .get_customer_kyc_documents(*app.get_test_users().get_customer().get_id())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let documents: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(documents.len(), 1);
+
+    let download_url = documents[0]["download_url"].as_str().unwrap();
+    assert!(download_url.starts_with(&app.get_storage_state().s3_server.uri()));
+    assert!(download_url.contains("X-Amz-Signature="));
+    assert!(download_url.contains("X-Amz-Expires=300"));
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unauthenticated_upload_returns_401() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_put_object(&app, 0).await;
+
+    // Act
+    let response = app
+        .post_kyc_document(document_form("passport", PNG, "image/png"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
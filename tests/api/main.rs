@@ -1,6 +1,29 @@
 mod account_tests;
+mod ach_tests;
+mod activation_tests;
+mod api_key_tests;
 mod base;
+mod beneficiary_tests;
+mod branch_tests;
 mod coa_tests;
+mod customer_search_tests;
+mod fiscal_tests;
 mod health_tests;
+mod hold_tests;
+mod inbound_payment_tests;
+mod jwks_tests;
+mod kyc_review_tests;
+mod kyc_tests;
 mod login_tests;
+mod nostro_tests;
+mod overdraft_tests;
+mod product_tests;
+mod profile_tests;
+mod scheduled_payment_tests;
+mod screening_tests;
+mod sepa_tests;
+mod session_tests;
 mod signup_tests;
+mod sso_tests;
+mod statement_tests;
+mod swift_tests;
@@ -0,0 +1,110 @@
+use crate::base::{TestApp, spawn_app};
+
+// An active dollar account for inbound SWIFT payments to be credited to
+async fn open_account(app: &TestApp) -> String {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-NOS", terms).await;
+    app.activate_account(account.id).await;
+
+    account.iban
+}
+
+fn inbound_mt103(reference: &str, iban: &str, amount: &str) -> String {
+    format!(
+        "{{1:F01THALUS33AXXX0000000000}}{{2:O1031015251219BARCGB22AXXX00000000002512191015N}}{{4:\r\n:20:{}\r\n:23B:CRED\r\n:32A:251219USD{}\r\n:50K:/GB29NWBK60161331926819\r\nACME EXPORTS LTD\r\n:59:/{}\r\nCUSTOMER\r\n:71A:SHA\r\n-}}",
+        reference, amount, iban
+    )
+}
+
+// What our correspondent shows for the two payments, the second less 5.00 of its charges
+fn statement(reference: &str) -> String {
+    let today = chrono::Utc::now().date_naive().format("%y%m%d");
+    format!(
+        ":20:{}\r\n:25:400123456\r\n:28C:00245/001\r\n:60F:C{}USD0,00\r\n:61:{}C1500,00NTRFPAY7781//CHS-88120\r\n:86:ACME EXPORTS LTD\r\n:61:{}C35,00NTRFNONREF//CHS-88121\r\n:62F:C{}USD1535,00\r\n-",
+        reference, today, today, today, today
+    )
+}
+
+#[actix_web::test]
+async fn statements_are_matched_by_reference_and_exceptions_by_hand() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let iban = open_account(&app).await;
+
+    let response = app
+        .post_swift_inbound(&inbound_mt103("PAY7781", &iban, "1500,00"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_swift_inbound(&inbound_mt103("PAY7782", &iban, "40,00"))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app.post_nostro_statement(&statement("STMT1")).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let imported: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(imported["format"], "mt940");
+    assert_eq!(imported["entry_count"], 2);
+    assert_eq!(imported["matched_count"], 1);
+
+    let response = app.post_nostro_statement(&statement("STMT1")).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let broken = statement("STMT2").replace(":62F:C", ":62F:D");
+    let response = app.post_nostro_statement(&broken).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let matched: serde_json::Value = app.get_nostro_items("matched").await.json().await.unwrap();
+    assert_eq!(matched[0]["reference"], "PAY7781");
+    assert_eq!(matched[0]["match_rule"], "reference");
+
+    let unmatched: serde_json::Value = app
+        .get_nostro_items("unmatched")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(unmatched.as_array().unwrap().len(), 1);
+    let item_id = unmatched[0]["id"].as_str().unwrap();
+
+    let lines: serde_json::Value = app.get_nostro_ledger_lines().await.json().await.unwrap();
+    assert_eq!(lines.as_array().unwrap().len(), 1);
+    assert_eq!(lines[0]["amount_cents"], 4_000);
+    assert_eq!(lines[0]["direction"], "credit");
+
+    let later = (chrono::Utc::now().date_naive() + chrono::Days::new(10)).to_string();
+    let aging: serde_json::Value = app.get_nostro_aging(&later).await.json().await.unwrap();
+    assert_eq!(aging[0]["currency"], "USD");
+    assert_eq!(aging[0]["statement_items"][1]["days"], "8-30");
+    assert_eq!(aging[0]["statement_items"][1]["amount_cents"], 3_500);
+    assert_eq!(aging[0]["ledger_lines"][1]["amount_cents"], 4_000);
+
+    let response = app
+        .post_nostro_match(
+            item_id,
+            &serde_json::json!({"journal_line_id": lines[0]["journal_line_id"]}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app
+        .post_nostro_match(
+            item_id,
+            &serde_json::json!({"journal_line_id": lines[0]["journal_line_id"],
+                                "note": "Correspondent deducted 5.00 charges"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let item: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(item["status"], "matched");
+    assert_eq!(item["match_rule"], "manual");
+
+    let lines: serde_json::Value = app.get_nostro_ledger_lines().await.json().await.unwrap();
+    assert!(lines.as_array().unwrap().is_empty());
+
+    app.clear_test_db().await;
+}
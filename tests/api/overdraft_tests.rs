@@ -0,0 +1,180 @@
+use chrono::{Datelike, Utc};
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+// A current account opened on a product allowing $1,000 overdrawn at 36.5% with a $25 unarranged fee
+async fn open_current_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "fees": [{"code": "unarranged_overdraft",
+                                             "amount_cents": 2_500, "frequency": "per_transaction"}],
+                                   "overdraft_limit_cents": 100_000, "debit_rate_bps": 3_650,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+
+    app.open_account("CUR-OD", terms).await.id
+}
+
+fn facility(limit_cents: i64) -> serde_json::Value {
+    let expires_on = Utc::now().date_naive() + chrono::Days::new(180);
+    serde_json::json!({"limit_cents": limit_cents, "expires_on": expires_on})
+}
+
+#[actix_web::test]
+async fn granted_limit_counts_towards_the_available_balance() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    let response = app.put_overdraft(account_id, &facility(150_000)).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    // Act
+    let response = app.put_overdraft(account_id, &facility(50_000)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_overdraft(account_id).await;
+    let overdraft: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(overdraft["balance_cents"], 0);
+    assert_eq!(overdraft["available_cents"], 50_000);
+    assert_eq!(overdraft["facility"]["is_active"], true);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn forced_debits_past_the_limit_are_charged_the_unarranged_fee() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    let response = app.put_overdraft(account_id, &facility(50_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let within = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CARD-1001",
+                                "description": "Card settlement"}),
+        )
+        .await;
+    let beyond = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CHQ-1002",
+                                "description": "Returned cheque"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(within.status().as_u16(), 200);
+    let within: serde_json::Value = within.json().await.unwrap();
+    assert_eq!(within["unarranged_fee_cents"], serde_json::Value::Null);
+    assert_eq!(within["available_cents"], 20_000);
+
+    assert_eq!(beyond.status().as_u16(), 200);
+    let beyond: serde_json::Value = beyond.json().await.unwrap();
+    assert_eq!(beyond["unarranged_fee_cents"], 2_500);
+    assert_eq!(beyond["balance_cents"], -62_500);
+    assert_eq!(beyond["available_cents"], -12_500);
+
+    let response = app.get_overdraft_utilization().await;
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report[0]["used_cents"], 62_500);
+    assert_eq!(report[0]["utilization_bps"], 12_500);
+    assert_eq!(report[0]["is_exceeded"], true);
+
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 100, "transaction_ref": "CHQ-1002",
+                                "description": "Returned cheque"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn month_end_accrual_charges_debit_interest() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    let response = app.put_overdraft(account_id, &facility(50_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CARD-1001",
+                                "description": "Card settlement"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Move the account's history to the last day of last month
+    let month_end = Utc::now()
+        .date_naive()
+        .with_day(1)
+        .unwrap()
+        .pred_opt()
+        .unwrap();
+    sqlx::query("UPDATE journal_entry SET created_date = $1 WHERE user_account_id = $2")
+        .bind(month_end.and_hms_opt(12, 0, 0).unwrap())
+        .bind(account_id)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = app
+        .post_overdraft_accruals(&serde_json::json!({"accrual_date": month_end}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let run: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(run["accounts_accrued"], 1);
+    assert_eq!(run["accounts_charged"], 1);
+    // One day overdrawn by $300 at 36.5% a year
+    assert_eq!(run["interest_charged_cents"], 30);
+
+    let response = app.get_overdraft(account_id).await;
+    let overdraft: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(overdraft["balance_cents"], -30_030);
+
+    let response = app
+        .post_overdraft_accruals(&serde_json::json!({"accrual_date": month_end}))
+        .await;
+    let run: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(run["accounts_accrued"], 0);
+    assert_eq!(run["accounts_charged"], 0);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn managers_cannot_grant_overdrafts_outside_their_branches() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_current_account(&app).await;
+
+    sqlx::query("UPDATE tuser SET access_role = 'manager' WHERE id = $1")
+        .bind(app.get_test_users().get_staff().get_id())
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+    app.login_staff().await;
+
+    // Act
+    let response = app.put_overdraft(account_id, &facility(50_000)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,242 @@
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+async fn store_fixtures(app: &TestApp) {
+    let pool = &app.get_db_state().pg_pool;
+    app.get_test_users().store_test_users(pool).await;
+    app.get_test_users().verify_customer(pool).await;
+    app.get_coas().store_coas(pool).await;
+    app.get_branches().store_branches(pool).await;
+}
+
+fn product_body(app: &TestApp, terms: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({"code": "sav-plus", "kind": "deposit", "name": "Savings Plus",
+                       "description": "Tiered savings account",
+                       "coa_id": app.get_coas().get_store().get("2020").unwrap().get_id(),
+                       "terms": terms})
+}
+
+fn terms(currencies: &[&str], max_age: Option<i16>) -> serde_json::Value {
+    serde_json::json!({"currencies": currencies, "min_age": 18, "max_age": max_age,
+                       "min_kyc_level": "verified",
+                       "interest_tiers": [{"min_balance_cents": 0, "rate_bps": 50},
+                                          {"min_balance_cents": 1_000_000, "rate_bps": 150}],
+                       "fees": [{"code": "maintenance", "amount_cents": 500, "frequency": "monthly"}],
+                       "overdraft_limit_cents": 0, "dormancy_days": 365,
+                       "statement_frequency": "monthly"})
+}
+
+fn account_body(app: &TestApp, product_id: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({"user_id": app.get_test_users().get_customer().get_id(),
+                       "branch_id": app.get_branches().get_head_office().id,
+                       "coa_id": Uuid::now_v7(),
+                       "account_class": product_id,
+                       "country_code": 840})
+}
+
+async fn create_product(app: &TestApp, terms: serde_json::Value) -> serde_json::Value {
+    let response = app.post_product(&product_body(app, terms)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+#[actix_web::test]
+async fn superuser_creates_a_product_with_its_first_version() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    // Act
+    let created = create_product(&app, terms(&["usd", "EUR"], None)).await;
+
+    // Assert
+    assert_eq!(created["code"], "SAV-PLUS");
+    assert_eq!(created["current"]["version"], 1);
+    assert_eq!(
+        created["current"]["currencies"],
+        serde_json::json!(["USD", "EUR"])
+    );
+
+    let response = app.get_product(created["id"].as_str().unwrap()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let fetched: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(fetched["current"]["interest_tiers"][1]["rate_bps"], 150);
+    assert_eq!(fetched["current"]["fees"][0]["frequency"], "monthly");
+
+    let response = app
+        .post_product(&product_body(&app, terms(&["USD"], None)))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn existing_accounts_keep_the_version_they_were_opened_on() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    let created = create_product(&app, terms(&["USD"], None)).await;
+    let product_id = created["id"].as_str().unwrap();
+
+    let response = app
+        .post_staff_account(&account_body(&app, &created["id"]))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .put_product(
+            product_id,
+            &serde_json::json!({"terms": terms(&["USD", "EUR"], None)}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let updated: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(updated["current"]["version"], 2);
+
+    let response = app.get_product_versions(product_id).await;
+    let versions: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(versions.as_array().unwrap().len(), 2);
+
+    let pinned: Option<Uuid> =
+        sqlx::query_scalar("SELECT product_version_id FROM user_account WHERE user_id = $1")
+            .bind(app.get_test_users().get_customer().get_id())
+            .fetch_one(&app.get_db_state().pg_pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        pinned.unwrap().to_string(),
+        created["current"]["id"].as_str().unwrap()
+    );
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn managers_cannot_create_products() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    sqlx::query("UPDATE tuser SET access_role = 'manager' WHERE id = $1")
+        .bind(app.get_test_users().get_staff().get_id())
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_product(&product_body(&app, terms(&["USD"], None)))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn product_with_an_unknown_currency_returns_400() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_product(&product_body(&app, terms(&["XYZ"], None)))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn retired_products_cannot_be_opened() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    let created = create_product(&app, terms(&["USD"], None)).await;
+
+    let response = app.delete_product(created["id"].as_str().unwrap()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .post_staff_account(&account_body(&app, &created["id"]))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn ineligible_customers_are_refused_with_422() {
+    // Arrange
+    let mut app = spawn_app().await;
+    store_fixtures(&app).await;
+    app.login_staff().await;
+
+    // The test customer is in their thirties and their account would be in USD
+    let youth = create_product(&app, terms(&["USD"], Some(25))).await;
+
+    let mut body = product_body(&app, terms(&["EUR"], None));
+    body["code"] = "SAV-EUR".into();
+    let response = app.post_product(&body).await;
+    let euro: serde_json::Value = response.json().await.unwrap();
+
+    // Act
+    let too_old = app
+        .post_staff_account(&account_body(&app, &youth["id"]))
+        .await;
+    let wrong_currency = app
+        .post_staff_account(&account_body(&app, &euro["id"]))
+        .await;
+
+    // Assert
+    assert_eq!(too_old.status().as_u16(), 422);
+    assert_eq!(wrong_currency.status().as_u16(), 422);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn products_cannot_waive_the_kyc_policy() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let pool = &app.get_db_state().pg_pool;
+    app.get_test_users().store_test_users(pool).await;
+    app.get_coas().store_coas(pool).await;
+    app.get_branches().store_branches(pool).await;
+    app.login_staff().await;
+
+    let mut lenient = terms(&["USD"], None);
+    lenient["min_kyc_level"] = "none".into();
+    let product = create_product(&app, lenient).await;
+
+    // Act
+    let response = app
+        .post_staff_account(&account_body(&app, &product["id"]))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clear_test_db().await;
+}
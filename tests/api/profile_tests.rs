@@ -0,0 +1,219 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+async fn mount_email_server(app: &TestApp) {
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+}
+
+fn address(line1: &str) -> serde_json::Value {
+    serde_json::json!({"kind": "residential", "line1": line1, "city": "Berlin",
+                       "postal_code": "10115", "country_code": "de"})
+}
+
+#[actix_web::test]
+async fn new_address_supersedes_the_current_one() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let response = app.put_address(&address("Invalidenstrasse 1")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app.put_address(&address("Chausseestrasse 2")).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let profile: serde_json::Value = app.get_profile().await.json().await.unwrap();
+    let addresses = profile["addresses"].as_array().unwrap();
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(addresses[0]["line1"], "Chausseestrasse 2");
+    assert_eq!(addresses[0]["country_code"], "DE");
+
+    let history: serde_json::Value = app.get_profile_history().await.json().await.unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 2);
+    assert_eq!(history[0]["field"], "residential_address");
+    assert_eq!(
+        history[0]["old_value"],
+        "Invalidenstrasse 1, Berlin, 10115, DE"
+    );
+    assert_eq!(history[0]["reason"], "customer_request");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn phone_numbers_must_be_international() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let response = app
+        .put_phone(&serde_json::json!({"kind": "mobile", "number": "0151 1234567"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    // Act
+    let response = app
+        .put_phone(&serde_json::json!({"kind": "mobile", "number": "+49 151 1234567"}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let phone: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(phone["number"], "+491511234567");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn email_is_only_swapped_once_the_new_address_is_confirmed() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    mount_email_server(&app).await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let old_email = app.get_test_users().get_customer().get_email().as_ref();
+
+    let response = app
+        .post_email_change(&serde_json::json!({"email": "moved@example.com"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let profile: serde_json::Value = app.get_profile().await.json().await.unwrap();
+    assert_eq!(profile["email"], old_email);
+    assert_eq!(profile["pending_email"], "moved@example.com");
+
+    let confirmation_path = app.email_change_paths().await.pop().unwrap();
+
+    // Act
+    let response = app.get_confirmation(&confirmation_path).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let profile: serde_json::Value = app.get_profile().await.json().await.unwrap();
+    assert_eq!(profile["email"], "moved@example.com");
+    assert!(profile["pending_email"].is_null());
+
+    let history: serde_json::Value = app.get_profile_history().await.json().await.unwrap();
+    assert_eq!(history[0]["field"], "email");
+    assert_eq!(history[0]["old_value"], old_email);
+
+    let response = app.get_confirmation(&confirmation_path).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn email_change_to_an_address_in_use_returns_409() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    mount_email_server(&app).await;
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    let staff_email = app.get_test_users().get_staff().get_email().as_ref();
+
+    // Act
+    let response = app
+        .post_email_change(&serde_json::json!({"email": staff_email}))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+    assert!(app.email_change_paths().await.is_empty());
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn staff_name_changes_need_a_reason_and_are_recorded() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_staff(), true).await;
+
+    let customer_id = *app.get_test_users().get_customer().get_id();
+    let old_last_name = app.get_test_users().get_customer().get_last_name().as_ref();
+
+    let response = app
+        .put_customer_profile(
+            customer_id,
+            &serde_json::json!({"last_name": "Lovelace", "reason": "because"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    // Act
+    let response = app
+        .put_customer_profile(
+            customer_id,
+            &serde_json::json!({"last_name": "Lovelace", "reason": "marriage"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let profile: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(profile["last_name"], "Lovelace");
+
+    let response = app.get_customer_profile_history(customer_id).await;
+    let history: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(history[0]["field"], "last_name");
+    assert_eq!(history[0]["old_value"], old_last_name);
+    assert_eq!(history[0]["reason"], "marriage");
+    assert_eq!(
+        history[0]["changed_by"],
+        app.get_test_users().get_staff().get_id().to_string()
+    );
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn staff_cannot_update_a_staff_profile() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login(app.get_test_users().get_staff(), true).await;
+
+    // Act
+    let response = app
+        .put_customer_profile(
+            *app.get_test_users().get_staff().get_id(),
+            &serde_json::json!({"first_name": "Grace", "reason": "data_correction"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,358 @@
+use chrono::{Months, Utc};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+// Two active current accounts for the customer, returned with the second's account number
+async fn open_accounts(app: &TestApp) -> (Uuid, Uuid, String) {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let accounts = app.open_accounts("CUR-SP", terms, 2).await;
+    for account in &accounts {
+        app.activate_account(account.id).await;
+    }
+
+    (
+        accounts[0].id,
+        accounts[1].id,
+        accounts[1].account_number.clone(),
+    )
+}
+
+async fn schedule_payment(
+    app: &TestApp,
+    account_id: Uuid,
+    destination: &str,
+    frequency: Option<&str>,
+) -> String {
+    let body = serde_json::json!({"account_id": account_id, "destination_account_number": destination,
+                                  "amount_cents": 20_000, "reference": "RENT FLAT 2",
+                                  "frequency": frequency, "start_date": Utc::now().date_naive()});
+    let response = app.post_scheduled_payment(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let payment: serde_json::Value = response.json().await.unwrap();
+    payment["id"].as_str().unwrap().to_string()
+}
+
+async fn run_scheduler(app: &TestApp) -> serde_json::Value {
+    let response = app.post_scheduler_run().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+#[actix_web::test]
+async fn standing_orders_are_paid_once_per_due_date() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, destination_id, destination) = open_accounts(&app).await;
+
+    let expires_on = Utc::now().date_naive() + chrono::Days::new(180);
+    let response = app
+        .put_overdraft(
+            account_id,
+            &serde_json::json!({"limit_cents": 50_000, "expires_on": expires_on}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment_id = schedule_payment(&app, account_id, &destination, Some("monthly")).await;
+
+    // Act
+    app.login(app.get_test_users().get_staff(), true).await;
+    let first_run = run_scheduler(&app).await;
+    let second_run = run_scheduler(&app).await;
+
+    // Assert
+    assert_eq!(first_run["executions_scheduled"], 1);
+    assert_eq!(first_run["succeeded"], 1);
+    assert_eq!(second_run["executions_scheduled"], 0);
+    assert_eq!(second_run["succeeded"], 0);
+
+    let source: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    let target: serde_json::Value = app
+        .get_overdraft(destination_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(source["balance_cents"], -20_000);
+    assert_eq!(target["balance_cents"], 20_000);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment: serde_json::Value = app
+        .get_scheduled_payment(&payment_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let next_due = Utc::now().date_naive() + Months::new(1);
+    assert_eq!(payment["next_due_date"], next_due.to_string());
+    assert_eq!(payment["executions"].as_array().unwrap().len(), 1);
+    assert_eq!(payment["executions"][0]["status"], "succeeded");
+    assert_eq!(payment["executions"][0]["attempts"], 1);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn payments_short_of_funds_are_retried_then_given_up_on() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, _, destination) = open_accounts(&app).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment_id = schedule_payment(&app, account_id, &destination, None).await;
+
+    // Act
+    app.login(app.get_test_users().get_staff(), true).await;
+    let mut runs = vec![];
+    for _ in 0..3 {
+        runs.push(run_scheduler(&app).await);
+        sqlx::query("UPDATE scheduled_payment_execution SET next_attempt_at = now()")
+            .execute(&app.get_db_state().pg_pool)
+            .await
+            .unwrap();
+    }
+
+    // Assert
+    assert_eq!(runs[0]["retrying"], 1);
+    assert_eq!(runs[1]["retrying"], 1);
+    assert_eq!(runs[2]["failed"], 1);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment: serde_json::Value = app
+        .get_scheduled_payment(&payment_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(payment["status"], "completed");
+    assert_eq!(payment["executions"][0]["status"], "failed");
+    assert_eq!(payment["executions"][0]["attempts"], 3);
+    assert_eq!(
+        payment["executions"][0]["last_error"],
+        "insufficient available funds"
+    );
+
+    let emails = app
+        .get_mail_state()
+        .email_server
+        .received_requests()
+        .await
+        .unwrap();
+    assert!(emails.iter().any(|r| {
+        String::from_utf8_lossy(&r.body).contains("Your scheduled payment was not made")
+    }));
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn cancelled_payments_are_not_executed() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, _, destination) = open_accounts(&app).await;
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment_id = schedule_payment(&app, account_id, &destination, Some("weekly")).await;
+
+    // Act
+    let response = app.delete_scheduled_payment(&payment_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.delete_scheduled_payment(&payment_id).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app
+        .post_scheduled_payment(
+            &serde_json::json!({"account_id": account_id, "destination_account_number": destination,
+                                "amount_cents": 20_000, "reference": "RENT FLAT 2",
+                                "start_date": Utc::now().date_naive() - chrono::Days::new(1)}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.login(app.get_test_users().get_staff(), true).await;
+    let run = run_scheduler(&app).await;
+    assert_eq!(run["executions_scheduled"], 0);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn payments_above_the_kyc_limit_fail_for_unverified_customers() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, _, destination) = open_accounts(&app).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let body = serde_json::json!({"account_id": account_id, "destination_account_number": destination,
+                                  "amount_cents": 150_000, "reference": "CAR DEPOSIT",
+                                  "start_date": Utc::now().date_naive()});
+    let response = app.post_scheduled_payment(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let payment: serde_json::Value = response.json().await.unwrap();
+
+    sqlx::query("UPDATE tuser SET is_verified = false WHERE id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Act
+    app.login(app.get_test_users().get_staff(), true).await;
+    let run = run_scheduler(&app).await;
+
+    // Assert
+    assert_eq!(run["failed"], 1);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let payment: serde_json::Value = app
+        .get_scheduled_payment(payment["id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(payment["executions"][0]["status"], "failed");
+    assert_eq!(payment["executions"][0]["attempts"], 1);
+    assert_eq!(
+        payment["executions"][0]["last_error"],
+        "the amount needs a verified customer identity"
+    );
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn standing_orders_to_a_beneficiary_are_queued_as_sepa_transfers() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, _, destination) = open_accounts(&app).await;
+    sqlx::query("UPDATE user_account SET currency = 'EUR' WHERE id = $1")
+        .bind(account_id)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    let expires_on = Utc::now().date_naive() + chrono::Days::new(180);
+    let response = app
+        .put_overdraft(
+            account_id,
+            &serde_json::json!({"limit_cents": 50_000, "expires_on": expires_on}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let response = app.post_beneficiary_challenge().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let challenge: serde_json::Value = response.json().await.unwrap();
+    let code = app.verification_codes().await.pop().unwrap();
+    let response = app
+        .post_beneficiary(&serde_json::json!({"name": "Jürgen Müller", "iban": "DE89 3704 0044 0532 0130 00",
+                                              "bic": "COBADEFFXXX", "currency": "EUR",
+                                              "challenge_id": challenge["challenge_id"], "code": code}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary: serde_json::Value = response.json().await.unwrap();
+
+    let payment = |amount_cents: i64| {
+        serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary["id"],
+                           "amount_cents": amount_cents, "reference": "RENT FLAT 2",
+                           "frequency": "monthly", "start_date": Utc::now().date_naive()})
+    };
+    let response = app.post_scheduled_payment(&payment(20_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let scheduled: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(scheduled["beneficiary_id"], beneficiary["id"]);
+    assert!(scheduled["destination_account_number"].is_null());
+
+    // Still cooling off when it falls due
+    let response = app.post_scheduled_payment(&payment(150_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let large: serde_json::Value = response.json().await.unwrap();
+
+    let response = app
+        .post_scheduled_payment(&serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary["id"],
+                                                    "destination_account_number": destination,
+                                                    "amount_cents": 20_000, "reference": "RENT FLAT 2",
+                                                    "start_date": Utc::now().date_naive()}))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    // Act
+    app.login(app.get_test_users().get_staff(), true).await;
+    let run = run_scheduler(&app).await;
+
+    // Assert
+    assert_eq!(run["succeeded"], 1);
+    assert_eq!(run["failed"], 1);
+
+    let (amount_cents, remittance_info, status, transaction_ref, execution_key): (
+        i64,
+        String,
+        String,
+        String,
+        String,
+    ) = sqlx::query_as(
+        "SELECT t.amount_cents, t.remittance_info, t.status::TEXT, j.transaction_ref, x.execution_key
+         FROM sepa_transfer t
+         JOIN journal_entry j ON j.id = t.journal_entry_id
+         JOIN scheduled_payment_execution x ON x.journal_entry_id = t.journal_entry_id
+         WHERE t.account_id = $1",
+    )
+    .bind(account_id)
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+    assert_eq!(amount_cents, 20_000);
+    assert_eq!(remittance_info, "RENT FLAT 2");
+    assert_eq!(status, "queued");
+    assert_eq!(transaction_ref, execution_key);
+
+    let source: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    assert_eq!(source["balance_cents"], -20_000);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let large: serde_json::Value = app
+        .get_scheduled_payment(large["id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(large["executions"][0]["status"], "failed");
+    assert!(
+        large["executions"][0]["last_error"]
+            .as_str()
+            .unwrap()
+            .starts_with("new beneficiaries can't be sent more than")
+    );
+
+    app.clear_test_db().await;
+}
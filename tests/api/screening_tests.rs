@@ -0,0 +1,217 @@
+use reqwest::multipart::{Form, Part};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+fn watchlist_form(csv: String) -> Form {
+    Form::new()
+        .text("source", "internal_pep")
+        .text("format", "simple_csv")
+        .text("kind", "pep")
+        .part(
+            "file",
+            Part::bytes(csv.into_bytes())
+                .file_name("pep.csv")
+                .mime_str("text/csv")
+                .unwrap(),
+        )
+}
+
+// A list naming the stored test customer with their date of birth
+fn listing_test_customer(app: &TestApp) -> String {
+    let customer = app.get_test_users().get_customer();
+    format!(
+        "id,name,aliases,date_of_birth\nPEP-1,{} {},,{}\nPEP-2,Nobody Inparticular,,1950\n",
+        customer.get_first_name().as_ref(),
+        customer.get_last_name().as_ref(),
+        customer.get_date_of_birth()
+    )
+}
+
+async fn open_hit_ids(app: &TestApp) -> Vec<Uuid> {
+    let response = app.get_screening_hits(Some("open")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let hits: serde_json::Value = response.json().await.unwrap();
+    hits.as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[actix_web::test]
+async fn loading_a_list_screens_existing_customers() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    // Act
+    let response = app
+        .post_watchlist(watchlist_form(listing_test_customer(&app)))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let loaded: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(loaded["version"]["entry_count"], 2);
+    assert_eq!(loaded["customers_screened"], 1);
+    assert_eq!(loaded["hits_opened"], 1);
+
+    let response = app.get_screening_hits(None).await;
+    let hits: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(hits[0]["external_ref"], "PEP-1");
+    assert_eq!(hits[0]["dob_match"], "exact");
+    assert_eq!(
+        hits[0]["user_id"],
+        app.get_test_users().get_customer().get_id().to_string()
+    );
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn reloading_the_same_list_returns_409() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let response = app
+        .post_watchlist(watchlist_form(listing_test_customer(&app)))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .post_watchlist(watchlist_form(listing_test_customer(&app)))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unlisted_customers_are_not_raised() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let csv = "id,name,aliases,date_of_birth\nPEP-2,Nobody Inparticular,,1950\n".to_string();
+
+    // Act
+    let response = app.post_watchlist(watchlist_form(csv)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let loaded: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(loaded["hits_opened"], 0);
+    assert!(open_hit_ids(&app).await.is_empty());
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn reviewing_a_hit_requires_a_note_and_decides_it_once() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    app.post_watchlist(watchlist_form(listing_test_customer(&app)))
+        .await;
+    let hit_id = open_hit_ids(&app).await[0];
+
+    let response = app
+        .post_screening_review(
+            hit_id,
+            &serde_json::json!({"decision": "dismiss", "note": "  "}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    // Act
+    let response = app
+        .post_screening_review(
+            hit_id,
+            &serde_json::json!({"decision": "dismiss", "note": "Different person, passport checked"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let hit: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(hit["status"], "dismissed");
+    assert_eq!(
+        hit["reviewer_id"],
+        app.get_test_users().get_staff().get_id().to_string()
+    );
+    assert!(open_hit_ids(&app).await.is_empty());
+
+    let response = app
+        .post_screening_review(
+            hit_id,
+            &serde_json::json!({"decision": "confirm", "note": "Second opinion"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn signup_of_a_listed_customer_opens_a_hit() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+    app.login_staff().await;
+
+    let csv = "id,name,aliases,date_of_birth\nPEP-9,Vladislav Kozhemyakin,,1994\n".to_string();
+    let response = app.post_watchlist(watchlist_form(csv)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    let mut customer_body = app.customer_to_json();
+    customer_body["first_name"] = "Vladislav".into();
+    customer_body["last_name"] = "Kozhemyakin".into();
+    customer_body["username"] = "vkozhemyakin".into();
+    customer_body["email"] = "v.kozhemyakin@example.com".into();
+
+    // Act
+    let response = app.post_customer_signup(&customer_body).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_screening_hits(Some("open")).await;
+    let hits: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+    assert_eq!(hits[0]["external_ref"], "PEP-9");
+    assert_eq!(hits[0]["dob_match"], "year");
+
+    app.clear_test_db().await;
+}
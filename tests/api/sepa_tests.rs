@@ -0,0 +1,306 @@
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+// An active account with a 500.00 overdraft, held in the given currency
+async fn open_account(app: &TestApp, currency: &str) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD", "EUR"], "min_kyc_level": "verified",
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-SEPA", terms).await;
+    app.activate_account(account.id).await;
+
+    // Accounts only open in the currency of their country code, euro has none
+    sqlx::query("UPDATE user_account SET currency = $2 WHERE id = $1")
+        .bind(account.id)
+        .bind(currency)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    app.arrange_overdraft(account.id, 50_000).await;
+
+    account.id
+}
+
+async fn add_beneficiary(app: &TestApp) -> Uuid {
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    let response = app.post_beneficiary_challenge().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let challenge: serde_json::Value = response.json().await.unwrap();
+    let code = app.verification_codes().await.pop().unwrap();
+
+    let response = app
+        .post_beneficiary(&serde_json::json!({"name": "Jürgen Müller", "iban": "DE89 3704 0044 0532 0130 00",
+                                              "bic": "COBADEFFXXX", "currency": "EUR",
+                                              "challenge_id": challenge["challenge_id"], "code": code}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary: serde_json::Value = response.json().await.unwrap();
+
+    beneficiary["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn send_transfer(
+    app: &TestApp,
+    account_id: Uuid,
+    beneficiary_id: Uuid,
+    amount_cents: i64,
+) -> String {
+    let response = app
+        .post_sepa_transfer(&serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                                                "amount_cents": amount_cents, "remittance_info": "INV 2025/07"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let transfer: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(transfer["status"], "queued");
+    transfer["end_to_end_id"].as_str().unwrap().to_string()
+}
+
+async fn generate_batch(app: &TestApp) -> serde_json::Value {
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+
+    app.login(app.get_test_users().get_staff(), true).await;
+    let response = app.post_sepa_batch().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response.json().await.unwrap()
+}
+
+async fn balance_cents(app: &TestApp, account_id: Uuid) -> i64 {
+    let overdraft: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    overdraft["balance_cents"].as_i64().unwrap()
+}
+
+#[actix_web::test]
+async fn queued_transfers_are_debited_and_batched_into_pain001() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app, "EUR").await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let end_to_end_id = send_transfer(&app, account_id, beneficiary_id, 20_000).await;
+
+    // Act
+    let batch = generate_batch(&app).await;
+
+    // Assert
+    assert_eq!(batch["status"], "generated");
+    assert_eq!(batch["n_transactions"], 1);
+    assert_eq!(batch["control_sum_cents"], 20_000);
+    let location = batch["file_location"].as_str().unwrap();
+    assert!(location.starts_with("s3://thalia-kyc/sepa/outbound/pain001_"));
+
+    let uploads = app
+        .get_storage_state()
+        .s3_server
+        .received_requests()
+        .await
+        .unwrap();
+    let pain001 = String::from_utf8_lossy(&uploads.last().unwrap().body).to_string();
+    assert!(pain001.contains("urn:iso:std:iso:20022:tech:xsd:pain.001.001.09"));
+    assert!(pain001.contains(&format!(
+        "<MsgId>{}</MsgId>",
+        batch["message_id"].as_str().unwrap()
+    )));
+    assert!(pain001.contains(&format!("<EndToEndId>{}</EndToEndId>", end_to_end_id)));
+    assert!(pain001.contains("<InstdAmt Ccy=\"EUR\">200.00</InstdAmt>"));
+    assert!(pain001.contains("<Nm>Jurgen Muller</Nm>"));
+    assert!(pain001.contains("<IBAN>DE89370400440532013000</IBAN>"));
+
+    assert_eq!(balance_cents(&app, account_id).await, -20_000);
+
+    // Nothing is left to batch
+    let response = app.post_sepa_batch().await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn rejected_transfers_in_status_reports_are_credited_back() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app, "EUR").await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let rejected = send_transfer(&app, account_id, beneficiary_id, 10_000).await;
+    let accepted = send_transfer(&app, account_id, beneficiary_id, 5_000).await;
+    let batch = generate_batch(&app).await;
+
+    let status_dir = &app.get_storage_state().sepa_status_dir;
+    std::fs::create_dir_all(status_dir).unwrap();
+    let report = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.002.001.10">
+  <CstmrPmtStsRpt>
+    <GrpHdr><MsgId>STS-1</MsgId><CreDtTm>2025-12-17T08:00:00Z</CreDtTm></GrpHdr>
+    <OrgnlGrpInfAndSts>
+      <OrgnlMsgId>{}</OrgnlMsgId>
+      <OrgnlMsgNmId>pain.001.001.09</OrgnlMsgNmId>
+      <GrpSts>PART</GrpSts>
+    </OrgnlGrpInfAndSts>
+    <OrgnlPmtInfAndSts>
+      <OrgnlPmtInfId>{}-1</OrgnlPmtInfId>
+      <TxInfAndSts>
+        <OrgnlEndToEndId>{}</OrgnlEndToEndId>
+        <TxSts>RJCT</TxSts>
+        <StsRsnInf><Rsn><Cd>AC04</Cd></Rsn></StsRsnInf>
+      </TxInfAndSts>
+    </OrgnlPmtInfAndSts>
+  </CstmrPmtStsRpt>
+</Document>"#,
+        batch["message_id"].as_str().unwrap(),
+        batch["message_id"].as_str().unwrap(),
+        rejected
+    );
+    std::fs::write(status_dir.join("pain002_1.xml"), report).unwrap();
+    std::fs::write(status_dir.join("pain002_2.xml"), "<Document>").unwrap();
+
+    // Act
+    let response = app.post_sepa_status_import().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(summary["files_processed"], 1);
+    assert_eq!(summary["files_failed"], 1);
+    assert_eq!(summary["transfers_accepted"], 1);
+    assert_eq!(summary["transfers_returned"], 1);
+
+    assert!(status_dir.join("processed").join("pain002_1.xml").exists());
+    assert!(status_dir.join("failed").join("pain002_2.xml").exists());
+
+    let detail: serde_json::Value = app
+        .get_sepa_batch(batch["id"].as_str().unwrap())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["status"], "partially_accepted");
+    let transfers = detail["transfers"].as_array().unwrap();
+    let returned = transfers
+        .iter()
+        .find(|t| t["end_to_end_id"] == rejected.as_str())
+        .unwrap();
+    assert_eq!(returned["status"], "returned");
+    assert_eq!(returned["return_reason"], "AC04");
+    assert!(
+        transfers
+            .iter()
+            .any(|t| t["end_to_end_id"] == accepted.as_str() && t["status"] == "accepted")
+    );
+
+    assert_eq!(balance_cents(&app, account_id).await, -5_000);
+
+    // The same report again changes nothing
+    std::fs::copy(
+        status_dir.join("processed").join("pain002_1.xml"),
+        status_dir.join("pain002_3.xml"),
+    )
+    .unwrap();
+    let summary: serde_json::Value = app.post_sepa_status_import().await.json().await.unwrap();
+    assert_eq!(summary["transfers_returned"], 0);
+    assert_eq!(balance_cents(&app, account_id).await, -5_000);
+
+    std::fs::remove_dir_all(status_dir).unwrap();
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn transfers_need_euro_funds_and_a_settled_beneficiary() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app, "USD").await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let transfer = |amount_cents: i64| {
+        serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                           "amount_cents": amount_cents})
+    };
+
+    // Act
+    let response = app.post_sepa_transfer(&transfer(10_000)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    sqlx::query("UPDATE user_account SET currency = 'EUR' WHERE id = $1")
+        .bind(account_id)
+        .execute(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Beyond the overdraft
+    let response = app.post_sepa_transfer(&transfer(60_000)).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    // Above the large amount threshold while the beneficiary is cooling off
+    let response = app.post_sepa_transfer(&transfer(150_000)).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app
+        .post_sepa_transfer(&serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                                                "amount_cents": 1_000, "remittance_info": "Rent €"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_sepa_transfer(&transfer(10_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let transfer: serde_json::Value = response.json().await.unwrap();
+    let response = app
+        .get_sepa_transfer(transfer["id"].as_str().unwrap())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unverified_customers_are_held_to_the_kyc_limit() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app, "EUR").await;
+    let beneficiary_id = add_beneficiary(&app).await;
+    let pool = &app.get_db_state().pg_pool;
+    sqlx::query(
+        "UPDATE beneficiary SET cooling_off_until = now() - interval '1 day' WHERE id = $1",
+    )
+    .bind(beneficiary_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tuser SET is_verified = false WHERE id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .execute(pool)
+        .await
+        .unwrap();
+    let transfer = |amount_cents: i64| {
+        serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                           "amount_cents": amount_cents})
+    };
+
+    // Act
+    let response = app.post_sepa_transfer(&transfer(100_001)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let error = response.text().await.unwrap();
+    assert!(error.contains("verified customer identity"));
+    assert_eq!(balance_cents(&app, account_id).await, 0);
+
+    let response = app.post_sepa_transfer(&transfer(20_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
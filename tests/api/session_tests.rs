@@ -0,0 +1,189 @@
+use uuid::Uuid;
+
+use crate::base::{TestApp, spawn_app};
+
+fn customer_login_body(app: &TestApp) -> serde_json::Value {
+    serde_json::json!({"login_id": {"email": app.get_test_users().get_customer().get_email().as_ref()},
+                        "password": app.get_test_users().get_customer().get_password().as_ref()})
+}
+
+// A second device signing in as the customer, it only holds the bearer token
+async fn login_other_device(app: &TestApp) -> String {
+    let response = reqwest::Client::new()
+        .post(format!("{}/customer/login", app.get_run_state().address))
+        .header(reqwest::header::USER_AGENT, "thalia-ios/2.4")
+        .header("X-Device-Name", "Customer iPhone")
+        .json(&customer_login_body(app))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    response
+        .headers()
+        .get(reqwest::header::AUTHORIZATION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn other_device_sessions(app: &TestApp, bearer: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .get(format!("{}/auth/sessions", app.get_run_state().address))
+        .header(reqwest::header::AUTHORIZATION, bearer)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn customer_lists_sessions_with_device_details() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    app.post_customer_login(&customer_login_body(&app)).await;
+    login_other_device(&app).await;
+
+    // Act
+    let response = app.get_sessions().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let sessions: Vec<serde_json::Value> = response.json().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|s| s["current"] == true).count(), 1);
+
+    let other = sessions.iter().find(|s| s["current"] == false).unwrap();
+    assert_eq!(other["device"], "Customer iPhone");
+    assert_eq!(other["user_agent"], "thalia-ios/2.4");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn ended_session_can_no_longer_be_used() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    app.post_customer_login(&customer_login_body(&app)).await;
+    let bearer = login_other_device(&app).await;
+
+    let sessions: Vec<serde_json::Value> = app.get_sessions().await.json().await.unwrap();
+    let other_id: Uuid = sessions.iter().find(|s| s["current"] == false).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    // Act
+    let response = app.delete_session(other_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        other_device_sessions(&app, &bearer).await.status().as_u16(),
+        401
+    );
+    assert_eq!(app.get_sessions().await.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn log_out_everywhere_ends_every_session() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    app.post_customer_login(&customer_login_body(&app)).await;
+    let bearer = login_other_device(&app).await;
+
+    // Act
+    let response = app.delete_sessions().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let ended: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(ended["ended"], 2);
+
+    assert_eq!(app.get_sessions().await.status().as_u16(), 401);
+    assert_eq!(
+        other_device_sessions(&app, &bearer).await.status().as_u16(),
+        401
+    );
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn staff_terminates_customer_sessions() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    let bearer = login_other_device(&app).await;
+
+    let staff_login = serde_json::json!({"login_id": {"email": app.get_test_users().get_staff().get_email().as_ref()},
+                                                "password": app.get_test_users().get_staff().get_password().as_ref()});
+    app.post_staff_login(&staff_login).await;
+
+    // Act
+    let response = app
+        .delete_customer_sessions(*app.get_test_users().get_customer().get_id())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        other_device_sessions(&app, &bearer).await.status().as_u16(),
+        401
+    );
+
+    let (ended_by,): (Option<Uuid>,) =
+        sqlx::query_as("SELECT ended_by FROM user_session WHERE user_id = $1")
+            .bind(app.get_test_users().get_customer().get_id())
+            .fetch_one(&app.get_db_state().pg_pool)
+            .await
+            .unwrap();
+
+    assert_eq!(ended_by, Some(*app.get_test_users().get_staff().get_id()));
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn staff_cannot_terminate_staff_sessions() {
+    // Arrange
+    let mut app = spawn_app().await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    let staff_login = serde_json::json!({"login_id": {"email": app.get_test_users().get_staff().get_email().as_ref()},
+                                                "password": app.get_test_users().get_staff().get_password().as_ref()});
+    app.post_staff_login(&staff_login).await;
+
+    // Act
+    let response = app
+        .delete_customer_sessions(*app.get_test_users().get_staff().get_id())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
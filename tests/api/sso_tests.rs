@@ -0,0 +1,259 @@
+use wiremock::{
+    Mock, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use crate::base::{TestApp, spawn_app};
+
+const IDP_KEY: &str = include_str!("../fixtures/oidc/idp.pem");
+const IDP_JWKS: &str = include_str!("../fixtures/oidc/jwks.json");
+
+async fn mount_idp(app: &TestApp) {
+    let idp = &app.get_idp_state().idp_server;
+    let issuer = idp.uri();
+
+    Mock::given(method("GET"))
+        .and(path("/.well-known/openid-configuration"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+            "response_types_supported": ["code"],
+        })))
+        .mount(idp)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/jwks"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(IDP_JWKS.as_bytes().to_vec(), "application/json"),
+        )
+        .mount(idp)
+        .await;
+
+    // Stands in for the IdP login page, the browser just lands here
+    Mock::given(method("GET"))
+        .and(path("/authorize"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(idp)
+        .await;
+}
+
+// Follows the redirect to the IdP and returns the state and nonce Thalia sent
+async fn start_sso(app: &TestApp) -> (String, String) {
+    let response = app.get_staff_sso_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let query: std::collections::HashMap<String, String> =
+        response.url().query_pairs().into_owned().collect();
+
+    assert_eq!(query["client_id"], "thalia");
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    (query["state"].clone(), query["nonce"].clone())
+}
+
+async fn mount_token(
+    app: &TestApp,
+    nonce: &str,
+    email: &str,
+    email_verified: bool,
+    groups: &[&str],
+) {
+    let idp = &app.get_idp_state().idp_server;
+    let now = jsonwebtoken::get_current_timestamp();
+
+    let claims = serde_json::json!({
+        "iss": idp.uri(),
+        "sub": "idp-subject-1",
+        "aud": "thalia",
+        "exp": now + 300,
+        "iat": now,
+        "nonce": nonce,
+        "email": email,
+        "email_verified": email_verified,
+        "preferred_username": "sso.staff",
+        "given_name": "Sso",
+        "family_name": "Staff",
+        "groups": groups,
+    });
+
+    let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
+    header.kid = Some("idp-k1".into());
+
+    let id_token = jsonwebtoken::encode(
+        &header,
+        &claims,
+        &jsonwebtoken::EncodingKey::from_rsa_pem(IDP_KEY.as_bytes()).unwrap(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "idp-access-token",
+            "token_type": "Bearer",
+            "expires_in": 300,
+            "id_token": id_token,
+        })))
+        .expect(1)
+        .mount(idp)
+        .await;
+}
+
+#[actix_web::test]
+async fn sso_login_provisions_staff_with_mapped_role() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_idp(&app).await;
+
+    let (state, nonce) = start_sso(&app).await;
+    mount_token(
+        &app,
+        &nonce,
+        "sso.staff@thalia.test",
+        true,
+        &["thalia-managers"],
+    )
+    .await;
+
+    // Act
+    let response = app.get_staff_sso_callback("auth-code", &state).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(
+        response
+            .headers()
+            .get(reqwest::header::AUTHORIZATION)
+            .is_some()
+    );
+
+    let (role, is_confirmed): (String, bool) = sqlx::query_as(
+        "SELECT access_role::TEXT, is_confirmed FROM tuser WHERE email = 'sso.staff@thalia.test'",
+    )
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(role, "manager");
+    assert!(is_confirmed);
+
+    let (subject,): (String,) = sqlx::query_as("SELECT subject FROM external_identity")
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(subject, "idp-subject-1");
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn sso_callback_with_mismatched_state_is_rejected() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_idp(&app).await;
+
+    let _ = start_sso(&app).await;
+
+    // Act
+    let response = app
+        .get_staff_sso_callback("auth-code", "forged-state")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn sso_login_without_a_staff_group_is_forbidden() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_idp(&app).await;
+
+    let (state, nonce) = start_sso(&app).await;
+    mount_token(&app, &nonce, "sso.staff@thalia.test", true, &["everyone"]).await;
+
+    // Act
+    let response = app.get_staff_sso_callback("auth-code", &state).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tuser")
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(count, 0);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn sso_login_cannot_take_over_a_customer() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_idp(&app).await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    let customer_email = app
+        .get_test_users()
+        .get_customer()
+        .get_email()
+        .as_ref()
+        .to_string();
+
+    let (state, nonce) = start_sso(&app).await;
+    mount_token(&app, &nonce, &customer_email, true, &["thalia-superusers"]).await;
+
+    // Act
+    let response = app.get_staff_sso_callback("auth-code", &state).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn sso_login_with_an_unverified_email_cannot_take_over_staff() {
+    // Arrange
+    let mut app = spawn_app().await;
+    mount_idp(&app).await;
+    app.get_test_users()
+        .store_test_users(&app.get_db_state().pg_pool)
+        .await;
+
+    let staff_email = app
+        .get_test_users()
+        .get_staff()
+        .get_email()
+        .as_ref()
+        .to_string();
+
+    let (state, nonce) = start_sso(&app).await;
+    mount_token(&app, &nonce, &staff_email, false, &["thalia-superusers"]).await;
+
+    // Act
+    let response = app.get_staff_sso_callback("auth-code", &state).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM external_identity")
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(count, 0);
+
+    app.clear_test_db().await;
+}
@@ -0,0 +1,293 @@
+use chrono::{Datelike, Days, Utc};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+// An active dollar account on a product with monthly statements
+async fn open_account(app: &TestApp) -> (Uuid, String) {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-STM", terms).await;
+    app.activate_account(account.id).await;
+
+    (account.id, account.iban)
+}
+
+fn inbound_mt103(iban: &str) -> String {
+    format!(
+        "{{1:F01THALUS33AXXX0000000000}}{{2:O1031015251219BARCGB22AXXX00000000002512191015N}}{{4:\r\n:20:PAY7781\r\n:23B:CRED\r\n:32A:251219USD1500,00\r\n:50K:/GB29NWBK60161331926819\r\nACME EXPORTS LTD\r\n:59:/{}\r\nCUSTOMER\r\n:71A:SHA\r\n-}}",
+        iban
+    )
+}
+
+#[actix_web::test]
+async fn last_months_postings_are_stated_stored_and_announced() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, iban) = open_account(&app).await;
+    let pool = &app.get_db_state().pg_pool;
+
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    let response = app.post_swift_inbound(&inbound_mt103(&iban)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CARD-1001",
+                                "description": "Card settlement"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Both postings, and the account, a month back
+    sqlx::query(
+        "UPDATE journal_entry SET created_date = created_date - interval '1 month'
+            WHERE user_account_id = $1",
+    )
+    .bind(account_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "UPDATE user_account SET created_at = created_at - interval '1 month' WHERE id = $1",
+    )
+    .bind(account_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    // Act
+    let response = app.post_statement_run(&serde_json::json!({})).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let run: serde_json::Value = response.json().await.unwrap();
+    let last_month_end = Utc::now().date_naive().with_day(1).unwrap() - Days::new(1);
+    assert_eq!(run["period_end"], last_month_end.to_string());
+    assert_eq!(run["statements_generated"], 1);
+
+    let run: serde_json::Value = app
+        .post_statement_run(&serde_json::json!({}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(run["statements_generated"], 0);
+
+    let response = app
+        .post_statement_run(&serde_json::json!({"period_end": "2025-11-29"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let uploads = app
+        .get_storage_state()
+        .s3_server
+        .received_requests()
+        .await
+        .unwrap();
+    assert_eq!(uploads.len(), 3);
+    let pdf = uploads
+        .iter()
+        .find(|r| r.url.path().ends_with(".pdf"))
+        .unwrap();
+    assert!(pdf.body.starts_with(b"%PDF-1.4"));
+    let csv = uploads
+        .iter()
+        .find(|r| r.url.path().ends_with(".csv"))
+        .unwrap();
+    let csv = String::from_utf8_lossy(&csv.body).to_string();
+    assert!(csv.contains("CARD-1001,Card settlement,transaction,300.00,,1200.00"));
+
+    let emails = app
+        .get_mail_state()
+        .email_server
+        .received_requests()
+        .await
+        .unwrap();
+    assert!(
+        emails
+            .iter()
+            .any(|r| String::from_utf8_lossy(&r.body).contains("Your statement is ready"))
+    );
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let statements: serde_json::Value = app
+        .get_account_statements(account_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(statements.as_array().unwrap().len(), 1);
+    assert_eq!(statements[0]["opening_cents"], 0);
+    assert_eq!(statements[0]["credits_cents"], 150_000);
+    assert_eq!(statements[0]["debits_cents"], 30_000);
+    assert_eq!(statements[0]["closing_cents"], 120_000);
+    assert_eq!(statements[0]["entry_count"], 2);
+    let statement_id = statements[0]["id"].as_str().unwrap();
+
+    let response = app
+        .get_statement_download(account_id, statement_id, "pdf")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let download: serde_json::Value = response.json().await.unwrap();
+    let download_url = download["download_url"].as_str().unwrap();
+    assert!(download_url.starts_with(&app.get_storage_state().s3_server.uri()));
+    assert!(download_url.contains(".pdf"));
+
+    let response = app
+        .get_statement_download(account_id, statement_id, "xls")
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.get_account_statements(Uuid::now_v7()).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn bookings_export_as_camt053_for_the_owner_and_staff() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, iban) = open_account(&app).await;
+
+    let response = app.post_swift_inbound(&inbound_mt103(&iban)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CARD-1001",
+                                "description": "Card settlement"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app.get_staff_camt053(account_id, "").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "application/xml"
+    );
+    let xml = response.text().await.unwrap();
+    assert!(xml.contains("urn:iso:std:iso:20022:tech:xsd:camt.053.001.08"));
+    assert!(xml.contains(&format!("<IBAN>{}</IBAN>", iban)));
+    assert!(xml.contains("<SubFmlyCd>XBCT</SubFmlyCd>"));
+    assert!(xml.contains("<EndToEndId>PAY7781</EndToEndId>"));
+    assert!(xml.contains("<Dt>2025-12-19</Dt>"));
+    assert!(xml.contains("<InstrId>CARD-1001</InstrId>"));
+    assert!(xml.contains("<Cd>CLBD</Cd>"));
+
+    let today = Utc::now().date_naive();
+    let response = app
+        .get_staff_camt053(account_id, &format!("to={}", today + Days::new(1)))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.login(app.get_test_users().get_customer(), false).await;
+    let response = app
+        .get_customer_camt053(account_id, &format!("from={}&to={}", today, today))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let xml = response.text().await.unwrap();
+    assert!(xml.contains("<NbOfNtries>2</NbOfNtries>"));
+
+    let response = app.get_customer_camt053(Uuid::now_v7(), "").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn the_balance_sheet_balances_against_the_chart_of_accounts() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let (account_id, iban) = open_account(&app).await;
+
+    let response = app.post_swift_inbound(&inbound_mt103(&iban)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_forced_debit(
+            account_id,
+            &serde_json::json!({"amount_cents": 30_000, "transaction_ref": "CARD-1001",
+                                "description": "Card settlement"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Act
+    let response = app.get_balance_sheet("").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let sheet: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(sheet["currency"], "USD");
+    assert_eq!(sheet["current"]["to"], Utc::now().date_naive().to_string());
+    let liabilities = &sheet["sections"][1];
+    assert_eq!(liabilities["name"], "Liabilities");
+    assert_eq!(liabilities["current_cents"], 120_000);
+    assert_eq!(liabilities["comparative_cents"], 0);
+    assert!(
+        liabilities["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|l| l["code"] == "2020" && l["depth"] == 1)
+    );
+    assert_eq!(
+        sheet["totals"][0]["current_cents"],
+        sheet["totals"][1]["current_cents"]
+    );
+
+    let response = app.get_balance_sheet("format=csv").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/csv"
+    );
+    let csv = response.text().await.unwrap();
+    assert!(csv.starts_with("section,code,name,"));
+    assert!(csv.contains("Liabilities,,Total liabilities,1200.00,0.00"));
+
+    let response = app.get_income_statement("").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let statement: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(statement["sections"][0]["name"], "Income");
+    assert_eq!(statement["totals"][0]["name"], "Net income");
+
+    let response = app.get_income_statement("format=xls").await;
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.get_income_statement("compare_from=2024-01-01").await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unauthenticated_financial_statements_return_401() {
+    // Arrange
+    let mut app = spawn_app().await;
+
+    // Act
+    let balance_sheet = app.get_balance_sheet("").await;
+    let income_statement = app.get_income_statement("").await;
+
+    // Assert
+    assert_eq!(balance_sheet.status().as_u16(), 401);
+    assert_eq!(income_statement.status().as_u16(), 401);
+
+    app.clear_test_db().await;
+}
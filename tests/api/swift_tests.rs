@@ -0,0 +1,234 @@
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::base::{TestApp, spawn_app};
+
+// An active dollar account with a 500.00 overdraft and a 25.00 SWIFT transfer fee
+async fn open_account(app: &TestApp) -> Uuid {
+    let terms = serde_json::json!({"currencies": ["USD"], "min_kyc_level": "verified",
+                                   "fees": [{"code": "swift_transfer", "amount_cents": 2_500,
+                                             "frequency": "per_transaction"}],
+                                   "overdraft_limit_cents": 50_000,
+                                   "dormancy_days": 365, "statement_frequency": "monthly"});
+    let account = app.open_account("CUR-SWF", terms).await;
+    app.activate_account(account.id).await;
+    app.arrange_overdraft(account.id, 50_000).await;
+
+    account.id
+}
+
+async fn add_beneficiary(app: &TestApp) -> Uuid {
+    app.login(app.get_test_users().get_customer(), false).await;
+
+    Mock::given(path("/v3/send"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_mail_state().email_server)
+        .await;
+
+    let response = app.post_beneficiary_challenge().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let challenge: serde_json::Value = response.json().await.unwrap();
+    let code = app.verification_codes().await.pop().unwrap();
+
+    let response = app
+        .post_beneficiary(&serde_json::json!({"name": "Siobhán O'Connor", "iban": "GB82 WEST 1234 5698 7654 32",
+                                              "bic": "WESTGB2LXXX", "currency": "GBP",
+                                              "challenge_id": challenge["challenge_id"], "code": code}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary: serde_json::Value = response.json().await.unwrap();
+
+    beneficiary["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn balance_cents(app: &TestApp, account_id: Uuid) -> i64 {
+    let overdraft: serde_json::Value = app.get_overdraft(account_id).await.json().await.unwrap();
+    overdraft["balance_cents"].as_i64().unwrap()
+}
+
+fn inbound_mt103(reference: &str, iban: &str, amount: &str) -> String {
+    format!(
+        "{{1:F01THALUS33AXXX0000000000}}{{2:O1031015251219BARCGB22AXXX00000000002512191015N}}{{4:\r\n:20:{}\r\n:23B:CRED\r\n:32A:251219USD{}\r\n:50K:/GB29NWBK60161331926819\r\nACME EXPORTS LTD\r\n:59:/{}\r\nCUSTOMER\r\n:71A:SHA\r\n-}}",
+        reference, amount, iban
+    )
+}
+
+#[actix_web::test]
+async fn transfers_in_another_currency_book_the_fx_margin_and_send_an_mt103() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+
+    let response = app
+        .put_fx_rate(
+            &serde_json::json!({"base_currency": "USD", "quote_currency": "GBP", "rate": "0.80"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .put_fx_rate(
+            &serde_json::json!({"base_currency": "USD", "quote_currency": "GBP", "rate": "0.8x"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let beneficiary_id = add_beneficiary(&app).await;
+
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+
+    // Act
+    let response = app
+        .post_swift_transfer(
+            &serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                                                 "amount_cents": 10_000, "charges": "SHA",
+                                                 "remittance_info": "Invoice 7781"}),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let transfer: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(transfer["currency"], "GBP");
+    assert_eq!(transfer["settlement_cents"], 10_000);
+    // 100.00 pounds at 0.796 less the 0.80 mid rate
+    assert_eq!(transfer["principal_cents"], 12_563);
+    assert_eq!(transfer["fx_margin_cents"], 63);
+    assert_eq!(transfer["fee_cents"], 2_500);
+    assert_eq!(transfer["rate"], "0.796000");
+    let location = transfer["message_location"].as_str().unwrap();
+    assert!(location.starts_with("s3://thalia-kyc/swift/outbound/mt103_THL"));
+
+    let uploads = app
+        .get_storage_state()
+        .s3_server
+        .received_requests()
+        .await
+        .unwrap();
+    let mt103 = String::from_utf8_lossy(&uploads.last().unwrap().body).to_string();
+    assert!(mt103.starts_with("{1:F01THALUS33AXXX0000000000}{2:I103CHASUS33AXXXN}"));
+    assert!(mt103.contains(&format!(
+        ":20:{}\r\n",
+        transfer["reference"].as_str().unwrap()
+    )));
+    assert!(mt103.contains("GBP100,00\r\n"));
+    assert!(mt103.contains(":59:/GB82WEST12345698765432\r\nSiobhan O'Connor\r\n"));
+    assert!(mt103.contains(":70:Invoice 7781\r\n:71A:SHA\r\n-}"));
+
+    assert_eq!(balance_cents(&app, account_id).await, -15_063);
+
+    let fx_gains: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(l.amount_cents), 0)::BIGINT FROM journal_line l
+            JOIN chart_of_account c ON c.id = l.coa_id WHERE c.code = '4220'",
+    )
+    .fetch_one(&app.get_db_state().pg_pool)
+    .await
+    .unwrap();
+    assert_eq!(fx_gains, 63);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn inbound_mt103s_are_credited_once_and_field_errors_are_named() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+    let (iban,): (String,) = sqlx::query_as("SELECT iban FROM user_account WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(&app.get_db_state().pg_pool)
+        .await
+        .unwrap();
+
+    // Act
+    let response = app
+        .post_swift_inbound(&inbound_mt103("PAY7781", &iban, "1500,00"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let inbound: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(inbound["sender_bic"], "BARCGB22XXX");
+    assert_eq!(inbound["ordering_party"], "ACME EXPORTS LTD");
+    assert_eq!(inbound["account_id"], account_id.to_string());
+    assert_eq!(balance_cents(&app, account_id).await, 150_000);
+
+    let response = app
+        .post_swift_inbound(&inbound_mt103("PAY7781", &iban, "1500,00"))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(balance_cents(&app, account_id).await, 150_000);
+
+    let response = app
+        .post_swift_inbound(&inbound_mt103("PAY7782", &iban, "1500.00"))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+    let error = response.text().await.unwrap();
+    assert!(error.contains("32A"));
+
+    let cover = "{1:F01THALUS33AXXX0000000000}{2:O2021030251219CHASUS33AXXX00000000002512191030N}{4:\r\n:20:COV7781\r\n:21:PAY7781\r\n:32A:251219USD1500,00\r\n:52A:BARCGB22\r\n:58A:THALUS33\r\n-}";
+    let response = app.post_swift_inbound(cover).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let inbound: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(inbound["related_reference"], "PAY7781");
+    assert!(inbound["journal_entry_id"].is_null());
+    assert_eq!(balance_cents(&app, account_id).await, 150_000);
+
+    app.clear_test_db().await;
+}
+
+#[actix_web::test]
+async fn unverified_customers_are_held_to_the_kyc_limit_in_the_account_currency() {
+    // Arrange
+    let mut app = spawn_app().await;
+    let account_id = open_account(&app).await;
+    let response = app
+        .put_fx_rate(
+            &serde_json::json!({"base_currency": "USD", "quote_currency": "GBP", "rate": "0.80"}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let beneficiary_id = add_beneficiary(&app).await;
+
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.get_storage_state().s3_server)
+        .await;
+
+    let pool = &app.get_db_state().pg_pool;
+    sqlx::query(
+        "UPDATE beneficiary SET cooling_off_until = now() - interval '1 day' WHERE id = $1",
+    )
+    .bind(beneficiary_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tuser SET is_verified = false WHERE id = $1")
+        .bind(app.get_test_users().get_customer().get_id())
+        .execute(pool)
+        .await
+        .unwrap();
+    let transfer = |amount_cents: i64| {
+        serde_json::json!({"account_id": account_id, "beneficiary_id": beneficiary_id,
+                           "amount_cents": amount_cents, "charges": "SHA"})
+    };
+
+    // Act
+    // 800.00 pounds is over 1000.00 dollars once converted
+    let response = app.post_swift_transfer(&transfer(80_000)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let error = response.text().await.unwrap();
+    assert!(error.contains("verified customer identity"));
+    assert_eq!(balance_cents(&app, account_id).await, 0);
+
+    let response = app.post_swift_transfer(&transfer(10_000)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clear_test_db().await;
+}